@@ -0,0 +1,310 @@
+pub mod app_backup {
+    use std::{
+        fs::{self, File},
+        io::{Read, Write},
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::{AppHandle, Manager};
+    use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+    use crate::{
+        api::{
+            app_credentials::{is_reference, resolve_reference, resolve_secrets},
+            app_state::AppState,
+        },
+        util::kubious_error::KubiousError,
+    };
+
+    const FORMAT: &str = "kubious-backup";
+    const FORMAT_VERSION: u32 = 1;
+    const MANIFEST_ENTRY: &str = "manifest.json";
+    const STATE_ENTRY: &str = "state.json";
+    const DATA_PREFIX: &str = "data/";
+
+    const REDACTED_AUTH_FIELDS: [&str; 3] = ["password", "token", "client-key-data"];
+    const REDACTED_PROVIDER_FIELDS: [&str; 3] = ["access-token", "id-token", "refresh-token"];
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BackupManifest {
+        pub format: String,
+        pub version: u32,
+        pub created: u64,
+        pub redacted: bool,
+        pub files: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ImportSummary {
+        pub configs_imported: usize,
+        pub configs: Vec<String>,
+        pub files_restored: Vec<String>,
+        pub files_skipped: Vec<String>,
+        pub redacted: bool,
+    }
+
+    /// Blanks out credential material so an exported archive can be shared.
+    fn redact_credentials(state: &mut Value) {
+        for (_, config) in state["configs"].as_object_mut().into_iter().flatten() {
+            for header in config["headers"].as_array_mut().into_iter().flatten() {
+                if let Some(value) = header.get_mut(1) {
+                    *value = Value::Null;
+                }
+            }
+            let Some(auth) = config["auth_info"].as_object_mut() else {
+                continue;
+            };
+            for field in REDACTED_AUTH_FIELDS {
+                auth.remove(field);
+            }
+            if let Some(exec) = auth.get_mut("exec").and_then(|e| e.as_object_mut()) {
+                exec.remove("env");
+            }
+            if let Some(provider) = auth
+                .get_mut("auth-provider")
+                .and_then(|p| p.get_mut("config"))
+                .and_then(|c| c.as_object_mut())
+            {
+                for field in REDACTED_PROVIDER_FIELDS {
+                    provider.remove(field);
+                }
+            }
+        }
+        for login in state["settings"]["registry_logins"]
+            .as_array_mut()
+            .into_iter()
+            .flatten()
+        {
+            login["secret"] = Value::String(String::new());
+        }
+    }
+
+    /// Swaps keyring references for the secrets they point to, so an
+    /// unredacted archive can be restored on another machine.
+    fn resolve_credentials(handle: &AppHandle, state: &mut Value) -> Result<(), KubiousError> {
+        for (key, config) in handle.state::<AppState>().get_configs() {
+            let resolved = resolve_secrets(config)?;
+            state["configs"][key.as_str()] =
+                serde_json::to_value(resolved).or(Err("Failed to serialize state.".to_string()))?;
+        }
+        for login in state["settings"]["registry_logins"]
+            .as_array_mut()
+            .into_iter()
+            .flatten()
+        {
+            let Some(reference) = login["secret"].as_str().filter(|s| is_reference(s)) else {
+                continue;
+            };
+            login["secret"] = Value::String(resolve_reference(reference)?);
+        }
+        Ok(())
+    }
+
+    fn collect_files(root: &Path, current: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(current) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_files(root, &path, files);
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+
+    /// `config.json` and its backups and temporary files, which the
+    /// archive carries as `state.json` instead.
+    fn is_state_file(path: &Path, config_path: &Path) -> bool {
+        if path.parent() != config_path.parent() {
+            return false;
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        name.starts_with("config")
+            && (name == "config.json" || name.ends_with(".bak") || name.ends_with(".tmp"))
+    }
+
+    fn relative_name(path: &Path) -> String {
+        path.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    pub fn export_backup(
+        handle: &AppHandle,
+        path: &str,
+        redact: bool,
+    ) -> Result<BackupManifest, KubiousError> {
+        let resolver = handle.path();
+        let data_root = resolver
+            .app_data_dir()
+            .or(Err("Failed to resolve app data directory.".to_string()))?;
+        let config_path = resolver
+            .parse("$APPCONFIG/config.json")
+            .or(Err("Failed to resolve config path.".to_string()))?;
+        let target = PathBuf::from(path);
+
+        let mut state = serde_json::from_str::<Value>(
+            handle
+                .state::<AppState>()
+                .to_json()
+                .or(Err("Failed to serialize state.".to_string()))?
+                .as_str(),
+        )
+        .or(Err("Failed to serialize state.".to_string()))?;
+        if redact {
+            redact_credentials(&mut state);
+        } else {
+            resolve_credentials(handle, &mut state)?;
+        }
+
+        let mut data_files: Vec<PathBuf> = Vec::new();
+        if data_root.exists() {
+            collect_files(&data_root, &data_root, &mut data_files);
+        }
+        data_files.retain(|f| {
+            let absolute = data_root.join(f);
+            absolute != target && !is_state_file(&absolute, &config_path)
+        });
+        data_files.sort();
+
+        let manifest = BackupManifest {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            redacted: redact,
+            files: data_files.iter().map(|f| relative_name(f)).collect(),
+        };
+
+        let file = File::create(&target).or(Err("Failed to create backup file.".to_string()))?;
+        let mut writer = ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let write_error = || "Failed to write backup archive.".to_string();
+
+        writer
+            .start_file(MANIFEST_ENTRY, options)
+            .or(Err(write_error()))?;
+        writer
+            .write_all(
+                serde_json::to_string_pretty(&manifest)
+                    .or(Err(write_error()))?
+                    .as_bytes(),
+            )
+            .or(Err(write_error()))?;
+        writer
+            .start_file(STATE_ENTRY, options)
+            .or(Err(write_error()))?;
+        writer
+            .write_all(
+                serde_json::to_string_pretty(&state)
+                    .or(Err(write_error()))?
+                    .as_bytes(),
+            )
+            .or(Err(write_error()))?;
+
+        for relative in &data_files {
+            let mut contents: Vec<u8> = Vec::new();
+            File::open(data_root.join(relative))
+                .and_then(|mut f| f.read_to_end(&mut contents))
+                .or(Err(format!("Failed to read {}", relative_name(relative))))?;
+            writer
+                .start_file(
+                    format!("{}{}", DATA_PREFIX, relative_name(relative)),
+                    options,
+                )
+                .or(Err(write_error()))?;
+            writer.write_all(&contents).or(Err(write_error()))?;
+        }
+        writer.finish().or(Err(write_error()))?;
+
+        Ok(manifest)
+    }
+
+    pub fn import_backup(
+        handle: &AppHandle,
+        path: &str,
+        overwrite: bool,
+    ) -> Result<ImportSummary, KubiousError> {
+        let file = File::open(path).or(Err("Failed to open backup file.".to_string()))?;
+        let mut archive =
+            ZipArchive::new(file).or(Err("Backup file is not a valid archive.".to_string()))?;
+        let read_entry =
+            |archive: &mut ZipArchive<File>, name: &str| -> Result<String, KubiousError> {
+                let mut contents = String::new();
+                archive
+                    .by_name(name)
+                    .or(Err(format!("Backup is missing {}", name)))?
+                    .read_to_string(&mut contents)
+                    .or(Err(format!("Failed to read {}", name)))?;
+                Ok(contents)
+            };
+
+        let manifest: BackupManifest =
+            serde_json::from_str(read_entry(&mut archive, MANIFEST_ENTRY)?.as_str())
+                .or(Err("Backup manifest is invalid.".to_string()))?;
+        if manifest.format != FORMAT || manifest.version > FORMAT_VERSION {
+            return Err("Unsupported backup format".into());
+        }
+        let imported = AppState::from_json(read_entry(&mut archive, STATE_ENTRY)?.as_str())
+            .or(Err("Backup state is invalid.".to_string()))?;
+
+        let data_root = handle
+            .path()
+            .app_data_dir()
+            .or(Err("Failed to resolve app data directory.".to_string()))?;
+        let mut restored: Vec<String> = Vec::new();
+        let mut skipped: Vec<String> = Vec::new();
+        for index in 0..archive.len() {
+            let mut entry = archive
+                .by_index(index)
+                .or(Err("Failed to read backup archive.".to_string()))?;
+            let Some(enclosed) = entry.enclosed_name() else {
+                continue;
+            };
+            let Ok(relative) = enclosed.strip_prefix(DATA_PREFIX) else {
+                continue;
+            };
+            let destination = data_root.join(relative);
+            let name = relative_name(relative);
+            if destination.exists() && !overwrite {
+                skipped.push(name);
+                continue;
+            }
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .or(Err(format!("Failed to create directory for {}", name)))?;
+            }
+            let mut contents: Vec<u8> = Vec::new();
+            entry
+                .read_to_end(&mut contents)
+                .or(Err(format!("Failed to read {}", name)))?;
+            File::create(&destination)
+                .and_then(|mut f| f.write_all(&contents))
+                .or(Err(format!("Failed to write {}", name)))?;
+            restored.push(name);
+        }
+
+        let state = handle.state::<AppState>();
+        let configs = state.merge_state(imported, overwrite);
+        state.save_state(handle.clone())?;
+
+        Ok(ImportSummary {
+            configs_imported: configs.len(),
+            configs,
+            files_restored: restored,
+            files_skipped: skipped,
+            redacted: manifest.redacted,
+        })
+    }
+}
@@ -0,0 +1,100 @@
+pub mod app_command_cache {
+    use std::{
+        collections::HashMap,
+        sync::{Mutex, MutexGuard},
+        time::{Duration, Instant},
+    };
+
+    use serde_json::Value;
+
+    use crate::{
+        api::{kube_api::KubeCommand, views_api::ViewsCommand, ApiCommand},
+        util::lock_recovery::recover,
+    };
+
+    const DISCOVERY_TTL: Duration = Duration::from_secs(300);
+    const CAPACITY_TTL: Duration = Duration::from_secs(60);
+    const OVERVIEW_TTL: Duration = Duration::from_secs(20);
+
+    /// How long the result of a read-only command may be reused. Only
+    /// commands that aggregate over the whole cluster are worth caching;
+    /// everything else returns `None` and always runs.
+    pub fn ttl(command: &ApiCommand) -> Option<Duration> {
+        match command {
+            ApiCommand::Kube(
+                KubeCommand::SupportedGroups {} | KubeCommand::SupportedResources { .. },
+            )
+            | ApiCommand::Views(ViewsCommand::ListKinds { .. }) => Some(DISCOVERY_TTL),
+            ApiCommand::Kube(KubeCommand::ExtendedResources {} | KubeCommand::NodePlacement {}) => {
+                Some(CAPACITY_TTL)
+            }
+            ApiCommand::Kube(
+                KubeCommand::ControlPlaneHealth {} | KubeCommand::ApiServiceHealth {},
+            ) => Some(OVERVIEW_TTL),
+            _ => None,
+        }
+    }
+
+    struct CachedResult {
+        stored: Instant,
+        ttl: Duration,
+        value: Value,
+        warnings: Vec<String>,
+    }
+
+    /// Successful results of cacheable commands per cluster, keyed by the
+    /// serialized command. Invalidated when the cluster's config changes.
+    #[derive(Default)]
+    pub struct CommandCache {
+        clusters: Mutex<HashMap<String, HashMap<String, CachedResult>>>,
+    }
+
+    impl CommandCache {
+        fn clusters_mutable(
+            &self,
+        ) -> MutexGuard<'_, HashMap<String, HashMap<String, CachedResult>>> {
+            recover(&self.clusters)
+        }
+
+        /// Returns the cached value and the warnings raised when it was
+        /// computed, unless it has expired.
+        pub fn get(&self, cluster: &str, key: &str) -> Option<(Value, Vec<String>)> {
+            let clusters = self.clusters_mutable();
+            let cached = clusters.get(cluster)?.get(key)?;
+            if cached.stored.elapsed() > cached.ttl {
+                return None;
+            }
+            Some((cached.value.clone(), cached.warnings.clone()))
+        }
+
+        pub fn put(
+            &self,
+            cluster: &str,
+            key: &str,
+            ttl: Duration,
+            value: Value,
+            warnings: Vec<String>,
+        ) {
+            let mut clusters = self.clusters_mutable();
+            let results = clusters.entry(cluster.to_string()).or_default();
+            results.retain(|_, cached| cached.stored.elapsed() <= cached.ttl);
+            results.insert(
+                key.to_string(),
+                CachedResult {
+                    stored: Instant::now(),
+                    ttl,
+                    value,
+                    warnings,
+                },
+            );
+        }
+
+        pub fn invalidate(&self, cluster: &str) {
+            self.clusters_mutable().remove(cluster);
+        }
+
+        pub fn clear(&self) {
+            self.clusters_mutable().clear();
+        }
+    }
+}
@@ -0,0 +1,269 @@
+pub mod app_credentials {
+    use keyring::Entry;
+    use kube::config::AuthInfo;
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::compat::kube_compat::KubeConfig;
+
+    const SERVICE: &str = "kubious";
+    const REFERENCE_PREFIX: &str = "keyring:";
+
+    const SECRET_AUTH_FIELDS: [&str; 3] = ["password", "token", "client-key-data"];
+    const SECRET_PROVIDER_FIELDS: [&str; 4] =
+        ["access-token", "id-token", "refresh-token", "client-secret"];
+
+    /// Where the secrets of a stored config live.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CredentialStatus {
+        pub config: String,
+        /// Secrets held in the keyring.
+        pub stored: usize,
+        /// Secrets still written to `config.json` in plaintext, because the
+        /// keyring was unavailable when they were saved.
+        pub plaintext: usize,
+        /// Keyring references whose entry is gone, e.g. after the keyring
+        /// was reset. These configs cannot authenticate until re-imported.
+        pub missing: usize,
+    }
+
+    pub fn is_reference(value: &str) -> bool {
+        value.starts_with(REFERENCE_PREFIX)
+    }
+
+    fn entry(account: &str) -> Result<Entry, String> {
+        Entry::new(SERVICE, account).or(Err("Failed to open keyring entry.".to_string()))
+    }
+
+    /// Every secret-bearing location in a serialized `AuthInfo`, as a path of
+    /// object keys / array indices paired with a stable account suffix.
+    fn secret_locations(auth: &Value) -> Vec<(Vec<String>, String)> {
+        let mut locations: Vec<(Vec<String>, String)> = Vec::new();
+        for field in SECRET_AUTH_FIELDS {
+            if auth[field].is_string() {
+                locations.push((vec![field.to_string()], field.to_string()));
+            }
+        }
+        for field in SECRET_PROVIDER_FIELDS {
+            if auth["auth-provider"]["config"][field].is_string() {
+                locations.push((
+                    vec![
+                        "auth-provider".to_string(),
+                        "config".to_string(),
+                        field.to_string(),
+                    ],
+                    format!("auth-provider/{}", field),
+                ));
+            }
+        }
+        for (index, env) in auth["exec"]["env"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            if let (Some(name), true) = (env["name"].as_str(), env["value"].is_string()) {
+                locations.push((
+                    vec![
+                        "exec".to_string(),
+                        "env".to_string(),
+                        index.to_string(),
+                        "value".to_string(),
+                    ],
+                    format!("exec/env/{}", name),
+                ));
+            }
+        }
+        locations
+    }
+
+    fn lookup_mut<'a>(value: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+        path.iter()
+            .try_fold(value, |current, segment| match current {
+                Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get_mut(i)),
+                other => other.get_mut(segment.as_str()),
+            })
+    }
+
+    fn map_secrets(
+        auth: &AuthInfo,
+        mut transform: impl FnMut(&str, &str) -> Result<Option<String>, String>,
+    ) -> Result<AuthInfo, String> {
+        let mut serialized =
+            serde_json::to_value(auth).or(Err("Failed to serialize credentials.".to_string()))?;
+        for (path, account) in secret_locations(&serialized) {
+            if let Some(slot) = lookup_mut(&mut serialized, &path) {
+                let current = slot.as_str().unwrap_or_default().to_string();
+                if let Some(replacement) = transform(account.as_str(), current.as_str())? {
+                    *slot = Value::String(replacement);
+                }
+            }
+        }
+        serde_json::from_value(serialized).or(Err("Failed to parse credentials.".to_string()))
+    }
+
+    /// Applies `transform` to every custom header value. Headers usually
+    /// carry auth proxy tokens or cookies, so all values are treated as
+    /// secrets.
+    fn map_header_secrets(
+        headers: &[(String, Option<String>)],
+        mut transform: impl FnMut(&str, &str) -> Result<Option<String>, String>,
+    ) -> Result<Vec<(String, Option<String>)>, String> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let Some(current) = value else {
+                    return Ok((name.clone(), None));
+                };
+                let account = format!("header/{}", name.to_lowercase());
+                let replacement = transform(account.as_str(), current.as_str())?;
+                Ok((name.clone(), Some(replacement.unwrap_or(current.clone()))))
+            })
+            .collect()
+    }
+
+    /// Moves secrets into the OS keyring, leaving opaque references behind.
+    /// Values that cannot be stored are left in place so nothing is lost.
+    pub fn store_secrets(key: &str, config: KubeConfig) -> KubeConfig {
+        let mut secured = config.clone();
+        let stored = map_secrets(&config.auth_info, |account, value| {
+            if is_reference(value) {
+                return Ok(None);
+            }
+            let account = format!("{}/{}", key, account);
+            Ok(entry(account.as_str())
+                .and_then(|e| {
+                    e.set_password(value)
+                        .or(Err("Failed to store secret.".to_string()))
+                })
+                .ok()
+                .map(|_| format!("{}{}", REFERENCE_PREFIX, account)))
+        });
+        if let Ok(auth_info) = stored {
+            secured.auth_info = auth_info;
+        }
+        let headers = map_header_secrets(&config.headers, |account, value| {
+            if is_reference(value) || value.is_empty() {
+                return Ok(None);
+            }
+            let account = format!("{}/{}", key, account);
+            Ok(entry(account.as_str())
+                .and_then(|e| {
+                    e.set_password(value)
+                        .or(Err("Failed to store secret.".to_string()))
+                })
+                .ok()
+                .map(|_| format!("{}{}", REFERENCE_PREFIX, account)))
+        });
+        if let Ok(headers) = headers {
+            secured.headers = headers;
+        }
+        secured
+    }
+
+    fn resolve_value(value: &str) -> Result<Option<String>, String> {
+        if let Some(account) = value.strip_prefix(REFERENCE_PREFIX) {
+            entry(account)?
+                .get_password()
+                .map(Some)
+                .or(Err(format!("Missing keyring secret for {}", account)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Replaces keyring references with their secret values.
+    pub fn resolve_secrets(config: KubeConfig) -> Result<KubeConfig, String> {
+        let mut resolved = config.clone();
+        resolved.auth_info = map_secrets(&config.auth_info, |_, value| resolve_value(value))?;
+        resolved.headers = map_header_secrets(&config.headers, |_, value| resolve_value(value))?;
+        Ok(resolved)
+    }
+
+    /// Deletes every keyring entry referenced by a config.
+    pub fn forget_secrets(config: &KubeConfig) {
+        let _ = map_secrets(&config.auth_info, |_, value| {
+            forget_reference(value);
+            Ok(None)
+        });
+        let _ = map_header_secrets(&config.headers, |_, value| {
+            forget_reference(value);
+            Ok(None)
+        });
+    }
+
+    pub fn has_plaintext_secrets(config: &KubeConfig) -> bool {
+        serde_json::to_value(&config.auth_info)
+            .map(|auth| {
+                let locations = secret_locations(&auth);
+                let mut auth = auth;
+                locations.iter().any(|(path, _)| {
+                    lookup_mut(&mut auth, path)
+                        .and_then(|v| v.as_str().map(|s| !is_reference(s)))
+                        .unwrap_or(false)
+                })
+            })
+            .unwrap_or(false)
+            || config.headers.iter().any(|(_, value)| {
+                value
+                    .as_ref()
+                    .is_some_and(|v| !v.is_empty() && !is_reference(v))
+            })
+    }
+
+    pub fn credential_status(key: &str, config: &KubeConfig) -> CredentialStatus {
+        let mut status = CredentialStatus {
+            config: key.to_string(),
+            stored: 0,
+            plaintext: 0,
+            missing: 0,
+        };
+        let mut count = |_: &str, value: &str| {
+            if !is_reference(value) {
+                status.plaintext += 1;
+            } else if resolve_value(value).is_ok() {
+                status.stored += 1;
+            } else {
+                status.missing += 1;
+            }
+            Ok(None)
+        };
+        let _ = map_secrets(&config.auth_info, &mut count);
+        let _ = map_header_secrets(
+            &config
+                .headers
+                .iter()
+                .filter(|(_, value)| value.as_ref().is_some_and(|v| !v.is_empty()))
+                .cloned()
+                .collect::<Vec<_>>(),
+            &mut count,
+        );
+        status
+    }
+
+    /// Stores a registry password, returning the reference to keep in settings.
+    pub fn store_registry_secret(registry: &str, password: &str) -> Result<String, String> {
+        let account = format!("registry/{}", registry);
+        entry(account.as_str())?
+            .set_password(password)
+            .or(Err("Failed to store secret.".to_string()))?;
+        Ok(format!("{}{}", REFERENCE_PREFIX, account))
+    }
+
+    pub fn resolve_reference(reference: &str) -> Result<String, String> {
+        let Some(account) = reference.strip_prefix(REFERENCE_PREFIX) else {
+            return Err("Not a keyring reference".to_string());
+        };
+        entry(account)?
+            .get_password()
+            .or(Err(format!("Missing keyring secret for {}", account)))
+    }
+
+    pub fn forget_reference(reference: &str) {
+        if let Some(account) = reference.strip_prefix(REFERENCE_PREFIX) {
+            if let Ok(e) = entry(account) {
+                let _ = e.delete_credential();
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+pub mod token_expiry {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{Mutex, MutexGuard},
+        time::Duration,
+    };
+
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use k8s_openapi::chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use tauri::{AppHandle, Emitter, Manager};
+    use tauri_plugin_shell::ShellExt;
+
+    use crate::{
+        api::{app_credentials::resolve_secrets, app_state::AppState},
+        compat::kube_compat::KubeConfig,
+        util::{kubious_error::KubiousError, lock_recovery::recover},
+    };
+
+    /// Event channel carrying a `TokenExpiry` when a token is about to
+    /// expire, and again once it has.
+    pub const TOKEN_EXPIRY_EVENT: &str = "token-expiry";
+
+    /// How often expiries are checked.
+    const INTERVAL: Duration = Duration::from_secs(30);
+
+    /// How long before expiry the warning is sent.
+    const WARNING_SECS: i64 = 600;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum TokenSource {
+        /// A static bearer token that is a JWT, e.g. a bound service account
+        /// token.
+        BearerToken,
+        /// The `id-token` of the OIDC auth provider.
+        OidcIdToken,
+        /// The `expiry` an auth provider recorded for its access token.
+        AuthProvider,
+        /// `expirationTimestamp` of the credential an exec plugin returned.
+        ExecPlugin,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TokenExpiry {
+        pub config: String,
+        pub source: TokenSource,
+        pub expires_at: String,
+        /// Negative once expired.
+        pub remaining_secs: i64,
+        pub expired: bool,
+        /// Whether this is the selected config.
+        pub current: bool,
+    }
+
+    #[derive(Clone)]
+    struct Known {
+        source: TokenSource,
+        expires_at: DateTime<Utc>,
+        warned: bool,
+        expired_sent: bool,
+    }
+
+    #[derive(Default)]
+    struct Tracker {
+        /// `None` for configs whose credentials carry no known expiry.
+        configs: HashMap<String, Option<Known>>,
+        /// Configs read with their exec plugin run, which happens once they
+        /// are selected.
+        exec_probed: HashSet<String>,
+        started: bool,
+    }
+
+    /// Token expiries per stored config, read once per config and kept
+    /// until its credentials change.
+    #[derive(Default)]
+    pub struct TokenExpiries {
+        tracker: Mutex<Tracker>,
+    }
+
+    impl TokenExpiries {
+        fn tracker_mutable(&self) -> MutexGuard<'_, Tracker> {
+            recover(&self.tracker)
+        }
+
+        pub fn invalidate(&self, config: &str) {
+            let mut tracker = self.tracker_mutable();
+            tracker.configs.remove(config);
+            tracker.exec_probed.remove(config);
+        }
+    }
+
+    /// The `exp` claim of a JWT; other tokens have none.
+    fn jwt_expiry(token: &str) -> Option<DateTime<Utc>> {
+        let payload = token.split('.').nth(1)?;
+        let claims: Value =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?)
+                .ok()?;
+        DateTime::from_timestamp(claims["exp"].as_i64()?, 0)
+    }
+
+    fn rfc3339(value: &Value) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(value.as_str()?)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    /// Expiry readable from the stored credentials alone.
+    fn stored_expiry(auth: &Value) -> Option<(TokenSource, DateTime<Utc>)> {
+        let provider = &auth["auth-provider"]["config"];
+        auth["token"]
+            .as_str()
+            .and_then(jwt_expiry)
+            .map(|at| (TokenSource::BearerToken, at))
+            .or(provider["id-token"]
+                .as_str()
+                .and_then(jwt_expiry)
+                .map(|at| (TokenSource::OidcIdToken, at)))
+            .or(rfc3339(&provider["expiry"]).map(|at| (TokenSource::AuthProvider, at)))
+    }
+
+    /// Runs the exec plugin non-interactively and reads the expiry of the
+    /// credential it returns.
+    async fn exec_expiry(handle: &AppHandle, exec: &Value) -> Result<DateTime<Utc>, KubiousError> {
+        let command = exec["command"]
+            .as_str()
+            .ok_or("Exec plugin has no command".to_string())?;
+        let info = json!({
+            "apiVersion": exec["apiVersion"].as_str().unwrap_or("client.authentication.k8s.io/v1"),
+            "kind": "ExecCredential",
+            "spec": { "interactive": false },
+        });
+        let mut process = handle
+            .shell()
+            .command(command)
+            .args(
+                exec["args"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|a| a.as_str()),
+            )
+            .env("KUBERNETES_EXEC_INFO", info.to_string());
+        for env in exec["env"].as_array().into_iter().flatten() {
+            if let (Some(name), Some(value)) = (env["name"].as_str(), env["value"].as_str()) {
+                process = process.env(name, value);
+            }
+        }
+        let output = process
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run {}: {}", command, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} exited with code {}",
+                command,
+                output.status.code().unwrap_or(-1)
+            )
+            .into());
+        }
+        let credential: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Invalid credential from {}: {}", command, e))?;
+        rfc3339(&credential["status"]["expirationTimestamp"])
+            .ok_or(format!("{} returned no expiration", command).into())
+    }
+
+    /// Reads a config's token expiry. Exec plugins are only run when
+    /// `probe_exec` is set, since they may be slow or reach out to a login
+    /// service.
+    async fn inspect(
+        handle: &AppHandle,
+        config: KubeConfig,
+        probe_exec: bool,
+    ) -> Result<Option<Known>, KubiousError> {
+        let resolved = resolve_secrets(config)?;
+        let auth = serde_json::to_value(&resolved.auth_info)
+            .or(Err("Failed to serialize credentials.".to_string()))?;
+        let found = match stored_expiry(&auth) {
+            Some(found) => Some(found),
+            None if probe_exec && auth["exec"].is_object() => Some((
+                TokenSource::ExecPlugin,
+                exec_expiry(handle, &auth["exec"]).await?,
+            )),
+            None => None,
+        };
+        Ok(found.map(|(source, expires_at)| Known {
+            source,
+            expires_at,
+            warned: false,
+            expired_sent: false,
+        }))
+    }
+
+    fn describe(config: &str, known: &Known, current: bool) -> TokenExpiry {
+        let remaining_secs = (known.expires_at - Utc::now()).num_seconds();
+        TokenExpiry {
+            config: config.to_string(),
+            source: known.source.clone(),
+            expires_at: known.expires_at.to_rfc3339(),
+            remaining_secs,
+            expired: remaining_secs <= 0,
+            current,
+        }
+    }
+
+    fn current_key(handle: &AppHandle) -> Option<String> {
+        handle
+            .state::<AppState>()
+            .get_current_config()
+            .map(|(key, _)| key)
+    }
+
+    /// Reads expiries of configs not seen yet. Exec plugins are run for the
+    /// selected config only.
+    async fn track(handle: &AppHandle) {
+        let current = current_key(handle);
+        let configs = handle.state::<AppState>().get_configs();
+        for (key, config) in configs {
+            let probe_exec = current.as_ref() == Some(&key);
+            let tracked = {
+                let expiries = handle.state::<TokenExpiries>();
+                let tracker = expiries.tracker_mutable();
+                tracker.configs.contains_key(&key)
+                    && (!probe_exec || tracker.exec_probed.contains(&key))
+            };
+            if tracked {
+                continue;
+            }
+            // Unreadable credentials are not retried on every sweep; the
+            // config fails on its next request anyway.
+            let known = inspect(handle, config, probe_exec).await.unwrap_or(None);
+            let expiries = handle.state::<TokenExpiries>();
+            let mut tracker = expiries.tracker_mutable();
+            if probe_exec {
+                tracker.exec_probed.insert(key.clone());
+            }
+            tracker.configs.insert(key, known);
+        }
+    }
+
+    /// Expiries of every config whose token has a known expiry, soonest
+    /// first.
+    pub async fn expiries(handle: &AppHandle) -> Vec<TokenExpiry> {
+        track(handle).await;
+        let current = current_key(handle);
+        let expiries = handle.state::<TokenExpiries>();
+        let tracker = expiries.tracker_mutable();
+        let mut listed: Vec<TokenExpiry> = tracker
+            .configs
+            .iter()
+            .filter_map(|(key, known)| {
+                known
+                    .as_ref()
+                    .map(|k| describe(key, k, current.as_ref() == Some(key)))
+            })
+            .collect();
+        listed.sort_by_key(|e| e.remaining_secs);
+        listed
+    }
+
+    /// Re-reads a config's expiry, running its exec plugin if it has one,
+    /// e.g. after the user signed in again.
+    pub async fn refresh(
+        handle: &AppHandle,
+        key: &str,
+    ) -> Result<Option<TokenExpiry>, KubiousError> {
+        let config = handle
+            .state::<AppState>()
+            .select_config(key)
+            .ok_or("Unknown config key".to_string())?;
+        let known = inspect(handle, config, true).await?;
+        let current = current_key(handle).as_deref() == Some(key);
+        let expiry = known.as_ref().map(|k| describe(key, k, current));
+        let expiries = handle.state::<TokenExpiries>();
+        let mut tracker = expiries.tracker_mutable();
+        tracker.exec_probed.insert(key.to_string());
+        tracker.configs.insert(key.to_string(), known);
+        Ok(expiry)
+    }
+
+    /// Warns once about tokens within `WARNING_SECS` of expiring and once
+    /// when they expire. Expired exec credentials of the selected config
+    /// are re-read first, since the plugin normally renews them itself.
+    pub async fn sweep(handle: &AppHandle) {
+        track(handle).await;
+        let current = current_key(handle);
+        let renew = current.as_ref().filter(|key| {
+            handle
+                .state::<TokenExpiries>()
+                .tracker_mutable()
+                .configs
+                .get(*key)
+                .and_then(|k| k.as_ref())
+                .is_some_and(|k| k.source == TokenSource::ExecPlugin && k.expires_at <= Utc::now())
+        });
+        if let Some(key) = renew {
+            let _ = refresh(handle, key.as_str()).await;
+        }
+
+        let mut events: Vec<TokenExpiry> = Vec::new();
+        {
+            let expiries = handle.state::<TokenExpiries>();
+            let mut tracker = expiries.tracker_mutable();
+            for (key, known) in tracker.configs.iter_mut() {
+                let Some(known) = known else {
+                    continue;
+                };
+                let expiry = describe(key, known, current.as_ref() == Some(key));
+                if expiry.expired && !known.expired_sent {
+                    known.expired_sent = true;
+                    known.warned = true;
+                    events.push(expiry);
+                } else if expiry.remaining_secs <= WARNING_SECS && !known.warned {
+                    known.warned = true;
+                    events.push(expiry);
+                }
+            }
+        }
+        for event in events {
+            let _ = handle.emit(TOKEN_EXPIRY_EVENT, event);
+        }
+    }
+
+    /// Starts the background check once; later calls do nothing.
+    pub async fn start(handle: AppHandle) {
+        {
+            let expiries = handle.state::<TokenExpiries>();
+            let mut tracker = expiries.tracker_mutable();
+            if tracker.started {
+                return;
+            }
+            tracker.started = true;
+        }
+        loop {
+            sweep(&handle).await;
+            tokio::time::sleep(INTERVAL).await;
+        }
+    }
+}
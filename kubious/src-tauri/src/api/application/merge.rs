@@ -0,0 +1,294 @@
+pub mod app_config_merge {
+    use std::collections::{HashMap, HashSet};
+
+    use kube::{
+        config::{KubeConfigOptions, Kubeconfig},
+        Config,
+    };
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        api::{app_credentials::resolve_secrets, app_state::AppState},
+        compat::kube_compat::{resolve_kubeconfig_paths, resolve_path, KubeConfig},
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    /// One kubeconfig file to merge. `path` is where it was read from, for
+    /// resolving relative certificate paths.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct KubeconfigSource {
+        pub path: Option<String>,
+        pub config: Kubeconfig,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct MergeCandidate {
+        pub source: usize,
+        pub context: String,
+        /// Key the context would be stored under, renamed where the context
+        /// name is taken or unwieldy.
+        pub key: String,
+        pub renamed: bool,
+        pub cluster_url: Option<String>,
+        pub namespace: Option<String>,
+        /// Stored config or earlier candidate reaching the same cluster as the
+        /// same user. Duplicates are not added by default.
+        pub duplicate_of: Option<String>,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct MergePlan {
+        pub candidates: Vec<MergeCandidate>,
+        /// Groups of already stored configs that are the same cluster and
+        /// user under different names.
+        pub stored_duplicates: Vec<Vec<String>>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct MergeSelection {
+        pub source: usize,
+        pub context: String,
+        pub key: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct MergeSummary {
+        pub added: Vec<String>,
+        pub removed: Vec<String>,
+        /// Selections that could not be converted, with the reason.
+        pub failed: HashMap<String, String>,
+        /// Contexts left out of an import because they duplicate a stored
+        /// config, with the key of that config.
+        pub skipped: HashMap<String, String>,
+    }
+
+    /// Identifies a cluster and user pair independently of the names used
+    /// for them, so the same credentials imported twice compare equal.
+    /// Secrets must already be resolved.
+    fn identity(config: &KubeConfig) -> String {
+        let auth = serde_json::to_string(&config.auth_info).unwrap_or_default();
+        let digest = Sha256::digest(
+            format!("{}\n{}", config.cluster_url.trim_end_matches('/'), auth).as_bytes(),
+        );
+        format!("{:x}", digest)
+    }
+
+    /// Shortens context names that embed provider identifiers, such as
+    /// `arn:aws:eks:<region>:<account>:cluster/<name>`, and replaces
+    /// characters that are awkward in keys.
+    fn suggested_key(context: &str) -> String {
+        let name = match context.strip_prefix("arn:") {
+            Some(arn) => arn.rsplit('/').next().unwrap_or(arn),
+            None => context,
+        };
+        let key: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || "-_.@".contains(c) {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        if key.is_empty() {
+            "context".to_string()
+        } else {
+            key
+        }
+    }
+
+    fn unique_key(base: &str, taken: &HashSet<String>) -> String {
+        if !taken.contains(base) {
+            return base.to_string();
+        }
+        (2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|candidate| !taken.contains(candidate))
+            .unwrap_or(base.to_string())
+    }
+
+    async fn convert(source: &KubeconfigSource, context: &str) -> Result<KubeConfig, KubiousError> {
+        let path = source
+            .path
+            .as_deref()
+            .map(|p| std::path::PathBuf::from(resolve_path(p, None)));
+        let config = resolve_kubeconfig_paths(source.config.clone(), path.as_deref());
+        let options = KubeConfigOptions {
+            context: Some(context.to_string()),
+            ..KubeConfigOptions::default()
+        };
+        Config::from_custom_kubeconfig(config, &options)
+            .await
+            .map(KubeConfig::from)
+            .context(format!("Failed to load context {}", context))
+    }
+
+    fn stored_identities(state: &AppState) -> Vec<(String, String)> {
+        let mut stored: Vec<(String, String)> = state
+            .get_configs()
+            .into_iter()
+            .filter_map(|(key, config)| {
+                resolve_secrets(config)
+                    .ok()
+                    .map(|config| (key, identity(&config)))
+            })
+            .collect();
+        stored.sort();
+        stored
+    }
+
+    /// Lists every context of the given kubeconfigs with the key it would be
+    /// stored under and whether it duplicates a stored config or another
+    /// context. Also reports duplicates already among the stored configs.
+    pub async fn plan(state: &AppState, sources: &[KubeconfigSource]) -> MergePlan {
+        let stored = stored_identities(state);
+        let mut known: HashMap<String, String> = HashMap::new();
+        let mut groups: Vec<Vec<String>> = Vec::new();
+        for (key, id) in &stored {
+            match known.get(id) {
+                Some(first) => match groups.iter_mut().find(|g| g.contains(first)) {
+                    Some(group) => group.push(key.clone()),
+                    None => groups.push(vec![first.clone(), key.clone()]),
+                },
+                None => {
+                    known.insert(id.clone(), key.clone());
+                }
+            }
+        }
+        let mut taken: HashSet<String> = stored.iter().map(|(key, _)| key.clone()).collect();
+
+        let mut candidates: Vec<MergeCandidate> = Vec::new();
+        for (index, source) in sources.iter().enumerate() {
+            for named in &source.config.contexts {
+                let base = suggested_key(named.name.as_str());
+                let mut candidate = MergeCandidate {
+                    source: index,
+                    context: named.name.clone(),
+                    key: base.clone(),
+                    renamed: false,
+                    cluster_url: None,
+                    namespace: named.context.as_ref().and_then(|c| c.namespace.clone()),
+                    duplicate_of: None,
+                    error: None,
+                };
+                match convert(source, named.name.as_str()).await {
+                    Ok(config) => {
+                        candidate.cluster_url = Some(config.cluster_url.clone());
+                        let id = identity(&config);
+                        match known.get(&id) {
+                            Some(existing) => candidate.duplicate_of = Some(existing.clone()),
+                            None => {
+                                candidate.key = unique_key(base.as_str(), &taken);
+                                taken.insert(candidate.key.clone());
+                                known.insert(id, candidate.key.clone());
+                            }
+                        }
+                    }
+                    Err(error) => candidate.error = Some(error.to_string()),
+                }
+                candidate.renamed = candidate.key != named.name;
+                candidates.push(candidate);
+            }
+        }
+        MergePlan {
+            candidates,
+            stored_duplicates: groups,
+        }
+    }
+
+    /// Stores the selected contexts under their chosen keys and removes the
+    /// given stored configs, typically the redundant members of
+    /// `stored_duplicates`.
+    pub async fn apply(
+        state: &AppState,
+        sources: &[KubeconfigSource],
+        selections: &[MergeSelection],
+        remove: &[String],
+    ) -> MergeSummary {
+        let mut summary = MergeSummary {
+            added: Vec::new(),
+            removed: Vec::new(),
+            failed: HashMap::new(),
+            skipped: HashMap::new(),
+        };
+        for selection in selections {
+            let Some(source) = sources.get(selection.source) else {
+                summary
+                    .failed
+                    .insert(selection.key.clone(), "Unknown kubeconfig".to_string());
+                continue;
+            };
+            match convert(source, selection.context.as_str()).await {
+                Ok(config) => {
+                    state.put_compat_config(selection.key.as_str(), config);
+                    summary.added.push(selection.key.clone());
+                }
+                Err(error) => {
+                    summary
+                        .failed
+                        .insert(selection.key.clone(), error.to_string());
+                }
+            }
+        }
+        let configs = state.get_configs();
+        for key in remove {
+            if configs.contains_key(key) && !summary.added.contains(key) {
+                state.remove_config(key.as_str());
+                summary.removed.push(key.clone());
+            }
+        }
+        summary
+    }
+
+    /// Reads a kubeconfig from a file or from raw YAML.
+    pub fn read_kubeconfig(
+        path: Option<&str>,
+        yaml: Option<&str>,
+    ) -> Result<KubeconfigSource, KubiousError> {
+        match (path, yaml) {
+            (Some(path), None) => {
+                let resolved = resolve_path(path, None);
+                Kubeconfig::read_from(resolved.as_str())
+                    .map(|config| KubeconfigSource {
+                        path: Some(resolved),
+                        config,
+                    })
+                    .context(format!("Failed to read kubeconfig {}", path))
+            }
+            (None, Some(yaml)) => Kubeconfig::from_yaml(yaml)
+                .map(|config| KubeconfigSource { path: None, config })
+                .context("Failed to parse kubeconfig"),
+            _ => Err("Exactly one of path or yaml is required".into()),
+        }
+    }
+
+    /// Stores every context of a kubeconfig under its suggested key, leaving
+    /// out contexts that duplicate a stored config.
+    pub async fn import(state: &AppState, source: KubeconfigSource) -> MergeSummary {
+        let sources = vec![source];
+        let plan = plan(state, &sources).await;
+        let mut selections: Vec<MergeSelection> = Vec::new();
+        let mut skipped: HashMap<String, String> = HashMap::new();
+        let mut failed: HashMap<String, String> = HashMap::new();
+        for candidate in plan.candidates {
+            if let Some(error) = candidate.error {
+                failed.insert(candidate.context, error);
+            } else if let Some(existing) = candidate.duplicate_of {
+                skipped.insert(candidate.context, existing);
+            } else {
+                selections.push(MergeSelection {
+                    source: candidate.source,
+                    context: candidate.context,
+                    key: candidate.key,
+                });
+            }
+        }
+        let mut summary = apply(state, &sources, &selections, &[]).await;
+        summary.failed.extend(failed);
+        summary.skipped = skipped;
+        summary
+    }
+}
@@ -0,0 +1,55 @@
+pub mod state_migrations {
+    use serde_json::{json, Map, Value};
+
+    /// Version written into `config.json`. Bump it together with a new entry
+    /// in `MIGRATIONS` whenever a stored field is renamed, removed or made
+    /// required.
+    pub const STATE_VERSION: u32 = 1;
+
+    /// `MIGRATIONS[n]` upgrades a state of version `n` to `n + 1`.
+    const MIGRATIONS: [fn(&mut Map<String, Value>); STATE_VERSION as usize] = [unversioned];
+
+    /// Files written before versioning. Early configs lack fields that
+    /// `KubeConfig` now requires, and the oldest lack `current_config`.
+    fn unversioned(state: &mut Map<String, Value>) {
+        state.entry("configs").or_insert_with(|| json!({}));
+        state.entry("current_config").or_insert(Value::Null);
+        let Some(configs) = state.get_mut("configs").and_then(|c| c.as_object_mut()) else {
+            return;
+        };
+        for config in configs.values_mut().filter_map(|c| c.as_object_mut()) {
+            config.entry("headers").or_insert_with(|| json!([]));
+            config
+                .entry("accept_invalid_certs")
+                .or_insert(Value::Bool(false));
+        }
+    }
+
+    /// The version a serialized state was written with; files from before
+    /// versioning count as version 0.
+    pub fn version_of(state: &Value) -> u32 {
+        state["version"].as_u64().unwrap_or(0) as u32
+    }
+
+    /// Runs every migration between the state's version and
+    /// `STATE_VERSION`, returning the upgraded state and the version it
+    /// started at. States written by a newer Kubious are rejected rather
+    /// than loaded with fields silently dropped.
+    pub fn migrate(mut state: Value) -> Result<(Value, u32), String> {
+        let from = version_of(&state);
+        if from > STATE_VERSION {
+            return Err(format!(
+                "State version {} is newer than this version of Kubious supports ({})",
+                from, STATE_VERSION
+            ));
+        }
+        let Some(object) = state.as_object_mut() else {
+            return Err("State is not a JSON object".to_string());
+        };
+        for migration in &MIGRATIONS[from as usize..] {
+            migration(object);
+        }
+        object.insert("version".to_string(), json!(STATE_VERSION));
+        Ok((state, from))
+    }
+}
@@ -1,21 +1,67 @@
 pub mod application_api {
 
-    use std::collections::HashMap;
+    use std::{collections::HashMap, time::Duration};
 
-    use crate::{compat::kube_compat::KubeConfig, CommandHandler};
+    use crate::{
+        api::{discovery_cache::DiscoveryCache, favorite_watches::{self, FavoriteWatches}, namespace_budgets, rbac_capabilities::CapabilityCache, rollout_triggers},
+        compat::kube_compat::{KeepAliveSettings, KubeConfig},
+        util::{
+            api_metrics,
+            clock_skew::{self, ClockSkew},
+            connection_tuning,
+            kubious_error::KubiousError,
+            registry_client::{self, RegistryCredential},
+        },
+        CommandHandler,
+    };
     use k8s_openapi::apimachinery::pkg::version::Info;
     use kube::config::Kubeconfig;
     use serde::{Deserialize, Serialize};
     use serde_json::Value;
     use tauri::Manager;
 
-    use super::app_state::AppState;
+    use super::{
+        app_backup, app_command_cache::CommandCache, app_config_merge::{self, KubeconfigSource, MergeSelection}, app_credentials::{credential_status, resolve_secrets}, app_requests::RequestRecorder,
+        app_settings::{BudgetAlert, DiffIgnoreRule, Favorite, RolloutTrigger, SessionLimits, TenancyFilter}, app_state::AppState, app_support, app_terminal,
+        session_limits::{self, SessionReaper},
+        token_expiry::{self, TokenExpiries},
+    };
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
     pub struct ConfigCheck {
         config: KubeConfig,
         connected: bool,
-        version: Option<Info>
+        version: Option<Info>,
+        clock_skew: Option<ClockSkew>
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RegistryLoginCheck {
+        registry: String,
+        status: Option<u16>,
+        authenticated: bool,
+        error: Option<String>
+    }
+
+    /// Drops everything cached for a config, for when it is added, changed
+    /// or removed.
+    fn invalidate_config_caches(handle: &tauri::AppHandle, key: &str) {
+        handle.state::<CapabilityCache>().invalidate(key);
+        handle.state::<CommandCache>().invalidate(key);
+        handle.state::<DiscoveryCache>().invalidate(key);
+        handle.state::<TokenExpiries>().invalidate(key);
+    }
+
+    async fn check_registry_login(registry: String, credential: Option<RegistryCredential>) -> RegistryLoginCheck {
+        match registry_client::check_login(registry.as_str(), &credential).await {
+            Ok(status) => RegistryLoginCheck {
+                registry,
+                status: Some(status),
+                authenticated: (200..300).contains(&status),
+                error: None
+            },
+            Err(e) => RegistryLoginCheck {registry, status: None, authenticated: false, error: Some(e)}
+        }
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -24,13 +70,74 @@ pub mod application_api {
         SetCurrentConfig { key: Option<String> },
         GetCurrentConfig {},
         GetConfigs {},
-        AddConfig { key: String, config: Kubeconfig },
+        /// `path` is the file the kubeconfig was read from; relative
+        /// certificate and key references are resolved against it.
+        AddConfig { key: String, config: Kubeconfig, #[serde(default)] path: Option<String> },
         RemoveConfig { key: String },
+        /// Lists the contexts of several kubeconfigs with deduplicated,
+        /// renamed keys, without storing anything.
+        PlanConfigMerge { kubeconfigs: Vec<KubeconfigSource> },
+        ApplyConfigMerge { kubeconfigs: Vec<KubeconfigSource>, selections: Vec<MergeSelection>, remove: Vec<String> },
+        /// Adds every context of a kubeconfig file, or of raw kubeconfig YAML.
+        ImportKubeconfig { path: Option<String>, yaml: Option<String> },
+        SetConfigHeaders { key: String, headers: Vec<(String, Option<String>)> },
         CheckConfigs {},
-        CheckConfig {key: String}
+        CheckConfig {key: String},
+        SetConfigKeepAlive { key: String, keep_alive: KeepAliveSettings },
+        /// How many secrets of each config are in the keyring, left in
+        /// plaintext, or missing from the keyring.
+        CredentialStatus {},
+        /// Retries moving plaintext secrets into the keyring.
+        SecureCredentials {},
+        /// Negotiated protocol, TLS version and `/version` round trips for a
+        /// config, to troubleshoot watches dying behind NAT or VPN gateways.
+        DiagnoseConnection { key: String, samples: Option<usize> },
+        GetDiffIgnoreRules {},
+        PutDiffIgnoreRule { rule: DiffIgnoreRule },
+        RemoveDiffIgnoreRule { id: String },
+        ExportBackup { path: String, redact_credentials: bool },
+        ImportBackup { path: String, overwrite: bool },
+        GenerateSupportBundle { path: Option<String>, clusters: Vec<String>, preview: bool },
+        SetRequestRecording { enabled: bool },
+        ShowRequests { limit: Option<usize> },
+        ClearRequests {},
+        ClearCommandCache {},
+        BackendStats { cluster: Option<String>, #[serde(default)] reset: bool },
+        ListRegistryLogins {},
+        AddRegistryLogin { registry: String, username: String, password: String, description: Option<String> },
+        TestRegistryLogin { registry: String },
+        RemoveRegistryLogin { registry: String },
+        ExportKubectlContext { namespace: Option<String> },
+        GetFavorites {},
+        PutFavorite { favorite: Favorite },
+        RemoveFavorite { id: String },
+        FavoriteStatuses {},
+        GetRolloutTriggers {},
+        PutRolloutTrigger { trigger: RolloutTrigger },
+        RemoveRolloutTrigger { id: String },
+        RolloutAudit { trigger_id: Option<String>, limit: Option<usize> },
+        GetBudgetAlerts {},
+        PutBudgetAlert { alert: BudgetAlert },
+        RemoveBudgetAlert { id: String },
+        BudgetBreaches { alert_id: Option<String>, limit: Option<usize> },
+        CheckBudgetAlerts {},
+        ListTenancyFilters { cluster: Option<String> },
+        PutTenancyFilter { filter: TenancyFilter },
+        RemoveTenancyFilter { id: String },
+        ActivateTenancyFilter { cluster: String, id: Option<String> },
+        GetSessionLimits {},
+        SetSessionLimits { limits: SessionLimits },
+        /// Marks an exec, log or port-forward session as in use, postponing
+        /// its idle timeout.
+        TouchSession { id: String },
+        SessionDeadlines {},
+        /// Token expiries of the stored configs, soonest first.
+        TokenExpiries {},
+        /// Re-reads a config's token expiry, e.g. after signing in again.
+        RefreshTokenExpiry { key: String }
     }
     impl CommandHandler for ApplicationCommand {
-        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, String> {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
             match self {
                 ApplicationCommand::SetCurrentConfig { key } => {
                     let state = handle.state::<AppState>();
@@ -39,9 +146,8 @@ pub mod application_api {
                         state
                             .save_state(handle.clone())
                             .and(self.wrap_in_value(Ok(conf)))
-                            .or(Err("Failed to save state".to_string()))
                     } else {
-                        return Err("Unknown config key".to_string());
+                        return Err(KubiousError::config("Unknown config key"));
                     }
                 }
                 ApplicationCommand::GetConfigs {} => {
@@ -50,58 +156,393 @@ pub mod application_api {
                 ApplicationCommand::GetCurrentConfig {} => {
                     self.wrap_in_value(Ok(handle.state::<AppState>().get_current_config()))
                 }
-                ApplicationCommand::AddConfig { key, config } => {
+                ApplicationCommand::AddConfig { key, config, path } => {
                     let state = handle.state::<AppState>();
-                    let conf = state.put_kubeconfig(key, config.clone()).await;
+                    invalidate_config_caches(handle, key);
+                    let conf = state.put_kubeconfig(key, config.clone(), path.as_deref()).await?;
+                    state
+                        .save_state(handle.clone())
+                        .and(self.wrap_in_value(Ok(conf)))
+                }
+                ApplicationCommand::SetConfigHeaders { key, headers } => {
+                    let state = handle.state::<AppState>();
+                    let conf = state.set_config_headers(key, headers.clone())?;
+                    invalidate_config_caches(handle, key);
                     state
                         .save_state(handle.clone())
                         .and(self.wrap_in_value(Ok(conf)))
-                        .or(Err("Failed to save state".to_string()))
                 }
                 ApplicationCommand::RemoveConfig { key } => {
                     let state = handle.state::<AppState>();
                     state.remove_config(key);
+                    invalidate_config_caches(handle, key);
                     state
                         .save_state(handle.clone())
                         .and(self.wrap_in_value(Ok(())))
-                        .or(Err("Failed to save state".to_string()))
+                },
+                ApplicationCommand::PlanConfigMerge { kubeconfigs } => {
+                    let state = handle.state::<AppState>();
+                    self.wrap_in_value(Ok(app_config_merge::plan(&state, kubeconfigs).await))
+                },
+                ApplicationCommand::ApplyConfigMerge { kubeconfigs, selections, remove } => {
+                    let state = handle.state::<AppState>();
+                    let summary = app_config_merge::apply(&state, kubeconfigs, selections, remove).await;
+                    for key in summary.added.iter().chain(summary.removed.iter()) {
+                        invalidate_config_caches(handle, key);
+                    }
+                    state
+                        .save_state(handle.clone())
+                        .and(self.wrap_in_value(Ok(summary)))
+                },
+                ApplicationCommand::ImportKubeconfig { path, yaml } => {
+                    let source = app_config_merge::read_kubeconfig(path.as_deref(), yaml.as_deref())?;
+                    let state = handle.state::<AppState>();
+                    let summary = app_config_merge::import(&state, source).await;
+                    for key in summary.added.iter() {
+                        invalidate_config_caches(handle, key);
+                    }
+                    state
+                        .save_state(handle.clone())
+                        .and(self.wrap_in_value(Ok(summary)))
                 },
                 ApplicationCommand::CheckConfig { key } => {
                     let state = handle.state::<AppState>();
                     if let Some(config) = state.select_config(key) {
                         if let Some(client) = state.client_for(key).await {
                             if let Ok(vers) = client.apiserver_version().await {
-                                self.wrap_in_value(Ok(ConfigCheck {config, connected: true, version: Some(vers)}))
+                                let skew = clock_skew::measure(&client).await;
+                                self.wrap_in_value(Ok(ConfigCheck {config, connected: true, version: Some(vers), clock_skew: skew}))
                             } else {
-                                self.wrap_in_value(Ok(ConfigCheck {config, connected: false, version: None}))
+                                self.wrap_in_value(Ok(ConfigCheck {config, connected: false, version: None, clock_skew: None}))
                             }
                         } else {
-                            self.wrap_in_value(Ok(ConfigCheck {config, connected: false, version: None}))
+                            self.wrap_in_value(Ok(ConfigCheck {config, connected: false, version: None, clock_skew: None}))
                         }
                     } else {
-                        Err("Unknown config key".to_string())
+                        Err(KubiousError::config("Unknown config key"))
                     }
                 },
+                ApplicationCommand::SetConfigKeepAlive { key, keep_alive } => {
+                    let state = handle.state::<AppState>();
+                    let conf = state.set_config_keep_alive(key, keep_alive.clone())?;
+                    invalidate_config_caches(handle, key);
+                    state
+                        .save_state(handle.clone())
+                        .and(self.wrap_in_value(Ok(conf)))
+                },
+                ApplicationCommand::CredentialStatus {} => {
+                    let mut statuses: Vec<_> = handle
+                        .state::<AppState>()
+                        .get_configs()
+                        .iter()
+                        .map(|(key, config)| credential_status(key, config))
+                        .collect();
+                    statuses.sort_by(|a, b| a.config.cmp(&b.config));
+                    self.wrap_in_value(Ok(statuses))
+                },
+                ApplicationCommand::SecureCredentials {} => {
+                    let state = handle.state::<AppState>();
+                    if state.secure_configs() {
+                        state.save_state(handle.clone())?;
+                    }
+                    let plaintext: usize = state
+                        .get_configs()
+                        .iter()
+                        .map(|(key, config)| credential_status(key, config).plaintext)
+                        .sum();
+                    self.wrap_in_value(Ok(plaintext))
+                },
+                ApplicationCommand::DiagnoseConnection { key, samples } => {
+                    let state = handle.state::<AppState>();
+                    let Some(config) = state.select_config(key) else {
+                        return Err(KubiousError::config("Unknown config key"));
+                    };
+                    let Some(client) = state.client_for(key).await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let keep_alive = config.keep_alive.clone();
+                    let mut resolved = resolve_secrets(config)?;
+                    resolved.connect_timeout = Some(Duration::from_secs(10));
+                    self.wrap_in_value(Ok(connection_tuning::diagnose(resolved.into(), keep_alive, &client, samples.unwrap_or(5)).await))
+                },
                 ApplicationCommand::CheckConfigs {  } => {
                     let state = handle.state::<AppState>();
                     let mut config_mapping: HashMap<String, ConfigCheck> = HashMap::new();
                     for (key, config) in state.get_configs() {
                         if let Some(client) = state.client_for(key.as_str()).await {
                             if let Ok(vers) = client.apiserver_version().await {
-                                config_mapping.insert(key, ConfigCheck {config, connected: true, version: Some(vers)});
+                                let skew = clock_skew::measure(&client).await;
+                                config_mapping.insert(key, ConfigCheck {config, connected: true, version: Some(vers), clock_skew: skew});
                             } else {
-                                config_mapping.insert(key, ConfigCheck {config, connected: false, version: None});
+                                config_mapping.insert(key, ConfigCheck {config, connected: false, version: None, clock_skew: None});
                             }
                         } else {
-                            config_mapping.insert(key, ConfigCheck {config, connected: false, version: None});
+                            config_mapping.insert(key, ConfigCheck {config, connected: false, version: None, clock_skew: None});
                         }
                     }
                     self.wrap_in_value(Ok(config_mapping))
+                },
+                ApplicationCommand::GetDiffIgnoreRules {} => {
+                    self.wrap_in_value(Ok(handle.state::<AppState>().get_settings().diff_ignore_rules))
+                },
+                ApplicationCommand::PutDiffIgnoreRule { rule } => {
+                    let state = handle.state::<AppState>();
+                    state.put_diff_ignore_rule(rule.clone());
+                    state
+                        .save_state(handle.clone())
+                        .and(self.wrap_in_value(Ok(rule.clone())))
+                },
+                ApplicationCommand::RemoveDiffIgnoreRule { id } => {
+                    let state = handle.state::<AppState>();
+                    if !state.remove_diff_ignore_rule(id) {
+                        return Err("Unknown rule id".into());
+                    }
+                    state
+                        .save_state(handle.clone())
+                        .and(self.wrap_in_value(Ok(())))
+                },
+                ApplicationCommand::ExportBackup { path, redact_credentials } => {
+                    self.wrap_in_value(app_backup::export_backup(handle, path, *redact_credentials))
+                },
+                ApplicationCommand::ImportBackup { path, overwrite } => {
+                    let summary = app_backup::import_backup(handle, path, *overwrite)?;
+                    for key in &summary.configs {
+                        invalidate_config_caches(handle, key.as_str());
+                    }
+                    self.wrap_in_value(Ok(summary))
+                },
+                ApplicationCommand::GenerateSupportBundle { path, clusters, preview } => {
+                    self.wrap_in_value(app_support::generate(handle, path.clone(), clusters, *preview).await)
+                }
+                ApplicationCommand::SetRequestRecording { enabled } => {
+                    handle.state::<RequestRecorder>().set_enabled(*enabled);
+                    self.wrap_in_value(Ok(*enabled))
+                }
+                ApplicationCommand::ShowRequests { limit } => {
+                    self.wrap_in_value(Ok(handle.state::<RequestRecorder>().traces(*limit)))
+                }
+                ApplicationCommand::ClearRequests {} => {
+                    handle.state::<RequestRecorder>().clear();
+                    self.wrap_in_value(Ok(()))
+                }
+                ApplicationCommand::ClearCommandCache {} => {
+                    handle.state::<CommandCache>().clear();
+                    self.wrap_in_value(Ok(()))
+                }
+                ApplicationCommand::BackendStats { cluster, reset } => {
+                    let stats = api_metrics::stats(cluster.as_deref());
+                    if *reset {
+                        api_metrics::reset(cluster.as_deref());
+                    }
+                    self.wrap_in_value(Ok(stats))
+                }
+                ApplicationCommand::ListRegistryLogins {} => {
+                    self.wrap_in_value(Ok(handle.state::<AppState>().get_settings().registry_logins))
+                }
+                ApplicationCommand::AddRegistryLogin { registry, username, password, description } => {
+                    let state = handle.state::<AppState>();
+                    let login = state.put_registry_login(registry, username, password, description.clone())?;
+                    state
+                        .save_state(handle.clone())
+                        .and(self.wrap_in_value(Ok(login)))
+                }
+                ApplicationCommand::TestRegistryLogin { registry } => {
+                    let Some(credential) = handle.state::<AppState>().registry_credential(registry) else {
+                        return Err("No login stored for this registry".into());
+                    };
+                    let registry = registry_client::normalize_registry_key(registry);
+                    self.wrap_in_value(Ok(check_registry_login(registry, Some(credential)).await))
+                }
+                ApplicationCommand::RemoveRegistryLogin { registry } => {
+                    let state = handle.state::<AppState>();
+                    if !state.remove_registry_login(registry) {
+                        return Err("No login stored for this registry".into());
+                    }
+                    state
+                        .save_state(handle.clone())
+                        .and(self.wrap_in_value(Ok(())))
+                }
+                ApplicationCommand::ExportKubectlContext { namespace } => {
+                    let Some((key, config)) = handle.state::<AppState>().get_current_config() else {
+                        return Err(KubiousError::config("No config selected"));
+                    };
+                    let config = resolve_secrets(config)?;
+                    self.wrap_in_value(app_terminal::export_context(handle, key.as_str(), &config, namespace.clone()))
+                }
+                ApplicationCommand::GetFavorites {} => {
+                    self.wrap_in_value(Ok(handle.state::<AppState>().get_settings().favorites))
+                }
+                ApplicationCommand::PutFavorite { favorite } => {
+                    let state = handle.state::<AppState>();
+                    state.put_favorite(favorite.clone());
+                    state.save_state(handle.clone())?;
+                    favorite_watches::sync(handle.clone()).await;
+                    self.wrap_in_value(Ok(favorite.clone()))
+                }
+                ApplicationCommand::RemoveFavorite { id } => {
+                    let state = handle.state::<AppState>();
+                    if !state.remove_favorite(id) {
+                        return Err("Unknown favorite id".into());
+                    }
+                    state.save_state(handle.clone())?;
+                    favorite_watches::sync(handle.clone()).await;
+                    self.wrap_in_value(Ok(()))
+                }
+                ApplicationCommand::FavoriteStatuses {} => {
+                    favorite_watches::sync(handle.clone()).await;
+                    self.wrap_in_value(Ok(handle.state::<FavoriteWatches>().statuses()))
+                }
+                ApplicationCommand::GetRolloutTriggers {} => {
+                    rollout_triggers::sync(handle.clone()).await;
+                    self.wrap_in_value(Ok(handle.state::<AppState>().get_settings().rollout_triggers))
+                }
+                ApplicationCommand::PutRolloutTrigger { trigger } => {
+                    if !["Deployment", "StatefulSet", "DaemonSet"].contains(&trigger.kind.as_str()) {
+                        return Err("Rollout triggers support Deployments, StatefulSets and DaemonSets".into());
+                    }
+                    let state = handle.state::<AppState>();
+                    state.put_rollout_trigger(trigger.clone());
+                    state.save_state(handle.clone())?;
+                    rollout_triggers::sync(handle.clone()).await;
+                    self.wrap_in_value(Ok(trigger.clone()))
+                }
+                ApplicationCommand::RemoveRolloutTrigger { id } => {
+                    let state = handle.state::<AppState>();
+                    if !state.remove_rollout_trigger(id) {
+                        return Err("Unknown rollout trigger id".into());
+                    }
+                    state.save_state(handle.clone())?;
+                    rollout_triggers::sync(handle.clone()).await;
+                    self.wrap_in_value(Ok(()))
+                }
+                ApplicationCommand::RolloutAudit { trigger_id, limit } => {
+                    let mut entries: Vec<_> = handle
+                        .state::<AppState>()
+                        .get_settings()
+                        .rollout_audit
+                        .into_iter()
+                        .rev()
+                        .filter(|e| trigger_id.as_ref().is_none_or(|id| &e.trigger_id == id))
+                        .collect();
+                    entries.truncate(limit.unwrap_or(entries.len()));
+                    self.wrap_in_value(Ok(entries))
+                }
+                ApplicationCommand::GetBudgetAlerts {} => {
+                    self.wrap_in_value(Ok(handle.state::<AppState>().get_settings().budget_alerts))
+                }
+                ApplicationCommand::PutBudgetAlert { alert } => {
+                    let thresholds = [alert.quota_percent, alert.cpu_cores, alert.memory_bytes];
+                    if thresholds.iter().flatten().all(|t| *t <= 0.0) && alert.restarts_per_hour.is_none() {
+                        return Err("A budget alert needs at least one positive threshold".into());
+                    }
+                    let state = handle.state::<AppState>();
+                    state.put_budget_alert(alert.clone());
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(alert.clone()))
+                }
+                ApplicationCommand::RemoveBudgetAlert { id } => {
+                    let state = handle.state::<AppState>();
+                    if !state.remove_budget_alert(id) {
+                        return Err("Unknown budget alert id".into());
+                    }
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(()))
+                }
+                ApplicationCommand::BudgetBreaches { alert_id, limit } => {
+                    let mut breaches: Vec<_> = handle
+                        .state::<AppState>()
+                        .get_settings()
+                        .budget_breaches
+                        .into_iter()
+                        .rev()
+                        .filter(|b| alert_id.as_ref().is_none_or(|id| &b.alert_id == id))
+                        .collect();
+                    breaches.truncate(limit.unwrap_or(breaches.len()));
+                    self.wrap_in_value(Ok(breaches))
+                }
+                ApplicationCommand::CheckBudgetAlerts {} => {
+                    self.wrap_in_value(Ok(namespace_budgets::evaluate(handle.clone()).await))
+                }
+                ApplicationCommand::ListTenancyFilters { cluster } => {
+                    let filters: Vec<TenancyFilter> = handle
+                        .state::<AppState>()
+                        .get_settings()
+                        .tenancy_filters
+                        .into_iter()
+                        .filter(|f| cluster.as_ref().map(|c| *c == f.cluster).unwrap_or(true))
+                        .collect();
+                    self.wrap_in_value(Ok(filters))
+                }
+                ApplicationCommand::PutTenancyFilter { filter } => {
+                    let state = handle.state::<AppState>();
+                    state.put_tenancy_filter(filter.clone());
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(filter.clone()))
+                }
+                ApplicationCommand::RemoveTenancyFilter { id } => {
+                    let state = handle.state::<AppState>();
+                    if !state.remove_tenancy_filter(id) {
+                        return Err("Unknown tenancy filter id".into());
+                    }
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(()))
+                }
+                ApplicationCommand::ActivateTenancyFilter { cluster, id } => {
+                    let state = handle.state::<AppState>();
+                    state.activate_tenancy_filter(cluster.as_str(), id.as_deref())?;
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(state.active_tenancy_filter(cluster.as_str())))
+                }
+                ApplicationCommand::GetSessionLimits {} => {
+                    self.wrap_in_value(Ok(handle.state::<AppState>().get_settings().session_limits))
+                }
+                ApplicationCommand::SetSessionLimits { limits } => {
+                    let state = handle.state::<AppState>();
+                    state.set_session_limits(limits.clone());
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(limits.clone()))
+                }
+                ApplicationCommand::TouchSession { id } => {
+                    if !handle.state::<SessionReaper>().touch(id) {
+                        return Err("Unknown session".into());
+                    }
+                    self.wrap_in_value(Ok(()))
+                }
+                ApplicationCommand::SessionDeadlines {} => {
+                    self.wrap_in_value(Ok(session_limits::deadlines(handle)))
+                }
+                ApplicationCommand::TokenExpiries {} => {
+                    self.wrap_in_value(Ok(token_expiry::expiries(handle).await))
+                }
+                ApplicationCommand::RefreshTokenExpiry { key } => {
+                    self.wrap_in_value(token_expiry::refresh(handle, key).await)
                 }
             }
         }
     }
 }
 
+mod backup;
+pub use backup::app_backup;
+mod merge;
+pub use merge::app_config_merge;
+mod credentials;
+pub use credentials::app_credentials;
+mod settings;
+pub use settings::app_settings;
 mod state;
 pub use state::app_state;
+mod migrations;
+pub use migrations::state_migrations;
+mod support;
+pub use support::app_support;
+mod requests;
+pub use requests::app_requests;
+mod terminal;
+pub use terminal::app_terminal;
+mod cache;
+pub use cache::app_command_cache;
+mod sessions;
+pub use sessions::session_limits;
+mod expiry;
+pub use expiry::token_expiry;
@@ -0,0 +1,88 @@
+pub mod app_requests {
+    use std::{
+        collections::VecDeque,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Mutex, MutexGuard,
+        },
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::util::{api_recorder::RecordedRequest, lock_recovery::recover};
+
+    const MAX_TRACES: usize = 50;
+
+    /// The API requests made while executing one command. As with support
+    /// logs, only the command's scope and name are kept.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CommandTrace {
+        pub id: u64,
+        pub timestamp: u64,
+        pub scope: String,
+        pub command: String,
+        pub success: bool,
+        pub requests: Vec<RecordedRequest>,
+    }
+
+    /// Debug-mode request recording. Disabled by default and not persisted.
+    #[derive(Default)]
+    pub struct RequestRecorder {
+        enabled: AtomicBool,
+        next_id: AtomicU64,
+        traces: Mutex<VecDeque<CommandTrace>>,
+    }
+
+    impl RequestRecorder {
+        fn traces_mutable(&self) -> MutexGuard<'_, VecDeque<CommandTrace>> {
+            recover(&self.traces)
+        }
+
+        pub fn enabled(&self) -> bool {
+            self.enabled.load(Ordering::Relaxed)
+        }
+
+        pub fn set_enabled(&self, enabled: bool) {
+            self.enabled.store(enabled, Ordering::Relaxed);
+        }
+
+        /// Stores the requests of a finished command. Commands that made no
+        /// API requests are skipped.
+        pub fn push(&self, command: &Value, success: bool, requests: Vec<RecordedRequest>) {
+            if requests.is_empty() {
+                return;
+            }
+            let mut traces = self.traces_mutable();
+            traces.push_back(CommandTrace {
+                id: self.next_id.fetch_add(1, Ordering::Relaxed),
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                scope: command["scope"].as_str().unwrap_or_default().to_string(),
+                command: command["command"].as_str().unwrap_or_default().to_string(),
+                success,
+                requests,
+            });
+            while traces.len() > MAX_TRACES {
+                traces.pop_front();
+            }
+        }
+
+        /// Most recent traces first.
+        pub fn traces(&self, limit: Option<usize>) -> Vec<CommandTrace> {
+            self.traces_mutable()
+                .iter()
+                .rev()
+                .take(limit.unwrap_or(MAX_TRACES))
+                .cloned()
+                .collect()
+        }
+
+        pub fn clear(&self) {
+            self.traces_mutable().clear();
+        }
+    }
+}
@@ -0,0 +1,277 @@
+pub mod session_limits {
+    use std::{
+        collections::HashMap,
+        sync::{Mutex, MutexGuard},
+        time::{Duration, Instant},
+    };
+
+    use serde::{Deserialize, Serialize};
+    use tauri::{AppHandle, Emitter, Manager};
+
+    use crate::api::{
+        app_settings::{SessionLimit, SessionLimits},
+        app_state::AppState,
+        exec_sessions::{self, ExecSessions},
+        log_sessions::{self, LogSessions},
+        multi_cluster_logs::{self, LogTails},
+        port_forwards::{self, PortForwardManager},
+    };
+    use crate::util::lock_recovery::recover;
+
+    /// Event channel carrying a `SessionLimitEvent` when a session is about
+    /// to be closed, and again once it was.
+    pub const SESSION_LIMIT_EVENT: &str = "session-limit";
+
+    /// How often sessions are checked against their limits.
+    const INTERVAL: Duration = Duration::from_secs(15);
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum SessionKind {
+        Exec,
+        /// Single-pod log follows and multi-cluster tails.
+        Log,
+        PortForward,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum LimitReason {
+        Idle,
+        Lifetime,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct SessionLimitEvent {
+        pub id: String,
+        pub kind: SessionKind,
+        pub reason: LimitReason,
+        pub remaining_secs: u64,
+        /// `false` for the warning, `true` once the session was closed.
+        pub terminated: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct SessionDeadline {
+        pub id: String,
+        pub kind: SessionKind,
+        pub idle_secs: u64,
+        /// Time left before the nearest limit closes the session; `None`
+        /// when no limit applies.
+        pub remaining_secs: Option<u64>,
+        pub reason: Option<LimitReason>,
+    }
+
+    struct Tracked {
+        kind: SessionKind,
+        started: Instant,
+        last_active: Instant,
+        warned: bool,
+    }
+
+    #[derive(Default)]
+    struct Tracker {
+        sessions: HashMap<String, Tracked>,
+        started: bool,
+    }
+
+    /// Activity of the running sessions, keyed by session id. Sessions are
+    /// picked up by the sweep, so their start is known to within `INTERVAL`.
+    #[derive(Default)]
+    pub struct SessionReaper {
+        tracker: Mutex<Tracker>,
+    }
+
+    impl SessionReaper {
+        fn tracker_mutable(&self) -> MutexGuard<'_, Tracker> {
+            recover(&self.tracker)
+        }
+
+        /// Marks a session as in use, postponing its idle timeout. Returns
+        /// whether the session is tracked.
+        pub fn touch(&self, id: &str) -> bool {
+            let mut tracker = self.tracker_mutable();
+            let Some(tracked) = tracker.sessions.get_mut(id) else {
+                return false;
+            };
+            tracked.last_active = Instant::now();
+            tracked.warned = false;
+            true
+        }
+    }
+
+    /// Every running session and whether it is in use right now, as port
+    /// forwards are while a connection is open.
+    fn running(handle: &AppHandle) -> Vec<(String, SessionKind, bool)> {
+        let exec = handle
+            .state::<ExecSessions>()
+            .active()
+            .into_iter()
+            .map(|s| (s.id, SessionKind::Exec, false));
+        let logs = handle
+            .state::<LogSessions>()
+            .active()
+            .into_iter()
+            .map(|s| (s.id, SessionKind::Log, false));
+        let tails = handle
+            .state::<LogTails>()
+            .active()
+            .into_iter()
+            .map(|id| (id, SessionKind::Log, false));
+        let forwards = handle
+            .state::<PortForwardManager>()
+            .active()
+            .into_iter()
+            .map(|f| (f.id, SessionKind::PortForward, f.active_connections > 0));
+        exec.chain(logs).chain(tails).chain(forwards).collect()
+    }
+
+    fn limit_for<'a>(limits: &'a SessionLimits, kind: &SessionKind) -> &'a SessionLimit {
+        match kind {
+            SessionKind::Exec => &limits.exec,
+            SessionKind::Log => &limits.logs,
+            SessionKind::PortForward => &limits.port_forwards,
+        }
+    }
+
+    /// When the nearest limit closes the session.
+    fn deadline(limit: &SessionLimit, tracked: &Tracked) -> Option<(Instant, LimitReason)> {
+        let minutes = |m: u32| Duration::from_secs(m as u64 * 60);
+        let idle = limit
+            .idle_minutes
+            .map(|m| (tracked.last_active + minutes(m), LimitReason::Idle));
+        let lifetime = limit
+            .max_minutes
+            .map(|m| (tracked.started + minutes(m), LimitReason::Lifetime));
+        [idle, lifetime]
+            .into_iter()
+            .flatten()
+            .min_by_key(|(at, _)| *at)
+    }
+
+    /// Starts tracking new sessions and forgets ones that ended.
+    fn sync(handle: &AppHandle) {
+        let running = running(handle);
+        let reaper = handle.state::<SessionReaper>();
+        let mut tracker = reaper.tracker_mutable();
+        let now = Instant::now();
+        tracker
+            .sessions
+            .retain(|id, _| running.iter().any(|(r, _, _)| r == id));
+        for (id, kind, in_use) in running {
+            let tracked = tracker.sessions.entry(id).or_insert(Tracked {
+                kind,
+                started: now,
+                last_active: now,
+                warned: false,
+            });
+            if in_use {
+                tracked.last_active = now;
+                tracked.warned = false;
+            }
+        }
+    }
+
+    pub fn deadlines(handle: &AppHandle) -> Vec<SessionDeadline> {
+        sync(handle);
+        let limits = handle.state::<AppState>().get_settings().session_limits;
+        let reaper = handle.state::<SessionReaper>();
+        let tracker = reaper.tracker_mutable();
+        let now = Instant::now();
+        let mut deadlines: Vec<SessionDeadline> = tracker
+            .sessions
+            .iter()
+            .map(|(id, tracked)| {
+                let next = deadline(limit_for(&limits, &tracked.kind), tracked);
+                SessionDeadline {
+                    id: id.clone(),
+                    kind: tracked.kind.clone(),
+                    idle_secs: now.duration_since(tracked.last_active).as_secs(),
+                    remaining_secs: next
+                        .as_ref()
+                        .map(|(at, _)| at.saturating_duration_since(now).as_secs()),
+                    reason: next.map(|(_, reason)| reason),
+                }
+            })
+            .collect();
+        deadlines.sort_by(|a, b| a.id.cmp(&b.id));
+        deadlines
+    }
+
+    fn terminate(handle: &AppHandle, id: &str, kind: &SessionKind) -> bool {
+        match kind {
+            SessionKind::Exec => exec_sessions::stop(handle, id),
+            SessionKind::Log => {
+                log_sessions::stop(handle, id) || multi_cluster_logs::stop(handle, id)
+            }
+            SessionKind::PortForward => port_forwards::stop(handle, id),
+        }
+    }
+
+    /// Warns about sessions within `warning_seconds` of a limit, once, and
+    /// closes the ones past it.
+    pub fn sweep(handle: &AppHandle) {
+        sync(handle);
+        let limits = handle.state::<AppState>().get_settings().session_limits;
+        let warning = Duration::from_secs(limits.warning_seconds as u64);
+        let now = Instant::now();
+        let mut events: Vec<SessionLimitEvent> = Vec::new();
+        {
+            let reaper = handle.state::<SessionReaper>();
+            let mut tracker = reaper.tracker_mutable();
+            for (id, tracked) in tracker.sessions.iter_mut() {
+                let Some((at, reason)) = deadline(limit_for(&limits, &tracked.kind), tracked)
+                else {
+                    continue;
+                };
+                let remaining = at.saturating_duration_since(now);
+                let terminated = remaining.is_zero();
+                if !terminated && (remaining > warning || tracked.warned) {
+                    continue;
+                }
+                tracked.warned = true;
+                events.push(SessionLimitEvent {
+                    id: id.clone(),
+                    kind: tracked.kind.clone(),
+                    reason,
+                    remaining_secs: remaining.as_secs(),
+                    terminated,
+                });
+            }
+        }
+        for event in events {
+            if event.terminated {
+                terminate(handle, event.id.as_str(), &event.kind);
+                handle
+                    .state::<SessionReaper>()
+                    .tracker_mutable()
+                    .sessions
+                    .remove(&event.id);
+            }
+            let _ = handle.emit(SESSION_LIMIT_EVENT, event);
+        }
+    }
+
+    /// Closes every exec session, log follow and port forward, returning how
+    /// many were closed. Run on exit so no connection outlives the app.
+    pub fn stop_all(handle: &AppHandle) -> usize {
+        running(handle)
+            .into_iter()
+            .filter(|(id, kind, _)| terminate(handle, id.as_str(), kind))
+            .count()
+    }
+
+    /// Starts the background sweep once; later calls do nothing.
+    pub async fn start(handle: AppHandle) {
+        {
+            let reaper = handle.state::<SessionReaper>();
+            let mut tracker = reaper.tracker_mutable();
+            if tracker.started {
+                return;
+            }
+            tracker.started = true;
+        }
+        loop {
+            sweep(&handle);
+            tokio::time::sleep(INTERVAL).await;
+        }
+    }
+}
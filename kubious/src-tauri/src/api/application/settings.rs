@@ -0,0 +1,307 @@
+pub mod app_settings {
+    use serde::{Deserialize, Serialize};
+
+    /// A set of field paths the diff/drift engines should not report.
+    /// `cluster` and `kind` narrow where the rule applies; `None` matches all.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DiffIgnoreRule {
+        pub id: String,
+        pub cluster: Option<String>,
+        pub kind: Option<String>,
+        pub paths: Vec<String>,
+        pub description: Option<String>,
+    }
+
+    impl DiffIgnoreRule {
+        pub fn applies_to(&self, cluster: &str, kind: &str) -> bool {
+            self.cluster.as_ref().map(|c| c == cluster).unwrap_or(true)
+                && self.kind.as_ref().map(|k| k == kind).unwrap_or(true)
+        }
+    }
+
+    fn default_diff_ignore_rules() -> Vec<DiffIgnoreRule> {
+        vec![DiffIgnoreRule {
+            id: "builtin-server-fields".to_string(),
+            cluster: None,
+            kind: None,
+            paths: vec![
+                "status".to_string(),
+                "metadata.managedFields".to_string(),
+                "metadata.resourceVersion".to_string(),
+                "metadata.generation".to_string(),
+                "metadata.uid".to_string(),
+                "metadata.creationTimestamp".to_string(),
+                "metadata.annotations[kubectl.kubernetes.io/last-applied-configuration]"
+                    .to_string(),
+            ],
+            description: Some("Fields populated by the API server".to_string()),
+        }]
+    }
+
+    /// A container registry login. The password is kept in the OS keyring
+    /// and `secret` only holds the reference to it.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RegistryLogin {
+        pub registry: String,
+        pub username: String,
+        pub secret: String,
+        pub description: Option<String>,
+    }
+
+    /// A pinned object whose status is watched for sidebar badges.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct Favorite {
+        pub id: String,
+        pub cluster: String,
+        pub group: String,
+        pub version: String,
+        pub kind: String,
+        pub namespace: Option<String>,
+        pub name: String,
+    }
+
+    /// What a rollout trigger does when a ConfigMap or Secret its workload
+    /// references changes.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    pub enum RolloutTriggerAction {
+        Notify,
+        Restart,
+    }
+
+    /// A Deployment, StatefulSet or DaemonSet whose referenced ConfigMaps and
+    /// Secrets are watched for changes.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct RolloutTrigger {
+        pub id: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub kind: String,
+        pub name: String,
+        pub action: RolloutTriggerAction,
+    }
+
+    /// A rollout restart done automatically by a trigger. `sources` are the
+    /// changed objects as `Kind/name`.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RolloutAuditEntry {
+        pub trigger_id: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub kind: String,
+        pub name: String,
+        pub sources: Vec<String>,
+        pub at: String,
+        pub error: Option<String>,
+    }
+
+    /// A log line saved with a note. The line is identified by its stream,
+    /// its timestamp if the stream had them, and a hash of its content.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct LogBookmark {
+        pub id: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub pod: String,
+        pub container: Option<String>,
+        pub timestamp: Option<String>,
+        pub line_hash: String,
+        pub preview: String,
+        pub note: String,
+        pub created: String,
+    }
+
+    /// Thresholds checked in the background for a namespace. Thresholds
+    /// left unset are not checked.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BudgetAlert {
+        pub id: String,
+        pub cluster: String,
+        pub namespace: String,
+        /// Percentage of any ResourceQuota hard limit in use.
+        pub quota_percent: Option<f64>,
+        /// Container restarts across the namespace in the last hour.
+        pub restarts_per_hour: Option<u32>,
+        /// Live CPU usage of the namespace in cores, from metrics-server.
+        pub cpu_cores: Option<f64>,
+        /// Live memory usage of the namespace in bytes, from metrics-server.
+        pub memory_bytes: Option<f64>,
+    }
+
+    /// A threshold of a budget alert that was crossed. `rule` names the
+    /// check, e.g. `quota/compute/requests.cpu` or `restarts`.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BudgetBreach {
+        pub alert_id: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub rule: String,
+        pub value: f64,
+        pub threshold: f64,
+        pub message: String,
+        pub at: String,
+    }
+
+    /// A workload scaled down by a namespace hibernation. `replicas` is set
+    /// for Deployments and StatefulSets, `suspended` for CronJobs.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct HibernatedWorkload {
+        pub kind: String,
+        pub name: String,
+        pub replicas: Option<i32>,
+        pub suspended: Option<bool>,
+    }
+
+    /// The state needed to wake a hibernated namespace back up.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct Hibernation {
+        pub cluster: String,
+        pub namespace: String,
+        pub hibernated_at: String,
+        pub workloads: Vec<HibernatedWorkload>,
+    }
+
+    /// A throwaway namespace created by the app, deleted once `expires`
+    /// (RFC 3339) has passed.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ScratchNamespace {
+        pub cluster: String,
+        pub namespace: String,
+        pub created: String,
+        pub expires: String,
+    }
+
+    /// Limits for one kind of long-lived session, in minutes. `None` turns
+    /// the limit off.
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    pub struct SessionLimit {
+        /// Closes the session after this long without use.
+        pub idle_minutes: Option<u32>,
+        /// Closes the session this long after it started, in use or not.
+        pub max_minutes: Option<u32>,
+    }
+
+    /// Exec sessions are in use while input is sent, port forwards while a
+    /// connection is open, and log follows while the UI touches them.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct SessionLimits {
+        pub exec: SessionLimit,
+        pub logs: SessionLimit,
+        pub port_forwards: SessionLimit,
+        /// How long before closing a session the user is warned.
+        pub warning_seconds: u32,
+    }
+
+    impl Default for SessionLimits {
+        fn default() -> Self {
+            SessionLimits {
+                exec: SessionLimit {
+                    idle_minutes: Some(30),
+                    max_minutes: Some(480),
+                },
+                logs: SessionLimit {
+                    idle_minutes: Some(60),
+                    max_minutes: None,
+                },
+                port_forwards: SessionLimit {
+                    idle_minutes: Some(120),
+                    max_minutes: None,
+                },
+                warning_seconds: 60,
+            }
+        }
+    }
+
+    /// A saved slice of a cluster for teams sharing it. Empty `namespaces` or
+    /// `kinds` leave that dimension unrestricted. At most one filter per
+    /// cluster is active, and it scopes every list and watch on the cluster.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TenancyFilter {
+        pub id: String,
+        pub cluster: String,
+        pub name: String,
+        #[serde(default)]
+        pub namespaces: Vec<String>,
+        pub label_selector: Option<String>,
+        #[serde(default)]
+        pub kinds: Vec<String>,
+        #[serde(default)]
+        pub active: bool,
+    }
+
+    impl TenancyFilter {
+        pub fn allows_kind(&self, kind: &str) -> bool {
+            self.kinds.is_empty() || self.kinds.iter().any(|k| k == kind)
+        }
+
+        pub fn allows_namespace(&self, namespace: &str) -> bool {
+            self.namespaces.is_empty() || self.namespaces.iter().any(|n| n == namespace)
+        }
+
+        /// Combines the filter's label selector with another one; both must
+        /// match.
+        pub fn label_selector(&self, selector: Option<&str>) -> Option<String> {
+            let parts: Vec<&str> = [self.label_selector.as_deref(), selector]
+                .into_iter()
+                .flatten()
+                .filter(|s| !s.trim().is_empty())
+                .collect();
+            (!parts.is_empty()).then(|| parts.join(","))
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct AppSettings {
+        #[serde(default = "default_diff_ignore_rules")]
+        pub diff_ignore_rules: Vec<DiffIgnoreRule>,
+        #[serde(default)]
+        pub registry_logins: Vec<RegistryLogin>,
+        #[serde(default)]
+        pub favorites: Vec<Favorite>,
+        #[serde(default)]
+        pub hibernations: Vec<Hibernation>,
+        #[serde(default)]
+        pub tenancy_filters: Vec<TenancyFilter>,
+        #[serde(default)]
+        pub rollout_triggers: Vec<RolloutTrigger>,
+        #[serde(default)]
+        pub rollout_audit: Vec<RolloutAuditEntry>,
+        #[serde(default)]
+        pub log_bookmarks: Vec<LogBookmark>,
+        #[serde(default)]
+        pub budget_alerts: Vec<BudgetAlert>,
+        #[serde(default)]
+        pub budget_breaches: Vec<BudgetBreach>,
+        /// Ids of manifest lint rules that are switched off.
+        #[serde(default)]
+        pub lint_disabled_rules: Vec<String>,
+        #[serde(default)]
+        pub scratch_namespaces: Vec<ScratchNamespace>,
+        /// Delete expired scratch namespaces without asking first.
+        #[serde(default)]
+        pub scratch_auto_delete: bool,
+        #[serde(default)]
+        pub session_limits: SessionLimits,
+    }
+
+    impl Default for AppSettings {
+        fn default() -> Self {
+            AppSettings {
+                diff_ignore_rules: default_diff_ignore_rules(),
+                registry_logins: Vec::new(),
+                favorites: Vec::new(),
+                hibernations: Vec::new(),
+                tenancy_filters: Vec::new(),
+                rollout_triggers: Vec::new(),
+                rollout_audit: Vec::new(),
+                log_bookmarks: Vec::new(),
+                budget_alerts: Vec::new(),
+                budget_breaches: Vec::new(),
+                lint_disabled_rules: Vec::new(),
+                scratch_namespaces: Vec::new(),
+                scratch_auto_delete: false,
+                session_limits: SessionLimits::default(),
+            }
+        }
+    }
+}
@@ -1,53 +1,125 @@
 pub mod app_state {
     use kube::{
+        client::ClientBuilder,
         config::{KubeConfigOptions, Kubeconfig},
         Client, Config,
     };
+    use http::{HeaderName, HeaderValue};
     use serde::{Deserialize, Serialize};
     use std::{
         collections::HashMap,
-        fs::File,
-        io::Write,
-        sync::{Mutex, MutexGuard}, time::Duration,
+        fmt,
+        fs,
+        path::PathBuf,
+        str::FromStr,
+        sync::{Mutex, MutexGuard}, time::{Duration, Instant},
     };
     use tauri::{AppHandle, Manager};
 
-    use crate::compat::kube_compat::KubeConfig;
+    use crate::{
+        compat::kube_compat::{resolve_kubeconfig_paths, resolve_path, KeepAliveSettings, KubeConfig},
+        util::{
+            api_metrics::MetricsLayer,
+            api_recorder::RecorderLayer,
+            api_warnings::WarningLayer,
+            atomic_file,
+            connection_tuning,
+            kubious_error::KubiousError,
+            lock_recovery::recover,
+            registry_client::{normalize_registry_key, RegistryCredential},
+        },
+    };
+
+    use crate::api::{
+        app_support::{LogLevel, SupportLog},
+        app_credentials::{
+            forget_reference, forget_secrets, has_plaintext_secrets, is_reference,
+            resolve_reference, resolve_secrets, store_registry_secret, store_secrets,
+        },
+        app_settings::{
+            AppSettings, BudgetAlert, BudgetBreach, DiffIgnoreRule, Favorite, Hibernation, LogBookmark, RegistryLogin, RolloutAuditEntry,
+            RolloutTrigger, ScratchNamespace, SessionLimits, TenancyFilter,
+        },
+        state_migrations::{migrate, STATE_VERSION},
+    };
+
+    /// Automatic rollout restarts kept in the audit log.
+    const MAX_ROLLOUT_AUDIT: usize = 500;
+
+    /// Budget breaches kept in the history.
+    const MAX_BUDGET_BREACHES: usize = 500;
+
+    /// Minimum time between two writes of `config.json`.
+    const SAVE_INTERVAL: Duration = Duration::from_millis(500);
+
+    /// When `config.json` was last written and whether a coalesced write is
+    /// waiting for `SAVE_INTERVAL` to pass.
+    #[derive(Default, Debug)]
+    struct SaveSchedule {
+        last_write: Option<Instant>,
+        pending: bool,
+    }
+
+    /// Clients built from stored configs, keyed by config name. Building a
+    /// client redoes the TLS setup, so one is reused until its config
+    /// changes or is removed.
+    #[derive(Default)]
+    struct ClientCache(Mutex<HashMap<String, Client>>);
+
+    impl fmt::Debug for ClientCache {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("ClientCache")
+        }
+    }
 
     #[derive(Serialize, Deserialize, Debug)]
     pub struct AppState {
+        #[serde(default)]
+        version: u32,
         configs: Mutex<HashMap<String, KubeConfig>>,
         current_config: Mutex<Option<String>>,
+        #[serde(default)]
+        settings: Mutex<AppSettings>,
+        #[serde(skip)]
+        clients: ClientCache,
+        #[serde(skip)]
+        saves: Mutex<SaveSchedule>,
     }
 
     impl AppState {
-        fn configs_mutable(&self) -> MutexGuard<HashMap<String, KubeConfig>> {
-            if let Ok(locked) = self.configs.lock() {
-                locked
-            } else {
-                panic!("Failed to lock state.configs!");
-            }
+        fn configs_mutable(&self) -> MutexGuard<'_, HashMap<String, KubeConfig>> {
+            recover(&self.configs)
         }
 
-        fn current_config_mutable(&self) -> MutexGuard<Option<String>> {
-            if let Ok(locked) = self.current_config.lock() {
-                locked
-            } else {
-                panic!("Failed to lock state.current_config!");
-            }
+        fn current_config_mutable(&self) -> MutexGuard<'_, Option<String>> {
+            recover(&self.current_config)
+        }
+
+        fn settings_mutable(&self) -> MutexGuard<'_, AppSettings> {
+            recover(&self.settings)
+        }
+
+        fn clients_mutable(&self) -> MutexGuard<'_, HashMap<String, Client>> {
+            recover(&self.clients.0)
+        }
+
+        /// Drops the cached client of a config so the next request builds a
+        /// new one from the stored config.
+        pub fn invalidate_client(&self, key: &str) {
+            self.clients_mutable().remove(key);
         }
 
         pub fn set_current_config(
             &self,
             value: Option<String>,
-        ) -> Result<Option<KubeConfig>, String> {
+        ) -> Result<Option<KubeConfig>, KubiousError> {
             let mut current = self.current_config_mutable();
             if let Some(name) = value {
                 if let Some(c) = self.configs_mutable().get(name.as_str()) {
                     *current = Some(name);
                     Ok(Some(c.clone()))
                 } else {
-                    Err("Unknown config name".to_string())
+                    Err(KubiousError::config("Unknown config name"))
                 }
             } else {
                 *current = None;
@@ -75,36 +147,109 @@ pub mod app_state {
 
         pub fn put_config(&self, key: &str, config: Config) -> KubeConfig {
             let mut configs = self.configs_mutable();
-            let converted = KubeConfig::from(config);
+            let mut converted = KubeConfig::from(config);
+            if let Some(existing) = configs.get(key) {
+                converted.keep_alive = existing.keep_alive.clone();
+            }
+            let converted = store_secrets(key, converted);
             (*configs).insert(key.to_string(), converted.clone());
+            self.invalidate_client(key);
             converted.clone()
         }
 
         pub fn put_compat_config(&self, key: &str, config: KubeConfig) -> KubeConfig {
             let mut configs = self.configs_mutable();
-            (*configs).insert(key.to_string(), config.clone());
-            config.clone()
+            let secured = store_secrets(key, config);
+            (*configs).insert(key.to_string(), secured.clone());
+            self.invalidate_client(key);
+            secured
         }
 
-        pub async fn put_kubeconfig(&self, key: &str, config: Kubeconfig) -> Result<KubeConfig, String> {
+        pub async fn put_kubeconfig(&self, key: &str, config: Kubeconfig, source: Option<&str>) -> Result<KubeConfig, KubiousError> {
             let bound = KubeConfigOptions::default();
-            let converted = Config::from_custom_kubeconfig(config, &bound).await;
-            if let Ok(conf) = converted {
-                Ok(self.put_config(key, conf))
-            } else {
-                Err("Kubeconfig parsing failed".to_string())
+            let source = source.map(|s| PathBuf::from(resolve_path(s, None)));
+            let config = resolve_kubeconfig_paths(config, source.as_deref());
+            match Config::from_custom_kubeconfig(config, &bound).await {
+                Ok(conf) => Ok(self.put_config(key, conf)),
+                Err(error) => Err(KubiousError::config(format!("Kubeconfig parsing failed: {}", error))),
             }
         }
 
         pub fn remove_config(&self, key: &str) {
+            // Same lock order as `set_current_config`: the current config
+            // first, then the configs.
+            {
+                let mut current = self.current_config_mutable();
+                if current.as_deref() == Some(key) {
+                    *current = None;
+                }
+            }
+            if let Some(removed) = self.configs_mutable().remove(key) {
+                forget_secrets(&removed);
+            }
+            self.invalidate_client(key);
+        }
+
+        /// Replaces the custom HTTP headers sent with every request for a
+        /// config, e.g. for identity-aware proxies in front of the API server.
+        /// Values are moved into the keyring; values that are already keyring
+        /// references are kept as they are.
+        pub fn set_config_headers(
+            &self,
+            key: &str,
+            headers: Vec<(String, Option<String>)>,
+        ) -> Result<KubeConfig, KubiousError> {
+            for (name, value) in &headers {
+                HeaderName::from_str(name.as_str())
+                    .or(Err(KubiousError::config(format!("Invalid header name {}", name))))?;
+                HeaderValue::from_str(value.clone().unwrap_or_default().as_str())
+                    .or(Err(KubiousError::config(format!("Invalid value for header {}", name))))?;
+            }
+            let mut configs = self.configs_mutable();
+            let Some(existing) = configs.get(key).cloned() else {
+                return Err(KubiousError::config("Unknown config"));
+            };
+            for (_, value) in &existing.headers {
+                if let Some(value) = value {
+                    if !headers.iter().any(|(_, kept)| kept.as_ref() == Some(value)) {
+                        forget_reference(value.as_str());
+                    }
+                }
+            }
+            let mut updated = existing;
+            updated.headers = headers;
+            let secured = store_secrets(key, updated);
+            configs.insert(key.to_string(), secured.clone());
+            self.invalidate_client(key);
+            Ok(secured)
+        }
+
+        pub fn set_config_keep_alive(
+            &self,
+            key: &str,
+            keep_alive: KeepAliveSettings,
+        ) -> Result<KubeConfig, KubiousError> {
+            let mut configs = self.configs_mutable();
+            let Some(existing) = configs.get_mut(key) else {
+                return Err(KubiousError::config("Unknown config"));
+            };
+            existing.keep_alive = keep_alive;
+            self.invalidate_client(key);
+            Ok(existing.clone())
+        }
+
+        /// Moves any plaintext credentials left in stored configs into the
+        /// keyring. Returns whether anything changed and should be saved.
+        pub fn secure_configs(&self) -> bool {
             let mut configs = self.configs_mutable();
-            let current = self.current_config_mutable();
-            if let Some(ck) = current.clone() {
-                if ck == key.to_string() {
-                    let _ = self.set_current_config(None);
+            let mut changed = false;
+            for (key, config) in configs.iter_mut() {
+                if has_plaintext_secrets(config) {
+                    *config = store_secrets(key, config.clone());
+                    changed = true;
                 }
             }
-            (*configs).remove(key);
+            changed
         }
 
         pub async fn register_default(&self) -> Option<KubeConfig> {
@@ -116,55 +261,551 @@ pub mod app_state {
             }
         }
 
+        pub fn get_settings(&self) -> AppSettings {
+            self.settings_mutable().clone()
+        }
+
+        pub fn put_diff_ignore_rule(&self, rule: DiffIgnoreRule) {
+            let mut settings = self.settings_mutable();
+            settings.diff_ignore_rules.retain(|r| r.id != rule.id);
+            settings.diff_ignore_rules.push(rule);
+        }
+
+        pub fn remove_diff_ignore_rule(&self, id: &str) -> bool {
+            let mut settings = self.settings_mutable();
+            let before = settings.diff_ignore_rules.len();
+            settings.diff_ignore_rules.retain(|r| r.id != id);
+            settings.diff_ignore_rules.len() != before
+        }
+
+        pub fn diff_ignore_rules_for(&self, cluster: &str, kind: &str) -> Vec<DiffIgnoreRule> {
+            self.settings_mutable()
+                .diff_ignore_rules
+                .iter()
+                .filter(|r| r.applies_to(cluster, kind))
+                .cloned()
+                .collect()
+        }
+
+        pub fn put_favorite(&self, favorite: Favorite) {
+            let mut settings = self.settings_mutable();
+            settings.favorites.retain(|f| f.id != favorite.id);
+            settings.favorites.push(favorite);
+        }
+
+        pub fn remove_favorite(&self, id: &str) -> bool {
+            let mut settings = self.settings_mutable();
+            let before = settings.favorites.len();
+            settings.favorites.retain(|f| f.id != id);
+            settings.favorites.len() != before
+        }
+
+        pub fn put_rollout_trigger(&self, trigger: RolloutTrigger) {
+            let mut settings = self.settings_mutable();
+            settings.rollout_triggers.retain(|t| t.id != trigger.id);
+            settings.rollout_triggers.push(trigger);
+        }
+
+        pub fn remove_rollout_trigger(&self, id: &str) -> bool {
+            let mut settings = self.settings_mutable();
+            let before = settings.rollout_triggers.len();
+            settings.rollout_triggers.retain(|t| t.id != id);
+            settings.rollout_triggers.len() != before
+        }
+
+        /// Appends an audit entry, keeping only the most recent ones.
+        pub fn record_rollout_audit(&self, entry: RolloutAuditEntry) {
+            let mut settings = self.settings_mutable();
+            settings.rollout_audit.push(entry);
+            let excess = settings.rollout_audit.len().saturating_sub(MAX_ROLLOUT_AUDIT);
+            settings.rollout_audit.drain(..excess);
+        }
+
+        pub fn put_budget_alert(&self, alert: BudgetAlert) {
+            let mut settings = self.settings_mutable();
+            settings.budget_alerts.retain(|a| a.id != alert.id);
+            settings.budget_alerts.push(alert);
+        }
+
+        pub fn remove_budget_alert(&self, id: &str) -> bool {
+            let mut settings = self.settings_mutable();
+            let before = settings.budget_alerts.len();
+            settings.budget_alerts.retain(|a| a.id != id);
+            settings.budget_alerts.len() != before
+        }
+
+        /// Appends breaches to the history, keeping only the most recent ones.
+        pub fn record_budget_breaches(&self, breaches: Vec<BudgetBreach>) {
+            let mut settings = self.settings_mutable();
+            settings.budget_breaches.extend(breaches);
+            let excess = settings.budget_breaches.len().saturating_sub(MAX_BUDGET_BREACHES);
+            settings.budget_breaches.drain(..excess);
+        }
+
+        pub fn set_lint_rule_enabled(&self, id: &str, enabled: bool) {
+            let mut settings = self.settings_mutable();
+            settings.lint_disabled_rules.retain(|r| r != id);
+            if !enabled {
+                settings.lint_disabled_rules.push(id.to_string());
+            }
+        }
+
+        pub fn put_log_bookmark(&self, bookmark: LogBookmark) {
+            let mut settings = self.settings_mutable();
+            settings.log_bookmarks.retain(|b| b.id != bookmark.id);
+            settings.log_bookmarks.push(bookmark);
+        }
+
+        pub fn remove_log_bookmark(&self, id: &str) -> bool {
+            let mut settings = self.settings_mutable();
+            let before = settings.log_bookmarks.len();
+            settings.log_bookmarks.retain(|b| b.id != id);
+            settings.log_bookmarks.len() != before
+        }
+
+        /// Bookmarks of a cluster, narrowed to one pod's stream when given,
+        /// oldest first.
+        pub fn log_bookmarks(&self, cluster: &str, namespace: Option<&str>, pod: Option<&str>) -> Vec<LogBookmark> {
+            self.settings_mutable()
+                .log_bookmarks
+                .iter()
+                .filter(|b| {
+                    b.cluster == cluster
+                        && namespace.is_none_or(|n| b.namespace == n)
+                        && pod.is_none_or(|p| b.pod == p)
+                })
+                .cloned()
+                .collect()
+        }
+
+        pub fn hibernation(&self, cluster: &str, namespace: &str) -> Option<Hibernation> {
+            self.settings_mutable()
+                .hibernations
+                .iter()
+                .find(|h| h.cluster == cluster && h.namespace == namespace)
+                .cloned()
+        }
+
+        /// Stores a hibernation, replacing any earlier one for the namespace.
+        /// An empty workload list removes it.
+        pub fn put_hibernation(&self, hibernation: Hibernation) {
+            let mut settings = self.settings_mutable();
+            settings.hibernations.retain(|h| {
+                h.cluster != hibernation.cluster || h.namespace != hibernation.namespace
+            });
+            if !hibernation.workloads.is_empty() {
+                settings.hibernations.push(hibernation);
+            }
+        }
+
+        pub fn scratch_namespace(&self, cluster: &str, namespace: &str) -> Option<ScratchNamespace> {
+            self.settings_mutable()
+                .scratch_namespaces
+                .iter()
+                .find(|s| s.cluster == cluster && s.namespace == namespace)
+                .cloned()
+        }
+
+        /// Stores a scratch namespace, replacing any earlier record of it.
+        pub fn put_scratch_namespace(&self, scratch: ScratchNamespace) {
+            let mut settings = self.settings_mutable();
+            settings
+                .scratch_namespaces
+                .retain(|s| s.cluster != scratch.cluster || s.namespace != scratch.namespace);
+            settings.scratch_namespaces.push(scratch);
+        }
+
+        pub fn remove_scratch_namespace(&self, cluster: &str, namespace: &str) -> bool {
+            let mut settings = self.settings_mutable();
+            let before = settings.scratch_namespaces.len();
+            settings
+                .scratch_namespaces
+                .retain(|s| s.cluster != cluster || s.namespace != namespace);
+            settings.scratch_namespaces.len() != before
+        }
+
+        pub fn set_scratch_auto_delete(&self, enabled: bool) {
+            self.settings_mutable().scratch_auto_delete = enabled;
+        }
+
+        pub fn set_session_limits(&self, limits: SessionLimits) {
+            self.settings_mutable().session_limits = limits;
+        }
+
+        /// Stores a tenancy filter. Activating it deactivates the other
+        /// filters of its cluster.
+        pub fn put_tenancy_filter(&self, filter: TenancyFilter) {
+            let mut settings = self.settings_mutable();
+            settings.tenancy_filters.retain(|f| f.id != filter.id);
+            if filter.active {
+                for other in settings.tenancy_filters.iter_mut().filter(|f| f.cluster == filter.cluster) {
+                    other.active = false;
+                }
+            }
+            settings.tenancy_filters.push(filter);
+        }
+
+        pub fn remove_tenancy_filter(&self, id: &str) -> bool {
+            let mut settings = self.settings_mutable();
+            let before = settings.tenancy_filters.len();
+            settings.tenancy_filters.retain(|f| f.id != id);
+            settings.tenancy_filters.len() != before
+        }
+
+        /// Makes `id` the active filter of a cluster, or clears the active
+        /// filter when `id` is `None`.
+        pub fn activate_tenancy_filter(&self, cluster: &str, id: Option<&str>) -> Result<(), KubiousError> {
+            let mut settings = self.settings_mutable();
+            if let Some(id) = id {
+                if !settings.tenancy_filters.iter().any(|f| f.id == id && f.cluster == cluster) {
+                    return Err(KubiousError::config("Unknown tenancy filter for this cluster"));
+                }
+            }
+            for filter in settings.tenancy_filters.iter_mut().filter(|f| f.cluster == cluster) {
+                filter.active = Some(filter.id.as_str()) == id;
+            }
+            Ok(())
+        }
+
+        pub fn active_tenancy_filter(&self, cluster: &str) -> Option<TenancyFilter> {
+            self.settings_mutable()
+                .tenancy_filters
+                .iter()
+                .find(|f| f.cluster == cluster && f.active)
+                .cloned()
+        }
+
+        /// Adds or replaces the login for a registry, moving the password into
+        /// the keyring.
+        pub fn put_registry_login(
+            &self,
+            registry: &str,
+            username: &str,
+            password: &str,
+            description: Option<String>,
+        ) -> Result<RegistryLogin, KubiousError> {
+            let registry = normalize_registry_key(registry);
+            if registry.is_empty() {
+                return Err(KubiousError::config("Invalid registry"));
+            }
+            let login = RegistryLogin {
+                secret: store_registry_secret(registry.as_str(), password)?,
+                registry,
+                username: username.to_string(),
+                description,
+            };
+            let mut settings = self.settings_mutable();
+            settings.registry_logins.retain(|l| l.registry != login.registry);
+            settings.registry_logins.push(login.clone());
+            Ok(login)
+        }
+
+        pub fn remove_registry_login(&self, registry: &str) -> bool {
+            let registry = normalize_registry_key(registry);
+            let mut settings = self.settings_mutable();
+            let (removed, kept): (Vec<RegistryLogin>, Vec<RegistryLogin>) = settings
+                .registry_logins
+                .drain(..)
+                .partition(|l| l.registry == registry);
+            settings.registry_logins = kept;
+            for login in &removed {
+                forget_reference(login.secret.as_str());
+            }
+            !removed.is_empty()
+        }
+
+        /// The stored credential for the registry serving `registry`, which
+        /// may be a host, URL or image reference prefix.
+        pub fn registry_credential(&self, registry: &str) -> Option<RegistryCredential> {
+            let registry = normalize_registry_key(registry);
+            let login = self
+                .settings_mutable()
+                .registry_logins
+                .iter()
+                .find(|l| l.registry == registry)
+                .cloned()?;
+            Some(RegistryCredential {
+                basic: None,
+                username: Some(login.username),
+                password: Some(resolve_reference(login.secret.as_str()).ok()?),
+            })
+        }
+
+        /// Pulls configs (and, when overwriting, settings) from another state,
+        /// returning how many configs were taken over.
+        /// Takes configs (and with `overwrite`, settings) from another state,
+        /// returning the keys of the configs that were imported.
+        pub fn merge_state(&self, other: AppState, overwrite: bool) -> Vec<String> {
+            let mut configs = self.configs_mutable();
+            let mut imported: Vec<String> = Vec::new();
+            for (key, config) in other.get_configs() {
+                if overwrite || !configs.contains_key(&key) {
+                    self.invalidate_client(key.as_str());
+                    let secured = store_secrets(key.as_str(), config);
+                    imported.push(key.clone());
+                    configs.insert(key, secured);
+                }
+            }
+            if overwrite {
+                let mut settings = other.get_settings();
+                for login in settings.registry_logins.iter_mut() {
+                    if login.secret.is_empty() || is_reference(login.secret.as_str()) {
+                        continue;
+                    }
+                    if let Ok(reference) =
+                        store_registry_secret(login.registry.as_str(), login.secret.as_str())
+                    {
+                        login.secret = reference;
+                    }
+                }
+                *self.settings_mutable() = settings;
+            }
+            imported
+        }
+
         pub fn to_json(&self) -> Result<String, serde_json::Error> {
             serde_json::to_string_pretty(self)
         }
 
-        pub fn from_json(value: &str) -> Result<Self, serde_json::Error> {
-            serde_json::from_str(value)
+        /// Parses a serialized state of any supported version, migrating it to
+        /// the current format first.
+        pub fn from_json(value: &str) -> Result<Self, String> {
+            let parsed: serde_json::Value = serde_json::from_str(value).map_err(|e| e.to_string())?;
+            let (migrated, _) = migrate(parsed)?;
+            serde_json::from_value(migrated).map_err(|e| e.to_string())
+        }
+
+        /// Parses the contents of a state file, returning the state and the
+        /// version it was written with.
+        fn parse_state(contents: &str, display: &str) -> Result<(Self, u32), KubiousError> {
+            let parsed: serde_json::Value = serde_json::from_str(contents)
+                .map_err(|e| KubiousError::config(format!("Failed to parse {}: {}", display, e)))?;
+            let (migrated, from) = migrate(parsed).map_err(KubiousError::config)?;
+            let state = serde_json::from_value(migrated)
+                .map_err(|e| KubiousError::config(format!("Failed to load {}: {}", display, e)))?;
+            Ok((state, from))
+        }
+
+        /// Loads `$APPCONFIG/config.json`, creating it from the inferred
+        /// default config on first run. A file that cannot be read or parsed
+        /// is replaced by `config.json.bak`, the state before the last save.
+        /// Files written by an older version are migrated and saved back,
+        /// keeping the original next to it as `config.v<version>.json.bak`,
+        /// with its credentials swapped for keyring references.
+        pub async fn load_state(handle: AppHandle) -> Result<Self, KubiousError> {
+            let path = Self::state_path(&handle)?;
+            let display = path.display().to_string();
+            if let Some(root) = path.parent() {
+                fs::create_dir_all(root).map_err(|e| KubiousError::io(e, root.display().to_string()))?;
+            }
+            let backup = path.with_extension("json.bak");
+            if !path.exists() && !backup.exists() {
+                let state = AppState::new();
+                state.register_default().await;
+                state.save_state(handle)?;
+                return Ok(state);
+            }
+
+            let loaded = fs::read_to_string(&path)
+                .map_err(|e| KubiousError::io(e, display.clone()))
+                .and_then(|contents| Self::parse_state(contents.as_str(), display.as_str()).map(|parsed| (parsed, contents)));
+            let (state, from, contents, recovered) = match loaded {
+                Ok(((state, from), contents)) => (state, from, contents, false),
+                Err(error) => {
+                    let backup_display = backup.display().to_string();
+                    let Ok(contents) = fs::read_to_string(&backup) else {
+                        return Err(error);
+                    };
+                    let Ok((state, from)) = Self::parse_state(contents.as_str(), backup_display.as_str()) else {
+                        return Err(error);
+                    };
+                    handle.state::<SupportLog>().log(
+                        LogLevel::Warning,
+                        "state",
+                        format!("{} Restored the previous state from {}.", error, backup_display).as_str(),
+                    );
+                    (state, from, contents, true)
+                }
+            };
+
+            let upgraded = from < STATE_VERSION;
+            if state.secure_configs() || upgraded || recovered {
+                if recovered {
+                    // Keep the good backup instead of rolling the broken file over it.
+                    let _ = fs::remove_file(&path);
+                }
+                state.save_state(handle)?;
+            }
+            if upgraded {
+                // Written after the secrets went to the keyring, so the old
+                // file's plaintext credentials do not survive in the backup.
+                let versioned = path.with_file_name(format!("config.v{}.json.bak", from));
+                if let Some(secured) = state.secured_original(contents.as_str()) {
+                    fs::write(&versioned, secured.as_bytes()).map_err(|e| KubiousError::io(e, versioned.display().to_string()))?;
+                }
+            }
+            Ok(state)
+        }
+
+        /// A state file as it was read, with plaintext credentials replaced
+        /// by those of the stored (and secured) config of the same name.
+        /// Configs that are no longer stored are dropped instead, so the
+        /// result is safe to keep on disk as a backup.
+        fn secured_original(&self, contents: &str) -> Option<String> {
+            let mut original: serde_json::Value = serde_json::from_str(contents).ok()?;
+            let configs = original.get_mut("configs")?.as_object_mut()?;
+            let current = self.get_configs();
+            let mut dropped: Vec<String> = Vec::new();
+            for (key, stored) in configs.iter_mut() {
+                // Configs from before a migration may not parse; treat them as plaintext.
+                let plaintext = serde_json::from_value::<KubeConfig>(stored.clone())
+                    .map(|c| has_plaintext_secrets(&c))
+                    .unwrap_or(true);
+                if !plaintext {
+                    continue;
+                }
+                let Some(config) = current.get(key) else {
+                    dropped.push(key.clone());
+                    continue;
+                };
+                let stored = stored.as_object_mut()?;
+                stored.insert("auth_info".to_string(), serde_json::to_value(&config.auth_info).ok()?);
+                stored.insert("headers".to_string(), serde_json::to_value(&config.headers).ok()?);
+            }
+            for key in dropped {
+                configs.remove(&key);
+            }
+            serde_json::to_string_pretty(&original).ok()
         }
 
         pub fn new() -> Self {
             AppState {
+                version: STATE_VERSION,
                 configs: Mutex::new(HashMap::<String, KubeConfig>::new()),
                 current_config: Mutex::new(None),
+                settings: Mutex::new(AppSettings::default()),
+                clients: ClientCache::default(),
+                saves: Mutex::new(SaveSchedule::default()),
             }
         }
 
-        pub async fn client(&self) -> Option<Client> {
-            if let Some(cur) = self.get_current_config() {
-                let mut current = cur.clone();
-                current.1.connect_timeout = Some(Duration::from_secs(10));
-                match Client::try_from(<KubeConfig as Into<Config>>::into(current.1)) {
-                    Ok(cl) => Some(cl),
-                    Err(_) => None,
-                }
-            } else {
-                None
+        fn build_client(key: &str, config: KubeConfig) -> Option<Client> {
+            let keep_alive = config.keep_alive.clone();
+            let config: Config = config.into();
+            if let Some(tuned) = connection_tuning::client_builder(&config, &keep_alive) {
+                return tuned.ok().map(|builder| {
+                    builder
+                        .with_layer(&MetricsLayer::new(key))
+                        .with_layer(&RecorderLayer)
+                        .with_layer(&WarningLayer)
+                        .build()
+                });
             }
+            ClientBuilder::try_from(config)
+                .ok()
+                .map(|builder| {
+                    builder
+                        .with_layer(&MetricsLayer::new(key))
+                        .with_layer(&RecorderLayer)
+                        .with_layer(&WarningLayer)
+                        .build()
+                })
+        }
+
+        /// The cached client of a config, building and caching one first if
+        /// there is none.
+        fn cached_client(&self, key: &str, config: KubeConfig) -> Option<Client> {
+            if let Some(client) = self.clients_mutable().get(key) {
+                return Some(client.clone());
+            }
+            let mut resolved = resolve_secrets(config).ok()?;
+            resolved.connect_timeout = Some(Duration::from_secs(10));
+            let client = Self::build_client(key, resolved)?;
+            self.clients_mutable().insert(key.to_string(), client.clone());
+            Some(client)
+        }
+
+        pub async fn client(&self) -> Option<Client> {
+            let (key, config) = self.get_current_config()?;
+            self.cached_client(key.as_str(), config)
         }
 
         pub async fn client_for(&self, key: &str) -> Option<Client> {
-            if let Some(sel) = (*self.configs_mutable()).get(key) {
-                let mut select = sel.clone();
-                select.connect_timeout = Some(Duration::from_secs(10));
-                match Client::try_from(<KubeConfig as Into<Config>>::into(select.clone())) {
-                    Ok(cl) => Some(cl),
-                    Err(_) => None,
+            let config = self.select_config(key)?;
+            self.cached_client(key, config)
+        }
+
+        fn state_path(handle: &AppHandle) -> Result<PathBuf, KubiousError> {
+            handle
+                .path()
+                .parse("$APPCONFIG/config.json")
+                .or(Err(KubiousError::config("Failed to resolve config path.")))
+        }
+
+        /// Writes the state to a temporary file and renames it over
+        /// `config.json`, so a crash or full disk mid-write never leaves a
+        /// truncated file behind. The replaced file is kept as
+        /// `config.json.bak`, minus any plaintext credentials.
+        fn write_state(&self, handle: &AppHandle) -> Result<(), KubiousError> {
+            let path = Self::state_path(handle)?;
+            let jsonified = self.to_json().or(Err("Failed to serialize state.".to_string()))?;
+            // The replaced file may predate the keyring move, so the backup
+            // is written from a secured copy rather than copied as is.
+            let previous = fs::read_to_string(&path).ok();
+            if let Some(secured) = previous.and_then(|contents| self.secured_original(contents.as_str())) {
+                let backup = path.with_extension("json.bak");
+                fs::write(&backup, secured.as_bytes()).map_err(|e| KubiousError::io(e, backup.display().to_string()))?;
+            }
+            atomic_file::write(&path, jsonified.as_bytes())
+        }
+
+        /// Saves the state, at most once per `SAVE_INTERVAL`. The first save
+        /// of a burst is written immediately and reports its errors; later
+        /// ones are coalesced into a single write at the end of the interval,
+        /// whose errors go to the support log.
+        pub fn save_state(&self, handle: AppHandle) -> Result<(), KubiousError> {
+            let delay = {
+                let mut saves = recover(&self.saves);
+                if saves.pending {
+                    return Ok(());
                 }
-            } else {
-                None
+                match saves.last_write.map(|at| at.elapsed()) {
+                    Some(elapsed) if elapsed < SAVE_INTERVAL => {
+                        saves.pending = true;
+                        SAVE_INTERVAL - elapsed
+                    }
+                    _ => {
+                        saves.last_write = Some(Instant::now());
+                        Duration::ZERO
+                    }
+                }
+            };
+            if delay.is_zero() {
+                return self.write_state(&handle);
             }
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(delay).await;
+                handle.state::<AppState>().flush_state(&handle);
+            });
+            Ok(())
         }
 
-        pub fn save_state(&self, handle: AppHandle) -> Result<(), String> {
-            if let Ok(path) = handle.path().parse("$APPCONFIG/config.json") {
-                let mut config_file = File::create(path).unwrap();
-                let jsonified = self.to_json().unwrap();
-                config_file.write_all(jsonified.as_bytes()).unwrap();
-                Ok(())
-            } else {
-                Err("Failed to write new current config to file.".to_string())
+        /// Writes a coalesced save right away, e.g. when the app exits.
+        pub fn flush_state(&self, handle: &AppHandle) {
+            {
+                let mut saves = recover(&self.saves);
+                if !saves.pending {
+                    return;
+                }
+                saves.pending = false;
+                saves.last_write = Some(Instant::now());
+            }
+            if let Err(error) = self.write_state(handle) {
+                handle.state::<SupportLog>().log(
+                    LogLevel::Error,
+                    "state",
+                    format!("Failed to save state: {}", error).as_str(),
+                );
             }
         }
     }
@@ -0,0 +1,490 @@
+pub mod app_support {
+    use std::{
+        cmp::Reverse,
+        collections::{BTreeMap, VecDeque},
+        fs::File,
+        io::Write,
+        sync::{Mutex, MutexGuard},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use k8s_openapi::api::core::v1::{Namespace, Node, Pod};
+    use kube::{
+        api::{ListParams, ObjectList, PartialObjectMeta},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use tauri::{AppHandle, Manager};
+    use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+    use crate::{
+        api::{app_state::AppState, termination_history::TerminationWatches},
+        util::{clock_skew, kubious_error::KubiousError, lock_recovery::recover},
+    };
+
+    const MAX_LOG_ENTRIES: usize = 500;
+    const MAX_FAILURES: usize = 100;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum LogLevel {
+        Info,
+        Warning,
+        Error,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct LogEntry {
+        pub timestamp: u64,
+        pub level: LogLevel,
+        pub source: String,
+        pub message: String,
+    }
+
+    /// A failed API command. Only the scope and command name are kept, since
+    /// arguments can carry credentials.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CommandFailure {
+        pub timestamp: u64,
+        pub scope: String,
+        pub command: String,
+        pub error: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct SupportFile {
+        pub path: String,
+        pub size: usize,
+        pub content: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct SupportBundle {
+        pub path: Option<String>,
+        pub written: bool,
+        pub files: Vec<SupportFile>,
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    /// In-memory backend log and recent command failures, kept bounded so
+    /// they can be attached to support bundles.
+    #[derive(Default)]
+    pub struct SupportLog {
+        entries: Mutex<VecDeque<LogEntry>>,
+        failures: Mutex<VecDeque<CommandFailure>>,
+    }
+
+    impl SupportLog {
+        fn entries_mutable(&self) -> MutexGuard<'_, VecDeque<LogEntry>> {
+            recover(&self.entries)
+        }
+
+        fn failures_mutable(&self) -> MutexGuard<'_, VecDeque<CommandFailure>> {
+            recover(&self.failures)
+        }
+
+        pub fn log(&self, level: LogLevel, source: &str, message: &str) {
+            let mut entries = self.entries_mutable();
+            entries.push_back(LogEntry {
+                timestamp: now(),
+                level,
+                source: source.to_string(),
+                message: message.to_string(),
+            });
+            while entries.len() > MAX_LOG_ENTRIES {
+                entries.pop_front();
+            }
+        }
+
+        /// Records a failed command from its serialized form.
+        pub fn record_failure(&self, command: &Value, error: &str) {
+            let scope = command["scope"].as_str().unwrap_or_default().to_string();
+            let name = command["command"].as_str().unwrap_or_default().to_string();
+            self.log(
+                LogLevel::Error,
+                format!("command/{}", scope).as_str(),
+                format!("{} failed: {}", name, error).as_str(),
+            );
+            let mut failures = self.failures_mutable();
+            failures.push_back(CommandFailure {
+                timestamp: now(),
+                scope,
+                command: name,
+                error: error.to_string(),
+            });
+            while failures.len() > MAX_FAILURES {
+                failures.pop_front();
+            }
+        }
+
+        pub fn entries(&self) -> Vec<LogEntry> {
+            self.entries_mutable().iter().cloned().collect()
+        }
+
+        pub fn failures(&self) -> Vec<CommandFailure> {
+            self.failures_mutable().iter().cloned().collect()
+        }
+    }
+
+    /// Namespaces every cluster has, left readable in the bundle.
+    const BUILTIN_NAMESPACES: [&str; 4] =
+        ["default", "kube-system", "kube-public", "kube-node-lease"];
+
+    /// Maps config keys and cluster URLs to stable `cluster-N` aliases, and
+    /// namespace, pod, node and user names to `namespace-N`, `pod-N`,
+    /// `node-N` and `user-N`. Names are only known for clusters that could be
+    /// listed when the bundle is generated.
+    struct Anonymizer {
+        aliases: BTreeMap<String, String>,
+        /// Every name given an alias so far, whatever its kind.
+        names: BTreeMap<String, String>,
+        counts: BTreeMap<&'static str, usize>,
+        replacements: Vec<(String, String)>,
+    }
+
+    impl Anonymizer {
+        fn new(state: &AppState) -> Self {
+            let configs = state.get_configs();
+            let urls: BTreeMap<String, String> = configs
+                .iter()
+                .map(|(key, config)| (key.clone(), config.cluster_url.clone()))
+                .collect();
+            let mut aliases: BTreeMap<String, String> = BTreeMap::new();
+            let mut replacements: Vec<(String, String)> = Vec::new();
+            for (index, (key, url)) in urls.into_iter().enumerate() {
+                let alias = format!("cluster-{}", index + 1);
+                replacements.push((url.trim_end_matches('/').to_string(), alias.clone()));
+                replacements.push((key.clone(), alias.clone()));
+                aliases.insert(key, alias);
+            }
+            let mut anonymizer = Anonymizer {
+                aliases,
+                names: BTreeMap::new(),
+                counts: BTreeMap::new(),
+                replacements,
+            };
+            for config in configs.values() {
+                let auth = &config.auth_info;
+                for user in [&auth.username, &auth.impersonate].into_iter().flatten() {
+                    anonymizer.name("user", user);
+                }
+                anonymizer.name("namespace", config.default_namespace.as_str());
+            }
+            anonymizer.sort();
+            anonymizer
+        }
+
+        fn name(&mut self, kind: &'static str, name: &str) {
+            if name.is_empty()
+                || self.names.contains_key(name)
+                || (kind == "namespace" && BUILTIN_NAMESPACES.contains(&name))
+            {
+                return;
+            }
+            let count = self.counts.entry(kind).or_default();
+            *count += 1;
+            let alias = format!("{}-{}", kind, count);
+            self.names.insert(name.to_string(), alias.clone());
+            self.replacements.push((name.to_string(), alias));
+        }
+
+        /// Adds the namespace, node and pod names of a cluster. Lists that
+        /// fail are skipped.
+        async fn add_cluster(&mut self, client: &Client) {
+            let params = ListParams::default();
+            if let Ok(list) = Api::<Namespace>::all(client.clone())
+                .list_metadata(&params)
+                .await
+            {
+                for name in object_names(list) {
+                    self.name("namespace", name.as_str());
+                }
+            }
+            if let Ok(list) = Api::<Node>::all(client.clone())
+                .list_metadata(&params)
+                .await
+            {
+                for name in object_names(list) {
+                    self.name("node", name.as_str());
+                }
+            }
+            if let Ok(list) = Api::<Pod>::all(client.clone()).list_metadata(&params).await {
+                for name in object_names(list) {
+                    self.name("pod", name.as_str());
+                }
+            }
+            self.sort();
+        }
+
+        /// Longer values first, so a name that is a prefix of another name
+        /// or URL does not break the longer replacement.
+        fn sort(&mut self) {
+            self.replacements.sort_by_key(|r| Reverse(r.0.len()));
+        }
+
+        fn alias(&self, key: &str) -> String {
+            self.aliases
+                .get(key)
+                .cloned()
+                .unwrap_or("cluster-unknown".to_string())
+        }
+
+        fn scrub(&self, text: &str) -> String {
+            let mut scrubbed = text.to_string();
+            for (value, alias) in &self.replacements {
+                if !value.is_empty() {
+                    scrubbed = replace_whole(scrubbed.as_str(), value.as_str(), alias.as_str());
+                }
+            }
+            scrubbed
+        }
+    }
+
+    fn object_names<K: Clone>(list: ObjectList<PartialObjectMeta<K>>) -> Vec<String> {
+        list.items
+            .into_iter()
+            .filter_map(|item| item.metadata.name)
+            .collect()
+    }
+
+    /// Replaces `value` only where it is not part of a longer name, so a
+    /// namespace called `app` leaves `application` alone.
+    fn replace_whole(text: &str, value: &str, alias: &str) -> String {
+        let part_of_name =
+            |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        let mut scrubbed = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(index) = rest.find(value) {
+            scrubbed.push_str(&rest[..index]);
+            rest = &rest[index + value.len()..];
+            if part_of_name(scrubbed.chars().next_back()) || part_of_name(rest.chars().next()) {
+                scrubbed.push_str(value);
+            } else {
+                scrubbed.push_str(alias);
+            }
+        }
+        scrubbed.push_str(rest);
+        scrubbed
+    }
+
+    fn auth_method(auth: &Value) -> &'static str {
+        if !auth["exec"].is_null() {
+            "exec"
+        } else if !auth["auth-provider"].is_null() {
+            "auth-provider"
+        } else if !auth["token"].is_null() || !auth["tokenFile"].is_null() {
+            "token"
+        } else if !auth["client-certificate-data"].is_null()
+            || !auth["client-certificate"].is_null()
+        {
+            "client-certificate"
+        } else if !auth["username"].is_null() {
+            "basic"
+        } else {
+            "none"
+        }
+    }
+
+    fn state_summary(state: &AppState, anonymizer: &Anonymizer) -> Value {
+        let current = state.get_current_config().map(|(key, _)| key);
+        let mut configs: Vec<Value> = state
+            .get_configs()
+            .into_iter()
+            .map(|(key, config)| {
+                let auth = serde_json::to_value(&config.auth_info).unwrap_or(Value::Null);
+                json!({
+                    "alias": anonymizer.alias(key.as_str()),
+                    "current": current.as_ref() == Some(&key),
+                    "auth_method": auth_method(&auth),
+                    "custom_ca": config.root_cert.is_some(),
+                    "accept_invalid_certs": config.accept_invalid_certs,
+                    "proxy": config.proxy_url.is_some(),
+                    "custom_headers": config.headers.len(),
+                })
+            })
+            .collect();
+        configs.sort_by(|a, b| a["alias"].as_str().cmp(&b["alias"].as_str()));
+        json!({
+            "configs": configs,
+            "diff_ignore_rules": state.get_settings().diff_ignore_rules.len(),
+        })
+    }
+
+    async fn cluster_diagnostics(handle: &AppHandle, key: &str, anonymizer: &Anonymizer) -> Value {
+        let alias = anonymizer.alias(key);
+        let watching = handle
+            .state::<TerminationWatches>()
+            .active()
+            .contains(&key.to_string());
+        let Some(client) = handle.state::<AppState>().client_for(key).await else {
+            return json!({"alias": alias, "connected": false, "error": "Could not build client"});
+        };
+        let version = match client.apiserver_version().await {
+            Ok(version) => version,
+            Err(error) => {
+                return json!({
+                    "alias": alias,
+                    "connected": false,
+                    "error": anonymizer.scrub(error.to_string().as_str()),
+                })
+            }
+        };
+        let skew = clock_skew::measure(&client).await;
+        let nodes = Api::<Node>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .ok()
+            .map(|nodes| {
+                let ready = nodes
+                    .items
+                    .iter()
+                    .filter(|n| {
+                        n.status
+                            .as_ref()
+                            .and_then(|s| s.conditions.as_ref())
+                            .map(|c| c.iter().any(|c| c.type_ == "Ready" && c.status == "True"))
+                            .unwrap_or(false)
+                    })
+                    .count();
+                json!({"total": nodes.items.len(), "ready": ready})
+            });
+        let namespaces = Api::<Namespace>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .ok()
+            .map(|namespaces| namespaces.items.len());
+        json!({
+            "alias": alias,
+            "connected": true,
+            "version": version.git_version,
+            "platform": version.platform,
+            "clock_skew": skew,
+            "nodes": nodes,
+            "namespaces": namespaces,
+            "termination_log_active": watching,
+        })
+    }
+
+    fn text_file(path: &str, content: String) -> SupportFile {
+        SupportFile {
+            path: path.to_string(),
+            size: content.len(),
+            content,
+        }
+    }
+
+    fn json_file(path: &str, value: &Value) -> SupportFile {
+        text_file(
+            path,
+            serde_json::to_string_pretty(value).unwrap_or_default(),
+        )
+    }
+
+    /// Collects the backend log, an anonymized state summary, recent command
+    /// failures and diagnostics for the selected clusters. Cluster, namespace,
+    /// pod, node and user names in all of them are replaced by aliases. With
+    /// `preview`, the files are only returned; otherwise they are also zipped
+    /// to `path`.
+    pub async fn generate(
+        handle: &AppHandle,
+        path: Option<String>,
+        clusters: &[String],
+        preview: bool,
+    ) -> Result<SupportBundle, KubiousError> {
+        if !preview && path.is_none() {
+            return Err("A path is required to write the support bundle".into());
+        }
+        let state = handle.state::<AppState>();
+        let log = handle.state::<SupportLog>();
+        let mut anonymizer = Anonymizer::new(&state);
+        for cluster in clusters {
+            if let Some(client) = state.client_for(cluster.as_str()).await {
+                anonymizer.add_cluster(&client).await;
+            }
+        }
+
+        let log_text: String = log
+            .entries()
+            .iter()
+            .map(|e| {
+                format!(
+                    "{} {:?} [{}] {}\n",
+                    e.timestamp,
+                    e.level,
+                    anonymizer.scrub(e.source.as_str()),
+                    anonymizer.scrub(e.message.as_str())
+                )
+            })
+            .collect();
+        let failures: Vec<CommandFailure> = log
+            .failures()
+            .into_iter()
+            .map(|mut f| {
+                f.error = anonymizer.scrub(f.error.as_str());
+                f
+            })
+            .collect();
+
+        let mut files: Vec<SupportFile> = vec![
+            json_file(
+                "manifest.json",
+                &json!({
+                    "created": now(),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "os": std::env::consts::OS,
+                    "arch": std::env::consts::ARCH,
+                    "clusters": clusters.iter().map(|c| anonymizer.alias(c)).collect::<Vec<String>>(),
+                }),
+            ),
+            text_file("logs/backend.log", log_text),
+            json_file("state-summary.json", &state_summary(&state, &anonymizer)),
+            json_file(
+                "failures.json",
+                &serde_json::to_value(&failures).unwrap_or(Value::Null),
+            ),
+        ];
+        for cluster in clusters {
+            let diagnostics = cluster_diagnostics(handle, cluster.as_str(), &anonymizer).await;
+            files.push(json_file(
+                format!("clusters/{}.json", anonymizer.alias(cluster)).as_str(),
+                &diagnostics,
+            ));
+        }
+
+        if preview {
+            return Ok(SupportBundle {
+                path,
+                written: false,
+                files,
+            });
+        }
+
+        let target = path.clone().unwrap_or_default();
+        let file = File::create(target.as_str())
+            .or(Err("Failed to create support bundle file.".to_string()))?;
+        let mut writer = ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let write_error = || "Failed to write support bundle.".to_string();
+        for entry in &files {
+            writer
+                .start_file(entry.path.as_str(), options)
+                .or(Err(write_error()))?;
+            writer
+                .write_all(entry.content.as_bytes())
+                .or(Err(write_error()))?;
+        }
+        writer.finish().or(Err(write_error()))?;
+
+        Ok(SupportBundle {
+            path,
+            written: true,
+            files,
+        })
+    }
+}
@@ -0,0 +1,170 @@
+pub mod app_terminal {
+    use std::{
+        fs::{self, OpenOptions},
+        io::Write,
+        path::PathBuf,
+    };
+
+    use k8s_openapi::ByteString;
+    use rand::RngCore;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use tauri::{AppHandle, Manager};
+
+    use crate::{compat::kube_compat::KubeConfig, util::kubious_error::KubiousError};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TerminalContext {
+        pub path: String,
+        pub context: String,
+        pub namespace: String,
+        /// `export KUBECONFIG=...` for POSIX shells.
+        pub shell: String,
+        pub powershell: String,
+        pub cmd: String,
+    }
+
+    fn base64(bytes: &[u8]) -> String {
+        serde_json::to_value(ByteString(bytes.to_vec()))
+            .ok()
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_default()
+    }
+
+    /// Re-encodes the DER root certificates kept by `Config` as the base64
+    /// PEM bundle kubeconfig files expect.
+    fn certificate_authority_data(certs: &[Vec<u8>]) -> String {
+        let mut pem = String::new();
+        for cert in certs {
+            let encoded = base64(cert);
+            pem.push_str("-----BEGIN CERTIFICATE-----\n");
+            for line in encoded.as_bytes().chunks(64) {
+                pem.push_str(String::from_utf8_lossy(line).as_ref());
+                pem.push('\n');
+            }
+            pem.push_str("-----END CERTIFICATE-----\n");
+        }
+        base64(pem.as_bytes())
+    }
+
+    fn kubeconfig(name: &str, config: &KubeConfig, namespace: &str) -> Result<Value, KubiousError> {
+        let mut cluster = json!({ "server": config.cluster_url });
+        if let Some(certs) = config.root_cert.as_ref().filter(|c| !c.is_empty()) {
+            cluster["certificate-authority-data"] = json!(certificate_authority_data(certs));
+        }
+        if config.accept_invalid_certs {
+            cluster["insecure-skip-tls-verify"] = json!(true);
+        }
+        if let Some(proxy) = &config.proxy_url {
+            cluster["proxy-url"] = json!(proxy);
+        }
+        if let Some(server_name) = &config.tls_server_name {
+            cluster["tls-server-name"] = json!(server_name);
+        }
+        let user = serde_json::to_value(&config.auth_info)
+            .or(Err("Failed to serialize credentials.".to_string()))?;
+        Ok(json!({
+            "apiVersion": "v1",
+            "kind": "Config",
+            "clusters": [{ "name": name, "cluster": cluster }],
+            "users": [{ "name": name, "user": user }],
+            "contexts": [{
+                "name": name,
+                "context": { "cluster": name, "user": name, "namespace": namespace }
+            }],
+            "current-context": name,
+        }))
+    }
+
+    const CONTEXT_DIR: &str = "contexts";
+
+    /// The app-private cache directory holding exported kubeconfigs,
+    /// readable only by the current user.
+    fn context_dir(handle: &AppHandle) -> Result<PathBuf, KubiousError> {
+        let root = handle
+            .path()
+            .app_cache_dir()
+            .or(Err("Failed to resolve app cache directory.".to_string()))?
+            .join(CONTEXT_DIR);
+        fs::create_dir_all(&root).or(Err("Failed to create context directory.".to_string()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&root, fs::Permissions::from_mode(0o700))
+                .or(Err("Failed to secure context directory.".to_string()))?;
+        }
+        Ok(root)
+    }
+
+    /// Writes a single-context kubeconfig for `key` to a new, randomly named
+    /// file in the app cache, readable only by the current user. `config`
+    /// must already have its keyring references resolved. The file holds
+    /// credentials, so callers remove it with `remove_context` once the
+    /// process using it exits; exports for the user's own terminal live
+    /// until Kubious exits.
+    pub fn export_context(
+        handle: &AppHandle,
+        key: &str,
+        config: &KubeConfig,
+        namespace: Option<String>,
+    ) -> Result<TerminalContext, KubiousError> {
+        let namespace = namespace.unwrap_or(config.default_namespace.clone());
+        let context = format!("kubious-{}", key);
+        // JSON is valid YAML, so kubectl reads this without conversion.
+        let contents = serde_json::to_string_pretty(&kubeconfig(
+            context.as_str(),
+            config,
+            namespace.as_str(),
+        )?)
+        .or(Err("Failed to serialize kubeconfig.".to_string()))?;
+        let file: String = key
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let mut suffix = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut suffix);
+        let suffix: String = suffix.iter().map(|b| format!("{:02x}", b)).collect();
+        let path = context_dir(handle)?.join(format!("{}-{}.kubeconfig", file, suffix));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(&path)
+            .and_then(|mut f| f.write_all(contents.as_bytes()))
+            .or(Err("Failed to write kubeconfig.".to_string()))?;
+
+        let path = path.to_string_lossy().to_string();
+        Ok(TerminalContext {
+            shell: format!("export KUBECONFIG='{}'", path.replace('\'', "'\\''")),
+            powershell: format!("$env:KUBECONFIG = '{}'", path.replace('\'', "''")),
+            cmd: format!("set \"KUBECONFIG={}\"", path),
+            path,
+            context,
+            namespace,
+        })
+    }
+
+    /// Deletes an exported kubeconfig.
+    pub fn remove_context(path: &str) {
+        let _ = fs::remove_file(path);
+    }
+
+    /// Deletes every exported kubeconfig, on exit and at startup to catch
+    /// files left behind by a crash.
+    pub fn clear_contexts(handle: &AppHandle) {
+        if let Ok(root) = handle.path().app_cache_dir() {
+            let _ = fs::remove_dir_all(root.join(CONTEXT_DIR));
+        }
+    }
+}
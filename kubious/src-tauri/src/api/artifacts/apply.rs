@@ -0,0 +1,681 @@
+pub mod artifact_apply {
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, Patch, PatchParams},
+        discovery::{pinned_kind, ApiResource, Scope},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::{
+            app_settings::DiffIgnoreRule,
+            artifacts::{
+                artifact_bundle::clean_manifest,
+                artifact_canonical,
+                artifact_diff::{self, FieldDiff},
+            },
+        },
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    pub const DEFAULT_FIELD_MANAGER: &str = "kubious";
+
+    /// Stages of an ordered apply. Objects are sent stage by stage so that
+    /// everything they reference already exists when they are created.
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum ApplyStage {
+        Namespaces,
+        Definitions,
+        ClusterConfiguration,
+        Policies,
+        Access,
+        Configuration,
+        Storage,
+        Services,
+        Workloads,
+        Routing,
+        CustomResources,
+        Webhooks,
+    }
+
+    impl ApplyStage {
+        pub fn of(manifest: &Value) -> Self {
+            match manifest["kind"].as_str().unwrap_or_default() {
+                "Namespace" => ApplyStage::Namespaces,
+                "CustomResourceDefinition" => ApplyStage::Definitions,
+                "PriorityClass" | "RuntimeClass" | "StorageClass" | "IngressClass"
+                | "ClusterRole" => ApplyStage::ClusterConfiguration,
+                "ResourceQuota" | "LimitRange" | "NetworkPolicy" => ApplyStage::Policies,
+                "ServiceAccount" | "Role" | "RoleBinding" | "ClusterRoleBinding" => {
+                    ApplyStage::Access
+                }
+                "ConfigMap" | "Secret" => ApplyStage::Configuration,
+                "PersistentVolume" | "PersistentVolumeClaim" => ApplyStage::Storage,
+                "Service" => ApplyStage::Services,
+                "Pod"
+                | "ReplicaSet"
+                | "ReplicationController"
+                | "Deployment"
+                | "StatefulSet"
+                | "DaemonSet"
+                | "Job"
+                | "CronJob" => ApplyStage::Workloads,
+                "Ingress" | "HorizontalPodAutoscaler" | "PodDisruptionBudget" => {
+                    ApplyStage::Routing
+                }
+                // Webhooks go last so they cannot reject the objects applied
+                // before their backing services are running.
+                "MutatingWebhookConfiguration"
+                | "ValidatingWebhookConfiguration"
+                | "ValidatingAdmissionPolicy"
+                | "ValidatingAdmissionPolicyBinding"
+                | "APIService" => ApplyStage::Webhooks,
+                _ => ApplyStage::CustomResources,
+            }
+        }
+    }
+
+    /// A manifest's place in the computed apply order.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct OrderedManifest {
+        pub position: usize,
+        /// Index of the manifest in the bundle or request.
+        pub index: usize,
+        pub stage: ApplyStage,
+        pub kind: Option<String>,
+        pub name: Option<String>,
+        pub namespace: Option<String>,
+    }
+
+    /// Sorts manifests into dependency order, keeping the original order
+    /// within each stage. Returns the sorted manifests and the order taken.
+    pub fn dependency_order(manifests: &[Value]) -> (Vec<Value>, Vec<OrderedManifest>) {
+        let mut indexed: Vec<(usize, ApplyStage)> = manifests
+            .iter()
+            .enumerate()
+            .map(|(index, manifest)| (index, ApplyStage::of(manifest)))
+            .collect();
+        indexed.sort_by_key(|(_, stage)| *stage);
+        let order = indexed
+            .iter()
+            .enumerate()
+            .map(|(position, (index, stage))| {
+                let manifest = &manifests[*index];
+                OrderedManifest {
+                    position,
+                    index: *index,
+                    stage: *stage,
+                    kind: manifest["kind"].as_str().map(|s| s.to_string()),
+                    name: manifest["metadata"]["name"].as_str().map(|s| s.to_string()),
+                    namespace: manifest["metadata"]["namespace"]
+                        .as_str()
+                        .map(|s| s.to_string()),
+                }
+            })
+            .collect();
+        let sorted = indexed
+            .iter()
+            .map(|(index, _)| manifests[*index].clone())
+            .collect();
+        (sorted, order)
+    }
+
+    /// A field another manager owns with a different value, as reported by a
+    /// rejected server-side apply.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct FieldConflict {
+        /// Field path as the server reports it, e.g. `.spec.replicas` or
+        /// `.spec.template.spec.containers[name="app"].image`.
+        pub field: String,
+        pub manager: String,
+        pub current: Option<Value>,
+        pub incoming: Option<Value>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ApplyOutcome {
+        pub kind: Option<String>,
+        pub api_version: Option<String>,
+        pub name: Option<String>,
+        pub namespace: Option<String>,
+        pub success: bool,
+        pub error: Option<String>,
+        /// Conflicting fields when the apply was rejected. After
+        /// `apply_forcing`, the fields left to their current managers.
+        #[serde(default)]
+        pub conflicts: Vec<FieldConflict>,
+    }
+
+    impl ApplyOutcome {
+        fn describe(manifest: &Value) -> Self {
+            ApplyOutcome {
+                kind: manifest["kind"].as_str().map(|s| s.to_string()),
+                api_version: manifest["apiVersion"].as_str().map(|s| s.to_string()),
+                name: manifest["metadata"]["name"].as_str().map(|s| s.to_string()),
+                namespace: manifest["metadata"]["namespace"]
+                    .as_str()
+                    .map(|s| s.to_string()),
+                success: false,
+                error: None,
+                conflicts: Vec::new(),
+            }
+        }
+
+        fn failed(mut self, error: String) -> Self {
+            self.success = false;
+            self.error = Some(error);
+            self
+        }
+    }
+
+    enum PathElement {
+        Field(String),
+        /// A list element, selected by index (`[2]`), by key fields
+        /// (`[name="app",port=80]`) or by value (`[="finalizer"]`).
+        Select(String),
+    }
+
+    enum Step {
+        Key(String),
+        Index(usize),
+    }
+
+    fn parse_field_path(path: &str) -> Vec<PathElement> {
+        let mut elements: Vec<PathElement> = Vec::new();
+        let mut current = String::new();
+        let mut bracketed = false;
+        let mut quoted = false;
+        for c in path.chars() {
+            match c {
+                '"' if bracketed => {
+                    quoted = !quoted;
+                    current.push(c);
+                }
+                ']' if bracketed && !quoted => {
+                    elements.push(PathElement::Select(std::mem::take(&mut current)));
+                    bracketed = false;
+                }
+                _ if bracketed => current.push(c),
+                '.' | '[' => {
+                    if !current.is_empty() {
+                        elements.push(PathElement::Field(std::mem::take(&mut current)));
+                    }
+                    bracketed = c == '[';
+                }
+                _ => current.push(c),
+            }
+        }
+        if !current.is_empty() {
+            elements.push(PathElement::Field(current));
+        }
+        elements
+    }
+
+    fn selected(item: &Value, selector: &str) -> bool {
+        let mut pairs: Vec<String> = vec![String::new()];
+        let mut quoted = false;
+        for c in selector.chars() {
+            match c {
+                ',' if !quoted => pairs.push(String::new()),
+                _ => {
+                    quoted ^= c == '"';
+                    pairs.last_mut().unwrap().push(c);
+                }
+            }
+        }
+        pairs.iter().all(|pair| {
+            let Some((key, raw)) = pair.split_once('=') else {
+                return false;
+            };
+            let Ok(expected) = serde_json::from_str::<Value>(raw) else {
+                return false;
+            };
+            if key.is_empty() {
+                *item == expected
+            } else {
+                item.get(key) == Some(&expected)
+            }
+        })
+    }
+
+    fn locate(value: &Value, elements: &[PathElement]) -> Option<Vec<Step>> {
+        let Some(first) = elements.first() else {
+            return Some(Vec::new());
+        };
+        match (value, first) {
+            (Value::Object(fields), PathElement::Field(_)) => {
+                // Label and annotation keys contain dots the path does not
+                // escape, so try successively longer names.
+                let mut name = String::new();
+                for (i, element) in elements.iter().enumerate() {
+                    let PathElement::Field(part) = element else {
+                        break;
+                    };
+                    if !name.is_empty() {
+                        name.push('.');
+                    }
+                    name.push_str(part);
+                    if let Some(mut steps) = fields
+                        .get(&name)
+                        .and_then(|child| locate(child, &elements[i + 1..]))
+                    {
+                        steps.insert(0, Step::Key(name));
+                        return Some(steps);
+                    }
+                }
+                None
+            }
+            (Value::Array(items), PathElement::Select(selector)) => {
+                let index = match selector.parse::<usize>() {
+                    Ok(index) => index,
+                    Err(_) => items.iter().position(|item| selected(item, selector))?,
+                };
+                let mut steps = locate(items.get(index)?, &elements[1..])?;
+                steps.insert(0, Step::Index(index));
+                Some(steps)
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolves a server-side apply field path against an object.
+    pub fn field_value(value: &Value, path: &str) -> Option<Value> {
+        locate(value, &parse_field_path(path))?
+            .iter()
+            .try_fold(value, |current, step| match step {
+                Step::Key(key) => current.get(key.as_str()),
+                Step::Index(index) => current.get(*index),
+            })
+            .cloned()
+    }
+
+    /// Removes the field at a server-side apply field path, returning it.
+    pub fn remove_field(value: &mut Value, path: &str) -> Option<Value> {
+        let steps = locate(value, &parse_field_path(path))?;
+        let (last, parents) = steps.split_last()?;
+        let mut target = value;
+        for step in parents {
+            target = match step {
+                Step::Key(key) => target.get_mut(key.as_str())?,
+                Step::Index(index) => target.get_mut(*index)?,
+            };
+        }
+        match (target, last) {
+            (Value::Object(fields), Step::Key(key)) => fields.remove(key),
+            (Value::Array(items), Step::Index(index)) => Some(items.remove(*index)),
+            _ => None,
+        }
+    }
+
+    /// Extracts `(manager, field)` pairs from a conflict message. One
+    /// conflict is reported on a single line
+    /// (`conflict with "kubectl" using apps/v1: .spec.replicas`); several are
+    /// listed as `- <field>` lines under the manager owning them.
+    fn parse_conflicts(message: &str) -> Vec<(String, String)> {
+        let mut manager = String::new();
+        let mut conflicts: Vec<(String, String)> = Vec::new();
+        for line in message.lines().map(|l| l.trim()) {
+            if let Some(field) = line.strip_prefix("- ") {
+                conflicts.push((manager.clone(), field.to_string()));
+                continue;
+            }
+            let Some((_, quoted)) = line.split_once('"') else {
+                continue;
+            };
+            let Some((name, rest)) = quoted.split_once('"') else {
+                continue;
+            };
+            manager = name.to_string();
+            if let Some((_, field)) = rest.split_once(": .") {
+                conflicts.push((manager.clone(), format!(".{}", field)));
+            }
+        }
+        conflicts
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ApplyOptions {
+        pub namespace: Option<String>,
+        pub field_manager: Option<String>,
+        pub dry_run: bool,
+        pub force: bool,
+    }
+
+    /// A manifest resolved against discovery, ready to be sent to the cluster.
+    pub struct ResolvedManifest {
+        pub api: Api<DynamicObject>,
+        pub resource: ApiResource,
+        pub object: DynamicObject,
+        pub name: String,
+        pub namespace: Option<String>,
+    }
+
+    /// Resolves a manifest's kind through discovery and picks the namespace it
+    /// targets, preferring `namespace` over the manifest's own namespace.
+    pub async fn resolve_manifest(
+        client: Client,
+        manifest: &Value,
+        namespace: Option<String>,
+    ) -> Result<ResolvedManifest, KubiousError> {
+        let mut object: DynamicObject = serde_json::from_value(manifest.clone())
+            .map_err(|e| format!("Invalid manifest: {}", e))?;
+        let Some(types) = object.types.clone() else {
+            return Err("Manifest is missing apiVersion/kind".into());
+        };
+        let Some(name) = object.metadata.name.clone() else {
+            return Err("Manifest is missing metadata.name".into());
+        };
+        let gvk = GroupVersionKind::try_from(&types).or(Err("Invalid apiVersion".to_string()))?;
+        let (resource, caps) = pinned_kind(&client, &gvk)
+            .await
+            .context(format!("Unknown resource kind {}", types.kind))?;
+
+        if caps.scope == Scope::Namespaced {
+            let namespace = namespace
+                .or(object.metadata.namespace.clone())
+                .unwrap_or(client.default_namespace().to_string());
+            object.metadata.namespace = Some(namespace.clone());
+            Ok(ResolvedManifest {
+                api: Api::namespaced_with(client.clone(), namespace.as_str(), &resource),
+                resource,
+                object,
+                name,
+                namespace: Some(namespace),
+            })
+        } else {
+            object.metadata.namespace = None;
+            Ok(ResolvedManifest {
+                api: Api::all_with(client.clone(), &resource),
+                resource,
+                object,
+                name,
+                namespace: None,
+            })
+        }
+    }
+
+    /// Server-side applies a single manifest, resolving its kind through discovery.
+    pub async fn apply_manifest(
+        client: Client,
+        manifest: &Value,
+        options: &ApplyOptions,
+    ) -> ApplyOutcome {
+        let mut outcome = ApplyOutcome::describe(manifest);
+        let resolved = match resolve_manifest(client, manifest, options.namespace.clone()).await {
+            Ok(resolved) => resolved,
+            Err(e) => return outcome.failed(e.to_string()),
+        };
+        outcome.namespace = resolved.namespace.clone();
+
+        let mut params = PatchParams::apply(
+            options
+                .field_manager
+                .as_deref()
+                .unwrap_or(DEFAULT_FIELD_MANAGER),
+        );
+        params.dry_run = options.dry_run;
+        if options.force {
+            params = params.force();
+        }
+
+        match resolved
+            .api
+            .patch(
+                resolved.name.as_str(),
+                &params,
+                &Patch::Apply(&resolved.object),
+            )
+            .await
+        {
+            Ok(_) => {
+                outcome.success = true;
+                outcome
+            }
+            Err(kube::Error::Api(e)) if e.code == 409 && e.reason == "Conflict" => {
+                let conflicts = parse_conflicts(e.message.as_str());
+                if !conflicts.is_empty() {
+                    let live = resolved
+                        .api
+                        .get(resolved.name.as_str())
+                        .await
+                        .ok()
+                        .and_then(|object| serde_json::to_value(object).ok());
+                    outcome.conflicts = conflicts
+                        .into_iter()
+                        .map(|(manager, field)| FieldConflict {
+                            current: live.as_ref().and_then(|l| field_value(l, &field)),
+                            incoming: field_value(manifest, &field),
+                            field,
+                            manager,
+                        })
+                        .collect();
+                }
+                outcome.failed(e.message)
+            }
+            Err(e) => outcome.failed(e.to_string()),
+        }
+    }
+
+    /// Applies a manifest, taking ownership of the conflicting fields listed
+    /// in `fields` only. Other conflicting fields are left out of the applied
+    /// object so they stay with their current managers, and are returned in
+    /// the outcome's `conflicts`.
+    pub async fn apply_forcing(
+        client: Client,
+        manifest: &Value,
+        options: &ApplyOptions,
+        fields: &[String],
+    ) -> ApplyOutcome {
+        let unforced = ApplyOptions {
+            force: false,
+            ..options.clone()
+        };
+        let outcome = apply_manifest(client.clone(), manifest, &unforced).await;
+        if outcome.success || outcome.conflicts.is_empty() {
+            return outcome;
+        }
+        let (forced, kept): (Vec<FieldConflict>, Vec<FieldConflict>) = outcome
+            .conflicts
+            .into_iter()
+            .partition(|c| fields.contains(&c.field));
+        if forced.is_empty() {
+            return ApplyOutcome {
+                conflicts: kept,
+                ..ApplyOutcome::describe(manifest)
+            }
+            .failed("No conflicting fields were selected".to_string());
+        }
+        let mut trimmed = manifest.clone();
+        for conflict in kept.iter() {
+            remove_field(&mut trimmed, conflict.field.as_str());
+        }
+        let forcing = ApplyOptions {
+            force: true,
+            ..options.clone()
+        };
+        let mut outcome = apply_manifest(client, &trimmed, &forcing).await;
+        outcome.conflicts = kept;
+        outcome
+    }
+
+    /// What applying a manifest would change on the live object.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ManifestDiff {
+        pub kind: Option<String>,
+        pub name: String,
+        pub namespace: Option<String>,
+        /// False when the apply would create the object.
+        pub exists: bool,
+        pub diffs: Vec<FieldDiff>,
+    }
+
+    /// Diffs the live object against the result of a server-side dry-run
+    /// apply of `manifest`, so defaults, admission webhooks and fields owned
+    /// by other managers are accounted for as the server would.
+    pub async fn diff_manifest(
+        client: Client,
+        manifest: &Value,
+        options: &ApplyOptions,
+        rules: &[DiffIgnoreRule],
+    ) -> Result<ManifestDiff, KubiousError> {
+        let resolved = resolve_manifest(client, manifest, options.namespace.clone()).await?;
+        let live = resolved
+            .api
+            .get_opt(resolved.name.as_str())
+            .await
+            .context(format!("Failed to get {}", resolved.name))?;
+
+        let mut params = PatchParams::apply(
+            options
+                .field_manager
+                .as_deref()
+                .unwrap_or(DEFAULT_FIELD_MANAGER),
+        )
+        .dry_run();
+        if options.force {
+            params = params.force();
+        }
+        let proposed = match resolved
+            .api
+            .patch(
+                resolved.name.as_str(),
+                &params,
+                &Patch::Apply(&resolved.object),
+            )
+            .await
+        {
+            Ok(proposed) => proposed,
+            Err(kube::Error::Api(e)) if e.code == 409 && e.reason == "Conflict" => {
+                let fields: Vec<String> = parse_conflicts(e.message.as_str())
+                    .into_iter()
+                    .map(|(manager, field)| format!("{} (owned by {})", field, manager))
+                    .collect();
+                return Err(format!(
+                    "Apply conflicts with other field managers: {}",
+                    fields.join(", ")
+                )
+                .into());
+            }
+            Err(e) => return Err(KubiousError::from(e).with_context("Dry-run apply failed")),
+        };
+
+        let exists = live.is_some();
+        let mut live = match live {
+            Some(live) => {
+                serde_json::to_value(live).or(Err("Failed to serialize resource.".to_string()))?
+            }
+            None => Value::Object(Default::default()),
+        };
+        let mut proposed =
+            serde_json::to_value(proposed).or(Err("Failed to serialize resource.".to_string()))?;
+        // resourceVersion, generation and managedFields change with every
+        // write, so they are dropped from both sides.
+        clean_manifest(&mut live);
+        clean_manifest(&mut proposed);
+        artifact_canonical::canonicalize(&mut live);
+        artifact_canonical::canonicalize(&mut proposed);
+
+        Ok(ManifestDiff {
+            kind: manifest["kind"].as_str().map(|s| s.to_string()),
+            name: resolved.name,
+            namespace: resolved.namespace,
+            exists,
+            diffs: artifact_diff::diff_values(&live, &proposed, true, rules),
+        })
+    }
+
+    /// Applies manifests in order. When `stop_on_failure` is set, remaining
+    /// manifests are reported as skipped after the first failure.
+    pub async fn apply_manifests(
+        client: Client,
+        manifests: &[Value],
+        options: &ApplyOptions,
+        stop_on_failure: bool,
+    ) -> Vec<ApplyOutcome> {
+        let mut outcomes: Vec<ApplyOutcome> = Vec::new();
+        let mut failed = false;
+        for manifest in manifests {
+            if failed && stop_on_failure {
+                outcomes.push(
+                    ApplyOutcome::describe(manifest)
+                        .failed("Skipped after earlier failure".to_string()),
+                );
+                continue;
+            }
+            let outcome = apply_manifest(client.clone(), manifest, options).await;
+            failed = failed || !outcome.success;
+            outcomes.push(outcome);
+        }
+        outcomes
+    }
+
+    /// One document of a YAML stream. `line` is where the document starts,
+    /// counting from 1.
+    pub struct YamlDocument {
+        pub index: usize,
+        pub line: usize,
+        pub manifest: Result<Value, String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DocumentOutcome {
+        pub document: usize,
+        pub line: usize,
+        #[serde(flatten)]
+        pub outcome: ApplyOutcome,
+    }
+
+    /// Splits a YAML stream on `---` separators and parses each document on
+    /// its own, so a syntax error only fails the document it is in. Empty
+    /// documents, such as a leading separator or comments only, are dropped.
+    pub fn parse_documents(yaml: &str) -> Vec<YamlDocument> {
+        let mut sections: Vec<(usize, String)> = vec![(1, String::new())];
+        for (number, line) in yaml.lines().enumerate() {
+            if line == "---" || line.starts_with("--- ") {
+                sections.push((number + 2, String::new()));
+            } else if let Some((_, text)) = sections.last_mut() {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+
+        let mut documents: Vec<YamlDocument> = Vec::new();
+        for (line, text) in sections {
+            let manifest = match serde_yaml::from_str::<Value>(text.as_str()) {
+                Ok(Value::Null) => continue,
+                Ok(Value::Object(object)) => Ok(Value::Object(object)),
+                Ok(_) => Err("Document is not a mapping".to_string()),
+                Err(e) => Err(format!("Invalid YAML: {}", e)),
+            };
+            documents.push(YamlDocument {
+                index: documents.len(),
+                line,
+                manifest,
+            });
+        }
+        documents
+    }
+
+    /// Applies every document of a YAML stream in the order written.
+    /// Documents that fail to parse are reported and the rest still applied.
+    pub async fn apply_yaml(
+        client: Client,
+        yaml: &str,
+        options: &ApplyOptions,
+    ) -> Vec<DocumentOutcome> {
+        let mut outcomes: Vec<DocumentOutcome> = Vec::new();
+        for document in parse_documents(yaml) {
+            let outcome = match &document.manifest {
+                Ok(manifest) => apply_manifest(client.clone(), manifest, options).await,
+                Err(e) => ApplyOutcome::describe(&Value::Null).failed(e.clone()),
+            };
+            outcomes.push(DocumentOutcome {
+                document: document.index,
+                line: document.line,
+                outcome,
+            });
+        }
+        outcomes
+    }
+}
@@ -0,0 +1,208 @@
+pub mod artifact_bundle {
+    use std::{
+        fs::{self, File},
+        io::{Read, Write},
+        path::PathBuf,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::{AppHandle, Manager};
+
+    use crate::util::kubious_error::KubiousError;
+
+    /// Annotation placed on live objects that are managed by a bundle.
+    pub const BUNDLE_ANNOTATION: &str = "kubious/artifact-bundle";
+
+    /// Label placed on objects applied from a bundle, used to find objects
+    /// that were dropped from the bundle since.
+    pub const BUNDLE_LABEL: &str = "kubious/applied-bundle";
+
+    const STRIPPED_METADATA: [&str; 7] = [
+        "uid",
+        "resourceVersion",
+        "generation",
+        "creationTimestamp",
+        "managedFields",
+        "selfLink",
+        "ownerReferences",
+    ];
+
+    const STRIPPED_ANNOTATIONS: [&str; 2] = [
+        "kubectl.kubernetes.io/last-applied-configuration",
+        "deployment.kubernetes.io/revision",
+    ];
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ArtifactBundle {
+        pub name: String,
+        pub description: Option<String>,
+        pub source: Option<String>,
+        pub created: u64,
+        pub manifests: Vec<Value>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BundleSummary {
+        pub name: String,
+        pub description: Option<String>,
+        pub source: Option<String>,
+        pub created: u64,
+        pub manifests: usize,
+    }
+
+    impl ArtifactBundle {
+        pub fn new(name: &str, description: Option<String>, source: Option<String>) -> Self {
+            ArtifactBundle {
+                name: name.to_string(),
+                description,
+                source,
+                created: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                manifests: Vec::new(),
+            }
+        }
+
+        pub fn summary(&self) -> BundleSummary {
+            BundleSummary {
+                name: self.name.clone(),
+                description: self.description.clone(),
+                source: self.source.clone(),
+                created: self.created,
+                manifests: self.manifests.len(),
+            }
+        }
+    }
+
+    /// Removes server-populated fields so a manifest can be re-applied elsewhere.
+    pub fn clean_manifest(manifest: &mut Value) {
+        if let Some(root) = manifest.as_object_mut() {
+            root.remove("status");
+        }
+
+        if let Some(metadata) = manifest["metadata"].as_object_mut() {
+            for field in STRIPPED_METADATA {
+                metadata.remove(field);
+            }
+            let mut empty_annotations = false;
+            if let Some(annotations) = metadata
+                .get_mut("annotations")
+                .and_then(|a| a.as_object_mut())
+            {
+                for annotation in STRIPPED_ANNOTATIONS {
+                    annotations.remove(annotation);
+                }
+                empty_annotations = annotations.is_empty();
+            }
+            if empty_annotations {
+                metadata.remove("annotations");
+            }
+        }
+
+        if manifest["kind"].as_str() == Some("Service") {
+            if let Some(spec) = manifest["spec"].as_object_mut() {
+                if spec.get("clusterIP").and_then(|ip| ip.as_str()) != Some("None") {
+                    spec.remove("clusterIP");
+                    spec.remove("clusterIPs");
+                }
+            }
+        }
+    }
+
+    /// Labels a manifest as applied from `bundle`. Names too long for a label
+    /// value are left untracked.
+    pub fn track_manifest(manifest: &mut Value, bundle: &str) {
+        if bundle.len() > 63 || !manifest["metadata"].is_object() {
+            return;
+        }
+        if !manifest["metadata"]["labels"].is_object() {
+            manifest["metadata"]["labels"] = Value::Object(Default::default());
+        }
+        manifest["metadata"]["labels"][BUNDLE_LABEL] = Value::String(bundle.to_string());
+    }
+
+    fn valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+            && !name.starts_with('.')
+    }
+
+    fn bundles_dir(handle: &AppHandle) -> Result<PathBuf, KubiousError> {
+        let root = handle
+            .path()
+            .app_data_dir()
+            .or(Err("Failed to resolve app data directory.".to_string()))?
+            .join("artifacts");
+        if !root.exists() {
+            fs::create_dir_all(&root)
+                .or(Err("Failed to create artifacts directory.".to_string()))?;
+        }
+        Ok(root)
+    }
+
+    fn bundle_path(handle: &AppHandle, name: &str) -> Result<PathBuf, KubiousError> {
+        if !valid_name(name) {
+            return Err("Invalid bundle name".into());
+        }
+        Ok(bundles_dir(handle)?.join(format!("{}.json", name)))
+    }
+
+    pub fn save_bundle(handle: &AppHandle, bundle: &ArtifactBundle) -> Result<(), KubiousError> {
+        let path = bundle_path(handle, bundle.name.as_str())?;
+        let serialized = serde_json::to_string_pretty(bundle)
+            .or(Err("Failed to serialize bundle.".to_string()))?;
+        File::create(path)
+            .and_then(|mut file| file.write_all(serialized.as_bytes()))
+            .or(Err("Failed to write bundle.".into()))
+    }
+
+    pub fn load_bundle(handle: &AppHandle, name: &str) -> Result<ArtifactBundle, KubiousError> {
+        let path = bundle_path(handle, name)?;
+        if !path.exists() {
+            return Err("Unknown bundle name".into());
+        }
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .or(Err("Failed to read bundle.".to_string()))?;
+        serde_json::from_str(contents.as_str()).or(Err("Failed to parse bundle.".into()))
+    }
+
+    pub fn bundle_exists(handle: &AppHandle, name: &str) -> bool {
+        bundle_path(handle, name)
+            .map(|path| path.exists())
+            .unwrap_or(false)
+    }
+
+    pub fn delete_bundle(handle: &AppHandle, name: &str) -> Result<(), KubiousError> {
+        let path = bundle_path(handle, name)?;
+        if path.exists() {
+            fs::remove_file(path).or(Err("Failed to delete bundle.".into()))
+        } else {
+            Err("Unknown bundle name".into())
+        }
+    }
+
+    pub fn list_bundles(handle: &AppHandle) -> Result<Vec<BundleSummary>, KubiousError> {
+        let entries = fs::read_dir(bundles_dir(handle)?)
+            .or(Err("Failed to read artifacts directory.".to_string()))?;
+        let mut bundles: Vec<BundleSummary> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    return None;
+                }
+                let name = path.file_stem()?.to_str()?.to_string();
+                load_bundle(handle, name.as_str()).ok().map(|b| b.summary())
+            })
+            .collect();
+        bundles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(bundles)
+    }
+}
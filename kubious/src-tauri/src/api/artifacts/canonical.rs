@@ -0,0 +1,137 @@
+pub mod artifact_canonical {
+    use serde_json::{Map, Value};
+
+    use crate::util::kube_quantity::canonical_quantity;
+
+    /// Maps whose values are all quantities.
+    const QUANTITY_MAPS: [&str; 6] = [
+        "limits",
+        "requests",
+        "capacity",
+        "allocatable",
+        "overhead",
+        "hard",
+    ];
+
+    /// Further quantity maps of LimitRange items.
+    const LIMIT_RANGE_MAPS: [&str; 5] = [
+        "default",
+        "defaultRequest",
+        "max",
+        "min",
+        "maxLimitRequestRatio",
+    ];
+
+    const QUANTITY_FIELDS: [&str; 1] = ["sizeLimit"];
+
+    /// Lists whose order carries no meaning, with the fields identifying
+    /// an item. Only fields the server does not default are used, so a
+    /// manifest and its live object sort alike. Plain values sort as they
+    /// are. `env`, `containers` and `args` keep their order, which matters.
+    const UNORDERED_LISTS: [(&str, &[&str]); 14] = [
+        ("volumes", &["name"]),
+        ("volumeMounts", &["mountPath"]),
+        ("volumeDevices", &["devicePath"]),
+        ("imagePullSecrets", &["name"]),
+        ("ports", &["name", "containerPort", "port"]),
+        ("tolerations", &["key", "value", "effect"]),
+        ("subjects", &["kind", "namespace", "name"]),
+        ("accessModes", &[]),
+        ("add", &[]),
+        ("drop", &[]),
+        ("verbs", &[]),
+        ("apiGroups", &[]),
+        ("resources", &[]),
+        ("resourceNames", &[]),
+    ];
+
+    /// Built-in kinds: the core group, groups without a domain and the
+    /// `*.k8s.io` groups. Custom resources may use the same field names
+    /// for anything, so only their key order is touched.
+    fn is_builtin(manifest: &Value) -> bool {
+        let api_version = manifest["apiVersion"].as_str().unwrap_or_default();
+        let kind = manifest["kind"].as_str().unwrap_or_default();
+        if kind == "CustomResourceDefinition" {
+            return false;
+        }
+        match api_version.split_once('/') {
+            None => true,
+            Some((group, _)) => !group.contains('.') || group.ends_with(".k8s.io"),
+        }
+    }
+
+    fn normalize_quantity(value: &mut Value) {
+        let canonical = match value {
+            Value::String(text) => canonical_quantity(text.as_str()),
+            Value::Number(number) => canonical_quantity(number.to_string().as_str()),
+            _ => return,
+        };
+        *value = Value::String(canonical);
+    }
+
+    fn sort_key(item: &Value, fields: &[&str]) -> String {
+        if !item.is_object() {
+            return serde_json::to_string(item).unwrap_or_default();
+        }
+        fields
+            .iter()
+            .map(|field| match &item[*field] {
+                Value::String(text) => text.clone(),
+                Value::Number(number) => format!("{:0>10}", number),
+                _ => String::new(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn walk(value: &mut Value, key: Option<&str>, quantities: &[&str], semantic: bool) {
+        match value {
+            Value::Object(map) => {
+                let quantity_map = semantic && key.is_some_and(|k| quantities.contains(&k));
+                let mut entries: Vec<(String, Value)> = std::mem::take(map).into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                let mut sorted = Map::new();
+                for (field, mut child) in entries {
+                    if quantity_map || (semantic && QUANTITY_FIELDS.contains(&field.as_str())) {
+                        normalize_quantity(&mut child);
+                    } else {
+                        walk(&mut child, Some(field.as_str()), quantities, semantic);
+                    }
+                    sorted.insert(field, child);
+                }
+                *map = sorted;
+            }
+            Value::Array(items) => {
+                for item in items.iter_mut() {
+                    walk(item, None, quantities, semantic);
+                }
+                let unordered = UNORDERED_LISTS
+                    .iter()
+                    .find(|(list, _)| Some(*list) == key)
+                    .filter(|_| semantic);
+                if let Some((_, fields)) = unordered {
+                    items.sort_by_cached_key(|item| sort_key(item, fields));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Rewrites a manifest so semantically equal manifests are equal as
+    /// values: keys in order, quantities in their shortest exact form
+    /// (`1024Mi` is `1Gi`) and order-free lists sorted. Used before
+    /// diffing, drift checks and exports.
+    pub fn canonicalize(manifest: &mut Value) {
+        let semantic = is_builtin(manifest);
+        let quantities: Vec<&str> = if manifest["kind"].as_str() == Some("LimitRange") {
+            QUANTITY_MAPS
+                .iter()
+                .chain(LIMIT_RANGE_MAPS.iter())
+                .copied()
+                .collect()
+        } else {
+            QUANTITY_MAPS.to_vec()
+        };
+        walk(manifest, None, &quantities, semantic);
+    }
+}
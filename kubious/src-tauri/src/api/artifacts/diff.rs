@@ -0,0 +1,123 @@
+pub mod artifact_diff {
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::app_settings::DiffIgnoreRule,
+        util::value_path::{format_path, parse_path},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum DiffKind {
+        Added,
+        Removed,
+        Changed,
+    }
+
+    /// A single differing field. `Added` means the desired manifest sets a
+    /// field the live object lacks; `Removed` is the inverse.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct FieldDiff {
+        pub path: String,
+        pub kind: DiffKind,
+        pub live: Option<Value>,
+        pub desired: Option<Value>,
+    }
+
+    fn rule_matches(rule: &[String], path: &[String]) -> bool {
+        rule.len() <= path.len()
+            && rule
+                .iter()
+                .zip(path.iter())
+                .all(|(r, p)| r == "*" || r == p)
+    }
+
+    pub fn is_ignored(path: &[String], rules: &[DiffIgnoreRule]) -> bool {
+        rules.iter().any(|rule| {
+            rule.paths
+                .iter()
+                .any(|p| rule_matches(&parse_path(p), path))
+        })
+    }
+
+    fn walk(
+        live: Option<&Value>,
+        desired: Option<&Value>,
+        path: &mut Vec<String>,
+        full: bool,
+        rules: &[DiffIgnoreRule],
+        diffs: &mut Vec<FieldDiff>,
+    ) {
+        if is_ignored(path, rules) {
+            return;
+        }
+        match (live, desired) {
+            (Some(Value::Object(l)), Some(Value::Object(d))) => {
+                for (key, value) in d {
+                    path.push(key.clone());
+                    walk(l.get(key), Some(value), path, full, rules, diffs);
+                    path.pop();
+                }
+                if full {
+                    for (key, value) in l {
+                        if !d.contains_key(key) {
+                            path.push(key.clone());
+                            walk(Some(value), None, path, full, rules, diffs);
+                            path.pop();
+                        }
+                    }
+                }
+            }
+            (Some(Value::Array(l)), Some(Value::Array(d))) => {
+                for index in 0..l.len().max(d.len()) {
+                    if index >= d.len() && !full {
+                        break;
+                    }
+                    path.push(index.to_string());
+                    walk(l.get(index), d.get(index), path, full, rules, diffs);
+                    path.pop();
+                }
+            }
+            (Some(l), Some(d)) if l != d => diffs.push(FieldDiff {
+                path: format_path(path),
+                kind: DiffKind::Changed,
+                live: Some(l.clone()),
+                desired: Some(d.clone()),
+            }),
+            (None, Some(d)) => diffs.push(FieldDiff {
+                path: format_path(path),
+                kind: DiffKind::Added,
+                live: None,
+                desired: Some(d.clone()),
+            }),
+            (Some(l), None) => diffs.push(FieldDiff {
+                path: format_path(path),
+                kind: DiffKind::Removed,
+                live: Some(l.clone()),
+                desired: None,
+            }),
+            _ => {}
+        }
+    }
+
+    /// Compares a live object with a desired manifest. When `full` is false
+    /// only fields set in `desired` are compared, which is what drift checks
+    /// want since live objects carry many server-side defaults.
+    pub fn diff_values(
+        live: &Value,
+        desired: &Value,
+        full: bool,
+        rules: &[DiffIgnoreRule],
+    ) -> Vec<FieldDiff> {
+        let mut diffs: Vec<FieldDiff> = Vec::new();
+        walk(
+            Some(live),
+            Some(desired),
+            &mut Vec::new(),
+            full,
+            rules,
+            &mut diffs,
+        );
+        diffs
+    }
+}
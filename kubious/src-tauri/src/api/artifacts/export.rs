@@ -0,0 +1,155 @@
+pub mod artifact_export {
+    use std::{fs, path::PathBuf};
+
+    use k8s_openapi::api::core::v1::Namespace;
+    use kube::{
+        api::{DynamicObject, ListParams, TypeMeta},
+        discovery::{verbs, Discovery, Scope},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::{
+            artifacts::{artifact_apply, artifact_bundle, artifact_canonical},
+            artifacts_api::{has_controller, is_default_object, DERIVED_KINDS},
+            views::resource_access::{self, ResourceRef},
+        },
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ExportedResource {
+        pub kind: Option<String>,
+        pub api_version: Option<String>,
+        pub name: Option<String>,
+        pub namespace: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct YamlExport {
+        pub yaml: String,
+        pub resources: Vec<ExportedResource>,
+        /// Where the YAML was written, if a path was given.
+        pub path: Option<String>,
+    }
+
+    fn describe(manifest: &Value) -> ExportedResource {
+        ExportedResource {
+            kind: manifest["kind"].as_str().map(|s| s.to_string()),
+            api_version: manifest["apiVersion"].as_str().map(|s| s.to_string()),
+            name: manifest["metadata"]["name"].as_str().map(|s| s.to_string()),
+            namespace: manifest["metadata"]["namespace"]
+                .as_str()
+                .map(|s| s.to_string()),
+        }
+    }
+
+    /// Serializes manifests as one multi-document YAML stream and writes it
+    /// to `path` when given.
+    pub fn to_export(
+        manifests: &[Value],
+        path: Option<String>,
+    ) -> Result<YamlExport, KubiousError> {
+        let documents = manifests
+            .iter()
+            .map(|manifest| {
+                let mut manifest = manifest.clone();
+                artifact_canonical::canonicalize(&mut manifest);
+                serde_yaml::to_string(&manifest)
+            })
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| format!("Failed to serialize YAML: {}", e))?;
+        let yaml = documents.join("---\n");
+        if let Some(path) = path.as_ref() {
+            fs::write(PathBuf::from(path), yaml.as_bytes())
+                .context(format!("Failed to write {}", path))?;
+        }
+        Ok(YamlExport {
+            yaml,
+            resources: manifests.iter().map(describe).collect(),
+            path,
+        })
+    }
+
+    /// Exports one live object without its status and server-managed
+    /// metadata.
+    pub async fn export_resource(
+        client: Client,
+        target: &ResourceRef,
+        path: Option<String>,
+    ) -> Result<YamlExport, KubiousError> {
+        let mut manifest = resource_access::get(client, target).await?;
+        artifact_bundle::clean_manifest(&mut manifest);
+        to_export(&[manifest], path)
+    }
+
+    /// Exports the namespace and everything in it that is not created by a
+    /// controller or by Kubernetes itself, in the order it can be applied.
+    /// `kinds` limits the export to the listed kinds.
+    pub async fn export_namespace(
+        client: Client,
+        namespace: &str,
+        kinds: &Option<Vec<String>>,
+        path: Option<String>,
+    ) -> Result<YamlExport, KubiousError> {
+        let namespace_object = Api::<Namespace>::all(client.clone())
+            .get(namespace)
+            .await
+            .context(format!("Failed to get namespace {}", namespace))?;
+        let mut manifests: Vec<Value> = Vec::new();
+        if kinds.is_none() {
+            let mut manifest = serde_json::to_value(&namespace_object)
+                .or(Err("Failed to serialize resource.".to_string()))?;
+            manifest["apiVersion"] = Value::String("v1".to_string());
+            manifest["kind"] = Value::String("Namespace".to_string());
+            artifact_bundle::clean_manifest(&mut manifest);
+            manifests.push(manifest);
+        }
+
+        let discovery = Discovery::new(client.clone())
+            .run()
+            .await
+            .context("Failed to run API discovery.")?;
+        for group in discovery.groups() {
+            for (resource, caps) in group.recommended_resources() {
+                if caps.scope != Scope::Namespaced
+                    || !caps.supports_operation(verbs::LIST)
+                    || DERIVED_KINDS.contains(&resource.kind.as_str())
+                    || kinds
+                        .as_ref()
+                        .is_some_and(|kinds| !kinds.contains(&resource.kind))
+                {
+                    continue;
+                }
+                let api: Api<DynamicObject> =
+                    Api::namespaced_with(client.clone(), namespace, &resource);
+                let Ok(objects) = api.list(&ListParams::default()).await else {
+                    continue;
+                };
+                for mut object in objects.items {
+                    let name = object.metadata.name.clone().unwrap_or_default();
+                    if is_default_object(resource.kind.as_str(), name.as_str())
+                        || has_controller(&object)
+                    {
+                        continue;
+                    }
+                    object.types = Some(TypeMeta {
+                        api_version: resource.api_version.clone(),
+                        kind: resource.kind.clone(),
+                    });
+                    let mut manifest = serde_json::to_value(&object)
+                        .or(Err("Failed to serialize resource.".to_string()))?;
+                    artifact_bundle::clean_manifest(&mut manifest);
+                    manifests.push(manifest);
+                }
+            }
+        }
+
+        if manifests.is_empty() {
+            return Err("No resources matched the selection".into());
+        }
+        to_export(&artifact_apply::dependency_order(&manifests).0, path)
+    }
+}
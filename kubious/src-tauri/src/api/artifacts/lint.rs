@@ -0,0 +1,343 @@
+pub mod artifact_lint {
+    use k8s_openapi::api::{
+        apps::v1::{DaemonSet, Deployment, StatefulSet},
+        batch::v1::{CronJob, Job},
+        core::v1::Pod,
+    };
+    use kube::{api::ListParams, Api, Client, Resource};
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    #[serde(rename_all = "snake_case")]
+    pub enum LintSeverity {
+        Info,
+        Warning,
+        Error,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct LintRule {
+        pub id: String,
+        pub severity: LintSeverity,
+        pub description: String,
+        pub enabled: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct LintFinding {
+        /// Position of the manifest in the linted list.
+        pub index: usize,
+        pub kind: Option<String>,
+        pub namespace: Option<String>,
+        pub name: Option<String>,
+        pub container: Option<String>,
+        pub rule: String,
+        pub severity: LintSeverity,
+        pub message: String,
+        pub suggestion: String,
+    }
+
+    /// Every built-in rule: id, severity and description.
+    const RULES: [(&str, LintSeverity, &str); 9] = [
+        (
+            "missing-readiness-probe",
+            LintSeverity::Warning,
+            "Long-running containers should have a readiness probe",
+        ),
+        (
+            "missing-liveness-probe",
+            LintSeverity::Info,
+            "Long-running containers should have a liveness probe",
+        ),
+        (
+            "missing-resource-requests",
+            LintSeverity::Warning,
+            "Containers should request CPU and memory",
+        ),
+        (
+            "missing-resource-limits",
+            LintSeverity::Warning,
+            "Containers should have a memory limit",
+        ),
+        (
+            "latest-tag",
+            LintSeverity::Warning,
+            "Images should be pinned to a tag other than latest, or a digest",
+        ),
+        (
+            "host-network",
+            LintSeverity::Error,
+            "Pods should not share the node's network namespace",
+        ),
+        (
+            "host-pid-ipc",
+            LintSeverity::Error,
+            "Pods should not share the node's PID or IPC namespace",
+        ),
+        (
+            "privileged-container",
+            LintSeverity::Error,
+            "Containers should not run privileged",
+        ),
+        (
+            "run-as-root",
+            LintSeverity::Info,
+            "Pods should set runAsNonRoot",
+        ),
+    ];
+
+    pub fn rules(disabled: &[String]) -> Vec<LintRule> {
+        RULES
+            .iter()
+            .map(|(id, severity, description)| LintRule {
+                id: id.to_string(),
+                severity: severity.clone(),
+                description: description.to_string(),
+                enabled: !disabled.iter().any(|d| d == id),
+            })
+            .collect()
+    }
+
+    pub fn is_rule(id: &str) -> bool {
+        RULES.iter().any(|(rule, _, _)| *rule == id)
+    }
+
+    /// The pod spec of a manifest and whether its pods are expected to run
+    /// to completion, in which case probes are not required.
+    fn pod_spec(manifest: &Value) -> Option<(&Value, bool)> {
+        let spec = &manifest["spec"];
+        let (pod, batch) = match manifest["kind"].as_str()? {
+            "Pod" => (spec, false),
+            "Deployment" | "StatefulSet" | "DaemonSet" | "ReplicaSet" | "ReplicationController" => {
+                (&spec["template"]["spec"], false)
+            }
+            "Job" => (&spec["template"]["spec"], true),
+            "CronJob" => (&spec["jobTemplate"]["spec"]["template"]["spec"], true),
+            _ => return None,
+        };
+        pod.is_object().then_some((pod, batch))
+    }
+
+    fn pinned(image: &str) -> bool {
+        if image.contains('@') {
+            return true;
+        }
+        let name = image.rsplit('/').next().unwrap_or(image);
+        match name.split_once(':') {
+            Some((_, tag)) => tag != "latest",
+            None => false,
+        }
+    }
+
+    struct Linter<'a> {
+        index: usize,
+        manifest: &'a Value,
+        disabled: &'a [String],
+        findings: Vec<LintFinding>,
+    }
+
+    impl Linter<'_> {
+        fn report(
+            &mut self,
+            rule: &str,
+            container: Option<&str>,
+            message: String,
+            suggestion: &str,
+        ) {
+            if self.disabled.iter().any(|d| d == rule) {
+                return;
+            }
+            let Some((_, severity, _)) = RULES.iter().find(|(id, _, _)| *id == rule) else {
+                return;
+            };
+            let metadata = &self.manifest["metadata"];
+            self.findings.push(LintFinding {
+                index: self.index,
+                kind: self.manifest["kind"].as_str().map(|s| s.to_string()),
+                namespace: metadata["namespace"].as_str().map(|s| s.to_string()),
+                name: metadata["name"].as_str().map(|s| s.to_string()),
+                container: container.map(|c| c.to_string()),
+                rule: rule.to_string(),
+                severity: severity.clone(),
+                message,
+                suggestion: suggestion.to_string(),
+            });
+        }
+    }
+
+    fn lint_manifest(index: usize, manifest: &Value, disabled: &[String]) -> Vec<LintFinding> {
+        let Some((pod, batch)) = pod_spec(manifest) else {
+            return Vec::new();
+        };
+        let mut linter = Linter {
+            index,
+            manifest,
+            disabled,
+            findings: Vec::new(),
+        };
+
+        if pod["hostNetwork"].as_bool() == Some(true) {
+            linter.report(
+                "host-network",
+                None,
+                "hostNetwork is enabled".to_string(),
+                "Remove spec.hostNetwork and expose ports through a Service",
+            );
+        }
+        for field in ["hostPID", "hostIPC"] {
+            if pod[field].as_bool() == Some(true) {
+                linter.report(
+                    "host-pid-ipc",
+                    None,
+                    format!("{} is enabled", field),
+                    "Remove the field unless the workload is a node agent that needs it",
+                );
+            }
+        }
+        let pod_non_root = pod["securityContext"]["runAsNonRoot"].as_bool();
+
+        let init = pod["initContainers"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|c| (c, true));
+        let regular = pod["containers"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|c| (c, false));
+        for (container, is_init) in init.chain(regular) {
+            let name = container["name"].as_str();
+            let image = container["image"].as_str().unwrap_or_default();
+            if !pinned(image) {
+                linter.report(
+                    "latest-tag",
+                    name,
+                    format!("Image {} is not pinned", image),
+                    "Use an explicit version tag or an image digest",
+                );
+            }
+            let security = &container["securityContext"];
+            if security["privileged"].as_bool() == Some(true) {
+                linter.report(
+                    "privileged-container",
+                    name,
+                    "Container runs privileged".to_string(),
+                    "Drop privileged and add only the capabilities the container needs",
+                );
+            }
+            if security["runAsNonRoot"].as_bool().or(pod_non_root) != Some(true)
+                && security["runAsUser"].as_i64().is_none_or(|uid| uid == 0)
+            {
+                linter.report(
+                    "run-as-root",
+                    name,
+                    "Container may run as root".to_string(),
+                    "Set securityContext.runAsNonRoot: true and a non-zero runAsUser",
+                );
+            }
+            let resources = &container["resources"];
+            let missing: Vec<&str> = ["cpu", "memory"]
+                .into_iter()
+                .filter(|r| resources["requests"][*r].is_null())
+                .collect();
+            if !missing.is_empty() {
+                linter.report(
+                    "missing-resource-requests",
+                    name,
+                    format!("No {} request", missing.join(" or ")),
+                    "Set resources.requests from the container's observed usage",
+                );
+            }
+            if resources["limits"]["memory"].is_null() {
+                linter.report(
+                    "missing-resource-limits",
+                    name,
+                    "No memory limit".to_string(),
+                    "Set resources.limits.memory so one container cannot exhaust the node",
+                );
+            }
+            if is_init || batch {
+                continue;
+            }
+            if container["readinessProbe"].is_null() {
+                linter.report(
+                    "missing-readiness-probe",
+                    name,
+                    "No readiness probe".to_string(),
+                    "Add a readinessProbe so traffic only reaches ready pods",
+                );
+            }
+            if container["livenessProbe"].is_null() {
+                linter.report(
+                    "missing-liveness-probe",
+                    name,
+                    "No liveness probe".to_string(),
+                    "Add a livenessProbe so hung containers are restarted",
+                );
+            }
+        }
+        linter.findings
+    }
+
+    /// Lints manifests against every enabled rule, most severe findings
+    /// first. Kinds without a pod template are skipped.
+    pub fn lint(manifests: &[Value], disabled: &[String]) -> Vec<LintFinding> {
+        let mut findings: Vec<LintFinding> = manifests
+            .iter()
+            .enumerate()
+            .flat_map(|(index, manifest)| lint_manifest(index, manifest, disabled))
+            .collect();
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.index.cmp(&b.index)));
+        findings
+    }
+
+    async fn list_live<K>(
+        client: &Client,
+        namespace: &str,
+        skip_owned: bool,
+    ) -> Result<Vec<Value>, KubiousError>
+    where
+        K: Resource<Scope = k8s_openapi::NamespaceResourceScope>
+            + Clone
+            + DeserializeOwned
+            + Serialize
+            + std::fmt::Debug,
+        K::DynamicType: Default,
+    {
+        Ok(Api::<K>::namespaced(client.clone(), namespace)
+            .list(&ListParams::default())
+            .await
+            .context(format!("Failed to list {}", K::kind(&Default::default())))?
+            .items
+            .into_iter()
+            .filter(|object| {
+                !skip_owned
+                    || object
+                        .meta()
+                        .owner_references
+                        .as_ref()
+                        .is_none_or(|o| o.is_empty())
+            })
+            .filter_map(|object| serde_json::to_value(object).ok())
+            .collect())
+    }
+
+    /// The live objects of a namespace that define pods themselves: workloads,
+    /// and Jobs and Pods not created by another object.
+    pub async fn live_manifests(
+        client: Client,
+        namespace: &str,
+    ) -> Result<Vec<Value>, KubiousError> {
+        let mut manifests = list_live::<Deployment>(&client, namespace, false).await?;
+        manifests.extend(list_live::<StatefulSet>(&client, namespace, false).await?);
+        manifests.extend(list_live::<DaemonSet>(&client, namespace, false).await?);
+        manifests.extend(list_live::<CronJob>(&client, namespace, false).await?);
+        manifests.extend(list_live::<Job>(&client, namespace, true).await?);
+        manifests.extend(list_live::<Pod>(&client, namespace, true).await?);
+        Ok(manifests)
+    }
+}
@@ -1,9 +1,805 @@
 pub mod artifacts_api {
+    use crate::{
+        api::{
+            app_state::AppState,
+            views::resource_access::{self, ResourceRef},
+        },
+        util::kubious_error::{ErrorContext, KubiousError},
+        CommandHandler,
+    };
+    use kube::{
+        api::{ApiResource, DynamicObject, ListParams, Patch, PatchParams, TypeMeta},
+        discovery::{verbs, Discovery, Scope},
+        Api,
+    };
     use serde::{Deserialize, Serialize};
-    use crate::CommandHandler;
+    use serde_json::{json, Value};
+    use tauri::Manager;
+
+    use super::{
+        artifact_apply::{self, ApplyOptions, ApplyOutcome, OrderedManifest},
+        artifact_bundle::{self, ArtifactBundle, BUNDLE_ANNOTATION},
+        artifact_canonical,
+        artifact_diff::{self, FieldDiff},
+        artifact_export, artifact_lint,
+        artifact_preflight::{self, PreflightReport},
+        artifact_prune::{self, PruneCandidate},
+        artifact_validation,
+    };
+
+    /// Kinds that are always derived from other objects and never belong in a bundle.
+    pub const DERIVED_KINDS: [&str; 6] = [
+        "Event",
+        "Endpoints",
+        "EndpointSlice",
+        "ControllerRevision",
+        "Lease",
+        "PodMetrics",
+    ];
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct AdoptedResource {
+        pub kind: String,
+        pub api_version: String,
+        pub name: String,
+        pub namespace: Option<String>,
+        pub annotated: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct AdoptResult {
+        pub bundle: String,
+        pub resources: Vec<AdoptedResource>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct FanOutTarget {
+        pub cluster: String,
+        pub namespace: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct FanOutResult {
+        pub target: FanOutTarget,
+        pub connected: bool,
+        pub success: bool,
+        pub preflight: Option<PreflightReport>,
+        /// The apply order, included for dry runs.
+        pub order: Option<Vec<OrderedManifest>>,
+        pub outcomes: Vec<ApplyOutcome>,
+        /// Objects applied from an earlier version of the bundle that it no
+        /// longer contains. Use `PruneBundle` to remove them.
+        pub prune_candidates: Option<Vec<PruneCandidate>>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DriftReport {
+        pub kind: Option<String>,
+        pub name: Option<String>,
+        pub namespace: Option<String>,
+        pub exists: bool,
+        pub drifted: bool,
+        pub diffs: Vec<FieldDiff>,
+        pub error: Option<String>,
+    }
+
+    fn resolve_manifests(
+        handle: &tauri::AppHandle,
+        bundle: &Option<String>,
+        manifests: &Option<Vec<Value>>,
+    ) -> Result<Vec<Value>, KubiousError> {
+        match (bundle, manifests) {
+            (Some(name), None) => Ok(artifact_bundle::load_bundle(handle, name)?.manifests),
+            (None, Some(manifests)) => Ok(manifests.clone()),
+            _ => Err("Exactly one of bundle or manifests is required".into()),
+        }
+    }
+
+    pub fn is_default_object(kind: &str, name: &str) -> bool {
+        matches!(
+            (kind, name),
+            ("ServiceAccount", "default") | ("ConfigMap", "kube-root-ca.crt")
+        )
+    }
+
+    fn owned_by(object: &DynamicObject, owner: &str) -> bool {
+        object
+            .metadata
+            .owner_references
+            .as_ref()
+            .map(|refs| refs.iter().any(|r| r.name == owner || r.uid == owner))
+            .unwrap_or(false)
+    }
+
+    pub fn has_controller(object: &DynamicObject) -> bool {
+        object
+            .metadata
+            .owner_references
+            .as_ref()
+            .map(|refs| refs.iter().any(|r| r.controller.unwrap_or(false)))
+            .unwrap_or(false)
+    }
+
+    /// Secrets the cluster or Helm writes for itself: service account
+    /// tokens and Helm's release records.
+    fn is_generated_secret(kind: &str, object: &DynamicObject) -> bool {
+        kind == "Secret"
+            && matches!(
+                object.data["type"].as_str(),
+                Some("kubernetes.io/service-account-token") | Some("helm.sh/release.v1")
+            )
+    }
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
     #[serde(tag = "command")]
-    pub enum ArtifactsCommand {}
-    impl CommandHandler for ArtifactsCommand {}
-}
\ No newline at end of file
+    pub enum ArtifactsCommand {
+        ListBundles {},
+        GetBundle {
+            name: String,
+        },
+        DeleteBundle {
+            name: String,
+        },
+        Adopt {
+            bundle: String,
+            description: Option<String>,
+            namespace: Option<String>,
+            label_selector: Option<String>,
+            owner: Option<String>,
+            kinds: Option<Vec<String>>,
+            annotate: bool,
+        },
+        FanOutApply {
+            bundle: Option<String>,
+            manifests: Option<Vec<Value>>,
+            targets: Vec<FanOutTarget>,
+            field_manager: Option<String>,
+            dry_run: bool,
+            stop_on_failure: bool,
+            #[serde(default)]
+            preflight: bool,
+            /// Apply in the given order instead of dependency order.
+            #[serde(default)]
+            preserve_order: bool,
+        },
+        Preflight {
+            bundle: Option<String>,
+            manifests: Option<Vec<Value>>,
+            namespace: Option<String>,
+        },
+        CheckDrift {
+            bundle: String,
+        },
+        PruneBundle {
+            bundle: String,
+            cluster: Option<String>,
+            namespace: Option<String>,
+            /// Ids from a dry run to delete; omit to only list candidates.
+            confirm: Option<Vec<String>>,
+        },
+        /// Re-applies a manifest whose apply was rejected with conflicts,
+        /// forcing only the listed fields.
+        ResolveApplyConflicts {
+            manifest: Value,
+            cluster: Option<String>,
+            namespace: Option<String>,
+            field_manager: Option<String>,
+            force_fields: Vec<String>,
+            #[serde(default)]
+            dry_run: bool,
+        },
+        /// Server-side applies raw YAML, which may hold several documents.
+        ApplyManifest {
+            yaml: String,
+            cluster: Option<String>,
+            namespace: Option<String>,
+            field_manager: Option<String>,
+            #[serde(default)]
+            dry_run: bool,
+        },
+        /// Diffs a live object against a server-side dry-run apply of
+        /// `manifest`.
+        DiffManifest {
+            manifest: Value,
+            cluster: Option<String>,
+            namespace: Option<String>,
+            field_manager: Option<String>,
+            #[serde(default)]
+            force: bool,
+        },
+        /// Exports a live object as YAML, writing it to `path` if given.
+        ExportResource {
+            resource: ResourceRef,
+            cluster: Option<String>,
+            path: Option<String>,
+        },
+        ExportNamespace {
+            namespace: String,
+            cluster: Option<String>,
+            kinds: Option<Vec<String>>,
+            path: Option<String>,
+        },
+        /// Runs the built-in lint rules on manifests or a bundle.
+        LintManifests {
+            bundle: Option<String>,
+            manifests: Option<Vec<Value>>,
+        },
+        LintResource {
+            resource: ResourceRef,
+            cluster: Option<String>,
+        },
+        /// Lints the workloads, Jobs and standalone Pods of a namespace.
+        LintNamespace {
+            namespace: String,
+            cluster: Option<String>,
+        },
+        ListLintRules {},
+        SetLintRule {
+            id: String,
+            enabled: bool,
+        },
+        CacheSchemas {},
+        ValidateManifests {
+            bundle: Option<String>,
+            manifests: Option<Vec<Value>>,
+            cluster: Option<String>,
+        },
+    }
+
+    impl CommandHandler for ArtifactsCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            match self {
+                ArtifactsCommand::ListBundles {} => {
+                    self.wrap_in_value(artifact_bundle::list_bundles(handle))
+                }
+                ArtifactsCommand::GetBundle { name } => {
+                    self.wrap_in_value(artifact_bundle::load_bundle(handle, name))
+                }
+                ArtifactsCommand::DeleteBundle { name } => {
+                    self.wrap_in_value(artifact_bundle::delete_bundle(handle, name))
+                }
+                ArtifactsCommand::Adopt {
+                    bundle,
+                    description,
+                    namespace,
+                    label_selector,
+                    owner,
+                    kinds,
+                    annotate,
+                } => {
+                    if namespace.is_none() && label_selector.is_none() && owner.is_none() {
+                        return Err("A namespace, label selector, or owner is required".into());
+                    }
+                    if artifact_bundle::bundle_exists(handle, bundle) {
+                        return Err("Bundle already exists".into());
+                    }
+                    let state = handle.state::<AppState>();
+                    let Some(client) = state.client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let source = state.get_current_config().map(|(key, _)| key);
+                    let mut created = ArtifactBundle::new(bundle, description.clone(), source);
+                    let mut adopted: Vec<(ApiResource, AdoptedResource)> = Vec::new();
+
+                    let discovery = Discovery::new(client.clone())
+                        .run()
+                        .await
+                        .context("Failed to run API discovery.")?;
+                    let mut params = ListParams::default();
+                    if let Some(selector) = label_selector {
+                        params = params.labels(selector.as_str());
+                    }
+
+                    for group in discovery.groups() {
+                        for (resource, caps) in group.recommended_resources() {
+                            if !caps.supports_operation(verbs::LIST)
+                                || DERIVED_KINDS.contains(&resource.kind.as_str())
+                            {
+                                continue;
+                            }
+                            if let Some(kinds) = kinds {
+                                if !kinds.contains(&resource.kind) {
+                                    continue;
+                                }
+                            }
+                            let api: Api<DynamicObject> = match (&caps.scope, namespace) {
+                                (Scope::Namespaced, Some(ns)) => {
+                                    Api::namespaced_with(client.clone(), ns.as_str(), &resource)
+                                }
+                                (Scope::Namespaced, None) => {
+                                    Api::all_with(client.clone(), &resource)
+                                }
+                                (Scope::Cluster, _) if label_selector.is_some() => {
+                                    Api::all_with(client.clone(), &resource)
+                                }
+                                (Scope::Cluster, _) => continue,
+                            };
+                            let Ok(objects) = api.list(&params).await else {
+                                continue;
+                            };
+
+                            for mut object in objects.items {
+                                let name = object.metadata.name.clone().unwrap_or_default();
+                                if is_default_object(resource.kind.as_str(), name.as_str())
+                                    || is_generated_secret(resource.kind.as_str(), &object)
+                                {
+                                    continue;
+                                }
+                                match owner {
+                                    Some(owner) if !owned_by(&object, owner) => continue,
+                                    None if has_controller(&object) => continue,
+                                    _ => {}
+                                }
+
+                                object.types = Some(TypeMeta {
+                                    api_version: resource.api_version.clone(),
+                                    kind: resource.kind.clone(),
+                                });
+                                let mut manifest = serde_json::to_value(&object)
+                                    .or(Err("Failed to serialize resource.".to_string()))?;
+                                artifact_bundle::clean_manifest(&mut manifest);
+                                created.manifests.push(manifest);
+
+                                adopted.push((
+                                    resource.clone(),
+                                    AdoptedResource {
+                                        kind: resource.kind.clone(),
+                                        api_version: resource.api_version.clone(),
+                                        name,
+                                        namespace: object.metadata.namespace.clone(),
+                                        annotated: false,
+                                    },
+                                ));
+                            }
+                        }
+                    }
+
+                    if created.manifests.is_empty() {
+                        return Err("No resources matched the selection".into());
+                    }
+                    created.manifests = artifact_apply::dependency_order(&created.manifests).0;
+                    artifact_bundle::save_bundle(handle, &created)?;
+
+                    // Only mark objects once the bundle holding them is saved.
+                    if *annotate {
+                        let patch = json!({
+                            "metadata": {"annotations": {BUNDLE_ANNOTATION: bundle}}
+                        });
+                        for (resource, adopted) in adopted.iter_mut() {
+                            let target: Api<DynamicObject> = match &adopted.namespace {
+                                Some(ns) => {
+                                    Api::namespaced_with(client.clone(), ns.as_str(), resource)
+                                }
+                                None => Api::all_with(client.clone(), resource),
+                            };
+                            adopted.annotated = target
+                                .patch(
+                                    adopted.name.as_str(),
+                                    &PatchParams::default(),
+                                    &Patch::Merge(&patch),
+                                )
+                                .await
+                                .is_ok();
+                        }
+                    }
+                    self.wrap_in_value(Ok(AdoptResult {
+                        bundle: bundle.clone(),
+                        resources: adopted.into_iter().map(|(_, adopted)| adopted).collect(),
+                    }))
+                }
+                ArtifactsCommand::FanOutApply {
+                    bundle,
+                    manifests,
+                    targets,
+                    field_manager,
+                    dry_run,
+                    stop_on_failure,
+                    preflight,
+                    preserve_order,
+                } => {
+                    let mut manifests = resolve_manifests(handle, bundle, manifests)?;
+                    if let Some(name) = bundle {
+                        for manifest in manifests.iter_mut() {
+                            artifact_bundle::track_manifest(manifest, name);
+                        }
+                    }
+                    let loaded = match bundle {
+                        Some(name) => Some(artifact_bundle::load_bundle(handle, name)?),
+                        None => None,
+                    };
+                    let order = if *preserve_order {
+                        None
+                    } else {
+                        let (sorted, order) = artifact_apply::dependency_order(&manifests);
+                        manifests = sorted;
+                        Some(order)
+                    };
+                    let state = handle.state::<AppState>();
+                    let mut results: Vec<FanOutResult> = Vec::new();
+
+                    for target in targets {
+                        let Some(client) = state.client_for(target.cluster.as_str()).await else {
+                            results.push(FanOutResult {
+                                target: target.clone(),
+                                connected: false,
+                                success: false,
+                                preflight: None,
+                                order: None,
+                                outcomes: Vec::new(),
+                                prune_candidates: None,
+                            });
+                            continue;
+                        };
+                        let report = if *preflight {
+                            Some(
+                                artifact_preflight::preflight(
+                                    client.clone(),
+                                    &manifests,
+                                    target.namespace.clone(),
+                                )
+                                .await
+                                .unwrap_or_else(|e| {
+                                    PreflightReport {
+                                        passed: false,
+                                        quotas: Vec::new(),
+                                        capacity: Vec::new(),
+                                        warnings: vec![format!("Preflight failed: {}", e)],
+                                    }
+                                }),
+                            )
+                        } else {
+                            None
+                        };
+                        let options = ApplyOptions {
+                            namespace: target.namespace.clone(),
+                            field_manager: field_manager.clone(),
+                            dry_run: *dry_run,
+                            force: false,
+                        };
+                        let outcomes = artifact_apply::apply_manifests(
+                            client.clone(),
+                            &manifests,
+                            &options,
+                            *stop_on_failure,
+                        )
+                        .await;
+                        let prune_candidates = match &loaded {
+                            Some(loaded) => artifact_prune::prune(
+                                client,
+                                loaded,
+                                target.namespace.clone(),
+                                None,
+                            )
+                            .await
+                            .ok()
+                            .map(|report| report.candidates),
+                            None => None,
+                        };
+                        results.push(FanOutResult {
+                            target: target.clone(),
+                            connected: true,
+                            success: outcomes.iter().all(|o| o.success),
+                            preflight: report,
+                            order: order.clone().filter(|_| *dry_run),
+                            outcomes,
+                            prune_candidates,
+                        });
+                    }
+
+                    self.wrap_in_value(Ok(results))
+                }
+                ArtifactsCommand::Preflight {
+                    bundle,
+                    manifests,
+                    namespace,
+                } => {
+                    let manifests = resolve_manifests(handle, bundle, manifests)?;
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        artifact_preflight::preflight(client, &manifests, namespace.clone()).await,
+                    )
+                }
+                ArtifactsCommand::CheckDrift { bundle } => {
+                    let loaded = artifact_bundle::load_bundle(handle, bundle)?;
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) =
+                        (state.client().await, state.get_current_config())
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let mut reports: Vec<DriftReport> = Vec::new();
+
+                    for manifest in &loaded.manifests {
+                        let mut report = DriftReport {
+                            kind: manifest["kind"].as_str().map(|s| s.to_string()),
+                            name: manifest["metadata"]["name"].as_str().map(|s| s.to_string()),
+                            namespace: None,
+                            exists: false,
+                            drifted: false,
+                            diffs: Vec::new(),
+                            error: None,
+                        };
+                        let resolved =
+                            match artifact_apply::resolve_manifest(client.clone(), manifest, None)
+                                .await
+                            {
+                                Ok(resolved) => resolved,
+                                Err(e) => {
+                                    report.error = Some(e.to_string());
+                                    reports.push(report);
+                                    continue;
+                                }
+                            };
+                        report.namespace = resolved.namespace.clone();
+
+                        match resolved.api.get_opt(resolved.name.as_str()).await {
+                            Ok(Some(live)) => {
+                                let rules = state.diff_ignore_rules_for(
+                                    cluster.as_str(),
+                                    resolved.resource.kind.as_str(),
+                                );
+                                let mut live = serde_json::to_value(&live)
+                                    .or(Err("Failed to serialize resource.".to_string()))?;
+                                let mut desired = manifest.clone();
+                                artifact_canonical::canonicalize(&mut live);
+                                artifact_canonical::canonicalize(&mut desired);
+                                report.exists = true;
+                                report.diffs =
+                                    artifact_diff::diff_values(&live, &desired, false, &rules);
+                                report.drifted = !report.diffs.is_empty();
+                            }
+                            Ok(None) => report.drifted = true,
+                            Err(e) => report.error = Some(e.to_string()),
+                        }
+                        reports.push(report);
+                    }
+
+                    self.wrap_in_value(Ok(reports))
+                }
+                ArtifactsCommand::PruneBundle {
+                    bundle,
+                    cluster,
+                    namespace,
+                    confirm,
+                } => {
+                    let loaded = artifact_bundle::load_bundle(handle, bundle)?;
+                    let state = handle.state::<AppState>();
+                    let client = match cluster {
+                        Some(cluster) => state.client_for(cluster.as_str()).await,
+                        None => state.client().await,
+                    };
+                    let Some(client) = client else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        artifact_prune::prune(client, &loaded, namespace.clone(), confirm.clone())
+                            .await,
+                    )
+                }
+                ArtifactsCommand::ResolveApplyConflicts {
+                    manifest,
+                    cluster,
+                    namespace,
+                    field_manager,
+                    force_fields,
+                    dry_run,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let client = match cluster {
+                        Some(cluster) => state.client_for(cluster.as_str()).await,
+                        None => state.client().await,
+                    };
+                    let Some(client) = client else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let options = ApplyOptions {
+                        namespace: namespace.clone(),
+                        field_manager: field_manager.clone(),
+                        dry_run: *dry_run,
+                        force: false,
+                    };
+                    self.wrap_in_value(Ok(artifact_apply::apply_forcing(
+                        client,
+                        manifest,
+                        &options,
+                        force_fields,
+                    )
+                    .await))
+                }
+                ArtifactsCommand::ApplyManifest {
+                    yaml,
+                    cluster,
+                    namespace,
+                    field_manager,
+                    dry_run,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let client = match cluster {
+                        Some(cluster) => state.client_for(cluster.as_str()).await,
+                        None => state.client().await,
+                    };
+                    let Some(client) = client else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let options = ApplyOptions {
+                        namespace: namespace.clone(),
+                        field_manager: field_manager.clone(),
+                        dry_run: *dry_run,
+                        force: false,
+                    };
+                    self.wrap_in_value(Ok(artifact_apply::apply_yaml(client, yaml, &options).await))
+                }
+                ArtifactsCommand::DiffManifest {
+                    manifest,
+                    cluster,
+                    namespace,
+                    field_manager,
+                    force,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let Some(cluster) = cluster
+                        .clone()
+                        .or(state.get_current_config().map(|(key, _)| key))
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let Some(client) = state.client_for(cluster.as_str()).await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let rules = state.diff_ignore_rules_for(
+                        cluster.as_str(),
+                        manifest["kind"].as_str().unwrap_or_default(),
+                    );
+                    let options = ApplyOptions {
+                        namespace: namespace.clone(),
+                        field_manager: field_manager.clone(),
+                        dry_run: true,
+                        force: *force,
+                    };
+                    self.wrap_in_value(
+                        artifact_apply::diff_manifest(client, manifest, &options, &rules).await,
+                    )
+                }
+                ArtifactsCommand::ExportResource {
+                    resource,
+                    cluster,
+                    path,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let client = match cluster {
+                        Some(cluster) => state.client_for(cluster.as_str()).await,
+                        None => state.client().await,
+                    };
+                    let Some(client) = client else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        artifact_export::export_resource(client, resource, path.clone()).await,
+                    )
+                }
+                ArtifactsCommand::ExportNamespace {
+                    namespace,
+                    cluster,
+                    kinds,
+                    path,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let client = match cluster {
+                        Some(cluster) => state.client_for(cluster.as_str()).await,
+                        None => state.client().await,
+                    };
+                    let Some(client) = client else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        artifact_export::export_namespace(
+                            client,
+                            namespace.as_str(),
+                            kinds,
+                            path.clone(),
+                        )
+                        .await,
+                    )
+                }
+                ArtifactsCommand::CacheSchemas {} => {
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) =
+                        (state.client().await, state.get_current_config())
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        artifact_validation::cache_schemas(handle, client, cluster).await,
+                    )
+                }
+                ArtifactsCommand::LintManifests { bundle, manifests } => {
+                    let manifests = resolve_manifests(handle, bundle, manifests)?;
+                    let disabled = handle
+                        .state::<AppState>()
+                        .get_settings()
+                        .lint_disabled_rules;
+                    self.wrap_in_value(Ok(artifact_lint::lint(&manifests, &disabled)))
+                }
+                ArtifactsCommand::LintResource { resource, cluster } => {
+                    let state = handle.state::<AppState>();
+                    let client = match cluster {
+                        Some(cluster) => state.client_for(cluster.as_str()).await,
+                        None => state.client().await,
+                    };
+                    let Some(client) = client else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let manifest = resource_access::get(client, resource).await?;
+                    let disabled = state.get_settings().lint_disabled_rules;
+                    self.wrap_in_value(Ok(artifact_lint::lint(&[manifest], &disabled)))
+                }
+                ArtifactsCommand::LintNamespace { namespace, cluster } => {
+                    let state = handle.state::<AppState>();
+                    let client = match cluster {
+                        Some(cluster) => state.client_for(cluster.as_str()).await,
+                        None => state.client().await,
+                    };
+                    let Some(client) = client else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let manifests = artifact_lint::live_manifests(client, namespace).await?;
+                    let disabled = state.get_settings().lint_disabled_rules;
+                    self.wrap_in_value(Ok(artifact_lint::lint(&manifests, &disabled)))
+                }
+                ArtifactsCommand::ListLintRules {} => {
+                    let disabled = handle
+                        .state::<AppState>()
+                        .get_settings()
+                        .lint_disabled_rules;
+                    self.wrap_in_value(Ok(artifact_lint::rules(&disabled)))
+                }
+                ArtifactsCommand::SetLintRule { id, enabled } => {
+                    if !artifact_lint::is_rule(id) {
+                        return Err("Unknown lint rule".into());
+                    }
+                    let state = handle.state::<AppState>();
+                    state.set_lint_rule_enabled(id, *enabled);
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(artifact_lint::rules(
+                        &state.get_settings().lint_disabled_rules,
+                    )))
+                }
+                ArtifactsCommand::ValidateManifests {
+                    bundle,
+                    manifests,
+                    cluster,
+                } => {
+                    let manifests = resolve_manifests(handle, bundle, manifests)?;
+                    let cluster = cluster.clone().or(handle
+                        .state::<AppState>()
+                        .get_current_config()
+                        .map(|(key, _)| key));
+                    self.wrap_in_value(Ok(artifact_validation::validate(
+                        handle, &manifests, cluster,
+                    )))
+                }
+            }
+        }
+    }
+}
+
+mod apply;
+pub use apply::artifact_apply;
+mod bundle;
+pub use bundle::artifact_bundle;
+mod canonical;
+pub use canonical::artifact_canonical;
+mod diff;
+pub use diff::artifact_diff;
+mod export;
+pub use export::artifact_export;
+mod lint;
+pub use lint::artifact_lint;
+mod preflight;
+pub use preflight::artifact_preflight;
+mod prune;
+pub use prune::artifact_prune;
+mod validate;
+pub use validate::artifact_validation;
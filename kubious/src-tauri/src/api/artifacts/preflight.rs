@@ -0,0 +1,341 @@
+pub mod artifact_preflight {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::api::core::v1::{Node, Pod, PodSpec, ResourceQuota};
+    use kube::{api::ListParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::artifacts::artifact_apply::resolve_manifest,
+        util::{
+            kube_quantity::quantity_value,
+            kubious_error::{ErrorContext, KubiousError},
+            resource_accounting::{
+                effective_limits, effective_requests, pod_is_active, pod_requests,
+            },
+        },
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct QuotaCheck {
+        pub namespace: String,
+        pub quota: String,
+        pub resource: String,
+        pub hard: f64,
+        pub used: f64,
+        pub requested: f64,
+        pub exceeded: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CapacityCheck {
+        pub resource: String,
+        pub allocatable: f64,
+        pub free: f64,
+        pub requested: f64,
+        pub exceeded: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PreflightReport {
+        pub passed: bool,
+        pub quotas: Vec<QuotaCheck>,
+        pub capacity: Vec<CapacityCheck>,
+        pub warnings: Vec<String>,
+    }
+
+    /// Resources a set of manifests adds to one namespace, net of what the
+    /// objects they replace already hold.
+    #[derive(Clone, Debug, Default)]
+    struct Demand {
+        pods: f64,
+        requests: BTreeMap<String, f64>,
+        limits: BTreeMap<String, f64>,
+    }
+
+    impl Demand {
+        fn add(&mut self, spec: &PodSpec, replicas: f64) {
+            self.pods += replicas;
+            for (name, value) in effective_requests(spec) {
+                *self.requests.entry(name).or_insert(0.0) += value * replicas;
+            }
+            for (name, value) in effective_limits(spec) {
+                *self.limits.entry(name).or_insert(0.0) += value * replicas;
+            }
+        }
+
+        /// The amount a quota key would be charged, for the compute keys
+        /// quotas track (`pods`, `cpu`, `requests.*`, `limits.*`).
+        fn charged(&self, key: &str) -> Option<f64> {
+            if key == "pods" {
+                return Some(self.pods);
+            }
+            if let Some(resource) = key.strip_prefix("limits.") {
+                return Some(self.limits.get(resource).copied().unwrap_or(0.0));
+            }
+            let resource = key.strip_prefix("requests.").unwrap_or(key);
+            if key.starts_with("requests.")
+                || matches!(resource, "cpu" | "memory" | "ephemeral-storage")
+            {
+                return Some(self.requests.get(resource).copied().unwrap_or(0.0));
+            }
+            None
+        }
+    }
+
+    /// Extracts the pod template of a workload manifest and how many pods it
+    /// will run. DaemonSets are counted once per schedulable node.
+    fn pod_template(manifest: &Value, daemon_nodes: usize) -> Option<(PodSpec, f64)> {
+        let spec = &manifest["spec"];
+        let (template, replicas) = match manifest["kind"].as_str()? {
+            "Pod" => (spec, 1),
+            "Deployment" | "StatefulSet" | "ReplicaSet" | "ReplicationController" => (
+                &spec["template"]["spec"],
+                spec["replicas"].as_i64().unwrap_or(1),
+            ),
+            "DaemonSet" => (&spec["template"]["spec"], daemon_nodes as i64),
+            "Job" => (
+                &spec["template"]["spec"],
+                spec["parallelism"].as_i64().unwrap_or(1),
+            ),
+            "CronJob" => (
+                &spec["jobTemplate"]["spec"]["template"]["spec"],
+                spec["jobTemplate"]["spec"]["parallelism"]
+                    .as_i64()
+                    .unwrap_or(1),
+            ),
+            _ => return None,
+        };
+        let template: PodSpec = serde_json::from_value(template.clone()).ok()?;
+        Some((template, replicas.max(0) as f64))
+    }
+
+    fn node_schedulable(node: &Node) -> bool {
+        let cordoned = node
+            .spec
+            .as_ref()
+            .and_then(|s| s.unschedulable)
+            .unwrap_or(false);
+        let ready = node
+            .status
+            .as_ref()
+            .and_then(|s| s.conditions.as_ref())
+            .and_then(|c| c.iter().find(|c| c.type_ == "Ready"))
+            .map(|c| c.status == "True")
+            .unwrap_or(false);
+        ready && !cordoned
+    }
+
+    /// Allocatable and free resources per schedulable node.
+    async fn node_headroom(
+        client: &Client,
+    ) -> Result<Vec<(BTreeMap<String, f64>, BTreeMap<String, f64>)>, KubiousError> {
+        let nodes = Api::<Node>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list nodes.")?;
+        let pods = Api::<Pod>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list pods.")?;
+
+        let mut used: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+        for pod in pods.items.iter().filter(|p| pod_is_active(p)) {
+            let Some(node) = pod.spec.as_ref().and_then(|s| s.node_name.clone()) else {
+                continue;
+            };
+            let totals = used.entry(node).or_default();
+            for (resource, value) in pod_requests(pod) {
+                *totals.entry(resource).or_insert(0.0) += value;
+            }
+        }
+
+        Ok(nodes
+            .items
+            .iter()
+            .filter(|n| node_schedulable(n))
+            .map(|node| {
+                let name = node.metadata.name.clone().unwrap_or_default();
+                let allocatable: BTreeMap<String, f64> = node
+                    .status
+                    .as_ref()
+                    .and_then(|s| s.allocatable.as_ref())
+                    .map(|a| {
+                        a.iter()
+                            .map(|(resource, quantity)| {
+                                (resource.clone(), quantity_value(quantity))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let node_used = used.get(&name).cloned().unwrap_or_default();
+                let free = allocatable
+                    .iter()
+                    .map(|(resource, value)| {
+                        (
+                            resource.clone(),
+                            value - node_used.get(resource).copied().unwrap_or(0.0),
+                        )
+                    })
+                    .collect();
+                (allocatable, free)
+            })
+            .collect())
+    }
+
+    fn fits(free: &BTreeMap<String, f64>, requests: &BTreeMap<String, f64>) -> bool {
+        requests
+            .iter()
+            .filter(|(_, value)| **value > 0.0)
+            .all(|(resource, value)| free.get(resource).copied().unwrap_or(0.0) >= *value)
+    }
+
+    /// Estimates whether applying `manifests` would leave pods Pending, by
+    /// comparing their requests with namespace quotas and node headroom.
+    /// Node selectors, affinity and taints are not considered, and quotas
+    /// restricted by scopes are skipped.
+    pub async fn preflight(
+        client: Client,
+        manifests: &[Value],
+        namespace: Option<String>,
+    ) -> Result<PreflightReport, KubiousError> {
+        let headroom = node_headroom(&client).await?;
+        let mut warnings: Vec<String> = Vec::new();
+        let mut demands: BTreeMap<String, Demand> = BTreeMap::new();
+        let mut templates: Vec<(String, BTreeMap<String, f64>)> = Vec::new();
+
+        for manifest in manifests {
+            let Some((spec, replicas)) = pod_template(manifest, headroom.len()) else {
+                continue;
+            };
+            let resolved = match resolve_manifest(client.clone(), manifest, namespace.clone()).await
+            {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    warnings.push(e.to_string());
+                    continue;
+                }
+            };
+            let label = format!("{}/{}", resolved.resource.kind, resolved.name);
+            let demand = demands
+                .entry(resolved.namespace.clone().unwrap_or_default())
+                .or_default();
+            demand.add(&spec, replicas);
+
+            // Updating an existing workload only charges the difference.
+            if let Ok(Some(live)) = resolved.api.get_opt(resolved.name.as_str()).await {
+                if let Ok(live) = serde_json::to_value(&live) {
+                    let mut typed = live.clone();
+                    typed["kind"] = Value::String(resolved.resource.kind.clone());
+                    if let Some((live_spec, live_replicas)) = pod_template(&typed, headroom.len()) {
+                        demand.add(&live_spec, -live_replicas);
+                    }
+                }
+            }
+            if replicas > 0.0 {
+                templates.push((label, effective_requests(&spec)));
+            }
+        }
+
+        let mut quotas: Vec<QuotaCheck> = Vec::new();
+        for (ns, demand) in &demands {
+            let Ok(list) = Api::<ResourceQuota>::namespaced(client.clone(), ns.as_str())
+                .list(&ListParams::default())
+                .await
+            else {
+                warnings.push(format!("Could not read resource quotas in {}", ns));
+                continue;
+            };
+            for quota in list.items {
+                let scoped = quota
+                    .spec
+                    .as_ref()
+                    .map(|s| s.scopes.is_some() || s.scope_selector.is_some())
+                    .unwrap_or(false);
+                if scoped {
+                    continue;
+                }
+                let status = quota.status.clone().unwrap_or_default();
+                let used = status.used.unwrap_or_default();
+                for (key, hard) in status.hard.unwrap_or_default() {
+                    let Some(requested) = demand.charged(key.as_str()) else {
+                        continue;
+                    };
+                    if requested <= 0.0 {
+                        continue;
+                    }
+                    let hard = quantity_value(&hard);
+                    let used = used.get(&key).map(quantity_value).unwrap_or(0.0);
+                    let exceeded = used + requested > hard;
+                    if exceeded {
+                        warnings.push(format!(
+                            "Quota {} in {} allows {} more {} but {} is requested",
+                            quota.metadata.name.clone().unwrap_or_default(),
+                            ns,
+                            (hard - used).max(0.0),
+                            key,
+                            requested
+                        ));
+                    }
+                    quotas.push(QuotaCheck {
+                        namespace: ns.clone(),
+                        quota: quota.metadata.name.clone().unwrap_or_default(),
+                        resource: key,
+                        hard,
+                        used,
+                        requested,
+                        exceeded,
+                    });
+                }
+            }
+        }
+
+        let mut total: BTreeMap<String, f64> = BTreeMap::new();
+        for demand in demands.values() {
+            for (resource, value) in &demand.requests {
+                *total.entry(resource.clone()).or_insert(0.0) += value;
+            }
+        }
+        let mut capacity: Vec<CapacityCheck> = Vec::new();
+        for (resource, requested) in total.into_iter().filter(|(_, v)| *v > 0.0) {
+            let allocatable: f64 = headroom
+                .iter()
+                .map(|(a, _)| a.get(&resource).copied().unwrap_or(0.0))
+                .sum();
+            let free: f64 = headroom
+                .iter()
+                .map(|(_, f)| f.get(&resource).copied().unwrap_or(0.0).max(0.0))
+                .sum();
+            let exceeded = requested > free;
+            if exceeded {
+                warnings.push(format!(
+                    "Schedulable nodes have {} {} free but {} is requested",
+                    free, resource, requested
+                ));
+            }
+            capacity.push(CapacityCheck {
+                resource,
+                allocatable,
+                free,
+                requested,
+                exceeded,
+            });
+        }
+        for (label, requests) in &templates {
+            if !headroom.iter().any(|(_, free)| fits(free, requests)) {
+                warnings.push(format!(
+                    "No schedulable node currently has room for a single pod of {}",
+                    label
+                ));
+            }
+        }
+
+        Ok(PreflightReport {
+            passed: warnings.is_empty(),
+            quotas,
+            capacity,
+            warnings,
+        })
+    }
+}
@@ -0,0 +1,184 @@
+pub mod artifact_prune {
+    use kube::{
+        api::{DeleteParams, DynamicObject, ListParams},
+        discovery::{verbs, Discovery, Scope},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::{
+            artifacts::artifact_bundle::{ArtifactBundle, BUNDLE_LABEL},
+            resources::crd_deletion::{self, CRD_DELETE_REFUSED},
+            views::resource_access::ResourceRef,
+        },
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PruneCandidate {
+        /// `apiVersion/kind/namespace/name`, passed back to confirm deletion.
+        pub id: String,
+        pub api_version: String,
+        pub kind: String,
+        pub name: String,
+        pub namespace: Option<String>,
+        pub deleted: bool,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PruneReport {
+        pub bundle: String,
+        pub dry_run: bool,
+        pub candidates: Vec<PruneCandidate>,
+    }
+
+    fn group_of(api_version: &str) -> &str {
+        api_version
+            .rsplit_once('/')
+            .map(|(group, _)| group)
+            .unwrap_or("")
+    }
+
+    /// Whether a live object still has a manifest in the bundle. Manifests
+    /// without a namespace match in any namespace, since the target
+    /// namespace is chosen at apply time.
+    fn in_bundle(
+        manifests: &[Value],
+        group: &str,
+        kind: &str,
+        name: &str,
+        namespace: &Option<String>,
+    ) -> bool {
+        manifests.iter().any(|m| {
+            group_of(m["apiVersion"].as_str().unwrap_or_default()) == group
+                && m["kind"].as_str() == Some(kind)
+                && m["metadata"]["name"].as_str() == Some(name)
+                && match (m["metadata"]["namespace"].as_str(), namespace) {
+                    (Some(expected), Some(actual)) => expected == actual,
+                    _ => true,
+                }
+        })
+    }
+
+    fn has_controller(object: &DynamicObject) -> bool {
+        object
+            .metadata
+            .owner_references
+            .as_ref()
+            .map(|refs| refs.iter().any(|r| r.controller.unwrap_or(false)))
+            .unwrap_or(false)
+    }
+
+    /// Finds live objects labelled as applied by `bundle` that the bundle no
+    /// longer contains. Nothing is deleted unless `confirm` lists the ids of
+    /// candidates from an earlier dry run; candidates not listed are kept.
+    /// CRDs are never deleted here and report an error instead.
+    pub async fn prune(
+        client: Client,
+        bundle: &ArtifactBundle,
+        namespace: Option<String>,
+        confirm: Option<Vec<String>>,
+    ) -> Result<PruneReport, KubiousError> {
+        let discovery = Discovery::new(client.clone())
+            .run()
+            .await
+            .context("Failed to run API discovery.")?;
+        let params =
+            ListParams::default().labels(format!("{}={}", BUNDLE_LABEL, bundle.name).as_str());
+        let mut candidates: Vec<PruneCandidate> = Vec::new();
+
+        for group in discovery.groups() {
+            for (resource, caps) in group.recommended_resources() {
+                if !caps.supports_operation(verbs::LIST) {
+                    continue;
+                }
+                let api: Api<DynamicObject> = match (&caps.scope, &namespace) {
+                    (Scope::Namespaced, Some(ns)) => {
+                        Api::namespaced_with(client.clone(), ns.as_str(), &resource)
+                    }
+                    _ => Api::all_with(client.clone(), &resource),
+                };
+                let Ok(objects) = api.list(&params).await else {
+                    continue;
+                };
+                for object in objects.items {
+                    let name = object.metadata.name.clone().unwrap_or_default();
+                    let object_ns = object.metadata.namespace.clone();
+                    if has_controller(&object)
+                        || in_bundle(
+                            &bundle.manifests,
+                            resource.group.as_str(),
+                            resource.kind.as_str(),
+                            name.as_str(),
+                            &object_ns,
+                        )
+                    {
+                        continue;
+                    }
+                    candidates.push(PruneCandidate {
+                        id: format!(
+                            "{}/{}/{}/{}",
+                            resource.api_version,
+                            resource.kind,
+                            object_ns.clone().unwrap_or_default(),
+                            name
+                        ),
+                        api_version: resource.api_version.clone(),
+                        kind: resource.kind.clone(),
+                        name,
+                        namespace: object_ns,
+                        deleted: false,
+                        error: None,
+                    });
+                }
+            }
+        }
+
+        let Some(confirmed) = confirm else {
+            return Ok(PruneReport {
+                bundle: bundle.name.clone(),
+                dry_run: true,
+                candidates,
+            });
+        };
+        for candidate in candidates.iter_mut().filter(|c| confirmed.contains(&c.id)) {
+            let Some((resource, _)) = discovery
+                .groups()
+                .flat_map(|g| g.recommended_resources())
+                .find(|(r, _)| r.api_version == candidate.api_version && r.kind == candidate.kind)
+            else {
+                continue;
+            };
+            let target = ResourceRef {
+                group: resource.group.clone(),
+                version: resource.version.clone(),
+                kind: resource.kind.clone(),
+                namespace: candidate.namespace.clone(),
+                name: candidate.name.clone(),
+            };
+            if crd_deletion::is_crd(&target) {
+                candidate.error = Some(CRD_DELETE_REFUSED.to_string());
+                continue;
+            }
+            let api: Api<DynamicObject> = match &candidate.namespace {
+                Some(ns) => Api::namespaced_with(client.clone(), ns.as_str(), &resource),
+                None => Api::all_with(client.clone(), &resource),
+            };
+            match api
+                .delete(candidate.name.as_str(), &DeleteParams::background())
+                .await
+            {
+                Ok(_) => candidate.deleted = true,
+                Err(e) => candidate.error = Some(e.to_string()),
+            }
+        }
+        Ok(PruneReport {
+            bundle: bundle.name.clone(),
+            dry_run: false,
+            candidates,
+        })
+    }
+}
@@ -0,0 +1,471 @@
+pub mod artifact_validation {
+    use std::{
+        fs::{self, File},
+        io::{Read, Write},
+        path::PathBuf,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use http::Request;
+    use k8s_openapi::{
+        api::{
+            apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
+            autoscaling::v2::HorizontalPodAutoscaler,
+            batch::v1::{CronJob, Job},
+            core::v1::{
+                ConfigMap, LimitRange, Namespace, PersistentVolume, PersistentVolumeClaim, Pod,
+                ResourceQuota, Secret, Service, ServiceAccount,
+            },
+            networking::v1::{Ingress, NetworkPolicy},
+            policy::v1::PodDisruptionBudget,
+            rbac::v1::{ClusterRole, ClusterRoleBinding, Role, RoleBinding},
+            storage::v1::StorageClass,
+        },
+        apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+    };
+    use kube::Client;
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use serde_json::{Map, Value};
+    use tauri::{AppHandle, Manager};
+
+    use crate::util::kubious_error::KubiousError;
+
+    /// Nesting beyond this is not validated, guarding against cyclic refs.
+    const MAX_DEPTH: usize = 64;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum SchemaSource {
+        /// Schemas compiled into the app for built-in kinds.
+        Bundled,
+        /// OpenAPI schemas previously cached from a cluster.
+        Cached,
+        None,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct SchemaIssue {
+        pub path: String,
+        pub message: String,
+        pub unknown_field: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ManifestValidation {
+        pub index: usize,
+        pub api_version: Option<String>,
+        pub kind: Option<String>,
+        pub name: Option<String>,
+        pub source: SchemaSource,
+        pub valid: bool,
+        pub issues: Vec<SchemaIssue>,
+    }
+
+    /// OpenAPI v3 component schemas of one cluster, indexed by
+    /// `group/version/Kind` (with an empty group for core kinds).
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    pub struct SchemaCache {
+        pub cluster: String,
+        pub fetched: u64,
+        pub index: Map<String, Value>,
+        pub schemas: Map<String, Value>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct SchemaCacheSummary {
+        pub cluster: String,
+        pub fetched: u64,
+        pub kinds: usize,
+    }
+
+    fn schemas_dir(handle: &AppHandle) -> Result<PathBuf, KubiousError> {
+        let root = handle
+            .path()
+            .app_data_dir()
+            .or(Err("Failed to resolve app data directory.".to_string()))?
+            .join("schemas");
+        if !root.exists() {
+            fs::create_dir_all(&root).or(Err("Failed to create schema directory.".to_string()))?;
+        }
+        Ok(root)
+    }
+
+    fn cache_path(handle: &AppHandle, cluster: &str) -> Result<PathBuf, KubiousError> {
+        let file: String = cluster
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Ok(schemas_dir(handle)?.join(format!("{}.json", file)))
+    }
+
+    fn load_cache(handle: &AppHandle, cluster: &str) -> Option<SchemaCache> {
+        let mut contents = String::new();
+        File::open(cache_path(handle, cluster).ok()?)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .ok()?;
+        serde_json::from_str(contents.as_str()).ok()
+    }
+
+    fn gvk_key(group: &str, version: &str, kind: &str) -> String {
+        format!("{}/{}/{}", group, version, kind)
+    }
+
+    fn manifest_key(manifest: &Value) -> Option<String> {
+        let api_version = manifest["apiVersion"].as_str()?;
+        let kind = manifest["kind"].as_str()?;
+        let (group, version) = api_version.rsplit_once('/').unwrap_or(("", api_version));
+        Some(gvk_key(group, version, kind))
+    }
+
+    /// Downloads the cluster's OpenAPI v3 documents and stores their
+    /// component schemas, so manifests can be validated without a connection.
+    pub async fn cache_schemas(
+        handle: &AppHandle,
+        client: Client,
+        cluster: String,
+    ) -> Result<SchemaCacheSummary, KubiousError> {
+        let fetch = |path: String| {
+            let client = client.clone();
+            async move {
+                let request = Request::get(path).body(Vec::new()).ok()?;
+                client.request::<Value>(request).await.ok()
+            }
+        };
+        let root = fetch("/openapi/v3".to_string())
+            .await
+            .ok_or("The cluster does not serve OpenAPI v3 schemas".to_string())?;
+        let mut cache = SchemaCache {
+            cluster: cluster.clone(),
+            fetched: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            ..SchemaCache::default()
+        };
+        for (path, entry) in root["paths"].as_object().into_iter().flatten() {
+            if !(path == "api/v1" || path.starts_with("apis/")) {
+                continue;
+            }
+            let Some(url) = entry["serverRelativeURL"].as_str() else {
+                continue;
+            };
+            let Some(document) = fetch(url.to_string()).await else {
+                continue;
+            };
+            for (name, schema) in document["components"]["schemas"]
+                .as_object()
+                .into_iter()
+                .flatten()
+            {
+                for gvk in schema["x-kubernetes-group-version-kind"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                {
+                    let key = gvk_key(
+                        gvk["group"].as_str().unwrap_or_default(),
+                        gvk["version"].as_str().unwrap_or_default(),
+                        gvk["kind"].as_str().unwrap_or_default(),
+                    );
+                    cache.index.insert(key, Value::String(name.clone()));
+                }
+                cache.schemas.insert(name.clone(), schema.clone());
+            }
+        }
+        if cache.index.is_empty() {
+            return Err("No schemas could be downloaded".into());
+        }
+
+        let serialized =
+            serde_json::to_string(&cache).or(Err("Failed to serialize schemas.".to_string()))?;
+        File::create(cache_path(handle, cluster.as_str())?)
+            .and_then(|mut file| file.write_all(serialized.as_bytes()))
+            .or(Err("Failed to write schema cache.".to_string()))?;
+        Ok(SchemaCacheSummary {
+            cluster,
+            fetched: cache.fetched,
+            kinds: cache.index.len(),
+        })
+    }
+
+    fn issue(path: &str, message: String) -> SchemaIssue {
+        SchemaIssue {
+            path: path.to_string(),
+            message,
+            unknown_field: false,
+        }
+    }
+
+    fn unknown(path: &str) -> SchemaIssue {
+        SchemaIssue {
+            path: path.to_string(),
+            message: "Unknown field".to_string(),
+            unknown_field: true,
+        }
+    }
+
+    fn child_path(path: &str, key: &str) -> String {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    }
+
+    /// Follows `$ref`s, including the `allOf: [{$ref}]` wrapping OpenAPI v3
+    /// uses to attach defaults and descriptions to references.
+    fn resolve<'a>(schema: &'a Value, schemas: &'a Map<String, Value>) -> &'a Value {
+        let reference = schema["$ref"].as_str().or_else(|| {
+            schema["allOf"]
+                .as_array()
+                .filter(|all| all.len() == 1)
+                .and_then(|all| all[0]["$ref"].as_str())
+        });
+        match reference
+            .and_then(|r| r.strip_prefix("#/components/schemas/"))
+            .and_then(|name| schemas.get(name))
+        {
+            Some(target) => resolve(target, schemas),
+            None => schema,
+        }
+    }
+
+    fn type_matches(value: &Value, expected: &str) -> bool {
+        match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "boolean" => value.is_boolean(),
+            _ => true,
+        }
+    }
+
+    fn check(
+        value: &Value,
+        schema: &Value,
+        schemas: &Map<String, Value>,
+        path: &str,
+        depth: usize,
+        issues: &mut Vec<SchemaIssue>,
+    ) {
+        let schema = resolve(schema, schemas);
+        if depth > MAX_DEPTH || value.is_null() {
+            return;
+        }
+        if schema["x-kubernetes-int-or-string"] == true {
+            if !(value.is_string() || value.is_i64() || value.is_u64()) {
+                issues.push(issue(path, "Expected an integer or string".to_string()));
+            }
+            return;
+        }
+        if let Some(expected) = schema["type"].as_str() {
+            if !type_matches(value, expected) {
+                issues.push(issue(path, format!("Expected {}", expected)));
+                return;
+            }
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                issues.push(issue(
+                    path,
+                    format!("Must be one of {}", Value::Array(allowed.clone())),
+                ));
+            }
+        }
+
+        if let Some(items) = value.as_array() {
+            if schema["items"].is_object() {
+                for (index, item) in items.iter().enumerate() {
+                    let item_path = format!("{}[{}]", path, index);
+                    check(
+                        item,
+                        &schema["items"],
+                        schemas,
+                        item_path.as_str(),
+                        depth + 1,
+                        issues,
+                    );
+                }
+            }
+            return;
+        }
+        let Some(object) = value.as_object() else {
+            return;
+        };
+        for required in schema["required"].as_array().into_iter().flatten() {
+            if let Some(field) = required.as_str() {
+                if !object.contains_key(field) {
+                    issues.push(issue(
+                        child_path(path, field).as_str(),
+                        "Required field is missing".to_string(),
+                    ));
+                }
+            }
+        }
+        let preserve_unknown = schema["x-kubernetes-preserve-unknown-fields"] == true;
+        let properties = schema["properties"].as_object();
+        for (key, child) in object {
+            let field_path = child_path(path, key);
+            if let Some(property) = properties.and_then(|p| p.get(key)) {
+                check(
+                    child,
+                    property,
+                    schemas,
+                    field_path.as_str(),
+                    depth + 1,
+                    issues,
+                );
+            } else if schema["additionalProperties"].is_object() {
+                check(
+                    child,
+                    &schema["additionalProperties"],
+                    schemas,
+                    field_path.as_str(),
+                    depth + 1,
+                    issues,
+                );
+            } else if properties.is_some() && !preserve_unknown {
+                issues.push(unknown(field_path.as_str()));
+            }
+        }
+    }
+
+    /// Reports fields of `original` that did not survive a round trip
+    /// through a typed struct, i.e. fields the type does not know.
+    fn dropped_fields(original: &Value, typed: &Value, path: &str, issues: &mut Vec<SchemaIssue>) {
+        match (original, typed) {
+            (Value::Object(original), Value::Object(typed)) => {
+                for (key, value) in original {
+                    let field_path = child_path(path, key);
+                    match typed.get(key) {
+                        Some(kept) => dropped_fields(value, kept, field_path.as_str(), issues),
+                        None if !value.is_null() => issues.push(unknown(field_path.as_str())),
+                        None => {}
+                    }
+                }
+            }
+            (Value::Array(original), Value::Array(typed)) => {
+                for (index, (value, kept)) in original.iter().zip(typed).enumerate() {
+                    dropped_fields(value, kept, format!("{}[{}]", path, index).as_str(), issues);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn typed_issues<K: DeserializeOwned + Serialize>(manifest: &Value) -> Vec<SchemaIssue> {
+        match serde_json::from_value::<K>(manifest.clone()) {
+            Ok(typed) => {
+                let mut issues = Vec::new();
+                if let Ok(round_trip) = serde_json::to_value(&typed) {
+                    dropped_fields(manifest, &round_trip, "", &mut issues);
+                }
+                issues
+            }
+            Err(e) => vec![issue("", e.to_string())],
+        }
+    }
+
+    /// Validates built-in kinds against the schemas compiled into the app.
+    fn bundled_issues(key: &str, manifest: &Value) -> Option<Vec<SchemaIssue>> {
+        Some(match key {
+            "/v1/ConfigMap" => typed_issues::<ConfigMap>(manifest),
+            "/v1/LimitRange" => typed_issues::<LimitRange>(manifest),
+            "/v1/Namespace" => typed_issues::<Namespace>(manifest),
+            "/v1/PersistentVolume" => typed_issues::<PersistentVolume>(manifest),
+            "/v1/PersistentVolumeClaim" => typed_issues::<PersistentVolumeClaim>(manifest),
+            "/v1/Pod" => typed_issues::<Pod>(manifest),
+            "/v1/ResourceQuota" => typed_issues::<ResourceQuota>(manifest),
+            "/v1/Secret" => typed_issues::<Secret>(manifest),
+            "/v1/Service" => typed_issues::<Service>(manifest),
+            "/v1/ServiceAccount" => typed_issues::<ServiceAccount>(manifest),
+            "apps/v1/DaemonSet" => typed_issues::<DaemonSet>(manifest),
+            "apps/v1/Deployment" => typed_issues::<Deployment>(manifest),
+            "apps/v1/ReplicaSet" => typed_issues::<ReplicaSet>(manifest),
+            "apps/v1/StatefulSet" => typed_issues::<StatefulSet>(manifest),
+            "autoscaling/v2/HorizontalPodAutoscaler" => {
+                typed_issues::<HorizontalPodAutoscaler>(manifest)
+            }
+            "batch/v1/CronJob" => typed_issues::<CronJob>(manifest),
+            "batch/v1/Job" => typed_issues::<Job>(manifest),
+            "networking.k8s.io/v1/Ingress" => typed_issues::<Ingress>(manifest),
+            "networking.k8s.io/v1/NetworkPolicy" => typed_issues::<NetworkPolicy>(manifest),
+            "policy/v1/PodDisruptionBudget" => typed_issues::<PodDisruptionBudget>(manifest),
+            "rbac.authorization.k8s.io/v1/ClusterRole" => typed_issues::<ClusterRole>(manifest),
+            "rbac.authorization.k8s.io/v1/ClusterRoleBinding" => {
+                typed_issues::<ClusterRoleBinding>(manifest)
+            }
+            "rbac.authorization.k8s.io/v1/Role" => typed_issues::<Role>(manifest),
+            "rbac.authorization.k8s.io/v1/RoleBinding" => typed_issues::<RoleBinding>(manifest),
+            "storage.k8s.io/v1/StorageClass" => typed_issues::<StorageClass>(manifest),
+            "apiextensions.k8s.io/v1/CustomResourceDefinition" => {
+                typed_issues::<CustomResourceDefinition>(manifest)
+            }
+            _ => return None,
+        })
+    }
+
+    /// Validates manifests without contacting a cluster. Kinds found in the
+    /// schema cache of `cluster` are checked against it; other built-in kinds
+    /// fall back to the bundled schemas.
+    pub fn validate(
+        handle: &AppHandle,
+        manifests: &[Value],
+        cluster: Option<String>,
+    ) -> Vec<ManifestValidation> {
+        let cache = cluster.and_then(|cluster| load_cache(handle, cluster.as_str()));
+        manifests
+            .iter()
+            .enumerate()
+            .map(|(index, manifest)| {
+                let mut result = ManifestValidation {
+                    index,
+                    api_version: manifest["apiVersion"].as_str().map(|s| s.to_string()),
+                    kind: manifest["kind"].as_str().map(|s| s.to_string()),
+                    name: manifest["metadata"]["name"].as_str().map(|s| s.to_string()),
+                    source: SchemaSource::None,
+                    valid: false,
+                    issues: Vec::new(),
+                };
+                let Some(key) = manifest_key(manifest) else {
+                    result
+                        .issues
+                        .push(issue("", "apiVersion and kind are required".to_string()));
+                    return result;
+                };
+                if result.name.is_none() && manifest["metadata"]["generateName"].is_null() {
+                    result.issues.push(issue(
+                        "metadata.name",
+                        "Required field is missing".to_string(),
+                    ));
+                }
+
+                let cached = cache.as_ref().and_then(|cache| {
+                    let name = cache.index.get(&key)?.as_str()?;
+                    Some((cache.schemas.get(name)?, &cache.schemas))
+                });
+                if let Some((schema, schemas)) = cached {
+                    result.source = SchemaSource::Cached;
+                    check(manifest, schema, schemas, "", 0, &mut result.issues);
+                } else if let Some(issues) = bundled_issues(key.as_str(), manifest) {
+                    result.source = SchemaSource::Bundled;
+                    result.issues.extend(issues);
+                } else {
+                    result.issues.push(issue(
+                        "",
+                        format!("No schema available for {}", key.trim_start_matches('/')),
+                    ));
+                }
+                result.valid = result.source != SchemaSource::None && result.issues.is_empty();
+                result
+            })
+            .collect()
+    }
+}
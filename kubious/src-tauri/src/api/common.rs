@@ -1,11 +1,22 @@
 pub mod kubious_api {
+    use std::time::Duration;
+
     use serde::{Deserialize, Serialize};
     use serde_json::Value;
-    use tauri::AppHandle;
+    use tauri::{AppHandle, Manager};
 
-    use crate::api::{
-        application_api::ApplicationCommand, artifacts_api::ArtifactsCommand,
-        helm_api::HelmCommand, kompose_api::KomposeCommand, kube_api::KubeCommand,
+    use crate::{
+        api::{
+            app_command_cache::{self, CommandCache}, app_requests::RequestRecorder,
+            app_state::AppState, app_support::SupportLog,
+            application_api::ApplicationCommand,
+            artifacts_api::ArtifactsCommand,
+            helm_api::HelmCommand, kompose_api::KomposeCommand, kube_api::KubeCommand,
+            events_api::EventsCommand, exec_api::ExecCommand, logs_api::LogsCommand, resources_api::ResourcesCommand,
+            views_api::ViewsCommand, dynamic_api::DynamicResourceCommand, journal_api::JournalCommand,
+            metrics_api::MetricsCommand,
+        },
+        util::{api_recorder, api_warnings, kubious_error::KubiousError},
     };
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -16,18 +27,23 @@ pub mod kubious_api {
         Helm(HelmCommand),
         Kompose(KomposeCommand),
         Artifacts(ArtifactsCommand),
+        Views(ViewsCommand),
+        Resources(ResourcesCommand),
+        Logs(LogsCommand),
+        Exec(ExecCommand),
+        Events(EventsCommand),
+        Dynamic(DynamicResourceCommand),
+        Journal(JournalCommand),
+        Metrics(MetricsCommand),
     }
 
     pub trait CommandHandler {
-        fn wrap_in_value(&self, result: Result<impl Serialize, String>) -> Result<Value, String> {
-            match result {
-                Ok(success) => Ok(serde_json::to_value(success).unwrap()),
-                Err(error) => Err(error)
-            }
+        fn wrap_in_value(&self, result: Result<impl Serialize, KubiousError>) -> Result<Value, KubiousError> {
+            result.map(|success| serde_json::to_value(success).unwrap())
         }
 
-        async fn execute(&self, _handle: &AppHandle) -> Result<Value, String> {
-            Err::<Value, String>("Execution not implemented".into())
+        async fn execute(&self, _handle: &AppHandle) -> Result<Value, KubiousError> {
+            Err("Execution not implemented".into())
         }
     }
 
@@ -36,10 +52,13 @@ pub mod kubious_api {
         command: ApiCommand,
         success: bool,
         value: Option<Value>,
-        error: Option<String>,
+        error: Option<KubiousError>,
+        warnings: Vec<String>,
+        /// Served from the command cache rather than computed.
+        cached: bool,
     }
 
-    fn unwrap_result(command: ApiCommand, result: Result<impl Serialize, String>) -> CommandResult {
+    fn unwrap_result(command: ApiCommand, result: Result<impl Serialize, KubiousError>, warnings: Vec<String>) -> CommandResult {
         match result {
             Ok(res) => serde_json::to_value(res)
                 .and_then(|v| Ok(CommandResult {
@@ -47,30 +66,82 @@ pub mod kubious_api {
                     success: true,
                     value: Some(v),
                     error: None,
+                    warnings: warnings.clone(),
+                    cached: false,
                 })).or_else(|_| Ok::<CommandResult, ()>(CommandResult {
                     command: command.clone(),
                     success: false,
                     value: None,
-                    error: Some("Failed to parse return value.".into()),
+                    error: Some(KubiousError::from("Failed to parse return value.")),
+                    warnings: warnings.clone(),
+                    cached: false,
                 })),
             Err(res) => Ok(CommandResult {
                     command,
                     success: false,
                     value: None,
                     error: Some(res),
+                    warnings,
+                    cached: false,
                 }),
         }.unwrap()
     }
 
-    pub async fn execute_command(app: AppHandle, command: ApiCommand) -> CommandResult {
-        let result = match command.clone() {
-            ApiCommand::Application(cmd) => unwrap_result(command, cmd.execute(&app.clone()).await),
-            ApiCommand::Kube(cmd) => unwrap_result(command, cmd.execute(&app.clone()).await),
-            ApiCommand::Helm(cmd) => unwrap_result(command, cmd.execute(&app.clone()).await),
-            ApiCommand::Kompose(cmd) => unwrap_result(command, cmd.execute(&app.clone()).await),
-            ApiCommand::Artifacts(cmd) => unwrap_result(command, cmd.execute(&app.clone()).await),
-        };
+    /// The cluster and key a cacheable command is stored under. The active
+    /// tenancy filter is part of the key since it changes what is returned.
+    fn cache_entry(app: &AppHandle, command: &ApiCommand) -> Option<(String, String, Duration)> {
+        let ttl = app_command_cache::ttl(command)?;
+        let state = app.state::<AppState>();
+        let (cluster, _) = state.get_current_config()?;
+        let filter = serde_json::to_string(&state.active_tenancy_filter(cluster.as_str())).ok()?;
+        let key = format!("{}\n{}", serde_json::to_string(command).ok()?, filter);
+        Some((cluster, key, ttl))
+    }
+
+    /// Runs a command. Cacheable commands are answered from the command
+    /// cache while their result is fresh, unless `force_refresh` is set.
+    pub async fn execute_command(app: AppHandle, command: ApiCommand, force_refresh: bool) -> CommandResult {
+        let cache = app.state::<CommandCache>();
+        let entry = cache_entry(&app, &command);
+        if let Some((cluster, key, _)) = entry.as_ref().filter(|_| !force_refresh) {
+            if let Some((value, warnings)) = cache.get(cluster, key) {
+                let mut cached = unwrap_result(command, Ok(value), warnings);
+                cached.cached = true;
+                return cached;
+            }
+        }
+
+        let recorder = app.state::<RequestRecorder>();
+        let run = api_warnings::collect(async {
+            match command.clone() {
+                ApiCommand::Application(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Kube(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Helm(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Kompose(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Artifacts(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Views(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Resources(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Logs(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Exec(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Events(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Dynamic(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Journal(cmd) => cmd.execute(&app.clone()).await,
+                ApiCommand::Metrics(cmd) => cmd.execute(&app.clone()).await,
+            }
+        });
+        let ((result, warnings), requests) = api_recorder::record(run, recorder.enabled()).await;
 
-        result
+        if let Ok(serialized) = serde_json::to_value(&command) {
+            if let Err(error) = &result {
+                app.state::<SupportLog>().record_failure(&serialized, error.message());
+            }
+            if recorder.enabled() {
+                recorder.push(&serialized, result.is_ok(), requests);
+            }
+        }
+        if let (Some((cluster, key, ttl)), Ok(value)) = (&entry, &result) {
+            cache.put(cluster, key, *ttl, value.clone(), warnings.clone());
+        }
+        unwrap_result(command, result, warnings)
     }
 }
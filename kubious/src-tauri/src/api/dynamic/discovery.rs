@@ -0,0 +1,187 @@
+pub mod discovery_cache {
+    use std::{
+        collections::HashMap,
+        sync::{Mutex, MutexGuard},
+        time::{Duration, Instant},
+    };
+
+    use kube::{
+        discovery::{verbs, ApiCapabilities, ApiResource, Discovery, Scope},
+        Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use tauri::{AppHandle, Manager};
+
+    use crate::{
+        api::views::resource_access::ResourceKind,
+        util::{
+            kubious_error::{ErrorContext, KubiousError},
+            lock_recovery::recover,
+        },
+    };
+
+    const CACHE_TTL: Duration = Duration::from_secs(300);
+
+    /// A served group/version/kind. Every served version of a group is
+    /// listed; `preferred` marks the one discovery recommends.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DiscoveredKind {
+        #[serde(flatten)]
+        pub kind: ResourceKind,
+        pub preferred: bool,
+        /// Subresources such as `status` or `scale`.
+        pub subresources: Vec<String>,
+    }
+
+    #[derive(Clone)]
+    struct Served {
+        resource: ApiResource,
+        caps: ApiCapabilities,
+        preferred: bool,
+    }
+
+    struct CachedDiscovery {
+        fetched: Instant,
+        served: Vec<Served>,
+    }
+
+    /// Discovery results per cluster, so resolving a kind does not query the
+    /// API server every time. Refreshed after `CACHE_TTL`, when a kind is not
+    /// found (it may have just been installed) and when the cluster's config
+    /// changes.
+    #[derive(Default)]
+    pub struct DiscoveryCache {
+        clusters: Mutex<HashMap<String, CachedDiscovery>>,
+    }
+
+    impl DiscoveryCache {
+        fn clusters_mutable(&self) -> MutexGuard<'_, HashMap<String, CachedDiscovery>> {
+            recover(&self.clusters)
+        }
+
+        fn get(&self, cluster: &str) -> Option<Vec<Served>> {
+            self.clusters_mutable()
+                .get(cluster)
+                .filter(|cached| cached.fetched.elapsed() <= CACHE_TTL)
+                .map(|cached| cached.served.clone())
+        }
+
+        pub fn invalidate(&self, cluster: &str) {
+            self.clusters_mutable().remove(cluster);
+        }
+    }
+
+    async fn run(client: Client) -> Result<Vec<Served>, KubiousError> {
+        let discovery = Discovery::new(client)
+            .run()
+            .await
+            .context("Failed to run API discovery.")?;
+        let mut served: Vec<Served> = Vec::new();
+        for group in discovery.groups() {
+            let preferred = group.preferred_version_or_latest().to_string();
+            for version in group.versions() {
+                for (resource, caps) in group.versioned_resources(version) {
+                    served.push(Served {
+                        resource,
+                        caps,
+                        preferred: version == preferred,
+                    });
+                }
+            }
+        }
+        Ok(served)
+    }
+
+    async fn served(
+        handle: &AppHandle,
+        client: &Client,
+        cluster: &str,
+        refresh: bool,
+    ) -> Result<Vec<Served>, KubiousError> {
+        let cache = handle.state::<DiscoveryCache>();
+        if !refresh {
+            if let Some(served) = cache.get(cluster) {
+                return Ok(served);
+            }
+        }
+        let served = run(client.clone()).await?;
+        cache.clusters_mutable().insert(
+            cluster.to_string(),
+            CachedDiscovery {
+                fetched: Instant::now(),
+                served: served.clone(),
+            },
+        );
+        Ok(served)
+    }
+
+    /// Every listable kind the cluster serves, optionally only the
+    /// namespaced (`Some(true)`) or cluster-scoped (`Some(false)`) ones.
+    pub async fn kinds(
+        handle: &AppHandle,
+        client: &Client,
+        cluster: &str,
+        namespaced: Option<bool>,
+        refresh: bool,
+    ) -> Result<Vec<DiscoveredKind>, KubiousError> {
+        let mut kinds: Vec<DiscoveredKind> = served(handle, client, cluster, refresh)
+            .await?
+            .into_iter()
+            .filter(|s| s.caps.supports_operation(verbs::LIST))
+            .map(|s| DiscoveredKind {
+                subresources: s
+                    .caps
+                    .subresources
+                    .iter()
+                    .map(|(sub, _)| sub.plural.clone())
+                    .collect(),
+                kind: ResourceKind {
+                    namespaced: s.caps.scope == Scope::Namespaced,
+                    group: s.resource.group,
+                    version: s.resource.version,
+                    kind: s.resource.kind,
+                    plural: s.resource.plural,
+                    verbs: s.caps.operations,
+                },
+                preferred: s.preferred,
+            })
+            .filter(|k| namespaced.is_none_or(|n| n == k.kind.namespaced))
+            .collect();
+        kinds.sort_by(|a, b| {
+            a.kind
+                .kind
+                .cmp(&b.kind.kind)
+                .then(a.kind.group.cmp(&b.kind.group))
+                .then(b.preferred.cmp(&a.preferred))
+                .then(a.kind.version.cmp(&b.kind.version))
+        });
+        Ok(kinds)
+    }
+
+    /// Resolves a kind from the cached discovery, rediscovering once if the
+    /// kind is unknown.
+    pub async fn resolve(
+        handle: &AppHandle,
+        client: &Client,
+        cluster: &str,
+        group: &str,
+        version: &str,
+        kind: &str,
+    ) -> Result<(ApiResource, ApiCapabilities), KubiousError> {
+        let find = |served: Vec<Served>| {
+            served
+                .into_iter()
+                .find(|s| {
+                    s.resource.group == group
+                        && s.resource.version == version
+                        && s.resource.kind == kind
+                })
+                .map(|s| (s.resource, s.caps))
+        };
+        if let Some(found) = find(served(handle, client, cluster, false).await?) {
+            return Ok(found);
+        }
+        find(served(handle, client, cluster, true).await?)
+            .ok_or(format!("Unknown resource kind {}", kind).into())
+    }
+}
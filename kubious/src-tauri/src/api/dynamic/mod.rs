@@ -0,0 +1,194 @@
+pub mod dynamic_api {
+    use crate::{
+        api::{
+            app_settings::TenancyFilter,
+            app_state::AppState,
+            resource_informers::{self, InformerKey},
+            views::resource_access::ResourceRef,
+        },
+        util::kubious_error::KubiousError,
+        CommandHandler,
+    };
+    use kube::discovery::Scope;
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::Manager;
+
+    use super::{
+        discovery_cache,
+        dynamic_objects::{self, DynamicPatch, DynamicQuery},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "command")]
+    pub enum DynamicResourceCommand {
+        /// Every served version of every listable kind, custom resources
+        /// included, from the discovery cache unless `refresh` is set.
+        DiscoverKinds {
+            namespaced: Option<bool>,
+            #[serde(default)]
+            refresh: bool,
+        },
+        ListDynamic {
+            query: DynamicQuery,
+        },
+        GetDynamic {
+            resource: ResourceRef,
+        },
+        PatchDynamic {
+            resource: ResourceRef,
+            patch: DynamicPatch,
+            #[serde(default)]
+            dry_run: bool,
+        },
+        /// Joins the shared informer for a kind; changes are pushed on its
+        /// channel. Leave it with `UnwatchDynamic`.
+        WatchDynamic {
+            group: String,
+            version: String,
+            kind: String,
+            namespace: Option<String>,
+        },
+        UnwatchDynamic {
+            id: String,
+        },
+    }
+
+    /// Refuses kinds and namespaces outside the active tenancy filter.
+    fn check_scope(
+        filter: Option<&TenancyFilter>,
+        kind: &str,
+        namespace: Option<&str>,
+    ) -> Result<(), KubiousError> {
+        let Some(filter) = filter else {
+            return Ok(());
+        };
+        if !filter.allows_kind(kind) || namespace.is_some_and(|ns| !filter.allows_namespace(ns)) {
+            return Err(format!("{} is outside the tenancy filter {}", kind, filter.name).into());
+        }
+        Ok(())
+    }
+
+    impl CommandHandler for DynamicResourceCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            let state = handle.state::<AppState>();
+            let (Some(client), Some((cluster, _))) =
+                (state.client().await, state.get_current_config())
+            else {
+                return Err(KubiousError::no_client());
+            };
+            let filter = state.active_tenancy_filter(cluster.as_str());
+            let filter = filter.as_ref();
+
+            match self {
+                DynamicResourceCommand::DiscoverKinds {
+                    namespaced,
+                    refresh,
+                } => {
+                    let kinds = discovery_cache::kinds(
+                        handle,
+                        &client,
+                        cluster.as_str(),
+                        *namespaced,
+                        *refresh,
+                    )
+                    .await;
+                    self.wrap_in_value(kinds.map(|kinds| {
+                        kinds
+                            .into_iter()
+                            .filter(|k| filter.is_none_or(|f| f.allows_kind(k.kind.kind.as_str())))
+                            .collect::<Vec<_>>()
+                    }))
+                }
+                DynamicResourceCommand::ListDynamic { query } => {
+                    check_scope(filter, query.kind.as_str(), query.namespace.as_deref())?;
+                    let mut query = query.clone();
+                    if let Some(f) = filter {
+                        let (_, caps) = discovery_cache::resolve(
+                            handle,
+                            &client,
+                            cluster.as_str(),
+                            query.group.as_str(),
+                            query.version.as_str(),
+                            query.kind.as_str(),
+                        )
+                        .await?;
+                        if caps.scope == Scope::Namespaced
+                            && query.namespace.is_none()
+                            && !f.namespaces.is_empty()
+                        {
+                            return Err(format!(
+                                "Choose a namespace within the tenancy filter {}",
+                                f.name
+                            )
+                            .into());
+                        }
+                        query.label_selector = f.label_selector(query.label_selector.as_deref());
+                    }
+                    self.wrap_in_value(
+                        dynamic_objects::list(handle, client, cluster.as_str(), &query).await,
+                    )
+                }
+                DynamicResourceCommand::GetDynamic { resource } => {
+                    check_scope(
+                        filter,
+                        resource.kind.as_str(),
+                        resource.namespace.as_deref(),
+                    )?;
+                    self.wrap_in_value(
+                        dynamic_objects::get(handle, client, cluster.as_str(), resource).await,
+                    )
+                }
+                DynamicResourceCommand::PatchDynamic {
+                    resource,
+                    patch,
+                    dry_run,
+                } => {
+                    check_scope(
+                        filter,
+                        resource.kind.as_str(),
+                        resource.namespace.as_deref(),
+                    )?;
+                    self.wrap_in_value(
+                        dynamic_objects::patch(
+                            handle,
+                            client,
+                            cluster.as_str(),
+                            resource,
+                            patch,
+                            *dry_run,
+                        )
+                        .await,
+                    )
+                }
+                DynamicResourceCommand::WatchDynamic {
+                    group,
+                    version,
+                    kind,
+                    namespace,
+                } => {
+                    check_scope(filter, kind.as_str(), namespace.as_deref())?;
+                    let key = InformerKey {
+                        cluster,
+                        group: group.clone(),
+                        version: version.clone(),
+                        kind: kind.clone(),
+                        namespace: namespace.clone(),
+                        tenancy_filter: None,
+                    };
+                    self.wrap_in_value(
+                        resource_informers::subscribe(handle, client, filter.cloned(), key).await,
+                    )
+                }
+                DynamicResourceCommand::UnwatchDynamic { id } => {
+                    self.wrap_in_value(resource_informers::unsubscribe(handle, id.as_str()))
+                }
+            }
+        }
+    }
+}
+
+mod discovery;
+pub use discovery::discovery_cache;
+mod objects;
+pub use objects::dynamic_objects;
@@ -0,0 +1,199 @@
+pub mod dynamic_objects {
+    use kube::{
+        api::{DynamicObject, ListParams, Patch, PatchParams},
+        discovery::{verbs, ApiCapabilities, ApiResource, Scope},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::AppHandle;
+
+    use crate::{
+        api::{
+            artifacts::artifact_apply::DEFAULT_FIELD_MANAGER,
+            discovery_cache,
+            views::{
+                printer_columns::{self, PrinterColumn},
+                resource_access::{self, ResourceRef},
+            },
+        },
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    /// A collection query for any served kind.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DynamicQuery {
+        pub group: String,
+        pub version: String,
+        pub kind: String,
+        /// Ignored for cluster-scoped kinds; all namespaces when unset.
+        pub namespace: Option<String>,
+        pub label_selector: Option<String>,
+        pub field_selector: Option<String>,
+        pub limit: Option<u32>,
+        /// `continue_token` of the previous page.
+        pub continue_token: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DynamicPage {
+        pub items: Vec<Value>,
+        /// Printer columns of a custom resource kind.
+        pub columns: Vec<PrinterColumn>,
+        /// Column values per item, aligned with `items`.
+        pub cells: Vec<Vec<Value>>,
+        /// Set while more items remain; pass it back to get the next page.
+        pub continue_token: Option<String>,
+        pub resource_version: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "type")]
+    pub enum DynamicPatch {
+        /// RFC 7386 merge patch; lists are replaced as a whole.
+        Merge { patch: Value },
+        /// Server-side apply of a partial object.
+        Apply {
+            patch: Value,
+            field_manager: Option<String>,
+            #[serde(default)]
+            force: bool,
+        },
+    }
+
+    async fn object_api(
+        handle: &AppHandle,
+        client: Client,
+        cluster: &str,
+        target: &ResourceRef,
+    ) -> Result<(Api<DynamicObject>, ApiResource, ApiCapabilities), KubiousError> {
+        let (resource, caps) = discovery_cache::resolve(
+            handle,
+            &client,
+            cluster,
+            target.group.as_str(),
+            target.version.as_str(),
+            target.kind.as_str(),
+        )
+        .await?;
+        if caps.scope == Scope::Namespaced && target.namespace.is_none() {
+            return Err(format!("{} is namespaced; a namespace is required", target.kind).into());
+        }
+        let api = resource_access::api_for(client, &resource, &caps, target.namespace.as_deref());
+        Ok((api, resource, caps))
+    }
+
+    /// Lists one page of any served kind, including custom resources.
+    pub async fn list(
+        handle: &AppHandle,
+        client: Client,
+        cluster: &str,
+        query: &DynamicQuery,
+    ) -> Result<DynamicPage, KubiousError> {
+        let (resource, caps) = discovery_cache::resolve(
+            handle,
+            &client,
+            cluster,
+            query.group.as_str(),
+            query.version.as_str(),
+            query.kind.as_str(),
+        )
+        .await?;
+        let columns = printer_columns::for_kind(client.clone(), &resource).await;
+        let api = resource_access::api_for(client, &resource, &caps, query.namespace.as_deref());
+        let mut params = ListParams::default();
+        if let Some(selector) = &query.label_selector {
+            params = params.labels(selector.as_str());
+        }
+        if let Some(selector) = &query.field_selector {
+            params = params.fields(selector.as_str());
+        }
+        if let Some(limit) = query.limit {
+            params = params.limit(limit);
+        }
+        if let Some(token) = &query.continue_token {
+            params = params.continue_token(token.as_str());
+        }
+        let list = api
+            .list(&params)
+            .await
+            .context(format!("Failed to list {}", resource.plural))?;
+        let items: Vec<Value> = list
+            .items
+            .into_iter()
+            .filter_map(|object| resource_access::to_item(object, &resource))
+            .collect();
+        Ok(DynamicPage {
+            continue_token: list.metadata.continue_.filter(|t| !t.is_empty()),
+            resource_version: list.metadata.resource_version,
+            cells: items
+                .iter()
+                .map(|item| printer_columns::cells(&columns, item))
+                .collect(),
+            columns,
+            items,
+        })
+    }
+
+    pub async fn get(
+        handle: &AppHandle,
+        client: Client,
+        cluster: &str,
+        target: &ResourceRef,
+    ) -> Result<Value, KubiousError> {
+        let (api, resource, _) = object_api(handle, client, cluster, target).await?;
+        let object = api
+            .get(target.name.as_str())
+            .await
+            .context(format!("Failed to get {} {}", target.kind, target.name))?;
+        resource_access::to_item(object, &resource).ok_or("Failed to serialize object.".into())
+    }
+
+    /// Patches an object of any kind and returns it as patched. Strategic
+    /// merge is not offered since custom resources do not support it.
+    pub async fn patch(
+        handle: &AppHandle,
+        client: Client,
+        cluster: &str,
+        target: &ResourceRef,
+        patch: &DynamicPatch,
+        dry_run: bool,
+    ) -> Result<Value, KubiousError> {
+        let (api, resource, caps) = object_api(handle, client, cluster, target).await?;
+        if !caps.supports_operation(verbs::PATCH) {
+            return Err(format!("{} does not support patching", target.kind).into());
+        }
+        let name = target.name.as_str();
+        let result = match patch {
+            DynamicPatch::Merge { patch } => {
+                let params = PatchParams {
+                    dry_run,
+                    ..PatchParams::default()
+                };
+                api.patch(name, &params, &Patch::Merge(patch)).await
+            }
+            DynamicPatch::Apply {
+                patch,
+                field_manager,
+                force,
+            } => {
+                if !patch.is_object() {
+                    return Err("An apply patch must be an object".into());
+                }
+                let mut object = patch.clone();
+                object["apiVersion"] = Value::String(resource.api_version.clone());
+                object["kind"] = Value::String(resource.kind.clone());
+                object["metadata"]["name"] = Value::String(target.name.clone());
+                let mut params =
+                    PatchParams::apply(field_manager.as_deref().unwrap_or(DEFAULT_FIELD_MANAGER));
+                params.dry_run = dry_run;
+                if *force {
+                    params = params.force();
+                }
+                api.patch(name, &params, &Patch::Apply(&object)).await
+            }
+        };
+        let object = result.context(format!("Failed to patch {} {}", target.kind, target.name))?;
+        resource_access::to_item(object, &resource).ok_or("Failed to serialize object.".into())
+    }
+}
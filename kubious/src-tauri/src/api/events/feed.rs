@@ -0,0 +1,530 @@
+pub mod event_feeds {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fmt::Debug,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard,
+        },
+    };
+
+    use futures::{stream, StreamExt};
+    use k8s_openapi::{
+        api::{core::v1 as core, events::v1 as events},
+        chrono::Utc,
+        NamespaceResourceScope,
+    };
+    use kube::{
+        api::ListParams,
+        runtime::watcher::{self, watcher, Event},
+        Api, Client, Resource,
+    };
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use tauri::{async_runtime::JoinHandle, AppHandle, Emitter, Manager};
+
+    use crate::util::{
+        kubious_error::{ErrorContext, KubiousError},
+        lock_recovery::recover,
+        stream_reconnect::{BackoffPolicy, Reconnector},
+    };
+
+    /// Prefix of the per-feed event channel; the full channel is
+    /// `event-feed/<id>`.
+    pub const EVENT_FEED_CHANNEL_PREFIX: &str = "event-feed/";
+
+    /// The object whose events are shown. Unset fields match any value.
+    #[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+    pub struct InvolvedObject {
+        pub kind: Option<String>,
+        pub name: Option<String>,
+        pub uid: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct EventSubject {
+        pub kind: String,
+        pub namespace: Option<String>,
+        pub name: String,
+    }
+
+    /// One Event object, from either API.
+    #[derive(Clone, Debug)]
+    struct EventRecord {
+        uid: String,
+        event_type: Option<String>,
+        reason: String,
+        message: String,
+        subject: EventSubject,
+        count: i32,
+        first_seen: Option<String>,
+        last_seen: Option<String>,
+        source: Option<String>,
+    }
+
+    impl EventRecord {
+        /// Events with the same subject, reason and message are repeats of
+        /// one occurrence and shown once.
+        fn key(&self) -> String {
+            format!(
+                "{}/{}/{}/{}/{}",
+                self.subject.kind,
+                self.subject.namespace.clone().unwrap_or_default(),
+                self.subject.name,
+                self.reason,
+                self.message
+            )
+        }
+    }
+
+    impl From<core::Event> for EventRecord {
+        fn from(event: core::Event) -> Self {
+            let involved = event.involved_object;
+            let last_seen = event
+                .series
+                .as_ref()
+                .and_then(|s| s.last_observed_time.as_ref())
+                .map(|t| t.0.to_rfc3339())
+                .or(event.last_timestamp.map(|t| t.0.to_rfc3339()))
+                .or(event.event_time.as_ref().map(|t| t.0.to_rfc3339()));
+            EventRecord {
+                uid: event.metadata.uid.unwrap_or_default(),
+                event_type: event.type_,
+                reason: event.reason.unwrap_or_default(),
+                message: event.message.unwrap_or_default(),
+                subject: EventSubject {
+                    kind: involved.kind.unwrap_or_default(),
+                    namespace: involved.namespace,
+                    name: involved.name.unwrap_or_default(),
+                },
+                count: event
+                    .series
+                    .and_then(|s| s.count)
+                    .or(event.count)
+                    .unwrap_or(1),
+                first_seen: event
+                    .first_timestamp
+                    .map(|t| t.0.to_rfc3339())
+                    .or(event.event_time.map(|t| t.0.to_rfc3339())),
+                last_seen,
+                source: event
+                    .reporting_component
+                    .filter(|c| !c.is_empty())
+                    .or(event.source.and_then(|s| s.component)),
+            }
+        }
+    }
+
+    impl From<events::Event> for EventRecord {
+        fn from(event: events::Event) -> Self {
+            let regarding = event.regarding.unwrap_or_default();
+            let last_seen = event
+                .series
+                .as_ref()
+                .map(|s| s.last_observed_time.0.to_rfc3339())
+                .or(event.deprecated_last_timestamp.map(|t| t.0.to_rfc3339()))
+                .or(event.event_time.as_ref().map(|t| t.0.to_rfc3339()));
+            EventRecord {
+                uid: event.metadata.uid.unwrap_or_default(),
+                event_type: event.type_,
+                reason: event.reason.unwrap_or_default(),
+                message: event.note.unwrap_or_default(),
+                subject: EventSubject {
+                    kind: regarding.kind.unwrap_or_default(),
+                    namespace: regarding.namespace,
+                    name: regarding.name.unwrap_or_default(),
+                },
+                count: event
+                    .series
+                    .map(|s| s.count)
+                    .or(event.deprecated_count)
+                    .unwrap_or(1),
+                first_seen: event
+                    .deprecated_first_timestamp
+                    .map(|t| t.0.to_rfc3339())
+                    .or(event.event_time.map(|t| t.0.to_rfc3339())),
+                last_seen,
+                source: event
+                    .reporting_controller
+                    .filter(|c| !c.is_empty())
+                    .or(event.deprecated_source.and_then(|s| s.component)),
+            }
+        }
+    }
+
+    /// Repeats of one occurrence, merged across Event objects and APIs.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct EventEntry {
+        pub key: String,
+        pub event_type: Option<String>,
+        pub reason: String,
+        pub message: String,
+        pub subject: EventSubject,
+        /// Total occurrences across the merged Event objects.
+        pub count: i32,
+        pub first_seen: Option<String>,
+        pub last_seen: Option<String>,
+        pub source: Option<String>,
+        /// Event objects merged into this entry.
+        pub objects: usize,
+    }
+
+    /// Merges records by key, newest first.
+    fn deduplicate<'a>(records: impl Iterator<Item = &'a EventRecord>) -> Vec<EventEntry> {
+        let mut entries: BTreeMap<String, EventEntry> = BTreeMap::new();
+        for record in records {
+            let entry = entries.entry(record.key()).or_insert(EventEntry {
+                key: record.key(),
+                event_type: record.event_type.clone(),
+                reason: record.reason.clone(),
+                message: record.message.clone(),
+                subject: record.subject.clone(),
+                count: 0,
+                first_seen: record.first_seen.clone(),
+                last_seen: record.last_seen.clone(),
+                source: record.source.clone(),
+                objects: 0,
+            });
+            entry.count += record.count.max(1);
+            entry.objects += 1;
+            if record.first_seen.is_some()
+                && (entry.first_seen.is_none() || record.first_seen < entry.first_seen)
+            {
+                entry.first_seen = record.first_seen.clone();
+            }
+            if record.last_seen > entry.last_seen {
+                entry.last_seen = record.last_seen.clone();
+                entry.event_type = record.event_type.clone();
+                entry.source = record.source.clone();
+            }
+        }
+        let mut entries: Vec<EventEntry> = entries.into_values().collect();
+        entries.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        entries
+    }
+
+    /// Field selectors for one API; core Events name the subject
+    /// `involvedObject`, events.k8s.io ones `regarding`.
+    fn selector(prefix: &str, involved: Option<&InvolvedObject>) -> Option<String> {
+        let involved = involved?;
+        let fields: Vec<String> = [
+            ("kind", &involved.kind),
+            ("name", &involved.name),
+            ("uid", &involved.uid),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| {
+            value
+                .as_ref()
+                .map(|value| format!("{}.{}={}", prefix, field, value))
+        })
+        .collect();
+        (!fields.is_empty()).then(|| fields.join(","))
+    }
+
+    fn api<K>(client: Client, namespace: Option<&str>) -> Api<K>
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+        K::DynamicType: Default,
+    {
+        match namespace {
+            Some(namespace) => Api::namespaced(client, namespace),
+            None => Api::all(client),
+        }
+    }
+
+    /// Lists the events of a namespace (or every namespace) or of one
+    /// object, from both Event APIs, with repeats merged.
+    pub async fn list(
+        client: Client,
+        namespace: Option<&str>,
+        involved: Option<&InvolvedObject>,
+    ) -> Result<Vec<EventEntry>, KubiousError> {
+        let mut core_params = ListParams::default();
+        if let Some(fields) = selector("involvedObject", involved) {
+            core_params = core_params.fields(fields.as_str());
+        }
+        let mut records: HashMap<String, EventRecord> =
+            api::<core::Event>(client.clone(), namespace)
+                .list(&core_params)
+                .await
+                .context("Failed to list events")?
+                .items
+                .into_iter()
+                .map(|event| {
+                    let record = EventRecord::from(event);
+                    (record.uid.clone(), record)
+                })
+                .collect();
+
+        // Both APIs serve the same objects, but events.k8s.io carries series
+        // counts. Clusters without it still get the core list.
+        let mut params = ListParams::default();
+        if let Some(fields) = selector("regarding", involved) {
+            params = params.fields(fields.as_str());
+        }
+        if let Ok(list) = api::<events::Event>(client, namespace).list(&params).await {
+            for event in list.items {
+                let record = EventRecord::from(event);
+                records.insert(record.uid.clone(), record);
+            }
+        }
+        Ok(deduplicate(records.values()))
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "type")]
+    pub enum EventFeedUpdate {
+        /// Every entry, sent when the watch (re)starts.
+        Reset {
+            entries: Vec<EventEntry>,
+        },
+        /// Entries that appeared or changed.
+        Changed {
+            entries: Vec<EventEntry>,
+        },
+        /// Keys of entries whose Events all expired.
+        Removed {
+            keys: Vec<String>,
+        },
+        Ended {
+            error: Option<String>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct EventFeed {
+        pub id: String,
+        pub channel: String,
+        pub cluster: String,
+        pub namespace: Option<String>,
+        pub involved: Option<InvolvedObject>,
+        pub started: String,
+    }
+
+    struct RunningFeed {
+        feed: EventFeed,
+        task: JoinHandle<()>,
+    }
+
+    /// Live event watches keyed by feed id.
+    #[derive(Default)]
+    pub struct EventFeeds {
+        feeds: Mutex<HashMap<String, RunningFeed>>,
+        counter: AtomicU64,
+    }
+
+    impl EventFeeds {
+        fn feeds_mutable(&self) -> MutexGuard<'_, HashMap<String, RunningFeed>> {
+            recover(&self.feeds)
+        }
+
+        fn next_id(&self) -> String {
+            format!("events-{}", self.counter.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        pub fn active(&self) -> Vec<EventFeed> {
+            let mut feeds: Vec<EventFeed> = self
+                .feeds_mutable()
+                .values()
+                .map(|running| running.feed.clone())
+                .collect();
+            feeds.sort_by(|a, b| a.started.cmp(&b.started));
+            feeds
+        }
+    }
+
+    /// A change from either watch; `core` tells which API it came from so
+    /// a relist of one only resets that API's records.
+    enum Change {
+        Init { core: bool },
+        Apply { core: bool, record: EventRecord },
+        Delete { core: bool, record: EventRecord },
+        Done { core: bool },
+        Failed(String),
+    }
+
+    fn changes<K>(
+        api: Api<K>,
+        config: watcher::Config,
+        core: bool,
+    ) -> stream::BoxStream<'static, Change>
+    where
+        K: Resource + Clone + DeserializeOwned + Debug + Send + 'static,
+        K::DynamicType: Default,
+        EventRecord: From<K>,
+    {
+        watcher(api, config)
+            .map(move |event| match event {
+                Ok(Event::Init) => Change::Init { core },
+                Ok(Event::InitApply(object)) | Ok(Event::Apply(object)) => Change::Apply {
+                    core,
+                    record: EventRecord::from(object),
+                },
+                Ok(Event::Delete(object)) => Change::Delete {
+                    core,
+                    record: EventRecord::from(object),
+                },
+                Ok(Event::InitDone) => Change::Done { core },
+                Err(error) => Change::Failed(error.to_string()),
+            })
+            .boxed()
+    }
+
+    /// Records of both APIs, preferring events.k8s.io for objects served by
+    /// both.
+    fn merged<'a>(
+        core: &'a HashMap<String, EventRecord>,
+        events: &'a HashMap<String, EventRecord>,
+    ) -> impl Iterator<Item = &'a EventRecord> {
+        events.values().chain(
+            core.iter()
+                .filter(|(uid, _)| !events.contains_key(*uid))
+                .map(|(_, record)| record),
+        )
+    }
+
+    async fn watch(handle: AppHandle, client: Client, feed: EventFeed) {
+        let namespace = feed.namespace.as_deref();
+        let mut core_config = watcher::Config::default();
+        if let Some(fields) = selector("involvedObject", feed.involved.as_ref()) {
+            core_config = core_config.fields(fields.as_str());
+        }
+        let mut watches = vec![changes(
+            api::<core::Event>(client.clone(), namespace),
+            core_config,
+            true,
+        )];
+        // Clusters without events.k8s.io are watched through the core API
+        // alone.
+        let events_api = api::<events::Event>(client, namespace);
+        if events_api
+            .list(&ListParams::default().limit(1))
+            .await
+            .is_ok()
+        {
+            let mut config = watcher::Config::default();
+            if let Some(fields) = selector("regarding", feed.involved.as_ref()) {
+                config = config.fields(fields.as_str());
+            }
+            watches.push(changes(events_api, config, false));
+        }
+        let mut stream = stream::select_all(watches);
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "events",
+            feed.id.as_str(),
+            BackoffPolicy::default(),
+        );
+        let emit = |update: EventFeedUpdate| {
+            let _ = handle.emit(feed.channel.as_str(), update);
+        };
+
+        let mut core_records: HashMap<String, EventRecord> = HashMap::new();
+        let mut event_records: HashMap<String, EventRecord> = HashMap::new();
+        // APIs still sending their initial list; updates wait for both.
+        let mut listing: Vec<bool> = Vec::new();
+        let mut error: Option<String> = None;
+        while let Some(change) = stream.next().await {
+            let (core, record, deleted) = match change {
+                Change::Init { core } => {
+                    if core {
+                        core_records.clear();
+                    } else {
+                        event_records.clear();
+                    }
+                    listing.push(core);
+                    continue;
+                }
+                Change::Done { core } => {
+                    listing.retain(|l| *l != core);
+                    if listing.is_empty() {
+                        emit(EventFeedUpdate::Reset {
+                            entries: deduplicate(merged(&core_records, &event_records)),
+                        });
+                        reconnector.connected();
+                    }
+                    continue;
+                }
+                Change::Apply { core, record } => (core, record, false),
+                Change::Delete { core, record } => (core, record, true),
+                Change::Failed(reason) => {
+                    if !reconnector.retry(reason.as_str()).await {
+                        error = Some(reason);
+                        break;
+                    }
+                    continue;
+                }
+            };
+            let records = if core {
+                &mut core_records
+            } else {
+                &mut event_records
+            };
+            let key = record.key();
+            if deleted {
+                records.remove(&record.uid);
+            } else {
+                records.insert(record.uid.clone(), record);
+            }
+            if !listing.is_empty() {
+                continue;
+            }
+            let entries =
+                deduplicate(merged(&core_records, &event_records).filter(|r| r.key() == key));
+            if entries.is_empty() {
+                emit(EventFeedUpdate::Removed { keys: vec![key] });
+            } else {
+                emit(EventFeedUpdate::Changed { entries });
+            }
+        }
+        reconnector.closed();
+        handle
+            .state::<EventFeeds>()
+            .feeds_mutable()
+            .remove(&feed.id);
+        emit(EventFeedUpdate::Ended { error });
+    }
+
+    /// Watches the events of a namespace (or every namespace) or of one
+    /// object and sends merged updates to the feed's own event channel
+    /// until the feed is stopped.
+    pub fn start(
+        handle: &AppHandle,
+        client: Client,
+        cluster: String,
+        namespace: Option<String>,
+        involved: Option<InvolvedObject>,
+    ) -> EventFeed {
+        let feeds = handle.state::<EventFeeds>();
+        let id = feeds.next_id();
+        let feed = EventFeed {
+            channel: format!("{}{}", EVENT_FEED_CHANNEL_PREFIX, id),
+            id: id.clone(),
+            cluster,
+            namespace,
+            involved,
+            started: Utc::now().to_rfc3339(),
+        };
+
+        let mut running = feeds.feeds_mutable();
+        let task = tauri::async_runtime::spawn(watch(handle.clone(), client, feed.clone()));
+        running.insert(
+            id,
+            RunningFeed {
+                feed: feed.clone(),
+                task,
+            },
+        );
+        feed
+    }
+
+    pub fn stop(handle: &AppHandle, id: &str) -> bool {
+        let removed = handle.state::<EventFeeds>().feeds_mutable().remove(id);
+        if let Some(running) = removed {
+            running.task.abort();
+            true
+        } else {
+            false
+        }
+    }
+}
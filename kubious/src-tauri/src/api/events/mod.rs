@@ -0,0 +1,116 @@
+pub mod events_api {
+    use crate::{
+        api::{app_settings::TenancyFilter, app_state::AppState},
+        util::kubious_error::KubiousError,
+        CommandHandler,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::Manager;
+
+    use super::event_feeds::{self, EventFeeds, InvolvedObject};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "command")]
+    pub enum EventsCommand {
+        /// Events of a namespace (or every namespace), optionally only those
+        /// about one object, with repeats merged.
+        ListEvents {
+            namespace: Option<String>,
+            involved: Option<InvolvedObject>,
+        },
+        /// Watches the same events as `ListEvents`. Updates arrive on the
+        /// returned feed's `channel`.
+        WatchEvents {
+            namespace: Option<String>,
+            involved: Option<InvolvedObject>,
+        },
+        StopEventWatch {
+            id: String,
+        },
+        ListEventWatches {},
+    }
+
+    /// Refuses scopes outside the active tenancy filter. A filter limited to
+    /// some namespaces needs one of them to be chosen.
+    fn check_scope(
+        filter: Option<&TenancyFilter>,
+        namespace: &Option<String>,
+        involved: &Option<InvolvedObject>,
+    ) -> Result<(), KubiousError> {
+        let Some(filter) = filter else {
+            return Ok(());
+        };
+        let namespace_allowed = match namespace {
+            Some(namespace) => filter.allows_namespace(namespace),
+            None => filter.namespaces.is_empty(),
+        };
+        let kind_allowed = involved
+            .as_ref()
+            .and_then(|i| i.kind.as_deref())
+            .is_none_or(|kind| filter.allows_kind(kind));
+        if namespace_allowed && kind_allowed {
+            Ok(())
+        } else {
+            Err(format!("Events outside the tenancy filter {}", filter.name).into())
+        }
+    }
+
+    impl CommandHandler for EventsCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            match self {
+                EventsCommand::ListEvents {
+                    namespace,
+                    involved,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) =
+                        (state.client().await, state.get_current_config())
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    check_scope(
+                        state.active_tenancy_filter(cluster.as_str()).as_ref(),
+                        namespace,
+                        involved,
+                    )?;
+                    self.wrap_in_value(
+                        event_feeds::list(client, namespace.as_deref(), involved.as_ref()).await,
+                    )
+                }
+                EventsCommand::WatchEvents {
+                    namespace,
+                    involved,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) =
+                        (state.client().await, state.get_current_config())
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    check_scope(
+                        state.active_tenancy_filter(cluster.as_str()).as_ref(),
+                        namespace,
+                        involved,
+                    )?;
+                    self.wrap_in_value(Ok(event_feeds::start(
+                        handle,
+                        client,
+                        cluster,
+                        namespace.clone(),
+                        involved.clone(),
+                    )))
+                }
+                EventsCommand::StopEventWatch { id } => {
+                    self.wrap_in_value(Ok(event_feeds::stop(handle, id.as_str())))
+                }
+                EventsCommand::ListEventWatches {} => {
+                    self.wrap_in_value(Ok(handle.state::<EventFeeds>().active()))
+                }
+            }
+        }
+    }
+}
+
+mod feed;
+pub use feed::event_feeds;
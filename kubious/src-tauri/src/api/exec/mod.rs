@@ -0,0 +1,151 @@
+pub mod exec_api {
+    use crate::{
+        api::{app_state::AppState, session_limits::SessionReaper},
+        util::kubious_error::KubiousError,
+        CommandHandler,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::Manager;
+
+    use super::{
+        exec_sessions::{self, ExecOptions, ExecSessions},
+        file_transfers,
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "command")]
+    pub enum ExecCommand {
+        /// Attaches a shell (or `options.command`) to a container in the
+        /// current cluster. Output arrives on the returned session's
+        /// `channel`.
+        StartExecSession {
+            namespace: String,
+            pod: String,
+            #[serde(default)]
+            options: ExecOptions,
+        },
+        WriteExecInput {
+            id: String,
+            data: String,
+        },
+        ResizeExecTerminal {
+            id: String,
+            width: u16,
+            height: u16,
+        },
+        StopExecSession {
+            id: String,
+        },
+        ListExecSessions {},
+        /// Copies a local file or directory into a container through
+        /// `tar`. Progress is pushed on `file-transfer`.
+        UploadFiles {
+            namespace: String,
+            pod: String,
+            container: Option<String>,
+            local_path: String,
+            remote_path: String,
+        },
+        DownloadFiles {
+            namespace: String,
+            pod: String,
+            container: Option<String>,
+            remote_path: String,
+            local_path: String,
+        },
+    }
+
+    impl CommandHandler for ExecCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            let sessions = handle.state::<ExecSessions>();
+            match self {
+                ExecCommand::StartExecSession {
+                    namespace,
+                    pod,
+                    options,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) =
+                        (state.client().await, state.get_current_config())
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        exec_sessions::start(
+                            handle,
+                            client,
+                            cluster,
+                            namespace.clone(),
+                            pod.clone(),
+                            options.clone(),
+                        )
+                        .await,
+                    )
+                }
+                ExecCommand::WriteExecInput { id, data } => {
+                    handle.state::<SessionReaper>().touch(id);
+                    self.wrap_in_value(sessions.write(id.as_str(), data.as_str()))
+                }
+                ExecCommand::ResizeExecTerminal { id, width, height } => {
+                    self.wrap_in_value(sessions.resize(id.as_str(), *width, *height))
+                }
+                ExecCommand::StopExecSession { id } => {
+                    self.wrap_in_value(Ok(exec_sessions::stop(handle, id.as_str())))
+                }
+                ExecCommand::ListExecSessions {} => self.wrap_in_value(Ok(sessions.active())),
+                ExecCommand::UploadFiles {
+                    namespace,
+                    pod,
+                    container,
+                    local_path,
+                    remote_path,
+                } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        file_transfers::upload(
+                            handle,
+                            client,
+                            namespace.clone(),
+                            pod.clone(),
+                            container.clone(),
+                            local_path.clone(),
+                            remote_path.clone(),
+                        )
+                        .await,
+                    )
+                }
+                ExecCommand::DownloadFiles {
+                    namespace,
+                    pod,
+                    container,
+                    remote_path,
+                    local_path,
+                } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        file_transfers::download(
+                            handle,
+                            client,
+                            namespace.clone(),
+                            pod.clone(),
+                            container.clone(),
+                            remote_path.clone(),
+                            local_path.clone(),
+                        )
+                        .await,
+                    )
+                }
+            }
+        }
+    }
+}
+
+mod session;
+pub use session::exec_sessions;
+mod transfer;
+pub use transfer::file_transfers;
@@ -0,0 +1,449 @@
+pub mod exec_sessions {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard,
+        },
+    };
+
+    use futures::{
+        channel::mpsc::Sender,
+        future::{self, Either},
+    };
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::Status;
+    use k8s_openapi::{api::core::v1::Pod, chrono::Utc};
+    use kube::{
+        api::{AttachParams, AttachedProcess, TerminalSize},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use tauri::{AppHandle, Emitter, Manager};
+    use tokio::{
+        io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+        sync::{
+            mpsc::{self, UnboundedReceiver, UnboundedSender},
+            oneshot,
+        },
+    };
+
+    use crate::util::{
+        kubious_error::{ErrorContext, KubiousError},
+        lock_recovery::recover,
+        stream_reconnect::{BackoffPolicy, Reconnector},
+        utf8_chunks::take_text,
+    };
+
+    /// Prefix of the per-session event channel; the full channel is
+    /// `exec-session/<id>`.
+    pub const EXEC_SESSION_CHANNEL_PREFIX: &str = "exec-session/";
+
+    /// Starts bash where the image has it and falls back to sh.
+    const DEFAULT_SHELL: [&str; 3] = [
+        "/bin/sh",
+        "-c",
+        "command -v bash >/dev/null 2>&1 && exec bash || exec sh",
+    ];
+
+    const CHUNK_SIZE: usize = 8192;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum OutputStream {
+        Stdout,
+        Stderr,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "type")]
+    pub enum ExecEvent {
+        Output {
+            stream: OutputStream,
+            data: String,
+        },
+        /// The process exited or the connection closed. `status` is the
+        /// `Success`/`Failure` status reported by the API server, if any.
+        Exited {
+            status: Option<String>,
+            message: Option<String>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ExecSession {
+        pub id: String,
+        pub channel: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub pod: String,
+        pub container: Option<String>,
+        pub command: Vec<String>,
+        pub tty: bool,
+        pub started: String,
+    }
+
+    struct RunningExec {
+        session: ExecSession,
+        stdin: UnboundedSender<Vec<u8>>,
+        resize: Option<Sender<TerminalSize>>,
+        /// Last terminal size, sent again after a reconnect.
+        size: Option<(u16, u16)>,
+        stop: oneshot::Sender<()>,
+    }
+
+    /// Open exec sessions keyed by id.
+    #[derive(Default)]
+    pub struct ExecSessions {
+        sessions: Mutex<HashMap<String, RunningExec>>,
+        counter: AtomicU64,
+    }
+
+    impl ExecSessions {
+        fn sessions_mutable(&self) -> MutexGuard<'_, HashMap<String, RunningExec>> {
+            recover(&self.sessions)
+        }
+
+        fn next_id(&self) -> String {
+            format!("exec-{}", self.counter.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        pub fn active(&self) -> Vec<ExecSession> {
+            let mut sessions: Vec<ExecSession> = self
+                .sessions_mutable()
+                .values()
+                .map(|running| running.session.clone())
+                .collect();
+            sessions.sort_by(|a, b| a.started.cmp(&b.started));
+            sessions
+        }
+
+        pub fn write(&self, id: &str, data: &str) -> Result<(), KubiousError> {
+            let sessions = self.sessions_mutable();
+            let running = sessions.get(id).ok_or("Unknown exec session".to_string())?;
+            running
+                .stdin
+                .send(data.as_bytes().to_vec())
+                .or(Err("Exec session has ended".into()))
+        }
+
+        pub fn resize(&self, id: &str, width: u16, height: u16) -> Result<(), KubiousError> {
+            let mut sessions = self.sessions_mutable();
+            let running = sessions
+                .get_mut(id)
+                .ok_or("Unknown exec session".to_string())?;
+            let Some(resize) = running.resize.as_mut() else {
+                return Err("Exec session has no terminal".into());
+            };
+            running.size = Some((width, height));
+            resize
+                .try_send(TerminalSize { width, height })
+                .or(Err("Failed to resize terminal".into()))
+        }
+    }
+
+    async fn pump_output(
+        handle: AppHandle,
+        channel: String,
+        stream: OutputStream,
+        reader: Option<impl AsyncRead + Unpin>,
+    ) {
+        let Some(mut reader) = reader else {
+            return;
+        };
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        let mut pending: Vec<u8> = Vec::new();
+        while let Ok(read) = reader.read(&mut chunk).await {
+            if read == 0 {
+                break;
+            }
+            pending.extend_from_slice(&chunk[..read]);
+            let data = take_text(&mut pending);
+            if !data.is_empty() {
+                let _ = handle.emit(
+                    channel.as_str(),
+                    ExecEvent::Output {
+                        stream: stream.clone(),
+                        data,
+                    },
+                );
+            }
+        }
+    }
+
+    async fn pump_input(
+        input: &mut UnboundedReceiver<Vec<u8>>,
+        writer: Option<impl AsyncWrite + Unpin>,
+    ) {
+        let Some(mut writer) = writer else {
+            return;
+        };
+        while let Some(data) = input.recv().await {
+            if writer.write_all(&data).await.is_err() || writer.flush().await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// How one connection of a session ended.
+    enum Outcome {
+        /// The process exited with the status the API server reported.
+        Exited(Box<Status>),
+        /// The connection closed without a status.
+        Dropped,
+        Stopped,
+    }
+
+    /// Pumps output and input for one connection until the process exits,
+    /// the connection drops or the session is stopped.
+    async fn run(
+        handle: &AppHandle,
+        channel: &str,
+        mut process: AttachedProcess,
+        input: &mut UnboundedReceiver<Vec<u8>>,
+        stopped: &mut oneshot::Receiver<()>,
+    ) -> Outcome {
+        let stdout = process.stdout();
+        let stderr = process.stderr();
+        let writer = process.stdin();
+        let status = process.take_status();
+        let run = async {
+            let output = future::join(
+                pump_output(
+                    handle.clone(),
+                    channel.to_string(),
+                    OutputStream::Stdout,
+                    stdout,
+                ),
+                pump_output(
+                    handle.clone(),
+                    channel.to_string(),
+                    OutputStream::Stderr,
+                    stderr,
+                ),
+            );
+            let input = pump_input(input, writer);
+            if let Either::Right((_, output)) =
+                future::select(Box::pin(output), Box::pin(input)).await
+            {
+                output.await;
+            }
+            match status {
+                Some(status) => status.await,
+                None => None,
+            }
+        };
+        let outcome = match future::select(Box::pin(run), stopped).await {
+            Either::Left((Some(status), _)) => Outcome::Exited(Box::new(status)),
+            Either::Left((None, _)) => Outcome::Dropped,
+            Either::Right(_) => {
+                process.abort();
+                Outcome::Stopped
+            }
+        };
+        let _ = process.join().await;
+        outcome
+    }
+
+    /// Starts a new process for a session whose connection dropped, retrying
+    /// with backoff. The new process replaces the session's terminal resize
+    /// channel and gets the last known size. `None` once the session was
+    /// stopped or the reconnect gave up.
+    async fn reconnect(
+        handle: &AppHandle,
+        api: &Api<Pod>,
+        session: &ExecSession,
+        params: &AttachParams,
+        reconnector: &mut Reconnector,
+    ) -> Option<AttachedProcess> {
+        let mut reason = "Exec connection closed".to_string();
+        loop {
+            if !reconnector.retry(reason.as_str()).await {
+                return None;
+            }
+            let mut process = match api
+                .exec(session.pod.as_str(), session.command.clone(), params)
+                .await
+            {
+                Ok(process) => process,
+                Err(e) => {
+                    reason = e.to_string();
+                    continue;
+                }
+            };
+            let mut resize = process.terminal_size();
+            let sessions = handle.state::<ExecSessions>();
+            let mut sessions = sessions.sessions_mutable();
+            let Some(running) = sessions.get_mut(&session.id) else {
+                process.abort();
+                return None;
+            };
+            if let (Some(sender), Some((width, height))) = (resize.as_mut(), running.size) {
+                let _ = sender.try_send(TerminalSize { width, height });
+            }
+            running.resize = resize;
+            drop(sessions);
+            reconnector.connected();
+            return Some(process);
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ExecOptions {
+        pub container: Option<String>,
+        /// Defaults to an interactive shell.
+        pub command: Option<Vec<String>>,
+        #[serde(default = "default_tty")]
+        pub tty: bool,
+        pub width: Option<u16>,
+        pub height: Option<u16>,
+    }
+
+    fn default_tty() -> bool {
+        true
+    }
+
+    impl Default for ExecOptions {
+        fn default() -> Self {
+            ExecOptions {
+                container: None,
+                command: None,
+                tty: default_tty(),
+                width: None,
+                height: None,
+            }
+        }
+    }
+
+    /// Runs a command in a container with stdin attached. Output is emitted
+    /// on the session's channel as it arrives; input and resizes go through
+    /// `ExecSessions`. With a TTY, stderr is merged into stdout by the
+    /// container runtime. When the connection drops without an exit status,
+    /// the default shell is started again on the same channel; the old
+    /// shell's state is lost.
+    pub async fn start(
+        handle: &AppHandle,
+        client: Client,
+        cluster: String,
+        namespace: String,
+        pod: String,
+        options: ExecOptions,
+    ) -> Result<ExecSession, KubiousError> {
+        let command: Vec<String> = options
+            .command
+            .clone()
+            .filter(|c| !c.is_empty())
+            .unwrap_or(DEFAULT_SHELL.iter().map(|s| s.to_string()).collect());
+        let mut params = if options.tty {
+            AttachParams::interactive_tty()
+        } else {
+            AttachParams::default().stdin(true)
+        };
+        if let Some(container) = options.container.as_ref() {
+            params = params.container(container.as_str());
+        }
+
+        let api: Api<Pod> = Api::namespaced(client, namespace.as_str());
+        let mut process = api
+            .exec(pod.as_str(), command.clone(), &params)
+            .await
+            .context("Failed to start exec session")?;
+
+        let sessions = handle.state::<ExecSessions>();
+        let id = sessions.next_id();
+        let session = ExecSession {
+            channel: format!("{}{}", EXEC_SESSION_CHANNEL_PREFIX, id),
+            id: id.clone(),
+            cluster,
+            namespace,
+            pod,
+            container: options.container.clone(),
+            command,
+            tty: options.tty,
+            started: Utc::now().to_rfc3339(),
+        };
+
+        let mut resize = process.terminal_size();
+        if let (Some(sender), Some(width), Some(height)) =
+            (resize.as_mut(), options.width, options.height)
+        {
+            let _ = sender.try_send(TerminalSize { width, height });
+        }
+        let (stdin, mut input) = mpsc::unbounded_channel::<Vec<u8>>();
+        // Re-running a custom command could repeat its side effects, so only
+        // the default shell is started again when the connection drops.
+        let reconnects = options.command.as_ref().is_none_or(|c| c.is_empty());
+
+        let (stop, mut stopped) = oneshot::channel::<()>();
+        let mut running = sessions.sessions_mutable();
+        tauri::async_runtime::spawn({
+            let handle = handle.clone();
+            let session = session.clone();
+            async move {
+                let mut reconnector = Reconnector::new(
+                    handle.clone(),
+                    "exec",
+                    session.id.as_str(),
+                    BackoffPolicy::default(),
+                );
+                reconnector.connected();
+                let status = loop {
+                    let outcome = run(
+                        &handle,
+                        session.channel.as_str(),
+                        process,
+                        &mut input,
+                        &mut stopped,
+                    )
+                    .await;
+                    match outcome {
+                        Outcome::Exited(status) => break Some(*status),
+                        Outcome::Stopped => break None,
+                        Outcome::Dropped if !reconnects => break None,
+                        Outcome::Dropped => {
+                            match reconnect(&handle, &api, &session, &params, &mut reconnector)
+                                .await
+                            {
+                                Some(reconnected) => process = reconnected,
+                                None => break None,
+                            }
+                        }
+                    }
+                };
+                reconnector.closed();
+
+                handle
+                    .state::<ExecSessions>()
+                    .sessions_mutable()
+                    .remove(&session.id);
+                let _ = handle.emit(
+                    session.channel.as_str(),
+                    ExecEvent::Exited {
+                        status: status.as_ref().and_then(|s| s.status.clone()),
+                        message: status.and_then(|s| s.message),
+                    },
+                );
+            }
+        });
+        running.insert(
+            id,
+            RunningExec {
+                session: session.clone(),
+                stdin,
+                resize,
+                size: options.width.zip(options.height),
+                stop,
+            },
+        );
+        Ok(session)
+    }
+
+    /// Tears down a session, closing the connection to the container.
+    pub fn stop(handle: &AppHandle, id: &str) -> bool {
+        let removed = handle.state::<ExecSessions>().sessions_mutable().remove(id);
+        if let Some(running) = removed {
+            let _ = running.stop.send(());
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,561 @@
+pub mod file_transfers {
+    use std::{
+        fs::{self, File},
+        io::{Read, Write},
+        path::{Component, Path, PathBuf},
+        time::UNIX_EPOCH,
+    };
+
+    use futures::future;
+    use k8s_openapi::api::core::v1::Pod;
+    use kube::{api::AttachParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use tauri::{AppHandle, Emitter};
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+    use crate::util::{
+        kubious_error::{ErrorContext, KubiousError},
+        tar_stream::{self, EntryType, BLOCK_SIZE},
+    };
+
+    /// Event channel carrying a `TransferProgress` as files are copied.
+    pub const TRANSFER_EVENT: &str = "file-transfer";
+
+    const CHUNK_SIZE: usize = 64 * 1024;
+    /// Bytes copied between progress events within one file.
+    const PROGRESS_STEP: u64 = 1024 * 1024;
+    /// Upper bound for long names and PAX headers read into memory.
+    const MAX_METADATA_SIZE: u64 = 1024 * 1024;
+
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+    pub enum TransferDirection {
+        Upload,
+        Download,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TransferProgress {
+        pub direction: TransferDirection,
+        pub namespace: String,
+        pub pod: String,
+        pub remote_path: String,
+        /// The file being copied, relative to the copied path.
+        pub current: Option<String>,
+        pub files: usize,
+        pub bytes: u64,
+        /// Known for uploads only; a download's size is not known upfront.
+        pub total_bytes: Option<u64>,
+        pub done: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TransferResult {
+        pub direction: TransferDirection,
+        pub local_path: String,
+        pub remote_path: String,
+        pub files: usize,
+        pub bytes: u64,
+        /// Symlinks and special files, which are not copied.
+        pub skipped: Vec<String>,
+    }
+
+    struct Progress<'a> {
+        handle: &'a AppHandle,
+        event: TransferProgress,
+        reported: u64,
+    }
+
+    impl Progress<'_> {
+        fn emit(&mut self) {
+            self.reported = self.event.bytes;
+            let _ = self.handle.emit(TRANSFER_EVENT, self.event.clone());
+        }
+
+        fn file(&mut self, path: &str) {
+            self.event.current = Some(path.to_string());
+            self.event.files += 1;
+            self.emit();
+        }
+
+        fn bytes(&mut self, count: u64) {
+            self.event.bytes += count;
+            if self.event.bytes - self.reported >= PROGRESS_STEP {
+                self.emit();
+            }
+        }
+
+        fn finish(&mut self) {
+            self.event.current = None;
+            self.event.done = true;
+            self.emit();
+        }
+    }
+
+    struct LocalEntry {
+        archive_path: String,
+        source: PathBuf,
+        entry_type: EntryType,
+        size: u64,
+        mtime: u64,
+    }
+
+    /// Splits a container path into its directory and last component.
+    fn split_remote(path: &str) -> Result<(String, String), KubiousError> {
+        let trimmed = path.trim_end_matches('/');
+        let (parent, name) = match trimmed.rsplit_once('/') {
+            Some(("", name)) => ("/", name),
+            Some((parent, name)) => (parent, name),
+            None => (".", trimmed),
+        };
+        if name.is_empty() || name == "." || name == ".." {
+            return Err(format!("Cannot copy to or from {}", path).into());
+        }
+        Ok((parent.to_string(), name.to_string()))
+    }
+
+    fn mtime(metadata: &fs::Metadata) -> u64 {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    /// Lists what an upload sends, directories before their contents.
+    fn collect_local(
+        root: &Path,
+        name: &str,
+        skipped: &mut Vec<String>,
+    ) -> Result<Vec<LocalEntry>, KubiousError> {
+        let mut entries = Vec::new();
+        let mut pending = vec![(root.to_path_buf(), name.to_string())];
+        while let Some((path, archive_path)) = pending.pop() {
+            let metadata = fs::symlink_metadata(&path)
+                .context(format!("Failed to read {}", path.display()))?;
+            if metadata.is_dir() {
+                entries.push(LocalEntry {
+                    archive_path: archive_path.clone(),
+                    source: path.clone(),
+                    entry_type: EntryType::Directory,
+                    size: 0,
+                    mtime: mtime(&metadata),
+                });
+                let mut children: Vec<PathBuf> = fs::read_dir(&path)
+                    .context(format!("Failed to read {}", path.display()))?
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .collect();
+                children.sort();
+                for child in children.into_iter().rev() {
+                    let child_name = child
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    pending.push((child, format!("{}/{}", archive_path, child_name)));
+                }
+            } else if metadata.is_file() {
+                entries.push(LocalEntry {
+                    archive_path,
+                    source: path,
+                    entry_type: EntryType::File,
+                    size: metadata.len(),
+                    mtime: mtime(&metadata),
+                });
+            } else {
+                skipped.push(archive_path);
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn collect_stderr(reader: Option<impl AsyncRead + Unpin>) -> String {
+        let mut output = Vec::new();
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_end(&mut output).await;
+        }
+        String::from_utf8_lossy(&output).trim().to_string()
+    }
+
+    /// Turns a failed `tar` in the container into a readable error.
+    fn tar_error(
+        status: Option<String>,
+        message: Option<String>,
+        stderr: String,
+    ) -> Result<(), KubiousError> {
+        if status.as_deref() != Some("Failure") {
+            return Ok(());
+        }
+        let message = message.unwrap_or_default();
+        if message.contains("executable file not found")
+            || (stderr.contains("not found") && stderr.contains("tar"))
+        {
+            return Err("The container has no tar, which copying files needs".into());
+        }
+        Err(if stderr.is_empty() {
+            message.into()
+        } else {
+            stderr.into()
+        })
+    }
+
+    async fn send_entries(
+        writer: &mut (impl AsyncWrite + Unpin),
+        entries: &[LocalEntry],
+        progress: &mut Progress<'_>,
+    ) -> Result<(), KubiousError> {
+        let failed = |e: std::io::Error| KubiousError::from(e).with_context("Failed to send files");
+        let mut written: u64 = 0;
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        for entry in entries {
+            let header = tar_stream::header(
+                entry.archive_path.as_str(),
+                entry.entry_type,
+                entry.size,
+                if entry.entry_type == EntryType::Directory {
+                    0o755
+                } else {
+                    0o644
+                },
+                entry.mtime,
+            );
+            writer.write_all(&header).await.map_err(failed)?;
+            written += header.len() as u64;
+            if entry.entry_type != EntryType::File {
+                continue;
+            }
+            progress.file(entry.archive_path.as_str());
+
+            // The header promised `size` bytes; a file that changed since
+            // is cut off or zero-filled to keep the archive readable.
+            let mut file = File::open(&entry.source)
+                .context(format!("Failed to read {}", entry.source.display()))?;
+            let mut remaining = entry.size;
+            while remaining > 0 {
+                let wanted = remaining.min(CHUNK_SIZE as u64) as usize;
+                let mut read = file.read(&mut chunk[..wanted]).unwrap_or(0);
+                if read == 0 {
+                    chunk[..wanted].fill(0);
+                    read = wanted;
+                }
+                writer.write_all(&chunk[..read]).await.map_err(failed)?;
+                remaining -= read as u64;
+                progress.bytes(read as u64);
+            }
+            let padding = tar_stream::padding(entry.size);
+            writer
+                .write_all(&vec![0u8; padding])
+                .await
+                .map_err(failed)?;
+            written += entry.size + padding as u64;
+        }
+        writer
+            .write_all(&tar_stream::end_of_archive(written))
+            .await
+            .map_err(failed)?;
+        writer.flush().await.map_err(failed)
+    }
+
+    /// Copies a local file or directory to `remote_path` in the container,
+    /// creating missing parent directories, like `kubectl cp`. A
+    /// `remote_path` ending in `/` receives the local name inside it.
+    /// Needs `sh` and `tar` in the container. Permissions are not carried
+    /// over: files arrive as 0644 and directories as 0755.
+    pub async fn upload(
+        handle: &AppHandle,
+        client: Client,
+        namespace: String,
+        pod: String,
+        container: Option<String>,
+        local_path: String,
+        remote_path: String,
+    ) -> Result<TransferResult, KubiousError> {
+        let local = PathBuf::from(local_path.as_str());
+        let local_name = local
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or(format!("Cannot copy {}", local_path))?;
+        let target = if remote_path.ends_with('/') {
+            format!("{}{}", remote_path, local_name)
+        } else {
+            remote_path.clone()
+        };
+        let (parent, name) = split_remote(target.as_str())?;
+        let mut skipped = Vec::new();
+        let entries = collect_local(&local, name.as_str(), &mut skipped)?;
+
+        let mut params = AttachParams::default()
+            .stdin(true)
+            .stdout(false)
+            .stderr(true);
+        if let Some(container) = container.as_ref() {
+            params = params.container(container.as_str());
+        }
+        let command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "mkdir -p \"$0\" && tar -xmf - -C \"$0\"".to_string(),
+            parent,
+        ];
+        let mut process = Api::<Pod>::namespaced(client, namespace.as_str())
+            .exec(pod.as_str(), command, &params)
+            .await
+            .context("Failed to start the copy")?;
+
+        let mut progress = Progress {
+            handle,
+            event: TransferProgress {
+                direction: TransferDirection::Upload,
+                namespace,
+                pod,
+                remote_path: target.clone(),
+                current: None,
+                files: 0,
+                bytes: 0,
+                total_bytes: Some(entries.iter().map(|e| e.size).sum()),
+                done: false,
+            },
+            reported: 0,
+        };
+        let Some(mut writer) = process.stdin() else {
+            return Err("Failed to attach to the container's stdin".into());
+        };
+        let stderr = process.stderr();
+        let status = process.take_status();
+        let (sent, stderr) = future::join(
+            async {
+                let sent = send_entries(&mut writer, &entries, &mut progress).await;
+                drop(writer);
+                sent
+            },
+            collect_stderr(stderr),
+        )
+        .await;
+        let status = match status {
+            Some(status) => status.await,
+            None => None,
+        };
+        let _ = process.join().await;
+        tar_error(
+            status.as_ref().and_then(|s| s.status.clone()),
+            status.and_then(|s| s.message),
+            stderr,
+        )?;
+        sent?;
+        progress.finish();
+
+        Ok(TransferResult {
+            direction: TransferDirection::Upload,
+            local_path,
+            remote_path: target,
+            files: progress.event.files,
+            bytes: progress.event.bytes,
+            skipped,
+        })
+    }
+
+    /// Reads exactly `buffer.len()` bytes, or fails on a cut-off archive.
+    async fn read_block(
+        reader: &mut (impl AsyncRead + Unpin),
+        buffer: &mut [u8],
+    ) -> Result<(), KubiousError> {
+        reader
+            .read_exact(buffer)
+            .await
+            .map(|_| ())
+            .context("The archive from the container ended early")
+    }
+
+    async fn skip(
+        reader: &mut (impl AsyncRead + Unpin),
+        mut count: u64,
+    ) -> Result<(), KubiousError> {
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        while count > 0 {
+            let wanted = count.min(CHUNK_SIZE as u64) as usize;
+            read_block(reader, &mut chunk[..wanted]).await?;
+            count -= wanted as u64;
+        }
+        Ok(())
+    }
+
+    /// Maps an archive path below the copied directory onto the local
+    /// destination, refusing anything that would escape it.
+    fn local_target(root: &Path, archive_path: &str) -> Option<PathBuf> {
+        let relative = Path::new(archive_path.trim_start_matches("./"));
+        let mut components = relative.components();
+        components.next()?;
+        let mut target = root.to_path_buf();
+        for component in components {
+            match component {
+                Component::Normal(part) => target.push(part),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        Some(target)
+    }
+
+    async fn receive_entries(
+        reader: &mut (impl AsyncRead + Unpin),
+        root: &Path,
+        progress: &mut Progress<'_>,
+        skipped: &mut Vec<String>,
+    ) -> Result<(), KubiousError> {
+        let mut block = [0u8; BLOCK_SIZE];
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        let mut next_path: Option<String> = None;
+        loop {
+            // `tar` writes nothing at all when the path cannot be read.
+            if reader.read_exact(&mut block).await.is_err() {
+                return Ok(());
+            }
+            let Some(header) = tar_stream::parse_header(&block) else {
+                return Ok(());
+            };
+            let padding = tar_stream::padding(header.size) as u64;
+            if matches!(header.entry_type, EntryType::LongName | EntryType::Extended) {
+                if header.size > MAX_METADATA_SIZE {
+                    return Err("The archive from the container is malformed".into());
+                }
+                let mut data = vec![0u8; header.size as usize];
+                read_block(reader, &mut data).await?;
+                skip(reader, padding).await?;
+                next_path = match header.entry_type {
+                    EntryType::LongName => Some(tar_stream::long_name(&data)),
+                    _ => tar_stream::pax_path(&data).or(next_path),
+                };
+                continue;
+            }
+
+            let path = next_path.take().unwrap_or(header.path);
+            let Some(target) = local_target(root, path.as_str()) else {
+                skipped.push(path);
+                skip(reader, header.size + padding).await?;
+                continue;
+            };
+            match header.entry_type {
+                EntryType::Directory => {
+                    fs::create_dir_all(&target)
+                        .context(format!("Failed to create {}", target.display()))?;
+                    skip(reader, header.size + padding).await?;
+                }
+                EntryType::File => {
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)
+                            .context(format!("Failed to create {}", parent.display()))?;
+                    }
+                    let mut file = File::create(&target)
+                        .context(format!("Failed to write {}", target.display()))?;
+                    progress.file(path.as_str());
+                    let mut remaining = header.size;
+                    while remaining > 0 {
+                        let wanted = remaining.min(CHUNK_SIZE as u64) as usize;
+                        read_block(reader, &mut chunk[..wanted]).await?;
+                        file.write_all(&chunk[..wanted])
+                            .context(format!("Failed to write {}", target.display()))?;
+                        remaining -= wanted as u64;
+                        progress.bytes(wanted as u64);
+                    }
+                    skip(reader, padding).await?;
+                }
+                _ => {
+                    skipped.push(path);
+                    skip(reader, header.size + padding).await?;
+                }
+            }
+        }
+    }
+
+    /// Copies a file or directory from the container to `local_path`, like
+    /// `kubectl cp`. An existing local directory receives the remote name
+    /// inside it. Needs `tar` in the container; symlinks are skipped.
+    pub async fn download(
+        handle: &AppHandle,
+        client: Client,
+        namespace: String,
+        pod: String,
+        container: Option<String>,
+        remote_path: String,
+        local_path: String,
+    ) -> Result<TransferResult, KubiousError> {
+        let (parent, name) = split_remote(remote_path.as_str())?;
+        let local = PathBuf::from(local_path.as_str());
+        let root = if local.is_dir() {
+            local.join(name.as_str())
+        } else {
+            local
+        };
+
+        let mut params = AttachParams::default().stdout(true).stderr(true);
+        if let Some(container) = container.as_ref() {
+            params = params.container(container.as_str());
+        }
+        let command = vec![
+            "tar".to_string(),
+            "cf".to_string(),
+            "-".to_string(),
+            "-C".to_string(),
+            parent,
+            name,
+        ];
+        let mut process = Api::<Pod>::namespaced(client, namespace.as_str())
+            .exec(pod.as_str(), command, &params)
+            .await
+            .context("Failed to start the copy")?;
+
+        let mut progress = Progress {
+            handle,
+            event: TransferProgress {
+                direction: TransferDirection::Download,
+                namespace,
+                pod,
+                remote_path: remote_path.clone(),
+                current: None,
+                files: 0,
+                bytes: 0,
+                total_bytes: None,
+                done: false,
+            },
+            reported: 0,
+        };
+        let Some(mut reader) = process.stdout() else {
+            return Err("Failed to attach to the container's stdout".into());
+        };
+        let stderr = process.stderr();
+        let status = process.take_status();
+        let mut skipped = Vec::new();
+        let (received, stderr) = future::join(
+            async {
+                let received =
+                    receive_entries(&mut reader, root.as_path(), &mut progress, &mut skipped).await;
+                // Drain what follows the end marker so the exec can finish.
+                let _ = tokio::io::copy(&mut reader, &mut tokio::io::sink()).await;
+                received
+            },
+            collect_stderr(stderr),
+        )
+        .await;
+        let status = match status {
+            Some(status) => status.await,
+            None => None,
+        };
+        let _ = process.join().await;
+        tar_error(
+            status.as_ref().and_then(|s| s.status.clone()),
+            status.and_then(|s| s.message),
+            stderr,
+        )?;
+        received?;
+        progress.finish();
+
+        Ok(TransferResult {
+            direction: TransferDirection::Download,
+            local_path: root.display().to_string(),
+            remote_path,
+            files: progress.event.files,
+            bytes: progress.event.bytes,
+            skipped,
+        })
+    }
+}
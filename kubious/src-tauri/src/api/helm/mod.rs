@@ -1,10 +1,31 @@
 pub mod helm_api {
-    use crate::CommandHandler;
+    use std::collections::HashMap;
+
+    use crate::{
+        api::app_state::AppState,
+        util::kubious_error::{ErrorContext, KubiousError},
+        CommandHandler,
+    };
+    use kube::{
+        api::{DynamicObject, ListParams},
+        discovery::{verbs, Discovery, Scope},
+        Api, Client,
+    };
     use serde::{Deserialize, Serialize};
     use serde_json::Value;
+    use tauri::Manager;
     use tauri_plugin_shell::ShellExt;
 
-    async fn get_shell_version(handle: &tauri::AppHandle) -> Result<String, String> {
+    use super::{
+        helm_releases::{self, HelmMode},
+        helm_values,
+    };
+
+    const MANAGED_BY_SELECTOR: &str = "app.kubernetes.io/managed-by=Helm";
+    const RELEASE_NAME_ANNOTATION: &str = "meta.helm.sh/release-name";
+    const RELEASE_NAMESPACE_ANNOTATION: &str = "meta.helm.sh/release-namespace";
+
+    async fn get_shell_version(handle: &tauri::AppHandle) -> Result<String, KubiousError> {
         let shell = handle.shell();
         let output = shell
             .command("helm")
@@ -19,23 +40,416 @@ pub mod helm_api {
                     Err(format!(
                         "Command failed with code {}",
                         out.status.code().unwrap_or(-1)
-                    ))
+                    )
+                    .into())
                 }
             });
         output
     }
 
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ResourceHealth {
+        pub kind: String,
+        pub api_version: String,
+        pub name: String,
+        pub namespace: Option<String>,
+        pub healthy: bool,
+        pub message: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ReleaseHealth {
+        pub release: String,
+        pub namespace: String,
+        pub healthy: bool,
+        pub total: usize,
+        pub unhealthy: usize,
+        pub resources: Vec<ResourceHealth>,
+    }
+
+    impl ReleaseHealth {
+        fn from_resources(
+            release: String,
+            namespace: String,
+            resources: Vec<ResourceHealth>,
+        ) -> Self {
+            let unhealthy = resources.iter().filter(|r| !r.healthy).count();
+            ReleaseHealth {
+                release,
+                namespace,
+                healthy: unhealthy == 0,
+                total: resources.len(),
+                unhealthy,
+                resources,
+            }
+        }
+    }
+
+    fn condition_status(data: &Value, condition: &str) -> Option<(bool, Option<String>)> {
+        data["status"]["conditions"]
+            .as_array()
+            .and_then(|conditions| {
+                conditions
+                    .iter()
+                    .find(|c| c["type"].as_str() == Some(condition))
+            })
+            .map(|c| {
+                (
+                    c["status"].as_str() == Some("True"),
+                    c["message"].as_str().map(|m| m.to_string()),
+                )
+            })
+    }
+
+    /// Best-effort health evaluation for a single Helm-created object.
+    /// Kinds without a meaningful status are considered healthy.
+    fn evaluate_health(kind: &str, data: &Value) -> (bool, Option<String>) {
+        let status = &data["status"];
+        match kind {
+            "Deployment" | "StatefulSet" | "ReplicaSet" => {
+                let desired = data["spec"]["replicas"].as_i64().unwrap_or(1);
+                let ready = status["readyReplicas"].as_i64().unwrap_or(0);
+                if ready >= desired {
+                    (true, None)
+                } else {
+                    (false, Some(format!("{}/{} replicas ready", ready, desired)))
+                }
+            }
+            "DaemonSet" => {
+                let desired = status["desiredNumberScheduled"].as_i64().unwrap_or(0);
+                let ready = status["numberReady"].as_i64().unwrap_or(0);
+                if ready >= desired {
+                    (true, None)
+                } else {
+                    (false, Some(format!("{}/{} pods ready", ready, desired)))
+                }
+            }
+            "Pod" => match status["phase"].as_str() {
+                Some("Running") | Some("Succeeded") => (true, None),
+                Some(phase) => (false, Some(format!("Pod is {}", phase))),
+                None => (false, Some("Pod has no phase".to_string())),
+            },
+            "Job" => match condition_status(data, "Failed") {
+                Some((true, message)) => (false, message.or(Some("Job failed".to_string()))),
+                _ => (true, None),
+            },
+            "PersistentVolumeClaim" => match status["phase"].as_str() {
+                Some("Bound") => (true, None),
+                Some(phase) => (false, Some(format!("Claim is {}", phase))),
+                None => (false, Some("Claim has no phase".to_string())),
+            },
+            _ => match condition_status(data, "Ready") {
+                Some((false, message)) => (false, message.or(Some("Not ready".to_string()))),
+                _ => (true, None),
+            },
+        }
+    }
+
+    /// Collects every object carrying Helm's release annotations, grouped by
+    /// `(release namespace, release name)`.
+    async fn collect_release_resources(
+        client: Client,
+        namespace: Option<String>,
+    ) -> Result<HashMap<(String, String), Vec<ResourceHealth>>, KubiousError> {
+        let discovery = Discovery::new(client.clone())
+            .run()
+            .await
+            .context("Failed to run API discovery.")?;
+        let params = ListParams::default().labels(MANAGED_BY_SELECTOR);
+        let mut releases: HashMap<(String, String), Vec<ResourceHealth>> = HashMap::new();
+
+        for group in discovery.groups() {
+            for (resource, caps) in group.recommended_resources() {
+                if !caps.supports_operation(verbs::LIST) || resource.kind == "Event" {
+                    continue;
+                }
+                let api: Api<DynamicObject> = match (&caps.scope, &namespace) {
+                    (Scope::Namespaced, Some(ns)) => {
+                        Api::namespaced_with(client.clone(), ns.as_str(), &resource)
+                    }
+                    _ => Api::all_with(client.clone(), &resource),
+                };
+                let objects = match api.list(&params).await {
+                    Ok(list) => list.items,
+                    Err(_) => continue,
+                };
+
+                for object in objects {
+                    let annotations = object.metadata.annotations.clone().unwrap_or_default();
+                    let (Some(release), Some(release_ns)) = (
+                        annotations.get(RELEASE_NAME_ANNOTATION),
+                        annotations.get(RELEASE_NAMESPACE_ANNOTATION),
+                    ) else {
+                        continue;
+                    };
+                    if let Some(ns) = &namespace {
+                        if release_ns != ns {
+                            continue;
+                        }
+                    }
+
+                    let (healthy, message) = evaluate_health(resource.kind.as_str(), &object.data);
+                    releases
+                        .entry((release_ns.clone(), release.clone()))
+                        .or_default()
+                        .push(ResourceHealth {
+                            kind: resource.kind.clone(),
+                            api_version: resource.api_version.clone(),
+                            name: object.metadata.name.clone().unwrap_or_default(),
+                            namespace: object.metadata.namespace.clone(),
+                            healthy,
+                            message,
+                        });
+                }
+            }
+        }
+
+        Ok(releases)
+    }
+
     #[derive(Serialize, Deserialize, Clone, Debug)]
     #[serde(tag = "command")]
     pub enum HelmCommand {
         GetVersion {},
+        ReleaseHealth {
+            namespace: String,
+            release: String,
+        },
+        ListReleaseHealth {
+            namespace: Option<String>,
+        },
+        /// Releases read from Helm's storage, newest revision of each.
+        ListReleases {
+            namespace: Option<String>,
+        },
+        GetReleaseHistory {
+            namespace: String,
+            release: String,
+        },
+        /// Values of a revision, the newest if unset. `all` merges in the
+        /// chart's defaults.
+        GetReleaseValues {
+            namespace: String,
+            release: String,
+            revision: Option<i64>,
+            #[serde(default)]
+            all: bool,
+        },
+        GetReleaseManifest {
+            namespace: String,
+            release: String,
+            revision: Option<i64>,
+        },
+        UninstallRelease {
+            namespace: String,
+            release: String,
+            #[serde(default)]
+            keep_history: bool,
+            #[serde(default)]
+            mode: HelmMode,
+        },
+        /// Rolls back to `revision`, or the one before the newest.
+        RollbackRelease {
+            namespace: String,
+            release: String,
+            revision: Option<i64>,
+            #[serde(default)]
+            mode: HelmMode,
+        },
+        /// Copies the user-supplied values of every revision Helm still
+        /// keeps into the artifact store.
+        ImportValuesHistory {
+            namespace: String,
+            release: String,
+        },
+        ListStoredValues {
+            namespace: String,
+            release: String,
+        },
+        GetStoredValues {
+            namespace: String,
+            release: String,
+            revision: i64,
+        },
+        DiffStoredValues {
+            namespace: String,
+            release: String,
+            from: i64,
+            to: i64,
+        },
     }
 
     impl CommandHandler for HelmCommand {
-        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, String> {
-            match *self {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            match self {
                 HelmCommand::GetVersion {} => self.wrap_in_value(get_shell_version(handle).await),
+                HelmCommand::ReleaseHealth { namespace, release } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let mut releases =
+                        collect_release_resources(client, Some(namespace.clone())).await?;
+                    if let Some(resources) = releases.remove(&(namespace.clone(), release.clone()))
+                    {
+                        self.wrap_in_value(Ok(ReleaseHealth::from_resources(
+                            release.clone(),
+                            namespace.clone(),
+                            resources,
+                        )))
+                    } else {
+                        Err("No resources found for release".into())
+                    }
+                }
+                HelmCommand::ListReleaseHealth { namespace } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let releases = collect_release_resources(client, namespace.clone()).await?;
+                    let mut rollup: Vec<ReleaseHealth> = releases
+                        .into_iter()
+                        .map(|((ns, name), resources)| {
+                            ReleaseHealth::from_resources(name, ns, resources)
+                        })
+                        .collect();
+                    rollup.sort_by(|a, b| {
+                        (&a.namespace, &a.release).cmp(&(&b.namespace, &b.release))
+                    });
+                    self.wrap_in_value(Ok(rollup))
+                }
+                HelmCommand::ListReleases { namespace } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(helm_releases::list(client, namespace.as_deref()).await)
+                }
+                HelmCommand::GetReleaseHistory { namespace, release } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        helm_releases::history(client, namespace.as_str(), release.as_str()).await,
+                    )
+                }
+                HelmCommand::GetReleaseValues {
+                    namespace,
+                    release,
+                    revision,
+                    all,
+                } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        helm_releases::values(
+                            client,
+                            namespace.as_str(),
+                            release.as_str(),
+                            *revision,
+                            *all,
+                        )
+                        .await,
+                    )
+                }
+                HelmCommand::GetReleaseManifest {
+                    namespace,
+                    release,
+                    revision,
+                } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        helm_releases::manifest(
+                            client,
+                            namespace.as_str(),
+                            release.as_str(),
+                            *revision,
+                        )
+                        .await,
+                    )
+                }
+                HelmCommand::UninstallRelease {
+                    namespace,
+                    release,
+                    keep_history,
+                    mode,
+                } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        helm_releases::uninstall(
+                            handle,
+                            client,
+                            namespace.as_str(),
+                            release.as_str(),
+                            *keep_history,
+                            *mode,
+                        )
+                        .await,
+                    )
+                }
+                HelmCommand::RollbackRelease {
+                    namespace,
+                    release,
+                    revision,
+                    mode,
+                } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        helm_releases::rollback(
+                            handle,
+                            client,
+                            namespace.as_str(),
+                            release.as_str(),
+                            *revision,
+                            *mode,
+                        )
+                        .await,
+                    )
+                }
+                HelmCommand::ImportValuesHistory { namespace, release } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        helm_values::import(handle, client, namespace.clone(), release.clone())
+                            .await,
+                    )
+                }
+                HelmCommand::ListStoredValues { namespace, release } => self.wrap_in_value(
+                    helm_values::stored(handle, namespace.as_str(), release.as_str()),
+                ),
+                HelmCommand::GetStoredValues {
+                    namespace,
+                    release,
+                    revision,
+                } => self.wrap_in_value(helm_values::load(
+                    handle,
+                    namespace.as_str(),
+                    release.as_str(),
+                    *revision,
+                )),
+                HelmCommand::DiffStoredValues {
+                    namespace,
+                    release,
+                    from,
+                    to,
+                } => self.wrap_in_value(helm_values::diff(
+                    handle,
+                    namespace.as_str(),
+                    release.as_str(),
+                    *from,
+                    *to,
+                )),
             }
         }
     }
 }
+
+mod releases;
+pub use releases::helm_releases;
+mod values;
+pub use values::helm_values;
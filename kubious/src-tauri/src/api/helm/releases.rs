@@ -0,0 +1,799 @@
+pub mod helm_releases {
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    use k8s_openapi::{
+        api::core::v1::{ConfigMap, Secret},
+        chrono::Utc,
+        ByteString,
+    };
+    use kube::{
+        api::{DeleteParams, ListParams, ObjectMeta, Patch, PatchParams, PostParams},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use tauri::{AppHandle, Manager};
+    use tauri_plugin_shell::ShellExt;
+
+    use crate::{
+        api::{
+            app_credentials::resolve_secrets,
+            app_state::AppState,
+            application::app_terminal,
+            artifacts::artifact_apply::{self, ApplyOptions, ApplyOutcome},
+            helm::helm_values,
+            resources::crd_deletion::{self, CRD_DELETE_REFUSED},
+            views::resource_access::ResourceRef,
+        },
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    const RELEASE_SECRET_TYPE: &str = "helm.sh/release.v1";
+    const RESOURCE_POLICY_ANNOTATION: &str = "helm.sh/resource-policy";
+    const HELM_FIELD_MANAGER: &str = "helm";
+
+    /// Where Helm keeps its release records.
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+    pub enum ReleaseDriver {
+        Secret,
+        ConfigMap,
+    }
+
+    /// How uninstalls and rollbacks are carried out. `Native` works on the
+    /// release records and objects directly but does not run hooks;
+    /// `Binary` runs the `helm` CLI against the selected config.
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+    pub enum HelmMode {
+        #[default]
+        Native,
+        Binary,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ReleaseRevision {
+        pub revision: i64,
+        pub status: String,
+        pub chart: Option<String>,
+        pub app_version: Option<String>,
+        pub updated: Option<String>,
+        pub description: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ReleaseSummary {
+        pub name: String,
+        pub namespace: String,
+        pub driver: ReleaseDriver,
+        /// The newest revision.
+        #[serde(flatten)]
+        pub latest: ReleaseRevision,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ReleaseValues {
+        pub revision: i64,
+        /// Whether chart defaults are merged in, as `helm get values --all`.
+        pub computed: bool,
+        pub values: Value,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ReleaseHook {
+        pub name: String,
+        pub kind: String,
+        pub events: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ReleaseManifest {
+        pub revision: i64,
+        pub manifest: String,
+        pub hooks: Vec<ReleaseHook>,
+        pub notes: Option<String>,
+    }
+
+    /// An object of a release removed, or kept because of its
+    /// `helm.sh/resource-policy: keep` annotation.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RemovedObject {
+        pub kind: Option<String>,
+        pub name: Option<String>,
+        pub namespace: Option<String>,
+        pub kept: bool,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct UninstallResult {
+        pub release: String,
+        pub namespace: String,
+        pub mode: HelmMode,
+        pub removed: Vec<RemovedObject>,
+        /// Hooks `Native` mode did not run.
+        pub skipped_hooks: Vec<ReleaseHook>,
+        pub history_kept: bool,
+        /// Output of the `helm` CLI in `Binary` mode.
+        pub output: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RollbackResult {
+        pub release: String,
+        pub namespace: String,
+        pub mode: HelmMode,
+        /// The revision rolled back to.
+        pub target: i64,
+        /// The revision recorded for the rollback, if known.
+        pub revision: Option<i64>,
+        pub applied: Vec<ApplyOutcome>,
+        /// Objects of the replaced revision the target does not have.
+        pub removed: Vec<RemovedObject>,
+        pub skipped_hooks: Vec<ReleaseHook>,
+        pub output: Option<String>,
+    }
+
+    /// A release record, decoded on demand since listing only needs the
+    /// newest revision of each release.
+    struct Record {
+        driver: ReleaseDriver,
+        object: String,
+        name: String,
+        namespace: String,
+        revision: i64,
+        payload: Vec<u8>,
+    }
+
+    impl Record {
+        fn decode(&self) -> Result<Value, KubiousError> {
+            helm_values::decode(&self.payload)
+        }
+    }
+
+    fn record_of(
+        driver: ReleaseDriver,
+        metadata: ObjectMeta,
+        payload: Option<Vec<u8>>,
+    ) -> Option<Record> {
+        let labels = metadata.labels.unwrap_or_default();
+        Some(Record {
+            driver,
+            object: metadata.name?,
+            name: labels.get("name")?.clone(),
+            namespace: metadata.namespace?,
+            revision: labels.get("version")?.parse().ok()?,
+            payload: payload?,
+        })
+    }
+
+    /// Reads the release records of `namespace`, or of every namespace, from
+    /// Secrets (the default driver) or, failing that, ConfigMaps.
+    async fn records(
+        client: Client,
+        namespace: Option<&str>,
+        release: Option<&str>,
+    ) -> Result<Vec<Record>, KubiousError> {
+        let selector = match release {
+            Some(release) => format!("owner=helm,name={}", release),
+            None => "owner=helm".to_string(),
+        };
+        let params = ListParams::default().labels(selector.as_str());
+        let secrets: Api<Secret> = match namespace {
+            Some(namespace) => Api::namespaced(client.clone(), namespace),
+            None => Api::all(client.clone()),
+        };
+        let mut records: Vec<Record> = secrets
+            .list(&params)
+            .await
+            .context("Failed to list release secrets")?
+            .items
+            .into_iter()
+            .filter(|s| s.type_.as_deref() == Some(RELEASE_SECRET_TYPE))
+            .filter_map(|s| {
+                let payload = s.data.and_then(|d| d.get("release").map(|r| r.0.clone()));
+                record_of(ReleaseDriver::Secret, s.metadata, payload)
+            })
+            .collect();
+        if records.is_empty() {
+            let config_maps: Api<ConfigMap> = match namespace {
+                Some(namespace) => Api::namespaced(client, namespace),
+                None => Api::all(client),
+            };
+            records = config_maps
+                .list(&params)
+                .await
+                .context("Failed to list release configmaps")?
+                .items
+                .into_iter()
+                .filter_map(|c| {
+                    let payload = c
+                        .data
+                        .and_then(|d| d.get("release").map(|r| r.clone().into_bytes()));
+                    record_of(ReleaseDriver::ConfigMap, c.metadata, payload)
+                })
+                .collect();
+        }
+        records.sort_by_key(|r| r.revision);
+        Ok(records)
+    }
+
+    /// Records of one release, oldest first; an error if it has none.
+    async fn release_records(
+        client: Client,
+        namespace: &str,
+        release: &str,
+    ) -> Result<Vec<Record>, KubiousError> {
+        let records = records(client, Some(namespace), Some(release)).await?;
+        if records.is_empty() {
+            return Err(format!("Release {} not found in namespace {}", release, namespace).into());
+        }
+        Ok(records)
+    }
+
+    /// The requested revision, or the newest one.
+    fn pick(records: &[Record], revision: Option<i64>) -> Result<&Record, KubiousError> {
+        match revision {
+            Some(revision) => records
+                .iter()
+                .find(|r| r.revision == revision)
+                .ok_or(format!("Revision {} not found", revision).into()),
+            None => records.last().ok_or("Release has no revisions".into()),
+        }
+    }
+
+    fn describe(revision: i64, release: &Value) -> ReleaseRevision {
+        let text = |value: &Value| value.as_str().map(|s| s.to_string());
+        let metadata = &release["chart"]["metadata"];
+        ReleaseRevision {
+            revision,
+            status: release["info"]["status"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            chart: metadata["name"].as_str().map(|name| {
+                format!(
+                    "{}-{}",
+                    name,
+                    metadata["version"].as_str().unwrap_or_default()
+                )
+            }),
+            app_version: text(&metadata["appVersion"]),
+            updated: text(&release["info"]["last_deployed"]),
+            description: text(&release["info"]["description"]),
+        }
+    }
+
+    /// The newest revision of every release, by namespace and name.
+    pub async fn list(
+        client: Client,
+        namespace: Option<&str>,
+    ) -> Result<Vec<ReleaseSummary>, KubiousError> {
+        let mut latest: BTreeMap<(String, String), Record> = BTreeMap::new();
+        for record in records(client, namespace, None).await? {
+            latest.insert((record.namespace.clone(), record.name.clone()), record);
+        }
+        latest
+            .into_values()
+            .map(|record| {
+                Ok(ReleaseSummary {
+                    latest: describe(record.revision, &record.decode()?),
+                    name: record.name,
+                    namespace: record.namespace,
+                    driver: record.driver,
+                })
+            })
+            .collect()
+    }
+
+    /// Every revision Helm still keeps, oldest first.
+    pub async fn history(
+        client: Client,
+        namespace: &str,
+        release: &str,
+    ) -> Result<Vec<ReleaseRevision>, KubiousError> {
+        release_records(client, namespace, release)
+            .await?
+            .iter()
+            .map(|record| Ok(describe(record.revision, &record.decode()?)))
+            .collect()
+    }
+
+    /// Merges `overrides` into `defaults` as Helm coalesces values: maps are
+    /// merged key by key and a `null` override removes the default.
+    fn coalesce(defaults: &Value, overrides: &Value) -> Value {
+        match (defaults, overrides) {
+            (Value::Object(defaults), Value::Object(overrides)) => {
+                let mut merged = defaults.clone();
+                for (key, value) in overrides {
+                    if value.is_null() {
+                        merged.remove(key);
+                    } else {
+                        let next = match merged.get(key) {
+                            Some(default) => coalesce(default, value),
+                            None => value.clone(),
+                        };
+                        merged.insert(key.clone(), next);
+                    }
+                }
+                Value::Object(merged)
+            }
+            (_, overrides) => overrides.clone(),
+        }
+    }
+
+    /// The user-supplied values of a revision, or with `all` set, those
+    /// merged over the chart's defaults.
+    pub async fn values(
+        client: Client,
+        namespace: &str,
+        release: &str,
+        revision: Option<i64>,
+        all: bool,
+    ) -> Result<ReleaseValues, KubiousError> {
+        let records = release_records(client, namespace, release).await?;
+        let record = pick(&records, revision)?;
+        let decoded = record.decode()?;
+        let config = match &decoded["config"] {
+            Value::Null => json!({}),
+            config => config.clone(),
+        };
+        Ok(ReleaseValues {
+            revision: record.revision,
+            computed: all,
+            values: if all {
+                coalesce(&decoded["chart"]["values"], &config)
+            } else {
+                config
+            },
+        })
+    }
+
+    fn hooks_of(release: &Value) -> Vec<ReleaseHook> {
+        release["hooks"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|hook| ReleaseHook {
+                name: hook["name"].as_str().unwrap_or_default().to_string(),
+                kind: hook["kind"].as_str().unwrap_or_default().to_string(),
+                events: hook["events"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|e| e.as_str().map(|s| s.to_string()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn hooks_for(release: &Value, events: &[&str]) -> Vec<ReleaseHook> {
+        hooks_of(release)
+            .into_iter()
+            .filter(|hook| hook.events.iter().any(|e| events.contains(&e.as_str())))
+            .collect()
+    }
+
+    pub async fn manifest(
+        client: Client,
+        namespace: &str,
+        release: &str,
+        revision: Option<i64>,
+    ) -> Result<ReleaseManifest, KubiousError> {
+        let records = release_records(client, namespace, release).await?;
+        let record = pick(&records, revision)?;
+        let decoded = record.decode()?;
+        Ok(ReleaseManifest {
+            revision: record.revision,
+            manifest: decoded["manifest"].as_str().unwrap_or_default().to_string(),
+            hooks: hooks_of(&decoded),
+            notes: decoded["info"]["notes"]
+                .as_str()
+                .filter(|n| !n.is_empty())
+                .map(|s| s.to_string()),
+        })
+    }
+
+    /// The objects of a release's manifest. Objects without a namespace go
+    /// to the release namespace, as Helm installs them; cluster-scoped kinds
+    /// drop it again when resolved.
+    fn objects_of(release: &Value, namespace: &str) -> Vec<Value> {
+        artifact_apply::parse_documents(release["manifest"].as_str().unwrap_or_default())
+            .into_iter()
+            .filter_map(|document| document.manifest.ok())
+            .map(|mut object| {
+                if object["metadata"]["namespace"].as_str().is_none() {
+                    object["metadata"]["namespace"] = json!(namespace);
+                }
+                object
+            })
+            .collect()
+    }
+
+    fn identity(object: &Value) -> (String, String, String, String) {
+        let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+        let group = text(&object["apiVersion"])
+            .rsplit_once('/')
+            .map(|(group, _)| group.to_string())
+            .unwrap_or_default();
+        (
+            group,
+            text(&object["kind"]),
+            text(&object["metadata"]["namespace"]),
+            text(&object["metadata"]["name"]),
+        )
+    }
+
+    /// Deletes objects in reverse apply order, leaving those Helm is told to
+    /// keep. Objects already gone count as removed. CRDs are refused, since
+    /// deleting one takes every instance with it; use `DeleteCrd` instead.
+    async fn remove_objects(client: Client, objects: &[Value]) -> Vec<RemovedObject> {
+        let (mut ordered, _) = artifact_apply::dependency_order(objects);
+        ordered.reverse();
+        let mut removed: Vec<RemovedObject> = Vec::new();
+        for object in ordered {
+            let mut outcome = RemovedObject {
+                kind: object["kind"].as_str().map(|s| s.to_string()),
+                name: object["metadata"]["name"].as_str().map(|s| s.to_string()),
+                namespace: None,
+                kept: object["metadata"]["annotations"][RESOURCE_POLICY_ANNOTATION].as_str()
+                    == Some("keep"),
+                error: None,
+            };
+            if !outcome.kept {
+                match artifact_apply::resolve_manifest(client.clone(), &object, None).await {
+                    Ok(resolved) => {
+                        outcome.namespace = resolved.namespace.clone();
+                        let target = ResourceRef {
+                            group: resolved.resource.group.clone(),
+                            version: resolved.resource.version.clone(),
+                            kind: resolved.resource.kind.clone(),
+                            namespace: resolved.namespace.clone(),
+                            name: resolved.name.clone(),
+                        };
+                        if crd_deletion::is_crd(&target) {
+                            outcome.error = Some(CRD_DELETE_REFUSED.to_string());
+                        } else {
+                            match resolved
+                                .api
+                                .delete(resolved.name.as_str(), &DeleteParams::background())
+                                .await
+                            {
+                                Ok(_) => {}
+                                Err(kube::Error::Api(e)) if e.code == 404 => {}
+                                Err(e) => outcome.error = Some(e.to_string()),
+                            }
+                        }
+                    }
+                    Err(e) => outcome.error = Some(e.to_string()),
+                }
+            }
+            removed.push(outcome);
+        }
+        removed
+    }
+
+    fn record_labels(release: &str, revision: i64, status: &str) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("name".to_string(), release.to_string()),
+            ("owner".to_string(), "helm".to_string()),
+            ("status".to_string(), status.to_string()),
+            ("version".to_string(), revision.to_string()),
+        ])
+    }
+
+    /// Rewrites an existing record with `release`, keeping its status label
+    /// in step.
+    async fn update_record(
+        client: Client,
+        record: &Record,
+        release: &Value,
+    ) -> Result<(), KubiousError> {
+        let payload = helm_values::encode(release)?;
+        let labels = json!({ "status": release["info"]["status"].as_str().unwrap_or("unknown") });
+        let params = PatchParams::default();
+        let result = match record.driver {
+            ReleaseDriver::Secret => {
+                let encoded = serde_json::to_value(ByteString(payload.into_bytes()))
+                    .or(Err("Failed to encode release.".to_string()))?;
+                let patch = json!({
+                    "metadata": { "labels": labels },
+                    "data": { "release": encoded },
+                });
+                Api::<Secret>::namespaced(client, record.namespace.as_str())
+                    .patch(record.object.as_str(), &params, &Patch::Merge(&patch))
+                    .await
+                    .map(|_| ())
+            }
+            ReleaseDriver::ConfigMap => {
+                let patch = json!({
+                    "metadata": { "labels": labels },
+                    "data": { "release": payload },
+                });
+                Api::<ConfigMap>::namespaced(client, record.namespace.as_str())
+                    .patch(record.object.as_str(), &params, &Patch::Merge(&patch))
+                    .await
+                    .map(|_| ())
+            }
+        };
+        result.context(format!("Failed to update release record {}", record.object))
+    }
+
+    /// Stores a new revision under Helm's `sh.helm.release.v1.<name>.v<n>`
+    /// naming, with the driver the release already uses.
+    async fn create_record(
+        client: Client,
+        driver: ReleaseDriver,
+        namespace: &str,
+        name: &str,
+        revision: i64,
+        release: &Value,
+    ) -> Result<(), KubiousError> {
+        let payload = helm_values::encode(release)?;
+        let metadata = ObjectMeta {
+            name: Some(format!("sh.helm.release.v1.{}.v{}", name, revision)),
+            namespace: Some(namespace.to_string()),
+            labels: Some(record_labels(
+                name,
+                revision,
+                release["info"]["status"].as_str().unwrap_or("unknown"),
+            )),
+            ..ObjectMeta::default()
+        };
+        let params = PostParams::default();
+        let result = match driver {
+            ReleaseDriver::Secret => Api::<Secret>::namespaced(client, namespace)
+                .create(
+                    &params,
+                    &Secret {
+                        metadata,
+                        type_: Some(RELEASE_SECRET_TYPE.to_string()),
+                        data: Some(BTreeMap::from([(
+                            "release".to_string(),
+                            ByteString(payload.into_bytes()),
+                        )])),
+                        ..Secret::default()
+                    },
+                )
+                .await
+                .map(|_| ()),
+            ReleaseDriver::ConfigMap => Api::<ConfigMap>::namespaced(client, namespace)
+                .create(
+                    &params,
+                    &ConfigMap {
+                        metadata,
+                        data: Some(BTreeMap::from([("release".to_string(), payload)])),
+                        ..ConfigMap::default()
+                    },
+                )
+                .await
+                .map(|_| ()),
+        };
+        result.context(format!("Failed to store revision {}", revision))
+    }
+
+    async fn delete_record(client: Client, record: &Record) -> Result<(), KubiousError> {
+        let params = DeleteParams::default();
+        let result = match record.driver {
+            ReleaseDriver::Secret => Api::<Secret>::namespaced(client, record.namespace.as_str())
+                .delete(record.object.as_str(), &params)
+                .await
+                .map(|_| ()),
+            ReleaseDriver::ConfigMap => {
+                Api::<ConfigMap>::namespaced(client, record.namespace.as_str())
+                    .delete(record.object.as_str(), &params)
+                    .await
+                    .map(|_| ())
+            }
+        };
+        result.context(format!("Failed to delete release record {}", record.object))
+    }
+
+    /// Runs the `helm` CLI against the selected config through a temporary
+    /// kubeconfig, removed once helm exits, returning its output.
+    async fn run_helm(
+        handle: &AppHandle,
+        args: Vec<String>,
+        namespace: &str,
+    ) -> Result<String, KubiousError> {
+        let Some((key, config)) = handle.state::<AppState>().get_current_config() else {
+            return Err("No config selected".into());
+        };
+        let context = app_terminal::export_context(
+            handle,
+            key.as_str(),
+            &resolve_secrets(config)?,
+            Some(namespace.to_string()),
+        )?;
+        let output = handle
+            .shell()
+            .command("helm")
+            .args(args)
+            .args(["--namespace", namespace])
+            .env("KUBECONFIG", context.path.as_str())
+            .output()
+            .await;
+        app_terminal::remove_context(context.path.as_str());
+        let output = output.or(Err("Command execution failed.".to_string()))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(format!(
+                "helm failed with code {}: {}",
+                output.status.code().unwrap_or(-1),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into())
+        }
+    }
+
+    /// Removes a release's objects and its history. With `keep_history`,
+    /// the newest record is kept and marked uninstalled so the release can
+    /// still be rolled back. If any object could not be removed, every
+    /// record is kept and the newest is marked failed, so the release stays
+    /// listed and the uninstall can be retried.
+    pub async fn uninstall(
+        handle: &AppHandle,
+        client: Client,
+        namespace: &str,
+        release: &str,
+        keep_history: bool,
+        mode: HelmMode,
+    ) -> Result<UninstallResult, KubiousError> {
+        let mut result = UninstallResult {
+            release: release.to_string(),
+            namespace: namespace.to_string(),
+            mode,
+            removed: Vec::new(),
+            skipped_hooks: Vec::new(),
+            history_kept: keep_history,
+            output: None,
+        };
+        if mode == HelmMode::Binary {
+            let mut args = vec!["uninstall".to_string(), release.to_string()];
+            if keep_history {
+                args.push("--keep-history".to_string());
+            }
+            result.output = Some(run_helm(handle, args, namespace).await?);
+            return Ok(result);
+        }
+
+        let records = release_records(client.clone(), namespace, release).await?;
+        let latest = pick(&records, None)?;
+        let mut decoded = latest.decode()?;
+        result.skipped_hooks = hooks_for(&decoded, &["pre-delete", "post-delete"]);
+        result.removed = remove_objects(client.clone(), &objects_of(&decoded, namespace)).await;
+
+        let errors: Vec<String> = result
+            .removed
+            .iter()
+            .filter_map(|removed| {
+                removed.error.as_ref().map(|error| {
+                    format!(
+                        "{} {}: {}",
+                        removed.kind.as_deref().unwrap_or_default(),
+                        removed.name.as_deref().unwrap_or_default(),
+                        error
+                    )
+                })
+            })
+            .collect();
+        if !errors.is_empty() {
+            decoded["info"]["status"] = json!("failed");
+            decoded["info"]["description"] =
+                json!(format!("Uninstallation failed: {}", errors.join("; ")));
+            update_record(client, latest, &decoded).await?;
+            result.history_kept = true;
+        } else if keep_history {
+            decoded["info"]["status"] = json!("uninstalled");
+            decoded["info"]["deleted"] = json!(Utc::now().to_rfc3339());
+            decoded["info"]["description"] = json!("Uninstallation complete");
+            update_record(client, latest, &decoded).await?;
+        } else {
+            for record in records.iter() {
+                delete_record(client.clone(), record).await?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Rolls a release back to `revision`, or the one before the newest.
+    /// The target's objects are applied, objects only the newest revision
+    /// had are removed, and the rollback is recorded as a new revision with
+    /// the previously deployed one marked superseded. If any object fails
+    /// to apply, nothing is removed and the release records are left as
+    /// they were.
+    pub async fn rollback(
+        handle: &AppHandle,
+        client: Client,
+        namespace: &str,
+        release: &str,
+        revision: Option<i64>,
+        mode: HelmMode,
+    ) -> Result<RollbackResult, KubiousError> {
+        let records = release_records(client.clone(), namespace, release).await?;
+        let latest = pick(&records, None)?;
+        let target = match revision {
+            Some(revision) => pick(&records, Some(revision))?,
+            None => records
+                .iter()
+                .rev()
+                .find(|r| r.revision < latest.revision)
+                .ok_or("No earlier revision to roll back to".to_string())?,
+        };
+        let mut result = RollbackResult {
+            release: release.to_string(),
+            namespace: namespace.to_string(),
+            mode,
+            target: target.revision,
+            revision: None,
+            applied: Vec::new(),
+            removed: Vec::new(),
+            skipped_hooks: Vec::new(),
+            output: None,
+        };
+        if mode == HelmMode::Binary {
+            let args = vec![
+                "rollback".to_string(),
+                release.to_string(),
+                target.revision.to_string(),
+            ];
+            result.output = Some(run_helm(handle, args, namespace).await?);
+            result.revision = Some(latest.revision + 1);
+            return Ok(result);
+        }
+
+        let current = latest.decode()?;
+        let mut rolled = target.decode()?;
+        result.skipped_hooks = hooks_for(&rolled, &["pre-rollback", "post-rollback"]);
+
+        let wanted = objects_of(&rolled, namespace);
+        let (ordered, _) = artifact_apply::dependency_order(&wanted);
+        let options = ApplyOptions {
+            namespace: None,
+            field_manager: Some(HELM_FIELD_MANAGER.to_string()),
+            dry_run: false,
+            force: true,
+        };
+        result.applied =
+            artifact_apply::apply_manifests(client.clone(), &ordered, &options, true).await;
+        if result.applied.iter().any(|outcome| !outcome.success) {
+            return Ok(result);
+        }
+
+        let keep: HashSet<_> = wanted.iter().map(identity).collect();
+        let stale: Vec<Value> = objects_of(&current, namespace)
+            .into_iter()
+            .filter(|object| !keep.contains(&identity(object)))
+            .collect();
+        result.removed = remove_objects(client.clone(), &stale).await;
+
+        let mut superseded: HashMap<i64, Value> = HashMap::new();
+        for record in records.iter() {
+            let decoded = if record.revision == latest.revision {
+                current.clone()
+            } else {
+                record.decode()?
+            };
+            if decoded["info"]["status"].as_str() == Some("deployed") {
+                superseded.insert(record.revision, decoded);
+            }
+        }
+        for record in records.iter() {
+            if let Some(mut decoded) = superseded.remove(&record.revision) {
+                decoded["info"]["status"] = json!("superseded");
+                update_record(client.clone(), record, &decoded).await?;
+            }
+        }
+
+        let next = latest.revision + 1;
+        let now = Utc::now().to_rfc3339();
+        rolled["version"] = json!(next);
+        rolled["info"]["first_deployed"] = current["info"]["first_deployed"].clone();
+        rolled["info"]["last_deployed"] = json!(now);
+        rolled["info"]["deleted"] = json!("");
+        rolled["info"]["status"] = json!("deployed");
+        rolled["info"]["description"] = json!(format!("Rollback to {}", target.revision));
+        create_record(client, latest.driver, namespace, release, next, &rolled).await?;
+        result.revision = Some(next);
+        Ok(result)
+    }
+}
@@ -0,0 +1,279 @@
+pub mod helm_values {
+    use std::{
+        collections::BTreeMap,
+        fs,
+        io::{Read, Write},
+        path::{Path, PathBuf},
+    };
+
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+    use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+    use kube::{api::ListParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::{AppHandle, Manager};
+
+    use crate::{
+        api::artifacts::artifact_diff::{self, FieldDiff},
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+    /// Metadata of one stored revision; the values sit next to it as
+    /// `v<revision>.yaml`.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct StoredRevision {
+        pub revision: i64,
+        pub chart: Option<String>,
+        pub app_version: Option<String>,
+        pub status: Option<String>,
+        pub updated: Option<String>,
+        pub path: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ValuesImport {
+        pub namespace: String,
+        pub release: String,
+        pub imported: Vec<i64>,
+        /// Revisions stored by an earlier import. Their values never change,
+        /// so only their status is refreshed.
+        pub skipped: Vec<i64>,
+        pub directory: String,
+    }
+
+    /// A release revision as recorded by Helm's storage driver.
+    struct Revision {
+        stored: StoredRevision,
+        values: Value,
+    }
+
+    fn valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+            && !name.starts_with('.')
+    }
+
+    fn release_dir(
+        handle: &AppHandle,
+        namespace: &str,
+        release: &str,
+    ) -> Result<PathBuf, KubiousError> {
+        if !valid_name(namespace) || !valid_name(release) {
+            return Err("Invalid namespace or release name".into());
+        }
+        Ok(handle
+            .path()
+            .app_data_dir()
+            .or(Err("Failed to resolve app data directory.".to_string()))?
+            .join("artifacts")
+            .join("helm-values")
+            .join(namespace)
+            .join(release))
+    }
+
+    /// Decodes Helm's `release` payload: base64 of a gzipped JSON document.
+    pub fn decode(payload: &[u8]) -> Result<Value, KubiousError> {
+        let text: Vec<u8> = payload
+            .iter()
+            .copied()
+            .filter(|b| !b.is_ascii_whitespace())
+            .collect();
+        let raw = STANDARD
+            .decode(text)
+            .map_err(|e| format!("Failed to decode release: {}", e))?;
+        let json = if raw.starts_with(&GZIP_MAGIC) {
+            let mut inflated = Vec::new();
+            GzDecoder::new(raw.as_slice())
+                .read_to_end(&mut inflated)
+                .context("Failed to decompress release")?;
+            inflated
+        } else {
+            raw
+        };
+        serde_json::from_slice(&json).map_err(|e| format!("Failed to parse release: {}", e).into())
+    }
+
+    /// Encodes a release the way Helm stores it, the inverse of `decode`.
+    pub fn encode(release: &Value) -> Result<String, KubiousError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(release.to_string().as_bytes())
+            .and_then(|_| encoder.finish())
+            .map(|gzipped| STANDARD.encode(gzipped))
+            .context("Failed to compress release")
+    }
+
+    fn revision_of(release: Value) -> Option<Revision> {
+        let revision = release["version"].as_i64()?;
+        let text = |value: &Value| value.as_str().map(|s| s.to_string());
+        let metadata = &release["chart"]["metadata"];
+        Some(Revision {
+            stored: StoredRevision {
+                revision,
+                chart: metadata["name"].as_str().map(|name| {
+                    format!(
+                        "{}-{}",
+                        name,
+                        metadata["version"].as_str().unwrap_or_default()
+                    )
+                }),
+                app_version: text(&metadata["appVersion"]),
+                status: text(&release["info"]["status"]),
+                updated: text(&release["info"]["last_deployed"]),
+                path: String::new(),
+            },
+            // Only what the user passed with --values and --set; chart
+            // defaults are not part of the release's config.
+            values: match &release["config"] {
+                Value::Null => Value::Object(Default::default()),
+                config => config.clone(),
+            },
+        })
+    }
+
+    /// Reads every revision Helm still keeps, from Secrets (the default
+    /// driver) or, failing that, ConfigMaps.
+    async fn revisions(
+        client: Client,
+        namespace: &str,
+        release: &str,
+    ) -> Result<Vec<Revision>, KubiousError> {
+        let params = ListParams::default().labels(format!("owner=helm,name={}", release).as_str());
+        let secrets = Api::<Secret>::namespaced(client.clone(), namespace)
+            .list(&params)
+            .await
+            .context("Failed to list release secrets")?;
+        let mut payloads: Vec<Vec<u8>> = secrets
+            .items
+            .into_iter()
+            .filter_map(|s| s.data.and_then(|d| d.get("release").map(|r| r.0.clone())))
+            .collect();
+        if payloads.is_empty() {
+            payloads = Api::<ConfigMap>::namespaced(client, namespace)
+                .list(&params)
+                .await
+                .context("Failed to list release configmaps")?
+                .items
+                .into_iter()
+                .filter_map(|c| {
+                    c.data
+                        .and_then(|d| d.get("release").map(|r| r.clone().into_bytes()))
+                })
+                .collect();
+        }
+        let mut revisions: Vec<Revision> = payloads
+            .iter()
+            .map(|payload| decode(payload))
+            .collect::<Result<Vec<Value>, KubiousError>>()?
+            .into_iter()
+            .filter_map(revision_of)
+            .collect();
+        revisions.sort_by_key(|r| r.stored.revision);
+        Ok(revisions)
+    }
+
+    fn index_path(dir: &Path) -> PathBuf {
+        dir.join("index.json")
+    }
+
+    fn read_index(dir: &Path) -> BTreeMap<i64, StoredRevision> {
+        fs::read_to_string(index_path(dir))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Vec<StoredRevision>>(&contents).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| (r.revision, r))
+            .collect()
+    }
+
+    /// Stores the user-supplied values of every revision of a release as
+    /// `v<revision>.yaml`, so they can be compared after Helm prunes its
+    /// history.
+    pub async fn import(
+        handle: &AppHandle,
+        client: Client,
+        namespace: String,
+        release: String,
+    ) -> Result<ValuesImport, KubiousError> {
+        let dir = release_dir(handle, namespace.as_str(), release.as_str())?;
+        let revisions = revisions(client, namespace.as_str(), release.as_str()).await?;
+        if revisions.is_empty() {
+            return Err(format!("No history found for release {}", release).into());
+        }
+        fs::create_dir_all(&dir).or(Err("Failed to create values directory.".to_string()))?;
+        let mut index = read_index(&dir);
+        let mut imported: Vec<i64> = Vec::new();
+        let mut skipped: Vec<i64> = Vec::new();
+        for Revision { mut stored, values } in revisions {
+            let path = dir.join(format!("v{}.yaml", stored.revision));
+            if index.contains_key(&stored.revision) && path.exists() {
+                // The status moves on, e.g. to superseded, so keep it current.
+                stored.path = path.to_string_lossy().to_string();
+                skipped.push(stored.revision);
+                index.insert(stored.revision, stored);
+                continue;
+            }
+            let yaml = serde_yaml::to_string(&values)
+                .map_err(|e| format!("Failed to serialize values: {}", e))?;
+            fs::write(&path, yaml.as_bytes())
+                .context(format!("Failed to write {}", path.display()))?;
+            stored.path = path.to_string_lossy().to_string();
+            imported.push(stored.revision);
+            index.insert(stored.revision, stored);
+        }
+        let serialized = serde_json::to_string_pretty(&index.into_values().collect::<Vec<_>>())
+            .or(Err("Failed to serialize values index.".to_string()))?;
+        fs::write(index_path(&dir), serialized.as_bytes())
+            .or(Err("Failed to write values index.".to_string()))?;
+        Ok(ValuesImport {
+            namespace,
+            release,
+            imported,
+            skipped,
+            directory: dir.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Revisions imported for a release, oldest first.
+    pub fn stored(
+        handle: &AppHandle,
+        namespace: &str,
+        release: &str,
+    ) -> Result<Vec<StoredRevision>, KubiousError> {
+        Ok(read_index(&release_dir(handle, namespace, release)?)
+            .into_values()
+            .collect())
+    }
+
+    pub fn load(
+        handle: &AppHandle,
+        namespace: &str,
+        release: &str,
+        revision: i64,
+    ) -> Result<Value, KubiousError> {
+        let path = release_dir(handle, namespace, release)?.join(format!("v{}.yaml", revision));
+        let contents = fs::read_to_string(&path)
+            .or(Err(format!("Revision {} has not been imported", revision)))?;
+        serde_yaml::from_str(contents.as_str())
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e).into())
+    }
+
+    /// Field-by-field changes from the values of revision `from` to `to`;
+    /// `Added` fields are set in `to` only.
+    pub fn diff(
+        handle: &AppHandle,
+        namespace: &str,
+        release: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<FieldDiff>, KubiousError> {
+        let before = load(handle, namespace, release, from)?;
+        let after = load(handle, namespace, release, to)?;
+        Ok(artifact_diff::diff_values(&before, &after, true, &[]))
+    }
+}
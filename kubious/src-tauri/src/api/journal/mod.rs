@@ -0,0 +1,103 @@
+pub mod journal_api {
+    use crate::{api::app_state::AppState, util::kubious_error::KubiousError, CommandHandler};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::Manager;
+
+    use super::incident_journal::{self, JournalQuery, NewJournalEntry};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "command")]
+    pub enum JournalCommand {
+        /// Records a note in the journal of the current cluster, or the one
+        /// given, linking events, log bookmarks and container terminations
+        /// from around its time.
+        AddJournalEntry {
+            cluster: Option<String>,
+            entry: NewJournalEntry,
+        },
+        UpdateJournalEntry {
+            cluster: Option<String>,
+            id: String,
+            note: Option<String>,
+            tags: Option<Vec<String>>,
+        },
+        RemoveJournalEntry {
+            cluster: Option<String>,
+            id: String,
+        },
+        ListJournalEntries {
+            cluster: Option<String>,
+            query: JournalQuery,
+        },
+        /// Entries and their linked items in time order, for postmortems.
+        JournalTimeline {
+            cluster: Option<String>,
+            query: JournalQuery,
+        },
+    }
+
+    fn journal_cluster(
+        handle: &tauri::AppHandle,
+        cluster: &Option<String>,
+    ) -> Result<String, KubiousError> {
+        cluster
+            .clone()
+            .or(handle
+                .state::<AppState>()
+                .get_current_config()
+                .map(|(key, _)| key))
+            .ok_or(KubiousError::config("No config selected."))
+    }
+
+    impl CommandHandler for JournalCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            match self {
+                JournalCommand::AddJournalEntry { cluster, entry } => {
+                    let cluster = journal_cluster(handle, cluster)?;
+                    let client = handle
+                        .state::<AppState>()
+                        .client_for(cluster.as_str())
+                        .await;
+                    self.wrap_in_value(
+                        incident_journal::add(handle, client, cluster.as_str(), entry.clone())
+                            .await,
+                    )
+                }
+                JournalCommand::UpdateJournalEntry {
+                    cluster,
+                    id,
+                    note,
+                    tags,
+                } => {
+                    let cluster = journal_cluster(handle, cluster)?;
+                    self.wrap_in_value(incident_journal::update(
+                        handle,
+                        cluster.as_str(),
+                        id.as_str(),
+                        note.clone(),
+                        tags.clone(),
+                    ))
+                }
+                JournalCommand::RemoveJournalEntry { cluster, id } => {
+                    let cluster = journal_cluster(handle, cluster)?;
+                    if !incident_journal::remove(handle, cluster.as_str(), id.as_str())? {
+                        return Err("No such journal entry".into());
+                    }
+                    self.wrap_in_value(Ok(()))
+                }
+                JournalCommand::ListJournalEntries { cluster, query } => {
+                    let cluster = journal_cluster(handle, cluster)?;
+                    self.wrap_in_value(incident_journal::list(handle, cluster.as_str(), query))
+                }
+                JournalCommand::JournalTimeline { cluster, query } => {
+                    let cluster = journal_cluster(handle, cluster)?;
+                    self.wrap_in_value(incident_journal::timeline(handle, cluster.as_str(), query))
+                }
+            }
+        }
+    }
+}
+
+mod store;
+pub use store::incident_journal;
@@ -0,0 +1,418 @@
+pub mod incident_journal {
+    use std::{
+        collections::HashSet,
+        fs,
+        path::PathBuf,
+        sync::{Mutex, MutexGuard},
+    };
+
+    use k8s_openapi::chrono::{DateTime, Duration, Utc};
+    use kube::Client;
+    use serde::{Deserialize, Serialize};
+    use tauri::{AppHandle, Manager};
+
+    use crate::util::lock_recovery::recover;
+    use crate::{
+        api::{
+            app_settings::LogBookmark,
+            app_state::AppState,
+            event_feeds::{self, EventEntry},
+            termination_history::{self, TerminationQuery, TerminationRecord},
+        },
+        util::kubious_error::KubiousError,
+    };
+
+    /// How far around an entry's time events, bookmarks and terminations
+    /// are linked, unless the entry sets its own window.
+    const DEFAULT_WINDOW_SECS: i64 = 900;
+    const MAX_WINDOW_SECS: i64 = 86_400;
+
+    /// A note about an incident, with what was captured around its time.
+    /// Events are copied since the API server drops them after about an
+    /// hour; bookmarks and terminations are copied so the journal stays
+    /// complete when they are removed.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct JournalEntry {
+        pub id: String,
+        pub cluster: String,
+        pub namespace: Option<String>,
+        /// When the noted thing happened; defaults to when it was recorded.
+        pub timestamp: String,
+        pub recorded: String,
+        pub note: String,
+        #[serde(default)]
+        pub tags: Vec<String>,
+        pub window_secs: i64,
+        #[serde(default)]
+        pub events: Vec<EventEntry>,
+        #[serde(default)]
+        pub bookmarks: Vec<LogBookmark>,
+        #[serde(default)]
+        pub terminations: Vec<TerminationRecord>,
+        /// Why events could not be captured, e.g. the cluster was
+        /// unreachable.
+        pub capture_error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NewJournalEntry {
+        pub namespace: Option<String>,
+        pub timestamp: Option<String>,
+        pub note: String,
+        #[serde(default)]
+        pub tags: Vec<String>,
+        pub window_secs: Option<i64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct JournalQuery {
+        pub namespace: Option<String>,
+        pub tag: Option<String>,
+        /// RFC 3339 bounds on the entries' timestamps.
+        pub since: Option<String>,
+        pub until: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "type")]
+    pub enum TimelineItem {
+        Note {
+            entry: JournalEntry,
+        },
+        Event {
+            entry_id: String,
+            event: EventEntry,
+        },
+        LogBookmark {
+            entry_id: String,
+            bookmark: LogBookmark,
+        },
+        Termination {
+            entry_id: String,
+            termination: TerminationRecord,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TimelineEntry {
+        pub at: String,
+        #[serde(flatten)]
+        pub item: TimelineItem,
+    }
+
+    /// Serializes writes to the journal files.
+    #[derive(Default)]
+    pub struct IncidentJournal {
+        files: Mutex<()>,
+    }
+
+    impl IncidentJournal {
+        fn files_mutable(&self) -> MutexGuard<'_, ()> {
+            recover(&self.files)
+        }
+    }
+
+    fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    fn journal_path(handle: &AppHandle, cluster: &str) -> Result<PathBuf, KubiousError> {
+        let root = handle
+            .path()
+            .app_data_dir()
+            .or(Err("Failed to resolve app data directory.".to_string()))?
+            .join("journal");
+        if !root.exists() {
+            fs::create_dir_all(&root).or(Err("Failed to create journal directory.".to_string()))?;
+        }
+        let file: String = cluster
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Ok(root.join(format!("{}.json", file)))
+    }
+
+    /// Entries of a cluster, oldest first. Keys that map to the same file
+    /// are told apart by the entries' `cluster`.
+    fn load(handle: &AppHandle, cluster: &str) -> Result<Vec<JournalEntry>, KubiousError> {
+        let path = journal_path(handle, cluster)?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents =
+            fs::read_to_string(&path).or(Err("Failed to read incident journal.".to_string()))?;
+        serde_json::from_str(contents.as_str()).or(Err("Failed to parse incident journal.".into()))
+    }
+
+    /// Loads every entry in the cluster's file, changes them and writes them
+    /// back, holding the journal lock throughout.
+    fn modify<T>(
+        handle: &AppHandle,
+        cluster: &str,
+        change: impl FnOnce(&mut Vec<JournalEntry>) -> Result<T, KubiousError>,
+    ) -> Result<T, KubiousError> {
+        let journal = handle.state::<IncidentJournal>();
+        let _guard = journal.files_mutable();
+        let mut entries = load(handle, cluster)?;
+        let result = change(&mut entries)?;
+        entries.sort_by_key(|e| parse_time(e.timestamp.as_str()));
+        let serialized = serde_json::to_string(&entries)
+            .or(Err("Failed to serialize incident journal.".to_string()))?;
+        fs::write(journal_path(handle, cluster)?, serialized.as_bytes())
+            .or(Err("Failed to write incident journal.".to_string()))?;
+        Ok(result)
+    }
+
+    fn within(value: Option<&str>, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        value
+            .and_then(parse_time)
+            .is_some_and(|at| at >= from && at <= to)
+    }
+
+    /// Events whose occurrences overlap the window.
+    async fn capture_events(
+        client: Client,
+        namespace: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<EventEntry>, KubiousError> {
+        Ok(event_feeds::list(client, namespace, None)
+            .await?
+            .into_iter()
+            .filter(|event| {
+                let first = event
+                    .first_seen
+                    .as_deref()
+                    .or(event.last_seen.as_deref())
+                    .and_then(parse_time);
+                let last = event.last_seen.as_deref().and_then(parse_time).or(first);
+                matches!((first, last), (Some(first), Some(last)) if first <= to && last >= from)
+            })
+            .collect())
+    }
+
+    /// Bookmarks and termination snapshots of the cluster within the window.
+    fn linked_logs(
+        handle: &AppHandle,
+        cluster: &str,
+        namespace: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> (Vec<LogBookmark>, Vec<TerminationRecord>) {
+        let bookmarks = handle
+            .state::<AppState>()
+            .log_bookmarks(cluster, namespace, None)
+            .into_iter()
+            .filter(|b| {
+                within(
+                    b.timestamp.as_deref().or(Some(b.created.as_str())),
+                    from,
+                    to,
+                )
+            })
+            .collect();
+        let query = TerminationQuery {
+            cluster: Some(cluster.to_string()),
+            namespace: namespace.map(|n| n.to_string()),
+            workload: None,
+            container: None,
+            limit: None,
+        };
+        let terminations = termination_history::query(handle, &query)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|t| within(t.finished_at.as_deref(), from, to))
+            .collect();
+        (bookmarks, terminations)
+    }
+
+    /// Records a note, capturing the events of its namespace (or the whole
+    /// cluster) and linking log bookmarks and container terminations from
+    /// around its time. `client` is `None` when the cluster is unreachable;
+    /// the note is kept without events then.
+    pub async fn add(
+        handle: &AppHandle,
+        client: Option<Client>,
+        cluster: &str,
+        new: NewJournalEntry,
+    ) -> Result<JournalEntry, KubiousError> {
+        let recorded = Utc::now();
+        let at = match &new.timestamp {
+            Some(timestamp) => {
+                parse_time(timestamp.as_str()).ok_or(format!("Invalid timestamp {}", timestamp))?
+            }
+            None => recorded,
+        };
+        let window_secs = new
+            .window_secs
+            .unwrap_or(DEFAULT_WINDOW_SECS)
+            .clamp(0, MAX_WINDOW_SECS);
+        let (from, to) = (
+            at - Duration::seconds(window_secs),
+            at + Duration::seconds(window_secs),
+        );
+        let namespace = new.namespace.as_deref();
+        let (events, capture_error) = match client {
+            Some(client) => match capture_events(client, namespace, from, to).await {
+                Ok(events) => (events, None),
+                Err(error) => (Vec::new(), Some(error.to_string())),
+            },
+            None => (Vec::new(), Some("Cluster is not connected".to_string())),
+        };
+        let (bookmarks, terminations) = linked_logs(handle, cluster, namespace, from, to);
+        let entry = JournalEntry {
+            id: format!(
+                "journal-{}",
+                recorded.timestamp_nanos_opt().unwrap_or_default()
+            ),
+            cluster: cluster.to_string(),
+            namespace: new.namespace,
+            timestamp: at.to_rfc3339(),
+            recorded: recorded.to_rfc3339(),
+            note: new.note,
+            tags: new.tags,
+            window_secs,
+            events,
+            bookmarks,
+            terminations,
+            capture_error,
+        };
+        modify(handle, cluster, |entries| {
+            entries.push(entry.clone());
+            Ok(entry)
+        })
+    }
+
+    /// Changes an entry's note and tags; what was captured stays as it was.
+    pub fn update(
+        handle: &AppHandle,
+        cluster: &str,
+        id: &str,
+        note: Option<String>,
+        tags: Option<Vec<String>>,
+    ) -> Result<JournalEntry, KubiousError> {
+        modify(handle, cluster, |entries| {
+            let entry = entries
+                .iter_mut()
+                .find(|e| e.id == id && e.cluster == cluster)
+                .ok_or(format!("Journal entry {} not found", id))?;
+            if let Some(note) = note {
+                entry.note = note;
+            }
+            if let Some(tags) = tags {
+                entry.tags = tags;
+            }
+            Ok(entry.clone())
+        })
+    }
+
+    pub fn remove(handle: &AppHandle, cluster: &str, id: &str) -> Result<bool, KubiousError> {
+        modify(handle, cluster, |entries| {
+            let before = entries.len();
+            entries.retain(|e| e.id != id || e.cluster != cluster);
+            Ok(entries.len() != before)
+        })
+    }
+
+    /// Entries of a cluster matching the query, oldest first.
+    pub fn list(
+        handle: &AppHandle,
+        cluster: &str,
+        query: &JournalQuery,
+    ) -> Result<Vec<JournalEntry>, KubiousError> {
+        let since = query.since.as_deref().and_then(parse_time);
+        let until = query.until.as_deref().and_then(parse_time);
+        Ok(load(handle, cluster)?
+            .into_iter()
+            .filter(|e| {
+                let at = parse_time(e.timestamp.as_str());
+                e.cluster == cluster
+                    && query
+                        .namespace
+                        .as_ref()
+                        .is_none_or(|n| e.namespace.as_ref() == Some(n))
+                    && query.tag.as_ref().is_none_or(|t| e.tags.contains(t))
+                    && since.is_none_or(|since| at.is_some_and(|at| at >= since))
+                    && until.is_none_or(|until| at.is_some_and(|at| at <= until))
+            })
+            .collect())
+    }
+
+    /// The matching entries and everything linked to them in time order,
+    /// for a postmortem. Items linked to several entries appear once, under
+    /// the first entry.
+    pub fn timeline(
+        handle: &AppHandle,
+        cluster: &str,
+        query: &JournalQuery,
+    ) -> Result<Vec<TimelineEntry>, KubiousError> {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut timeline: Vec<TimelineEntry> = Vec::new();
+        for entry in list(handle, cluster, query)? {
+            for event in entry.events.iter() {
+                if seen.insert(format!("event/{}", event.key)) {
+                    timeline.push(TimelineEntry {
+                        at: event
+                            .last_seen
+                            .clone()
+                            .or(event.first_seen.clone())
+                            .unwrap_or(entry.timestamp.clone()),
+                        item: TimelineItem::Event {
+                            entry_id: entry.id.clone(),
+                            event: event.clone(),
+                        },
+                    });
+                }
+            }
+            for bookmark in entry.bookmarks.iter() {
+                if seen.insert(format!("bookmark/{}", bookmark.id)) {
+                    timeline.push(TimelineEntry {
+                        at: bookmark
+                            .timestamp
+                            .clone()
+                            .unwrap_or(bookmark.created.clone()),
+                        item: TimelineItem::LogBookmark {
+                            entry_id: entry.id.clone(),
+                            bookmark: bookmark.clone(),
+                        },
+                    });
+                }
+            }
+            for termination in entry.terminations.iter() {
+                let key = format!(
+                    "termination/{}/{}/{}/{}",
+                    termination.namespace,
+                    termination.pod,
+                    termination.container,
+                    termination.finished_at.clone().unwrap_or_default()
+                );
+                if seen.insert(key) {
+                    timeline.push(TimelineEntry {
+                        at: termination
+                            .finished_at
+                            .clone()
+                            .unwrap_or(entry.timestamp.clone()),
+                        item: TimelineItem::Termination {
+                            entry_id: entry.id.clone(),
+                            termination: termination.clone(),
+                        },
+                    });
+                }
+            }
+            timeline.push(TimelineEntry {
+                at: entry.timestamp.clone(),
+                item: TimelineItem::Note { entry },
+            });
+        }
+        timeline.sort_by_key(|item| parse_time(item.at.as_str()));
+        Ok(timeline)
+    }
+}
@@ -1,11 +1,11 @@
 pub mod kompose_api {
     use serde::{Deserialize, Serialize};
     use serde_json::Value;
-    use crate::CommandHandler;
+    use crate::{util::kubious_error::KubiousError, CommandHandler};
 
     use tauri_plugin_shell::ShellExt;
 
-    async fn get_shell_version(handle: &tauri::AppHandle) -> Result<String, String> {
+    async fn get_shell_version(handle: &tauri::AppHandle) -> Result<String, KubiousError> {
         let shell = handle.shell();
         let output = shell
                 .command("helm")
@@ -20,7 +20,8 @@ pub mod kompose_api {
                 Err(format!(
                     "Command failed with code {}",
                     out.status.code().unwrap_or(-1)
-                ))
+                )
+                .into())
             }
         });
         output
@@ -32,7 +33,7 @@ pub mod kompose_api {
         GetVersion{}
     }
     impl CommandHandler for KomposeCommand {
-        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, String> {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
             match *self {
                 KomposeCommand::GetVersion{} => self.wrap_in_value(get_shell_version(handle).await)
             }
@@ -0,0 +1,422 @@
+pub mod admission_policies {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::{
+        api::{
+            admissionregistration::{
+                v1::{
+                    MatchResources, NamedRuleWithOperations, ValidatingAdmissionPolicy,
+                    ValidatingAdmissionPolicyBinding,
+                },
+                v1beta1,
+            },
+            core::v1::Namespace,
+        },
+        apimachinery::pkg::apis::meta::v1::LabelSelector,
+    };
+    use kube::{
+        api::{ListParams, Patch, PatchParams},
+        Api, Client, ResourceExt,
+    };
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::artifacts::artifact_apply::{resolve_manifest, DEFAULT_FIELD_MANAGER},
+        util::{
+            api_warnings,
+            kubious_error::{ErrorContext, KubiousError},
+        },
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PolicyValidation {
+        pub expression: String,
+        pub message: Option<String>,
+        pub message_expression: Option<String>,
+        pub reason: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PolicyBinding {
+        pub name: String,
+        /// `Deny`, `Warn` and/or `Audit`.
+        pub actions: Vec<String>,
+        pub param: Option<String>,
+        pub match_resources: Option<MatchResources>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PolicySummary {
+        pub name: String,
+        pub api_version: String,
+        pub failure_policy: Option<String>,
+        pub param_kind: Option<String>,
+        pub match_constraints: Option<MatchResources>,
+        pub match_conditions: Vec<String>,
+        pub validations: Vec<PolicyValidation>,
+        pub bindings: Vec<PolicyBinding>,
+        /// Type checking problems the API server found in the expressions.
+        pub type_warnings: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct MatchedPolicy {
+        pub policy: String,
+        pub binding: String,
+        pub actions: Vec<String>,
+        /// Match conditions are CEL and only evaluated by the server, so a
+        /// matched policy may still skip the object.
+        pub has_match_conditions: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PolicyMessage {
+        pub policy: Option<String>,
+        pub binding: Option<String>,
+        pub message: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PolicyTest {
+        pub operation: String,
+        /// Policies whose resource rules and selectors match the manifest.
+        pub matched: Vec<MatchedPolicy>,
+        pub allowed: bool,
+        pub denials: Vec<PolicyMessage>,
+        pub warnings: Vec<PolicyMessage>,
+        /// Rejections not caused by an admission policy, such as schema
+        /// errors or webhooks.
+        pub error: Option<String>,
+    }
+
+    /// Lists a kind at v1, falling back to v1beta1 for clusters before
+    /// 1.30. Both versions share the same schema.
+    async fn list_either<V1, Beta>(client: &Client) -> Result<(Vec<V1>, String), KubiousError>
+    where
+        V1: kube::Resource<DynamicType = ()>
+            + Clone
+            + DeserializeOwned
+            + std::fmt::Debug
+            + k8s_openapi::Resource,
+        Beta: kube::Resource<DynamicType = ()>
+            + Clone
+            + DeserializeOwned
+            + Serialize
+            + std::fmt::Debug,
+    {
+        match Api::<V1>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+        {
+            Ok(list) => Ok((list.items, V1::API_VERSION.to_string())),
+            Err(kube::Error::Api(e)) if e.code == 404 => {
+                let list = Api::<Beta>::all(client.clone())
+                    .list(&ListParams::default())
+                    .await
+                    .context("Failed to list admission policies")?;
+                let items = list
+                    .items
+                    .into_iter()
+                    .filter_map(|item| {
+                        serde_json::to_value(item)
+                            .ok()
+                            .and_then(|v| serde_json::from_value(v).ok())
+                    })
+                    .collect();
+                Ok((items, "admissionregistration.k8s.io/v1beta1".to_string()))
+            }
+            Err(e) => Err(KubiousError::from(e).with_context("Failed to list admission policies")),
+        }
+    }
+
+    async fn load(client: &Client) -> Result<Vec<PolicySummary>, KubiousError> {
+        let (policies, api_version) =
+            list_either::<ValidatingAdmissionPolicy, v1beta1::ValidatingAdmissionPolicy>(client)
+                .await?;
+        let (bindings, _) = list_either::<
+            ValidatingAdmissionPolicyBinding,
+            v1beta1::ValidatingAdmissionPolicyBinding,
+        >(client)
+        .await?;
+
+        let mut summaries: Vec<PolicySummary> = policies
+            .into_iter()
+            .map(|policy| {
+                let name = policy.name_any();
+                let spec = policy.spec.unwrap_or_default();
+                PolicySummary {
+                    bindings: bindings
+                        .iter()
+                        .filter(|b| {
+                            b.spec.as_ref().and_then(|s| s.policy_name.as_deref())
+                                == Some(name.as_str())
+                        })
+                        .map(|b| {
+                            let spec = b.spec.clone().unwrap_or_default();
+                            PolicyBinding {
+                                name: b.name_any(),
+                                actions: spec.validation_actions.unwrap_or_default(),
+                                param: spec.param_ref.map(|p| match (p.namespace, p.name) {
+                                    (Some(ns), Some(name)) => format!("{}/{}", ns, name),
+                                    (None, Some(name)) => name,
+                                    _ => "selector".to_string(),
+                                }),
+                                match_resources: spec.match_resources,
+                            }
+                        })
+                        .collect(),
+                    type_warnings: policy
+                        .status
+                        .and_then(|s| s.type_checking)
+                        .and_then(|t| t.expression_warnings)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|w| format!("{}: {}", w.field_ref, w.warning))
+                        .collect(),
+                    api_version: api_version.clone(),
+                    failure_policy: spec.failure_policy,
+                    param_kind: spec.param_kind.map(|p| {
+                        format!(
+                            "{}/{}",
+                            p.api_version.unwrap_or_default(),
+                            p.kind.unwrap_or_default()
+                        )
+                    }),
+                    match_constraints: spec.match_constraints,
+                    match_conditions: spec
+                        .match_conditions
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|c| c.expression)
+                        .collect(),
+                    validations: spec
+                        .validations
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|v| PolicyValidation {
+                            expression: v.expression,
+                            message: v.message,
+                            message_expression: v.message_expression,
+                            reason: v.reason,
+                        })
+                        .collect(),
+                    name,
+                }
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(summaries)
+    }
+
+    pub async fn list(client: Client) -> Result<Vec<PolicySummary>, KubiousError> {
+        load(&client).await
+    }
+
+    fn selector_matches(
+        selector: &Option<LabelSelector>,
+        labels: &BTreeMap<String, String>,
+    ) -> bool {
+        let Some(selector) = selector else {
+            return true;
+        };
+        let labels_match = selector
+            .match_labels
+            .as_ref()
+            .is_none_or(|wanted| wanted.iter().all(|(k, v)| labels.get(k) == Some(v)));
+        let expressions_match = selector
+            .match_expressions
+            .as_ref()
+            .is_none_or(|expressions| {
+                expressions.iter().all(|e| {
+                    let values = e.values.clone().unwrap_or_default();
+                    match e.operator.as_str() {
+                        "In" => labels.get(&e.key).is_some_and(|v| values.contains(v)),
+                        "NotIn" => labels.get(&e.key).is_none_or(|v| !values.contains(v)),
+                        "Exists" => labels.contains_key(&e.key),
+                        "DoesNotExist" => !labels.contains_key(&e.key),
+                        _ => false,
+                    }
+                })
+            });
+        labels_match && expressions_match
+    }
+
+    /// What a rule is checked against.
+    struct Request<'a> {
+        group: &'a str,
+        version: &'a str,
+        resource: &'a str,
+        name: &'a str,
+        operation: &'a str,
+        namespaced: bool,
+    }
+
+    fn rule_matches(rule: &NamedRuleWithOperations, request: &Request) -> bool {
+        let listed = |values: &Option<Vec<String>>, value: &str| {
+            values
+                .as_ref()
+                .is_some_and(|v| v.iter().any(|v| v == "*" || v == value))
+        };
+        let scope = match rule.scope.as_deref() {
+            Some("Namespaced") => request.namespaced,
+            Some("Cluster") => !request.namespaced,
+            _ => true,
+        };
+        listed(&rule.api_groups, request.group)
+            && listed(&rule.api_versions, request.version)
+            && listed(&rule.resources, request.resource)
+            && listed(&rule.operations, request.operation)
+            && rule
+                .resource_names
+                .as_ref()
+                .is_none_or(|names| names.is_empty() || names.iter().any(|n| n == request.name))
+            && scope
+    }
+
+    fn resources_match(
+        resources: &MatchResources,
+        request: &Request,
+        labels: &BTreeMap<String, String>,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> bool {
+        let included = resources
+            .resource_rules
+            .as_ref()
+            .is_some_and(|rules| rules.iter().any(|r| rule_matches(r, request)));
+        let excluded = resources
+            .exclude_resource_rules
+            .as_ref()
+            .is_some_and(|rules| rules.iter().any(|r| rule_matches(r, request)));
+        let namespace = !request.namespaced
+            || selector_matches(&resources.namespace_selector, namespace_labels);
+        included && !excluded && namespace && selector_matches(&resources.object_selector, labels)
+    }
+
+    /// Splits a message such as `ValidatingAdmissionPolicy 'name' with
+    /// binding 'binding' denied request: reason` into its parts.
+    fn policy_message(text: &str) -> Option<PolicyMessage> {
+        let start = text.find("ValidatingAdmissionPolicy '")?;
+        let rest = &text[start + "ValidatingAdmissionPolicy '".len()..];
+        let (policy, rest) = rest.split_once('\'')?;
+        let binding = rest
+            .split_once("binding '")
+            .and_then(|(_, b)| b.split_once('\''))
+            .map(|(b, _)| b.to_string());
+        let message = rest
+            .split_once(": ")
+            .map(|(_, m)| m.to_string())
+            .unwrap_or(text.to_string());
+        Some(PolicyMessage {
+            policy: Some(policy.to_string()),
+            binding,
+            message,
+        })
+    }
+
+    /// Tests a manifest against the cluster's admission policies with a
+    /// server-side dry-run apply, which runs every policy and webhook
+    /// without persisting anything. Policies whose rules and selectors
+    /// match are listed alongside the result.
+    pub async fn test(
+        client: Client,
+        manifest: &Value,
+        namespace: Option<String>,
+    ) -> Result<PolicyTest, KubiousError> {
+        let resolved = resolve_manifest(client.clone(), manifest, namespace).await?;
+        let exists = resolved
+            .api
+            .get_opt(resolved.name.as_str())
+            .await
+            .context(format!("Failed to look up {}", resolved.name))?
+            .is_some();
+        let operation = if exists { "UPDATE" } else { "CREATE" };
+        let request = Request {
+            group: resolved.resource.group.as_str(),
+            version: resolved.resource.version.as_str(),
+            resource: resolved.resource.plural.as_str(),
+            name: resolved.name.as_str(),
+            operation,
+            namespaced: resolved.namespace.is_some(),
+        };
+        let labels = resolved.object.labels().clone();
+        let namespace_labels = match resolved.namespace.as_ref() {
+            Some(ns) => Api::<Namespace>::all(client.clone())
+                .get_opt(ns.as_str())
+                .await
+                .ok()
+                .flatten()
+                .map(|n| n.labels().clone())
+                .unwrap_or_default(),
+            None => BTreeMap::new(),
+        };
+
+        let mut matched: Vec<MatchedPolicy> = Vec::new();
+        for policy in load(&client).await? {
+            let Some(constraints) = policy.match_constraints.as_ref() else {
+                continue;
+            };
+            if !resources_match(constraints, &request, &labels, &namespace_labels) {
+                continue;
+            }
+            for binding in &policy.bindings {
+                let bound = binding.match_resources.as_ref().is_none_or(|resources| {
+                    resources_match(resources, &request, &labels, &namespace_labels)
+                });
+                if bound {
+                    matched.push(MatchedPolicy {
+                        policy: policy.name.clone(),
+                        binding: binding.name.clone(),
+                        actions: binding.actions.clone(),
+                        has_match_conditions: !policy.match_conditions.is_empty(),
+                    });
+                }
+            }
+        }
+
+        let params = PatchParams::apply(DEFAULT_FIELD_MANAGER).dry_run();
+        let (result, warnings) = api_warnings::collect(async {
+            resolved
+                .api
+                .patch(
+                    resolved.name.as_str(),
+                    &params,
+                    &Patch::Apply(&resolved.object),
+                )
+                .await
+        })
+        .await;
+
+        let mut test = PolicyTest {
+            operation: operation.to_string(),
+            matched,
+            allowed: result.is_ok(),
+            denials: Vec::new(),
+            warnings: warnings
+                .iter()
+                .map(|w| {
+                    policy_message(w).unwrap_or(PolicyMessage {
+                        policy: None,
+                        binding: None,
+                        message: w.clone(),
+                    })
+                })
+                .collect(),
+            error: None,
+        };
+        match result {
+            Ok(_) => {}
+            Err(kube::Error::Api(e)) => {
+                let denials: Vec<PolicyMessage> =
+                    e.message.split("; ").filter_map(policy_message).collect();
+                if denials.is_empty() {
+                    test.error = Some(e.message);
+                } else {
+                    test.denials = denials;
+                }
+            }
+            Err(e) => test.error = Some(e.to_string()),
+        }
+        Ok(test)
+    }
+}
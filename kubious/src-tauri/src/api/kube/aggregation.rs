@@ -0,0 +1,177 @@
+pub mod api_service_health {
+    use k8s_openapi::{
+        api::core::v1::Endpoints, kube_aggregator::pkg::apis::apiregistration::v1::APIService,
+    };
+    use kube::{api::ListParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum AggregatedApiKind {
+        Metrics,
+        CustomMetrics,
+        ExternalMetrics,
+        ServiceCatalog,
+        Other,
+    }
+
+    impl AggregatedApiKind {
+        fn of(group: &str) -> Self {
+            match group {
+                "metrics.k8s.io" => AggregatedApiKind::Metrics,
+                "custom.metrics.k8s.io" => AggregatedApiKind::CustomMetrics,
+                "external.metrics.k8s.io" => AggregatedApiKind::ExternalMetrics,
+                "servicecatalog.k8s.io" => AggregatedApiKind::ServiceCatalog,
+                _ => AggregatedApiKind::Other,
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BackendService {
+        pub namespace: String,
+        pub name: String,
+        pub port: Option<i32>,
+        /// Ready endpoint addresses behind the service, if they could be read.
+        pub ready_endpoints: Option<usize>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ApiServiceStatus {
+        pub name: String,
+        pub group: String,
+        pub version: String,
+        /// Served by an extension API server rather than kube-apiserver.
+        pub aggregated: bool,
+        pub kind: Option<AggregatedApiKind>,
+        pub backend: Option<BackendService>,
+        pub available: bool,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+        pub since: Option<String>,
+        pub insecure_skip_tls_verify: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ApiServiceHealth {
+        pub healthy: bool,
+        pub services: Vec<ApiServiceStatus>,
+        /// Group versions whose discovery fails while their APIService is
+        /// unavailable, which breaks listing any kind and any view on them.
+        pub unavailable: Vec<String>,
+        pub hints: Vec<String>,
+    }
+
+    async fn ready_endpoints(client: &Client, namespace: &str, name: &str) -> Option<usize> {
+        let api: Api<Endpoints> = Api::namespaced(client.clone(), namespace);
+        let endpoints = api.get_opt(name).await.ok()?;
+        Some(
+            endpoints
+                .and_then(|e| e.subsets)
+                .unwrap_or_default()
+                .iter()
+                .map(|s| s.addresses.as_ref().map_or(0, |a| a.len()))
+                .sum(),
+        )
+    }
+
+    fn hint(status: &ApiServiceStatus) -> Option<String> {
+        if status.available {
+            return None;
+        }
+        let group_version = format!("{}/{}", status.group, status.version);
+        let backend = status.backend.as_ref()?;
+        let target = format!("{}/{}", backend.namespace, backend.name);
+        Some(match (&status.kind, backend.ready_endpoints) {
+            (_, Some(0)) => format!(
+                "{} has no ready endpoints behind {}; the backing pods are down or not ready",
+                group_version, target
+            ),
+            (Some(AggregatedApiKind::Metrics), _) => format!(
+                "{} is unavailable; pod and node usage will be missing until metrics-server in {} recovers",
+                group_version, target
+            ),
+            (Some(AggregatedApiKind::CustomMetrics | AggregatedApiKind::ExternalMetrics), _) => {
+                format!(
+                    "{} is unavailable; autoscalers using these metrics cannot scale until {} recovers",
+                    group_version, target
+                )
+            }
+            _ => format!(
+                "{} is unavailable; check {} and network policies between the API server and it",
+                group_version, target
+            ),
+        })
+    }
+
+    /// Lists every APIService with its Available condition. Unavailable
+    /// aggregated APIs are what make discovery fail for the whole cluster, so
+    /// their backing service is checked for ready endpoints as well.
+    pub async fn report(client: Client) -> Result<ApiServiceHealth, KubiousError> {
+        let api: Api<APIService> = Api::all(client.clone());
+        let list = api
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list APIServices")?;
+
+        let mut services: Vec<ApiServiceStatus> = Vec::new();
+        for service in list.items {
+            let spec = service.spec.clone().unwrap_or_default();
+            let group = spec.group.clone().unwrap_or_default();
+            let available = service
+                .status
+                .as_ref()
+                .and_then(|s| s.conditions.as_ref())
+                .and_then(|c| c.iter().find(|c| c.type_ == "Available"))
+                .cloned();
+            let backend = match spec.service.as_ref() {
+                Some(reference) => {
+                    let namespace = reference.namespace.clone().unwrap_or_default();
+                    let name = reference.name.clone().unwrap_or_default();
+                    Some(BackendService {
+                        ready_endpoints: ready_endpoints(&client, &namespace, &name).await,
+                        namespace,
+                        name,
+                        port: reference.port,
+                    })
+                }
+                None => None,
+            };
+            services.push(ApiServiceStatus {
+                name: service.metadata.name.clone().unwrap_or_default(),
+                aggregated: backend.is_some(),
+                kind: backend
+                    .as_ref()
+                    .map(|_| AggregatedApiKind::of(group.as_str())),
+                group,
+                version: spec.version.clone().unwrap_or_default(),
+                backend,
+                available: available.as_ref().is_some_and(|c| c.status == "True"),
+                reason: available.as_ref().and_then(|c| c.reason.clone()),
+                message: available.as_ref().and_then(|c| c.message.clone()),
+                since: available
+                    .as_ref()
+                    .and_then(|c| c.last_transition_time.as_ref())
+                    .map(|t| t.0.to_rfc3339()),
+                insecure_skip_tls_verify: spec.insecure_skip_tls_verify.unwrap_or(false),
+            });
+        }
+        services.sort_by(|a, b| {
+            (a.available, !a.aggregated, &a.name).cmp(&(b.available, !b.aggregated, &b.name))
+        });
+
+        let unavailable: Vec<String> = services
+            .iter()
+            .filter(|s| !s.available)
+            .map(|s| format!("{}/{}", s.group, s.version))
+            .collect();
+        let hints = services.iter().filter_map(hint).collect();
+        Ok(ApiServiceHealth {
+            healthy: unavailable.is_empty(),
+            services,
+            unavailable,
+            hints,
+        })
+    }
+}
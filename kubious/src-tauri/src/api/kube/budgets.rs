@@ -0,0 +1,354 @@
+pub mod namespace_budgets {
+    use std::{
+        collections::{HashMap, HashSet, VecDeque},
+        sync::{Mutex, MutexGuard},
+        time::{Duration, Instant},
+    };
+
+    use k8s_openapi::{
+        api::core::v1::{Pod, ResourceQuota},
+        chrono::Utc,
+    };
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use tauri::{AppHandle, Emitter, Manager};
+
+    use crate::{
+        api::{
+            app_settings::{BudgetAlert, BudgetBreach},
+            app_state::AppState,
+        },
+        util::{
+            kube_quantity::{parse_quantity, quantity_value},
+            kubious_error::{ErrorContext, KubiousError},
+            lock_recovery::recover,
+        },
+    };
+
+    /// Event channel carrying a `BudgetBreach` whenever a threshold is
+    /// newly crossed.
+    pub const BUDGET_ALERT_EVENT: &str = "budget-alert";
+
+    /// How often the background loop evaluates every alert.
+    const INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Window the restart rate is measured over.
+    const RESTART_WINDOW: Duration = Duration::from_secs(3600);
+
+    /// The outcome of one threshold of an alert.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BudgetCheck {
+        pub alert_id: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub rule: String,
+        pub value: f64,
+        pub threshold: f64,
+        pub breached: bool,
+        pub message: String,
+    }
+
+    /// Restart counts per container at one point in time.
+    type RestartSample = (Instant, HashMap<String, i32>);
+
+    #[derive(Default)]
+    struct Tracking {
+        /// `(alert id, rule)` pairs currently in breach, so a breach is
+        /// recorded once when it starts rather than on every evaluation.
+        active: HashSet<(String, String)>,
+        /// Restart counts per container, sampled on every evaluation.
+        samples: HashMap<String, VecDeque<RestartSample>>,
+        started: bool,
+    }
+
+    #[derive(Default)]
+    pub struct BudgetAlerts {
+        tracking: Mutex<Tracking>,
+    }
+
+    impl BudgetAlerts {
+        fn tracking_mutable(&self) -> MutexGuard<'_, Tracking> {
+            recover(&self.tracking)
+        }
+    }
+
+    fn check(
+        alert: &BudgetAlert,
+        rule: String,
+        value: f64,
+        threshold: f64,
+        message: String,
+    ) -> BudgetCheck {
+        BudgetCheck {
+            alert_id: alert.id.clone(),
+            cluster: alert.cluster.clone(),
+            namespace: alert.namespace.clone(),
+            breached: value > threshold,
+            rule,
+            value,
+            threshold,
+            message,
+        }
+    }
+
+    /// Usage of every resource of every unscoped ResourceQuota, as a
+    /// percentage of its hard limit.
+    async fn quota_checks(
+        client: &Client,
+        alert: &BudgetAlert,
+        percent: f64,
+    ) -> Result<Vec<BudgetCheck>, KubiousError> {
+        let quotas = Api::<ResourceQuota>::namespaced(client.clone(), alert.namespace.as_str())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list resource quotas")?;
+        let mut checks: Vec<BudgetCheck> = Vec::new();
+        for quota in quotas.items {
+            let scoped = quota
+                .spec
+                .as_ref()
+                .map(|s| s.scopes.is_some() || s.scope_selector.is_some())
+                .unwrap_or(false);
+            if scoped {
+                continue;
+            }
+            let name = quota.metadata.name.clone().unwrap_or_default();
+            let status = quota.status.unwrap_or_default();
+            let used = status.used.unwrap_or_default();
+            for (key, hard) in status.hard.unwrap_or_default() {
+                let hard = quantity_value(&hard);
+                if hard <= 0.0 {
+                    continue;
+                }
+                let used = used.get(&key).map(quantity_value).unwrap_or(0.0);
+                let value = used / hard * 100.0;
+                checks.push(check(
+                    alert,
+                    format!("quota/{}/{}", name, key),
+                    value,
+                    percent,
+                    format!(
+                        "{} of quota {} in {} is {:.0}% used ({} of {})",
+                        key, name, alert.namespace, value, used, hard
+                    ),
+                ));
+            }
+        }
+        Ok(checks)
+    }
+
+    /// Live CPU cores and memory bytes of the namespace, or `None` without
+    /// metrics-server.
+    async fn usage(client: &Client, namespace: &str) -> Option<(f64, f64)> {
+        let gvk = GroupVersionKind::gvk("metrics.k8s.io", "v1beta1", "PodMetrics");
+        let (resource, _) = pinned_kind(client, &gvk).await.ok()?;
+        let metrics = Api::<DynamicObject>::namespaced_with(client.clone(), namespace, &resource)
+            .list(&ListParams::default())
+            .await
+            .ok()?;
+        let mut total = (0.0, 0.0);
+        for pod in metrics.items {
+            let pod = serde_json::to_value(pod).unwrap_or_default();
+            for container in pod["containers"].as_array().into_iter().flatten() {
+                let value = |key: &str| {
+                    container["usage"][key]
+                        .as_str()
+                        .and_then(parse_quantity)
+                        .unwrap_or(0.0)
+                };
+                total.0 += value("cpu");
+                total.1 += value("memory");
+            }
+        }
+        Some(total)
+    }
+
+    /// Current restart count of every container in the namespace, keyed by
+    /// pod UID and container name so recreated pods are not mistaken for
+    /// restarts going backwards.
+    async fn restart_counts(
+        client: &Client,
+        namespace: &str,
+    ) -> Result<HashMap<String, i32>, KubiousError> {
+        let pods = Api::<Pod>::namespaced(client.clone(), namespace)
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list pods")?;
+        let mut counts: HashMap<String, i32> = HashMap::new();
+        for pod in pods.items {
+            let uid = pod.metadata.uid.clone().unwrap_or_default();
+            let status = pod.status.unwrap_or_default();
+            for container in status
+                .init_container_statuses
+                .unwrap_or_default()
+                .into_iter()
+                .chain(status.container_statuses.unwrap_or_default())
+            {
+                counts.insert(
+                    format!("{}/{}", uid, container.name),
+                    container.restart_count,
+                );
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Restarts within the window, measured against the oldest sample still
+    /// in it. Containers that appeared since then count from zero.
+    fn restarts_in_window(
+        samples: &mut VecDeque<RestartSample>,
+        counts: HashMap<String, i32>,
+    ) -> i32 {
+        let now = Instant::now();
+        while samples
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > RESTART_WINDOW)
+        {
+            samples.pop_front();
+        }
+        let restarts = match samples.front() {
+            Some((_, oldest)) => counts
+                .iter()
+                .map(|(key, count)| (count - oldest.get(key).copied().unwrap_or(0)).max(0))
+                .sum(),
+            None => 0,
+        };
+        samples.push_back((now, counts));
+        restarts
+    }
+
+    async fn evaluate_alert(
+        handle: &AppHandle,
+        client: &Client,
+        alert: &BudgetAlert,
+    ) -> Result<Vec<BudgetCheck>, KubiousError> {
+        let mut checks: Vec<BudgetCheck> = Vec::new();
+        if let Some(percent) = alert.quota_percent {
+            checks.extend(quota_checks(client, alert, percent).await?);
+        }
+        if alert.cpu_cores.is_some() || alert.memory_bytes.is_some() {
+            if let Some((cpu, memory)) = usage(client, alert.namespace.as_str()).await {
+                if let Some(cores) = alert.cpu_cores {
+                    checks.push(check(
+                        alert,
+                        "usage/cpu".to_string(),
+                        cpu,
+                        cores,
+                        format!("{} is using {:.2} CPU cores", alert.namespace, cpu),
+                    ));
+                }
+                if let Some(bytes) = alert.memory_bytes {
+                    checks.push(check(
+                        alert,
+                        "usage/memory".to_string(),
+                        memory,
+                        bytes,
+                        format!(
+                            "{} is using {:.0} MiB of memory",
+                            alert.namespace,
+                            memory / 1048576.0
+                        ),
+                    ));
+                }
+            }
+        }
+        if let Some(limit) = alert.restarts_per_hour {
+            let counts = restart_counts(client, alert.namespace.as_str()).await?;
+            let restarts = restarts_in_window(
+                handle
+                    .state::<BudgetAlerts>()
+                    .tracking_mutable()
+                    .samples
+                    .entry(alert.id.clone())
+                    .or_default(),
+                counts,
+            );
+            checks.push(check(
+                alert,
+                "restarts".to_string(),
+                restarts as f64,
+                limit as f64,
+                format!(
+                    "Containers in {} restarted {} times in the last hour",
+                    alert.namespace, restarts
+                ),
+            ));
+        }
+        Ok(checks)
+    }
+
+    /// Evaluates every configured alert. Thresholds that are newly crossed
+    /// are recorded in the breach history and emitted on
+    /// `BUDGET_ALERT_EVENT`; ones that stay breached are not reported again
+    /// until they recover. Alerts on unreachable clusters are skipped.
+    pub async fn evaluate(handle: AppHandle) -> Vec<BudgetCheck> {
+        let state = handle.state::<AppState>();
+        let alerts = state.get_settings().budget_alerts;
+        let mut checks: Vec<BudgetCheck> = Vec::new();
+        for alert in &alerts {
+            let Some(client) = state.client_for(alert.cluster.as_str()).await else {
+                continue;
+            };
+            if let Ok(results) = evaluate_alert(&handle, &client, alert).await {
+                checks.extend(results);
+            }
+        }
+
+        let budgets = handle.state::<BudgetAlerts>();
+        let mut breaches: Vec<BudgetBreach> = Vec::new();
+        {
+            let mut tracking = budgets.tracking_mutable();
+            tracking
+                .samples
+                .retain(|id, _| alerts.iter().any(|a| a.id == *id));
+            tracking
+                .active
+                .retain(|(id, _)| alerts.iter().any(|a| a.id == *id));
+            for check in &checks {
+                let key = (check.alert_id.clone(), check.rule.clone());
+                if !check.breached {
+                    tracking.active.remove(&key);
+                } else if tracking.active.insert(key) {
+                    breaches.push(BudgetBreach {
+                        alert_id: check.alert_id.clone(),
+                        cluster: check.cluster.clone(),
+                        namespace: check.namespace.clone(),
+                        rule: check.rule.clone(),
+                        value: check.value,
+                        threshold: check.threshold,
+                        message: check.message.clone(),
+                        at: Utc::now().to_rfc3339(),
+                    });
+                }
+            }
+        }
+        if !breaches.is_empty() {
+            for breach in &breaches {
+                let _ = handle.emit(BUDGET_ALERT_EVENT, breach.clone());
+            }
+            state.record_budget_breaches(breaches);
+            let _ = state.save_state(handle.clone());
+        }
+        checks
+    }
+
+    /// Starts the background loop once; later calls do nothing.
+    pub async fn start(handle: AppHandle) {
+        {
+            let budgets = handle.state::<BudgetAlerts>();
+            let mut tracking = budgets.tracking_mutable();
+            if tracking.started {
+                return;
+            }
+            tracking.started = true;
+        }
+        loop {
+            evaluate(handle.clone()).await;
+            tokio::time::sleep(INTERVAL).await;
+        }
+    }
+}
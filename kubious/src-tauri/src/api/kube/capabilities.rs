@@ -0,0 +1,266 @@
+pub mod rbac_capabilities {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        sync::{Mutex, MutexGuard},
+        time::{Duration, Instant},
+    };
+
+    use futures::future::join_all;
+    use k8s_openapi::api::authorization::v1::{
+        ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+    };
+    use kube::{api::PostParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    use crate::{compat::kube_compat::KubeConfig, util::lock_recovery::recover};
+
+    /// Cached maps are also dropped after this long, since roles can change
+    /// on the server without the local credentials changing.
+    const CACHE_TTL: Duration = Duration::from_secs(300);
+
+    const VERBS: [&str; 7] = [
+        "get", "list", "watch", "create", "update", "patch", "delete",
+    ];
+
+    /// Resources the UI works with, as (group, resource, namespaced).
+    const RESOURCES: [(&str, &str, bool); 32] = [
+        ("", "pods", true),
+        ("", "pods/log", true),
+        ("", "pods/exec", true),
+        ("", "pods/portforward", true),
+        ("", "services", true),
+        ("", "configmaps", true),
+        ("", "secrets", true),
+        ("", "persistentvolumeclaims", true),
+        ("", "serviceaccounts", true),
+        ("", "events", true),
+        ("", "resourcequotas", true),
+        ("apps", "deployments", true),
+        ("apps", "statefulsets", true),
+        ("apps", "daemonsets", true),
+        ("apps", "replicasets", true),
+        ("batch", "jobs", true),
+        ("batch", "cronjobs", true),
+        ("autoscaling", "horizontalpodautoscalers", true),
+        ("networking.k8s.io", "ingresses", true),
+        ("networking.k8s.io", "networkpolicies", true),
+        ("policy", "poddisruptionbudgets", true),
+        ("rbac.authorization.k8s.io", "roles", true),
+        ("rbac.authorization.k8s.io", "rolebindings", true),
+        ("", "namespaces", false),
+        ("", "nodes", false),
+        ("", "persistentvolumes", false),
+        ("storage.k8s.io", "storageclasses", false),
+        ("apiextensions.k8s.io", "customresourcedefinitions", false),
+        ("rbac.authorization.k8s.io", "clusterroles", false),
+        ("rbac.authorization.k8s.io", "clusterrolebindings", false),
+        (
+            "admissionregistration.k8s.io",
+            "validatingwebhookconfigurations",
+            false,
+        ),
+        (
+            "admissionregistration.k8s.io",
+            "mutatingwebhookconfigurations",
+            false,
+        ),
+    ];
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CapabilityMap {
+        /// `None` for the cluster-wide map, which also covers cluster-scoped
+        /// resources.
+        pub namespace: Option<String>,
+        /// Allowed verbs keyed by `group/resource`, or just `resource` for
+        /// the core group.
+        pub resources: BTreeMap<String, BTreeMap<String, bool>>,
+        /// Resources the user can list, i.e. sections worth showing.
+        pub visible: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CapabilityReport {
+        pub cluster: String,
+        pub cached: bool,
+        pub maps: Vec<CapabilityMap>,
+    }
+
+    struct CachedMaps {
+        fingerprint: String,
+        fetched: Instant,
+        maps: HashMap<Option<String>, CapabilityMap>,
+    }
+
+    /// Capability maps per cluster, invalidated when the cluster's stored
+    /// credentials change.
+    #[derive(Default)]
+    pub struct CapabilityCache {
+        clusters: Mutex<HashMap<String, CachedMaps>>,
+    }
+
+    impl CapabilityCache {
+        fn clusters_mutable(&self) -> MutexGuard<'_, HashMap<String, CachedMaps>> {
+            recover(&self.clusters)
+        }
+
+        fn get(
+            &self,
+            cluster: &str,
+            fingerprint: &str,
+            namespace: &Option<String>,
+        ) -> Option<CapabilityMap> {
+            let clusters = self.clusters_mutable();
+            let cached = clusters.get(cluster)?;
+            if cached.fingerprint != fingerprint || cached.fetched.elapsed() > CACHE_TTL {
+                return None;
+            }
+            cached.maps.get(namespace).cloned()
+        }
+
+        fn put(&self, cluster: &str, fingerprint: &str, map: CapabilityMap) {
+            let mut clusters = self.clusters_mutable();
+            let stale = clusters
+                .get(cluster)
+                .map(|c| c.fingerprint != fingerprint || c.fetched.elapsed() > CACHE_TTL)
+                .unwrap_or(true);
+            if stale {
+                clusters.insert(
+                    cluster.to_string(),
+                    CachedMaps {
+                        fingerprint: fingerprint.to_string(),
+                        fetched: Instant::now(),
+                        maps: HashMap::new(),
+                    },
+                );
+            }
+            if let Some(cached) = clusters.get_mut(cluster) {
+                cached.maps.insert(map.namespace.clone(), map);
+            }
+        }
+
+        pub fn invalidate(&self, cluster: &str) {
+            self.clusters_mutable().remove(cluster);
+        }
+    }
+
+    /// Identifies the identity a config authenticates as. Secrets are stored
+    /// as keyring references, so this changes whenever credentials are
+    /// replaced without exposing them.
+    pub fn fingerprint(config: &KubeConfig) -> String {
+        let auth = serde_json::to_string(&config.auth_info).unwrap_or_default();
+        let digest = Sha256::digest(format!("{}\n{}", config.cluster_url, auth).as_bytes());
+        format!("{:x}", digest)
+    }
+
+    async fn allowed(
+        client: &Client,
+        namespace: &Option<String>,
+        group: &str,
+        resource: &str,
+        verb: &str,
+    ) -> bool {
+        let (resource, subresource) = match resource.split_once('/') {
+            Some((resource, sub)) => (resource, Some(sub.to_string())),
+            None => (resource, None),
+        };
+        let review = SelfSubjectAccessReview {
+            spec: SelfSubjectAccessReviewSpec {
+                resource_attributes: Some(ResourceAttributes {
+                    group: Some(group.to_string()),
+                    resource: Some(resource.to_string()),
+                    subresource,
+                    verb: Some(verb.to_string()),
+                    namespace: namespace.clone(),
+                    ..ResourceAttributes::default()
+                }),
+                ..SelfSubjectAccessReviewSpec::default()
+            },
+            ..SelfSubjectAccessReview::default()
+        };
+        Api::<SelfSubjectAccessReview>::all(client.clone())
+            .create(&PostParams::default(), &review)
+            .await
+            .ok()
+            .and_then(|r| r.status)
+            .map(|s| s.allowed)
+            .unwrap_or(false)
+    }
+
+    async fn build_map(client: &Client, namespace: Option<String>) -> CapabilityMap {
+        let checks: Vec<(String, &str, &str, &str)> = RESOURCES
+            .iter()
+            .filter(|(_, _, namespaced)| *namespaced || namespace.is_none())
+            .flat_map(|(group, resource, _)| {
+                let key = if group.is_empty() {
+                    resource.to_string()
+                } else {
+                    format!("{}/{}", group, resource)
+                };
+                VERBS
+                    .iter()
+                    .map(move |verb| (key.clone(), *group, *resource, *verb))
+            })
+            .collect();
+        let results =
+            join_all(checks.iter().map(|(_, group, resource, verb)| {
+                allowed(client, &namespace, group, resource, verb)
+            }))
+            .await;
+
+        let mut resources: BTreeMap<String, BTreeMap<String, bool>> = BTreeMap::new();
+        for ((key, _, _, verb), allowed) in checks.into_iter().zip(results) {
+            resources
+                .entry(key)
+                .or_default()
+                .insert(verb.to_string(), allowed);
+        }
+        let visible = resources
+            .iter()
+            .filter(|(_, verbs)| verbs.get("list").copied().unwrap_or(false))
+            .map(|(key, _)| key.clone())
+            .collect();
+        CapabilityMap {
+            namespace,
+            resources,
+            visible,
+        }
+    }
+
+    /// Capability maps for the cluster-wide scope and each namespace given,
+    /// answered from the cache unless `refresh` is set or the credentials
+    /// changed since they were computed.
+    pub async fn capabilities(
+        cache: &CapabilityCache,
+        client: Client,
+        cluster: String,
+        config: &KubeConfig,
+        namespaces: Vec<String>,
+        refresh: bool,
+    ) -> CapabilityReport {
+        let fingerprint = fingerprint(config);
+        if refresh {
+            cache.invalidate(cluster.as_str());
+        }
+        let scopes: Vec<Option<String>> = std::iter::once(None)
+            .chain(namespaces.into_iter().map(Some))
+            .collect();
+        let mut cached = true;
+        let mut maps: Vec<CapabilityMap> = Vec::new();
+        for scope in scopes {
+            if let Some(map) = cache.get(cluster.as_str(), fingerprint.as_str(), &scope) {
+                maps.push(map);
+                continue;
+            }
+            cached = false;
+            let map = build_map(&client, scope).await;
+            cache.put(cluster.as_str(), fingerprint.as_str(), map.clone());
+            maps.push(map);
+        }
+        CapabilityReport {
+            cluster,
+            cached,
+            maps,
+        }
+    }
+}
@@ -0,0 +1,324 @@
+pub mod cert_manager {
+    use k8s_openapi::chrono::{SecondsFormat, Utc};
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams, Patch, PatchParams},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    const CERTIFICATE_NAME_ANNOTATION: &str = "cert-manager.io/certificate-name";
+    const RENEW_REASON: &str = "ManuallyTriggered";
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CertificateSummary {
+        pub name: String,
+        pub namespace: String,
+        pub secret_name: Option<String>,
+        pub issuer: Option<String>,
+        pub dns_names: Vec<String>,
+        pub ready: bool,
+        pub issuing: bool,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+        pub not_after: Option<String>,
+        pub renewal_time: Option<String>,
+        pub revision: Option<i64>,
+    }
+
+    /// One object in the Certificate → CertificateRequest → Order → Challenge
+    /// chain, with the state its controller reported.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ChainLink {
+        pub kind: String,
+        pub name: String,
+        pub state: Option<String>,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+        pub failed: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CertificateInspection {
+        pub certificate: CertificateSummary,
+        pub chain: Vec<ChainLink>,
+        /// The deepest failing link, which is usually the root cause.
+        pub root_cause: Option<ChainLink>,
+    }
+
+    async fn api_for(
+        client: &Client,
+        group: &str,
+        kind: &str,
+        namespace: Option<&str>,
+    ) -> Result<Api<DynamicObject>, KubiousError> {
+        let gvk = GroupVersionKind::gvk(group, "v1", kind);
+        let (resource, _) = pinned_kind(client, &gvk)
+            .await
+            .context("cert-manager CRDs are not installed")?;
+        Ok(match namespace {
+            Some(ns) => Api::namespaced_with(client.clone(), ns, &resource),
+            None => Api::all_with(client.clone(), &resource),
+        })
+    }
+
+    fn condition<'a>(object: &'a DynamicObject, name: &str) -> Option<&'a Value> {
+        object.data["status"]["conditions"]
+            .as_array()?
+            .iter()
+            .find(|c| c["type"].as_str() == Some(name))
+    }
+
+    fn text(value: &Value) -> Option<String> {
+        value.as_str().map(|s| s.to_string())
+    }
+
+    fn summarize(certificate: &DynamicObject) -> CertificateSummary {
+        let spec = &certificate.data["spec"];
+        let status = &certificate.data["status"];
+        let ready = condition(certificate, "Ready");
+        CertificateSummary {
+            name: certificate.metadata.name.clone().unwrap_or_default(),
+            namespace: certificate.metadata.namespace.clone().unwrap_or_default(),
+            secret_name: text(&spec["secretName"]),
+            issuer: spec["issuerRef"]["name"].as_str().map(|name| {
+                format!(
+                    "{}/{}",
+                    spec["issuerRef"]["kind"].as_str().unwrap_or("Issuer"),
+                    name
+                )
+            }),
+            dns_names: spec["dnsNames"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(text)
+                .collect(),
+            ready: ready.map(|c| c["status"] == "True").unwrap_or(false),
+            issuing: condition(certificate, "Issuing")
+                .map(|c| c["status"] == "True")
+                .unwrap_or(false),
+            reason: ready.and_then(|c| text(&c["reason"])),
+            message: ready.and_then(|c| text(&c["message"])),
+            not_after: text(&status["notAfter"]),
+            renewal_time: text(&status["renewalTime"]),
+            revision: status["revision"].as_i64(),
+        }
+    }
+
+    fn owned_by(object: &DynamicObject, owner: &DynamicObject) -> bool {
+        let uid = owner.metadata.uid.clone().unwrap_or_default();
+        object
+            .metadata
+            .owner_references
+            .iter()
+            .flatten()
+            .any(|r| r.uid == uid)
+    }
+
+    fn newest_first(objects: &mut [DynamicObject]) {
+        objects.sort_by(|a, b| {
+            b.metadata
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0)
+                .cmp(&a.metadata.creation_timestamp.as_ref().map(|t| t.0))
+        });
+    }
+
+    fn request_link(request: &DynamicObject) -> ChainLink {
+        let ready = condition(request, "Ready");
+        let denied = condition(request, "Denied").map(|c| c["status"] == "True");
+        ChainLink {
+            kind: "CertificateRequest".to_string(),
+            name: request.metadata.name.clone().unwrap_or_default(),
+            state: ready.and_then(|c| text(&c["status"])),
+            reason: ready.and_then(|c| text(&c["reason"])),
+            message: ready
+                .and_then(|c| text(&c["message"]))
+                .or(text(&request.data["status"]["failureReason"])),
+            failed: denied.unwrap_or(false)
+                || ready
+                    .map(|c| c["reason"] == "Failed" || c["reason"] == "Denied")
+                    .unwrap_or(false),
+        }
+    }
+
+    fn acme_link(kind: &str, object: &DynamicObject) -> ChainLink {
+        let state = text(&object.data["status"]["state"]);
+        ChainLink {
+            kind: kind.to_string(),
+            name: object.metadata.name.clone().unwrap_or_default(),
+            failed: matches!(
+                state.as_deref(),
+                Some("invalid") | Some("errored") | Some("expired")
+            ),
+            state,
+            reason: None,
+            message: text(&object.data["status"]["reason"]),
+        }
+    }
+
+    pub async fn list_certificates(
+        client: Client,
+        namespace: Option<String>,
+    ) -> Result<Vec<CertificateSummary>, KubiousError> {
+        let certificates = api_for(
+            &client,
+            "cert-manager.io",
+            "Certificate",
+            namespace.as_deref(),
+        )
+        .await?
+        .list(&ListParams::default())
+        .await
+        .context("Failed to list Certificates.")?;
+        let mut summaries: Vec<CertificateSummary> =
+            certificates.items.iter().map(summarize).collect();
+        summaries.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+        Ok(summaries)
+    }
+
+    /// Follows the latest CertificateRequest of a Certificate down to its ACME
+    /// Order and Challenges, collecting each step's reported state.
+    pub async fn inspect_certificate(
+        client: Client,
+        namespace: String,
+        name: String,
+    ) -> Result<CertificateInspection, KubiousError> {
+        let ns = Some(namespace.as_str());
+        let certificate = api_for(&client, "cert-manager.io", "Certificate", ns)
+            .await?
+            .get(name.as_str())
+            .await
+            .context("Unknown Certificate")?;
+        let summary = summarize(&certificate);
+        let mut chain: Vec<ChainLink> = vec![ChainLink {
+            kind: "Certificate".to_string(),
+            name: name.clone(),
+            state: condition(&certificate, "Ready").and_then(|c| text(&c["status"])),
+            reason: summary.reason.clone(),
+            message: summary.message.clone(),
+            failed: !summary.ready && !summary.issuing,
+        }];
+
+        let mut requests: Vec<DynamicObject> =
+            api_for(&client, "cert-manager.io", "CertificateRequest", ns)
+                .await?
+                .list(&ListParams::default())
+                .await
+                .context("Failed to list CertificateRequests.")?
+                .items
+                .into_iter()
+                .filter(|r| {
+                    owned_by(r, &certificate)
+                        || r.metadata
+                            .annotations
+                            .as_ref()
+                            .and_then(|a| a.get(CERTIFICATE_NAME_ANNOTATION))
+                            == Some(&name)
+                })
+                .collect();
+        newest_first(&mut requests);
+
+        if let Some(request) = requests.first() {
+            chain.push(request_link(request));
+            // Orders and Challenges only exist for ACME issuers.
+            if let Ok(orders_api) = api_for(&client, "acme.cert-manager.io", "Order", ns).await {
+                let mut orders: Vec<DynamicObject> = orders_api
+                    .list(&ListParams::default())
+                    .await
+                    .map(|l| l.items)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|o| owned_by(o, request))
+                    .collect();
+                newest_first(&mut orders);
+                if let Some(order) = orders.first() {
+                    chain.push(acme_link("Order", order));
+                    let challenges =
+                        match api_for(&client, "acme.cert-manager.io", "Challenge", ns).await {
+                            Ok(api) => api
+                                .list(&ListParams::default())
+                                .await
+                                .map(|l| l.items)
+                                .unwrap_or_default(),
+                            Err(_) => Vec::new(),
+                        };
+                    for challenge in challenges.iter().filter(|c| owned_by(c, order)) {
+                        let mut link = acme_link("Challenge", challenge);
+                        link.name = format!(
+                            "{} ({})",
+                            link.name,
+                            challenge.data["spec"]["dnsName"]
+                                .as_str()
+                                .unwrap_or_default()
+                        );
+                        link.failed = link.failed
+                            || (challenge.data["status"]["presented"] == false
+                                && challenge.data["status"]["reason"].is_string());
+                        chain.push(link);
+                    }
+                }
+            }
+        }
+
+        Ok(CertificateInspection {
+            certificate: summary,
+            root_cause: chain.iter().rev().find(|l| l.failed).cloned(),
+            chain,
+        })
+    }
+
+    /// Requests reissuance the way `cmctl renew` does: by setting the
+    /// Certificate's `Issuing` condition, which cert-manager acts on.
+    pub async fn renew_certificate(
+        client: Client,
+        namespace: String,
+        name: String,
+    ) -> Result<CertificateSummary, KubiousError> {
+        let api = api_for(
+            &client,
+            "cert-manager.io",
+            "Certificate",
+            Some(namespace.as_str()),
+        )
+        .await?;
+        let certificate = api
+            .get(name.as_str())
+            .await
+            .context("Unknown Certificate")?;
+        if condition(&certificate, "Issuing").map(|c| c["status"] == "True") == Some(true) {
+            return Err("Certificate is already being issued".into());
+        }
+
+        let mut conditions: Vec<Value> = certificate.data["status"]["conditions"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|c| c["type"] != "Issuing")
+            .collect();
+        conditions.push(json!({
+            "type": "Issuing",
+            "status": "True",
+            "reason": RENEW_REASON,
+            "message": "Certificate re-issuance manually triggered",
+            "lastTransitionTime": Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            "observedGeneration": certificate.metadata.generation,
+        }));
+        let patch = json!({"status": {"conditions": conditions}});
+        let updated = api
+            .patch_status(
+                name.as_str(),
+                &PatchParams::default(),
+                &Patch::Merge(&patch),
+            )
+            .await
+            .context("Failed to renew Certificate")?;
+        Ok(summarize(&updated))
+    }
+}
@@ -0,0 +1,351 @@
+pub mod cleanup_advisor {
+    use std::collections::{HashMap, HashSet};
+
+    use k8s_openapi::{
+        api::{
+            apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
+            batch::v1::{CronJob, Job},
+            core::v1::{ConfigMap, Namespace, Pod},
+        },
+        apimachinery::pkg::apis::meta::v1::Time,
+        chrono::Utc,
+        NamespaceResourceScope,
+    };
+    use kube::{
+        api::{DeleteParams, ListParams, ObjectMeta},
+        Api, Client, Resource,
+    };
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    const DEFAULT_MIN_AGE_DAYS: i64 = 30;
+
+    /// Namespaces never suggested for removal, even when idle.
+    const PROTECTED_NAMESPACES: [&str; 4] =
+        ["default", "kube-system", "kube-public", "kube-node-lease"];
+
+    /// ConfigMaps the control plane creates in every namespace.
+    const PROTECTED_CONFIGMAPS: [&str; 1] = ["kube-root-ca.crt"];
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum CleanupReason {
+        /// A Job that finished long ago and is not managed by a CronJob.
+        CompletedJob,
+        /// A ConfigMap no pod or pod template references.
+        UnusedConfigMap,
+        /// A namespace without running pods.
+        IdleNamespace,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CleanupSuggestion {
+        /// `kind/namespace/name`, passed back to apply the suggestion.
+        pub id: String,
+        pub kind: String,
+        pub namespace: Option<String>,
+        pub name: String,
+        pub reason: CleanupReason,
+        /// Days since the object was created or, for Jobs, finished.
+        pub idle_days: i64,
+        pub detail: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CleanupReport {
+        pub min_age_days: i64,
+        pub suggestions: Vec<CleanupSuggestion>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CleanupOutcome {
+        pub id: String,
+        pub deleted: bool,
+        pub error: Option<String>,
+    }
+
+    fn days_since(time: Option<&Time>) -> Option<i64> {
+        time.map(|t| (Utc::now() - t.0).num_days())
+    }
+
+    fn suggestion(
+        kind: &str,
+        meta: &ObjectMeta,
+        reason: CleanupReason,
+        idle_days: i64,
+        detail: String,
+    ) -> CleanupSuggestion {
+        let name = meta.name.clone().unwrap_or_default();
+        CleanupSuggestion {
+            id: format!(
+                "{}/{}/{}",
+                kind,
+                meta.namespace.clone().unwrap_or_default(),
+                name
+            ),
+            kind: kind.to_string(),
+            namespace: meta.namespace.clone(),
+            name,
+            reason,
+            idle_days,
+            detail,
+        }
+    }
+
+    async fn list<K>(client: &Client, namespace: &Option<String>) -> Result<Vec<K>, KubiousError>
+    where
+        K: Resource<Scope = NamespaceResourceScope> + Clone + DeserializeOwned + std::fmt::Debug,
+        K::DynamicType: Default,
+    {
+        let api: Api<K> = match namespace {
+            Some(ns) => Api::namespaced(client.clone(), ns.as_str()),
+            None => Api::all(client.clone()),
+        };
+        api.list(&ListParams::default())
+            .await
+            .map(|list| list.items)
+            .context(format!("Failed to list {}", K::kind(&Default::default())))
+    }
+
+    /// Collects the names of ConfigMaps referenced anywhere in an object,
+    /// through volumes, projected sources, `envFrom` or `valueFrom`.
+    fn collect_config_maps(value: &Value, names: &mut HashSet<String>) {
+        match value {
+            Value::Object(fields) => {
+                for (key, child) in fields {
+                    if matches!(
+                        key.as_str(),
+                        "configMap" | "configMapRef" | "configMapKeyRef"
+                    ) {
+                        if let Some(name) = child["name"].as_str() {
+                            names.insert(name.to_string());
+                        }
+                    }
+                    collect_config_maps(child, names);
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|i| collect_config_maps(i, names)),
+            _ => {}
+        }
+    }
+
+    fn references<K: Serialize + Resource>(
+        objects: &[K],
+        referenced: &mut HashMap<String, HashSet<String>>,
+    ) {
+        for object in objects {
+            let namespace = object.meta().namespace.clone().unwrap_or_default();
+            if let Ok(value) = serde_json::to_value(object) {
+                collect_config_maps(&value["spec"], referenced.entry(namespace).or_default());
+            }
+        }
+    }
+
+    fn completed_jobs(jobs: &[Job], min_age_days: i64) -> Vec<CleanupSuggestion> {
+        jobs.iter()
+            .filter(|job| {
+                !job.metadata
+                    .owner_references
+                    .iter()
+                    .flatten()
+                    .any(|o| o.kind == "CronJob")
+            })
+            .filter_map(|job| {
+                let status = job.status.as_ref()?;
+                let finished = status.completion_time.as_ref().or_else(|| {
+                    status
+                        .conditions
+                        .iter()
+                        .flatten()
+                        .find(|c| c.type_ == "Failed" && c.status == "True")
+                        .and_then(|c| c.last_transition_time.as_ref())
+                })?;
+                let idle = days_since(Some(finished))?;
+                let outcome = if status.succeeded.unwrap_or(0) > 0 {
+                    "succeeded"
+                } else {
+                    "failed"
+                };
+                (idle >= min_age_days).then(|| {
+                    suggestion(
+                        "Job",
+                        &job.metadata,
+                        CleanupReason::CompletedJob,
+                        idle,
+                        format!("Job {} {} days ago", outcome, idle),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn unused_config_maps(
+        config_maps: &[ConfigMap],
+        referenced: &HashMap<String, HashSet<String>>,
+        min_age_days: i64,
+    ) -> Vec<CleanupSuggestion> {
+        config_maps
+            .iter()
+            .filter_map(|cm| {
+                let name = cm.metadata.name.as_deref()?;
+                let namespace = cm.metadata.namespace.clone().unwrap_or_default();
+                if PROTECTED_CONFIGMAPS.contains(&name)
+                    || cm.metadata.owner_references.is_some()
+                    || referenced
+                        .get(&namespace)
+                        .is_some_and(|names| names.contains(name))
+                {
+                    return None;
+                }
+                let idle = days_since(cm.metadata.creation_timestamp.as_ref())?;
+                (idle >= min_age_days).then(|| {
+                    suggestion(
+                        "ConfigMap",
+                        &cm.metadata,
+                        CleanupReason::UnusedConfigMap,
+                        idle,
+                        "Not referenced by any pod or workload template".to_string(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    async fn idle_namespaces(
+        client: &Client,
+        pods: &[Pod],
+        min_age_days: i64,
+    ) -> Result<Vec<CleanupSuggestion>, KubiousError> {
+        let namespaces = Api::<Namespace>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list namespaces.")?;
+        let active: HashSet<String> = pods
+            .iter()
+            .filter(|p| p.status.as_ref().and_then(|s| s.phase.as_deref()) == Some("Running"))
+            .filter_map(|p| p.metadata.namespace.clone())
+            .collect();
+        Ok(namespaces
+            .items
+            .iter()
+            .filter_map(|ns| {
+                let name = ns.metadata.name.as_deref()?;
+                if PROTECTED_NAMESPACES.contains(&name) || active.contains(name) {
+                    return None;
+                }
+                let idle = days_since(ns.metadata.creation_timestamp.as_ref())?;
+                let total = pods
+                    .iter()
+                    .filter(|p| p.metadata.namespace.as_deref() == Some(name))
+                    .count();
+                (idle >= min_age_days).then(|| {
+                    suggestion(
+                        "Namespace",
+                        &ns.metadata,
+                        CleanupReason::IdleNamespace,
+                        idle,
+                        format!("No running pods ({} pods in other phases)", total),
+                    )
+                })
+            })
+            .collect())
+    }
+
+    /// Finds resources that look abandoned: finished Jobs, ConfigMaps nothing
+    /// references and namespaces without running pods, all at least
+    /// `min_age_days` old. Namespaces are only checked cluster-wide.
+    pub async fn analyze(
+        client: Client,
+        namespace: Option<String>,
+        min_age_days: Option<i64>,
+    ) -> Result<CleanupReport, KubiousError> {
+        let min_age_days = min_age_days.unwrap_or(DEFAULT_MIN_AGE_DAYS);
+        let pods: Vec<Pod> = list(&client, &namespace).await?;
+        let jobs: Vec<Job> = list(&client, &namespace).await?;
+        let config_maps: Vec<ConfigMap> = list(&client, &namespace).await?;
+
+        let mut referenced: HashMap<String, HashSet<String>> = HashMap::new();
+        references(&pods, &mut referenced);
+        references(&jobs, &mut referenced);
+        references(
+            &list::<Deployment>(&client, &namespace).await?,
+            &mut referenced,
+        );
+        references(
+            &list::<StatefulSet>(&client, &namespace).await?,
+            &mut referenced,
+        );
+        references(
+            &list::<DaemonSet>(&client, &namespace).await?,
+            &mut referenced,
+        );
+        references(
+            &list::<ReplicaSet>(&client, &namespace).await?,
+            &mut referenced,
+        );
+        references(
+            &list::<CronJob>(&client, &namespace).await?,
+            &mut referenced,
+        );
+
+        let mut suggestions = completed_jobs(&jobs, min_age_days);
+        suggestions.extend(unused_config_maps(&config_maps, &referenced, min_age_days));
+        if namespace.is_none() {
+            suggestions.extend(idle_namespaces(&client, &pods, min_age_days).await?);
+        }
+        suggestions.sort_by(|a, b| b.idle_days.cmp(&a.idle_days).then(a.id.cmp(&b.id)));
+        Ok(CleanupReport {
+            min_age_days,
+            suggestions,
+        })
+    }
+
+    /// Deletes the suggestions with the given ids. The analysis is re-run
+    /// first and ids that are no longer suggested are skipped, so objects
+    /// that became active since the report was shown are kept.
+    pub async fn apply(
+        client: Client,
+        namespace: Option<String>,
+        min_age_days: Option<i64>,
+        ids: Vec<String>,
+    ) -> Result<Vec<CleanupOutcome>, KubiousError> {
+        let report = analyze(client.clone(), namespace, min_age_days).await?;
+        let mut outcomes: Vec<CleanupOutcome> = Vec::new();
+        for id in ids {
+            let Some(target) = report.suggestions.iter().find(|s| s.id == id) else {
+                outcomes.push(CleanupOutcome {
+                    id,
+                    deleted: false,
+                    error: Some("No longer suggested for cleanup".to_string()),
+                });
+                continue;
+            };
+            let params = DeleteParams::background();
+            let ns = target.namespace.clone().unwrap_or_default();
+            let name = target.name.as_str();
+            let result = match target.reason {
+                CleanupReason::CompletedJob => Api::<Job>::namespaced(client.clone(), ns.as_str())
+                    .delete(name, &params)
+                    .await
+                    .map(|_| ()),
+                CleanupReason::UnusedConfigMap => {
+                    Api::<ConfigMap>::namespaced(client.clone(), ns.as_str())
+                        .delete(name, &params)
+                        .await
+                        .map(|_| ())
+                }
+                CleanupReason::IdleNamespace => Api::<Namespace>::all(client.clone())
+                    .delete(name, &params)
+                    .await
+                    .map(|_| ()),
+            };
+            outcomes.push(CleanupOutcome {
+                id,
+                deleted: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+        Ok(outcomes)
+    }
+}
@@ -0,0 +1,414 @@
+pub mod config_editor {
+    use std::collections::BTreeMap;
+
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use k8s_openapi::{
+        api::core::v1::{ConfigMap, Secret},
+        ByteString,
+    };
+    use kube::{
+        api::{ObjectMeta, PostParams},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        api::kube::config_rollout::ConfigKind,
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    /// How an entry's `value` is written: as it is, or base64 because the
+    /// bytes are not valid UTF-8.
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+    pub enum ValueEncoding {
+        #[default]
+        Text,
+        Base64,
+    }
+
+    /// One key of a ConfigMap or Secret. Secret values are decoded, so they
+    /// read the same as ConfigMap values.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ConfigEntry {
+        pub key: String,
+        pub value: String,
+        #[serde(default)]
+        pub encoding: ValueEncoding,
+        /// Size of the decoded value in bytes.
+        #[serde(default)]
+        pub size: usize,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ConfigObject {
+        pub kind: ConfigKind,
+        pub namespace: String,
+        pub name: String,
+        pub immutable: bool,
+        /// The Secret type, e.g. `kubernetes.io/tls`.
+        pub secret_type: Option<String>,
+        pub resource_version: Option<String>,
+        pub entries: Vec<ConfigEntry>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NewConfigObject {
+        pub kind: ConfigKind,
+        pub name: String,
+        #[serde(default)]
+        pub entries: Vec<ConfigEntry>,
+        pub secret_type: Option<String>,
+        #[serde(default)]
+        pub immutable: bool,
+        pub labels: Option<BTreeMap<String, String>>,
+    }
+
+    /// Key changes applied together: renames first, then removals, then
+    /// values set.
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    pub struct ConfigEdit {
+        #[serde(default)]
+        pub set: Vec<ConfigEntry>,
+        #[serde(default)]
+        pub remove: Vec<String>,
+        /// Old key to new key.
+        #[serde(default)]
+        pub rename: BTreeMap<String, String>,
+        /// Rejects the edit if the object changed since it was fetched.
+        pub resource_version: Option<String>,
+        /// Marks the object immutable once the edit is applied.
+        #[serde(default)]
+        pub make_immutable: bool,
+    }
+
+    /// Keys must be valid file names, as they are when mounted.
+    fn check_key(key: &str) -> Result<(), KubiousError> {
+        let valid = !key.is_empty()
+            && key.len() <= 253
+            && key != "."
+            && key != ".."
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+        if valid {
+            Ok(())
+        } else {
+            Err(format!(
+                "Invalid key {:?}: use letters, digits, '-', '_' and '.'",
+                key
+            )
+            .into())
+        }
+    }
+
+    fn entry(key: String, bytes: Vec<u8>) -> ConfigEntry {
+        let size = bytes.len();
+        match String::from_utf8(bytes) {
+            Ok(value) => ConfigEntry {
+                key,
+                value,
+                encoding: ValueEncoding::Text,
+                size,
+            },
+            Err(e) => ConfigEntry {
+                key,
+                value: STANDARD.encode(e.into_bytes()),
+                encoding: ValueEncoding::Base64,
+                size,
+            },
+        }
+    }
+
+    fn entry_bytes(entry: &ConfigEntry) -> Result<Vec<u8>, KubiousError> {
+        check_key(entry.key.as_str())?;
+        match entry.encoding {
+            ValueEncoding::Text => Ok(entry.value.clone().into_bytes()),
+            ValueEncoding::Base64 => STANDARD
+                .decode(entry.value.trim())
+                .map_err(|e| format!("Value of {} is not valid base64: {}", entry.key, e).into()),
+        }
+    }
+
+    fn immutable_error(kind: ConfigKind, name: &str) -> String {
+        format!(
+            "{:?} {} is immutable; delete and recreate it to change its data",
+            kind, name
+        )
+    }
+
+    /// Explains failures the server reports only as a generic 422 or 409.
+    fn describe_error(kind: ConfigKind, name: &str, error: kube::Error) -> KubiousError {
+        match error {
+            kube::Error::Api(e) if e.code == 409 => KubiousError::Api {
+                message: format!(
+                    "{:?} {} was changed by someone else; reload it and try again",
+                    kind, name
+                ),
+                status: Some(e.code),
+                reason: Some(e.reason),
+            },
+            kube::Error::Api(e) if e.code == 422 && e.message.contains("immutable") => {
+                KubiousError::Api {
+                    message: immutable_error(kind, name),
+                    status: Some(e.code),
+                    reason: Some(e.reason),
+                }
+            }
+            e => KubiousError::from(e).with_context(format!("Failed to save {:?} {}", kind, name)),
+        }
+    }
+
+    fn from_config_map(namespace: &str, map: ConfigMap) -> ConfigObject {
+        let mut entries: Vec<ConfigEntry> = map
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| entry(key, value.into_bytes()))
+            .collect();
+        entries.extend(
+            map.binary_data
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(key, value)| ConfigEntry {
+                    key,
+                    size: value.0.len(),
+                    value: STANDARD.encode(value.0),
+                    encoding: ValueEncoding::Base64,
+                }),
+        );
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        ConfigObject {
+            kind: ConfigKind::ConfigMap,
+            namespace: namespace.to_string(),
+            name: map.metadata.name.unwrap_or_default(),
+            immutable: map.immutable == Some(true),
+            secret_type: None,
+            resource_version: map.metadata.resource_version,
+            entries,
+        }
+    }
+
+    fn from_secret(namespace: &str, secret: Secret) -> ConfigObject {
+        let data: BTreeMap<String, Vec<u8>> = secret
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| (key, value.0))
+            .collect();
+        ConfigObject {
+            kind: ConfigKind::Secret,
+            namespace: namespace.to_string(),
+            name: secret.metadata.name.unwrap_or_default(),
+            immutable: secret.immutable == Some(true),
+            secret_type: secret.type_,
+            resource_version: secret.metadata.resource_version,
+            entries: data
+                .into_iter()
+                .map(|(key, value)| entry(key, value))
+                .collect(),
+        }
+    }
+
+    /// Writes the entries into a ConfigMap, text values into `data` and
+    /// the rest into `binaryData`.
+    fn fill_config_map(map: &mut ConfigMap, entries: BTreeMap<String, Vec<u8>>) {
+        let mut data = BTreeMap::new();
+        let mut binary = BTreeMap::new();
+        for (key, value) in entries {
+            match String::from_utf8(value) {
+                Ok(text) => {
+                    data.insert(key, text);
+                }
+                Err(e) => {
+                    binary.insert(key, ByteString(e.into_bytes()));
+                }
+            }
+        }
+        map.data = Some(data);
+        map.binary_data = (!binary.is_empty()).then_some(binary);
+    }
+
+    fn fill_secret(secret: &mut Secret, entries: BTreeMap<String, Vec<u8>>) {
+        secret.string_data = None;
+        secret.data = Some(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, ByteString(value)))
+                .collect(),
+        );
+    }
+
+    fn current_entries(object: &ConfigObject) -> Result<BTreeMap<String, Vec<u8>>, KubiousError> {
+        object
+            .entries
+            .iter()
+            .map(|e| entry_bytes(e).map(|bytes| (e.key.clone(), bytes)))
+            .collect()
+    }
+
+    /// Applies an edit to the decoded entries, refusing renames and
+    /// removals of keys that do not exist and renames onto existing keys.
+    fn edit_entries(
+        mut entries: BTreeMap<String, Vec<u8>>,
+        edit: &ConfigEdit,
+    ) -> Result<BTreeMap<String, Vec<u8>>, KubiousError> {
+        for (from, to) in edit.rename.iter() {
+            check_key(to.as_str())?;
+            let Some(value) = entries.remove(from) else {
+                return Err(format!("Cannot rename {}: no such key", from).into());
+            };
+            if entries.contains_key(to) && !edit.remove.contains(to) {
+                return Err(format!("Cannot rename {} to {}: the key exists", from, to).into());
+            }
+            entries.insert(to.clone(), value);
+        }
+        for key in edit.remove.iter() {
+            if edit.rename.values().any(|to| to == key) {
+                continue;
+            }
+            if entries.remove(key).is_none() {
+                return Err(format!("Cannot remove {}: no such key", key).into());
+            }
+        }
+        for entry in edit.set.iter() {
+            entries.insert(entry.key.clone(), entry_bytes(entry)?);
+        }
+        Ok(entries)
+    }
+
+    pub async fn get(
+        client: Client,
+        kind: ConfigKind,
+        namespace: &str,
+        name: &str,
+    ) -> Result<ConfigObject, KubiousError> {
+        match kind {
+            ConfigKind::ConfigMap => Api::<ConfigMap>::namespaced(client, namespace)
+                .get(name)
+                .await
+                .map(|map| from_config_map(namespace, map))
+                .context(format!("Failed to get ConfigMap {}", name)),
+            ConfigKind::Secret => Api::<Secret>::namespaced(client, namespace)
+                .get(name)
+                .await
+                .map(|secret| from_secret(namespace, secret))
+                .context(format!("Failed to get Secret {}", name)),
+        }
+    }
+
+    pub async fn create(
+        client: Client,
+        namespace: &str,
+        object: NewConfigObject,
+    ) -> Result<ConfigObject, KubiousError> {
+        let mut entries = BTreeMap::new();
+        for entry in object.entries.iter() {
+            if entries
+                .insert(entry.key.clone(), entry_bytes(entry)?)
+                .is_some()
+            {
+                return Err(format!("Key {} is given more than once", entry.key).into());
+            }
+        }
+        let metadata = ObjectMeta {
+            name: Some(object.name.clone()),
+            namespace: Some(namespace.to_string()),
+            labels: object.labels.clone(),
+            ..ObjectMeta::default()
+        };
+        let immutable = object.immutable.then_some(true);
+        match object.kind {
+            ConfigKind::ConfigMap => {
+                let mut map = ConfigMap {
+                    metadata,
+                    immutable,
+                    ..ConfigMap::default()
+                };
+                fill_config_map(&mut map, entries);
+                Api::<ConfigMap>::namespaced(client, namespace)
+                    .create(&PostParams::default(), &map)
+                    .await
+                    .map(|map| from_config_map(namespace, map))
+                    .map_err(|e| describe_error(object.kind, object.name.as_str(), e))
+            }
+            ConfigKind::Secret => {
+                let mut secret = Secret {
+                    metadata,
+                    immutable,
+                    type_: object.secret_type.clone(),
+                    ..Secret::default()
+                };
+                fill_secret(&mut secret, entries);
+                Api::<Secret>::namespaced(client, namespace)
+                    .create(&PostParams::default(), &secret)
+                    .await
+                    .map(|secret| from_secret(namespace, secret))
+                    .map_err(|e| describe_error(object.kind, object.name.as_str(), e))
+            }
+        }
+    }
+
+    /// Checks that the object may be edited and returns its entries with
+    /// the edit applied.
+    fn edited_entries(
+        current: &ConfigObject,
+        edit: &ConfigEdit,
+    ) -> Result<BTreeMap<String, Vec<u8>>, KubiousError> {
+        if current.immutable {
+            return Err(immutable_error(current.kind, current.name.as_str()).into());
+        }
+        if let Some(version) = edit.resource_version.as_ref() {
+            if current.resource_version.as_ref() != Some(version) {
+                return Err(format!(
+                    "{:?} {} was changed by someone else; reload it and try again",
+                    current.kind, current.name
+                )
+                .into());
+            }
+        }
+        edit_entries(current_entries(current)?, edit)
+    }
+
+    /// Replaces the object's data with the edited entries. The replace
+    /// carries the resource version read, so a concurrent change is
+    /// reported rather than overwritten.
+    pub async fn edit(
+        client: Client,
+        kind: ConfigKind,
+        namespace: &str,
+        name: &str,
+        edit: ConfigEdit,
+    ) -> Result<ConfigObject, KubiousError> {
+        let immutable = edit.make_immutable.then_some(true);
+        match kind {
+            ConfigKind::ConfigMap => {
+                let api = Api::<ConfigMap>::namespaced(client, namespace);
+                let mut map = api
+                    .get(name)
+                    .await
+                    .context(format!("Failed to get ConfigMap {}", name))?;
+                let entries = edited_entries(&from_config_map(namespace, map.clone()), &edit)?;
+                map.immutable = immutable.or(map.immutable);
+                fill_config_map(&mut map, entries);
+                api.replace(name, &PostParams::default(), &map)
+                    .await
+                    .map(|map| from_config_map(namespace, map))
+                    .map_err(|e| describe_error(kind, name, e))
+            }
+            ConfigKind::Secret => {
+                let api = Api::<Secret>::namespaced(client, namespace);
+                let mut secret = api
+                    .get(name)
+                    .await
+                    .context(format!("Failed to get Secret {}", name))?;
+                let entries = edited_entries(&from_secret(namespace, secret.clone()), &edit)?;
+                secret.immutable = immutable.or(secret.immutable);
+                fill_secret(&mut secret, entries);
+                api.replace(name, &PostParams::default(), &secret)
+                    .await
+                    .map(|secret| from_secret(namespace, secret))
+                    .map_err(|e| describe_error(kind, name, e))
+            }
+        }
+    }
+}
@@ -0,0 +1,252 @@
+pub mod control_plane_health {
+    use http::Request;
+    use k8s_openapi::{
+        api::{
+            coordination::v1::Lease,
+            core::v1::{ComponentStatus, Pod},
+        },
+        chrono::Utc,
+    };
+    use kube::{api::ListParams, client::Body, Api, Client};
+    use serde::{Deserialize, Serialize};
+
+    const ENDPOINTS: [&str; 2] = ["readyz", "livez"];
+
+    /// Control plane components running as static pods labelled
+    /// `component=<name>` on self-managed clusters.
+    const COMPONENTS: [&str; 4] = [
+        "kube-apiserver",
+        "kube-controller-manager",
+        "kube-scheduler",
+        "etcd",
+    ];
+
+    /// Leader election leases renewed by running controllers; a stale lease
+    /// means the component is down even where its pods are not visible.
+    const LEADER_LEASES: [&str; 2] = ["kube-controller-manager", "kube-scheduler"];
+    const LEASE_GRACE_SECONDS: i64 = 60;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct HealthCheck {
+        pub name: String,
+        pub healthy: bool,
+        pub message: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct EndpointHealth {
+        pub endpoint: String,
+        pub status: Option<u16>,
+        pub healthy: bool,
+        pub checks: Vec<HealthCheck>,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum ComponentSource {
+        ComponentStatus,
+        Pod,
+        Lease,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ComponentHealth {
+        pub component: String,
+        pub source: ComponentSource,
+        pub healthy: bool,
+        pub message: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ControlPlaneHealth {
+        pub healthy: bool,
+        pub endpoints: Vec<EndpointHealth>,
+        pub components: Vec<ComponentHealth>,
+        /// Names of every failing check and component.
+        pub failing: Vec<String>,
+    }
+
+    /// Parses `[+]name ok` / `[-]name failed: reason` lines of a verbose
+    /// health endpoint response.
+    fn parse_checks(body: &str) -> Vec<HealthCheck> {
+        body.lines()
+            .filter_map(|line| {
+                let (healthy, rest) = if let Some(rest) = line.strip_prefix("[+]") {
+                    (true, rest)
+                } else {
+                    (false, line.strip_prefix("[-]")?)
+                };
+                let (name, message) = rest.split_once(' ').unwrap_or((rest, ""));
+                let message = message.trim();
+                Some(HealthCheck {
+                    name: name.to_string(),
+                    healthy,
+                    message: (!message.is_empty() && message != "ok").then(|| message.to_string()),
+                })
+            })
+            .collect()
+    }
+
+    async fn endpoint_health(client: &Client, endpoint: &str) -> EndpointHealth {
+        let mut health = EndpointHealth {
+            endpoint: endpoint.to_string(),
+            status: None,
+            healthy: false,
+            checks: Vec::new(),
+            error: None,
+        };
+        let Ok(request) = Request::get(format!("/{}?verbose", endpoint)).body(Body::empty()) else {
+            return health;
+        };
+        // Failing endpoints answer 500 with the same check listing, so the
+        // body is read regardless of status.
+        match client.send(request).await {
+            Ok(response) => {
+                let status = response.status();
+                health.status = Some(status.as_u16());
+                health.healthy = status.is_success();
+                match response.into_body().collect_bytes().await {
+                    Ok(bytes) => {
+                        health.checks = parse_checks(String::from_utf8_lossy(&bytes).as_ref())
+                    }
+                    Err(e) => health.error = Some(e.to_string()),
+                }
+            }
+            Err(e) => health.error = Some(e.to_string()),
+        }
+        health
+    }
+
+    async fn component_statuses(client: &Client) -> Vec<ComponentHealth> {
+        let Ok(statuses) = Api::<ComponentStatus>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+        else {
+            return Vec::new();
+        };
+        statuses
+            .items
+            .into_iter()
+            .map(|status| {
+                let condition = status
+                    .conditions
+                    .iter()
+                    .flatten()
+                    .find(|c| c.type_ == "Healthy")
+                    .cloned();
+                ComponentHealth {
+                    component: status.metadata.name.unwrap_or_default(),
+                    source: ComponentSource::ComponentStatus,
+                    healthy: condition
+                        .as_ref()
+                        .map(|c| c.status == "True")
+                        .unwrap_or(false),
+                    message: condition.and_then(|c| c.error.or(c.message)),
+                }
+            })
+            .collect()
+    }
+
+    async fn static_pods(client: &Client) -> Vec<ComponentHealth> {
+        let pods: Api<Pod> = Api::namespaced(client.clone(), "kube-system");
+        let mut components: Vec<ComponentHealth> = Vec::new();
+        for component in COMPONENTS {
+            let Ok(found) = pods
+                .list(&ListParams::default().labels(format!("component={}", component).as_str()))
+                .await
+            else {
+                continue;
+            };
+            for pod in found.items {
+                let status = pod.status.unwrap_or_default();
+                let ready = status
+                    .conditions
+                    .iter()
+                    .flatten()
+                    .find(|c| c.type_ == "Ready")
+                    .cloned();
+                components.push(ComponentHealth {
+                    component: pod.metadata.name.unwrap_or(component.to_string()),
+                    source: ComponentSource::Pod,
+                    healthy: ready.as_ref().map(|c| c.status == "True").unwrap_or(false),
+                    message: ready
+                        .and_then(|c| c.message)
+                        .or(status.phase.filter(|p| p != "Running")),
+                });
+            }
+        }
+        components
+    }
+
+    async fn leader_leases(client: &Client) -> Vec<ComponentHealth> {
+        let leases: Api<Lease> = Api::namespaced(client.clone(), "kube-system");
+        let mut components: Vec<ComponentHealth> = Vec::new();
+        for name in LEADER_LEASES {
+            let Ok(Some(lease)) = leases.get_opt(name).await else {
+                continue;
+            };
+            let spec = lease.spec.unwrap_or_default();
+            let duration = spec.lease_duration_seconds.unwrap_or(15) as i64;
+            let age = spec.renew_time.map(|t| (Utc::now() - t.0).num_seconds());
+            let healthy = age
+                .map(|age| age <= duration + LEASE_GRACE_SECONDS)
+                .unwrap_or(false);
+            components.push(ComponentHealth {
+                component: name.to_string(),
+                source: ComponentSource::Lease,
+                healthy,
+                message: Some(match (age, spec.holder_identity) {
+                    (Some(age), Some(holder)) => {
+                        format!("Leader {} renewed {}s ago", holder, age)
+                    }
+                    (Some(age), None) => format!("Renewed {}s ago", age),
+                    (None, _) => "Lease was never renewed".to_string(),
+                }),
+            });
+        }
+        components
+    }
+
+    /// Aggregates the API server's verbose health endpoints with component
+    /// surrogates: the deprecated ComponentStatus API, control plane static
+    /// pods and leader election leases. Sources the cluster does not expose
+    /// (as on most managed offerings) are left out.
+    pub async fn check(client: Client) -> ControlPlaneHealth {
+        let mut endpoints: Vec<EndpointHealth> = Vec::new();
+        for endpoint in ENDPOINTS {
+            endpoints.push(endpoint_health(&client, endpoint).await);
+        }
+        let mut components = component_statuses(&client).await;
+        components.extend(static_pods(&client).await);
+        components.extend(leader_leases(&client).await);
+
+        let mut failing: Vec<String> = Vec::new();
+        for endpoint in &endpoints {
+            let failed: Vec<String> = endpoint
+                .checks
+                .iter()
+                .filter(|c| !c.healthy)
+                .map(|c| format!("{}/{}", endpoint.endpoint, c.name))
+                .collect();
+            // Other error statuses usually mean the endpoint is forbidden,
+            // which says nothing about the control plane.
+            if failed.is_empty() && endpoint.status.map(|s| s >= 500).unwrap_or(false) {
+                failing.push(endpoint.endpoint.clone());
+            }
+            failing.extend(failed);
+        }
+        failing.extend(
+            components
+                .iter()
+                .filter(|c| !c.healthy)
+                .map(|c| c.component.clone()),
+        );
+
+        ControlPlaneHealth {
+            healthy: failing.is_empty(),
+            endpoints,
+            components,
+            failing,
+        }
+    }
+}
@@ -0,0 +1,296 @@
+pub mod crd_forms {
+    use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+        CustomResourceDefinition, JSONSchemaProps, JSONSchemaPropsOrArray, JSONSchemaPropsOrBool,
+    };
+    use kube::{Api, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Map, Value};
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    /// Schemas nested deeper than this are left as free-form YAML fields.
+    const MAX_DEPTH: usize = 12;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum FieldType {
+        String,
+        Integer,
+        Number,
+        Boolean,
+        Object,
+        Array,
+        Map,
+        IntOrString,
+        /// Arbitrary content (`x-kubernetes-preserve-unknown-fields` or no
+        /// type), best edited as raw YAML.
+        Any,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct FormField {
+        /// Dotted path from the object root. Array items are written as
+        /// `[]` and map values as `{}`, e.g. `spec.rules[].hosts`.
+        pub path: String,
+        pub name: String,
+        pub depth: usize,
+        pub field_type: FieldType,
+        /// Element type for arrays and value type for maps.
+        pub item_type: Option<FieldType>,
+        pub format: Option<String>,
+        pub description: Option<String>,
+        pub required: bool,
+        pub nullable: bool,
+        pub default: Option<Value>,
+        pub enum_values: Vec<Value>,
+        pub minimum: Option<f64>,
+        pub maximum: Option<f64>,
+        pub min_length: Option<i64>,
+        pub max_length: Option<i64>,
+        pub pattern: Option<String>,
+        /// Keys identifying list items for `x-kubernetes-list-type: map`.
+        pub list_map_keys: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct FormModel {
+        pub group: String,
+        pub version: String,
+        pub kind: String,
+        pub plural: String,
+        pub namespaced: bool,
+        pub versions: Vec<String>,
+        pub fields: Vec<FormField>,
+        /// A starting object with `apiVersion`, `kind`, metadata and the
+        /// defaults of required fields filled in.
+        pub template: Value,
+    }
+
+    fn field_type(schema: &JSONSchemaProps) -> FieldType {
+        if schema.x_kubernetes_int_or_string == Some(true) {
+            return FieldType::IntOrString;
+        }
+        match schema.type_.as_deref() {
+            Some("string") => FieldType::String,
+            Some("integer") => FieldType::Integer,
+            Some("number") => FieldType::Number,
+            Some("boolean") => FieldType::Boolean,
+            Some("array") => FieldType::Array,
+            Some("object") if schema.properties.is_some() => FieldType::Object,
+            Some("object") if map_values(schema).is_some() => FieldType::Map,
+            Some("object") if schema.x_kubernetes_preserve_unknown_fields != Some(true) => {
+                FieldType::Object
+            }
+            _ => FieldType::Any,
+        }
+    }
+
+    fn map_values(schema: &JSONSchemaProps) -> Option<&JSONSchemaProps> {
+        match schema.additional_properties.as_ref()? {
+            JSONSchemaPropsOrBool::Schema(values) => Some(values),
+            JSONSchemaPropsOrBool::Bool(_) => None,
+        }
+    }
+
+    fn array_items(schema: &JSONSchemaProps) -> Option<&JSONSchemaProps> {
+        match schema.items.as_ref()? {
+            JSONSchemaPropsOrArray::Schema(items) => Some(items),
+            JSONSchemaPropsOrArray::Schemas(items) => items.first(),
+        }
+    }
+
+    fn flatten(
+        schema: &JSONSchemaProps,
+        path: &str,
+        name: &str,
+        depth: usize,
+        required: bool,
+        fields: &mut Vec<FormField>,
+    ) {
+        let mut kind = field_type(schema);
+        if depth > MAX_DEPTH && matches!(kind, FieldType::Object | FieldType::Array) {
+            kind = FieldType::Any;
+        }
+        let children = match &kind {
+            FieldType::Array => array_items(schema).map(|items| (items, format!("{}[]", path))),
+            FieldType::Map => map_values(schema).map(|values| (values, format!("{}{{}}", path))),
+            _ => None,
+        };
+        fields.push(FormField {
+            path: path.to_string(),
+            name: name.to_string(),
+            depth,
+            item_type: children.as_ref().map(|(child, _)| field_type(child)),
+            field_type: kind.clone(),
+            format: schema.format.clone(),
+            description: schema.description.clone(),
+            required,
+            nullable: schema.nullable.unwrap_or(false),
+            default: schema.default.as_ref().map(|d| d.0.clone()),
+            enum_values: schema.enum_.iter().flatten().map(|v| v.0.clone()).collect(),
+            minimum: schema.minimum,
+            maximum: schema.maximum,
+            min_length: schema.min_length,
+            max_length: schema.max_length,
+            pattern: schema.pattern.clone(),
+            list_map_keys: schema
+                .x_kubernetes_list_map_keys
+                .clone()
+                .unwrap_or_default(),
+        });
+
+        if kind == FieldType::Object {
+            flatten_properties(schema, path, depth + 1, fields);
+        } else if let Some((child, child_path)) = children {
+            // Only object items get their own fields; other item types are
+            // described by `item_type` on the parent.
+            if field_type(child) == FieldType::Object {
+                flatten_properties(child, child_path.as_str(), depth + 1, fields);
+            }
+        }
+    }
+
+    fn flatten_properties(
+        schema: &JSONSchemaProps,
+        path: &str,
+        depth: usize,
+        fields: &mut Vec<FormField>,
+    ) {
+        let required = schema.required.clone().unwrap_or_default();
+        for (name, property) in schema.properties.iter().flatten() {
+            let child_path = if path.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", path, name)
+            };
+            flatten(
+                property,
+                child_path.as_str(),
+                name.as_str(),
+                depth,
+                required.contains(name),
+                fields,
+            );
+        }
+    }
+
+    /// Builds the required skeleton of an object schema, using defaults
+    /// where the schema provides them.
+    fn skeleton(schema: &JSONSchemaProps) -> Option<Value> {
+        if let Some(default) = &schema.default {
+            return Some(default.0.clone());
+        }
+        let required = schema.required.clone().unwrap_or_default();
+        let mut object = Map::new();
+        for (name, property) in schema.properties.iter().flatten() {
+            if !required.contains(name) {
+                continue;
+            }
+            let value = match field_type(property) {
+                _ if property.default.is_some() => skeleton(property).unwrap_or_default(),
+                FieldType::Object => skeleton(property).unwrap_or(json!({})),
+                FieldType::Array => json!([]),
+                FieldType::Map => json!({}),
+                FieldType::Boolean => json!(false),
+                FieldType::Integer | FieldType::Number => json!(0),
+                _ => property
+                    .enum_
+                    .as_ref()
+                    .and_then(|e| e.first())
+                    .map(|v| v.0.clone())
+                    .unwrap_or(json!("")),
+            };
+            object.insert(name.clone(), value);
+        }
+        (!object.is_empty()).then_some(Value::Object(object))
+    }
+
+    /// Flattens the structural schema of one CRD version (the storage
+    /// version by default) into form fields for guided creation.
+    pub async fn form_model(
+        client: Client,
+        crd: String,
+        version: Option<String>,
+    ) -> Result<FormModel, KubiousError> {
+        let definition = Api::<CustomResourceDefinition>::all(client)
+            .get(crd.as_str())
+            .await
+            .context("Unknown CustomResourceDefinition")?;
+        let spec = definition.spec;
+        let selected = spec
+            .versions
+            .iter()
+            .find(|v| match &version {
+                Some(version) => &v.name == version,
+                None => v.storage,
+            })
+            .ok_or("Unknown version".to_string())?;
+        let schema = selected
+            .schema
+            .as_ref()
+            .and_then(|s| s.open_api_v3_schema.as_ref())
+            .ok_or("This version has no schema".to_string())?;
+        let namespaced = spec.scope == "Namespaced";
+
+        let mut fields: Vec<FormField> = Vec::new();
+        flatten(
+            &JSONSchemaProps {
+                type_: Some("string".to_string()),
+                description: Some("Name of the new object.".to_string()),
+                ..JSONSchemaProps::default()
+            },
+            "metadata.name",
+            "name",
+            0,
+            true,
+            &mut fields,
+        );
+        if namespaced {
+            flatten(
+                &JSONSchemaProps {
+                    type_: Some("string".to_string()),
+                    description: Some("Namespace of the new object.".to_string()),
+                    ..JSONSchemaProps::default()
+                },
+                "metadata.namespace",
+                "namespace",
+                0,
+                false,
+                &mut fields,
+            );
+        }
+        let mut body = schema.clone();
+        if let Some(properties) = body.properties.as_mut() {
+            for reserved in ["apiVersion", "kind", "metadata"] {
+                properties.remove(reserved);
+            }
+        }
+        flatten_properties(&body, "", 0, &mut fields);
+
+        let mut template = json!({
+            "apiVersion": format!("{}/{}", spec.group, selected.name),
+            "kind": spec.names.kind,
+            "metadata": {"name": ""},
+        });
+        if let Some(Value::Object(required)) = skeleton(&body) {
+            for (key, value) in required {
+                template[key] = value;
+            }
+        }
+
+        Ok(FormModel {
+            group: spec.group.clone(),
+            version: selected.name.clone(),
+            kind: spec.names.kind.clone(),
+            plural: spec.names.plural.clone(),
+            namespaced,
+            versions: spec
+                .versions
+                .iter()
+                .filter(|v| v.served)
+                .map(|v| v.name.clone())
+                .collect(),
+            fields,
+            template,
+        })
+    }
+}
@@ -0,0 +1,517 @@
+pub mod pod_effective_config {
+    use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+    use k8s_openapi::{
+        api::{
+            core::v1::{Container, LimitRange, LocalObjectReference, Pod, ServiceAccount},
+            node::v1::RuntimeClass,
+            scheduling::v1::PriorityClass,
+        },
+        apimachinery::pkg::api::resource::Quantity,
+    };
+    use kube::{api::ListParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    const LIMIT_RANGER_ANNOTATION: &str = "kubernetes.io/limit-ranger";
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[serde(tag = "type")]
+    pub enum ValueSource {
+        /// Set in the pod spec itself.
+        Spec,
+        LimitRange {
+            name: String,
+        },
+        ServiceAccount {
+            name: String,
+        },
+        PriorityClass {
+            name: String,
+        },
+        RuntimeClass {
+            name: String,
+        },
+        /// Filled in by API server defaulting.
+        Default {
+            detail: String,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct EffectiveField {
+        /// Path into the pod, e.g. `spec.containers.0.resources.limits.cpu`.
+        pub path: String,
+        pub container: Option<String>,
+        pub value: Value,
+        pub source: ValueSource,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct EffectiveConfig {
+        pub namespace: String,
+        pub pod: Option<String>,
+        /// Whether the pod was read from the cluster, in which case admission
+        /// already applied the defaults and sources are inferred.
+        pub live: bool,
+        pub fields: Vec<EffectiveField>,
+        pub warnings: Vec<String>,
+    }
+
+    /// Per-resource container defaults from the namespace's LimitRanges,
+    /// with the LimitRange each came from. The first LimitRange to set a
+    /// resource wins, as with the LimitRanger admission plugin.
+    #[derive(Default)]
+    struct ContainerDefaults {
+        limits: BTreeMap<String, (Quantity, String)>,
+        requests: BTreeMap<String, (Quantity, String)>,
+    }
+
+    fn container_defaults(ranges: &[LimitRange]) -> ContainerDefaults {
+        let mut defaults = ContainerDefaults::default();
+        for range in ranges {
+            let name = range.metadata.name.clone().unwrap_or_default();
+            for item in range.spec.iter().flat_map(|s| s.limits.iter()) {
+                if item.type_ != "Container" {
+                    continue;
+                }
+                for (resource, quantity) in item.default.iter().flatten() {
+                    defaults
+                        .limits
+                        .entry(resource.clone())
+                        .or_insert((quantity.clone(), name.clone()));
+                }
+                for (resource, quantity) in item.default_request.iter().flatten() {
+                    defaults
+                        .requests
+                        .entry(resource.clone())
+                        .or_insert((quantity.clone(), name.clone()));
+                }
+            }
+        }
+        defaults
+    }
+
+    /// Parses the annotation LimitRanger leaves on pods it changed, e.g.
+    /// `LimitRanger plugin set: cpu, memory request for container app; cpu
+    /// limit for container app`, into `(container, "request" | "limit",
+    /// resource)` entries.
+    fn limit_ranger_changes(pod: &Pod) -> HashSet<(String, String, String)> {
+        let mut changes: HashSet<(String, String, String)> = HashSet::new();
+        let Some(annotation) = pod
+            .metadata
+            .annotations
+            .as_ref()
+            .and_then(|a| a.get(LIMIT_RANGER_ANNOTATION))
+        else {
+            return changes;
+        };
+        let settings = annotation
+            .split_once(':')
+            .map(|(_, rest)| rest)
+            .unwrap_or_default();
+        for setting in settings.split(';') {
+            for kind in ["request", "limit"] {
+                let Some((resources, target)) =
+                    setting.split_once(format!(" {} for ", kind).as_str())
+                else {
+                    continue;
+                };
+                let Some(container) = target.split_whitespace().last() else {
+                    continue;
+                };
+                for resource in resources.split(',') {
+                    changes.insert((
+                        container.to_string(),
+                        kind.to_string(),
+                        resource.trim().to_string(),
+                    ));
+                }
+            }
+        }
+        changes
+    }
+
+    fn container_fields(
+        prefix: &str,
+        containers: &[Container],
+        defaults: &ContainerDefaults,
+        changes: &HashSet<(String, String, String)>,
+        live: bool,
+    ) -> Vec<EffectiveField> {
+        let mut fields: Vec<EffectiveField> = Vec::new();
+        for (index, container) in containers.iter().enumerate() {
+            let resources = container.resources.clone().unwrap_or_default();
+            let limits = resources.limits.unwrap_or_default();
+            let requests = resources.requests.unwrap_or_default();
+            let names: BTreeSet<&String> = limits
+                .keys()
+                .chain(requests.keys())
+                .chain(defaults.limits.keys())
+                .chain(defaults.requests.keys())
+                .collect();
+            let resource_field =
+                |kind: &str, resource: &str, quantity: &Quantity, source: ValueSource| {
+                    EffectiveField {
+                        path: format!("{}.{}.resources.{}.{}", prefix, index, kind, resource),
+                        container: Some(container.name.clone()),
+                        value: json!(quantity.0),
+                        source,
+                    }
+                };
+            let changed = |kind: &str, resource: &str| {
+                changes.contains(&(
+                    container.name.clone(),
+                    kind.to_string(),
+                    resource.to_string(),
+                ))
+            };
+            let from_range = |found: Option<&(Quantity, String)>| ValueSource::LimitRange {
+                name: found.map(|(_, name)| name.clone()).unwrap_or_default(),
+            };
+
+            for resource in names {
+                let default_limit = defaults.limits.get(resource);
+                let limit = match (limits.get(resource), default_limit) {
+                    (Some(quantity), _) if live && changed("limit", resource) => {
+                        Some((quantity.clone(), from_range(default_limit)))
+                    }
+                    (Some(quantity), _) => Some((quantity.clone(), ValueSource::Spec)),
+                    (None, Some((quantity, _))) if !live => {
+                        Some((quantity.clone(), from_range(default_limit)))
+                    }
+                    _ => None,
+                };
+                let default_request = defaults.requests.get(resource);
+                let request = match (requests.get(resource), default_request) {
+                    (Some(quantity), _) if live && changed("request", resource) => {
+                        Some((quantity.clone(), from_range(default_request)))
+                    }
+                    (Some(quantity), _) => Some((quantity.clone(), ValueSource::Spec)),
+                    (None, Some((quantity, _))) if !live => {
+                        Some((quantity.clone(), from_range(default_request)))
+                    }
+                    (None, _) => limit.as_ref().map(|(quantity, _)| {
+                        (
+                            quantity.clone(),
+                            ValueSource::Default {
+                                detail: "Requests default to limits".to_string(),
+                            },
+                        )
+                    }),
+                };
+                if let Some((quantity, source)) = &limit {
+                    fields.push(resource_field("limits", resource, quantity, source.clone()));
+                }
+                if let Some((quantity, source)) = request {
+                    fields.push(resource_field("requests", resource, &quantity, source));
+                }
+            }
+        }
+        fields
+    }
+
+    fn field(path: &str, value: Value, source: ValueSource) -> EffectiveField {
+        EffectiveField {
+            path: path.to_string(),
+            container: None,
+            value,
+            source,
+        }
+    }
+
+    fn secret_names(secrets: Option<&Vec<LocalObjectReference>>) -> Vec<String> {
+        secrets
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.name.clone())
+            .collect()
+    }
+
+    async fn service_account_fields(
+        client: &Client,
+        namespace: &str,
+        pod: &Pod,
+        live: bool,
+        warnings: &mut Vec<String>,
+    ) -> Vec<EffectiveField> {
+        let spec = pod.spec.clone().unwrap_or_default();
+        let mut fields: Vec<EffectiveField> = Vec::new();
+        let (name, source) = match spec
+            .service_account_name
+            .clone()
+            .or(spec.service_account.clone())
+        {
+            Some(name) if name != "default" => (name, ValueSource::Spec),
+            _ => (
+                "default".to_string(),
+                ValueSource::Default {
+                    detail: "Pods without a service account use \"default\"".to_string(),
+                },
+            ),
+        };
+        fields.push(field("spec.serviceAccountName", json!(name), source));
+
+        let account = match Api::<ServiceAccount>::namespaced(client.clone(), namespace)
+            .get(name.as_str())
+            .await
+        {
+            Ok(account) => Some(account),
+            Err(_) => {
+                warnings.push(format!("ServiceAccount {} could not be read", name));
+                None
+            }
+        };
+        let from_account = ValueSource::ServiceAccount { name: name.clone() };
+
+        let (automount, source) = match (
+            spec.automount_service_account_token,
+            account
+                .as_ref()
+                .and_then(|a| a.automount_service_account_token),
+        ) {
+            (Some(automount), _) => (automount, ValueSource::Spec),
+            (None, Some(automount)) => (automount, from_account.clone()),
+            (None, None) => (
+                true,
+                ValueSource::Default {
+                    detail: "Tokens are mounted unless disabled".to_string(),
+                },
+            ),
+        };
+        fields.push(field(
+            "spec.automountServiceAccountToken",
+            json!(automount),
+            source,
+        ));
+
+        let own = secret_names(spec.image_pull_secrets.as_ref());
+        let inherited = secret_names(account.as_ref().and_then(|a| a.image_pull_secrets.as_ref()));
+        // The ServiceAccount's pull secrets are only added to pods that set
+        // none themselves.
+        let secrets = match (own.is_empty(), inherited.is_empty()) {
+            (false, _) if live && own == inherited => Some((own, from_account)),
+            (false, _) => Some((own, ValueSource::Spec)),
+            (true, false) if !live => Some((inherited, from_account)),
+            _ => None,
+        };
+        if let Some((secrets, source)) = secrets {
+            fields.push(field("spec.imagePullSecrets", json!(secrets), source));
+        }
+        fields
+    }
+
+    async fn priority_fields(
+        client: &Client,
+        pod: &Pod,
+        live: bool,
+        warnings: &mut Vec<String>,
+    ) -> Vec<EffectiveField> {
+        let spec = pod.spec.clone().unwrap_or_default();
+        let mut fields: Vec<EffectiveField> = Vec::new();
+        let classes = match Api::<PriorityClass>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+        {
+            Ok(classes) => classes.items,
+            Err(_) => {
+                warnings.push("PriorityClasses could not be listed".to_string());
+                Vec::new()
+            }
+        };
+        let named = |name: &str| {
+            classes
+                .iter()
+                .find(|c| c.metadata.name.as_deref() == Some(name))
+        };
+        let global_default = classes.iter().find(|c| c.global_default == Some(true));
+
+        let class = match spec.priority_class_name.as_deref() {
+            Some(name) => {
+                let class = named(name);
+                if class.is_none() {
+                    warnings.push(format!("PriorityClass {} does not exist", name));
+                }
+                // Admission writes the global default into live pods, so it
+                // cannot be told apart from a class set in the spec.
+                let source = match class {
+                    Some(c) if live && c.global_default == Some(true) => {
+                        ValueSource::PriorityClass {
+                            name: name.to_string(),
+                        }
+                    }
+                    _ => ValueSource::Spec,
+                };
+                fields.push(field("spec.priorityClassName", json!(name), source));
+                class
+            }
+            None if !live => {
+                if let Some(class) = global_default {
+                    let name = class.metadata.name.clone().unwrap_or_default();
+                    fields.push(field(
+                        "spec.priorityClassName",
+                        json!(name),
+                        ValueSource::PriorityClass { name: name.clone() },
+                    ));
+                }
+                global_default
+            }
+            None => None,
+        };
+
+        match class {
+            Some(class) => {
+                let from_class = ValueSource::PriorityClass {
+                    name: class.metadata.name.clone().unwrap_or_default(),
+                };
+                fields.push(field(
+                    "spec.priority",
+                    json!(class.value),
+                    from_class.clone(),
+                ));
+                let (policy, source) = match &class.preemption_policy {
+                    Some(policy) => (policy.clone(), from_class),
+                    None => (
+                        "PreemptLowerPriority".to_string(),
+                        ValueSource::Default {
+                            detail: "Default preemption policy".to_string(),
+                        },
+                    ),
+                };
+                fields.push(field("spec.preemptionPolicy", json!(policy), source));
+            }
+            None => fields.push(field(
+                "spec.priority",
+                json!(spec.priority.unwrap_or(0)),
+                ValueSource::Default {
+                    detail: "No priority class applies".to_string(),
+                },
+            )),
+        }
+        fields
+    }
+
+    async fn runtime_class_fields(
+        client: &Client,
+        pod: &Pod,
+        warnings: &mut Vec<String>,
+    ) -> Vec<EffectiveField> {
+        let mut fields: Vec<EffectiveField> = Vec::new();
+        let Some(name) = pod.spec.as_ref().and_then(|s| s.runtime_class_name.clone()) else {
+            return fields;
+        };
+        fields.push(field(
+            "spec.runtimeClassName",
+            json!(name),
+            ValueSource::Spec,
+        ));
+        let Ok(class) = Api::<RuntimeClass>::all(client.clone())
+            .get(name.as_str())
+            .await
+        else {
+            warnings.push(format!("RuntimeClass {} could not be read", name));
+            return fields;
+        };
+        let source = ValueSource::RuntimeClass { name: name.clone() };
+        fields.push(field(
+            "runtimeClass.handler",
+            json!(class.handler),
+            source.clone(),
+        ));
+        for (resource, quantity) in class.overhead.and_then(|o| o.pod_fixed).unwrap_or_default() {
+            fields.push(field(
+                format!("spec.overhead.{}", resource).as_str(),
+                json!(quantity.0),
+                source.clone(),
+            ));
+        }
+        if let Some(scheduling) = class.scheduling {
+            for (key, value) in scheduling.node_selector.unwrap_or_default() {
+                fields.push(field(
+                    format!("spec.nodeSelector.{}", key).as_str(),
+                    json!(value),
+                    source.clone(),
+                ));
+            }
+            if let Some(tolerations) = scheduling.tolerations.filter(|t| !t.is_empty()) {
+                fields.push(field("spec.tolerations", json!(tolerations), source));
+            }
+        }
+        fields
+    }
+
+    /// Computes the configuration a pod runs (or would run) with once
+    /// admission has applied LimitRange defaults, the ServiceAccount's
+    /// token and pull secrets, its priority class and its runtime class,
+    /// with where each value came from. Pass either the name of an existing
+    /// pod or a Pod manifest that has not been created yet.
+    pub async fn effective_config(
+        client: Client,
+        namespace: String,
+        pod: Option<String>,
+        manifest: Option<Value>,
+    ) -> Result<EffectiveConfig, KubiousError> {
+        let (object, live) = match (&pod, manifest) {
+            (Some(name), None) => (
+                Api::<Pod>::namespaced(client.clone(), namespace.as_str())
+                    .get(name.as_str())
+                    .await
+                    .context(format!("Failed to get pod {}", name))?,
+                true,
+            ),
+            (None, Some(manifest)) => (
+                serde_json::from_value::<Pod>(manifest)
+                    .map_err(|e| format!("Invalid pod manifest: {}", e))?,
+                false,
+            ),
+            _ => return Err("Exactly one of pod or manifest is required".into()),
+        };
+        let Some(spec) = object.spec.clone() else {
+            return Err("Pod has no spec".into());
+        };
+
+        let mut warnings: Vec<String> = Vec::new();
+        let ranges = match Api::<LimitRange>::namespaced(client.clone(), namespace.as_str())
+            .list(&ListParams::default())
+            .await
+        {
+            Ok(ranges) => ranges.items,
+            Err(_) => {
+                warnings.push("LimitRanges could not be listed".to_string());
+                Vec::new()
+            }
+        };
+        let defaults = container_defaults(&ranges);
+        let changes = limit_ranger_changes(&object);
+
+        let mut fields = container_fields(
+            "spec.initContainers",
+            &spec.init_containers.unwrap_or_default(),
+            &defaults,
+            &changes,
+            live,
+        );
+        fields.extend(container_fields(
+            "spec.containers",
+            &spec.containers,
+            &defaults,
+            &changes,
+            live,
+        ));
+        fields.extend(
+            service_account_fields(&client, namespace.as_str(), &object, live, &mut warnings).await,
+        );
+        fields.extend(priority_fields(&client, &object, live, &mut warnings).await);
+        fields.extend(runtime_class_fields(&client, &object, &mut warnings).await);
+
+        Ok(EffectiveConfig {
+            namespace,
+            pod: pod.or(object.metadata.name.clone()),
+            live,
+            fields,
+            warnings,
+        })
+    }
+}
@@ -0,0 +1,514 @@
+pub mod removal_simulation {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use k8s_openapi::{
+        api::core::v1::{
+            Node, NodeSelectorRequirement, NodeSelectorTerm, Pod, PodAffinityTerm, Taint,
+            Toleration,
+        },
+        apimachinery::pkg::apis::meta::v1::LabelSelector,
+    };
+    use kube::{api::ListParams, Api, Client, ResourceExt};
+    use serde::{Deserialize, Serialize};
+
+    use crate::util::{
+        kube_quantity::quantity_value,
+        kubious_error::{ErrorContext, KubiousError},
+        resource_accounting::{pod_is_active, pod_requests, workload_of},
+    };
+
+    const MIRROR_ANNOTATION: &str = "kubernetes.io/config.mirror";
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[serde(tag = "outcome")]
+    pub enum PodOutcome {
+        /// The first node in simulated scheduling order that fits the pod.
+        Rescheduled { node: String },
+        /// No remaining node fits. `reasons` counts nodes per reason, as the
+        /// scheduler reports it.
+        Pending { reasons: BTreeMap<String, usize> },
+        /// Goes away with the node: DaemonSet, static and unmanaged pods.
+        NotRecreated { reason: String },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DisplacedPod {
+        pub namespace: String,
+        pub name: String,
+        pub workload: Option<String>,
+        pub from_node: String,
+        pub priority: i32,
+        pub cpu_request: f64,
+        pub memory_request: f64,
+        #[serde(flatten)]
+        pub outcome: PodOutcome,
+    }
+
+    /// Requested share of allocatable on a remaining node, from 0 to 1,
+    /// before and after the displaced pods land.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NodePressure {
+        pub node: String,
+        pub pods_added: usize,
+        pub cpu_before: f64,
+        pub cpu_after: f64,
+        pub memory_before: f64,
+        pub memory_after: f64,
+        pub pods_before: f64,
+        pub pods_after: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RemovalSimulation {
+        pub removed_nodes: Vec<String>,
+        pub displaced: usize,
+        pub rescheduled: usize,
+        pub pending: usize,
+        pub not_recreated: usize,
+        pub pods: Vec<DisplacedPod>,
+        /// Remaining schedulable nodes, busiest after the move first.
+        pub nodes: Vec<NodePressure>,
+        /// What the simulation leaves out, e.g. volume topology.
+        pub caveats: Vec<String>,
+    }
+
+    /// A remaining node as the simulation fills it.
+    struct Candidate {
+        name: String,
+        labels: BTreeMap<String, String>,
+        taints: Vec<Taint>,
+        allocatable: BTreeMap<String, f64>,
+        requested: BTreeMap<String, f64>,
+        before: BTreeMap<String, f64>,
+        /// Namespace and labels of the pods on the node, for pod affinity.
+        pods: Vec<(String, BTreeMap<String, String>)>,
+        added: usize,
+    }
+
+    impl Candidate {
+        fn ratio(
+            values: &BTreeMap<String, f64>,
+            allocatable: &BTreeMap<String, f64>,
+            resource: &str,
+        ) -> f64 {
+            let total = allocatable.get(resource).copied().unwrap_or(0.0);
+            if total > 0.0 {
+                values.get(resource).copied().unwrap_or(0.0) / total
+            } else {
+                0.0
+            }
+        }
+
+        /// Resources the pod asks for that the node has too little left of.
+        fn shortfall(&self, requests: &BTreeMap<String, f64>) -> Option<String> {
+            requests
+                .iter()
+                .chain([(&"pods".to_string(), &1.0)])
+                .filter(|(_, amount)| **amount > 0.0)
+                .find(|(resource, amount)| {
+                    let free = self.allocatable.get(*resource).copied().unwrap_or(0.0)
+                        - self.requested.get(*resource).copied().unwrap_or(0.0);
+                    **amount > free
+                })
+                .map(|(resource, _)| format!("Insufficient {}", resource))
+        }
+
+        /// The scheduler's least-allocated score: the emptier, the better.
+        fn free_share(&self) -> f64 {
+            let used = Self::ratio(&self.requested, &self.allocatable, "cpu").max(Self::ratio(
+                &self.requested,
+                &self.allocatable,
+                "memory",
+            ));
+            1.0 - used
+        }
+
+        fn place(
+            &mut self,
+            requests: &BTreeMap<String, f64>,
+            namespace: &str,
+            labels: &BTreeMap<String, String>,
+        ) {
+            for (resource, amount) in requests {
+                *self.requested.entry(resource.clone()).or_insert(0.0) += amount;
+            }
+            *self.requested.entry("pods".to_string()).or_insert(0.0) += 1.0;
+            self.pods.push((namespace.to_string(), labels.clone()));
+            self.added += 1;
+        }
+    }
+
+    fn selector_matches(
+        selector: &Option<LabelSelector>,
+        labels: &BTreeMap<String, String>,
+    ) -> bool {
+        let Some(selector) = selector else {
+            return false;
+        };
+        let labels_match = selector
+            .match_labels
+            .as_ref()
+            .is_none_or(|wanted| wanted.iter().all(|(k, v)| labels.get(k) == Some(v)));
+        let expressions_match = selector
+            .match_expressions
+            .as_ref()
+            .is_none_or(|expressions| {
+                expressions.iter().all(|e| {
+                    let values = e.values.clone().unwrap_or_default();
+                    match e.operator.as_str() {
+                        "In" => labels.get(&e.key).is_some_and(|v| values.contains(v)),
+                        "NotIn" => labels.get(&e.key).is_none_or(|v| !values.contains(v)),
+                        "Exists" => labels.contains_key(&e.key),
+                        "DoesNotExist" => !labels.contains_key(&e.key),
+                        _ => false,
+                    }
+                })
+            });
+        labels_match && expressions_match
+    }
+
+    fn requirement_matches(requirement: &NodeSelectorRequirement, value: Option<&String>) -> bool {
+        let values = requirement.values.clone().unwrap_or_default();
+        let number = |v: &String| v.parse::<i64>().ok();
+        match requirement.operator.as_str() {
+            "In" => value.is_some_and(|v| values.contains(v)),
+            "NotIn" => value.is_none_or(|v| !values.contains(v)),
+            "Exists" => value.is_some(),
+            "DoesNotExist" => value.is_none(),
+            "Gt" => value
+                .and_then(number)
+                .zip(values.first().and_then(number))
+                .is_some_and(|(v, l)| v > l),
+            "Lt" => value
+                .and_then(number)
+                .zip(values.first().and_then(number))
+                .is_some_and(|(v, l)| v < l),
+            _ => false,
+        }
+    }
+
+    fn term_matches(term: &NodeSelectorTerm, node: &Candidate) -> bool {
+        let empty = term.match_expressions.as_ref().is_none_or(|e| e.is_empty())
+            && term.match_fields.as_ref().is_none_or(|f| f.is_empty());
+        !empty
+            && term
+                .match_expressions
+                .iter()
+                .flatten()
+                .all(|r| requirement_matches(r, node.labels.get(&r.key)))
+            && term
+                .match_fields
+                .iter()
+                .flatten()
+                .all(|r| r.key == "metadata.name" && requirement_matches(r, Some(&node.name)))
+    }
+
+    fn tolerates(tolerations: &[Toleration], taint: &Taint) -> bool {
+        tolerations.iter().any(|t| {
+            let effect = t
+                .effect
+                .as_deref()
+                .is_none_or(|e| e.is_empty() || e == taint.effect);
+            let key = t
+                .key
+                .as_deref()
+                .is_none_or(|k| k.is_empty() || k == taint.key);
+            let value = match t.operator.as_deref() {
+                Some("Exists") => true,
+                _ => {
+                    t.value.as_deref().unwrap_or_default()
+                        == taint.value.as_deref().unwrap_or_default()
+                }
+            };
+            effect && key && value
+        })
+    }
+
+    /// Whether any pod matching the term sits in the same topology domain
+    /// as `node`.
+    fn domain_has_match(
+        term: &PodAffinityTerm,
+        namespace: &str,
+        node: &Candidate,
+        nodes: &[Candidate],
+    ) -> bool {
+        let Some(domain) = node.labels.get(&term.topology_key) else {
+            return false;
+        };
+        let namespaces: Vec<String> = term
+            .namespaces
+            .clone()
+            .filter(|n| !n.is_empty())
+            .unwrap_or(vec![namespace.to_string()]);
+        let any_namespace = term.namespace_selector.is_some();
+        nodes
+            .iter()
+            .filter(|other| other.labels.get(&term.topology_key) == Some(domain))
+            .flat_map(|other| other.pods.iter())
+            .any(|(ns, labels)| {
+                (any_namespace || namespaces.contains(ns))
+                    && selector_matches(&term.label_selector, labels)
+            })
+    }
+
+    /// Why the pod cannot go to `nodes[index]`, if it cannot.
+    fn unfit(
+        pod: &Pod,
+        requests: &BTreeMap<String, f64>,
+        index: usize,
+        nodes: &[Candidate],
+    ) -> Option<String> {
+        let node = &nodes[index];
+        let spec = pod.spec.clone().unwrap_or_default();
+        let namespace = pod.namespace().unwrap_or_default();
+        let tolerations = spec.tolerations.clone().unwrap_or_default();
+
+        if let Some(taint) = node
+            .taints
+            .iter()
+            .filter(|t| t.effect == "NoSchedule" || t.effect == "NoExecute")
+            .find(|t| !tolerates(&tolerations, t))
+        {
+            return Some(format!("Untolerated taint {}", taint.key));
+        }
+        let selector = spec.node_selector.clone().unwrap_or_default();
+        if selector.iter().any(|(k, v)| node.labels.get(k) != Some(v)) {
+            return Some("Node selector mismatch".to_string());
+        }
+        let affinity = spec.affinity.clone().unwrap_or_default();
+        let required_terms = affinity
+            .node_affinity
+            .and_then(|a| a.required_during_scheduling_ignored_during_execution)
+            .map(|r| r.node_selector_terms);
+        if required_terms.is_some_and(|terms| !terms.iter().any(|t| term_matches(t, node))) {
+            return Some("Node affinity mismatch".to_string());
+        }
+        let affinity_terms = affinity
+            .pod_affinity
+            .and_then(|a| a.required_during_scheduling_ignored_during_execution)
+            .unwrap_or_default();
+        if affinity_terms
+            .iter()
+            .any(|t| !domain_has_match(t, namespace.as_str(), node, nodes))
+        {
+            return Some("Pod affinity not satisfied".to_string());
+        }
+        let anti_affinity_terms = affinity
+            .pod_anti_affinity
+            .and_then(|a| a.required_during_scheduling_ignored_during_execution)
+            .unwrap_or_default();
+        if anti_affinity_terms
+            .iter()
+            .any(|t| domain_has_match(t, namespace.as_str(), node, nodes))
+        {
+            return Some("Pod anti-affinity conflict".to_string());
+        }
+        node.shortfall(requests)
+    }
+
+    fn not_recreated(pod: &Pod) -> Option<String> {
+        if pod.annotations().contains_key(MIRROR_ANNOTATION) {
+            return Some("Static pod of the removed node".to_string());
+        }
+        let controller = pod
+            .owner_references()
+            .iter()
+            .find(|o| o.controller == Some(true));
+        match controller {
+            Some(c) if c.kind == "DaemonSet" => {
+                Some("DaemonSet pod of the removed node".to_string())
+            }
+            None => Some("Not managed by a controller".to_string()),
+            _ => None,
+        }
+    }
+
+    fn ready(node: &Node) -> bool {
+        node.status
+            .as_ref()
+            .and_then(|s| s.conditions.as_ref())
+            .is_some_and(|c| c.iter().any(|c| c.type_ == "Ready" && c.status == "True"))
+    }
+
+    /// Simulates removing `removed` nodes, as for maintenance or a spot
+    /// interruption: their pods are placed on the remaining ready,
+    /// schedulable nodes by requests, node selectors, required node and pod
+    /// (anti-)affinity and taints, highest priority and largest first.
+    /// Nothing in the cluster is changed.
+    pub async fn simulate(
+        client: Client,
+        removed: Vec<String>,
+    ) -> Result<RemovalSimulation, KubiousError> {
+        if removed.is_empty() {
+            return Err("No nodes to remove".into());
+        }
+        let nodes = Api::<Node>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list nodes.")?;
+        let pods = Api::<Pod>::all(client)
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list pods.")?;
+        let removed_set: BTreeSet<&String> = removed.iter().collect();
+        let known: BTreeSet<String> = nodes.items.iter().map(|n| n.name_any()).collect();
+        if let Some(missing) = removed.iter().find(|n| !known.contains(*n)) {
+            return Err(format!("Node {} does not exist", missing).into());
+        }
+
+        let mut candidates: Vec<Candidate> = nodes
+            .items
+            .iter()
+            .filter(|n| !removed_set.contains(&n.name_any()))
+            .filter(|n| {
+                ready(n)
+                    && !n
+                        .spec
+                        .as_ref()
+                        .and_then(|s| s.unschedulable)
+                        .unwrap_or(false)
+            })
+            .map(|n| Candidate {
+                name: n.name_any(),
+                labels: n.labels().clone(),
+                taints: n
+                    .spec
+                    .as_ref()
+                    .and_then(|s| s.taints.clone())
+                    .unwrap_or_default(),
+                allocatable: n
+                    .status
+                    .as_ref()
+                    .and_then(|s| s.allocatable.as_ref())
+                    .map(|a| {
+                        a.iter()
+                            .map(|(k, v)| (k.clone(), quantity_value(v)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                requested: BTreeMap::new(),
+                before: BTreeMap::new(),
+                pods: Vec::new(),
+                added: 0,
+            })
+            .collect();
+
+        let mut displaced: Vec<&Pod> = Vec::new();
+        for pod in pods.items.iter().filter(|p| pod_is_active(p)) {
+            let Some(node) = pod.spec.as_ref().and_then(|s| s.node_name.as_ref()) else {
+                continue;
+            };
+            if removed_set.contains(node) {
+                displaced.push(pod);
+            } else if let Some(candidate) = candidates.iter_mut().find(|c| &c.name == node) {
+                candidate.place(
+                    &pod_requests(pod),
+                    pod.namespace().unwrap_or_default().as_str(),
+                    pod.labels(),
+                );
+                candidate.added = 0;
+            }
+        }
+        for candidate in candidates.iter_mut() {
+            candidate.before = candidate.requested.clone();
+        }
+
+        let priority = |pod: &Pod| pod.spec.as_ref().and_then(|s| s.priority).unwrap_or(0);
+        let size = |pod: &Pod| {
+            let requests = pod_requests(pod);
+            (
+                requests.get("cpu").copied().unwrap_or(0.0),
+                requests.get("memory").copied().unwrap_or(0.0),
+            )
+        };
+        displaced.sort_by(|a, b| {
+            priority(b)
+                .cmp(&priority(a))
+                .then(size(b).0.total_cmp(&size(a).0))
+                .then(size(b).1.total_cmp(&size(a).1))
+        });
+
+        let mut results: Vec<DisplacedPod> = Vec::new();
+        for pod in displaced {
+            let requests = pod_requests(pod);
+            let outcome = if let Some(reason) = not_recreated(pod) {
+                PodOutcome::NotRecreated { reason }
+            } else {
+                let mut reasons: BTreeMap<String, usize> = BTreeMap::new();
+                let mut best: Option<usize> = None;
+                for index in 0..candidates.len() {
+                    match unfit(pod, &requests, index, &candidates) {
+                        Some(reason) => *reasons.entry(reason).or_insert(0) += 1,
+                        None => {
+                            if best.is_none_or(|b| {
+                                candidates[index].free_share() > candidates[b].free_share()
+                            }) {
+                                best = Some(index);
+                            }
+                        }
+                    }
+                }
+                match best {
+                    Some(index) => {
+                        let namespace = pod.namespace().unwrap_or_default();
+                        candidates[index].place(&requests, namespace.as_str(), pod.labels());
+                        PodOutcome::Rescheduled {
+                            node: candidates[index].name.clone(),
+                        }
+                    }
+                    None => PodOutcome::Pending { reasons },
+                }
+            };
+            results.push(DisplacedPod {
+                namespace: pod.namespace().unwrap_or_default(),
+                name: pod.name_any(),
+                workload: workload_of(pod),
+                from_node: pod
+                    .spec
+                    .as_ref()
+                    .and_then(|s| s.node_name.clone())
+                    .unwrap_or_default(),
+                priority: priority(pod),
+                cpu_request: requests.get("cpu").copied().unwrap_or(0.0),
+                memory_request: requests.get("memory").copied().unwrap_or(0.0),
+                outcome,
+            });
+        }
+
+        let mut pressure: Vec<NodePressure> = candidates
+            .iter()
+            .map(|c| NodePressure {
+                node: c.name.clone(),
+                pods_added: c.added,
+                cpu_before: Candidate::ratio(&c.before, &c.allocatable, "cpu"),
+                cpu_after: Candidate::ratio(&c.requested, &c.allocatable, "cpu"),
+                memory_before: Candidate::ratio(&c.before, &c.allocatable, "memory"),
+                memory_after: Candidate::ratio(&c.requested, &c.allocatable, "memory"),
+                pods_before: Candidate::ratio(&c.before, &c.allocatable, "pods"),
+                pods_after: Candidate::ratio(&c.requested, &c.allocatable, "pods"),
+            })
+            .collect();
+        pressure.sort_by(|a, b| {
+            b.cpu_after
+                .max(b.memory_after)
+                .total_cmp(&a.cpu_after.max(a.memory_after))
+        });
+
+        let count = |f: fn(&PodOutcome) -> bool| results.iter().filter(|p| f(&p.outcome)).count();
+        Ok(RemovalSimulation {
+            removed_nodes: removed,
+            displaced: results.len(),
+            rescheduled: count(|o| matches!(o, PodOutcome::Rescheduled { .. })),
+            pending: count(|o| matches!(o, PodOutcome::Pending { .. })),
+            not_recreated: count(|o| matches!(o, PodOutcome::NotRecreated { .. })),
+            pods: results,
+            nodes: pressure,
+            caveats: vec![
+                "Volume topology, such as zonal or local persistent volumes, is not checked.".to_string(),
+                "Preemption of lower-priority pods and topology spread constraints are not simulated.".to_string(),
+                "Pods with a namespace selector in a pod affinity term are matched across all namespaces."
+                    .to_string(),
+            ],
+        })
+    }
+}
@@ -0,0 +1,113 @@
+pub mod extended_resources {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::api::core::v1::{Node, Pod};
+    use kube::{api::ListParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+
+    use crate::util::{
+        kube_quantity::quantity_value,
+        kubious_error::{ErrorContext, KubiousError},
+        resource_accounting::{controller_of, is_extended_resource, pod_is_active, pod_requests},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NodeExtendedResource {
+        pub resource: String,
+        pub capacity: f64,
+        pub allocatable: f64,
+        pub requested: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NodeExtendedResources {
+        pub node: String,
+        pub resources: Vec<NodeExtendedResource>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PodExtendedResource {
+        pub pod: String,
+        pub namespace: String,
+        pub node: Option<String>,
+        pub workload: Option<String>,
+        pub resource: String,
+        pub requested: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ExtendedResourceReport {
+        pub nodes: Vec<NodeExtendedResources>,
+        pub pods: Vec<PodExtendedResource>,
+    }
+
+    pub async fn report(client: Client) -> Result<ExtendedResourceReport, KubiousError> {
+        let nodes = Api::<Node>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list nodes.")?;
+        let pods = Api::<Pod>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list pods.")?;
+
+        let mut consumers: Vec<PodExtendedResource> = Vec::new();
+        let mut requested: BTreeMap<(String, String), f64> = BTreeMap::new();
+        for pod in pods.items.iter().filter(|p| pod_is_active(p)) {
+            let node = pod.spec.as_ref().and_then(|s| s.node_name.clone());
+            for (resource, value) in pod_requests(pod) {
+                if !is_extended_resource(resource.as_str()) || value <= 0.0 {
+                    continue;
+                }
+                if let Some(node) = &node {
+                    *requested
+                        .entry((node.clone(), resource.clone()))
+                        .or_insert(0.0) += value;
+                }
+                consumers.push(PodExtendedResource {
+                    pod: pod.metadata.name.clone().unwrap_or_default(),
+                    namespace: pod.metadata.namespace.clone().unwrap_or_default(),
+                    node: node.clone(),
+                    workload: controller_of(&pod.metadata.owner_references),
+                    resource,
+                    requested: value,
+                });
+            }
+        }
+
+        let mut node_reports: Vec<NodeExtendedResources> = Vec::new();
+        for node in nodes.items {
+            let name = node.metadata.name.clone().unwrap_or_default();
+            let status = node.status.unwrap_or_default();
+            let capacity = status.capacity.unwrap_or_default();
+            let allocatable = status.allocatable.unwrap_or_default();
+            let mut resources: Vec<NodeExtendedResource> = capacity
+                .iter()
+                .filter(|(resource, _)| is_extended_resource(resource.as_str()))
+                .map(|(resource, quantity)| NodeExtendedResource {
+                    resource: resource.clone(),
+                    capacity: quantity_value(quantity),
+                    allocatable: allocatable.get(resource).map(quantity_value).unwrap_or(0.0),
+                    requested: requested
+                        .get(&(name.clone(), resource.clone()))
+                        .copied()
+                        .unwrap_or(0.0),
+                })
+                .filter(|r| r.capacity > 0.0 || r.requested > 0.0)
+                .collect();
+            if resources.is_empty() {
+                continue;
+            }
+            resources.sort_by(|a, b| a.resource.cmp(&b.resource));
+            node_reports.push(NodeExtendedResources {
+                node: name,
+                resources,
+            });
+        }
+
+        Ok(ExtendedResourceReport {
+            nodes: node_reports,
+            pods: consumers,
+        })
+    }
+}
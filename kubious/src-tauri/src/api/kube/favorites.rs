@@ -0,0 +1,235 @@
+pub mod favorite_watches {
+    use std::{
+        collections::HashMap,
+        sync::{Mutex, MutexGuard},
+    };
+
+    use futures::StreamExt;
+    use kube::{
+        api::{DynamicObject, GroupVersionKind},
+        discovery::pinned_kind,
+        runtime::watcher::{self, watcher, Event},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::{async_runtime::JoinHandle, AppHandle, Emitter, Manager};
+
+    use crate::{
+        api::{app_settings::Favorite, app_state::AppState},
+        util::{
+            lock_recovery::recover,
+            resource_health::{parse_health, HealthState},
+            stream_reconnect::{BackoffPolicy, Reconnector},
+        },
+    };
+
+    /// Event channel carrying a `FavoriteStatus` whenever a favorite's
+    /// badge would change.
+    pub const FAVORITE_STATUS_EVENT: &str = "favorite-status";
+
+    /// The compact status shown on a favorite's sidebar badge.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct FavoriteStatus {
+        pub id: String,
+        pub exists: bool,
+        pub health: HealthState,
+        pub reason: Option<String>,
+        pub ready: Option<i64>,
+        pub desired: Option<i64>,
+    }
+
+    /// One watch task per favorite plus the last status sent for each, so
+    /// events are only emitted on change.
+    #[derive(Default)]
+    pub struct FavoriteWatches {
+        tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+        statuses: Mutex<HashMap<String, FavoriteStatus>>,
+    }
+
+    impl FavoriteWatches {
+        fn tasks_mutable(&self) -> MutexGuard<'_, HashMap<String, JoinHandle<()>>> {
+            recover(&self.tasks)
+        }
+
+        fn statuses_mutable(&self) -> MutexGuard<'_, HashMap<String, FavoriteStatus>> {
+            recover(&self.statuses)
+        }
+
+        pub fn statuses(&self) -> Vec<FavoriteStatus> {
+            let mut statuses: Vec<FavoriteStatus> =
+                self.statuses_mutable().values().cloned().collect();
+            statuses.sort_by(|a, b| a.id.cmp(&b.id));
+            statuses
+        }
+
+        /// Stores the status and reports whether it differs from the last one.
+        fn update(&self, status: FavoriteStatus) -> bool {
+            let mut statuses = self.statuses_mutable();
+            if statuses.get(&status.id) == Some(&status) {
+                return false;
+            }
+            statuses.insert(status.id.clone(), status);
+            true
+        }
+    }
+
+    /// Ready and desired counts for the kinds that have them: replicas for
+    /// workloads, scheduled pods for DaemonSets and containers for Pods.
+    fn replica_counts(item: &Value) -> (Option<i64>, Option<i64>) {
+        let status = &item["status"];
+        if let Some(desired) = status["desiredNumberScheduled"].as_i64() {
+            return (
+                Some(status["numberReady"].as_i64().unwrap_or(0)),
+                Some(desired),
+            );
+        }
+        if let Some(desired) = item["spec"]["replicas"].as_i64() {
+            return (
+                Some(status["readyReplicas"].as_i64().unwrap_or(0)),
+                Some(desired),
+            );
+        }
+        if let Some(containers) = status["containerStatuses"].as_array() {
+            let ready = containers.iter().filter(|c| c["ready"] == true).count();
+            return (Some(ready as i64), Some(containers.len() as i64));
+        }
+        (None, None)
+    }
+
+    fn status_of(id: &str, item: Option<&Value>) -> FavoriteStatus {
+        let Some(item) = item else {
+            return FavoriteStatus {
+                id: id.to_string(),
+                exists: false,
+                health: HealthState::Unknown,
+                reason: Some("NotFound".to_string()),
+                ready: None,
+                desired: None,
+            };
+        };
+        let badge = parse_health(item);
+        let (ready, desired) = replica_counts(item);
+        FavoriteStatus {
+            id: id.to_string(),
+            exists: true,
+            health: badge.state,
+            reason: badge.reason,
+            ready,
+            desired,
+        }
+    }
+
+    fn publish(handle: &AppHandle, status: FavoriteStatus) {
+        if handle.state::<FavoriteWatches>().update(status.clone()) {
+            let _ = handle.emit(FAVORITE_STATUS_EVENT, status);
+        }
+    }
+
+    /// Watches the single object with a name field selector, so each
+    /// favorite costs one narrow watch rather than a full list.
+    async fn watch(handle: AppHandle, client: Client, favorite: Favorite) {
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "favorites",
+            favorite.id.as_str(),
+            BackoffPolicy::default(),
+        );
+        let gvk = GroupVersionKind::gvk(
+            favorite.group.as_str(),
+            favorite.version.as_str(),
+            favorite.kind.as_str(),
+        );
+        let resource = loop {
+            match pinned_kind(&client, &gvk).await {
+                Ok((resource, _)) => break resource,
+                Err(error) => {
+                    if !reconnector.retry(error.to_string().as_str()).await {
+                        return;
+                    }
+                }
+            }
+        };
+        let api: Api<DynamicObject> = match &favorite.namespace {
+            Some(ns) => Api::namespaced_with(client, ns.as_str(), &resource),
+            None => Api::all_with(client, &resource),
+        };
+        let config =
+            watcher::Config::default().fields(format!("metadata.name={}", favorite.name).as_str());
+        let mut found = false;
+        let mut stream = watcher(api, config).boxed();
+        while let Some(event) = stream.next().await {
+            match event {
+                Ok(Event::Apply(object)) | Ok(Event::InitApply(object)) => {
+                    found = true;
+                    let item = serde_json::to_value(&object).ok();
+                    publish(&handle, status_of(favorite.id.as_str(), item.as_ref()));
+                }
+                Ok(Event::Delete(_)) => {
+                    found = false;
+                    publish(&handle, status_of(favorite.id.as_str(), None));
+                }
+                Ok(Event::Init) => found = false,
+                Ok(Event::InitDone) => {
+                    if !found {
+                        publish(&handle, status_of(favorite.id.as_str(), None));
+                    }
+                    reconnector.connected();
+                }
+                Err(error) => {
+                    if !reconnector.retry(error.to_string().as_str()).await {
+                        return;
+                    }
+                }
+            }
+        }
+        reconnector.closed();
+    }
+
+    /// Starts watches for favorites that have none and stops the watches of
+    /// favorites that were removed. Favorites on unreachable clusters are
+    /// retried on the next sync.
+    pub async fn sync(handle: AppHandle) {
+        let state = handle.state::<AppState>();
+        let watches = handle.state::<FavoriteWatches>();
+        let favorites = state.get_settings().favorites;
+
+        let stale: Vec<String> = watches
+            .tasks_mutable()
+            .keys()
+            .filter(|id| !favorites.iter().any(|f| f.id == **id))
+            .cloned()
+            .collect();
+        for id in stale {
+            if let Some(task) = watches.tasks_mutable().remove(&id) {
+                task.abort();
+            }
+            watches.statuses_mutable().remove(&id);
+        }
+
+        for favorite in favorites {
+            if watches.tasks_mutable().contains_key(&favorite.id) {
+                continue;
+            }
+            let Some(client) = state.client_for(favorite.cluster.as_str()).await else {
+                continue;
+            };
+            let id = favorite.id.clone();
+            let watch_handle = handle.clone();
+            let mut tasks = watches.tasks_mutable();
+            if tasks.contains_key(&id) {
+                continue;
+            }
+            let task = tauri::async_runtime::spawn(async move {
+                let id = favorite.id.clone();
+                watch(watch_handle.clone(), client, favorite).await;
+                // Forget finished watches so the next sync restarts them.
+                watch_handle
+                    .state::<FavoriteWatches>()
+                    .tasks_mutable()
+                    .remove(&id);
+            });
+            tasks.insert(id, task);
+        }
+    }
+}
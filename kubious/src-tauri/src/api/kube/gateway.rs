@@ -0,0 +1,340 @@
+pub mod gateway_routes {
+    use std::collections::{BTreeMap, HashSet};
+
+    use k8s_openapi::api::core::v1::Service;
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    const GATEWAY_GROUP: &str = "gateway.networking.k8s.io";
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ParentStatus {
+        /// `namespace/name` of the Gateway, with `/section` if the route
+        /// attaches to one listener.
+        pub parent: String,
+        pub gateway_exists: bool,
+        /// `None` when no controller has reported on this parent.
+        pub accepted: Option<bool>,
+        pub resolved_refs: Option<bool>,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RouteReport {
+        pub namespace: String,
+        pub name: String,
+        pub hostnames: Vec<String>,
+        pub parents: Vec<ParentStatus>,
+        /// Backend Services that do not exist, as `namespace/name`.
+        pub missing_backends: Vec<String>,
+        pub healthy: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RouteConflict {
+        pub parent: String,
+        pub hostname: String,
+        /// The match shared by the routes, e.g. `PathPrefix /api GET`.
+        pub matched: String,
+        /// `namespace/name` of the routes, oldest first. Gateway API gives
+        /// the oldest route precedence, so the first one wins.
+        pub routes: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct GatewayRouteReport {
+        pub installed: bool,
+        pub routes: Vec<RouteReport>,
+        pub conflicts: Vec<RouteConflict>,
+    }
+
+    async fn gateway_api(
+        client: &Client,
+        kind: &str,
+        namespace: Option<&str>,
+    ) -> Option<Api<DynamicObject>> {
+        for version in ["v1", "v1beta1"] {
+            let gvk = GroupVersionKind::gvk(GATEWAY_GROUP, version, kind);
+            if let Ok((resource, _)) = pinned_kind(client, &gvk).await {
+                return Some(match namespace {
+                    Some(ns) => Api::namespaced_with(client.clone(), ns, &resource),
+                    None => Api::all_with(client.clone(), &resource),
+                });
+            }
+        }
+        None
+    }
+
+    fn strings(value: &Value) -> Vec<String> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect()
+    }
+
+    fn parent_key(parent: &Value, route_namespace: &str) -> String {
+        let mut key = format!(
+            "{}/{}",
+            parent["namespace"].as_str().unwrap_or(route_namespace),
+            parent["name"].as_str().unwrap_or_default()
+        );
+        if let Some(section) = parent["sectionName"].as_str() {
+            key = format!("{}/{}", key, section);
+        }
+        key
+    }
+
+    fn condition(conditions: &Value, kind: &str) -> Option<(bool, Option<String>, Option<String>)> {
+        conditions
+            .as_array()?
+            .iter()
+            .find(|c| c["type"].as_str() == Some(kind))
+            .map(|c| {
+                (
+                    c["status"].as_str() == Some("True"),
+                    c["reason"].as_str().map(|s| s.to_string()),
+                    c["message"].as_str().map(|s| s.to_string()),
+                )
+            })
+    }
+
+    /// A readable signature of one HTTPRoute match. Matches with the same
+    /// signature on the same hostname and parent select the same requests.
+    fn match_signature(matched: &Value) -> String {
+        let mut parts = vec![format!(
+            "{} {}",
+            matched["path"]["type"].as_str().unwrap_or("PathPrefix"),
+            matched["path"]["value"].as_str().unwrap_or("/")
+        )];
+        if let Some(method) = matched["method"].as_str() {
+            parts.push(method.to_string());
+        }
+        let mut pairs = |field: &str, label: &str| {
+            let mut values: Vec<String> = matched[field]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|h| {
+                    format!(
+                        "{}:{}{}={}",
+                        label,
+                        h["type"].as_str().unwrap_or("Exact"),
+                        h["name"].as_str().unwrap_or_default().to_lowercase(),
+                        h["value"].as_str().unwrap_or_default()
+                    )
+                })
+                .collect();
+            values.sort();
+            parts.extend(values);
+        };
+        pairs("headers", "header");
+        pairs("queryParams", "query");
+        parts.join(" ")
+    }
+
+    fn route_matches(route: &Value) -> Vec<String> {
+        let mut matches: Vec<String> = Vec::new();
+        for rule in route["spec"]["rules"].as_array().into_iter().flatten() {
+            match rule["matches"].as_array() {
+                Some(list) if !list.is_empty() => matches.extend(list.iter().map(match_signature)),
+                _ => matches.push(match_signature(&Value::Null)),
+            }
+        }
+        if matches.is_empty() {
+            matches.push(match_signature(&Value::Null));
+        }
+        matches
+    }
+
+    async fn service_exists(client: &Client, namespace: &str, name: &str) -> bool {
+        Api::<Service>::namespaced(client.clone(), namespace)
+            .get_opt(name)
+            .await
+            .ok()
+            .flatten()
+            .is_some()
+    }
+
+    /// Reports every HTTPRoute's Accepted and ResolvedRefs conditions per
+    /// parent, backends pointing at missing Services, and routes whose
+    /// matches overlap on the same Gateway and hostname.
+    pub async fn analyze(
+        client: Client,
+        namespace: Option<String>,
+    ) -> Result<GatewayRouteReport, KubiousError> {
+        let Some(routes_api) = gateway_api(&client, "HTTPRoute", namespace.as_deref()).await else {
+            return Ok(GatewayRouteReport {
+                installed: false,
+                routes: Vec::new(),
+                conflicts: Vec::new(),
+            });
+        };
+        let mut routes: Vec<Value> = routes_api
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list HTTPRoutes")?
+            .items
+            .into_iter()
+            .filter_map(|route| serde_json::to_value(route).ok())
+            .collect();
+        routes.sort_by(|a, b| {
+            let key = |v: &Value| {
+                (
+                    v["metadata"]["creationTimestamp"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    v["metadata"]["namespace"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    v["metadata"]["name"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                )
+            };
+            key(a).cmp(&key(b))
+        });
+
+        let gateways: HashSet<String> = match gateway_api(&client, "Gateway", None).await {
+            Some(api) => api
+                .list(&ListParams::default())
+                .await
+                .map(|list| {
+                    list.items
+                        .iter()
+                        .map(|g| {
+                            format!(
+                                "{}/{}",
+                                g.metadata.namespace.clone().unwrap_or_default(),
+                                g.metadata.name.clone().unwrap_or_default()
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            None => HashSet::new(),
+        };
+
+        let mut reports: Vec<RouteReport> = Vec::new();
+        let mut seen: BTreeMap<(String, String, String), Vec<String>> = BTreeMap::new();
+        for route in &routes {
+            let route_namespace = route["metadata"]["namespace"].as_str().unwrap_or_default();
+            let route_name = route["metadata"]["name"].as_str().unwrap_or_default();
+            let route_id = format!("{}/{}", route_namespace, route_name);
+            let hostnames = strings(&route["spec"]["hostnames"]);
+            let statuses = route["status"]["parents"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+
+            let mut parents: Vec<ParentStatus> = Vec::new();
+            for parent in route["spec"]["parentRefs"].as_array().into_iter().flatten() {
+                let key = parent_key(parent, route_namespace);
+                let gateway = format!(
+                    "{}/{}",
+                    parent["namespace"].as_str().unwrap_or(route_namespace),
+                    parent["name"].as_str().unwrap_or_default()
+                );
+                let status = statuses
+                    .iter()
+                    .find(|s| parent_key(&s["parentRef"], route_namespace) == key);
+                let accepted = status.and_then(|s| condition(&s["conditions"], "Accepted"));
+                let resolved = status.and_then(|s| condition(&s["conditions"], "ResolvedRefs"));
+                let failing = [&accepted, &resolved]
+                    .into_iter()
+                    .flatten()
+                    .find(|(ok, _, _)| !ok);
+                parents.push(ParentStatus {
+                    gateway_exists: parent["kind"].as_str().is_some_and(|k| k != "Gateway")
+                        || gateways.contains(&gateway),
+                    accepted: accepted.as_ref().map(|c| c.0),
+                    resolved_refs: resolved.as_ref().map(|c| c.0),
+                    reason: failing.and_then(|c| c.1.clone()),
+                    message: failing.and_then(|c| c.2.clone()).or(match status {
+                        None => Some("No controller has reported on this parent".to_string()),
+                        Some(_) => None,
+                    }),
+                    parent: key.clone(),
+                });
+
+                let hosts = if hostnames.is_empty() {
+                    vec!["*".to_string()]
+                } else {
+                    hostnames.clone()
+                };
+                for host in hosts {
+                    for matched in route_matches(route) {
+                        let owners = seen
+                            .entry((key.clone(), host.clone(), matched))
+                            .or_default();
+                        if !owners.contains(&route_id) {
+                            owners.push(route_id.clone());
+                        }
+                    }
+                }
+            }
+
+            let mut missing_backends: Vec<String> = Vec::new();
+            for rule in route["spec"]["rules"].as_array().into_iter().flatten() {
+                for backend in rule["backendRefs"].as_array().into_iter().flatten() {
+                    let core = backend["group"].as_str().unwrap_or_default().is_empty();
+                    if !core || backend["kind"].as_str().unwrap_or("Service") != "Service" {
+                        continue;
+                    }
+                    let backend_namespace =
+                        backend["namespace"].as_str().unwrap_or(route_namespace);
+                    let name = backend["name"].as_str().unwrap_or_default();
+                    let id = format!("{}/{}", backend_namespace, name);
+                    if !missing_backends.contains(&id)
+                        && !service_exists(&client, backend_namespace, name).await
+                    {
+                        missing_backends.push(id);
+                    }
+                }
+            }
+
+            reports.push(RouteReport {
+                namespace: route_namespace.to_string(),
+                name: route_name.to_string(),
+                healthy: missing_backends.is_empty()
+                    && parents.iter().all(|p| {
+                        p.gateway_exists
+                            && p.accepted == Some(true)
+                            && p.resolved_refs != Some(false)
+                    }),
+                hostnames,
+                parents,
+                missing_backends,
+            });
+        }
+
+        let conflicts = seen
+            .into_iter()
+            .filter(|(_, routes)| routes.len() > 1)
+            .map(|((parent, hostname, matched), routes)| RouteConflict {
+                parent,
+                hostname,
+                matched,
+                routes,
+            })
+            .collect();
+        reports.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+        Ok(GatewayRouteReport {
+            installed: true,
+            routes: reports,
+            conflicts,
+        })
+    }
+}
@@ -0,0 +1,228 @@
+pub mod namespace_hibernation {
+    use k8s_openapi::{
+        api::{
+            apps::v1::{Deployment, StatefulSet},
+            batch::v1::CronJob,
+        },
+        chrono::Utc,
+    };
+    use kube::{
+        api::{ListParams, Patch, PatchParams},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::{
+        api::app_settings::{HibernatedWorkload, Hibernation},
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct WorkloadOutcome {
+        pub kind: String,
+        pub name: String,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct HibernationResult {
+        /// What is still recorded for the namespace afterwards.
+        pub hibernation: Option<Hibernation>,
+        pub outcomes: Vec<WorkloadOutcome>,
+    }
+
+    /// Applies a merge patch to a workload. Returns `Ok(false)` if the
+    /// workload no longer exists.
+    async fn patch(
+        client: &Client,
+        namespace: &str,
+        kind: &str,
+        name: &str,
+        patch: &Value,
+    ) -> Result<bool, KubiousError> {
+        let params = PatchParams::default();
+        let patch = Patch::Merge(patch);
+        let result = match kind {
+            "Deployment" => Api::<Deployment>::namespaced(client.clone(), namespace)
+                .patch(name, &params, &patch)
+                .await
+                .map(|_| ()),
+            "StatefulSet" => Api::<StatefulSet>::namespaced(client.clone(), namespace)
+                .patch(name, &params, &patch)
+                .await
+                .map(|_| ()),
+            "CronJob" => Api::<CronJob>::namespaced(client.clone(), namespace)
+                .patch(name, &params, &patch)
+                .await
+                .map(|_| ()),
+            other => return Err(format!("Unsupported workload kind {}", other).into()),
+        };
+        match result {
+            Ok(()) => Ok(true),
+            Err(kube::Error::Api(e)) if e.code == 404 => Ok(false),
+            Err(e) => {
+                Err(KubiousError::from(e)
+                    .with_context(format!("Failed to update {} {}", kind, name)))
+            }
+        }
+    }
+
+    fn workload_patch(workload: &HibernatedWorkload, hibernate: bool) -> Value {
+        match (workload.replicas, workload.suspended) {
+            (Some(replicas), _) => {
+                json!({ "spec": { "replicas": if hibernate { 0 } else { replicas } } })
+            }
+            (None, suspended) => {
+                json!({ "spec": { "suspend": hibernate || suspended.unwrap_or(false) } })
+            }
+        }
+    }
+
+    /// Workloads in the namespace that are currently running, with what is
+    /// needed to restore them.
+    async fn running_workloads(
+        client: &Client,
+        namespace: &str,
+    ) -> Result<Vec<HibernatedWorkload>, KubiousError> {
+        let params = ListParams::default();
+        let failed = |kind: &str| format!("Failed to list {}s.", kind);
+        let mut workloads: Vec<HibernatedWorkload> = Vec::new();
+        for deployment in Api::<Deployment>::namespaced(client.clone(), namespace)
+            .list(&params)
+            .await
+            .context(failed("Deployment"))?
+        {
+            let replicas = deployment.spec.and_then(|s| s.replicas).unwrap_or(1);
+            if replicas > 0 {
+                workloads.push(HibernatedWorkload {
+                    kind: "Deployment".to_string(),
+                    name: deployment.metadata.name.unwrap_or_default(),
+                    replicas: Some(replicas),
+                    suspended: None,
+                });
+            }
+        }
+        for statefulset in Api::<StatefulSet>::namespaced(client.clone(), namespace)
+            .list(&params)
+            .await
+            .context(failed("StatefulSet"))?
+        {
+            let replicas = statefulset.spec.and_then(|s| s.replicas).unwrap_or(1);
+            if replicas > 0 {
+                workloads.push(HibernatedWorkload {
+                    kind: "StatefulSet".to_string(),
+                    name: statefulset.metadata.name.unwrap_or_default(),
+                    replicas: Some(replicas),
+                    suspended: None,
+                });
+            }
+        }
+        for cronjob in Api::<CronJob>::namespaced(client.clone(), namespace)
+            .list(&params)
+            .await
+            .context(failed("CronJob"))?
+        {
+            let suspended = cronjob.spec.and_then(|s| s.suspend).unwrap_or(false);
+            if !suspended {
+                workloads.push(HibernatedWorkload {
+                    kind: "CronJob".to_string(),
+                    name: cronjob.metadata.name.unwrap_or_default(),
+                    replicas: None,
+                    suspended: Some(false),
+                });
+            }
+        }
+        Ok(workloads)
+    }
+
+    /// Scales every Deployment and StatefulSet in the namespace to zero and
+    /// suspends its CronJobs, returning the record to persist. Workloads that
+    /// fail to scale are left out of the record, since they are still
+    /// running. Hibernating an already hibernated namespace adds to the
+    /// existing record, so counts recorded earlier are not lost.
+    pub async fn hibernate(
+        client: Client,
+        cluster: String,
+        namespace: String,
+        existing: Option<Hibernation>,
+    ) -> Result<HibernationResult, KubiousError> {
+        let workloads = running_workloads(&client, namespace.as_str()).await?;
+        let mut hibernated: Vec<HibernatedWorkload> = existing
+            .map(|h| h.workloads)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|w| {
+                !workloads
+                    .iter()
+                    .any(|running| running.kind == w.kind && running.name == w.name)
+            })
+            .collect();
+        let mut outcomes: Vec<WorkloadOutcome> = Vec::new();
+        for workload in workloads {
+            let result = patch(
+                &client,
+                namespace.as_str(),
+                workload.kind.as_str(),
+                workload.name.as_str(),
+                &workload_patch(&workload, true),
+            )
+            .await;
+            outcomes.push(WorkloadOutcome {
+                kind: workload.kind.clone(),
+                name: workload.name.clone(),
+                error: result.clone().err().map(|e| e.to_string()),
+            });
+            if result == Ok(true) {
+                hibernated.push(workload);
+            }
+        }
+        Ok(HibernationResult {
+            hibernation: Some(Hibernation {
+                cluster,
+                namespace,
+                hibernated_at: Utc::now().to_rfc3339(),
+                workloads: hibernated,
+            }),
+            outcomes,
+        })
+    }
+
+    /// Restores the recorded replica counts and CronJob suspension. Workloads
+    /// that fail to restore stay in the returned record so the restore can be
+    /// retried; workloads deleted in the meantime are dropped.
+    pub async fn restore(client: Client, hibernation: Hibernation) -> HibernationResult {
+        let mut remaining: Vec<HibernatedWorkload> = Vec::new();
+        let mut outcomes: Vec<WorkloadOutcome> = Vec::new();
+        for workload in hibernation.workloads.iter() {
+            let result = patch(
+                &client,
+                hibernation.namespace.as_str(),
+                workload.kind.as_str(),
+                workload.name.as_str(),
+                &workload_patch(workload, false),
+            )
+            .await;
+            if result.is_err() {
+                remaining.push(workload.clone());
+            }
+            outcomes.push(WorkloadOutcome {
+                kind: workload.kind.clone(),
+                name: workload.name.clone(),
+                error: match result {
+                    Ok(true) => None,
+                    Ok(false) => Some("Workload no longer exists".to_string()),
+                    Err(e) => Some(e.to_string()),
+                },
+            });
+        }
+        HibernationResult {
+            hibernation: Some(Hibernation {
+                workloads: remaining,
+                ..hibernation
+            })
+            .filter(|h| !h.workloads.is_empty()),
+            outcomes,
+        }
+    }
+}
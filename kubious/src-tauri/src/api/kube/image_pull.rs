@@ -0,0 +1,465 @@
+pub mod image_pull_diagnostics {
+    use k8s_openapi::api::core::v1::{Event, Node, Pod, Secret, ServiceAccount};
+    use kube::{api::ListParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri_plugin_http::reqwest::{self, StatusCode};
+
+    use crate::util::{
+        kubious_error::{ErrorContext, KubiousError},
+        registry_client::{
+            normalize_registry_key, registry_get, registry_host, RegistryCredential,
+        },
+    };
+
+    const PULL_FAILURE_REASONS: [&str; 3] =
+        ["ImagePullBackOff", "ErrImagePull", "InvalidImageName"];
+    const MANIFEST_ACCEPT: &str = "application/vnd.oci.image.index.v1+json, application/vnd.docker.distribution.manifest.list.v2+json, application/vnd.oci.image.manifest.v1+json, application/vnd.docker.distribution.manifest.v2+json";
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum PullFailureCause {
+        Auth,
+        MissingTag,
+        MissingRepository,
+        ArchMismatch,
+        RateLimit,
+        Network,
+        Certificate,
+        InvalidReference,
+        Unknown,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ImagePullDiagnosis {
+        pub namespace: String,
+        pub pod: String,
+        pub container: String,
+        pub image: String,
+        pub node: Option<String>,
+        pub reason: String,
+        pub events: Vec<String>,
+        pub cause: PullFailureCause,
+        pub registry_status: Option<u16>,
+        pub detail: Option<String>,
+        pub suggestions: Vec<String>,
+    }
+
+    #[derive(Clone, Debug)]
+    struct ImageReference {
+        registry: String,
+        repository: String,
+        reference: String,
+    }
+
+    struct RegistryCheck {
+        cause: Option<PullFailureCause>,
+        status: Option<u16>,
+        detail: Option<String>,
+    }
+
+    fn parse_reference(image: &str) -> Option<ImageReference> {
+        let (name, reference) = if let Some((name, digest)) = image.split_once('@') {
+            (name, digest.to_string())
+        } else {
+            match image.rsplit_once(':') {
+                Some((name, tag)) if !tag.contains('/') => (name, tag.to_string()),
+                _ => (image, "latest".to_string()),
+            }
+        };
+        if name.is_empty() || reference.is_empty() {
+            return None;
+        }
+        let (registry, repository) = match name.split_once('/') {
+            Some((host, rest))
+                if host.contains('.') || host.contains(':') || host == "localhost" =>
+            {
+                (host.to_string(), rest.to_string())
+            }
+            Some(_) => ("docker.io".to_string(), name.to_string()),
+            None => ("docker.io".to_string(), format!("library/{}", name)),
+        };
+        Some(ImageReference {
+            registry,
+            repository,
+            reference,
+        })
+    }
+
+    /// Collects credentials for `registry` from the pod's and service
+    /// account's image pull secrets.
+    async fn credentials_for(
+        client: &Client,
+        pod: &Pod,
+        registry: &str,
+    ) -> Option<RegistryCredential> {
+        let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+        let spec = pod.spec.as_ref()?;
+        let mut names: Vec<String> = spec
+            .image_pull_secrets
+            .iter()
+            .flatten()
+            .filter_map(|s| s.name.clone())
+            .collect();
+        let account = spec
+            .service_account_name
+            .clone()
+            .unwrap_or("default".to_string());
+        if let Ok(account) = Api::<ServiceAccount>::namespaced(client.clone(), namespace.as_str())
+            .get(account.as_str())
+            .await
+        {
+            names.extend(
+                account
+                    .image_pull_secrets
+                    .iter()
+                    .flatten()
+                    .filter_map(|s| s.name.clone()),
+            );
+        }
+
+        let secrets: Api<Secret> = Api::namespaced(client.clone(), namespace.as_str());
+        for name in names {
+            let Ok(secret) = secrets.get(name.as_str()).await else {
+                continue;
+            };
+            let data = secret.data.unwrap_or_default();
+            let auths: Value = if let Some(config) = data.get(".dockerconfigjson") {
+                serde_json::from_slice::<Value>(&config.0)
+                    .map(|v| v["auths"].clone())
+                    .unwrap_or(Value::Null)
+            } else if let Some(config) = data.get(".dockercfg") {
+                serde_json::from_slice(&config.0).unwrap_or(Value::Null)
+            } else {
+                continue;
+            };
+            for (key, entry) in auths.as_object().into_iter().flatten() {
+                if normalize_registry_key(key) == registry {
+                    return Some(RegistryCredential {
+                        basic: entry["auth"].as_str().map(|a| a.to_string()),
+                        username: entry["username"].as_str().map(|u| u.to_string()),
+                        password: entry["password"].as_str().map(|p| p.to_string()),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn error_check(error: reqwest::Error) -> RegistryCheck {
+        let text = format!("{:?}", error).to_lowercase();
+        let cause = if text.contains("certificate") || text.contains("tls") {
+            PullFailureCause::Certificate
+        } else {
+            PullFailureCause::Network
+        };
+        RegistryCheck {
+            cause: Some(cause),
+            status: None,
+            detail: Some(error.to_string()),
+        }
+    }
+
+    fn platform_matches(platform: &Value, os: &str, arch: &str) -> bool {
+        platform["os"].as_str().unwrap_or(os) == os
+            && platform["architecture"].as_str().unwrap_or(arch) == arch
+    }
+
+    /// Looks the manifest up in the registry and, when it exists, checks that
+    /// it provides an image for the node's platform.
+    async fn check_registry(
+        image: &ImageReference,
+        credential: &Option<RegistryCredential>,
+        platform: Option<(String, String)>,
+    ) -> RegistryCheck {
+        let http = reqwest::Client::new();
+        let base = format!(
+            "https://{}/v2/{}",
+            registry_host(image.registry.as_str()),
+            image.repository
+        );
+        let response = match registry_get(
+            &http,
+            format!("{}/manifests/{}", base, image.reference).as_str(),
+            MANIFEST_ACCEPT,
+            credential,
+        )
+        .await
+        {
+            Ok(response) => response,
+            Err(error) => return error_check(error),
+        };
+        let status = response.status();
+        let body: Value = response
+            .text()
+            .await
+            .ok()
+            .and_then(|t| serde_json::from_str(t.as_str()).ok())
+            .unwrap_or(Value::Null);
+        let code = body["errors"][0]["code"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let detail = body["errors"][0]["message"].as_str().map(|m| m.to_string());
+        let cause = match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Some(PullFailureCause::Auth),
+            StatusCode::TOO_MANY_REQUESTS => Some(PullFailureCause::RateLimit),
+            StatusCode::NOT_FOUND if code == "NAME_UNKNOWN" => {
+                Some(PullFailureCause::MissingRepository)
+            }
+            StatusCode::NOT_FOUND => Some(PullFailureCause::MissingTag),
+            s if s.is_success() => None,
+            _ => Some(PullFailureCause::Unknown),
+        };
+        if cause.is_some() {
+            return RegistryCheck {
+                cause,
+                status: Some(status.as_u16()),
+                detail,
+            };
+        }
+
+        let Some((os, arch)) = platform else {
+            return RegistryCheck {
+                cause: None,
+                status: Some(status.as_u16()),
+                detail: None,
+            };
+        };
+        let supported = if let Some(manifests) = body["manifests"].as_array() {
+            manifests
+                .iter()
+                .any(|m| platform_matches(&m["platform"], os.as_str(), arch.as_str()))
+        } else if let Some(digest) = body["config"]["digest"].as_str() {
+            match registry_get(
+                &http,
+                format!("{}/blobs/{}", base, digest).as_str(),
+                MANIFEST_ACCEPT,
+                credential,
+            )
+            .await
+            {
+                Ok(config) => config
+                    .text()
+                    .await
+                    .ok()
+                    .and_then(|t| serde_json::from_str::<Value>(t.as_str()).ok())
+                    .map(|c| platform_matches(&c, os.as_str(), arch.as_str()))
+                    .unwrap_or(true),
+                Err(_) => true,
+            }
+        } else {
+            true
+        };
+        RegistryCheck {
+            cause: (!supported).then_some(PullFailureCause::ArchMismatch),
+            status: Some(status.as_u16()),
+            detail: (!supported).then(|| format!("No image for {}/{}", os, arch)),
+        }
+    }
+
+    fn classify_events(events: &[String]) -> PullFailureCause {
+        let text = events.join("\n").to_lowercase();
+        if text.contains("toomanyrequests") || text.contains("rate limit") {
+            PullFailureCause::RateLimit
+        } else if text.contains("no matching manifest") || text.contains("exec format") {
+            PullFailureCause::ArchMismatch
+        } else if text.contains("unauthorized")
+            || text.contains("pull access denied")
+            || text.contains("authentication required")
+            || text.contains("403 forbidden")
+        {
+            PullFailureCause::Auth
+        } else if text.contains("manifest unknown") || text.contains(": not found") {
+            PullFailureCause::MissingTag
+        } else if text.contains("x509") || text.contains("certificate") {
+            PullFailureCause::Certificate
+        } else if text.contains("no such host")
+            || text.contains("i/o timeout")
+            || text.contains("connection refused")
+        {
+            PullFailureCause::Network
+        } else if text.contains("invalid reference format") || text.contains("invalidimagename") {
+            PullFailureCause::InvalidReference
+        } else {
+            PullFailureCause::Unknown
+        }
+    }
+
+    fn suggestions(cause: &PullFailureCause, image: &str, has_credentials: bool) -> Vec<String> {
+        match cause {
+            PullFailureCause::Auth if has_credentials => vec![
+                "The configured pull secret was rejected; check that it has not expired and grants read access to this repository.".to_string(),
+            ],
+            PullFailureCause::Auth => vec![
+                "Create a docker-registry secret for this registry and reference it in imagePullSecrets or on the pod's service account.".to_string(),
+            ],
+            PullFailureCause::MissingTag => vec![
+                format!("The tag or digest of {} does not exist; verify it was pushed and the reference is spelled correctly.", image),
+            ],
+            PullFailureCause::MissingRepository => vec![
+                "The repository does not exist or is private; check the image name and registry.".to_string(),
+            ],
+            PullFailureCause::ArchMismatch => vec![
+                "Publish a multi-arch image or constrain the pod with a kubernetes.io/arch node selector.".to_string(),
+            ],
+            PullFailureCause::RateLimit => vec![
+                "Authenticate pulls with a pull secret or mirror the image to a registry without anonymous rate limits.".to_string(),
+            ],
+            PullFailureCause::Network => vec![
+                "Nodes could not reach the registry; check DNS, proxies and egress rules from the node.".to_string(),
+            ],
+            PullFailureCause::Certificate => vec![
+                "The registry certificate is not trusted; install its CA on the nodes or configure the runtime for this registry.".to_string(),
+            ],
+            PullFailureCause::InvalidReference => vec![
+                "The image reference is malformed; check for stray characters or uppercase letters.".to_string(),
+            ],
+            PullFailureCause::Unknown => vec![
+                "Inspect the pod events and the container runtime logs on the node.".to_string(),
+            ],
+        }
+    }
+
+    async fn pod_events(client: &Client, namespace: &str, pod: &str) -> Vec<String> {
+        let params = ListParams::default()
+            .fields(format!("involvedObject.kind=Pod,involvedObject.name={}", pod).as_str());
+        Api::<Event>::namespaced(client.clone(), namespace)
+            .list(&params)
+            .await
+            .map(|events| {
+                events
+                    .items
+                    .into_iter()
+                    .filter(|e| e.type_.as_deref() == Some("Warning"))
+                    .filter_map(|e| e.message)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    async fn node_platform(client: &Client, node: &Option<String>) -> Option<(String, String)> {
+        let node = Api::<Node>::all(client.clone())
+            .get(node.as_ref()?.as_str())
+            .await
+            .ok()?;
+        let info = node.status?.node_info?;
+        Some((info.operating_system, info.architecture))
+    }
+
+    /// Looks up credentials saved in the app for a registry host.
+    pub type StoredCredentials<'a> = &'a (dyn Fn(&str) -> Option<RegistryCredential> + Sync);
+
+    async fn diagnose_pod(
+        client: &Client,
+        pod: &Pod,
+        stored: StoredCredentials<'_>,
+    ) -> Vec<ImagePullDiagnosis> {
+        let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+        let name = pod.metadata.name.clone().unwrap_or_default();
+        let node = pod.spec.as_ref().and_then(|s| s.node_name.clone());
+        let Some(status) = pod.status.as_ref() else {
+            return Vec::new();
+        };
+        let failing: Vec<(String, String, String)> = status
+            .init_container_statuses
+            .iter()
+            .flatten()
+            .chain(status.container_statuses.iter().flatten())
+            .filter_map(|c| {
+                let reason = c.state.as_ref()?.waiting.as_ref()?.reason.clone()?;
+                PULL_FAILURE_REASONS
+                    .contains(&reason.as_str())
+                    .then(|| (c.name.clone(), c.image.clone(), reason))
+            })
+            .collect();
+        if failing.is_empty() {
+            return Vec::new();
+        }
+
+        let events = pod_events(client, namespace.as_str(), name.as_str()).await;
+        let platform = node_platform(client, &node).await;
+        let mut diagnoses: Vec<ImagePullDiagnosis> = Vec::new();
+        for (container, image, reason) in failing {
+            let container_events: Vec<String> = events
+                .iter()
+                .filter(|e| e.contains(image.as_str()) || !e.contains('"'))
+                .cloned()
+                .collect();
+            let from_events = classify_events(&container_events);
+            let (check, has_credentials) = match parse_reference(image.as_str()) {
+                Some(reference) => {
+                    let credential =
+                        match credentials_for(client, pod, reference.registry.as_str()).await {
+                            Some(credential) => Some(credential),
+                            None => stored(reference.registry.as_str()),
+                        };
+                    (
+                        check_registry(&reference, &credential, platform.clone()).await,
+                        credential.is_some(),
+                    )
+                }
+                None => (
+                    RegistryCheck {
+                        cause: Some(PullFailureCause::InvalidReference),
+                        status: None,
+                        detail: None,
+                    },
+                    false,
+                ),
+            };
+            // The registry may be reachable from here but not from the node,
+            // so a clean registry check defers to what the events report.
+            let cause = match check.cause {
+                Some(PullFailureCause::Network) | None => from_events,
+                Some(cause) => cause,
+            };
+            diagnoses.push(ImagePullDiagnosis {
+                namespace: namespace.clone(),
+                pod: name.clone(),
+                container,
+                suggestions: suggestions(&cause, image.as_str(), has_credentials),
+                image,
+                node: node.clone(),
+                reason,
+                events: container_events,
+                cause,
+                registry_status: check.status,
+                detail: check.detail,
+            });
+        }
+        diagnoses
+    }
+
+    pub async fn diagnose(
+        client: Client,
+        namespace: Option<String>,
+        pod: Option<String>,
+        stored: StoredCredentials<'_>,
+    ) -> Result<Vec<ImagePullDiagnosis>, KubiousError> {
+        let pods: Vec<Pod> = match (&namespace, &pod) {
+            (Some(ns), Some(name)) => vec![Api::<Pod>::namespaced(client.clone(), ns.as_str())
+                .get(name.as_str())
+                .await
+                .context("Unknown pod")?],
+            (Some(ns), None) => {
+                Api::<Pod>::namespaced(client.clone(), ns.as_str())
+                    .list(&ListParams::default())
+                    .await
+                    .context("Failed to list pods.")?
+                    .items
+            }
+            (None, _) => {
+                Api::<Pod>::all(client.clone())
+                    .list(&ListParams::default())
+                    .await
+                    .context("Failed to list pods.")?
+                    .items
+            }
+        };
+        let mut diagnoses: Vec<ImagePullDiagnosis> = Vec::new();
+        for pod in pods {
+            diagnoses.extend(diagnose_pod(&client, &pod, stored).await);
+        }
+        Ok(diagnoses)
+    }
+}
@@ -0,0 +1,293 @@
+pub mod keda_insights {
+    use http::Request;
+    use k8s_openapi::api::core::v1::Event;
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::util::{
+        kube_quantity::quantity_value,
+        kubious_error::{ErrorContext, KubiousError},
+    };
+
+    const EXTERNAL_METRICS: &str = "/apis/external.metrics.k8s.io/v1beta1";
+    const MAX_EVENTS: usize = 20;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TriggerInsight {
+        pub trigger_type: String,
+        pub name: Option<String>,
+        pub metric_type: Option<String>,
+        /// Trigger settings as written in the spec; secrets are only referenced
+        /// through `authentication`.
+        pub metadata: Value,
+        pub authentication: Option<String>,
+        /// The external metric KEDA exposes for this trigger, e.g. `s0-rabbitmq-queue`.
+        pub metric_name: Option<String>,
+        pub current_value: Option<f64>,
+        pub target_value: Option<String>,
+        pub health: Option<String>,
+        pub failures: Option<i64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ScalingEvent {
+        pub object: String,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+        pub event_type: Option<String>,
+        pub count: Option<i32>,
+        pub last_seen: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ScalerInsight {
+        pub kind: String,
+        pub namespace: String,
+        pub name: String,
+        pub target: Option<String>,
+        pub hpa: Option<String>,
+        pub min_replicas: Option<i64>,
+        pub max_replicas: Option<i64>,
+        pub ready: Option<bool>,
+        pub active: Option<bool>,
+        pub paused: bool,
+        pub fallback: Option<bool>,
+        pub last_active: Option<String>,
+        pub triggers: Vec<TriggerInsight>,
+        pub events: Vec<ScalingEvent>,
+    }
+
+    async fn api_for(
+        client: &Client,
+        kind: &str,
+        namespace: Option<&str>,
+    ) -> Result<Api<DynamicObject>, KubiousError> {
+        let gvk = GroupVersionKind::gvk("keda.sh", "v1alpha1", kind);
+        let (resource, _) = pinned_kind(client, &gvk)
+            .await
+            .context("KEDA CRDs are not installed")?;
+        Ok(match namespace {
+            Some(ns) => Api::namespaced_with(client.clone(), ns, &resource),
+            None => Api::all_with(client.clone(), &resource),
+        })
+    }
+
+    fn condition(object: &DynamicObject, name: &str) -> Option<bool> {
+        object.data["status"]["conditions"]
+            .as_array()?
+            .iter()
+            .find(|c| c["type"].as_str() == Some(name))
+            .and_then(|c| c["status"].as_str())
+            .and_then(|s| match s {
+                "True" => Some(true),
+                "False" => Some(false),
+                _ => None,
+            })
+    }
+
+    fn text(value: &Value) -> Option<String> {
+        value.as_str().map(|s| s.to_string())
+    }
+
+    /// Reads the current value of an external metric for one scaler, the
+    /// same query the HPA makes against KEDA's metrics adapter.
+    async fn metric_value(
+        client: &Client,
+        namespace: &str,
+        scaler: &str,
+        metric: &str,
+    ) -> Option<f64> {
+        let uri = format!(
+            "{}/namespaces/{}/{}?labelSelector=scaledobject.keda.sh%2Fname%3D{}",
+            EXTERNAL_METRICS, namespace, metric, scaler
+        );
+        let request = Request::get(uri).body(Vec::new()).ok()?;
+        let values: Value = client.request(request).await.ok()?;
+        let quantities: Vec<f64> = values["items"]
+            .as_array()?
+            .iter()
+            .filter_map(|item| serde_json::from_value(item["value"].clone()).ok())
+            .map(|q| quantity_value(&q))
+            .collect();
+        (!quantities.is_empty()).then(|| quantities.iter().sum())
+    }
+
+    /// Trigger target thresholds use different keys per scaler type.
+    fn target_of(metadata: &Value) -> Option<String> {
+        [
+            "value",
+            "targetValue",
+            "threshold",
+            "queueLength",
+            "lagThreshold",
+            "listLength",
+            "desiredReplicas",
+            "activationThreshold",
+        ]
+        .iter()
+        .find_map(|key| match &metadata[key] {
+            Value::String(s) => Some(format!("{}={}", key, s)),
+            Value::Number(n) => Some(format!("{}={}", key, n)),
+            _ => None,
+        })
+    }
+
+    async fn scaling_events(
+        client: &Client,
+        namespace: &str,
+        objects: &[(&str, String)],
+    ) -> Vec<ScalingEvent> {
+        let api = Api::<Event>::namespaced(client.clone(), namespace);
+        let mut events: Vec<ScalingEvent> = Vec::new();
+        for (kind, name) in objects {
+            let params = ListParams::default().fields(
+                format!("involvedObject.kind={},involvedObject.name={}", kind, name).as_str(),
+            );
+            let Ok(list) = api.list(&params).await else {
+                continue;
+            };
+            events.extend(list.items.into_iter().map(|e| {
+                ScalingEvent {
+                    object: format!("{}/{}", kind, name),
+                    reason: e.reason,
+                    message: e.message,
+                    event_type: e.type_,
+                    count: e.count,
+                    last_seen: e
+                        .last_timestamp
+                        .map(|t| t.0.to_rfc3339())
+                        .or(e.event_time.map(|t| t.0.to_rfc3339())),
+                }
+            }));
+        }
+        events.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        events.truncate(MAX_EVENTS);
+        events
+    }
+
+    async fn inspect(client: &Client, kind: &str, object: &DynamicObject) -> ScalerInsight {
+        let namespace = object.metadata.namespace.clone().unwrap_or_default();
+        let name = object.metadata.name.clone().unwrap_or_default();
+        let spec = &object.data["spec"];
+        let status = &object.data["status"];
+        let metric_names: Vec<String> = status["externalMetricNames"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(text)
+            .collect();
+
+        let mut triggers: Vec<TriggerInsight> = Vec::new();
+        for (index, trigger) in spec["triggers"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+        {
+            let trigger_type = trigger["type"].as_str().unwrap_or_default().to_string();
+            // KEDA names metrics `s<index>-<type>...`, in trigger order.
+            let prefix = format!("s{}-", index);
+            let metric_name = metric_names
+                .iter()
+                .find(|m| m.starts_with(prefix.as_str()))
+                .cloned();
+            let current_value = match (&metric_name, kind) {
+                (Some(metric), "ScaledObject") => {
+                    metric_value(client, namespace.as_str(), name.as_str(), metric.as_str()).await
+                }
+                _ => None,
+            };
+            let health = metric_name.as_ref().map(|m| &status["health"][m]);
+            triggers.push(TriggerInsight {
+                name: text(&trigger["name"]),
+                metric_type: text(&trigger["metricType"]),
+                metadata: trigger["metadata"].clone(),
+                authentication: text(&trigger["authenticationRef"]["name"]),
+                target_value: target_of(&trigger["metadata"]),
+                health: health.and_then(|h| text(&h["status"])),
+                failures: health.and_then(|h| h["numberOfFailures"].as_i64()),
+                trigger_type,
+                metric_name,
+                current_value,
+            });
+        }
+
+        let hpa = text(&status["hpaName"]).or_else(|| {
+            (kind == "ScaledObject").then(|| {
+                text(&spec["advanced"]["horizontalPodAutoscalerConfig"]["name"])
+                    .unwrap_or(format!("keda-hpa-{}", name))
+            })
+        });
+        let mut event_sources = vec![(kind, name.clone())];
+        if let Some(hpa) = &hpa {
+            event_sources.push(("HorizontalPodAutoscaler", hpa.clone()));
+        }
+        let events = scaling_events(client, namespace.as_str(), &event_sources).await;
+        let paused = condition(object, "Paused") == Some(true)
+            || object
+                .metadata
+                .annotations
+                .as_ref()
+                .map(|a| {
+                    a.contains_key("autoscaling.keda.sh/paused-replicas")
+                        || a.get("autoscaling.keda.sh/paused").map(|v| v.as_str()) == Some("true")
+                })
+                .unwrap_or(false);
+
+        ScalerInsight {
+            kind: kind.to_string(),
+            target: match kind {
+                "ScaledObject" => spec["scaleTargetRef"]["name"].as_str().map(|target| {
+                    format!(
+                        "{}/{}",
+                        spec["scaleTargetRef"]["kind"]
+                            .as_str()
+                            .unwrap_or("Deployment"),
+                        target
+                    )
+                }),
+                _ => Some("Job".to_string()),
+            },
+            hpa,
+            min_replicas: spec["minReplicaCount"].as_i64(),
+            max_replicas: spec["maxReplicaCount"].as_i64(),
+            ready: condition(object, "Ready"),
+            active: condition(object, "Active"),
+            paused,
+            fallback: condition(object, "Fallback"),
+            last_active: text(&status["lastActiveTime"]),
+            triggers,
+            events,
+            namespace,
+            name,
+        }
+    }
+
+    /// Lists ScaledObjects and ScaledJobs with their triggers, the current
+    /// external metric value behind each trigger and recent scaling events
+    /// of the scaler and its HPA.
+    pub async fn scalers(
+        client: Client,
+        namespace: Option<String>,
+    ) -> Result<Vec<ScalerInsight>, KubiousError> {
+        let mut insights: Vec<ScalerInsight> = Vec::new();
+        for kind in ["ScaledObject", "ScaledJob"] {
+            let objects = api_for(&client, kind, namespace.as_deref())
+                .await?
+                .list(&ListParams::default())
+                .await
+                .context(format!("Failed to list {}s.", kind))?;
+            for object in &objects.items {
+                insights.push(inspect(&client, kind, object).await);
+            }
+        }
+        insights.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+        Ok(insights)
+    }
+}
@@ -0,0 +1,338 @@
+pub mod multi_cluster_logs {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard,
+        },
+    };
+
+    use futures::{
+        stream::{self, select_all, BoxStream},
+        AsyncBufReadExt, StreamExt,
+    };
+    use k8s_openapi::{
+        api::core::v1::Pod,
+        chrono::{DateTime, Utc},
+    };
+    use kube::{
+        api::{ListParams, LogParams},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use tauri::{async_runtime::JoinHandle, AppHandle, Emitter, Manager};
+
+    use crate::util::{
+        kubious_error::{ErrorContext, KubiousError},
+        lock_recovery::recover,
+        stream_reconnect::{BackoffPolicy, Reconnector},
+    };
+
+    /// Event channel carrying `LogLine`s from every running tail.
+    pub const LOG_LINE_EVENT: &str = "log-line";
+
+    /// Event channel carrying `LogTailEnded` once every stream of a tail closed.
+    pub const LOG_TAIL_ENDED_EVENT: &str = "log-tail-ended";
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct LogLine {
+        pub tail: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub pod: String,
+        pub container: String,
+        pub line: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct LogTailEnded {
+        pub tail: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TailSource {
+        pub cluster: String,
+        pub namespace: String,
+        pub pod: String,
+        pub container: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TailSummary {
+        pub id: String,
+        pub sources: Vec<TailSource>,
+        /// Clusters that could not be reached or listed, with the reason.
+        pub failed: HashMap<String, String>,
+    }
+
+    /// Running tails keyed by id. Each tail is a single task draining the
+    /// merged streams of all its clusters.
+    #[derive(Default)]
+    pub struct LogTails {
+        tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+        counter: AtomicU64,
+    }
+
+    impl LogTails {
+        fn tasks_mutable(&self) -> MutexGuard<'_, HashMap<String, JoinHandle<()>>> {
+            recover(&self.tasks)
+        }
+
+        fn next_id(&self) -> String {
+            format!("tail-{}", self.counter.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        pub fn active(&self) -> Vec<String> {
+            let mut tails: Vec<String> = self.tasks_mutable().keys().cloned().collect();
+            tails.sort();
+            tails
+        }
+    }
+
+    /// Pods matching the selector in one cluster, expanded to the containers
+    /// to follow.
+    async fn sources(
+        client: &Client,
+        cluster: &str,
+        namespace: &Option<String>,
+        selector: &str,
+        container: &Option<String>,
+    ) -> Result<Vec<TailSource>, KubiousError> {
+        let api: Api<Pod> = match namespace {
+            Some(ns) => Api::namespaced(client.clone(), ns.as_str()),
+            None => Api::all(client.clone()),
+        };
+        let pods = api
+            .list(&ListParams::default().labels(selector))
+            .await
+            .context("Failed to list pods.")?;
+        let mut sources: Vec<TailSource> = Vec::new();
+        for pod in pods.items {
+            let (Some(name), Some(pod_ns), Some(spec)) = (
+                pod.metadata.name.clone(),
+                pod.metadata.namespace.clone(),
+                pod.spec.as_ref(),
+            ) else {
+                continue;
+            };
+            for c in &spec.containers {
+                if container.as_ref().is_some_and(|wanted| *wanted != c.name) {
+                    continue;
+                }
+                sources.push(TailSource {
+                    cluster: cluster.to_string(),
+                    namespace: pod_ns.clone(),
+                    pod: name.clone(),
+                    container: c.name.clone(),
+                });
+            }
+        }
+        Ok(sources)
+    }
+
+    /// Whether the pod still runs the container, so a log stream that ended
+    /// or failed was cut off rather than finished. A pod that is gone counts
+    /// as not running.
+    pub async fn container_running(
+        api: &Api<Pod>,
+        pod: &str,
+        container: Option<&str>,
+    ) -> Result<bool, String> {
+        let pod = api.get_opt(pod).await.map_err(|e| e.to_string())?;
+        Ok(pod
+            .and_then(|p| p.status)
+            .and_then(|s| s.container_statuses)
+            .unwrap_or_default()
+            .iter()
+            .filter(|s| container.is_none_or(|c| s.name == c))
+            .any(|s| {
+                s.state
+                    .as_ref()
+                    .is_some_and(|state| state.running.is_some())
+            }))
+    }
+
+    /// State of one followed container between lines.
+    struct Follow {
+        api: Api<Pod>,
+        tail: String,
+        source: TailSource,
+        params: LogParams,
+        lines: Option<BoxStream<'static, std::io::Result<String>>>,
+        reconnector: Reconnector,
+        /// When output last arrived, to resume from after a reconnect.
+        received: Option<DateTime<Utc>>,
+    }
+
+    impl Follow {
+        fn line(&self, line: String) -> LogLine {
+            LogLine {
+                tail: self.tail.clone(),
+                cluster: self.source.cluster.clone(),
+                namespace: self.source.namespace.clone(),
+                pod: self.source.pod.clone(),
+                container: self.source.container.clone(),
+                line,
+            }
+        }
+
+        /// The next line, reopening the stream with backoff while the
+        /// container is still running. `None` once the container stopped or
+        /// the reconnect gave up.
+        async fn next_line(&mut self) -> Option<LogLine> {
+            loop {
+                let reason = match self.lines.as_mut() {
+                    None => match self
+                        .api
+                        .log_stream(self.source.pod.as_str(), &self.params)
+                        .await
+                    {
+                        Ok(reader) => {
+                            self.reconnector.connected();
+                            self.received.get_or_insert(Utc::now());
+                            self.lines = Some(reader.lines().boxed());
+                            continue;
+                        }
+                        Err(e) => e.to_string(),
+                    },
+                    Some(lines) => match lines.next().await {
+                        Some(Ok(line)) => {
+                            self.received = Some(Utc::now());
+                            return Some(self.line(line));
+                        }
+                        Some(Err(e)) => e.to_string(),
+                        None => "Log stream closed".to_string(),
+                    },
+                };
+                self.lines = None;
+                let container = Some(self.source.container.as_str());
+                if let Ok(false) =
+                    container_running(&self.api, self.source.pod.as_str(), container).await
+                {
+                    self.reconnector.closed();
+                    return None;
+                }
+                if let Some(since) = self.received {
+                    self.params.tail_lines = None;
+                    self.params.since_time = Some(since);
+                }
+                if !self.reconnector.retry(reason.as_str()).await {
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Follows one container's log, tagging every line with its source.
+    /// Streams that drop while the container runs are reopened; ones whose
+    /// container has stopped end without ending the tail.
+    fn follow(
+        handle: &AppHandle,
+        client: Client,
+        tail: String,
+        source: TailSource,
+        tail_lines: Option<i64>,
+    ) -> BoxStream<'static, LogLine> {
+        let name = format!(
+            "{}/{}/{}/{}/{}",
+            tail, source.cluster, source.namespace, source.pod, source.container
+        );
+        let follow = Follow {
+            api: Api::namespaced(client, source.namespace.as_str()),
+            params: LogParams {
+                container: Some(source.container.clone()),
+                follow: true,
+                tail_lines,
+                ..LogParams::default()
+            },
+            reconnector: Reconnector::new(
+                handle.clone(),
+                "logs",
+                name.as_str(),
+                BackoffPolicy::default(),
+            ),
+            tail,
+            source,
+            lines: None,
+            received: None,
+        };
+        stream::unfold(follow, |mut follow| async move {
+            follow.next_line().await.map(|line| (line, follow))
+        })
+        .boxed()
+    }
+
+    async fn drain(handle: AppHandle, tail: String, streams: Vec<BoxStream<'static, LogLine>>) {
+        let mut merged = select_all(streams);
+        while let Some(line) = merged.next().await {
+            let _ = handle.emit(LOG_LINE_EVENT, line);
+        }
+        handle.state::<LogTails>().tasks_mutable().remove(&tail);
+        let _ = handle.emit(LOG_TAIL_ENDED_EVENT, LogTailEnded { tail });
+    }
+
+    /// Starts following every container of the pods matching `selector` in
+    /// all given clusters at once. Lines arrive on `LOG_LINE_EVENT` in the
+    /// order they are received, tagged with their cluster and pod. Pods
+    /// created after the tail starts are not picked up.
+    pub async fn start(
+        handle: &AppHandle,
+        clients: Vec<(String, Option<Client>)>,
+        namespace: Option<String>,
+        selector: String,
+        container: Option<String>,
+        tail_lines: Option<i64>,
+    ) -> Result<TailSummary, KubiousError> {
+        let tails = handle.state::<LogTails>();
+        let id = tails.next_id();
+        let mut all_sources: Vec<TailSource> = Vec::new();
+        let mut failed: HashMap<String, String> = HashMap::new();
+        let mut streams: Vec<BoxStream<'static, LogLine>> = Vec::new();
+
+        for (cluster, client) in clients {
+            let Some(client) = client else {
+                failed.insert(cluster, "Could not establish connection.".to_string());
+                continue;
+            };
+            match sources(&client, cluster.as_str(), &namespace, &selector, &container).await {
+                Ok(found) => {
+                    for source in found {
+                        streams.push(follow(
+                            handle,
+                            client.clone(),
+                            id.clone(),
+                            source.clone(),
+                            tail_lines,
+                        ));
+                        all_sources.push(source);
+                    }
+                }
+                Err(error) => {
+                    failed.insert(cluster, error.to_string());
+                }
+            }
+        }
+
+        if streams.is_empty() {
+            return Err("No matching containers in the selected clusters.".into());
+        }
+        let mut tasks = tails.tasks_mutable();
+        let task = tauri::async_runtime::spawn(drain(handle.clone(), id.clone(), streams));
+        tasks.insert(id.clone(), task);
+        Ok(TailSummary {
+            id,
+            sources: all_sources,
+            failed,
+        })
+    }
+
+    pub fn stop(handle: &AppHandle, tail: &str) -> bool {
+        let removed = handle.state::<LogTails>().tasks_mutable().remove(tail);
+        if let Some(task) = removed {
+            task.abort();
+            true
+        } else {
+            false
+        }
+    }
+}
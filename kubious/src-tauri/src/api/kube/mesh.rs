@@ -0,0 +1,590 @@
+pub mod service_mesh {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use k8s_openapi::api::core::v1::{Namespace, Pod, Service};
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        time::timeout,
+    };
+
+    use crate::util::{
+        kubious_error::{ErrorContext, KubiousError},
+        resource_accounting::workload_of,
+    };
+
+    const ISTIO_PROXY: &str = "istio-proxy";
+    const LINKERD_PROXY: &str = "linkerd-proxy";
+    const ENVOY_ADMIN_PORT: u16 = 15000;
+    const LINKERD_ADMIN_PORT: u16 = 4191;
+    const ADMIN_TIMEOUT: Duration = Duration::from_secs(10);
+    const CLUSTER_DOMAIN: &str = "svc.cluster.local";
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum Mesh {
+        Istio,
+        Linkerd,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NamespaceInjection {
+        pub namespace: String,
+        /// `istio-injection` label value.
+        pub istio_injection: Option<String>,
+        /// `istio.io/rev` label value, used by revision-based installs.
+        pub istio_revision: Option<String>,
+        /// `linkerd.io/inject` annotation value.
+        pub linkerd_inject: Option<String>,
+        pub enabled: Vec<Mesh>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct WorkloadInjection {
+        pub namespace: String,
+        pub workload: String,
+        pub mesh: Option<Mesh>,
+        pub pods: usize,
+        pub injected: usize,
+        /// Set when the pod template opts out of an injection enabled on its
+        /// namespace.
+        pub opted_out: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct InjectionReport {
+        /// Meshes whose CRDs are installed in the cluster.
+        pub installed: Vec<Mesh>,
+        pub namespaces: Vec<NamespaceInjection>,
+        pub workloads: Vec<WorkloadInjection>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct MeshRoute {
+        pub kind: String,
+        pub namespace: String,
+        pub name: String,
+        /// How the object refers to the service (`host`, `destination`).
+        pub matched_by: String,
+        pub hosts: Vec<String>,
+        pub gateways: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ProxyStatus {
+        pub mesh: Mesh,
+        pub container: String,
+        pub admin_port: u16,
+        pub ready: bool,
+        pub version: Option<String>,
+        pub state: Option<String>,
+        pub uptime_seconds: Option<i64>,
+        /// Upstream clusters (Envoy) or outbound endpoints known to the proxy.
+        pub upstreams: Option<usize>,
+        pub unhealthy_upstreams: Option<usize>,
+        pub identity_expires_at: Option<i64>,
+        pub errors: Vec<String>,
+    }
+
+    async fn mesh_api(
+        client: &Client,
+        group: &str,
+        version: &str,
+        kind: &str,
+        namespace: Option<&str>,
+    ) -> Option<Api<DynamicObject>> {
+        let gvk = GroupVersionKind::gvk(group, version, kind);
+        let (resource, _) = pinned_kind(client, &gvk).await.ok()?;
+        Some(match namespace {
+            Some(ns) => Api::namespaced_with(client.clone(), ns, &resource),
+            None => Api::all_with(client.clone(), &resource),
+        })
+    }
+
+    async fn installed_meshes(client: &Client) -> Vec<Mesh> {
+        let mut installed = Vec::new();
+        if mesh_api(
+            client,
+            "networking.istio.io",
+            "v1beta1",
+            "VirtualService",
+            None,
+        )
+        .await
+        .is_some()
+        {
+            installed.push(Mesh::Istio);
+        }
+        if mesh_api(client, "linkerd.io", "v1alpha2", "ServiceProfile", None)
+            .await
+            .is_some()
+        {
+            installed.push(Mesh::Linkerd);
+        }
+        installed
+    }
+
+    /// The mesh whose proxy runs in a pod, checking native sidecars too.
+    fn pod_mesh(pod: &Pod) -> Option<(Mesh, String)> {
+        let spec = pod.spec.as_ref()?;
+        spec.containers
+            .iter()
+            .chain(spec.init_containers.iter().flatten())
+            .find_map(|c| match c.name.as_str() {
+                ISTIO_PROXY => Some((Mesh::Istio, c.name.clone())),
+                LINKERD_PROXY => Some((Mesh::Linkerd, c.name.clone())),
+                _ => None,
+            })
+    }
+
+    fn namespace_injection(namespace: &Namespace) -> NamespaceInjection {
+        let labels = namespace.metadata.labels.clone().unwrap_or_default();
+        let annotations = namespace.metadata.annotations.clone().unwrap_or_default();
+        let istio_injection = labels.get("istio-injection").cloned();
+        let istio_revision = labels.get("istio.io/rev").cloned();
+        let linkerd_inject = annotations.get("linkerd.io/inject").cloned();
+        let mut enabled = Vec::new();
+        if istio_injection.as_deref() == Some("enabled")
+            || (istio_revision.is_some() && istio_injection.as_deref() != Some("disabled"))
+        {
+            enabled.push(Mesh::Istio);
+        }
+        if linkerd_inject.as_deref() == Some("enabled") {
+            enabled.push(Mesh::Linkerd);
+        }
+        NamespaceInjection {
+            namespace: namespace.metadata.name.clone().unwrap_or_default(),
+            istio_injection,
+            istio_revision,
+            linkerd_inject,
+            enabled,
+        }
+    }
+
+    fn opts_out(pod: &Pod) -> bool {
+        let labels = pod.metadata.labels.clone().unwrap_or_default();
+        let annotations = pod.metadata.annotations.clone().unwrap_or_default();
+        labels.get("sidecar.istio.io/inject").map(|v| v.as_str()) == Some("false")
+            || annotations
+                .get("sidecar.istio.io/inject")
+                .map(|v| v.as_str())
+                == Some("false")
+            || annotations.get("linkerd.io/inject").map(|v| v.as_str()) == Some("disabled")
+    }
+
+    /// Reports which namespaces have sidecar injection enabled and how many
+    /// pods of each workload actually run a mesh proxy.
+    pub async fn injection_status(
+        client: Client,
+        namespace: Option<String>,
+    ) -> Result<InjectionReport, KubiousError> {
+        let namespaces: Vec<Namespace> = match &namespace {
+            Some(ns) => vec![Api::<Namespace>::all(client.clone())
+                .get(ns.as_str())
+                .await
+                .context("Unknown namespace")?],
+            None => {
+                Api::<Namespace>::all(client.clone())
+                    .list(&ListParams::default())
+                    .await
+                    .context("Failed to list namespaces.")?
+                    .items
+            }
+        };
+        let pods = match &namespace {
+            Some(ns) => Api::<Pod>::namespaced(client.clone(), ns.as_str()),
+            None => Api::<Pod>::all(client.clone()),
+        }
+        .list(&ListParams::default())
+        .await
+        .context("Failed to list pods.")?;
+
+        let namespaces: Vec<NamespaceInjection> =
+            namespaces.iter().map(namespace_injection).collect();
+        let mut workloads: BTreeMap<(String, String), WorkloadInjection> = BTreeMap::new();
+        for pod in &pods.items {
+            let ns = pod.metadata.namespace.clone().unwrap_or_default();
+            let workload = workload_of(pod).unwrap_or(format!(
+                "Pod/{}",
+                pod.metadata.name.clone().unwrap_or_default()
+            ));
+            let mesh = pod_mesh(pod).map(|(mesh, _)| mesh);
+            let enabled = namespaces
+                .iter()
+                .find(|n| n.namespace == ns)
+                .map(|n| !n.enabled.is_empty())
+                .unwrap_or(false);
+            let entry =
+                workloads
+                    .entry((ns.clone(), workload.clone()))
+                    .or_insert(WorkloadInjection {
+                        namespace: ns,
+                        workload,
+                        mesh: None,
+                        pods: 0,
+                        injected: 0,
+                        opted_out: false,
+                    });
+            entry.pods += 1;
+            if mesh.is_some() {
+                entry.injected += 1;
+                entry.mesh = mesh;
+            }
+            entry.opted_out = entry.opted_out || (enabled && opts_out(pod));
+        }
+
+        Ok(InjectionReport {
+            installed: installed_meshes(&client).await,
+            namespaces,
+            workloads: workloads.into_values().collect(),
+        })
+    }
+
+    /// Expands a mesh host to its FQDN, resolving short names relative to the
+    /// namespace of the object declaring them.
+    fn qualify(host: &str, namespace: &str) -> String {
+        if host.starts_with('*') || host.ends_with(CLUSTER_DOMAIN) {
+            return host.to_string();
+        }
+        match host.matches('.').count() {
+            0 => format!("{}.{}.{}", host, namespace, CLUSTER_DOMAIN),
+            1 => format!("{}.{}", host, CLUSTER_DOMAIN),
+            _ if host.ends_with(".svc") => format!("{}.cluster.local", host),
+            _ => host.to_string(),
+        }
+    }
+
+    fn host_matches(host: &str, namespace: &str, fqdn: &str) -> bool {
+        let host = qualify(host, namespace);
+        match host.strip_prefix('*') {
+            Some(suffix) => fqdn.ends_with(suffix),
+            None => host == fqdn,
+        }
+    }
+
+    fn strings(value: &Value) -> Vec<String> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect()
+    }
+
+    fn route_of(object: &DynamicObject, kind: &str, matched_by: &str) -> MeshRoute {
+        MeshRoute {
+            kind: kind.to_string(),
+            namespace: object.metadata.namespace.clone().unwrap_or_default(),
+            name: object.metadata.name.clone().unwrap_or_default(),
+            matched_by: matched_by.to_string(),
+            hosts: strings(&object.data["spec"]["hosts"]),
+            gateways: strings(&object.data["spec"]["gateways"]),
+        }
+    }
+
+    /// Route destinations of a VirtualService across its http, tcp and tls
+    /// sections.
+    fn destinations(spec: &Value) -> Vec<String> {
+        ["http", "tcp", "tls"]
+            .iter()
+            .flat_map(|section| spec[section].as_array().cloned().unwrap_or_default())
+            .flat_map(|rule| {
+                let mut hosts: Vec<String> = rule["route"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|r| r["destination"]["host"].as_str().map(|h| h.to_string()))
+                    .collect();
+                if let Some(mirror) = rule["mirror"]["host"].as_str() {
+                    hosts.push(mirror.to_string());
+                }
+                hosts
+            })
+            .collect()
+    }
+
+    /// Lists the Istio VirtualServices and DestinationRules and the Linkerd
+    /// ServiceProfile that apply to a Service. The cluster domain is assumed
+    /// to be `cluster.local`.
+    pub async fn routes_for_service(
+        client: Client,
+        namespace: String,
+        service: String,
+    ) -> Result<Vec<MeshRoute>, KubiousError> {
+        Api::<Service>::namespaced(client.clone(), namespace.as_str())
+            .get(service.as_str())
+            .await
+            .context("Unknown service")?;
+        let fqdn = format!("{}.{}.{}", service, namespace, CLUSTER_DOMAIN);
+        let mut routes: Vec<MeshRoute> = Vec::new();
+
+        if let Some(api) = mesh_api(
+            &client,
+            "networking.istio.io",
+            "v1beta1",
+            "VirtualService",
+            None,
+        )
+        .await
+        {
+            let services = api
+                .list(&ListParams::default())
+                .await
+                .context("Failed to list VirtualServices.")?;
+            for vs in &services.items {
+                let ns = vs.metadata.namespace.clone().unwrap_or_default();
+                let spec = &vs.data["spec"];
+                if strings(&spec["hosts"])
+                    .iter()
+                    .any(|h| host_matches(h, ns.as_str(), fqdn.as_str()))
+                {
+                    routes.push(route_of(vs, "VirtualService", "host"));
+                } else if destinations(spec)
+                    .iter()
+                    .any(|h| host_matches(h, ns.as_str(), fqdn.as_str()))
+                {
+                    routes.push(route_of(vs, "VirtualService", "destination"));
+                }
+            }
+        }
+
+        if let Some(api) = mesh_api(
+            &client,
+            "networking.istio.io",
+            "v1beta1",
+            "DestinationRule",
+            None,
+        )
+        .await
+        {
+            let rules = api
+                .list(&ListParams::default())
+                .await
+                .context("Failed to list DestinationRules.")?;
+            for rule in &rules.items {
+                let ns = rule.metadata.namespace.clone().unwrap_or_default();
+                if let Some(host) = rule.data["spec"]["host"].as_str() {
+                    if host_matches(host, ns.as_str(), fqdn.as_str()) {
+                        let mut route = route_of(rule, "DestinationRule", "host");
+                        route.hosts = vec![host.to_string()];
+                        routes.push(route);
+                    }
+                }
+            }
+        }
+
+        // Linkerd only honours the ServiceProfile named after the service's
+        // FQDN in the service's own namespace.
+        if let Some(api) = mesh_api(
+            &client,
+            "linkerd.io",
+            "v1alpha2",
+            "ServiceProfile",
+            Some(namespace.as_str()),
+        )
+        .await
+        {
+            if let Ok(Some(profile)) = api.get_opt(fqdn.as_str()).await {
+                let mut route = route_of(&profile, "ServiceProfile", "host");
+                route.hosts = vec![fqdn.clone()];
+                routes.push(route);
+            }
+        }
+
+        if routes.is_empty() && installed_meshes(&client).await.is_empty() {
+            return Err("No service mesh CRDs are installed".into());
+        }
+        Ok(routes)
+    }
+
+    /// Issues a plain HTTP/1.0 GET against a pod-local port through a
+    /// port-forward and returns the status code and body.
+    async fn admin_get(
+        pods: &Api<Pod>,
+        pod: &str,
+        port: u16,
+        path: &str,
+    ) -> Result<(u16, String), KubiousError> {
+        let request = async {
+            let mut forwarder = pods
+                .portforward(pod, &[port])
+                .await
+                .context(format!("Failed to port-forward to {}", port))?;
+            let mut stream = forwarder
+                .take_stream(port)
+                .ok_or("Port-forward stream unavailable".to_string())?;
+            stream
+                .write_all(format!("GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path).as_bytes())
+                .await
+                .context("Failed to send admin request.")?;
+            let mut response = Vec::new();
+            stream
+                .read_to_end(&mut response)
+                .await
+                .context("Failed to read admin response.")?;
+            drop(stream);
+            let _ = forwarder.join().await;
+            Ok::<Vec<u8>, String>(response)
+        };
+        let response = timeout(ADMIN_TIMEOUT, request)
+            .await
+            .or(Err(format!("Timed out querying {}", path)))??;
+        let response = String::from_utf8_lossy(&response).to_string();
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or(format!("Malformed response from {}", path))?;
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or(format!("Malformed response from {}", path))?;
+        Ok((status, body.to_string()))
+    }
+
+    async fn envoy_status(pods: &Api<Pod>, pod: &str, status: &mut ProxyStatus) {
+        match admin_get(pods, pod, ENVOY_ADMIN_PORT, "/server_info").await {
+            Ok((200, body)) => {
+                let info: Value = serde_json::from_str(body.as_str()).unwrap_or_default();
+                status.state = info["state"].as_str().map(|s| s.to_string());
+                status.ready = status.state.as_deref() == Some("LIVE");
+                status.version = info["version"].as_str().map(|s| s.to_string());
+                status.uptime_seconds = info["uptime_current_epoch"]
+                    .as_str()
+                    .and_then(|u| u.trim_end_matches('s').parse::<f64>().ok())
+                    .map(|u| u as i64);
+            }
+            Ok((code, _)) => status
+                .errors
+                .push(format!("/server_info returned {}", code)),
+            Err(e) => status.errors.push(e.to_string()),
+        }
+        match admin_get(pods, pod, ENVOY_ADMIN_PORT, "/clusters?format=json").await {
+            Ok((200, body)) => {
+                let clusters: Value = serde_json::from_str(body.as_str()).unwrap_or_default();
+                let statuses = clusters["cluster_statuses"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default();
+                status.upstreams = Some(statuses.len());
+                status.unhealthy_upstreams = Some(
+                    statuses
+                        .iter()
+                        .filter(|c| {
+                            c["host_statuses"]
+                                .as_array()
+                                .into_iter()
+                                .flatten()
+                                .any(|h| {
+                                    h["health_status"]["eds_health_status"]
+                                        .as_str()
+                                        .map(|s| s != "HEALTHY")
+                                        .unwrap_or(false)
+                                        || h["health_status"]["failed_outlier_check"] == true
+                                })
+                        })
+                        .count(),
+                );
+            }
+            Ok((code, _)) => status.errors.push(format!("/clusters returned {}", code)),
+            Err(e) => status.errors.push(e.to_string()),
+        }
+    }
+
+    /// Finds the first sample of a Prometheus metric, returning its series
+    /// and value.
+    fn metric(metrics: &str, name: &str) -> Option<(String, f64)> {
+        metrics
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .find(|l| l.starts_with(name) && l[name.len()..].starts_with([' ', '{']))
+            .and_then(|line| {
+                let (series, value) = line.rsplit_once(' ')?;
+                Some((series.to_string(), value.parse::<f64>().ok()?))
+            })
+    }
+
+    fn label(series: &str, name: &str) -> Option<String> {
+        let start = series.find(format!("{}=\"", name).as_str())? + name.len() + 2;
+        let end = series[start..].find('"')?;
+        Some(series[start..start + end].to_string())
+    }
+
+    async fn linkerd_status(pods: &Api<Pod>, pod: &str, status: &mut ProxyStatus) {
+        match admin_get(pods, pod, LINKERD_ADMIN_PORT, "/ready").await {
+            Ok((code, body)) => {
+                status.ready = code == 200;
+                status.state = Some(body.trim().to_string()).filter(|s| !s.is_empty());
+            }
+            Err(e) => status.errors.push(e.to_string()),
+        }
+        match admin_get(pods, pod, LINKERD_ADMIN_PORT, "/metrics").await {
+            Ok((200, metrics)) => {
+                status.version = metric(metrics.as_str(), "proxy_build_info")
+                    .and_then(|(series, _)| label(series.as_str(), "version"));
+                let started = metric(metrics.as_str(), "process_start_time_seconds");
+                status.uptime_seconds = started
+                    .map(|(_, start)| k8s_openapi::chrono::Utc::now().timestamp() - start as i64);
+                status.identity_expires_at = metric(
+                    metrics.as_str(),
+                    "identity_cert_expiration_timestamp_seconds",
+                )
+                .map(|(_, expiry)| expiry as i64);
+                let endpoints: Vec<&str> = metrics
+                    .lines()
+                    .filter(|l| l.starts_with("outbound_http_balancer_endpoints{"))
+                    .collect();
+                if !endpoints.is_empty() {
+                    let count = |state: &str| {
+                        endpoints
+                            .iter()
+                            .filter(|l| label(l, "endpoint_state").as_deref() == Some(state))
+                            .filter_map(|l| l.rsplit_once(' ')?.1.parse::<f64>().ok())
+                            .sum::<f64>() as usize
+                    };
+                    status.upstreams = Some(count("ready") + count("pending"));
+                    status.unhealthy_upstreams = Some(count("pending"));
+                }
+            }
+            Ok((code, _)) => status.errors.push(format!("/metrics returned {}", code)),
+            Err(e) => status.errors.push(e.to_string()),
+        }
+    }
+
+    /// Queries the admin endpoint of the mesh proxy running in a pod: Envoy
+    /// on port 15000 for Istio, or the Linkerd proxy on port 4191.
+    pub async fn proxy_status(
+        client: Client,
+        namespace: String,
+        name: String,
+    ) -> Result<ProxyStatus, KubiousError> {
+        let pods = Api::<Pod>::namespaced(client, namespace.as_str());
+        let pod = pods.get(name.as_str()).await.context("Unknown pod")?;
+        let (mesh, container) = pod_mesh(&pod).ok_or("Pod has no mesh proxy".to_string())?;
+        let mut status = ProxyStatus {
+            admin_port: match mesh {
+                Mesh::Istio => ENVOY_ADMIN_PORT,
+                Mesh::Linkerd => LINKERD_ADMIN_PORT,
+            },
+            mesh: mesh.clone(),
+            container,
+            ready: false,
+            version: None,
+            state: None,
+            uptime_seconds: None,
+            upstreams: None,
+            unhealthy_upstreams: None,
+            identity_expires_at: None,
+            errors: Vec::new(),
+        };
+        match mesh {
+            Mesh::Istio => envoy_status(&pods, name.as_str(), &mut status).await,
+            Mesh::Linkerd => linkerd_status(&pods, name.as_str(), &mut status).await,
+        }
+        Ok(status)
+    }
+}
@@ -1,17 +1,311 @@
 pub mod kube_api {
-    use crate::{api::app_state::AppState, CommandHandler};
+    use crate::{
+        api::{app_settings::Hibernation, app_state::AppState},
+        util::kubious_error::KubiousError,
+        CommandHandler,
+    };
     use k8s_openapi::apimachinery::pkg::apis::meta::v1::APIGroup;
+    use kube::api::GroupVersionKind;
     use serde::{Deserialize, Serialize};
     use tauri::Manager;
 
+    use super::{
+        admission_policies, api_service_health, cert_manager, config_editor::{self, ConfigEdit, NewConfigObject}, config_rollout::{self, ConfigChange, ConfigKind, RolloutStep}, cleanup_advisor, control_plane_health, gateway_routes, pod_effective_config,
+        rbac_capabilities::{self, CapabilityCache}, crd_forms, extended_resources, image_pull_diagnostics, keda_insights, label_taxonomy,
+        multi_cluster_logs::{self, LogTails}, namespace_hibernation, node_operations::{self, DrainOptions, NodeTaint}, node_placement, removal_simulation, pod_startup, service_mesh,
+        port_forwards::{self, ForwardTarget, PortForwardManager}, revision_logs, scratch_namespaces,
+        security_inspector, service_probe::{self, ProbeRequest},
+        termination_history::{self, TerminationQuery, TerminationWatches},
+        velero_backups, volume_recovery, vpa_insights, workload_parity, workload_rollout, workload_scale,
+    };
+
     #[derive(Serialize, Deserialize, Clone, Debug)]
     #[serde(tag = "command")]
     pub enum KubeCommand {
         SupportedGroups {},
-        SupportedResources { group: APIGroup }
+        SupportedResources { group: APIGroup },
+        ExtendedResources {},
+        NodePlacement {},
+        ControlPlaneHealth {},
+        ApiServiceHealth {},
+        ListVolumes {},
+        InspectSecurityContext { namespace: String, kind: String, name: String },
+        EffectivePodConfig { namespace: String, pod: Option<String>, manifest: Option<serde_json::Value> },
+        HibernateNamespace { namespace: String },
+        RestoreNamespace { namespace: String },
+        ListHibernations {},
+        /// Creates a namespace on the current cluster that the app deletes
+        /// once `ttl_minutes` have passed.
+        CreateScratchNamespace { prefix: Option<String>, ttl_minutes: u32 },
+        ExtendScratchNamespace { cluster: String, namespace: String, ttl_minutes: u32 },
+        /// Deletes a scratch namespace now, e.g. to confirm an expired one.
+        DeleteScratchNamespace { cluster: String, namespace: String },
+        ListScratchNamespaces {},
+        SetScratchAutoDelete { enabled: bool },
+        CleanupAdvice { namespace: Option<String>, min_age_days: Option<i64> },
+        ApplyCleanup { namespace: Option<String>, min_age_days: Option<i64>, ids: Vec<String> },
+        SetReclaimPolicy { name: String, policy: String },
+        MakeVolumeAvailable { name: String },
+        CapabilityMap { namespaces: Vec<String>, refresh: bool },
+        VpaRecommendations { namespace: Option<String>, threshold: Option<f64>, only_flagged: bool },
+        AdoptVpaRecommendation { namespace: String, name: String, confirm: bool },
+        DiagnoseImagePulls { namespace: Option<String>, pod: Option<String> },
+        PodStartupTimeline { namespace: String, name: String },
+        ListCertificates { namespace: Option<String> },
+        InspectCertificate { namespace: String, name: String },
+        RenewCertificate { namespace: String, name: String },
+        MeshInjectionStatus { namespace: Option<String> },
+        MeshRoutesForService { namespace: String, service: String },
+        MeshProxyStatus { namespace: String, pod: String },
+        ProbeService { namespace: String, service: String, port: Option<String>, probe: ProbeRequest },
+        ListVeleroBackups { velero_namespace: Option<String> },
+        ListVeleroRestores { velero_namespace: Option<String> },
+        CreateVeleroBackup {
+            velero_namespace: Option<String>,
+            namespace: String,
+            name: Option<String>,
+            ttl: Option<String>,
+            storage_location: Option<String>
+        },
+        VeleroBackupProgress { velero_namespace: Option<String>, name: String },
+        VeleroRestoreProgress { velero_namespace: Option<String>, name: String },
+        KedaScalers { namespace: Option<String> },
+        SetImage {
+            namespace: String,
+            kind: String,
+            workload: String,
+            container: String,
+            image: String,
+            timeout: Option<u64>
+        },
+        /// Sets the replicas of a Deployment, StatefulSet or ReplicaSet.
+        ScaleWorkload { namespace: String, kind: String, name: String, replicas: i32 },
+        WorkloadReplicas { namespace: String, kind: String, name: String },
+        /// Restarts the pods of a Deployment, StatefulSet or DaemonSet.
+        RolloutRestart { namespace: String, kind: String, name: String },
+        RolloutStatus { namespace: String, kind: String, name: String },
+        RolloutHistory { namespace: String, kind: String, name: String },
+        /// Rolls back to `revision`, the previous one if omitted.
+        RolloutUndo { namespace: String, kind: String, name: String, revision: Option<i64> },
+        /// Applies ConfigMap and Secret changes, then restarts `steps` in
+        /// order, waiting for each to be ready and stopping at the first
+        /// failure.
+        ConfigRollout { namespace: String, changes: Vec<ConfigChange>, steps: Vec<RolloutStep> },
+        GetConfigData { kind: ConfigKind, namespace: String, name: String },
+        CreateConfigData { namespace: String, object: NewConfigObject },
+        /// Renames, removes and sets keys of a ConfigMap or Secret. Secret
+        /// values are given and returned decoded.
+        EditConfigData { kind: ConfigKind, namespace: String, name: String, edit: ConfigEdit },
+        CordonNode { name: String },
+        UncordonNode { name: String },
+        AddNodeTaint { name: String, taint: NodeTaint },
+        /// Removes taints with `key`, only the one with `effect` if given.
+        RemoveNodeTaint { name: String, key: String, effect: Option<String> },
+        /// Cordons a node and evicts its pods. Progress is pushed on
+        /// `node-drain`.
+        DrainNode { name: String, #[serde(default)] options: DrainOptions },
+        /// Reports where the pods of `nodes` would go if the nodes were
+        /// removed, and which would be left Pending.
+        SimulateNodeRemoval { nodes: Vec<String> },
+        CrdFormModel { crd: String, version: Option<String> },
+        LabelTaxonomy {
+            namespace: Option<String>,
+            kinds: Option<Vec<GroupVersionKind>>,
+            annotations: bool,
+            max_values: Option<usize>
+        },
+        StartTerminationLog {},
+        StopTerminationLog { cluster: Option<String> },
+        ListTerminationLogs {},
+        TerminationHistory {
+            cluster: Option<String>,
+            namespace: Option<String>,
+            workload: Option<String>,
+            container: Option<String>,
+            limit: Option<usize>
+        },
+        StartLogTail {
+            clusters: Vec<String>,
+            namespace: Option<String>,
+            selector: String,
+            container: Option<String>,
+            tail_lines: Option<i64>
+        },
+        StopLogTail { tail: String },
+        ListLogTails {},
+        /// Forwards a local port to a pod or service in `cluster`, the
+        /// current config if omitted.
+        StartPortForward {
+            cluster: Option<String>,
+            namespace: String,
+            target: ForwardTarget,
+            local_port: Option<u16>
+        },
+        StopPortForward { id: String },
+        ListPortForwards {},
+        /// Logs of a Deployment's current and previous revision.
+        RevisionLogs { namespace: String, deployment: String, container: Option<String>, tail_lines: Option<i64> },
+        ListAdmissionPolicies {},
+        /// Dry-runs a manifest against the cluster's ValidatingAdmissionPolicies.
+        TestAdmissionPolicies { manifest: serde_json::Value, namespace: Option<String> },
+        GatewayRouteReport { namespace: Option<String> },
+        /// Lines up a Deployment, StatefulSet or DaemonSet's spec across
+        /// clusters for config parity audits.
+        CompareWorkloadAcrossClusters {
+            clusters: Vec<String>,
+            namespace: String,
+            kind: String,
+            name: String,
+            only_differences: Option<bool>
+        }
     }
     impl CommandHandler for KubeCommand {
-        async fn execute(&self, handle: &tauri::AppHandle) -> Result<serde_json::Value, String> {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<serde_json::Value, KubiousError> {
+            match self {
+                KubeCommand::TerminationHistory { cluster, namespace, workload, container, limit } => {
+                    let query = TerminationQuery {
+                        cluster: cluster.clone(),
+                        namespace: namespace.clone(),
+                        workload: workload.clone(),
+                        container: container.clone(),
+                        limit: *limit,
+                    };
+                    return self.wrap_in_value(termination_history::query(handle, &query));
+                }
+                KubeCommand::StartLogTail { clusters, namespace, selector, container, tail_lines } => {
+                    let state = handle.state::<AppState>();
+                    let mut clients = Vec::new();
+                    for cluster in clusters {
+                        clients.push((cluster.clone(), state.client_for(cluster.as_str()).await));
+                    }
+                    return self.wrap_in_value(
+                        multi_cluster_logs::start(
+                            handle,
+                            clients,
+                            namespace.clone(),
+                            selector.clone(),
+                            container.clone(),
+                            *tail_lines,
+                        )
+                        .await,
+                    );
+                }
+                KubeCommand::CompareWorkloadAcrossClusters { clusters, namespace, kind, name, only_differences } => {
+                    if !["Deployment", "StatefulSet", "DaemonSet"].contains(&kind.as_str()) {
+                        return Err("Only Deployments, StatefulSets and DaemonSets can be compared".into());
+                    }
+                    let state = handle.state::<AppState>();
+                    let mut clients = Vec::new();
+                    for cluster in clusters {
+                        clients.push((cluster.clone(), state.client_for(cluster.as_str()).await));
+                    }
+                    return self.wrap_in_value(
+                        workload_parity::compare(
+                            clients,
+                            kind.clone(),
+                            namespace.clone(),
+                            name.clone(),
+                            only_differences.unwrap_or(false),
+                        )
+                        .await,
+                    );
+                }
+                KubeCommand::StopLogTail { tail } => {
+                    return self.wrap_in_value(Ok(multi_cluster_logs::stop(handle, tail.as_str())));
+                }
+                KubeCommand::StartPortForward { cluster, namespace, target, local_port } => {
+                    let state = handle.state::<AppState>();
+                    let Some(cluster) = cluster.clone().or(state.get_current_config().map(|(key, _)| key)) else {
+                        return Err(KubiousError::config("No config selected."));
+                    };
+                    let Some(client) = state.client_for(cluster.as_str()).await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    return self.wrap_in_value(
+                        port_forwards::start(handle, client, cluster, namespace.clone(), target.clone(), *local_port).await,
+                    );
+                }
+                KubeCommand::StopPortForward { id } => {
+                    return self.wrap_in_value(Ok(port_forwards::stop(handle, id.as_str())));
+                }
+                KubeCommand::ListPortForwards {} => {
+                    return self.wrap_in_value(Ok(handle.state::<PortForwardManager>().active()));
+                }
+                KubeCommand::RevisionLogs { namespace, deployment, container, tail_lines } => {
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) = (state.client().await, state.get_current_config()) else {
+                        return Err(KubiousError::no_client());
+                    };
+                    return self.wrap_in_value(
+                        revision_logs::compare(
+                            handle,
+                            client,
+                            cluster,
+                            namespace.clone(),
+                            deployment.clone(),
+                            container.clone(),
+                            *tail_lines,
+                        )
+                        .await,
+                    );
+                }
+                KubeCommand::ListHibernations {} => {
+                    return self.wrap_in_value(Ok(handle.state::<AppState>().get_settings().hibernations));
+                }
+                KubeCommand::ListScratchNamespaces {} => {
+                    let scratches = handle.state::<AppState>().get_settings().scratch_namespaces;
+                    return self.wrap_in_value(Ok(scratch_namespaces::statuses(scratches)));
+                }
+                KubeCommand::SetScratchAutoDelete { enabled } => {
+                    let state = handle.state::<AppState>();
+                    state.set_scratch_auto_delete(*enabled);
+                    state.save_state(handle.clone())?;
+                    return self.wrap_in_value(Ok(*enabled));
+                }
+                KubeCommand::ExtendScratchNamespace { cluster, namespace, ttl_minutes } => {
+                    let state = handle.state::<AppState>();
+                    let Some(scratch) = state.scratch_namespace(cluster, namespace) else {
+                        return Err("Not a scratch namespace".into());
+                    };
+                    let Some(client) = state.client_for(cluster.as_str()).await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let extended = scratch_namespaces::extend(client, scratch, *ttl_minutes).await?;
+                    state.put_scratch_namespace(extended.clone());
+                    state.save_state(handle.clone())?;
+                    return self.wrap_in_value(Ok(extended));
+                }
+                KubeCommand::DeleteScratchNamespace { cluster, namespace } => {
+                    let state = handle.state::<AppState>();
+                    let Some(scratch) = state.scratch_namespace(cluster, namespace) else {
+                        return Err("Not a scratch namespace".into());
+                    };
+                    let Some(client) = state.client_for(cluster.as_str()).await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let existed = scratch_namespaces::delete(client, &scratch).await?;
+                    state.remove_scratch_namespace(cluster, namespace);
+                    state.save_state(handle.clone())?;
+                    return self.wrap_in_value(Ok(existed));
+                }
+                KubeCommand::ListLogTails {} => {
+                    return self.wrap_in_value(Ok(handle.state::<LogTails>().active()));
+                }
+                KubeCommand::ListTerminationLogs {} => {
+                    return self.wrap_in_value(Ok(handle.state::<TerminationWatches>().active()));
+                }
+                KubeCommand::StopTerminationLog { cluster } => {
+                    let cluster = cluster
+                        .clone()
+                        .or(handle.state::<AppState>().get_current_config().map(|(key, _)| key));
+                    return match cluster {
+                        Some(cluster) => self.wrap_in_value(Ok(termination_history::stop(handle, cluster.as_str()))),
+                        None => Err(KubiousError::config("No cluster selected")),
+                    };
+                }
+                _ => {}
+            }
+
             if let Some(client) = handle.state::<AppState>().client().await {
                 match self {
                     KubeCommand::SupportedGroups {} => {
@@ -25,7 +319,7 @@ pub mod kube_api {
                             });
                             self.wrap_in_value(Ok(all_groups))
                         } else {
-                            Err("Failed to list groups.".to_string())
+                            Err("Failed to list groups.".into())
                         }
                     }
                     KubeCommand::SupportedResources { group } => {
@@ -36,13 +330,13 @@ pub mod kube_api {
                                     {
                                         self.wrap_in_value(Ok(resources.resources))
                                     } else {
-                                        Err("Failed to list resources.".to_string())
+                                        Err("Failed to list resources.".into())
                                     }
                                 } else {
-                                    Err("No valid versions".to_string())
+                                    Err("No valid versions".into())
                                 }
                             } else {
-                                Err("Failed to fetch API version".to_string())
+                                Err("Failed to fetch API version".into())
                             }
                         } else {
                             if let Ok(resources) = client
@@ -61,16 +355,379 @@ pub mod kube_api {
                             {
                                 self.wrap_in_value(Ok(resources.resources))
                             } else {
-                                Err("Failed to list resources.".to_string())
+                                Err("Failed to list resources.".into())
                             }
                         }
                         
                     }
-                    
+                    KubeCommand::ExtendedResources {} => {
+                        self.wrap_in_value(extended_resources::report(client).await)
+                    }
+                    KubeCommand::NodePlacement {} => {
+                        self.wrap_in_value(node_placement::report(client).await)
+                    }
+                    KubeCommand::ControlPlaneHealth {} => {
+                        self.wrap_in_value(Ok(control_plane_health::check(client).await))
+                    }
+                    KubeCommand::ApiServiceHealth {} => {
+                        self.wrap_in_value(api_service_health::report(client).await)
+                    }
+                    KubeCommand::GatewayRouteReport { namespace } => {
+                        self.wrap_in_value(gateway_routes::analyze(client, namespace.clone()).await)
+                    }
+                    KubeCommand::ListAdmissionPolicies {} => {
+                        self.wrap_in_value(admission_policies::list(client).await)
+                    }
+                    KubeCommand::TestAdmissionPolicies { manifest, namespace } => {
+                        self.wrap_in_value(admission_policies::test(client, manifest, namespace.clone()).await)
+                    }
+                    KubeCommand::CleanupAdvice { namespace, min_age_days } => {
+                        self.wrap_in_value(cleanup_advisor::analyze(client, namespace.clone(), *min_age_days).await)
+                    }
+                    KubeCommand::ApplyCleanup { namespace, min_age_days, ids } => {
+                        self.wrap_in_value(
+                            cleanup_advisor::apply(client, namespace.clone(), *min_age_days, ids.clone()).await,
+                        )
+                    }
+                    KubeCommand::HibernateNamespace { namespace } => {
+                        let state = handle.state::<AppState>();
+                        let Some((cluster, _)) = state.get_current_config() else {
+                            return Err(KubiousError::config("No cluster selected"));
+                        };
+                        let existing = state.hibernation(cluster.as_str(), namespace.as_str());
+                        let result =
+                            namespace_hibernation::hibernate(client, cluster, namespace.clone(), existing).await?;
+                        if let Some(hibernation) = result.hibernation.clone() {
+                            state.put_hibernation(hibernation);
+                        }
+                        state.save_state(handle.clone())?;
+                        self.wrap_in_value(Ok(result))
+                    }
+                    KubeCommand::CreateScratchNamespace { prefix, ttl_minutes } => {
+                        let state = handle.state::<AppState>();
+                        let Some((cluster, _)) = state.get_current_config() else {
+                            return Err(KubiousError::config("No cluster selected"));
+                        };
+                        let scratch = scratch_namespaces::create(client, cluster, prefix.clone(), *ttl_minutes).await?;
+                        state.put_scratch_namespace(scratch.clone());
+                        state.save_state(handle.clone())?;
+                        self.wrap_in_value(Ok(scratch))
+                    }
+                    KubeCommand::RestoreNamespace { namespace } => {
+                        let state = handle.state::<AppState>();
+                        let Some((cluster, _)) = state.get_current_config() else {
+                            return Err(KubiousError::config("No cluster selected"));
+                        };
+                        let Some(hibernation) = state.hibernation(cluster.as_str(), namespace.as_str()) else {
+                            return Err("Namespace is not hibernated".into());
+                        };
+                        let result = namespace_hibernation::restore(client, hibernation.clone()).await;
+                        state.put_hibernation(result.hibernation.clone().unwrap_or(Hibernation {
+                            workloads: Vec::new(),
+                            ..hibernation
+                        }));
+                        state.save_state(handle.clone())?;
+                        self.wrap_in_value(Ok(result))
+                    }
+                    KubeCommand::InspectSecurityContext { namespace, kind, name } => {
+                        self.wrap_in_value(
+                            security_inspector::inspect(client, namespace.clone(), kind.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::EffectivePodConfig { namespace, pod, manifest } => {
+                        self.wrap_in_value(
+                            pod_effective_config::effective_config(client, namespace.clone(), pod.clone(), manifest.clone())
+                                .await,
+                        )
+                    }
+                    KubeCommand::ListVolumes {} => {
+                        self.wrap_in_value(volume_recovery::list_volumes(client).await)
+                    }
+                    KubeCommand::SetReclaimPolicy { name, policy } => {
+                        self.wrap_in_value(
+                            volume_recovery::set_reclaim_policy(client, name.clone(), policy.clone()).await,
+                        )
+                    }
+                    KubeCommand::MakeVolumeAvailable { name } => {
+                        self.wrap_in_value(volume_recovery::make_available(client, name.clone()).await)
+                    }
+                    KubeCommand::VpaRecommendations { namespace, threshold, only_flagged } => {
+                        self.wrap_in_value(
+                            vpa_insights::recommendations(client, namespace.clone(), *threshold, *only_flagged).await,
+                        )
+                    }
+                    KubeCommand::AdoptVpaRecommendation { namespace, name, confirm } => {
+                        self.wrap_in_value(
+                            vpa_insights::adopt_recommendation(client, namespace.clone(), name.clone(), *confirm).await,
+                        )
+                    }
+                    KubeCommand::DiagnoseImagePulls { namespace, pod } => {
+                        let state = handle.state::<AppState>();
+                        let stored = |registry: &str| state.registry_credential(registry);
+                        self.wrap_in_value(
+                            image_pull_diagnostics::diagnose(client, namespace.clone(), pod.clone(), &stored)
+                                .await,
+                        )
+                    }
+                    KubeCommand::PodStartupTimeline { namespace, name } => {
+                        self.wrap_in_value(
+                            pod_startup::startup_timeline(client, namespace.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::ListCertificates { namespace } => {
+                        self.wrap_in_value(cert_manager::list_certificates(client, namespace.clone()).await)
+                    }
+                    KubeCommand::InspectCertificate { namespace, name } => {
+                        self.wrap_in_value(
+                            cert_manager::inspect_certificate(client, namespace.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::RenewCertificate { namespace, name } => {
+                        self.wrap_in_value(
+                            cert_manager::renew_certificate(client, namespace.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::MeshInjectionStatus { namespace } => {
+                        self.wrap_in_value(service_mesh::injection_status(client, namespace.clone()).await)
+                    }
+                    KubeCommand::MeshRoutesForService { namespace, service } => {
+                        self.wrap_in_value(
+                            service_mesh::routes_for_service(client, namespace.clone(), service.clone()).await,
+                        )
+                    }
+                    KubeCommand::MeshProxyStatus { namespace, pod } => {
+                        self.wrap_in_value(service_mesh::proxy_status(client, namespace.clone(), pod.clone()).await)
+                    }
+                    KubeCommand::ProbeService { namespace, service, port, probe } => {
+                        self.wrap_in_value(
+                            service_probe::probe(client, namespace.clone(), service.clone(), port.clone(), probe.clone())
+                                .await,
+                        )
+                    }
+                    KubeCommand::ListVeleroBackups { velero_namespace } => {
+                        self.wrap_in_value(velero_backups::list_backups(client, velero_namespace.clone()).await)
+                    }
+                    KubeCommand::ListVeleroRestores { velero_namespace } => {
+                        self.wrap_in_value(velero_backups::list_restores(client, velero_namespace.clone()).await)
+                    }
+                    KubeCommand::CreateVeleroBackup { velero_namespace, namespace, name, ttl, storage_location } => {
+                        self.wrap_in_value(
+                            velero_backups::create_backup(
+                                client,
+                                velero_namespace.clone(),
+                                namespace.clone(),
+                                name.clone(),
+                                ttl.clone(),
+                                storage_location.clone(),
+                            )
+                            .await,
+                        )
+                    }
+                    KubeCommand::VeleroBackupProgress { velero_namespace, name } => {
+                        self.wrap_in_value(
+                            velero_backups::backup_progress(client, velero_namespace.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::VeleroRestoreProgress { velero_namespace, name } => {
+                        self.wrap_in_value(
+                            velero_backups::restore_progress(client, velero_namespace.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::KedaScalers { namespace } => {
+                        self.wrap_in_value(keda_insights::scalers(client, namespace.clone()).await)
+                    }
+                    KubeCommand::SetImage { namespace, kind, workload, container, image, timeout } => {
+                        self.wrap_in_value(
+                            workload_rollout::set_image(
+                                client,
+                                namespace.clone(),
+                                kind.clone(),
+                                workload.clone(),
+                                container.clone(),
+                                image.clone(),
+                                *timeout,
+                            )
+                            .await,
+                        )
+                    }
+                    KubeCommand::ScaleWorkload { namespace, kind, name, replicas } => {
+                        self.wrap_in_value(
+                            workload_scale::scale(client, namespace.clone(), kind.clone(), name.clone(), *replicas)
+                                .await,
+                        )
+                    }
+                    KubeCommand::WorkloadReplicas { namespace, kind, name } => {
+                        self.wrap_in_value(
+                            workload_scale::status(client, namespace.clone(), kind.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::RolloutRestart { namespace, kind, name } => {
+                        self.wrap_in_value(
+                            workload_rollout::restart(client, namespace.clone(), kind.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::RolloutStatus { namespace, kind, name } => {
+                        self.wrap_in_value(
+                            workload_rollout::status(client, namespace.clone(), kind.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::RolloutHistory { namespace, kind, name } => {
+                        self.wrap_in_value(
+                            workload_rollout::history(client, namespace.clone(), kind.clone(), name.clone()).await,
+                        )
+                    }
+                    KubeCommand::RolloutUndo { namespace, kind, name, revision } => {
+                        self.wrap_in_value(
+                            workload_rollout::undo(client, namespace.clone(), kind.clone(), name.clone(), *revision)
+                                .await,
+                        )
+                    }
+                    KubeCommand::ConfigRollout { namespace, changes, steps } => {
+                        self.wrap_in_value(
+                            config_rollout::run(client, namespace.clone(), changes.clone(), steps.clone()).await,
+                        )
+                    }
+                    KubeCommand::GetConfigData { kind, namespace, name } => {
+                        self.wrap_in_value(config_editor::get(client, *kind, namespace.as_str(), name.as_str()).await)
+                    }
+                    KubeCommand::CreateConfigData { namespace, object } => {
+                        self.wrap_in_value(config_editor::create(client, namespace.as_str(), object.clone()).await)
+                    }
+                    KubeCommand::EditConfigData { kind, namespace, name, edit } => {
+                        self.wrap_in_value(
+                            config_editor::edit(client, *kind, namespace.as_str(), name.as_str(), edit.clone()).await,
+                        )
+                    }
+                    KubeCommand::CordonNode { name } => {
+                        self.wrap_in_value(node_operations::set_unschedulable(client, name.as_str(), true).await)
+                    }
+                    KubeCommand::UncordonNode { name } => {
+                        self.wrap_in_value(node_operations::set_unschedulable(client, name.as_str(), false).await)
+                    }
+                    KubeCommand::AddNodeTaint { name, taint } => {
+                        self.wrap_in_value(node_operations::add_taint(client, name.as_str(), taint.clone()).await)
+                    }
+                    KubeCommand::RemoveNodeTaint { name, key, effect } => {
+                        self.wrap_in_value(
+                            node_operations::remove_taint(client, name.as_str(), key.as_str(), effect.as_deref()).await,
+                        )
+                    }
+                    KubeCommand::DrainNode { name, options } => {
+                        self.wrap_in_value(node_operations::drain(handle, client, name.clone(), options.clone()).await)
+                    }
+                    KubeCommand::SimulateNodeRemoval { nodes } => {
+                        self.wrap_in_value(removal_simulation::simulate(client, nodes.clone()).await)
+                    }
+                    KubeCommand::CrdFormModel { crd, version } => {
+                        self.wrap_in_value(crd_forms::form_model(client, crd.clone(), version.clone()).await)
+                    }
+                    KubeCommand::LabelTaxonomy { namespace, kinds, annotations, max_values } => {
+                        self.wrap_in_value(
+                            label_taxonomy::scan(client, namespace.clone(), kinds.clone(), *annotations, *max_values)
+                                .await,
+                        )
+                    }
+                    KubeCommand::CapabilityMap { namespaces, refresh } => {
+                        let Some((cluster, config)) = handle.state::<AppState>().get_current_config() else {
+                            return Err(KubiousError::config("No cluster selected"));
+                        };
+                        self.wrap_in_value(Ok(rbac_capabilities::capabilities(
+                            &handle.state::<CapabilityCache>(),
+                            client,
+                            cluster,
+                            &config,
+                            namespaces.clone(),
+                            *refresh,
+                        )
+                        .await))
+                    }
+                    KubeCommand::StartTerminationLog {} => {
+                        if let Some((cluster, _)) = handle.state::<AppState>().get_current_config() {
+                            self.wrap_in_value(Ok(termination_history::start(handle, client, cluster)))
+                        } else {
+                            Err(KubiousError::config("No cluster selected"))
+                        }
+                    }
+                    _ => Err("Unsupported command".into()),
                 }
             } else {
-                Err("Could not establish connection.".to_string())
+                Err(KubiousError::no_client())
             }
         }
     }
 }
+
+mod extended;
+pub use extended::extended_resources;
+mod vpa;
+pub use vpa::vpa_insights;
+mod image_pull;
+pub use image_pull::image_pull_diagnostics;
+mod startup;
+pub use startup::pod_startup;
+mod certmanager;
+pub use certmanager::cert_manager;
+mod mesh;
+pub use mesh::service_mesh;
+mod probe;
+pub use probe::service_probe;
+mod velero;
+pub use velero::velero_backups;
+mod keda;
+pub use keda::keda_insights;
+mod crd_form;
+pub use crd_form::crd_forms;
+mod taxonomy;
+pub use taxonomy::label_taxonomy;
+mod placement;
+pub use placement::node_placement;
+mod rollout;
+pub use rollout::workload_rollout;
+mod pipeline;
+pub use pipeline::config_rollout;
+mod config_data;
+pub use config_data::config_editor;
+mod control_plane;
+pub use control_plane::control_plane_health;
+mod aggregation;
+pub use aggregation::api_service_health;
+mod effective;
+pub use effective::pod_effective_config;
+mod security;
+pub use security::security_inspector;
+mod volumes;
+pub use volumes::volume_recovery;
+mod cleanup;
+pub use cleanup::cleanup_advisor;
+mod hibernate;
+pub use hibernate::namespace_hibernation;
+mod capabilities;
+pub use capabilities::rbac_capabilities;
+mod favorites;
+pub use favorites::favorite_watches;
+mod log_tail;
+pub use log_tail::multi_cluster_logs;
+mod port_forward;
+pub use port_forward::port_forwards;
+mod terminations;
+pub use terminations::termination_history;
+mod revisions;
+pub use revisions::revision_logs;
+mod admission;
+pub use admission::admission_policies;
+mod gateway;
+pub use gateway::gateway_routes;
+mod triggers;
+pub use triggers::rollout_triggers;
+mod budgets;
+pub use budgets::namespace_budgets;
+mod parity;
+pub use parity::workload_parity;
+mod scratch;
+pub use scratch::scratch_namespaces;
+mod scale;
+pub use scale::workload_scale;
+mod nodes;
+pub use nodes::node_operations;
+mod eviction;
+pub use eviction::removal_simulation;
@@ -0,0 +1,393 @@
+pub mod node_operations {
+    use std::time::{Duration, Instant};
+
+    use k8s_openapi::api::core::v1::{Node, Pod, Taint};
+    use kube::{
+        api::{DeleteParams, EvictParams, ListParams, Patch, PatchParams, PostParams},
+        Api, Client, ResourceExt,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use tauri::{AppHandle, Emitter};
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    /// Event channel carrying a `DrainProgress` for every step of a drain.
+    pub const DRAIN_EVENT: &str = "node-drain";
+
+    const MIRROR_ANNOTATION: &str = "kubernetes.io/config.mirror";
+    const TAINT_EFFECTS: [&str; 3] = ["NoSchedule", "PreferNoSchedule", "NoExecute"];
+    const DEFAULT_TIMEOUT: u64 = 300;
+    /// Wait between eviction retries while a PodDisruptionBudget blocks one.
+    const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+    const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NodeTaint {
+        pub key: String,
+        pub value: Option<String>,
+        /// `NoSchedule`, `PreferNoSchedule` or `NoExecute`.
+        pub effect: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    pub struct DrainOptions {
+        /// Overrides the pods' termination grace period.
+        pub grace_period_seconds: Option<u32>,
+        /// Seconds to wait for evictions and for the pods to terminate.
+        pub timeout: Option<u64>,
+        /// Evict pods no controller will recreate.
+        #[serde(default)]
+        pub force: bool,
+        /// Evict pods using emptyDir volumes, losing their data.
+        #[serde(default)]
+        pub delete_emptydir_data: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum DrainPhase {
+        Cordoned,
+        Skipped,
+        Evicting,
+        /// A PodDisruptionBudget does not allow the eviction yet; retrying.
+        Blocked,
+        Evicted,
+        Failed,
+        Terminated,
+        Finished,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DrainProgress {
+        pub node: String,
+        pub phase: DrainPhase,
+        pub namespace: Option<String>,
+        pub pod: Option<String>,
+        pub message: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DrainPod {
+        pub namespace: String,
+        pub name: String,
+        pub reason: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DrainResult {
+        pub node: String,
+        pub evicted: Vec<DrainPod>,
+        pub skipped: Vec<DrainPod>,
+        pub failed: Vec<DrainPod>,
+        /// Every evicted pod terminated before the timeout.
+        pub complete: bool,
+    }
+
+    pub async fn set_unschedulable(
+        client: Client,
+        name: &str,
+        unschedulable: bool,
+    ) -> Result<bool, KubiousError> {
+        let patch = json!({ "spec": { "unschedulable": unschedulable } });
+        Api::<Node>::all(client)
+            .patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+            .await
+            .map(|node| node.spec.and_then(|s| s.unschedulable).unwrap_or(false))
+            .context(format!("Failed to update node {}", name))
+    }
+
+    /// Rewrites the node's taints with `change`. The replace carries the
+    /// resource version read, so concurrent edits are rejected rather than
+    /// lost.
+    async fn update_taints(
+        client: Client,
+        name: &str,
+        change: impl FnOnce(&mut Vec<Taint>),
+    ) -> Result<Vec<Taint>, KubiousError> {
+        let api = Api::<Node>::all(client);
+        let mut node = api
+            .get(name)
+            .await
+            .context(format!("Failed to get node {}", name))?;
+        let spec = node.spec.get_or_insert_with(Default::default);
+        let mut taints = spec.taints.take().unwrap_or_default();
+        change(&mut taints);
+        spec.taints = (!taints.is_empty()).then_some(taints);
+        api.replace(name, &PostParams::default(), &node)
+            .await
+            .map(|node| node.spec.and_then(|s| s.taints).unwrap_or_default())
+            .context(format!("Failed to update taints of {}", name))
+    }
+
+    /// Adds a taint, replacing one with the same key and effect.
+    pub async fn add_taint(
+        client: Client,
+        name: &str,
+        taint: NodeTaint,
+    ) -> Result<Vec<Taint>, KubiousError> {
+        if !TAINT_EFFECTS.contains(&taint.effect.as_str()) {
+            return Err(format!("Unknown taint effect {}", taint.effect).into());
+        }
+        if taint.key.is_empty() {
+            return Err("Taint key cannot be empty".into());
+        }
+        update_taints(client, name, |taints| {
+            taints.retain(|t| !(t.key == taint.key && t.effect == taint.effect));
+            taints.push(Taint {
+                key: taint.key,
+                value: taint.value.filter(|v| !v.is_empty()),
+                effect: taint.effect,
+                time_added: None,
+            });
+        })
+        .await
+    }
+
+    /// Removes the taints with `key`, only those with `effect` if given.
+    pub async fn remove_taint(
+        client: Client,
+        name: &str,
+        key: &str,
+        effect: Option<&str>,
+    ) -> Result<Vec<Taint>, KubiousError> {
+        update_taints(client, name, |taints| {
+            taints.retain(|t| !(t.key == key && effect.is_none_or(|e| t.effect == e)));
+        })
+        .await
+    }
+
+    /// Why a pod on the node is not evicted, or `Err` if it would block the
+    /// drain without `force` or `delete_emptydir_data`.
+    fn skip_reason(pod: &Pod, options: &DrainOptions) -> Result<Option<String>, KubiousError> {
+        if pod.annotations().contains_key(MIRROR_ANNOTATION) {
+            return Ok(Some("Static pod managed by the kubelet".to_string()));
+        }
+        let controller = pod
+            .owner_references()
+            .iter()
+            .find(|o| o.controller == Some(true));
+        if controller.is_some_and(|c| c.kind == "DaemonSet") {
+            return Ok(Some("Managed by a DaemonSet".to_string()));
+        }
+        // Finished pods hold nothing that eviction could lose.
+        let finished = pod
+            .status
+            .as_ref()
+            .and_then(|s| s.phase.as_deref())
+            .is_some_and(|p| p == "Succeeded" || p == "Failed");
+        if finished {
+            return Ok(None);
+        }
+        if controller.is_none() && !options.force {
+            return Err("Not managed by a controller; use force to evict it".into());
+        }
+        let empty_dir = pod
+            .spec
+            .as_ref()
+            .and_then(|s| s.volumes.as_ref())
+            .is_some_and(|v| v.iter().any(|v| v.empty_dir.is_some()));
+        if empty_dir && !options.delete_emptydir_data {
+            return Err("Uses emptyDir data that eviction would delete".into());
+        }
+        Ok(None)
+    }
+
+    fn emit(
+        handle: &AppHandle,
+        node: &str,
+        phase: DrainPhase,
+        pod: Option<&DrainPod>,
+        message: Option<String>,
+    ) {
+        let _ = handle.emit(
+            DRAIN_EVENT,
+            DrainProgress {
+                node: node.to_string(),
+                phase,
+                namespace: pod.map(|p| p.namespace.clone()),
+                pod: pod.map(|p| p.name.clone()),
+                message,
+            },
+        );
+    }
+
+    /// Evicts one pod, retrying while a PodDisruptionBudget refuses it.
+    async fn evict(
+        handle: &AppHandle,
+        client: &Client,
+        node: &str,
+        pod: &DrainPod,
+        params: &EvictParams,
+        deadline: Instant,
+    ) -> Result<(), KubiousError> {
+        let api = Api::<Pod>::namespaced(client.clone(), pod.namespace.as_str());
+        loop {
+            match api.evict(pod.name.as_str(), params).await {
+                Ok(_) => return Ok(()),
+                Err(kube::Error::Api(e)) if e.code == 404 => return Ok(()),
+                Err(kube::Error::Api(e)) if e.code == 429 => {
+                    if Instant::now() + RETRY_INTERVAL >= deadline {
+                        return Err(format!("Still blocked at the timeout: {}", e.message).into());
+                    }
+                    emit(
+                        handle,
+                        node,
+                        DrainPhase::Blocked,
+                        Some(pod),
+                        Some(e.message),
+                    );
+                    tokio::time::sleep(RETRY_INTERVAL).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Cordons the node and evicts its pods through the eviction API, so
+    /// PodDisruptionBudgets are honored. DaemonSet and static pods are
+    /// skipped. If any pod would block the drain, nothing is evicted.
+    pub async fn drain(
+        handle: &AppHandle,
+        client: Client,
+        name: String,
+        options: DrainOptions,
+    ) -> Result<DrainResult, KubiousError> {
+        let params = ListParams::default().fields(format!("spec.nodeName={}", name).as_str());
+        let pods = Api::<Pod>::all(client.clone())
+            .list(&params)
+            .await
+            .context(format!("Failed to list pods on {}", name))?
+            .items;
+
+        let mut result = DrainResult {
+            node: name.clone(),
+            evicted: Vec::new(),
+            skipped: Vec::new(),
+            failed: Vec::new(),
+            complete: false,
+        };
+        let mut targets: Vec<(DrainPod, Option<String>)> = Vec::new();
+        let mut blocking: Vec<String> = Vec::new();
+        for pod in pods.iter() {
+            let mut entry = DrainPod {
+                namespace: pod.namespace().unwrap_or_default(),
+                name: pod.name_any(),
+                reason: None,
+            };
+            match skip_reason(pod, &options) {
+                Ok(Some(reason)) => {
+                    entry.reason = Some(reason);
+                    result.skipped.push(entry);
+                }
+                Ok(None) => targets.push((entry, pod.uid())),
+                Err(reason) => {
+                    blocking.push(format!("{}/{}: {}", entry.namespace, entry.name, reason))
+                }
+            }
+        }
+        if !blocking.is_empty() {
+            return Err(format!("Cannot drain {}: {}", name, blocking.join("; ")).into());
+        }
+
+        set_unschedulable(client.clone(), name.as_str(), true).await?;
+        emit(handle, name.as_str(), DrainPhase::Cordoned, None, None);
+        for pod in result.skipped.iter() {
+            emit(
+                handle,
+                name.as_str(),
+                DrainPhase::Skipped,
+                Some(pod),
+                pod.reason.clone(),
+            );
+        }
+
+        let deadline =
+            Instant::now() + Duration::from_secs(options.timeout.unwrap_or(DEFAULT_TIMEOUT));
+        let evict_params = EvictParams {
+            delete_options: Some(DeleteParams {
+                grace_period_seconds: options.grace_period_seconds,
+                ..DeleteParams::default()
+            }),
+            ..EvictParams::default()
+        };
+        let mut pending: Vec<(DrainPod, Option<String>)> = Vec::new();
+        for (mut pod, uid) in targets {
+            emit(
+                handle,
+                name.as_str(),
+                DrainPhase::Evicting,
+                Some(&pod),
+                None,
+            );
+            match evict(
+                handle,
+                &client,
+                name.as_str(),
+                &pod,
+                &evict_params,
+                deadline,
+            )
+            .await
+            {
+                Ok(()) => {
+                    emit(handle, name.as_str(), DrainPhase::Evicted, Some(&pod), None);
+                    pending.push((pod.clone(), uid));
+                    result.evicted.push(pod);
+                }
+                Err(error) => {
+                    emit(
+                        handle,
+                        name.as_str(),
+                        DrainPhase::Failed,
+                        Some(&pod),
+                        Some((error.clone()).to_string()),
+                    );
+                    pod.reason = Some(error.to_string());
+                    result.failed.push(pod);
+                }
+            }
+        }
+
+        // Evicted pods terminate gracefully; a pod with the same name but a
+        // new uid is a replacement, not the evicted pod.
+        while !pending.is_empty() {
+            let mut waiting: Vec<(DrainPod, Option<String>)> = Vec::new();
+            for (pod, uid) in pending {
+                let live = Api::<Pod>::namespaced(client.clone(), pod.namespace.as_str())
+                    .get_opt(pod.name.as_str())
+                    .await;
+                match live {
+                    Ok(Some(live)) if live.uid() == uid => waiting.push((pod, uid)),
+                    Err(_) => waiting.push((pod, uid)),
+                    _ => emit(
+                        handle,
+                        name.as_str(),
+                        DrainPhase::Terminated,
+                        Some(&pod),
+                        None,
+                    ),
+                }
+            }
+            pending = waiting;
+            if pending.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        result.complete = pending.is_empty() && result.failed.is_empty();
+        emit(
+            handle,
+            name.as_str(),
+            DrainPhase::Finished,
+            None,
+            (!result.complete).then(|| {
+                format!(
+                    "{} pods failed to evict, {} still terminating",
+                    result.failed.len(),
+                    pending.len()
+                )
+            }),
+        );
+        Ok(result)
+    }
+}
@@ -0,0 +1,227 @@
+pub mod workload_parity {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use kube::{
+        api::{DynamicObject, GroupVersionKind},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+    pub enum ParityCategory {
+        Replicas,
+        Image,
+        Env,
+        Resources,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ClusterWorkload {
+        pub cluster: String,
+        pub found: bool,
+        pub error: Option<String>,
+    }
+
+    /// One field of the workload, with its value in every cluster in the
+    /// order of `ParityReport::clusters`. `None` means the field is unset
+    /// there, or the workload could not be read.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ParityRow {
+        pub category: ParityCategory,
+        /// e.g. `app/env/LOG_LEVEL` or `app/limits/memory`.
+        pub field: String,
+        pub values: Vec<Option<String>>,
+        pub differs: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ParityReport {
+        pub kind: String,
+        pub namespace: String,
+        pub name: String,
+        pub clusters: Vec<ClusterWorkload>,
+        pub rows: Vec<ParityRow>,
+        pub differences: usize,
+    }
+
+    /// How an env var gets its value. References are shown rather than
+    /// resolved, so Secret values never reach the comparison.
+    fn env_value(env: &Value) -> String {
+        if let Some(value) = env["value"].as_str() {
+            return value.to_string();
+        }
+        let from = &env["valueFrom"];
+        let reference = |source: &str| {
+            format!(
+                "{}:{}/{}",
+                source,
+                from[source]["name"].as_str().unwrap_or_default(),
+                from[source]["key"].as_str().unwrap_or_default()
+            )
+        };
+        if from["secretKeyRef"].is_object() {
+            reference("secretKeyRef")
+        } else if from["configMapKeyRef"].is_object() {
+            reference("configMapKeyRef")
+        } else if let Some(path) = from["fieldRef"]["fieldPath"].as_str() {
+            format!("fieldRef:{}", path)
+        } else if let Some(resource) = from["resourceFieldRef"]["resource"].as_str() {
+            format!("resourceFieldRef:{}", resource)
+        } else {
+            String::new()
+        }
+    }
+
+    /// The compared fields of a workload, keyed by category and field.
+    fn fields(workload: &Value) -> BTreeMap<(ParityCategory, String), String> {
+        let mut fields = BTreeMap::new();
+        let spec = &workload["spec"];
+        if let Some(replicas) = spec["replicas"].as_i64() {
+            fields.insert(
+                (ParityCategory::Replicas, "replicas".to_string()),
+                replicas.to_string(),
+            );
+        }
+        let template = &spec["template"]["spec"];
+        for (field, prefix) in [("initContainers", "init:"), ("containers", "")] {
+            for container in template[field].as_array().into_iter().flatten() {
+                let name = format!(
+                    "{}{}",
+                    prefix,
+                    container["name"].as_str().unwrap_or_default()
+                );
+                if let Some(image) = container["image"].as_str() {
+                    fields.insert((ParityCategory::Image, name.clone()), image.to_string());
+                }
+                for env in container["env"].as_array().into_iter().flatten() {
+                    fields.insert(
+                        (
+                            ParityCategory::Env,
+                            format!("{}/env/{}", name, env["name"].as_str().unwrap_or_default()),
+                        ),
+                        env_value(env),
+                    );
+                }
+                for env in container["envFrom"].as_array().into_iter().flatten() {
+                    for (source, label) in [("configMapRef", "configMap"), ("secretRef", "secret")]
+                    {
+                        if let Some(reference) = env[source]["name"].as_str() {
+                            fields.insert(
+                                (
+                                    ParityCategory::Env,
+                                    format!(
+                                        "{}/envFrom/{}{}",
+                                        name,
+                                        env["prefix"].as_str().unwrap_or_default(),
+                                        label
+                                    ),
+                                ),
+                                reference.to_string(),
+                            );
+                        }
+                    }
+                }
+                for kind in ["requests", "limits"] {
+                    if let Some(resources) = container["resources"][kind].as_object() {
+                        for (resource, quantity) in resources {
+                            fields.insert(
+                                (
+                                    ParityCategory::Resources,
+                                    format!("{}/{}/{}", name, kind, resource),
+                                ),
+                                quantity
+                                    .as_str()
+                                    .map(|q| q.to_string())
+                                    .unwrap_or_else(|| quantity.to_string()),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        fields
+    }
+
+    async fn fetch(
+        client: &Client,
+        kind: &str,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<Value>, KubiousError> {
+        let gvk = GroupVersionKind::gvk("apps", "v1", kind);
+        let (resource, _) = pinned_kind(client, &gvk)
+            .await
+            .context(format!("Unknown workload kind {}", kind))?;
+        Api::<DynamicObject>::namespaced_with(client.clone(), namespace, &resource)
+            .get_opt(name)
+            .await
+            .map(|object| object.and_then(|o| serde_json::to_value(o).ok()))
+            .context(format!("Failed to get {}", name))
+    }
+
+    /// Reads the same workload from every cluster and lines up its replicas,
+    /// images, env vars and resources field by field. Clusters without a
+    /// client or without the workload keep their column, with every value
+    /// unset, so the matrix stays aligned.
+    pub async fn compare(
+        clients: Vec<(String, Option<Client>)>,
+        kind: String,
+        namespace: String,
+        name: String,
+        only_differences: bool,
+    ) -> Result<ParityReport, KubiousError> {
+        if clients.len() < 2 {
+            return Err("At least two clusters are needed for a comparison".into());
+        }
+        let mut clusters: Vec<ClusterWorkload> = Vec::new();
+        let mut columns: Vec<BTreeMap<(ParityCategory, String), String>> = Vec::new();
+        for (cluster, client) in clients {
+            let fetched = match client {
+                Some(client) => {
+                    fetch(&client, kind.as_str(), namespace.as_str(), name.as_str()).await
+                }
+                None => Err(KubiousError::no_client()),
+            };
+            let (found, error, column) = match fetched {
+                Ok(Some(workload)) => (true, None, fields(&workload)),
+                Ok(None) => (false, None, BTreeMap::new()),
+                Err(error) => (false, Some(error.to_string()), BTreeMap::new()),
+            };
+            clusters.push(ClusterWorkload {
+                cluster,
+                found,
+                error,
+            });
+            columns.push(column);
+        }
+
+        let keys: BTreeSet<&(ParityCategory, String)> =
+            columns.iter().flat_map(|c| c.keys()).collect();
+        let rows: Vec<ParityRow> = keys
+            .into_iter()
+            .map(|key| {
+                let values: Vec<Option<String>> =
+                    columns.iter().map(|c| c.get(key).cloned()).collect();
+                ParityRow {
+                    category: key.0.clone(),
+                    field: key.1.clone(),
+                    differs: values.iter().any(|v| *v != values[0]),
+                    values,
+                }
+            })
+            .filter(|row| row.differs || !only_differences)
+            .collect();
+        Ok(ParityReport {
+            differences: rows.iter().filter(|r| r.differs).count(),
+            kind,
+            namespace,
+            name,
+            clusters,
+            rows,
+        })
+    }
+}
@@ -0,0 +1,283 @@
+pub mod config_rollout {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+    use kube::{
+        api::{ObjectMeta, Patch, PatchParams, PostParams},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Map, Value};
+
+    use crate::{
+        api::kube::workload_rollout::{self, FailingPod, RolloutOutcome},
+        util::kubious_error::KubiousError,
+    };
+
+    const RESTARTABLE_KINDS: [&str; 3] = ["Deployment", "StatefulSet", "DaemonSet"];
+
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+    pub enum ConfigKind {
+        ConfigMap,
+        Secret,
+    }
+
+    /// Keys to set or, with a `null` value, remove. Secret values are given
+    /// as plain text.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ConfigChange {
+        pub kind: ConfigKind,
+        pub name: String,
+        pub data: BTreeMap<String, Option<String>>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RolloutStep {
+        pub kind: String,
+        pub name: String,
+        /// Seconds to wait for the step's pods to become ready.
+        pub timeout: Option<u64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ConfigChangeOutcome {
+        pub kind: ConfigKind,
+        pub name: String,
+        pub created: bool,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum StepState {
+        Succeeded,
+        Failed,
+        TimedOut,
+        /// Not run because an earlier step or config change failed.
+        Skipped,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct StepOutcome {
+        pub kind: String,
+        pub name: String,
+        pub state: StepState,
+        pub message: Option<String>,
+        pub elapsed_secs: u64,
+        pub failing_pod: Option<FailingPod>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ConfigRolloutReport {
+        pub namespace: String,
+        pub changes: Vec<ConfigChangeOutcome>,
+        pub steps: Vec<StepOutcome>,
+        pub completed: bool,
+        /// What stopped the rollout, if it did not complete.
+        pub aborted: Option<String>,
+    }
+
+    fn skipped(step: &RolloutStep) -> StepOutcome {
+        StepOutcome {
+            kind: step.kind.clone(),
+            name: step.name.clone(),
+            state: StepState::Skipped,
+            message: None,
+            elapsed_secs: 0,
+            failing_pod: None,
+        }
+    }
+
+    /// Explains the server's refusal to change an immutable object, which
+    /// otherwise reads as a generic validation error.
+    fn describe_error(change: &ConfigChange, immutable: bool, error: kube::Error) -> String {
+        if immutable {
+            format!(
+                "{:?} {} is immutable; it has to be deleted and recreated",
+                change.kind, change.name
+            )
+        } else {
+            format!(
+                "Failed to update {:?} {}: {}",
+                change.kind, change.name, error
+            )
+        }
+    }
+
+    /// Merges a change into a ConfigMap or Secret, creating it if missing.
+    async fn apply_change(
+        client: Client,
+        namespace: &str,
+        change: &ConfigChange,
+    ) -> ConfigChangeOutcome {
+        let mut outcome = ConfigChangeOutcome {
+            kind: change.kind,
+            name: change.name.clone(),
+            created: false,
+            error: None,
+        };
+        let set: BTreeMap<String, String> = change
+            .data
+            .iter()
+            .filter_map(|(key, value)| value.clone().map(|v| (key.clone(), v)))
+            .collect();
+        let removed: Map<String, Value> = change
+            .data
+            .iter()
+            .filter(|(_, value)| value.is_none())
+            .map(|(key, _)| (key.clone(), Value::Null))
+            .collect();
+        let metadata = ObjectMeta {
+            name: Some(change.name.clone()),
+            namespace: Some(namespace.to_string()),
+            ..ObjectMeta::default()
+        };
+
+        let result = match change.kind {
+            ConfigKind::ConfigMap => {
+                let api = Api::<ConfigMap>::namespaced(client, namespace);
+                match api.get_opt(change.name.as_str()).await {
+                    Ok(Some(existing)) => {
+                        let mut data: Map<String, Value> = removed;
+                        data.extend(set.into_iter().map(|(k, v)| (k, Value::String(v))));
+                        api.patch(
+                            change.name.as_str(),
+                            &PatchParams::default(),
+                            &Patch::Merge(json!({ "data": data })),
+                        )
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| describe_error(change, existing.immutable == Some(true), e))
+                    }
+                    Ok(None) => {
+                        outcome.created = true;
+                        let object = ConfigMap {
+                            metadata,
+                            data: Some(set),
+                            ..ConfigMap::default()
+                        };
+                        api.create(&PostParams::default(), &object)
+                            .await
+                            .map(|_| ())
+                            .map_err(|e| {
+                                format!("Failed to create ConfigMap {}: {}", change.name, e)
+                            })
+                    }
+                    Err(e) => Err(format!("Failed to get ConfigMap {}: {}", change.name, e)),
+                }
+            }
+            ConfigKind::Secret => {
+                let api = Api::<Secret>::namespaced(client, namespace);
+                match api.get_opt(change.name.as_str()).await {
+                    Ok(Some(existing)) => {
+                        let patch = json!({ "data": removed, "stringData": set });
+                        api.patch(
+                            change.name.as_str(),
+                            &PatchParams::default(),
+                            &Patch::Merge(patch),
+                        )
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| describe_error(change, existing.immutable == Some(true), e))
+                    }
+                    Ok(None) => {
+                        outcome.created = true;
+                        let object = Secret {
+                            metadata,
+                            string_data: Some(set),
+                            ..Secret::default()
+                        };
+                        api.create(&PostParams::default(), &object)
+                            .await
+                            .map(|_| ())
+                            .map_err(|e| format!("Failed to create Secret {}: {}", change.name, e))
+                    }
+                    Err(e) => Err(format!("Failed to get Secret {}: {}", change.name, e)),
+                }
+            }
+        };
+        outcome.error = result.err();
+        outcome
+    }
+
+    /// Applies the config changes, then restarts the workloads one at a
+    /// time in the order given, waiting for each to be ready before the
+    /// next. The first failure aborts the rest; changes already made are
+    /// not reverted.
+    pub async fn run(
+        client: Client,
+        namespace: String,
+        changes: Vec<ConfigChange>,
+        steps: Vec<RolloutStep>,
+    ) -> Result<ConfigRolloutReport, KubiousError> {
+        if let Some(step) = steps
+            .iter()
+            .find(|s| !RESTARTABLE_KINDS.contains(&s.kind.as_str()))
+        {
+            return Err(format!("Unsupported workload kind {}", step.kind).into());
+        }
+        let mut report = ConfigRolloutReport {
+            namespace: namespace.clone(),
+            changes: Vec::new(),
+            steps: Vec::new(),
+            completed: false,
+            aborted: None,
+        };
+
+        for change in changes.iter() {
+            let outcome = apply_change(client.clone(), namespace.as_str(), change).await;
+            if let Some(error) = &outcome.error {
+                report.aborted = Some(error.clone());
+            }
+            report.changes.push(outcome);
+            if report.aborted.is_some() {
+                report.steps = steps.iter().map(skipped).collect();
+                return Ok(report);
+            }
+        }
+
+        for step in steps.iter() {
+            if report.aborted.is_some() {
+                report.steps.push(skipped(step));
+                continue;
+            }
+            let outcome = match workload_rollout::restart_and_wait(
+                client.clone(),
+                namespace.clone(),
+                step.kind.clone(),
+                step.name.clone(),
+                step.timeout,
+            )
+            .await
+            {
+                Ok(restart) => StepOutcome {
+                    kind: restart.kind,
+                    name: restart.name,
+                    state: match restart.outcome {
+                        RolloutOutcome::Succeeded => StepState::Succeeded,
+                        RolloutOutcome::Failed => StepState::Failed,
+                        RolloutOutcome::TimedOut => StepState::TimedOut,
+                    },
+                    message: Some(restart.message),
+                    elapsed_secs: restart.elapsed_secs,
+                    failing_pod: restart.failing_pod,
+                },
+                Err(error) => StepOutcome {
+                    message: Some(error.to_string()),
+                    state: StepState::Failed,
+                    ..skipped(step)
+                },
+            };
+            if outcome.state != StepState::Succeeded {
+                report.aborted = Some(format!(
+                    "{} {}: {}",
+                    step.kind,
+                    step.name,
+                    outcome.message.clone().unwrap_or_default()
+                ));
+            }
+            report.steps.push(outcome);
+        }
+        report.completed = report.aborted.is_none();
+        Ok(report)
+    }
+}
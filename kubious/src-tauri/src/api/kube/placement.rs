@@ -0,0 +1,216 @@
+pub mod node_placement {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::api::core::v1::{Node, Pod};
+    use kube::{api::ListParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+
+    use crate::util::{
+        kube_quantity::quantity_value,
+        kubious_error::{ErrorContext, KubiousError},
+        resource_accounting::{pod_is_active, pod_requests},
+    };
+
+    const ZONE_LABEL: &str = "topology.kubernetes.io/zone";
+    const REGION_LABEL: &str = "topology.kubernetes.io/region";
+    const INSTANCE_TYPE_LABEL: &str = "node.kubernetes.io/instance-type";
+
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    pub struct Usage {
+        pub pods: usize,
+        pub pod_capacity: f64,
+        pub cpu_requested: f64,
+        pub cpu_allocatable: f64,
+        pub memory_requested: f64,
+        pub memory_allocatable: f64,
+    }
+
+    impl Usage {
+        fn add(&mut self, other: &Usage) {
+            self.pods += other.pods;
+            self.pod_capacity += other.pod_capacity;
+            self.cpu_requested += other.cpu_requested;
+            self.cpu_allocatable += other.cpu_allocatable;
+            self.memory_requested += other.memory_requested;
+            self.memory_allocatable += other.memory_allocatable;
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NodePlacement {
+        pub node: String,
+        pub zone: Option<String>,
+        pub region: Option<String>,
+        pub instance_type: Option<String>,
+        pub ready: bool,
+        pub schedulable: bool,
+        #[serde(flatten)]
+        pub usage: Usage,
+        /// Requested share of allocatable, from 0 to 1.
+        pub cpu_ratio: f64,
+        pub memory_ratio: f64,
+        pub pod_ratio: f64,
+        pub namespaces: BTreeMap<String, usize>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ZonePlacement {
+        pub zone: String,
+        pub region: Option<String>,
+        pub nodes: usize,
+        #[serde(flatten)]
+        pub usage: Usage,
+        pub cpu_ratio: f64,
+        pub memory_ratio: f64,
+    }
+
+    /// Spread of the per-node request ratios; higher means less balanced.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct Imbalance {
+        pub cpu_stddev: f64,
+        pub memory_stddev: f64,
+        pub pod_stddev: f64,
+        pub busiest_node: Option<String>,
+        pub idlest_node: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PlacementReport {
+        pub nodes: Vec<NodePlacement>,
+        pub zones: Vec<ZonePlacement>,
+        pub imbalance: Imbalance,
+        /// Active pods not bound to a node yet.
+        pub pending_pods: usize,
+    }
+
+    fn ratio(used: f64, total: f64) -> f64 {
+        if total > 0.0 {
+            used / total
+        } else {
+            0.0
+        }
+    }
+
+    fn stddev(values: &[f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
+    }
+
+    /// Per-node and per-zone placement of active pods, with requested versus
+    /// allocatable CPU and memory, shaped for heatmap and treemap views.
+    pub async fn report(client: Client) -> Result<PlacementReport, KubiousError> {
+        let nodes = Api::<Node>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list nodes.")?;
+        let pods = Api::<Pod>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list pods.")?;
+
+        let mut pending_pods = 0;
+        let mut usage: BTreeMap<String, (Usage, BTreeMap<String, usize>)> = BTreeMap::new();
+        for pod in pods.items.iter().filter(|p| pod_is_active(p)) {
+            let Some(node) = pod.spec.as_ref().and_then(|s| s.node_name.clone()) else {
+                pending_pods += 1;
+                continue;
+            };
+            let (totals, namespaces) = usage.entry(node).or_default();
+            let requests = pod_requests(pod);
+            totals.pods += 1;
+            totals.cpu_requested += requests.get("cpu").copied().unwrap_or(0.0);
+            totals.memory_requested += requests.get("memory").copied().unwrap_or(0.0);
+            *namespaces
+                .entry(pod.metadata.namespace.clone().unwrap_or_default())
+                .or_insert(0) += 1;
+        }
+
+        let mut placements: Vec<NodePlacement> = Vec::new();
+        for node in nodes.items {
+            let name = node.metadata.name.clone().unwrap_or_default();
+            let labels = node.metadata.labels.clone().unwrap_or_default();
+            let status = node.status.unwrap_or_default();
+            let allocatable = status.allocatable.unwrap_or_default();
+            let allocatable_of =
+                |resource: &str| allocatable.get(resource).map(quantity_value).unwrap_or(0.0);
+            let (mut totals, namespaces) = usage.remove(&name).unwrap_or_default();
+            totals.pod_capacity = allocatable_of("pods");
+            totals.cpu_allocatable = allocatable_of("cpu");
+            totals.memory_allocatable = allocatable_of("memory");
+            placements.push(NodePlacement {
+                node: name,
+                zone: labels.get(ZONE_LABEL).cloned(),
+                region: labels.get(REGION_LABEL).cloned(),
+                instance_type: labels.get(INSTANCE_TYPE_LABEL).cloned(),
+                ready: status
+                    .conditions
+                    .iter()
+                    .flatten()
+                    .any(|c| c.type_ == "Ready" && c.status == "True"),
+                schedulable: !node.spec.and_then(|s| s.unschedulable).unwrap_or(false),
+                cpu_ratio: ratio(totals.cpu_requested, totals.cpu_allocatable),
+                memory_ratio: ratio(totals.memory_requested, totals.memory_allocatable),
+                pod_ratio: ratio(totals.pods as f64, totals.pod_capacity),
+                usage: totals,
+                namespaces,
+            });
+        }
+
+        let mut zones: BTreeMap<String, ZonePlacement> = BTreeMap::new();
+        for node in &placements {
+            let zone = zones
+                .entry(node.zone.clone().unwrap_or("unknown".to_string()))
+                .or_insert(ZonePlacement {
+                    zone: node.zone.clone().unwrap_or("unknown".to_string()),
+                    region: node.region.clone(),
+                    nodes: 0,
+                    usage: Usage::default(),
+                    cpu_ratio: 0.0,
+                    memory_ratio: 0.0,
+                });
+            zone.nodes += 1;
+            zone.usage.add(&node.usage);
+        }
+        let zones: Vec<ZonePlacement> = zones
+            .into_values()
+            .map(|mut zone| {
+                zone.cpu_ratio = ratio(zone.usage.cpu_requested, zone.usage.cpu_allocatable);
+                zone.memory_ratio =
+                    ratio(zone.usage.memory_requested, zone.usage.memory_allocatable);
+                zone
+            })
+            .collect();
+
+        // Cordoned and NotReady nodes would skew the spread towards idle.
+        let active: Vec<&NodePlacement> = placements
+            .iter()
+            .filter(|n| n.ready && n.schedulable)
+            .collect();
+        let collect =
+            |f: fn(&NodePlacement) -> f64| active.iter().map(|n| f(n)).collect::<Vec<f64>>();
+        let load = |n: &&NodePlacement| n.cpu_ratio.max(n.memory_ratio);
+        let imbalance = Imbalance {
+            cpu_stddev: stddev(&collect(|n| n.cpu_ratio)),
+            memory_stddev: stddev(&collect(|n| n.memory_ratio)),
+            pod_stddev: stddev(&collect(|n| n.pod_ratio)),
+            busiest_node: active
+                .iter()
+                .max_by(|a, b| load(a).total_cmp(&load(b)))
+                .map(|n| n.node.clone()),
+            idlest_node: active
+                .iter()
+                .min_by(|a, b| load(a).total_cmp(&load(b)))
+                .map(|n| n.node.clone()),
+        };
+
+        Ok(PlacementReport {
+            nodes: placements,
+            zones,
+            imbalance,
+            pending_pods,
+        })
+    }
+}
@@ -0,0 +1,372 @@
+pub mod port_forwards {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard,
+        },
+    };
+
+    use k8s_openapi::{api::core::v1::Pod, chrono::Utc};
+    use kube::{api::Portforwarder, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use tauri::{async_runtime::JoinHandle, AppHandle, Emitter, Manager};
+    use tokio::{
+        io::{copy_bidirectional, AsyncRead, AsyncWrite},
+        net::{TcpListener, TcpStream},
+    };
+
+    use crate::{
+        api::kube::service_probe,
+        util::{
+            kubious_error::{ErrorContext, KubiousError},
+            lock_recovery::recover,
+            stream_reconnect::{BackoffPolicy, Reconnector},
+        },
+    };
+
+    /// Event channel carrying a `PortForward` whenever a forward's state or
+    /// connection count changes.
+    pub const PORT_FORWARD_EVENT: &str = "port-forward";
+
+    /// Attempts to open a tunnel for a local connection before giving up on
+    /// it.
+    const CONNECT_ATTEMPTS: u32 = 5;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "kind")]
+    pub enum ForwardTarget {
+        Pod {
+            name: String,
+            port: u16,
+        },
+        /// Forwards to a ready pod behind the service, picked again for every
+        /// connection so the forward follows rollouts. `port` is the service
+        /// port name or number, the first one if omitted.
+        Service {
+            name: String,
+            port: Option<String>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum ForwardState {
+        /// Waiting for the first local connection.
+        Listening,
+        /// The last tunnel to the cluster opened successfully.
+        Connected,
+        /// The last tunnel failed to open or dropped; a new one is being
+        /// opened with backoff.
+        Broken,
+        Stopped,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PortForward {
+        pub id: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub target: ForwardTarget,
+        pub local_port: u16,
+        pub state: ForwardState,
+        pub active_connections: usize,
+        /// Pod the last connection was forwarded to.
+        pub pod: Option<String>,
+        pub error: Option<String>,
+        pub started: String,
+    }
+
+    struct RunningForward {
+        forward: PortForward,
+        task: JoinHandle<()>,
+    }
+
+    /// Active forwards keyed by id. Each forward holds its own client, so it
+    /// keeps running when the current config changes or configs are
+    /// reloaded.
+    #[derive(Default)]
+    pub struct PortForwardManager {
+        forwards: Mutex<HashMap<String, RunningForward>>,
+        counter: AtomicU64,
+    }
+
+    impl PortForwardManager {
+        fn forwards_mutable(&self) -> MutexGuard<'_, HashMap<String, RunningForward>> {
+            recover(&self.forwards)
+        }
+
+        fn next_id(&self) -> String {
+            format!(
+                "forward-{}",
+                self.counter.fetch_add(1, Ordering::SeqCst) + 1
+            )
+        }
+
+        pub fn active(&self) -> Vec<PortForward> {
+            let mut forwards: Vec<PortForward> = self
+                .forwards_mutable()
+                .values()
+                .map(|running| running.forward.clone())
+                .collect();
+            forwards.sort_by(|a, b| a.started.cmp(&b.started));
+            forwards
+        }
+    }
+
+    fn update(handle: &AppHandle, id: &str, change: impl FnOnce(&mut PortForward)) {
+        let manager = handle.state::<PortForwardManager>();
+        let updated = {
+            let mut forwards = manager.forwards_mutable();
+            let Some(running) = forwards.get_mut(id) else {
+                return;
+            };
+            change(&mut running.forward);
+            running.forward.clone()
+        };
+        let _ = handle.emit(PORT_FORWARD_EVENT, updated);
+    }
+
+    async fn tunnel(
+        client: Client,
+        namespace: &str,
+        target: &ForwardTarget,
+    ) -> Result<(String, u16), KubiousError> {
+        match target {
+            ForwardTarget::Pod { name, port } => Ok((name.clone(), *port)),
+            ForwardTarget::Service { name, port } => {
+                service_probe::backend(&client, namespace, name.as_str(), port.as_deref()).await
+            }
+        }
+    }
+
+    /// Resolves the target and opens a tunnel to it. The pod is resolved
+    /// again on every call, so a service forward moves to another ready pod.
+    async fn open(
+        client: Client,
+        namespace: &str,
+        target: &ForwardTarget,
+    ) -> Result<(String, Portforwarder, impl AsyncRead + AsyncWrite + Unpin), KubiousError> {
+        let (pod, port) = tunnel(client.clone(), namespace, target).await?;
+        let mut forwarder = Api::<Pod>::namespaced(client, namespace)
+            .portforward(pod.as_str(), &[port])
+            .await
+            .context(format!("Failed to port-forward to {}:{}", pod, port))?;
+        let stream = forwarder
+            .take_stream(port)
+            .ok_or("Port-forward stream unavailable".to_string())?;
+        Ok((pod, forwarder, stream))
+    }
+
+    fn broken(handle: &AppHandle, id: &str) -> bool {
+        handle
+            .state::<PortForwardManager>()
+            .forwards_mutable()
+            .get(id)
+            .is_some_and(|running| running.forward.state == ForwardState::Broken)
+    }
+
+    /// After a tunnel dropped, opens a fresh one with backoff until the
+    /// target is reachable again, so the forward reports `Connected` before
+    /// the next local connection. The dropped connection itself cannot be
+    /// resumed. Stops once the forward is stopped or another connection
+    /// got through first.
+    async fn reestablish(
+        handle: AppHandle,
+        client: Client,
+        id: String,
+        namespace: String,
+        target: ForwardTarget,
+        mut reason: String,
+    ) {
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "port-forward",
+            id.as_str(),
+            BackoffPolicy::default(),
+        );
+        loop {
+            if !reconnector.retry(reason.as_str()).await || !broken(&handle, id.as_str()) {
+                return;
+            }
+            match open(client.clone(), namespace.as_str(), &target).await {
+                Ok((pod, forwarder, stream)) => {
+                    drop(stream);
+                    let _ = forwarder.join().await;
+                    reconnector.connected();
+                    update(&handle, id.as_str(), |f| {
+                        f.state = ForwardState::Connected;
+                        f.error = None;
+                        f.pod = Some(pod);
+                    });
+                    return;
+                }
+                Err(error) => {
+                    reason = error.to_string();
+                    update(&handle, id.as_str(), |f| f.error = Some(error.to_string()));
+                }
+            }
+        }
+    }
+
+    async fn serve(
+        handle: AppHandle,
+        client: Client,
+        id: String,
+        namespace: String,
+        target: ForwardTarget,
+        mut local: TcpStream,
+    ) {
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "port-forward",
+            id.as_str(),
+            BackoffPolicy {
+                max_attempts: Some(CONNECT_ATTEMPTS),
+                ..BackoffPolicy::default()
+            },
+        );
+        let (pod, forwarder, mut remote) = loop {
+            match open(client.clone(), namespace.as_str(), &target).await {
+                Ok(opened) => break opened,
+                Err(error) => {
+                    update(&handle, id.as_str(), |f| {
+                        f.state = ForwardState::Broken;
+                        f.error = Some(error.to_string());
+                    });
+                    if !reconnector.retry(error.message()).await {
+                        return;
+                    }
+                }
+            }
+        };
+
+        reconnector.connected();
+        update(&handle, id.as_str(), |f| {
+            f.state = ForwardState::Connected;
+            f.error = None;
+            f.pod = Some(pod.clone());
+            f.active_connections += 1;
+        });
+        let copied = copy_bidirectional(&mut local, &mut remote).await;
+        drop(remote);
+        let joined = forwarder.join().await;
+        let error = copied
+            .err()
+            .map(|e| e.to_string())
+            .or(joined.err().map(|e| e.to_string()));
+        update(&handle, id.as_str(), |f| {
+            f.active_connections = f.active_connections.saturating_sub(1);
+            if let Some(error) = error.clone() {
+                f.state = ForwardState::Broken;
+                f.error = Some(error);
+            }
+        });
+        if let Some(error) = error {
+            reestablish(handle, client, id, namespace, target, error).await;
+        }
+    }
+
+    async fn accept(
+        handle: AppHandle,
+        client: Client,
+        id: String,
+        namespace: String,
+        target: ForwardTarget,
+        listener: TcpListener,
+    ) {
+        loop {
+            match listener.accept().await {
+                Ok((local, _)) => {
+                    tauri::async_runtime::spawn(serve(
+                        handle.clone(),
+                        client.clone(),
+                        id.clone(),
+                        namespace.clone(),
+                        target.clone(),
+                        local,
+                    ));
+                }
+                Err(e) => {
+                    update(&handle, id.as_str(), |f| {
+                        f.state = ForwardState::Broken;
+                        f.error = Some(format!("Local listener failed: {}", e));
+                    });
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Listens on `127.0.0.1:<local_port>` (any free port if omitted) and
+    /// opens a tunnel to the target for every local connection.
+    pub async fn start(
+        handle: &AppHandle,
+        client: Client,
+        cluster: String,
+        namespace: String,
+        target: ForwardTarget,
+        local_port: Option<u16>,
+    ) -> Result<PortForward, KubiousError> {
+        if let ForwardTarget::Service { name, port } = &target {
+            service_probe::backend(&client, namespace.as_str(), name.as_str(), port.as_deref())
+                .await?;
+        }
+        let listener = TcpListener::bind(("127.0.0.1", local_port.unwrap_or(0)))
+            .await
+            .context("Failed to listen on local port")?;
+        let bound = listener
+            .local_addr()
+            .context("Failed to listen on local port")?;
+
+        let manager = handle.state::<PortForwardManager>();
+        let id = manager.next_id();
+        let forward = PortForward {
+            id: id.clone(),
+            cluster,
+            namespace: namespace.clone(),
+            target: target.clone(),
+            local_port: bound.port(),
+            state: ForwardState::Listening,
+            active_connections: 0,
+            pod: None,
+            error: None,
+            started: Utc::now().to_rfc3339(),
+        };
+        let mut forwards = manager.forwards_mutable();
+        let task = tauri::async_runtime::spawn(accept(
+            handle.clone(),
+            client,
+            id.clone(),
+            namespace,
+            target,
+            listener,
+        ));
+        forwards.insert(
+            id,
+            RunningForward {
+                forward: forward.clone(),
+                task,
+            },
+        );
+        drop(forwards);
+        let _ = handle.emit(PORT_FORWARD_EVENT, forward.clone());
+        Ok(forward)
+    }
+
+    /// Closes the local listener. Connections already open are left to
+    /// finish.
+    pub fn stop(handle: &AppHandle, id: &str) -> bool {
+        let removed = handle
+            .state::<PortForwardManager>()
+            .forwards_mutable()
+            .remove(id);
+        if let Some(mut running) = removed {
+            running.task.abort();
+            running.forward.state = ForwardState::Stopped;
+            let _ = handle.emit(PORT_FORWARD_EVENT, running.forward);
+            true
+        } else {
+            false
+        }
+    }
+}
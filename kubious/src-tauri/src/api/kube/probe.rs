@@ -0,0 +1,441 @@
+pub mod service_probe {
+    use std::{
+        collections::BTreeMap,
+        time::{Duration, Instant},
+    };
+
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bytes::Bytes;
+    use k8s_openapi::api::core::v1::{Endpoints, Pod, Service};
+    use kube::{Api, Client};
+    use rand::RngCore;
+    use serde::{Deserialize, Serialize};
+    use sha1::{Digest, Sha1};
+    use tokio::{
+        io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+        time::timeout,
+    };
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+    const BODY_PREVIEW: usize = 1024;
+    const MAX_HEAD: usize = 16 * 1024;
+    const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    const HEALTH_CHECK_PATH: &str = "/grpc.health.v1.Health/Check";
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "protocol")]
+    pub enum ProbeRequest {
+        Http {
+            path: Option<String>,
+        },
+        /// The standard `grpc.health.v1.Health/Check` call over plaintext
+        /// HTTP/2. An empty service checks the server as a whole.
+        Grpc {
+            service: Option<String>,
+        },
+        /// An HTTP/1.1 upgrade handshake, closed right after it succeeds.
+        WebSocket {
+            path: Option<String>,
+            #[serde(default)]
+            subprotocols: Vec<String>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "protocol")]
+    pub enum ProbeDetail {
+        Http {
+            status: u16,
+            headers: BTreeMap<String, String>,
+            body_preview: String,
+        },
+        Grpc {
+            http_status: u16,
+            grpc_status: Option<i32>,
+            grpc_message: Option<String>,
+            /// `SERVING`, `NOT_SERVING`, `SERVICE_UNKNOWN` or `UNKNOWN`.
+            serving_status: Option<String>,
+        },
+        WebSocket {
+            status: u16,
+            upgraded: bool,
+            /// Whether `Sec-WebSocket-Accept` matches the key that was sent.
+            accept_valid: bool,
+            subprotocol: Option<String>,
+            extensions: Option<String>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ProbeResult {
+        pub service: String,
+        /// The pod the port-forward went to.
+        pub pod: Option<String>,
+        pub port: Option<u16>,
+        pub success: bool,
+        pub latency_ms: u64,
+        pub detail: Option<ProbeDetail>,
+        pub error: Option<String>,
+    }
+
+    /// Picks a ready pod behind the service and the container port the
+    /// requested service port (by name or number, the first one if omitted)
+    /// maps to. Named target ports are resolved through the Endpoints.
+    pub async fn backend(
+        client: &Client,
+        namespace: &str,
+        service: &str,
+        port: Option<&str>,
+    ) -> Result<(String, u16), KubiousError> {
+        let svc = Api::<Service>::namespaced(client.clone(), namespace)
+            .get(service)
+            .await
+            .context(format!("Failed to get service {}", service))?;
+        let ports = svc.spec.and_then(|s| s.ports).unwrap_or_default();
+        let service_port = match port {
+            Some(port) => ports
+                .iter()
+                .find(|p| p.name.as_deref() == Some(port) || p.port.to_string() == port),
+            None => ports.first(),
+        }
+        .ok_or(format!(
+            "Service {} has no port {}",
+            service,
+            port.unwrap_or("")
+        ))?;
+
+        let endpoints = Api::<Endpoints>::namespaced(client.clone(), namespace)
+            .get(service)
+            .await
+            .context(format!("Failed to get endpoints of {}", service))?;
+        endpoints
+            .subsets
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|subset| {
+                let port = subset
+                    .ports
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|p| p.name == service_port.name)?
+                    .port;
+                let pod = subset
+                    .addresses
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|a| a.target_ref)
+                    .find(|r| r.kind.as_deref() == Some("Pod"))?
+                    .name?;
+                Some((pod, u16::try_from(port).ok()?))
+            })
+            .ok_or(format!("Service {} has no ready pods", service).into())
+    }
+
+    /// Reads an HTTP response head, up to and including the blank line.
+    async fn read_head<S: AsyncRead + Unpin>(stream: &mut S) -> Result<String, KubiousError> {
+        let mut head: Vec<u8> = Vec::new();
+        let mut byte = [0u8; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            if head.len() > MAX_HEAD {
+                return Err("Response head too large".into());
+            }
+            let read = stream
+                .read(&mut byte)
+                .await
+                .context("Failed to read response.")?;
+            if read == 0 {
+                break;
+            }
+            head.push(byte[0]);
+        }
+        Ok(String::from_utf8_lossy(&head).to_string())
+    }
+
+    fn parse_head(head: &str) -> Result<(u16, BTreeMap<String, String>), KubiousError> {
+        let mut lines = head.split("\r\n");
+        let status = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or("Malformed response".to_string())?;
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+        Ok((status, headers))
+    }
+
+    async fn probe_http<S: AsyncRead + AsyncWrite + Unpin>(
+        stream: &mut S,
+        host: &str,
+        path: &str,
+    ) -> Result<ProbeDetail, KubiousError> {
+        stream
+            .write_all(
+                format!(
+                    "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+                    path, host
+                )
+                .as_bytes(),
+            )
+            .await
+            .context("Failed to send request.")?;
+        let (status, headers) = parse_head(read_head(stream).await?.as_str())?;
+        let mut body: Vec<u8> = Vec::new();
+        let _ = stream
+            .take(BODY_PREVIEW as u64)
+            .read_to_end(&mut body)
+            .await;
+        Ok(ProbeDetail::Http {
+            status,
+            headers,
+            body_preview: String::from_utf8_lossy(&body).to_string(),
+        })
+    }
+
+    async fn probe_websocket<S: AsyncRead + AsyncWrite + Unpin>(
+        stream: &mut S,
+        host: &str,
+        path: &str,
+        subprotocols: &[String],
+    ) -> Result<ProbeDetail, KubiousError> {
+        let mut nonce = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let key = STANDARD.encode(nonce);
+        let mut request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n",
+            path, host, key
+        );
+        if !subprotocols.is_empty() {
+            request.push_str(
+                format!("Sec-WebSocket-Protocol: {}\r\n", subprotocols.join(", ")).as_str(),
+            );
+        }
+        request.push_str("\r\n");
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .context("Failed to send upgrade request.")?;
+
+        let (status, headers) = parse_head(read_head(stream).await?.as_str())?;
+        let expected = STANDARD.encode(Sha1::digest(format!("{}{}", key, WEBSOCKET_GUID)));
+        let upgraded = status == 101
+            && headers
+                .get("upgrade")
+                .is_some_and(|u| u.eq_ignore_ascii_case("websocket"));
+        if upgraded {
+            // A masked close frame without payload.
+            let mut close = vec![0x88, 0x80];
+            close.extend_from_slice(&rand::random::<[u8; 4]>());
+            let _ = stream.write_all(&close).await;
+        }
+        Ok(ProbeDetail::WebSocket {
+            status,
+            upgraded,
+            accept_valid: headers.get("sec-websocket-accept") == Some(&expected),
+            subprotocol: headers.get("sec-websocket-protocol").cloned(),
+            extensions: headers.get("sec-websocket-extensions").cloned(),
+        })
+    }
+
+    /// Encodes a `HealthCheckRequest` as a length-prefixed gRPC message.
+    fn health_check_request(service: &str) -> Bytes {
+        let mut message: Vec<u8> = Vec::new();
+        if !service.is_empty() {
+            message.push(0x0a);
+            let mut len = service.len();
+            while len >= 0x80 {
+                message.push((len as u8 & 0x7f) | 0x80);
+                len >>= 7;
+            }
+            message.push(len as u8);
+            message.extend_from_slice(service.as_bytes());
+        }
+        let mut frame = vec![0u8];
+        frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        frame.extend(message);
+        Bytes::from(frame)
+    }
+
+    /// Reads the `status` field of a `HealthCheckResponse` message.
+    fn serving_status(body: &[u8]) -> Option<String> {
+        let message = body.get(5..)?;
+        let status = match message {
+            [] => 0,
+            [0x08, value, ..] => *value,
+            _ => return None,
+        };
+        Some(
+            match status {
+                1 => "SERVING",
+                2 => "NOT_SERVING",
+                3 => "SERVICE_UNKNOWN",
+                _ => "UNKNOWN",
+            }
+            .to_string(),
+        )
+    }
+
+    async fn probe_grpc<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+        stream: S,
+        host: &str,
+        service: &str,
+    ) -> Result<ProbeDetail, KubiousError> {
+        let (client, connection) = h2::client::handshake(stream)
+            .await
+            .map_err(|e| format!("HTTP/2 handshake failed: {}", e))?;
+        tauri::async_runtime::spawn(async move {
+            let _ = connection.await;
+        });
+        let mut client = client
+            .ready()
+            .await
+            .map_err(|e| format!("HTTP/2 connection failed: {}", e))?;
+        let request = http::Request::builder()
+            .method("POST")
+            .uri(format!("http://{}{}", host, HEALTH_CHECK_PATH))
+            .header("content-type", "application/grpc")
+            .header("te", "trailers")
+            .body(())
+            .or(Err("Failed to build request.".to_string()))?;
+        let (response, mut send) = client
+            .send_request(request, false)
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+        send.send_data(health_check_request(service), true)
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+
+        let response = response
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        let http_status = response.status().as_u16();
+        // Errors may come as a trailers-only response, in the headers.
+        let header = |headers: &http::HeaderMap, name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let mut grpc_status = header(response.headers(), "grpc-status");
+        let mut grpc_message = header(response.headers(), "grpc-message");
+        let mut body = response.into_body();
+        let mut data: Vec<u8> = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.map_err(|e| format!("Failed to read response: {}", e))?;
+            let _ = body.flow_control().release_capacity(chunk.len());
+            data.extend_from_slice(&chunk);
+        }
+        if let Ok(Some(trailers)) = body.trailers().await {
+            grpc_status = grpc_status.or(header(&trailers, "grpc-status"));
+            grpc_message = grpc_message.or(header(&trailers, "grpc-message"));
+        }
+        let grpc_status = grpc_status.and_then(|s| s.parse::<i32>().ok());
+        Ok(ProbeDetail::Grpc {
+            http_status,
+            grpc_status,
+            grpc_message,
+            serving_status: (grpc_status == Some(0))
+                .then(|| serving_status(&data))
+                .flatten(),
+        })
+    }
+
+    fn succeeded(detail: &ProbeDetail) -> bool {
+        match detail {
+            ProbeDetail::Http { status, .. } => *status < 400,
+            ProbeDetail::Grpc { serving_status, .. } => {
+                serving_status.as_deref() == Some("SERVING")
+            }
+            ProbeDetail::WebSocket {
+                upgraded,
+                accept_valid,
+                ..
+            } => *upgraded && *accept_valid,
+        }
+    }
+
+    /// Port-forwards to a pod behind a service and runs an HTTP, gRPC health
+    /// or WebSocket handshake probe against it. Connection failures are
+    /// reported in the result rather than as an error.
+    pub async fn probe(
+        client: Client,
+        namespace: String,
+        service: String,
+        port: Option<String>,
+        request: ProbeRequest,
+    ) -> Result<ProbeResult, KubiousError> {
+        let mut result = ProbeResult {
+            service: service.clone(),
+            pod: None,
+            port: None,
+            success: false,
+            latency_ms: 0,
+            detail: None,
+            error: None,
+        };
+        let (pod, target) = match backend(
+            &client,
+            namespace.as_str(),
+            service.as_str(),
+            port.as_deref(),
+        )
+        .await
+        {
+            Ok(backend) => backend,
+            Err(error) => {
+                result.error = Some(error.to_string());
+                return Ok(result);
+            }
+        };
+        result.pod = Some(pod.clone());
+        result.port = Some(target);
+        let host = format!("{}.{}", service, namespace);
+
+        let started = Instant::now();
+        let run = async {
+            let mut forwarder = Api::<Pod>::namespaced(client, namespace.as_str())
+                .portforward(pod.as_str(), &[target])
+                .await
+                .context(format!("Failed to port-forward to {}", target))?;
+            let mut stream = forwarder
+                .take_stream(target)
+                .ok_or("Port-forward stream unavailable".to_string())?;
+            let detail = match &request {
+                ProbeRequest::Http { path } => {
+                    probe_http(&mut stream, host.as_str(), path.as_deref().unwrap_or("/")).await
+                }
+                ProbeRequest::Grpc { service } => {
+                    probe_grpc(
+                        stream,
+                        host.as_str(),
+                        service.as_deref().unwrap_or_default(),
+                    )
+                    .await
+                }
+                ProbeRequest::WebSocket { path, subprotocols } => {
+                    probe_websocket(
+                        &mut stream,
+                        host.as_str(),
+                        path.as_deref().unwrap_or("/"),
+                        subprotocols,
+                    )
+                    .await
+                }
+            };
+            forwarder.abort();
+            detail
+        };
+        match timeout(PROBE_TIMEOUT, run).await {
+            Ok(Ok(detail)) => {
+                result.success = succeeded(&detail);
+                result.detail = Some(detail);
+            }
+            Ok(Err(error)) => result.error = Some(error.to_string()),
+            Err(_) => result.error = Some("Probe timed out".to_string()),
+        }
+        result.latency_ms = started.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+}
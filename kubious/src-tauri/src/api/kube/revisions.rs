@@ -0,0 +1,295 @@
+pub mod revision_logs {
+    use k8s_openapi::{
+        api::{
+            apps::v1::{Deployment, ReplicaSet},
+            core::v1::Pod,
+        },
+        apimachinery::pkg::apis::meta::v1::OwnerReference,
+    };
+    use kube::{
+        api::{ListParams, LogParams},
+        Api, Client, ResourceExt,
+    };
+    use serde::{Deserialize, Serialize};
+    use tauri::AppHandle;
+
+    use crate::{
+        api::termination_history::{self, TerminationQuery},
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    const REVISION_ANNOTATION: &str = "deployment.kubernetes.io/revision";
+    const DEFAULT_TAIL_LINES: i64 = 200;
+    /// Pods read per revision; replicas of one revision usually log alike.
+    const MAX_PODS: usize = 3;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum LogSource {
+        /// Read from a pod that still exists.
+        Live,
+        /// Taken from the termination history, for pods already deleted.
+        /// Only the last lines before each termination are kept there.
+        Recorded,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PodLogs {
+        pub pod: String,
+        pub container: String,
+        pub source: LogSource,
+        /// When a recorded container terminated.
+        pub finished_at: Option<String>,
+        pub lines: Vec<String>,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RevisionLogs {
+        pub revision: i64,
+        pub replica_set: String,
+        pub images: Vec<String>,
+        pub created: Option<String>,
+        pub pods: Vec<PodLogs>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RevisionComparison {
+        pub namespace: String,
+        pub deployment: String,
+        pub current: Option<RevisionLogs>,
+        /// The revision rolled out before the current one, if its
+        /// ReplicaSet is still kept by the Deployment's revision history.
+        pub previous: Option<RevisionLogs>,
+        pub notes: Vec<String>,
+    }
+
+    fn revision_of(replica_set: &ReplicaSet) -> i64 {
+        replica_set
+            .annotations()
+            .get(REVISION_ANNOTATION)
+            .and_then(|r| r.parse().ok())
+            .unwrap_or(0)
+    }
+
+    fn owned_by(owners: &[OwnerReference], uid: &str) -> bool {
+        owners
+            .iter()
+            .any(|o| o.controller == Some(true) && o.uid == uid)
+    }
+
+    fn container_names(replica_set: &ReplicaSet, container: &Option<String>) -> Vec<String> {
+        let names: Vec<String> = replica_set
+            .spec
+            .as_ref()
+            .and_then(|s| s.template.as_ref())
+            .and_then(|t| t.spec.as_ref())
+            .map(|s| s.containers.iter().map(|c| c.name.clone()).collect())
+            .unwrap_or_default();
+        match container {
+            Some(container) => names.into_iter().filter(|n| n == container).collect(),
+            None => names.into_iter().take(1).collect(),
+        }
+    }
+
+    async fn live_logs(
+        api: &Api<Pod>,
+        pods: &[Pod],
+        containers: &[String],
+        tail_lines: i64,
+    ) -> Vec<PodLogs> {
+        let mut logs: Vec<PodLogs> = Vec::new();
+        for pod in pods.iter().take(MAX_PODS) {
+            for container in containers {
+                let params = LogParams {
+                    container: Some(container.clone()),
+                    tail_lines: Some(tail_lines),
+                    timestamps: true,
+                    ..LogParams::default()
+                };
+                let read = api.logs(pod.name_any().as_str(), &params).await;
+                logs.push(PodLogs {
+                    pod: pod.name_any(),
+                    container: container.clone(),
+                    source: LogSource::Live,
+                    finished_at: None,
+                    lines: read
+                        .as_ref()
+                        .map(|l| l.lines().map(|l| l.to_string()).collect())
+                        .unwrap_or_default(),
+                    error: read.err().map(|e| e.to_string()),
+                });
+            }
+        }
+        logs
+    }
+
+    fn recorded_logs(
+        handle: &AppHandle,
+        cluster: &str,
+        namespace: &str,
+        deployment: &str,
+        replica_set: &str,
+        containers: &[String],
+    ) -> Vec<PodLogs> {
+        let query = TerminationQuery {
+            cluster: Some(cluster.to_string()),
+            namespace: Some(namespace.to_string()),
+            workload: Some(format!("Deployment/{}", deployment)),
+            container: None,
+            limit: None,
+        };
+        let prefix = format!("{}-", replica_set);
+        termination_history::query(handle, &query)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|r| r.pod.starts_with(prefix.as_str()) && containers.contains(&r.container))
+            .take(MAX_PODS)
+            .map(|r| PodLogs {
+                pod: r.pod,
+                container: r.container,
+                source: LogSource::Recorded,
+                finished_at: r.finished_at,
+                lines: r.last_lines,
+                error: None,
+            })
+            .collect()
+    }
+
+    async fn read_revision(
+        handle: &AppHandle,
+        client: &Client,
+        cluster: &str,
+        deployment: &str,
+        replica_set: &ReplicaSet,
+        container: &Option<String>,
+        tail_lines: i64,
+    ) -> RevisionLogs {
+        let namespace = replica_set.namespace().unwrap_or_default();
+        let name = replica_set.name_any();
+        let containers = container_names(replica_set, container);
+        let api: Api<Pod> = Api::namespaced(client.clone(), namespace.as_str());
+        let uid = replica_set.uid().unwrap_or_default();
+        let mut params = ListParams::default();
+        if let Some(hash) = replica_set.labels().get("pod-template-hash") {
+            params = params.labels(format!("pod-template-hash={}", hash).as_str());
+        }
+        let pods: Vec<Pod> = api
+            .list(&params)
+            .await
+            .map(|list| list.items)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| owned_by(p.owner_references(), uid.as_str()))
+            .collect();
+
+        let pods = if pods.is_empty() {
+            recorded_logs(
+                handle,
+                cluster,
+                namespace.as_str(),
+                deployment,
+                name.as_str(),
+                &containers,
+            )
+        } else {
+            live_logs(&api, &pods, &containers, tail_lines).await
+        };
+        RevisionLogs {
+            revision: revision_of(replica_set),
+            images: replica_set
+                .spec
+                .as_ref()
+                .and_then(|s| s.template.as_ref())
+                .and_then(|t| t.spec.as_ref())
+                .map(|s| {
+                    s.containers
+                        .iter()
+                        .filter_map(|c| c.image.clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            created: replica_set.creation_timestamp().map(|t| t.0.to_rfc3339()),
+            replica_set: name,
+            pods,
+        }
+    }
+
+    /// Reads the logs of a Deployment's current revision and of the one
+    /// before it, for comparing behaviour before and after a rollout.
+    /// Pods of the previous revision are usually gone once a rollout
+    /// completes; their last lines are then taken from the termination
+    /// history, which only has them if termination logging was running.
+    pub async fn compare(
+        handle: &AppHandle,
+        client: Client,
+        cluster: String,
+        namespace: String,
+        deployment: String,
+        container: Option<String>,
+        tail_lines: Option<i64>,
+    ) -> Result<RevisionComparison, KubiousError> {
+        let tail_lines = tail_lines.unwrap_or(DEFAULT_TAIL_LINES);
+        let owner = Api::<Deployment>::namespaced(client.clone(), namespace.as_str())
+            .get(deployment.as_str())
+            .await
+            .context(format!("Failed to get deployment {}", deployment))?;
+        let uid = owner.uid().unwrap_or_default();
+        let mut replica_sets: Vec<ReplicaSet> =
+            Api::<ReplicaSet>::namespaced(client.clone(), namespace.as_str())
+                .list(&ListParams::default())
+                .await
+                .context("Failed to list replica sets")?
+                .items
+                .into_iter()
+                .filter(|rs| owned_by(rs.owner_references(), uid.as_str()))
+                .collect();
+        replica_sets.sort_by_key(|rs| std::cmp::Reverse(revision_of(rs)));
+
+        let mut comparison = RevisionComparison {
+            namespace,
+            deployment: deployment.clone(),
+            current: None,
+            previous: None,
+            notes: Vec::new(),
+        };
+        if let Some(current) = replica_sets.first() {
+            comparison.current = Some(
+                read_revision(
+                    handle,
+                    &client,
+                    cluster.as_str(),
+                    deployment.as_str(),
+                    current,
+                    &container,
+                    tail_lines,
+                )
+                .await,
+            );
+        }
+        match replica_sets.get(1) {
+            Some(previous) => {
+                let logs = read_revision(
+                    handle,
+                    &client,
+                    cluster.as_str(),
+                    deployment.as_str(),
+                    previous,
+                    &container,
+                    tail_lines,
+                )
+                .await;
+                if logs.pods.is_empty() {
+                    comparison.notes.push(format!(
+                        "No pods of revision {} remain and none were recorded; start termination logging to keep logs of replaced pods",
+                        logs.revision
+                    ));
+                }
+                comparison.previous = Some(logs);
+            }
+            None => comparison.notes.push(
+                "No previous revision is kept; the deployment has one revision or revisionHistoryLimit is 0".to_string(),
+            ),
+        }
+        Ok(comparison)
+    }
+}
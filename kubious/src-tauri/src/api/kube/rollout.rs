@@ -0,0 +1,747 @@
+pub mod workload_rollout {
+    use std::{
+        collections::BTreeMap,
+        time::{Duration, Instant},
+    };
+
+    use k8s_openapi::{
+        api::{
+            apps::v1::{ControllerRevision, DaemonSet, Deployment, ReplicaSet, StatefulSet},
+            core::v1::{Container, ContainerStatus, Event, Pod, PodSpec},
+        },
+        apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta},
+        chrono::Utc,
+    };
+    use kube::{
+        api::{ListParams, Patch, PatchParams, PostParams},
+        Api, Client, ResourceExt,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    const REVISION_ANNOTATION: &str = "deployment.kubernetes.io/revision";
+    const CHANGE_CAUSE_ANNOTATION: &str = "kubernetes.io/change-cause";
+    const RESTARTED_AT_ANNOTATION: &str = "kubectl.kubernetes.io/restartedAt";
+    const DEFAULT_TIMEOUT: u64 = 300;
+    const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+    /// Container states that will not resolve without a new image or config.
+    const FAILURE_REASONS: [&str; 6] = [
+        "ErrImagePull",
+        "ImagePullBackOff",
+        "InvalidImageName",
+        "CrashLoopBackOff",
+        "CreateContainerConfigError",
+        "CreateContainerError",
+    ];
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum RolloutOutcome {
+        Succeeded,
+        Failed,
+        TimedOut,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct FailingPod {
+        pub name: String,
+        pub container: String,
+        pub reason: String,
+        pub message: Option<String>,
+        pub events: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RolloutResult {
+        pub kind: String,
+        pub namespace: String,
+        pub name: String,
+        pub container: String,
+        pub previous_image: Option<String>,
+        pub image: String,
+        pub outcome: RolloutOutcome,
+        pub message: String,
+        pub elapsed_secs: u64,
+        pub failing_pod: Option<FailingPod>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct GatedRestart {
+        pub kind: String,
+        pub namespace: String,
+        pub name: String,
+        pub outcome: RolloutOutcome,
+        pub message: String,
+        pub elapsed_secs: u64,
+        pub failing_pod: Option<FailingPod>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RolloutStatus {
+        pub kind: String,
+        pub namespace: String,
+        pub name: String,
+        /// The Deployment's current revision; not tracked for other kinds.
+        pub revision: Option<i64>,
+        pub desired: i32,
+        pub total: i32,
+        pub updated: i32,
+        pub available: i32,
+        pub complete: bool,
+        pub failure: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RolloutRevision {
+        pub revision: i64,
+        /// The ReplicaSet of a Deployment revision, or the ControllerRevision
+        /// of a StatefulSet or DaemonSet revision.
+        pub source: String,
+        pub images: Vec<String>,
+        pub change_cause: Option<String>,
+        pub created: Option<String>,
+        pub current: bool,
+    }
+
+    /// Rollout state shared by the supported workload kinds.
+    struct Progress {
+        observed: bool,
+        desired: i32,
+        /// Pods of all revisions, so old pods still terminating are counted.
+        total: i32,
+        updated: i32,
+        available: i32,
+        /// Set when the controller itself gave up, e.g. a progress deadline.
+        failure: Option<String>,
+    }
+
+    impl Progress {
+        fn complete(&self) -> bool {
+            self.observed
+                && self.updated >= self.desired
+                && self.total <= self.updated
+                && self.available >= self.desired
+        }
+    }
+
+    enum Workload {
+        Deployment(Api<Deployment>),
+        StatefulSet(Api<StatefulSet>),
+        DaemonSet(Api<DaemonSet>),
+    }
+
+    impl Workload {
+        fn new(client: Client, namespace: &str, kind: &str) -> Result<Self, KubiousError> {
+            match kind {
+                "Deployment" => Ok(Workload::Deployment(Api::namespaced(client, namespace))),
+                "StatefulSet" => Ok(Workload::StatefulSet(Api::namespaced(client, namespace))),
+                "DaemonSet" => Ok(Workload::DaemonSet(Api::namespaced(client, namespace))),
+                _ => Err(format!("Unsupported workload kind {}", kind).into()),
+            }
+        }
+
+        /// The pod template and selector of the workload.
+        async fn template(&self, name: &str) -> Result<(PodSpec, LabelSelector), KubiousError> {
+            let failed = || format!("Failed to get workload {}.", name);
+            let (template, selector) = match self {
+                Workload::Deployment(api) => {
+                    let spec = api.get(name).await.context(failed())?.spec;
+                    spec.map(|s| (s.template, s.selector))
+                }
+                Workload::StatefulSet(api) => {
+                    let spec = api.get(name).await.context(failed())?.spec;
+                    spec.map(|s| (s.template, s.selector))
+                }
+                Workload::DaemonSet(api) => {
+                    let spec = api.get(name).await.context(failed())?.spec;
+                    spec.map(|s| (s.template, s.selector))
+                }
+            }
+            .ok_or(failed())?;
+            Ok((template.spec.unwrap_or_default(), selector))
+        }
+
+        async fn patch(&self, name: &str, patch: &serde_json::Value) -> Result<(), KubiousError> {
+            let params = PatchParams::default();
+            let patch = Patch::Strategic(patch);
+            let result = match self {
+                Workload::Deployment(api) => api.patch(name, &params, &patch).await.map(|_| ()),
+                Workload::StatefulSet(api) => api.patch(name, &params, &patch).await.map(|_| ()),
+                Workload::DaemonSet(api) => api.patch(name, &params, &patch).await.map(|_| ()),
+            };
+            result.context(format!("Failed to update {}", name))
+        }
+
+        async fn progress(&self, name: &str) -> Result<Progress, KubiousError> {
+            let failed = || format!("Failed to get workload {}.", name);
+            match self {
+                Workload::Deployment(api) => {
+                    let deployment = api.get(name).await.context(failed())?;
+                    let status = deployment.status.unwrap_or_default();
+                    let failure = status
+                        .conditions
+                        .iter()
+                        .flatten()
+                        .find(|c| {
+                            c.type_ == "Progressing"
+                                && c.status == "False"
+                                && c.reason.as_deref() == Some("ProgressDeadlineExceeded")
+                        })
+                        .map(|c| c.message.clone().unwrap_or(c.type_.clone()));
+                    Ok(Progress {
+                        observed: status.observed_generation >= deployment.metadata.generation,
+                        desired: deployment.spec.and_then(|s| s.replicas).unwrap_or(1),
+                        total: status.replicas.unwrap_or(0),
+                        updated: status.updated_replicas.unwrap_or(0),
+                        available: status.available_replicas.unwrap_or(0),
+                        failure,
+                    })
+                }
+                Workload::StatefulSet(api) => {
+                    let set = api.get(name).await.context(failed())?;
+                    let status = set.status.unwrap_or_default();
+                    Ok(Progress {
+                        observed: status.observed_generation >= set.metadata.generation
+                            && status.update_revision.is_some()
+                            && status.current_revision == status.update_revision,
+                        desired: set.spec.and_then(|s| s.replicas).unwrap_or(1),
+                        total: status.replicas,
+                        updated: status.updated_replicas.unwrap_or(0),
+                        available: status.available_replicas.unwrap_or(0),
+                        failure: None,
+                    })
+                }
+                Workload::DaemonSet(api) => {
+                    let set = api.get(name).await.context(failed())?;
+                    let status = set.status.unwrap_or_default();
+                    Ok(Progress {
+                        observed: status.observed_generation >= set.metadata.generation,
+                        desired: status.desired_number_scheduled,
+                        total: status.current_number_scheduled,
+                        updated: status.updated_number_scheduled.unwrap_or(0),
+                        available: status.number_available.unwrap_or(0),
+                        failure: None,
+                    })
+                }
+            }
+        }
+    }
+
+    fn revision_of(meta: &ObjectMeta) -> Option<i64> {
+        meta.annotations
+            .as_ref()
+            .and_then(|a| a.get(REVISION_ANNOTATION))
+            .and_then(|r| r.parse().ok())
+    }
+
+    fn images(spec: Option<&PodSpec>) -> Vec<String> {
+        spec.map(|s| {
+            s.containers
+                .iter()
+                .filter_map(|c| c.image.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    fn revision_entry(meta: &ObjectMeta, revision: i64, images: Vec<String>) -> RolloutRevision {
+        RolloutRevision {
+            revision,
+            source: meta.name.clone().unwrap_or_default(),
+            images,
+            change_cause: meta
+                .annotations
+                .as_ref()
+                .and_then(|a| a.get(CHANGE_CAUSE_ANNOTATION).cloned()),
+            created: meta.creation_timestamp.as_ref().map(|t| t.0.to_rfc3339()),
+            current: false,
+        }
+    }
+
+    fn controlled_by(meta: &ObjectMeta, uid: &str) -> bool {
+        meta.owner_references
+            .iter()
+            .flatten()
+            .any(|o| o.controller == Some(true) && o.uid == uid)
+    }
+
+    fn selector_string(selector: &LabelSelector) -> String {
+        selector
+            .match_labels
+            .clone()
+            .unwrap_or_default()
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    async fn pod_events(client: &Client, namespace: &str, pod: &str) -> Vec<String> {
+        let params = ListParams::default()
+            .fields(format!("involvedObject.kind=Pod,involvedObject.name={}", pod).as_str());
+        Api::<Event>::namespaced(client.clone(), namespace)
+            .list(&params)
+            .await
+            .map(|events| {
+                events
+                    .items
+                    .into_iter()
+                    .filter(|e| e.type_.as_deref() == Some("Warning"))
+                    .filter_map(|e| e.message)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Finds a pod with a container stuck in a failure state, among the
+    /// pods and containers `matches` accepts.
+    async fn failing_pod(
+        client: &Client,
+        namespace: &str,
+        selector: &str,
+        matches: impl Fn(&ObjectMeta, &ContainerStatus) -> bool,
+    ) -> Option<FailingPod> {
+        let pods = Api::<Pod>::namespaced(client.clone(), namespace)
+            .list(&ListParams::default().labels(selector))
+            .await
+            .ok()?;
+        for pod in pods.items {
+            let status = pod.status.unwrap_or_default();
+            let statuses = status
+                .container_statuses
+                .iter()
+                .flatten()
+                .chain(status.init_container_statuses.iter().flatten());
+            for status in statuses {
+                if !matches(&pod.metadata, status) {
+                    continue;
+                }
+                let Some(waiting) = status.state.as_ref().and_then(|s| s.waiting.as_ref()) else {
+                    continue;
+                };
+                let reason = waiting.reason.clone().unwrap_or_default();
+                if FAILURE_REASONS.contains(&reason.as_str()) {
+                    let name = pod.metadata.name.clone().unwrap_or_default();
+                    return Some(FailingPod {
+                        events: pod_events(client, namespace, name.as_str()).await,
+                        name,
+                        container: status.name.clone(),
+                        reason,
+                        message: waiting.message.clone(),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Sets the image of one container of a workload, then watches the
+    /// rollout until it completes, fails or `timeout` seconds pass.
+    pub async fn set_image(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+        container: String,
+        image: String,
+        timeout: Option<u64>,
+    ) -> Result<RolloutResult, KubiousError> {
+        let workload = Workload::new(client.clone(), namespace.as_str(), kind.as_str())?;
+        let (spec, selector) = workload.template(name.as_str()).await?;
+        let find = |containers: &Vec<Container>| {
+            containers
+                .iter()
+                .find(|c| c.name == container)
+                .map(|c| c.image.clone())
+        };
+        let (field, previous_image) = if let Some(image) = find(&spec.containers) {
+            ("containers", image)
+        } else if let Some(image) = spec.init_containers.as_ref().and_then(find) {
+            ("initContainers", image)
+        } else {
+            return Err(format!("Container {} not found in {}", container, name).into());
+        };
+
+        let mut template_spec = BTreeMap::new();
+        template_spec.insert(field, json!([{"name": container, "image": image}]));
+        let patch = json!({"spec": {"template": {"spec": template_spec}}});
+        workload.patch(name.as_str(), &patch).await?;
+
+        let selector = selector_string(&selector);
+        let started = Instant::now();
+        let timeout = Duration::from_secs(timeout.unwrap_or(DEFAULT_TIMEOUT));
+        let mut result = RolloutResult {
+            kind,
+            namespace: namespace.clone(),
+            name: name.clone(),
+            container: container.clone(),
+            previous_image,
+            image: image.clone(),
+            outcome: RolloutOutcome::TimedOut,
+            message: String::new(),
+            elapsed_secs: 0,
+            failing_pod: None,
+        };
+
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let progress = workload.progress(name.as_str()).await?;
+            if progress.complete() {
+                result.outcome = RolloutOutcome::Succeeded;
+                result.message = format!(
+                    "{} of {} replicas updated",
+                    progress.updated, progress.desired
+                );
+                break;
+            }
+            let stuck = failing_pod(
+                &client,
+                namespace.as_str(),
+                selector.as_str(),
+                |_, status| status.name == container && status.image.ends_with(image.as_str()),
+            )
+            .await;
+            if stuck.is_some() || progress.failure.is_some() {
+                result.outcome = RolloutOutcome::Failed;
+                result.message = progress.failure.unwrap_or(
+                    stuck
+                        .as_ref()
+                        .map(|p| format!("Pod {} is in {}", p.name, p.reason))
+                        .unwrap_or_default(),
+                );
+                result.failing_pod = stuck;
+                break;
+            }
+            if started.elapsed() >= timeout {
+                result.message = format!(
+                    "{} of {} replicas updated, {} available",
+                    progress.updated, progress.desired, progress.available
+                );
+                break;
+            }
+        }
+        result.elapsed_secs = started.elapsed().as_secs();
+        Ok(result)
+    }
+
+    /// Restarts the workload's pods the way `kubectl rollout restart` does,
+    /// by stamping the pod template.
+    pub async fn restart(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+    ) -> Result<RolloutStatus, KubiousError> {
+        let workload = Workload::new(client.clone(), namespace.as_str(), kind.as_str())?;
+        workload
+            .patch(
+                name.as_str(),
+                &restart_patch(Utc::now().to_rfc3339().as_str()),
+            )
+            .await?;
+        status(client, namespace, kind, name).await
+    }
+
+    fn restart_patch(at: &str) -> Value {
+        json!({
+            "spec": {
+                "template": {
+                    "metadata": {
+                        "annotations": { RESTARTED_AT_ANNOTATION: at }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Restarts a workload and waits until its new pods are ready, one of
+    /// them gets stuck, or `timeout` seconds pass.
+    pub async fn restart_and_wait(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+        timeout: Option<u64>,
+    ) -> Result<GatedRestart, KubiousError> {
+        let workload = Workload::new(client.clone(), namespace.as_str(), kind.as_str())?;
+        let (_, selector) = workload.template(name.as_str()).await?;
+        let stamp = Utc::now().to_rfc3339();
+        workload
+            .patch(name.as_str(), &restart_patch(stamp.as_str()))
+            .await?;
+
+        let selector = selector_string(&selector);
+        let started = Instant::now();
+        let timeout = Duration::from_secs(timeout.unwrap_or(DEFAULT_TIMEOUT));
+        let mut result = GatedRestart {
+            kind,
+            namespace: namespace.clone(),
+            name: name.clone(),
+            outcome: RolloutOutcome::TimedOut,
+            message: String::new(),
+            elapsed_secs: 0,
+            failing_pod: None,
+        };
+
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let progress = workload.progress(name.as_str()).await?;
+            if progress.complete() {
+                result.outcome = RolloutOutcome::Succeeded;
+                result.message = format!(
+                    "{} of {} replicas ready",
+                    progress.available, progress.desired
+                );
+                break;
+            }
+            // Only pods of the restarted template count; older pods are
+            // being replaced anyway.
+            let stuck = failing_pod(&client, namespace.as_str(), selector.as_str(), |meta, _| {
+                meta.annotations
+                    .as_ref()
+                    .and_then(|a| a.get(RESTARTED_AT_ANNOTATION))
+                    == Some(&stamp)
+            })
+            .await;
+            if stuck.is_some() || progress.failure.is_some() {
+                result.outcome = RolloutOutcome::Failed;
+                result.message = progress.failure.unwrap_or(
+                    stuck
+                        .as_ref()
+                        .map(|p| format!("Pod {} is in {}", p.name, p.reason))
+                        .unwrap_or_default(),
+                );
+                result.failing_pod = stuck;
+                break;
+            }
+            if started.elapsed() >= timeout {
+                result.message = format!(
+                    "{} of {} replicas updated, {} available",
+                    progress.updated, progress.desired, progress.available
+                );
+                break;
+            }
+        }
+        result.elapsed_secs = started.elapsed().as_secs();
+        Ok(result)
+    }
+
+    pub async fn status(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+    ) -> Result<RolloutStatus, KubiousError> {
+        let workload = Workload::new(client, namespace.as_str(), kind.as_str())?;
+        let progress = workload.progress(name.as_str()).await?;
+        let revision = match &workload {
+            Workload::Deployment(api) => api
+                .get(name.as_str())
+                .await
+                .ok()
+                .and_then(|d| revision_of(&d.metadata)),
+            _ => None,
+        };
+        Ok(RolloutStatus {
+            complete: progress.complete(),
+            kind,
+            namespace,
+            name,
+            revision,
+            desired: progress.desired,
+            total: progress.total,
+            updated: progress.updated,
+            available: progress.available,
+            failure: progress.failure,
+        })
+    }
+
+    async fn deployment_history(
+        client: &Client,
+        api: &Api<Deployment>,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Vec<RolloutRevision>, KubiousError> {
+        let deployment = api
+            .get(name)
+            .await
+            .context(format!("Failed to get workload {}", name))?;
+        let uid = deployment.uid().unwrap_or_default();
+        let current = revision_of(&deployment.metadata);
+        let selector = deployment
+            .spec
+            .map(|s| selector_string(&s.selector))
+            .unwrap_or_default();
+        let replica_sets = Api::<ReplicaSet>::namespaced(client.clone(), namespace)
+            .list(&ListParams::default().labels(selector.as_str()))
+            .await
+            .context("Failed to list ReplicaSets")?;
+        Ok(replica_sets
+            .items
+            .into_iter()
+            .filter(|rs| controlled_by(&rs.metadata, uid.as_str()))
+            .filter_map(|rs| {
+                let revision = revision_of(&rs.metadata)?;
+                let spec = rs.spec.as_ref().and_then(|s| s.template.as_ref());
+                let mut entry = revision_entry(
+                    &rs.metadata,
+                    revision,
+                    images(spec.and_then(|t| t.spec.as_ref())),
+                );
+                entry.current = current == Some(revision);
+                Some(entry)
+            })
+            .collect())
+    }
+
+    /// StatefulSets and DaemonSets keep their history as ControllerRevisions
+    /// holding a patch of the pod template.
+    async fn controller_history(
+        client: &Client,
+        namespace: &str,
+        uid: String,
+        selector: &LabelSelector,
+    ) -> Result<Vec<RolloutRevision>, KubiousError> {
+        let revisions = Api::<ControllerRevision>::namespaced(client.clone(), namespace)
+            .list(&ListParams::default().labels(selector_string(selector).as_str()))
+            .await
+            .context("Failed to list ControllerRevisions")?;
+        let mut history: Vec<RolloutRevision> = revisions
+            .items
+            .into_iter()
+            .filter(|cr| controlled_by(&cr.metadata, uid.as_str()))
+            .map(|cr| {
+                let data = cr.data.map(|d| d.0).unwrap_or(Value::Null);
+                let images = data["spec"]["template"]["spec"]["containers"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|c| c["image"].as_str().map(|i| i.to_string()))
+                    .collect();
+                revision_entry(&cr.metadata, cr.revision, images)
+            })
+            .collect();
+        let latest = history.iter().map(|r| r.revision).max();
+        for entry in history.iter_mut() {
+            entry.current = Some(entry.revision) == latest;
+        }
+        Ok(history)
+    }
+
+    /// The revisions still kept for the workload, oldest first.
+    pub async fn history(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+    ) -> Result<Vec<RolloutRevision>, KubiousError> {
+        let workload = Workload::new(client.clone(), namespace.as_str(), kind.as_str())?;
+        let failed = |e: kube::Error| {
+            KubiousError::from(e).with_context(format!("Failed to get workload {}", name))
+        };
+        let mut history = match &workload {
+            Workload::Deployment(api) => {
+                deployment_history(&client, api, namespace.as_str(), name.as_str()).await?
+            }
+            Workload::StatefulSet(api) => {
+                let set = api.get(name.as_str()).await.map_err(failed)?;
+                let selector = set.spec.map(|s| s.selector).unwrap_or_default();
+                controller_history(
+                    &client,
+                    namespace.as_str(),
+                    set.metadata.uid.unwrap_or_default(),
+                    &selector,
+                )
+                .await?
+            }
+            Workload::DaemonSet(api) => {
+                let set = api.get(name.as_str()).await.map_err(failed)?;
+                let selector = set.spec.map(|s| s.selector).unwrap_or_default();
+                controller_history(
+                    &client,
+                    namespace.as_str(),
+                    set.metadata.uid.unwrap_or_default(),
+                    &selector,
+                )
+                .await?
+            }
+        };
+        history.sort_by_key(|r| r.revision);
+        Ok(history)
+    }
+
+    /// Rolls the pod template back to `revision`, the one before the current
+    /// revision if omitted, like `kubectl rollout undo`. The rollback itself
+    /// becomes the newest revision.
+    pub async fn undo(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+        revision: Option<i64>,
+    ) -> Result<RolloutRevision, KubiousError> {
+        let history = history(
+            client.clone(),
+            namespace.clone(),
+            kind.clone(),
+            name.clone(),
+        )
+        .await?;
+        let current = history.iter().find(|r| r.current).map(|r| r.revision);
+        let target = match revision {
+            Some(revision) => history.iter().find(|r| r.revision == revision),
+            None => history
+                .iter()
+                .rev()
+                .find(|r| current.is_none_or(|c| r.revision < c)),
+        }
+        .ok_or(format!("No revision to roll {} back to", name))?;
+        if current == Some(target.revision) {
+            return Err(format!("Revision {} is already current", target.revision).into());
+        }
+
+        let failed = |e: kube::Error| {
+            KubiousError::from(e).with_context(format!("Failed to roll back {}", name))
+        };
+        match Workload::new(client.clone(), namespace.as_str(), kind.as_str())? {
+            Workload::Deployment(api) => {
+                let mut deployment = api.get(name.as_str()).await.map_err(failed)?;
+                let Some(spec) = deployment.spec.as_mut() else {
+                    return Err(format!("{} has no spec", name).into());
+                };
+                if spec.paused == Some(true) {
+                    return Err(format!("{} is paused; resume it before rolling back", name).into());
+                }
+                let mut template = Api::<ReplicaSet>::namespaced(client, namespace.as_str())
+                    .get(target.source.as_str())
+                    .await
+                    .map_err(failed)?
+                    .spec
+                    .and_then(|s| s.template)
+                    .ok_or(format!("{} has no pod template", target.source))?;
+                // Added by the Deployment controller to tell its ReplicaSets
+                // apart; it is recomputed for the restored template.
+                if let Some(labels) = template.metadata.as_mut().and_then(|m| m.labels.as_mut()) {
+                    labels.remove("pod-template-hash");
+                }
+                spec.template = template;
+                api.replace(name.as_str(), &PostParams::default(), &deployment)
+                    .await
+                    .map_err(failed)?;
+            }
+            workload => {
+                let data = Api::<ControllerRevision>::namespaced(client, namespace.as_str())
+                    .get(target.source.as_str())
+                    .await
+                    .map_err(failed)?
+                    .data
+                    .map(|d| d.0)
+                    .ok_or(format!("{} holds no template", target.source))?;
+                workload.patch(name.as_str(), &data).await?;
+            }
+        }
+        Ok(target.clone())
+    }
+}
@@ -0,0 +1,140 @@
+pub mod workload_scale {
+    use k8s_openapi::api::apps::v1::{Deployment, ReplicaSet, StatefulSet};
+    use kube::{
+        api::{Patch, PatchParams},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ReplicaStatus {
+        pub kind: String,
+        pub namespace: String,
+        pub name: String,
+        /// Replicas requested in the spec.
+        pub desired: i32,
+        /// Pods currently created by the controller, of any revision.
+        pub current: i32,
+        pub ready: i32,
+        /// Pods on the latest revision. Equal to `current` for ReplicaSets.
+        pub updated: i32,
+        /// Whether the controller has seen the latest spec and the counts
+        /// have settled at `desired`.
+        pub settled: bool,
+    }
+
+    enum Scalable {
+        Deployment(Api<Deployment>),
+        StatefulSet(Api<StatefulSet>),
+        ReplicaSet(Api<ReplicaSet>),
+    }
+
+    impl Scalable {
+        fn new(client: Client, namespace: &str, kind: &str) -> Result<Self, KubiousError> {
+            match kind {
+                "Deployment" => Ok(Scalable::Deployment(Api::namespaced(client, namespace))),
+                "StatefulSet" => Ok(Scalable::StatefulSet(Api::namespaced(client, namespace))),
+                "ReplicaSet" => Ok(Scalable::ReplicaSet(Api::namespaced(client, namespace))),
+                _ => Err(format!("Cannot scale workload kind {}", kind).into()),
+            }
+        }
+
+        /// Desired, current, ready and updated replicas, and whether the
+        /// latest generation was observed.
+        async fn counts(&self, name: &str) -> Result<(i32, i32, i32, i32, bool), KubiousError> {
+            let failed = |e: kube::Error| {
+                KubiousError::from(e).with_context(format!("Failed to get workload {}", name))
+            };
+            match self {
+                Scalable::Deployment(api) => {
+                    let deployment = api.get(name).await.map_err(failed)?;
+                    let status = deployment.status.unwrap_or_default();
+                    Ok((
+                        deployment.spec.and_then(|s| s.replicas).unwrap_or(1),
+                        status.replicas.unwrap_or(0),
+                        status.ready_replicas.unwrap_or(0),
+                        status.updated_replicas.unwrap_or(0),
+                        status.observed_generation >= deployment.metadata.generation,
+                    ))
+                }
+                Scalable::StatefulSet(api) => {
+                    let statefulset = api.get(name).await.map_err(failed)?;
+                    let status = statefulset.status.unwrap_or_default();
+                    Ok((
+                        statefulset.spec.and_then(|s| s.replicas).unwrap_or(1),
+                        status.replicas,
+                        status.ready_replicas.unwrap_or(0),
+                        status.updated_replicas.unwrap_or(0),
+                        status.observed_generation >= statefulset.metadata.generation,
+                    ))
+                }
+                Scalable::ReplicaSet(api) => {
+                    let replicaset = api.get(name).await.map_err(failed)?;
+                    let status = replicaset.status.unwrap_or_default();
+                    Ok((
+                        replicaset.spec.and_then(|s| s.replicas).unwrap_or(1),
+                        status.replicas,
+                        status.ready_replicas.unwrap_or(0),
+                        status.replicas,
+                        status.observed_generation >= replicaset.metadata.generation,
+                    ))
+                }
+            }
+        }
+
+        async fn patch_scale(&self, name: &str, replicas: i32) -> Result<(), KubiousError> {
+            let patch = json!({ "spec": { "replicas": replicas } });
+            let params = PatchParams::default();
+            let patch = Patch::Merge(&patch);
+            let result = match self {
+                Scalable::Deployment(api) => api.patch_scale(name, &params, &patch).await,
+                Scalable::StatefulSet(api) => api.patch_scale(name, &params, &patch).await,
+                Scalable::ReplicaSet(api) => api.patch_scale(name, &params, &patch).await,
+            };
+            result
+                .map(|_| ())
+                .context(format!("Failed to scale {}", name))
+        }
+    }
+
+    pub async fn status(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+    ) -> Result<ReplicaStatus, KubiousError> {
+        let workload = Scalable::new(client, namespace.as_str(), kind.as_str())?;
+        let (desired, current, ready, updated, observed) = workload.counts(name.as_str()).await?;
+        Ok(ReplicaStatus {
+            settled: observed && current == desired && ready == desired && updated == desired,
+            kind,
+            namespace,
+            name,
+            desired,
+            current,
+            ready,
+            updated,
+        })
+    }
+
+    /// Sets the replica count through the `scale` subresource, so only
+    /// `spec.replicas` changes, and returns the status right after.
+    pub async fn scale(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+        replicas: i32,
+    ) -> Result<ReplicaStatus, KubiousError> {
+        if replicas < 0 {
+            return Err("Replicas cannot be negative".into());
+        }
+        Scalable::new(client.clone(), namespace.as_str(), kind.as_str())?
+            .patch_scale(name.as_str(), replicas)
+            .await?;
+        status(client, namespace, kind, name).await
+    }
+}
@@ -0,0 +1,291 @@
+pub mod scratch_namespaces {
+    use std::{
+        collections::{BTreeMap, HashSet},
+        sync::{Mutex, MutexGuard},
+        time::Duration,
+    };
+
+    use k8s_openapi::{
+        api::core::v1::Namespace,
+        apimachinery::pkg::apis::meta::v1::ObjectMeta,
+        chrono::{DateTime, Utc},
+    };
+    use kube::{
+        api::{DeleteParams, Patch, PatchParams, PostParams},
+        Api, Client,
+    };
+    use rand::{distributions::Alphanumeric, Rng};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use tauri::{AppHandle, Emitter, Manager};
+
+    use crate::{
+        api::{app_settings::ScratchNamespace, app_state::AppState},
+        util::{
+            kubious_error::{ErrorContext, KubiousError},
+            lock_recovery::recover,
+        },
+    };
+
+    /// Marks namespaces created as scratch space, so nothing else is ever
+    /// deleted by the cleanup.
+    pub const SCRATCH_LABEL: &str = "kubious/scratch";
+
+    /// When the namespace expires, as RFC 3339. Kept on the namespace so
+    /// others sharing the cluster can see, or extend, the deadline.
+    pub const EXPIRES_ANNOTATION: &str = "kubious/expires-at";
+
+    /// Event channel carrying a `ScratchEvent` when a scratch namespace
+    /// expires or is deleted by the cleanup.
+    pub const SCRATCH_EVENT: &str = "scratch-namespace";
+
+    /// How often the background cleanup checks for expired namespaces.
+    const INTERVAL: Duration = Duration::from_secs(60);
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ScratchEvent {
+        pub cluster: String,
+        pub namespace: String,
+        pub expires: String,
+        /// `false` when the namespace is waiting for the user to confirm
+        /// the deletion.
+        pub deleted: bool,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ScratchStatus {
+        #[serde(flatten)]
+        pub scratch: ScratchNamespace,
+        pub expired: bool,
+    }
+
+    #[derive(Default)]
+    struct Cleanup {
+        /// Expired namespaces the user was already told about.
+        notified: HashSet<(String, String)>,
+        started: bool,
+    }
+
+    #[derive(Default)]
+    pub struct ScratchCleanup {
+        cleanup: Mutex<Cleanup>,
+    }
+
+    impl ScratchCleanup {
+        fn cleanup_mutable(&self) -> MutexGuard<'_, Cleanup> {
+            recover(&self.cleanup)
+        }
+    }
+
+    fn expired(scratch: &ScratchNamespace) -> bool {
+        DateTime::parse_from_rfc3339(scratch.expires.as_str()).is_ok_and(|at| at <= Utc::now())
+    }
+
+    pub fn statuses(scratches: Vec<ScratchNamespace>) -> Vec<ScratchStatus> {
+        scratches
+            .into_iter()
+            .map(|scratch| ScratchStatus {
+                expired: expired(&scratch),
+                scratch,
+            })
+            .collect()
+    }
+
+    fn ttl_deadline(ttl_minutes: u32) -> Result<String, KubiousError> {
+        if ttl_minutes == 0 {
+            return Err("TTL must be at least one minute".into());
+        }
+        Ok((Utc::now() + k8s_openapi::chrono::Duration::minutes(ttl_minutes as i64)).to_rfc3339())
+    }
+
+    /// Creates a namespace named `<prefix>-<random suffix>`, labelled as
+    /// scratch space and annotated with its expiry.
+    pub async fn create(
+        client: Client,
+        cluster: String,
+        prefix: Option<String>,
+        ttl_minutes: u32,
+    ) -> Result<ScratchNamespace, KubiousError> {
+        let prefix = prefix.unwrap_or("scratch".to_string()).to_lowercase();
+        let valid = !prefix.is_empty()
+            && prefix.len() <= 56
+            && prefix.starts_with(|c: char| c.is_ascii_lowercase())
+            && prefix
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err("Prefix must be a DNS label of at most 56 characters".into());
+        }
+        let suffix: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(6)
+            .map(|c| (c as char).to_ascii_lowercase())
+            .collect();
+        let expires = ttl_deadline(ttl_minutes)?;
+        let namespace = Namespace {
+            metadata: ObjectMeta {
+                name: Some(format!("{}-{}", prefix, suffix)),
+                labels: Some(BTreeMap::from([(
+                    SCRATCH_LABEL.to_string(),
+                    "true".to_string(),
+                )])),
+                annotations: Some(BTreeMap::from([(
+                    EXPIRES_ANNOTATION.to_string(),
+                    expires.clone(),
+                )])),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let created = Api::<Namespace>::all(client)
+            .create(&PostParams::default(), &namespace)
+            .await
+            .context("Failed to create namespace")?;
+        Ok(ScratchNamespace {
+            cluster,
+            namespace: created.metadata.name.unwrap_or_default(),
+            created: Utc::now().to_rfc3339(),
+            expires,
+        })
+    }
+
+    /// Moves the expiry to `ttl_minutes` from now.
+    pub async fn extend(
+        client: Client,
+        scratch: ScratchNamespace,
+        ttl_minutes: u32,
+    ) -> Result<ScratchNamespace, KubiousError> {
+        let expires = ttl_deadline(ttl_minutes)?;
+        let patch = json!({ "metadata": { "annotations": { EXPIRES_ANNOTATION: expires } } });
+        Api::<Namespace>::all(client)
+            .patch(
+                scratch.namespace.as_str(),
+                &PatchParams::default(),
+                &Patch::Merge(&patch),
+            )
+            .await
+            .context(format!("Failed to extend {}", scratch.namespace))?;
+        Ok(ScratchNamespace { expires, ..scratch })
+    }
+
+    /// Deletes a scratch namespace after checking it still carries the
+    /// scratch label. Returns `Ok(false)` if it no longer exists.
+    pub async fn delete(client: Client, scratch: &ScratchNamespace) -> Result<bool, KubiousError> {
+        let api = Api::<Namespace>::all(client);
+        let Some(live) = api
+            .get_opt(scratch.namespace.as_str())
+            .await
+            .context(format!("Failed to get {}", scratch.namespace))?
+        else {
+            return Ok(false);
+        };
+        let labelled = live
+            .metadata
+            .labels
+            .is_some_and(|l| l.get(SCRATCH_LABEL).is_some_and(|v| v == "true"));
+        if !labelled {
+            return Err(format!(
+                "{} is not labelled as a scratch namespace",
+                scratch.namespace
+            )
+            .into());
+        }
+        api.delete(scratch.namespace.as_str(), &DeleteParams::default())
+            .await
+            .map(|_| true)
+            .context(format!("Failed to delete {}", scratch.namespace))
+    }
+
+    /// The expiry on the live namespace, which may have been extended by
+    /// someone else. `None` if the namespace is gone.
+    async fn live_expiry(client: &Client, namespace: &str) -> Result<Option<String>, KubiousError> {
+        Ok(Api::<Namespace>::all(client.clone())
+            .get_opt(namespace)
+            .await?
+            .map(|ns| {
+                ns.metadata
+                    .annotations
+                    .and_then(|a| a.get(EXPIRES_ANNOTATION).cloned())
+                    .unwrap_or_default()
+            }))
+    }
+
+    /// Checks every expired scratch namespace. Namespaces that are gone are
+    /// forgotten and ones extended on the cluster pick up the new expiry.
+    /// The rest are deleted when auto-delete is on, and otherwise announced
+    /// once on `SCRATCH_EVENT` for the user to confirm.
+    pub async fn sweep(handle: AppHandle) {
+        let state = handle.state::<AppState>();
+        let settings = state.get_settings();
+        let mut changed = false;
+        for scratch in settings.scratch_namespaces.into_iter().filter(expired) {
+            let Some(client) = state.client_for(scratch.cluster.as_str()).await else {
+                continue;
+            };
+            let key = (scratch.cluster.clone(), scratch.namespace.clone());
+            match live_expiry(&client, scratch.namespace.as_str()).await {
+                Ok(None) => {
+                    changed |= state.remove_scratch_namespace(&key.0, &key.1);
+                    continue;
+                }
+                Ok(Some(expires)) if expires != scratch.expires => {
+                    state.put_scratch_namespace(ScratchNamespace { expires, ..scratch });
+                    changed = true;
+                    continue;
+                }
+                Ok(Some(_)) => {}
+                Err(_) => continue,
+            }
+
+            let cleanup = handle.state::<ScratchCleanup>();
+            let (deleted, error) = if settings.scratch_auto_delete {
+                match delete(client, &scratch).await {
+                    Ok(_) => {
+                        state.remove_scratch_namespace(&key.0, &key.1);
+                        changed = true;
+                        (true, None)
+                    }
+                    Err(error) => (false, Some(error.to_string())),
+                }
+            } else {
+                (false, None)
+            };
+            if deleted {
+                cleanup.cleanup_mutable().notified.remove(&key);
+            } else if !cleanup.cleanup_mutable().notified.insert(key.clone()) {
+                // Already announced; failed deletions are retried quietly.
+                continue;
+            }
+            let _ = handle.emit(
+                SCRATCH_EVENT,
+                ScratchEvent {
+                    cluster: scratch.cluster.clone(),
+                    namespace: scratch.namespace.clone(),
+                    expires: scratch.expires.clone(),
+                    deleted,
+                    error,
+                },
+            );
+        }
+        if changed {
+            let _ = state.save_state(handle.clone());
+        }
+    }
+
+    /// Starts the background cleanup once; later calls do nothing.
+    pub async fn start(handle: AppHandle) {
+        {
+            let cleanup = handle.state::<ScratchCleanup>();
+            let mut cleanup = cleanup.cleanup_mutable();
+            if cleanup.started {
+                return;
+            }
+            cleanup.started = true;
+        }
+        loop {
+            sweep(handle.clone()).await;
+            tokio::time::sleep(INTERVAL).await;
+        }
+    }
+}
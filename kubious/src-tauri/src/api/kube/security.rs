@@ -0,0 +1,418 @@
+pub mod security_inspector {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use k8s_openapi::api::{
+        apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
+        batch::v1::{CronJob, Job},
+        core::v1::{
+            AppArmorProfile, Container, Pod, PodSecurityContext, PodSpec, SeccompProfile,
+            SecurityContext,
+        },
+    };
+    use kube::{Api, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::util::kubious_error::KubiousError;
+
+    /// Capabilities container runtimes (containerd, CRI-O) grant by default.
+    const DEFAULT_CAPABILITIES: [&str; 14] = [
+        "AUDIT_WRITE",
+        "CHOWN",
+        "DAC_OVERRIDE",
+        "FOWNER",
+        "FSETID",
+        "KILL",
+        "MKNOD",
+        "NET_BIND_SERVICE",
+        "NET_RAW",
+        "SETFCAP",
+        "SETGID",
+        "SETPCAP",
+        "SETUID",
+        "SYS_CHROOT",
+    ];
+
+    /// Capabilities that effectively give up container isolation.
+    const DANGEROUS_CAPABILITIES: [&str; 6] = [
+        "SYS_ADMIN",
+        "SYS_PTRACE",
+        "SYS_MODULE",
+        "NET_ADMIN",
+        "DAC_READ_SEARCH",
+        "BPF",
+    ];
+
+    const SECCOMP_POD_ANNOTATION: &str = "seccomp.security.alpha.kubernetes.io/pod";
+    const SECCOMP_CONTAINER_ANNOTATION: &str = "container.seccomp.security.alpha.kubernetes.io/";
+    const APPARMOR_ANNOTATION: &str = "container.apparmor.security.beta.kubernetes.io/";
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum SettingSource {
+        Container,
+        Pod,
+        /// A deprecated annotation on the pod.
+        Annotation,
+        /// Taken from the image (`runAsUser`, `runAsGroup`).
+        Image,
+        /// Kubernetes or container runtime default.
+        Default,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct Setting {
+        /// `null` when the value comes from the image and is not known.
+        pub value: Value,
+        pub source: SettingSource,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ContainerSecurity {
+        pub name: String,
+        pub init: bool,
+        pub run_as_user: Setting,
+        pub run_as_group: Setting,
+        pub run_as_non_root: Setting,
+        pub privileged: Setting,
+        pub allow_privilege_escalation: Setting,
+        pub read_only_root_filesystem: Setting,
+        pub seccomp_profile: Setting,
+        pub apparmor_profile: Setting,
+        pub se_linux_options: Setting,
+        pub capabilities_added: Vec<String>,
+        pub capabilities_dropped: Vec<String>,
+        /// The capability set the process ends up with.
+        pub capabilities: Vec<String>,
+        pub findings: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct WorkloadSecurity {
+        pub namespace: String,
+        pub kind: String,
+        pub name: String,
+        pub host_network: bool,
+        pub host_pid: bool,
+        pub host_ipc: bool,
+        pub fs_group: Option<i64>,
+        pub supplemental_groups: Vec<i64>,
+        pub sysctls: BTreeMap<String, String>,
+        pub containers: Vec<ContainerSecurity>,
+        pub findings: Vec<String>,
+    }
+
+    async fn pod_template(
+        client: &Client,
+        namespace: &str,
+        kind: &str,
+        name: &str,
+    ) -> Result<(BTreeMap<String, String>, PodSpec), KubiousError> {
+        let failed = |e: kube::Error| {
+            KubiousError::from(e).with_context(format!("Failed to get {} {}", kind, name))
+        };
+        let template = match kind {
+            "Pod" => {
+                let pod = Api::<Pod>::namespaced(client.clone(), namespace)
+                    .get(name)
+                    .await
+                    .map_err(failed)?;
+                return Ok((
+                    pod.metadata.annotations.unwrap_or_default(),
+                    pod.spec.unwrap_or_default(),
+                ));
+            }
+            "Deployment" => Api::<Deployment>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(failed)?
+                .spec
+                .map(|s| s.template),
+            "StatefulSet" => Api::<StatefulSet>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(failed)?
+                .spec
+                .map(|s| s.template),
+            "DaemonSet" => Api::<DaemonSet>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(failed)?
+                .spec
+                .map(|s| s.template),
+            "ReplicaSet" => Api::<ReplicaSet>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(failed)?
+                .spec
+                .and_then(|s| s.template),
+            "Job" => Api::<Job>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(failed)?
+                .spec
+                .map(|s| s.template),
+            "CronJob" => Api::<CronJob>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(failed)?
+                .spec
+                .and_then(|s| s.job_template.spec)
+                .map(|s| s.template),
+            other => return Err(format!("Unsupported workload kind {}", other).into()),
+        };
+        let template = template.ok_or(format!("{} {} has no pod template", kind, name))?;
+        Ok((
+            template
+                .metadata
+                .and_then(|m| m.annotations)
+                .unwrap_or_default(),
+            template.spec.unwrap_or_default(),
+        ))
+    }
+
+    fn setting(value: Value, source: SettingSource) -> Setting {
+        Setting { value, source }
+    }
+
+    /// Takes the container value, then the pod value, then the fallback.
+    fn inherited<T: Serialize>(container: Option<T>, pod: Option<T>, fallback: Setting) -> Setting {
+        match (container, pod) {
+            (Some(value), _) => setting(json!(value), SettingSource::Container),
+            (None, Some(value)) => setting(json!(value), SettingSource::Pod),
+            (None, None) => fallback,
+        }
+    }
+
+    fn seccomp_name(profile: &SeccompProfile) -> String {
+        match &profile.localhost_profile {
+            Some(path) => format!("{}/{}", profile.type_, path),
+            None => profile.type_.clone(),
+        }
+    }
+
+    fn apparmor_name(profile: &AppArmorProfile) -> String {
+        match &profile.localhost_profile {
+            Some(path) => format!("{}/{}", profile.type_, path),
+            None => profile.type_.clone(),
+        }
+    }
+
+    fn inspect_container(
+        container: &Container,
+        init: bool,
+        pod: &PodSecurityContext,
+        annotations: &BTreeMap<String, String>,
+    ) -> ContainerSecurity {
+        let context: SecurityContext = container.security_context.clone().unwrap_or_default();
+        let annotation = |key: String| annotations.get(&key).cloned();
+
+        let privileged = context.privileged.unwrap_or(false);
+        let capabilities = context.capabilities.clone().unwrap_or_default();
+        let normalize = |caps: Option<Vec<String>>| -> Vec<String> {
+            caps.unwrap_or_default()
+                .into_iter()
+                .map(|c| c.trim_start_matches("CAP_").to_uppercase())
+                .collect()
+        };
+        let added = normalize(capabilities.add);
+        let dropped = normalize(capabilities.drop);
+        let effective: BTreeSet<String> = if privileged {
+            BTreeSet::from(["ALL".to_string()])
+        } else {
+            DEFAULT_CAPABILITIES
+                .iter()
+                .map(|c| c.to_string())
+                .filter(|c| !dropped.iter().any(|d| d == "ALL" || d == c))
+                .chain(added.iter().cloned())
+                .collect()
+        };
+
+        let seccomp = match (
+            context.seccomp_profile.as_ref().map(seccomp_name),
+            pod.seccomp_profile.as_ref().map(seccomp_name),
+        ) {
+            (None, None) => match annotation(format!(
+                "{}{}",
+                SECCOMP_CONTAINER_ANNOTATION, container.name
+            ))
+            .or(annotation(SECCOMP_POD_ANNOTATION.to_string()))
+            {
+                Some(profile) => setting(json!(profile), SettingSource::Annotation),
+                None => setting(json!("Unconfined"), SettingSource::Default),
+            },
+            (container_profile, pod_profile) => inherited(
+                container_profile,
+                pod_profile,
+                setting(Value::Null, SettingSource::Default),
+            ),
+        };
+        let apparmor = match (
+            context.app_armor_profile.as_ref().map(apparmor_name),
+            pod.app_armor_profile.as_ref().map(apparmor_name),
+        ) {
+            (None, None) => {
+                match annotation(format!("{}{}", APPARMOR_ANNOTATION, container.name)) {
+                    Some(profile) => setting(json!(profile), SettingSource::Annotation),
+                    None => setting(json!("RuntimeDefault"), SettingSource::Default),
+                }
+            }
+            (container_profile, pod_profile) => inherited(
+                container_profile,
+                pod_profile,
+                setting(Value::Null, SettingSource::Default),
+            ),
+        };
+
+        // Privilege escalation is always allowed for privileged containers
+        // and those granted SYS_ADMIN.
+        let allow_privilege_escalation = if privileged || added.iter().any(|c| c == "SYS_ADMIN") {
+            setting(json!(true), SettingSource::Default)
+        } else {
+            inherited(
+                context.allow_privilege_escalation,
+                None,
+                setting(json!(true), SettingSource::Default),
+            )
+        };
+        let run_as_user = inherited(
+            context.run_as_user,
+            pod.run_as_user,
+            setting(Value::Null, SettingSource::Image),
+        );
+        let run_as_non_root = inherited(
+            context.run_as_non_root,
+            pod.run_as_non_root,
+            setting(json!(false), SettingSource::Default),
+        );
+
+        let mut findings: Vec<String> = Vec::new();
+        if privileged {
+            findings.push("Runs privileged".to_string());
+        }
+        if allow_privilege_escalation.value == json!(true) {
+            findings.push("Allows privilege escalation".to_string());
+        }
+        if run_as_user.value == json!(0) {
+            findings.push("Runs as root (UID 0)".to_string());
+        } else if run_as_user.value.is_null() && run_as_non_root.value != json!(true) {
+            findings.push("May run as root; the UID comes from the image".to_string());
+        }
+        if !dropped.iter().any(|c| c == "ALL") {
+            findings.push("Does not drop all capabilities".to_string());
+        }
+        for capability in added
+            .iter()
+            .filter(|c| DANGEROUS_CAPABILITIES.contains(&c.as_str()))
+        {
+            findings.push(format!("Adds capability {}", capability));
+        }
+        if seccomp.value == json!("Unconfined") {
+            findings.push("No seccomp profile".to_string());
+        }
+        if apparmor.value == json!("Unconfined") || apparmor.value == json!("unconfined") {
+            findings.push("AppArmor is unconfined".to_string());
+        }
+
+        ContainerSecurity {
+            name: container.name.clone(),
+            init,
+            run_as_user,
+            run_as_group: inherited(
+                context.run_as_group,
+                pod.run_as_group,
+                setting(Value::Null, SettingSource::Image),
+            ),
+            run_as_non_root,
+            privileged: inherited(
+                context.privileged,
+                None,
+                setting(json!(false), SettingSource::Default),
+            ),
+            allow_privilege_escalation,
+            read_only_root_filesystem: inherited(
+                context.read_only_root_filesystem,
+                None,
+                setting(json!(false), SettingSource::Default),
+            ),
+            seccomp_profile: seccomp,
+            apparmor_profile: apparmor,
+            se_linux_options: inherited(
+                context.se_linux_options,
+                pod.se_linux_options.clone(),
+                setting(Value::Null, SettingSource::Default),
+            ),
+            capabilities_added: added,
+            capabilities_dropped: dropped,
+            capabilities: effective.into_iter().collect(),
+            findings,
+        }
+    }
+
+    /// Flattens the pod and container security contexts of a Pod or
+    /// workload template, resolving each setting to the container value,
+    /// the pod value, or the default that applies, and lists findings worth
+    /// a security reviewer's attention.
+    pub async fn inspect(
+        client: Client,
+        namespace: String,
+        kind: String,
+        name: String,
+    ) -> Result<WorkloadSecurity, KubiousError> {
+        let (annotations, spec) =
+            pod_template(&client, namespace.as_str(), kind.as_str(), name.as_str()).await?;
+        let pod = spec.security_context.clone().unwrap_or_default();
+
+        let containers: Vec<ContainerSecurity> = spec
+            .init_containers
+            .iter()
+            .flatten()
+            .map(|c| inspect_container(c, true, &pod, &annotations))
+            .chain(
+                spec.containers
+                    .iter()
+                    .map(|c| inspect_container(c, false, &pod, &annotations)),
+            )
+            .collect();
+
+        let host_network = spec.host_network.unwrap_or(false);
+        let host_pid = spec.host_pid.unwrap_or(false);
+        let host_ipc = spec.host_ipc.unwrap_or(false);
+        let mut findings: Vec<String> = [
+            (host_network, "Shares the host network namespace"),
+            (host_pid, "Shares the host PID namespace"),
+            (host_ipc, "Shares the host IPC namespace"),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, finding)| finding.to_string())
+        .collect();
+        for volume in spec.volumes.iter().flatten() {
+            if let Some(host_path) = &volume.host_path {
+                findings.push(format!(
+                    "Mounts host path {} as volume {}",
+                    host_path.path, volume.name
+                ));
+            }
+        }
+
+        Ok(WorkloadSecurity {
+            namespace,
+            kind,
+            name,
+            host_network,
+            host_pid,
+            host_ipc,
+            fs_group: pod.fs_group,
+            supplemental_groups: pod.supplemental_groups.clone().unwrap_or_default(),
+            sysctls: pod
+                .sysctls
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|s| (s.name, s.value))
+                .collect(),
+            containers,
+            findings,
+        })
+    }
+}
@@ -0,0 +1,290 @@
+pub mod pod_startup {
+    use k8s_openapi::{
+        api::core::v1::{ContainerStatus, Pod, PodCondition},
+        apimachinery::pkg::apis::meta::v1::Time,
+    };
+    use kube::{Api, Client};
+    use serde::{Deserialize, Serialize};
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum ContainerRole {
+        Init,
+        Sidecar,
+        App,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum ContainerPhase {
+        Pending,
+        Waiting,
+        Running,
+        Terminated,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ContainerStartup {
+        pub name: String,
+        pub role: ContainerRole,
+        pub order: usize,
+        pub phase: ContainerPhase,
+        pub reason: Option<String>,
+        pub started_at: Option<String>,
+        pub finished_at: Option<String>,
+        /// Seconds from pod creation until the container started.
+        pub start_offset: Option<i64>,
+        /// Seconds the container ran; for running containers, up to now.
+        pub duration: Option<i64>,
+        pub exit_code: Option<i32>,
+        pub restart_count: i32,
+        pub started: Option<bool>,
+        pub ready: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ConditionStep {
+        pub condition: String,
+        pub status: Option<String>,
+        pub transitioned_at: Option<String>,
+        /// Seconds from pod creation until the last transition.
+        pub offset: Option<i64>,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct StartupTimeline {
+        pub namespace: String,
+        pub pod: String,
+        pub phase: Option<String>,
+        pub created_at: Option<String>,
+        pub conditions: Vec<ConditionStep>,
+        pub readiness_gates: Vec<ConditionStep>,
+        pub containers: Vec<ContainerStartup>,
+        /// The step that is currently holding the pod back from Ready, if any.
+        pub blocked_on: Option<String>,
+        /// Seconds from creation until Ready, or until now when not Ready yet.
+        pub elapsed: Option<i64>,
+    }
+
+    const POD_CONDITIONS: [&str; 5] = [
+        "PodScheduled",
+        "PodReadyToStartContainers",
+        "Initialized",
+        "ContainersReady",
+        "Ready",
+    ];
+
+    fn seconds_between(from: Option<&Time>, to: Option<&Time>) -> Option<i64> {
+        let from = from?;
+        let to = to
+            .map(|t| t.0)
+            .unwrap_or_else(k8s_openapi::chrono::Utc::now);
+        Some((to - from.0).num_seconds())
+    }
+
+    fn condition_step(
+        name: &str,
+        condition: Option<&PodCondition>,
+        created: Option<&Time>,
+    ) -> ConditionStep {
+        ConditionStep {
+            condition: name.to_string(),
+            status: condition.map(|c| c.status.clone()),
+            transitioned_at: condition
+                .and_then(|c| c.last_transition_time.as_ref())
+                .map(|t| t.0.to_rfc3339()),
+            offset: condition
+                .and_then(|c| c.last_transition_time.as_ref())
+                .and_then(|t| seconds_between(created, Some(t))),
+            reason: condition.and_then(|c| c.reason.clone()),
+            message: condition.and_then(|c| c.message.clone()),
+        }
+    }
+
+    fn container_startup(
+        name: &str,
+        role: ContainerRole,
+        order: usize,
+        status: Option<&ContainerStatus>,
+        created: Option<&Time>,
+    ) -> ContainerStartup {
+        let mut startup = ContainerStartup {
+            name: name.to_string(),
+            role,
+            order,
+            phase: ContainerPhase::Pending,
+            reason: None,
+            started_at: None,
+            finished_at: None,
+            start_offset: None,
+            duration: None,
+            exit_code: None,
+            restart_count: 0,
+            started: None,
+            ready: false,
+        };
+        let Some(status) = status else {
+            return startup;
+        };
+        startup.restart_count = status.restart_count;
+        startup.started = status.started;
+        startup.ready = status.ready;
+
+        let state = status.state.clone().unwrap_or_default();
+        if let Some(terminated) = &state.terminated {
+            startup.phase = ContainerPhase::Terminated;
+            startup.reason = terminated.reason.clone();
+            startup.exit_code = Some(terminated.exit_code);
+            startup.started_at = terminated.started_at.as_ref().map(|t| t.0.to_rfc3339());
+            startup.finished_at = terminated.finished_at.as_ref().map(|t| t.0.to_rfc3339());
+            startup.start_offset = seconds_between(created, terminated.started_at.as_ref());
+            startup.duration = terminated.finished_at.as_ref().and_then(|finished| {
+                seconds_between(terminated.started_at.as_ref(), Some(finished))
+            });
+        } else if let Some(running) = &state.running {
+            startup.phase = ContainerPhase::Running;
+            startup.started_at = running.started_at.as_ref().map(|t| t.0.to_rfc3339());
+            startup.start_offset = seconds_between(created, running.started_at.as_ref());
+            startup.duration = seconds_between(running.started_at.as_ref(), None);
+        } else if let Some(waiting) = &state.waiting {
+            startup.phase = ContainerPhase::Waiting;
+            startup.reason = waiting.reason.clone();
+        }
+
+        // A restarted container's current state hides how its previous
+        // attempt ended, which is usually what explains a slow start.
+        if startup.exit_code.is_none() {
+            startup.exit_code = status
+                .last_state
+                .as_ref()
+                .and_then(|s| s.terminated.as_ref())
+                .map(|t| t.exit_code);
+        }
+        startup
+    }
+
+    fn blocked_on(timeline: &StartupTimeline) -> Option<String> {
+        let pending = |condition: &str| {
+            timeline
+                .conditions
+                .iter()
+                .find(|c| c.condition == condition)
+                .map(|c| c.status.as_deref() != Some("True"))
+                .unwrap_or(true)
+        };
+        if pending("PodScheduled") {
+            return Some("Scheduling".to_string());
+        }
+        if pending("Initialized") {
+            let stuck = timeline.containers.iter().find(|c| match c.role {
+                ContainerRole::Init => {
+                    c.phase != ContainerPhase::Terminated || c.exit_code != Some(0)
+                }
+                ContainerRole::Sidecar => c.started != Some(true),
+                ContainerRole::App => false,
+            });
+            return Some(match stuck {
+                Some(container) => format!("Init container {}", container.name),
+                None => "Initialization".to_string(),
+            });
+        }
+        if let Some(container) = timeline
+            .containers
+            .iter()
+            .find(|c| c.role != ContainerRole::Init && !c.ready)
+        {
+            return Some(format!("Container {}", container.name));
+        }
+        if let Some(gate) = timeline
+            .readiness_gates
+            .iter()
+            .find(|g| g.status.as_deref() != Some("True"))
+        {
+            return Some(format!("Readiness gate {}", gate.condition));
+        }
+        pending("Ready").then(|| "Ready".to_string())
+    }
+
+    pub fn timeline(pod: &Pod) -> StartupTimeline {
+        let created = pod.metadata.creation_timestamp.as_ref();
+        let spec = pod.spec.clone().unwrap_or_default();
+        let status = pod.status.clone().unwrap_or_default();
+        let conditions = status.conditions.clone().unwrap_or_default();
+        let find_condition = |name: &str| conditions.iter().find(|c| c.type_ == name);
+
+        let init_statuses = status.init_container_statuses.clone().unwrap_or_default();
+        let app_statuses = status.container_statuses.clone().unwrap_or_default();
+        let mut containers: Vec<ContainerStartup> = Vec::new();
+        for (order, container) in spec.init_containers.iter().flatten().enumerate() {
+            // Init containers with restartPolicy Always are native sidecars:
+            // they only need to start, not exit, before the next one runs.
+            let role = if container.restart_policy.as_deref() == Some("Always") {
+                ContainerRole::Sidecar
+            } else {
+                ContainerRole::Init
+            };
+            containers.push(container_startup(
+                container.name.as_str(),
+                role,
+                order,
+                init_statuses.iter().find(|s| s.name == container.name),
+                created,
+            ));
+        }
+        let offset = containers.len();
+        for (order, container) in spec.containers.iter().enumerate() {
+            containers.push(container_startup(
+                container.name.as_str(),
+                ContainerRole::App,
+                offset + order,
+                app_statuses.iter().find(|s| s.name == container.name),
+                created,
+            ));
+        }
+
+        let readiness_gates: Vec<ConditionStep> = spec
+            .readiness_gates
+            .iter()
+            .flatten()
+            .map(|gate| {
+                condition_step(
+                    gate.condition_type.as_str(),
+                    find_condition(gate.condition_type.as_str()),
+                    created,
+                )
+            })
+            .collect();
+        let ready = find_condition("Ready").filter(|c| c.status == "True");
+
+        let mut result = StartupTimeline {
+            namespace: pod.metadata.namespace.clone().unwrap_or_default(),
+            pod: pod.metadata.name.clone().unwrap_or_default(),
+            phase: status.phase.clone(),
+            created_at: created.map(|t| t.0.to_rfc3339()),
+            conditions: POD_CONDITIONS
+                .iter()
+                .map(|name| condition_step(name, find_condition(name), created))
+                .collect(),
+            readiness_gates,
+            containers,
+            blocked_on: None,
+            elapsed: seconds_between(created, ready.and_then(|c| c.last_transition_time.as_ref())),
+        };
+        result.blocked_on = blocked_on(&result);
+        result
+    }
+
+    pub async fn startup_timeline(
+        client: Client,
+        namespace: String,
+        name: String,
+    ) -> Result<StartupTimeline, KubiousError> {
+        let pod = Api::<Pod>::namespaced(client, namespace.as_str())
+            .get(name.as_str())
+            .await
+            .context("Unknown pod")?;
+        Ok(timeline(&pod))
+    }
+}
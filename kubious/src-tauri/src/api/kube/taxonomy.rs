@@ -0,0 +1,232 @@
+pub mod label_taxonomy {
+    use std::collections::{BTreeMap, HashMap};
+
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams},
+        discovery::{pinned_kind, Scope},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+
+    use crate::util::kubious_error::KubiousError;
+
+    const DEFAULT_MAX_VALUES: usize = 25;
+
+    /// Kinds scanned when none are given: the objects teams usually label.
+    const DEFAULT_KINDS: [(&str, &str, &str); 14] = [
+        ("", "v1", "Namespace"),
+        ("", "v1", "Node"),
+        ("", "v1", "Pod"),
+        ("", "v1", "Service"),
+        ("", "v1", "ConfigMap"),
+        ("", "v1", "Secret"),
+        ("", "v1", "PersistentVolumeClaim"),
+        ("", "v1", "ServiceAccount"),
+        ("apps", "v1", "Deployment"),
+        ("apps", "v1", "StatefulSet"),
+        ("apps", "v1", "DaemonSet"),
+        ("batch", "v1", "Job"),
+        ("batch", "v1", "CronJob"),
+        ("networking.k8s.io", "v1", "Ingress"),
+    ];
+
+    /// Prefixes of keys set by Kubernetes and common tooling rather than by
+    /// the teams owning the objects.
+    const SYSTEM_PREFIXES: [&str; 6] = [
+        "kubernetes.io",
+        "k8s.io",
+        "kubectl.kubernetes.io",
+        "node.kubernetes.io",
+        "deployment.kubernetes.io",
+        "batch.kubernetes.io",
+    ];
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ValueUsage {
+        pub value: String,
+        pub count: usize,
+        /// A label selector matching objects with this value.
+        pub selector: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct KeyUsage {
+        pub key: String,
+        pub prefix: Option<String>,
+        /// Set by Kubernetes or its tooling rather than by users.
+        pub system: bool,
+        /// One of the `app.kubernetes.io/*` recommended labels.
+        pub recommended: bool,
+        pub objects: usize,
+        pub kinds: BTreeMap<String, usize>,
+        pub namespaces: BTreeMap<String, usize>,
+        pub distinct_values: usize,
+        /// Most used values first, capped at the requested maximum. Always
+        /// empty for annotations, whose values are often large blobs.
+        pub values: Vec<ValueUsage>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct KindScan {
+        pub kind: String,
+        pub objects: usize,
+        /// Objects of this kind without any labels.
+        pub unlabeled: usize,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TaxonomyReport {
+        pub scanned: Vec<KindScan>,
+        pub labels: Vec<KeyUsage>,
+        pub annotations: Vec<KeyUsage>,
+        pub warnings: Vec<String>,
+    }
+
+    #[derive(Default)]
+    struct KeyTally {
+        objects: usize,
+        kinds: BTreeMap<String, usize>,
+        namespaces: BTreeMap<String, usize>,
+        values: HashMap<String, usize>,
+    }
+
+    impl KeyTally {
+        fn add(&mut self, kind: &str, namespace: Option<&String>, value: Option<&String>) {
+            self.objects += 1;
+            *self.kinds.entry(kind.to_string()).or_insert(0) += 1;
+            if let Some(namespace) = namespace {
+                *self.namespaces.entry(namespace.clone()).or_insert(0) += 1;
+            }
+            if let Some(value) = value {
+                *self.values.entry(value.clone()).or_insert(0) += 1;
+            }
+        }
+
+        fn usage(self, key: String, max_values: usize) -> KeyUsage {
+            let prefix = key.split_once('/').map(|(prefix, _)| prefix.to_string());
+            let system = prefix
+                .as_deref()
+                .map(|p| {
+                    SYSTEM_PREFIXES
+                        .iter()
+                        .any(|s| p == *s || p.ends_with(format!(".{}", s).as_str()))
+                })
+                .unwrap_or(false)
+                || key == "pod-template-hash"
+                || key == "controller-revision-hash";
+            let distinct_values = self.values.len();
+            let mut values: Vec<ValueUsage> = self
+                .values
+                .into_iter()
+                .map(|(value, count)| ValueUsage {
+                    selector: format!("{}={}", key, value),
+                    value,
+                    count,
+                })
+                .collect();
+            values.sort_by(|a, b| b.count.cmp(&a.count).then(a.value.cmp(&b.value)));
+            values.truncate(max_values);
+            KeyUsage {
+                recommended: prefix.as_deref() == Some("app.kubernetes.io"),
+                key,
+                prefix,
+                system,
+                objects: self.objects,
+                kinds: self.kinds,
+                namespaces: self.namespaces,
+                distinct_values,
+                values,
+            }
+        }
+    }
+
+    fn ranked(tallies: HashMap<String, KeyTally>, max_values: usize) -> Vec<KeyUsage> {
+        let mut usages: Vec<KeyUsage> = tallies
+            .into_iter()
+            .map(|(key, tally)| tally.usage(key, max_values))
+            .collect();
+        usages.sort_by(|a, b| b.objects.cmp(&a.objects).then(a.key.cmp(&b.key)));
+        usages
+    }
+
+    /// Aggregates label (and optionally annotation) keys across the given
+    /// kinds, counting usage per kind, namespace and value. Only object
+    /// metadata is fetched, so Secrets are scanned without their data.
+    pub async fn scan(
+        client: Client,
+        namespace: Option<String>,
+        kinds: Option<Vec<GroupVersionKind>>,
+        annotations: bool,
+        max_values: Option<usize>,
+    ) -> Result<TaxonomyReport, KubiousError> {
+        let kinds = kinds.unwrap_or(
+            DEFAULT_KINDS
+                .iter()
+                .map(|(group, version, kind)| GroupVersionKind::gvk(group, version, kind))
+                .collect(),
+        );
+        let max_values = max_values.unwrap_or(DEFAULT_MAX_VALUES);
+        let mut scanned: Vec<KindScan> = Vec::new();
+        let mut warnings: Vec<String> = Vec::new();
+        let mut labels: HashMap<String, KeyTally> = HashMap::new();
+        let mut notes: HashMap<String, KeyTally> = HashMap::new();
+
+        for gvk in &kinds {
+            let Ok((resource, caps)) = pinned_kind(&client, gvk).await else {
+                warnings.push(format!("Unknown resource kind {}", gvk.kind));
+                continue;
+            };
+            let api: Api<DynamicObject> = match (&caps.scope, &namespace) {
+                (Scope::Namespaced, Some(ns)) => {
+                    Api::namespaced_with(client.clone(), ns.as_str(), &resource)
+                }
+                (Scope::Cluster, Some(_)) => continue,
+                _ => Api::all_with(client.clone(), &resource),
+            };
+            let objects = match api.list_metadata(&ListParams::default()).await {
+                Ok(objects) => objects,
+                Err(e) => {
+                    warnings.push(format!("Failed to list {}: {}", gvk.kind, e));
+                    continue;
+                }
+            };
+
+            let mut scan = KindScan {
+                kind: resource.kind.clone(),
+                objects: objects.items.len(),
+                unlabeled: 0,
+            };
+            for object in &objects.items {
+                let metadata = &object.metadata;
+                let object_labels = metadata.labels.clone().unwrap_or_default();
+                if object_labels.is_empty() {
+                    scan.unlabeled += 1;
+                }
+                for (key, value) in &object_labels {
+                    labels.entry(key.clone()).or_default().add(
+                        resource.kind.as_str(),
+                        metadata.namespace.as_ref(),
+                        Some(value),
+                    );
+                }
+                if annotations {
+                    for key in metadata.annotations.iter().flatten().map(|(key, _)| key) {
+                        notes.entry(key.clone()).or_default().add(
+                            resource.kind.as_str(),
+                            metadata.namespace.as_ref(),
+                            None,
+                        );
+                    }
+                }
+            }
+            scanned.push(scan);
+        }
+
+        Ok(TaxonomyReport {
+            scanned,
+            labels: ranked(labels, max_values),
+            annotations: ranked(notes, 0),
+            warnings,
+        })
+    }
+}
@@ -0,0 +1,315 @@
+pub mod termination_history {
+    use std::{
+        collections::{HashMap, HashSet},
+        fs::{self, File},
+        io::Read,
+        path::PathBuf,
+        sync::{Mutex, MutexGuard},
+    };
+
+    use futures::StreamExt;
+    use k8s_openapi::api::core::v1::{ContainerStateTerminated, Pod};
+    use kube::{
+        api::LogParams,
+        runtime::watcher::{self, watcher, Event},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use tauri::{async_runtime::JoinHandle, AppHandle, Manager};
+
+    use crate::util::{
+        atomic_file,
+        kubious_error::KubiousError,
+        lock_recovery::recover,
+        resource_accounting::workload_of,
+        stream_reconnect::{BackoffPolicy, Reconnector},
+    };
+
+    const MAX_RECORDS_PER_CONTAINER: usize = 50;
+    const LOG_TAIL_LINES: i64 = 20;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TerminationRecord {
+        pub cluster: String,
+        pub namespace: String,
+        pub pod: String,
+        pub workload: Option<String>,
+        pub container: String,
+        pub exit_code: i32,
+        pub signal: Option<i32>,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+        pub started_at: Option<String>,
+        pub finished_at: Option<String>,
+        pub restart_count: i32,
+        pub last_lines: Vec<String>,
+    }
+
+    impl TerminationRecord {
+        fn identity(&self) -> String {
+            format!(
+                "{}/{}/{}/{}/{}",
+                self.cluster,
+                self.namespace,
+                self.pod,
+                self.container,
+                self.finished_at.clone().unwrap_or_default()
+            )
+        }
+
+        fn series(&self) -> String {
+            format!(
+                "{}/{}/{}/{}",
+                self.cluster,
+                self.namespace,
+                self.workload.clone().unwrap_or(self.pod.clone()),
+                self.container
+            )
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TerminationQuery {
+        pub cluster: Option<String>,
+        pub namespace: Option<String>,
+        pub workload: Option<String>,
+        pub container: Option<String>,
+        pub limit: Option<usize>,
+    }
+
+    /// Running pod watchers, one per cluster, plus a lock serializing writes
+    /// to the history file.
+    #[derive(Default)]
+    pub struct TerminationWatches {
+        tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+        history: Mutex<()>,
+    }
+
+    impl TerminationWatches {
+        fn tasks_mutable(&self) -> MutexGuard<'_, HashMap<String, JoinHandle<()>>> {
+            recover(&self.tasks)
+        }
+
+        pub fn active(&self) -> Vec<String> {
+            let mut clusters: Vec<String> = self.tasks_mutable().keys().cloned().collect();
+            clusters.sort();
+            clusters
+        }
+    }
+
+    fn history_path(handle: &AppHandle) -> Result<PathBuf, KubiousError> {
+        let root = handle
+            .path()
+            .app_data_dir()
+            .or(Err("Failed to resolve app data directory.".to_string()))?
+            .join("history");
+        if !root.exists() {
+            fs::create_dir_all(&root).or(Err("Failed to create history directory.".to_string()))?;
+        }
+        Ok(root.join("terminations.json"))
+    }
+
+    fn load_records(handle: &AppHandle) -> Result<Vec<TerminationRecord>, KubiousError> {
+        let path = history_path(handle)?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .or(Err("Failed to read termination history.".to_string()))?;
+        serde_json::from_str(contents.as_str())
+            .or(Err("Failed to parse termination history.".into()))
+    }
+
+    fn save_records(handle: &AppHandle, records: &[TerminationRecord]) -> Result<(), KubiousError> {
+        let serialized = serde_json::to_string(records)
+            .or(Err("Failed to serialize termination history.".to_string()))?;
+        atomic_file::write(&history_path(handle)?, serialized.as_bytes())
+    }
+
+    /// Appends new records, skipping ones already stored and keeping only the
+    /// most recent entries for each workload container.
+    fn append_records(handle: &AppHandle, new: Vec<TerminationRecord>) -> Result<(), KubiousError> {
+        let watches = handle.state::<TerminationWatches>();
+        let _guard = recover(&watches.history);
+        let mut records = load_records(handle)?;
+        let known: HashSet<String> = records.iter().map(|r| r.identity()).collect();
+        let before = records.len();
+        records.extend(new.into_iter().filter(|r| !known.contains(&r.identity())));
+        if records.len() == before {
+            return Ok(());
+        }
+
+        let mut per_series: HashMap<String, usize> = HashMap::new();
+        let mut kept: Vec<TerminationRecord> = records
+            .into_iter()
+            .rev()
+            .filter(|r| {
+                let count = per_series.entry(r.series()).or_insert(0);
+                *count += 1;
+                *count <= MAX_RECORDS_PER_CONTAINER
+            })
+            .collect();
+        kept.reverse();
+        save_records(handle, &kept)
+    }
+
+    pub fn query(
+        handle: &AppHandle,
+        query: &TerminationQuery,
+    ) -> Result<Vec<TerminationRecord>, KubiousError> {
+        let matches = |expected: &Option<String>, actual: Option<&String>| {
+            expected.is_none() || expected.as_ref() == actual
+        };
+        let mut records: Vec<TerminationRecord> = load_records(handle)?
+            .into_iter()
+            .filter(|r| {
+                matches(&query.cluster, Some(&r.cluster))
+                    && matches(&query.namespace, Some(&r.namespace))
+                    && matches(&query.workload, r.workload.as_ref())
+                    && matches(&query.container, Some(&r.container))
+            })
+            .collect();
+        records.sort_by(|a, b| b.finished_at.cmp(&a.finished_at));
+        if let Some(limit) = query.limit {
+            records.truncate(limit);
+        }
+        Ok(records)
+    }
+
+    async fn last_lines(api: &Api<Pod>, pod: &str, container: &str, previous: bool) -> Vec<String> {
+        let params = LogParams {
+            container: Some(container.to_string()),
+            previous,
+            tail_lines: Some(LOG_TAIL_LINES),
+            ..LogParams::default()
+        };
+        api.logs(pod, &params)
+            .await
+            .map(|logs| logs.lines().map(|l| l.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Records the pod's terminations that are not in `seen` yet, fetching
+    /// the last log lines only for those.
+    async fn snapshots(
+        client: &Client,
+        cluster: &str,
+        pod: &Pod,
+        seen: &mut HashSet<String>,
+    ) -> Vec<TerminationRecord> {
+        let (Some(name), Some(namespace), Some(status)) = (
+            pod.metadata.name.clone(),
+            pod.metadata.namespace.clone(),
+            pod.status.as_ref(),
+        ) else {
+            return Vec::new();
+        };
+        let api: Api<Pod> = Api::namespaced(client.clone(), namespace.as_str());
+        let workload = workload_of(pod);
+        let statuses = status
+            .container_statuses
+            .iter()
+            .flatten()
+            .chain(status.init_container_statuses.iter().flatten());
+
+        let mut records: Vec<TerminationRecord> = Vec::new();
+        for container in statuses {
+            let current = container.state.as_ref().and_then(|s| s.terminated.as_ref());
+            let last = container
+                .last_state
+                .as_ref()
+                .and_then(|s| s.terminated.as_ref());
+            let candidates: [(Option<&ContainerStateTerminated>, bool); 2] =
+                [(last, true), (current, false)];
+            for (terminated, previous) in candidates {
+                let Some(terminated) = terminated else {
+                    continue;
+                };
+                let mut record = TerminationRecord {
+                    cluster: cluster.to_string(),
+                    namespace: namespace.clone(),
+                    pod: name.clone(),
+                    workload: workload.clone(),
+                    container: container.name.clone(),
+                    exit_code: terminated.exit_code,
+                    signal: terminated.signal,
+                    reason: terminated.reason.clone(),
+                    message: terminated.message.clone(),
+                    started_at: terminated.started_at.as_ref().map(|t| t.0.to_rfc3339()),
+                    finished_at: terminated.finished_at.as_ref().map(|t| t.0.to_rfc3339()),
+                    restart_count: container.restart_count,
+                    last_lines: Vec::new(),
+                };
+                if !seen.insert(record.identity()) {
+                    continue;
+                }
+                record.last_lines =
+                    last_lines(&api, name.as_str(), container.name.as_str(), previous).await;
+                records.push(record);
+            }
+        }
+        records
+    }
+
+    async fn watch(handle: AppHandle, client: Client, cluster: String) {
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "terminations",
+            cluster.as_str(),
+            BackoffPolicy::default(),
+        );
+        let api: Api<Pod> = Api::all(client.clone());
+        // Terminations already in the history are not fetched again.
+        let mut seen: HashSet<String> = load_records(&handle)
+            .unwrap_or_default()
+            .iter()
+            .filter(|r| r.cluster == cluster)
+            .map(|r| r.identity())
+            .collect();
+        let mut stream = watcher(api, watcher::Config::default()).boxed();
+        while let Some(event) = stream.next().await {
+            match event {
+                Ok(Event::Apply(pod)) | Ok(Event::InitApply(pod)) => {
+                    let new = snapshots(&client, cluster.as_str(), &pod, &mut seen).await;
+                    if !new.is_empty() {
+                        if let Err(error) = append_records(&handle, new) {
+                            reconnector.degraded(error.message());
+                        }
+                    }
+                }
+                Ok(Event::InitDone) => reconnector.connected(),
+                Ok(_) => {}
+                Err(error) => {
+                    if !reconnector.retry(error.to_string().as_str()).await {
+                        return;
+                    }
+                }
+            }
+        }
+        reconnector.closed();
+    }
+
+    pub fn start(handle: &AppHandle, client: Client, cluster: String) -> bool {
+        let watches = handle.state::<TerminationWatches>();
+        let mut tasks = watches.tasks_mutable();
+        if tasks.contains_key(&cluster) {
+            return false;
+        }
+        let task = tauri::async_runtime::spawn(watch(handle.clone(), client, cluster.clone()));
+        tasks.insert(cluster, task);
+        true
+    }
+
+    pub fn stop(handle: &AppHandle, cluster: &str) -> bool {
+        let watches = handle.state::<TerminationWatches>();
+        let removed = watches.tasks_mutable().remove(cluster);
+        if let Some(task) = removed {
+            task.abort();
+            true
+        } else {
+            false
+        }
+    }
+}
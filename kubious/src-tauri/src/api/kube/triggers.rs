@@ -0,0 +1,348 @@
+pub mod rollout_triggers {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{Mutex, MutexGuard},
+        time::Duration,
+    };
+
+    use futures::{stream, StreamExt};
+    use k8s_openapi::{
+        api::core::v1::{ConfigMap, Secret},
+        chrono::Utc,
+    };
+    use kube::{
+        api::{DynamicObject, GroupVersionKind},
+        discovery::pinned_kind,
+        runtime::watcher::{self, watcher, Event},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::{async_runtime::JoinHandle, AppHandle, Emitter, Manager};
+    use tokio::time::{timeout_at, Instant};
+
+    use crate::{
+        api::{
+            app_settings::{RolloutAuditEntry, RolloutTrigger, RolloutTriggerAction},
+            app_state::AppState,
+            kube::workload_rollout,
+        },
+        util::{
+            kubious_error::{ErrorContext, KubiousError},
+            lock_recovery::recover,
+            stream_reconnect::{BackoffPolicy, Reconnector},
+        },
+    };
+
+    /// Event channel carrying a `RolloutTriggerEvent` whenever a trigger
+    /// fires.
+    pub const ROLLOUT_TRIGGER_EVENT: &str = "rollout-trigger";
+
+    /// How long to wait for further changes before acting, so updating
+    /// several referenced objects at once causes a single restart.
+    const SETTLE_DELAY: Duration = Duration::from_secs(5);
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RolloutTriggerEvent {
+        pub trigger_id: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub kind: String,
+        pub name: String,
+        pub sources: Vec<String>,
+        pub action: RolloutTriggerAction,
+        pub restarted: bool,
+        pub error: Option<String>,
+    }
+
+    /// One watch task per trigger, along with the trigger it was started
+    /// for so edited triggers are restarted on the next sync.
+    #[derive(Default)]
+    pub struct RolloutTriggers {
+        tasks: Mutex<HashMap<String, (RolloutTrigger, JoinHandle<()>)>>,
+    }
+
+    impl RolloutTriggers {
+        fn tasks_mutable(
+            &self,
+        ) -> MutexGuard<'_, HashMap<String, (RolloutTrigger, JoinHandle<()>)>> {
+            recover(&self.tasks)
+        }
+    }
+
+    enum Change {
+        Workload(Event<DynamicObject>),
+        ConfigMap(Event<ConfigMap>),
+        Secret(Event<Secret>),
+    }
+
+    /// The ConfigMaps and Secrets a pod template reads through volumes,
+    /// projected volumes, `env` and `envFrom`.
+    fn references(template: &Value) -> (HashSet<String>, HashSet<String>) {
+        let mut config_maps: HashSet<String> = HashSet::new();
+        let mut secrets: HashSet<String> = HashSet::new();
+        let add = |set: &mut HashSet<String>, name: &Value| {
+            if let Some(name) = name.as_str() {
+                set.insert(name.to_string());
+            }
+        };
+        for volume in template["volumes"].as_array().into_iter().flatten() {
+            add(&mut config_maps, &volume["configMap"]["name"]);
+            add(&mut secrets, &volume["secret"]["secretName"]);
+            for source in volume["projected"]["sources"]
+                .as_array()
+                .into_iter()
+                .flatten()
+            {
+                add(&mut config_maps, &source["configMap"]["name"]);
+                add(&mut secrets, &source["secret"]["name"]);
+            }
+        }
+        for field in ["containers", "initContainers"] {
+            for container in template[field].as_array().into_iter().flatten() {
+                for env in container["env"].as_array().into_iter().flatten() {
+                    add(
+                        &mut config_maps,
+                        &env["valueFrom"]["configMapKeyRef"]["name"],
+                    );
+                    add(&mut secrets, &env["valueFrom"]["secretKeyRef"]["name"]);
+                }
+                for env in container["envFrom"].as_array().into_iter().flatten() {
+                    add(&mut config_maps, &env["configMapRef"]["name"]);
+                    add(&mut secrets, &env["secretRef"]["name"]);
+                }
+            }
+        }
+        (config_maps, secrets)
+    }
+
+    /// The content of a ConfigMap or Secret, ignoring metadata so label or
+    /// annotation edits do not count as changes.
+    fn fingerprint(data: impl Serialize, binary: impl Serialize) -> String {
+        serde_json::to_string(&(data, binary)).unwrap_or_default()
+    }
+
+    async fn workload_api(
+        client: &Client,
+        trigger: &RolloutTrigger,
+    ) -> Result<Api<DynamicObject>, KubiousError> {
+        let gvk = GroupVersionKind::gvk("apps", "v1", trigger.kind.as_str());
+        let (resource, _) = pinned_kind(client, &gvk)
+            .await
+            .context(format!("Unknown workload kind {}", trigger.kind))?;
+        Ok(Api::namespaced_with(
+            client.clone(),
+            trigger.namespace.as_str(),
+            &resource,
+        ))
+    }
+
+    async fn fire(
+        handle: &AppHandle,
+        client: &Client,
+        trigger: &RolloutTrigger,
+        sources: Vec<String>,
+    ) {
+        let (restarted, error) = match trigger.action {
+            RolloutTriggerAction::Notify => (false, None),
+            RolloutTriggerAction::Restart => {
+                let result = workload_rollout::restart(
+                    client.clone(),
+                    trigger.namespace.clone(),
+                    trigger.kind.clone(),
+                    trigger.name.clone(),
+                )
+                .await;
+                let state = handle.state::<AppState>();
+                state.record_rollout_audit(RolloutAuditEntry {
+                    trigger_id: trigger.id.clone(),
+                    cluster: trigger.cluster.clone(),
+                    namespace: trigger.namespace.clone(),
+                    kind: trigger.kind.clone(),
+                    name: trigger.name.clone(),
+                    sources: sources.clone(),
+                    at: Utc::now().to_rfc3339(),
+                    error: result.as_ref().err().map(|e| e.to_string()),
+                });
+                let _ = state.save_state(handle.clone());
+                (result.is_ok(), result.err().map(|e| e.to_string()))
+            }
+        };
+        let _ = handle.emit(
+            ROLLOUT_TRIGGER_EVENT,
+            RolloutTriggerEvent {
+                trigger_id: trigger.id.clone(),
+                cluster: trigger.cluster.clone(),
+                namespace: trigger.namespace.clone(),
+                kind: trigger.kind.clone(),
+                name: trigger.name.clone(),
+                sources,
+                action: trigger.action.clone(),
+                restarted,
+                error,
+            },
+        );
+    }
+
+    /// Watches the workload and the ConfigMaps and Secrets of its namespace.
+    /// Every object is fingerprinted, whether or not it is referenced yet,
+    /// so content changes to referenced ones, including changes missed while
+    /// disconnected and found on relist, fire the trigger once things have
+    /// settled.
+    async fn watch(handle: AppHandle, client: Client, trigger: RolloutTrigger) {
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "rollout-triggers",
+            trigger.id.as_str(),
+            BackoffPolicy::default(),
+        );
+        let workloads = loop {
+            match workload_api(&client, &trigger).await {
+                Ok(api) => break api,
+                Err(error) => {
+                    if !reconnector.retry(error.message()).await {
+                        return;
+                    }
+                }
+            }
+        };
+        let namespace = trigger.namespace.as_str();
+        let workload_config =
+            watcher::Config::default().fields(format!("metadata.name={}", trigger.name).as_str());
+        let mut changes = stream::select(
+            watcher(workloads, workload_config).map(|e| e.map(Change::Workload)),
+            stream::select(
+                watcher(
+                    Api::<ConfigMap>::namespaced(client.clone(), namespace),
+                    watcher::Config::default(),
+                )
+                .map(|e| e.map(Change::ConfigMap)),
+                watcher(
+                    Api::<Secret>::namespaced(client.clone(), namespace),
+                    watcher::Config::default(),
+                )
+                .map(|e| e.map(Change::Secret)),
+            ),
+        )
+        .boxed();
+
+        let mut config_maps: HashSet<String> = HashSet::new();
+        let mut secrets: HashSet<String> = HashSet::new();
+        let mut fingerprints: HashMap<String, String> = HashMap::new();
+        let mut pending: Vec<String> = Vec::new();
+        let mut deadline: Option<Instant> = None;
+        loop {
+            let next = match deadline {
+                Some(at) => match timeout_at(at, changes.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        deadline = None;
+                        fire(&handle, &client, &trigger, std::mem::take(&mut pending)).await;
+                        continue;
+                    }
+                },
+                None => changes.next().await,
+            };
+            let Some(change) = next else {
+                break;
+            };
+            let (source, referenced, content) = match change {
+                Ok(Change::Workload(Event::Apply(object)))
+                | Ok(Change::Workload(Event::InitApply(object))) => {
+                    let object = serde_json::to_value(&object).unwrap_or_default();
+                    (config_maps, secrets) = references(&object["spec"]["template"]["spec"]);
+                    continue;
+                }
+                Ok(Change::Workload(Event::Delete(_))) => {
+                    config_maps.clear();
+                    secrets.clear();
+                    continue;
+                }
+                Ok(Change::Workload(Event::InitDone)) => {
+                    reconnector.connected();
+                    continue;
+                }
+                Ok(Change::ConfigMap(Event::Apply(object)))
+                | Ok(Change::ConfigMap(Event::InitApply(object))) => {
+                    let name = object.metadata.name.clone().unwrap_or_default();
+                    (
+                        format!("ConfigMap/{}", name),
+                        config_maps.contains(&name),
+                        fingerprint(&object.data, &object.binary_data),
+                    )
+                }
+                Ok(Change::Secret(Event::Apply(object)))
+                | Ok(Change::Secret(Event::InitApply(object))) => {
+                    let name = object.metadata.name.clone().unwrap_or_default();
+                    (
+                        format!("Secret/{}", name),
+                        secrets.contains(&name),
+                        fingerprint(&object.data, &object.string_data),
+                    )
+                }
+                Ok(_) => continue,
+                Err(error) => {
+                    if !reconnector.retry(error.to_string().as_str()).await {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            let previous = fingerprints.insert(source.clone(), content.clone());
+            if referenced && previous.is_some_and(|previous| previous != content) {
+                if !pending.contains(&source) {
+                    pending.push(source);
+                }
+                deadline = Some(Instant::now() + SETTLE_DELAY);
+            }
+        }
+        reconnector.closed();
+    }
+
+    /// Starts watches for new triggers, restarts the watches of edited ones
+    /// and stops those of removed ones. Triggers on unreachable clusters are
+    /// retried on the next sync.
+    pub async fn sync(handle: AppHandle) {
+        let state = handle.state::<AppState>();
+        let watches = handle.state::<RolloutTriggers>();
+        let triggers = state.get_settings().rollout_triggers;
+
+        let stale: Vec<String> = watches
+            .tasks_mutable()
+            .iter()
+            .filter(|(id, (running, _))| !triggers.iter().any(|t| t.id == **id && t == running))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in stale {
+            if let Some((_, task)) = watches.tasks_mutable().remove(&id) {
+                task.abort();
+            }
+        }
+
+        for trigger in triggers {
+            if watches.tasks_mutable().contains_key(&trigger.id) {
+                continue;
+            }
+            let Some(client) = state.client_for(trigger.cluster.as_str()).await else {
+                continue;
+            };
+            let id = trigger.id.clone();
+            let watch_handle = handle.clone();
+            let mut tasks = watches.tasks_mutable();
+            if tasks.contains_key(&id) {
+                continue;
+            }
+            let running = trigger.clone();
+            let task = tauri::async_runtime::spawn(async move {
+                let id = trigger.id.clone();
+                watch(watch_handle.clone(), client, trigger).await;
+                // Forget finished watches so the next sync restarts them.
+                watch_handle
+                    .state::<RolloutTriggers>()
+                    .tasks_mutable()
+                    .remove(&id);
+            });
+            tasks.insert(id, (running, task));
+        }
+    }
+}
@@ -0,0 +1,264 @@
+pub mod velero_backups {
+    use k8s_openapi::chrono::Utc;
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams, PostParams},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    const DEFAULT_NAMESPACE: &str = "velero";
+    const DEFAULT_TTL: &str = "720h0m0s";
+    const FINISHED_PHASES: [&str; 5] = [
+        "Completed",
+        "PartiallyFailed",
+        "Failed",
+        "FailedValidation",
+        "Deleting",
+    ];
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BackupSummary {
+        pub name: String,
+        pub phase: Option<String>,
+        pub finished: bool,
+        pub included_namespaces: Vec<String>,
+        pub storage_location: Option<String>,
+        pub schedule: Option<String>,
+        pub started: Option<String>,
+        pub completed: Option<String>,
+        pub expiration: Option<String>,
+        pub items_done: Option<i64>,
+        pub items_total: Option<i64>,
+        /// Share of items processed so far, from 0 to 100.
+        pub percent: Option<f64>,
+        pub errors: i64,
+        pub warnings: i64,
+        pub failure_reason: Option<String>,
+        pub validation_errors: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RestoreSummary {
+        pub name: String,
+        pub backup: Option<String>,
+        pub phase: Option<String>,
+        pub finished: bool,
+        pub included_namespaces: Vec<String>,
+        pub started: Option<String>,
+        pub completed: Option<String>,
+        pub items_done: Option<i64>,
+        pub items_total: Option<i64>,
+        pub percent: Option<f64>,
+        pub errors: i64,
+        pub warnings: i64,
+        pub failure_reason: Option<String>,
+        pub validation_errors: Vec<String>,
+    }
+
+    async fn api_for(
+        client: &Client,
+        kind: &str,
+        namespace: Option<String>,
+    ) -> Result<Api<DynamicObject>, KubiousError> {
+        let gvk = GroupVersionKind::gvk("velero.io", "v1", kind);
+        let (resource, _) = pinned_kind(client, &gvk)
+            .await
+            .context("Velero CRDs are not installed")?;
+        Ok(Api::namespaced_with(
+            client.clone(),
+            namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE),
+            &resource,
+        ))
+    }
+
+    fn text(value: &Value) -> Option<String> {
+        value.as_str().map(|s| s.to_string())
+    }
+
+    fn strings(value: &Value) -> Vec<String> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(text)
+            .collect()
+    }
+
+    /// Reads `status.progress`, which Velero reports as items done out of the
+    /// total it has discovered so far.
+    fn progress(status: &Value, done_field: &str) -> (Option<i64>, Option<i64>, Option<f64>) {
+        let done = status["progress"][done_field].as_i64();
+        let total = status["progress"]["totalItems"].as_i64();
+        let percent = match (done, total) {
+            (Some(done), Some(total)) if total > 0 => Some(done as f64 * 100.0 / total as f64),
+            _ => None,
+        };
+        (done, total, percent)
+    }
+
+    fn finished(phase: &Option<String>) -> bool {
+        phase
+            .as_deref()
+            .map(|p| FINISHED_PHASES.contains(&p))
+            .unwrap_or(false)
+    }
+
+    fn backup_summary(backup: &DynamicObject) -> BackupSummary {
+        let spec = &backup.data["spec"];
+        let status = &backup.data["status"];
+        let phase = text(&status["phase"]);
+        let (items_done, items_total, percent) = progress(status, "itemsBackedUp");
+        BackupSummary {
+            name: backup.metadata.name.clone().unwrap_or_default(),
+            finished: finished(&phase),
+            phase,
+            included_namespaces: strings(&spec["includedNamespaces"]),
+            storage_location: text(&spec["storageLocation"]),
+            schedule: backup
+                .metadata
+                .labels
+                .as_ref()
+                .and_then(|l| l.get("velero.io/schedule-name").cloned()),
+            started: text(&status["startTimestamp"]),
+            completed: text(&status["completionTimestamp"]),
+            expiration: text(&status["expiration"]),
+            items_done,
+            items_total,
+            percent,
+            errors: status["errors"].as_i64().unwrap_or(0),
+            warnings: status["warnings"].as_i64().unwrap_or(0),
+            failure_reason: text(&status["failureReason"]),
+            validation_errors: strings(&status["validationErrors"]),
+        }
+    }
+
+    fn restore_summary(restore: &DynamicObject) -> RestoreSummary {
+        let spec = &restore.data["spec"];
+        let status = &restore.data["status"];
+        let phase = text(&status["phase"]);
+        let (items_done, items_total, percent) = progress(status, "itemsRestored");
+        RestoreSummary {
+            name: restore.metadata.name.clone().unwrap_or_default(),
+            backup: text(&spec["backupName"]).or(text(&spec["scheduleName"])),
+            finished: finished(&phase),
+            phase,
+            included_namespaces: strings(&spec["includedNamespaces"]),
+            started: text(&status["startTimestamp"]),
+            completed: text(&status["completionTimestamp"]),
+            items_done,
+            items_total,
+            percent,
+            errors: status["errors"].as_i64().unwrap_or(0),
+            warnings: status["warnings"].as_i64().unwrap_or(0),
+            failure_reason: text(&status["failureReason"]),
+            validation_errors: strings(&status["validationErrors"]),
+        }
+    }
+
+    fn newest_first(started: &Option<String>, other: &Option<String>) -> std::cmp::Ordering {
+        // Unstarted objects are the most recent ones.
+        match (started, other) {
+            (None, None) => std::cmp::Ordering::Equal,
+            (None, Some(_)) => std::cmp::Ordering::Less,
+            (Some(_), None) => std::cmp::Ordering::Greater,
+            (Some(a), Some(b)) => b.cmp(a),
+        }
+    }
+
+    pub async fn list_backups(
+        client: Client,
+        velero_namespace: Option<String>,
+    ) -> Result<Vec<BackupSummary>, KubiousError> {
+        let backups = api_for(&client, "Backup", velero_namespace)
+            .await?
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list Backups.")?;
+        let mut summaries: Vec<BackupSummary> = backups.items.iter().map(backup_summary).collect();
+        summaries.sort_by(|a, b| newest_first(&a.started, &b.started));
+        Ok(summaries)
+    }
+
+    pub async fn list_restores(
+        client: Client,
+        velero_namespace: Option<String>,
+    ) -> Result<Vec<RestoreSummary>, KubiousError> {
+        let restores = api_for(&client, "Restore", velero_namespace)
+            .await?
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list Restores.")?;
+        let mut summaries: Vec<RestoreSummary> =
+            restores.items.iter().map(restore_summary).collect();
+        summaries.sort_by(|a, b| newest_first(&a.started, &b.started));
+        Ok(summaries)
+    }
+
+    /// Creates a Backup of a single namespace. Without a name, it is named
+    /// `<namespace>-<timestamp>` the way scheduled backups are.
+    pub async fn create_backup(
+        client: Client,
+        velero_namespace: Option<String>,
+        namespace: String,
+        name: Option<String>,
+        ttl: Option<String>,
+        storage_location: Option<String>,
+    ) -> Result<BackupSummary, KubiousError> {
+        let api = api_for(&client, "Backup", velero_namespace).await?;
+        let name = name.unwrap_or(format!(
+            "{}-{}",
+            namespace,
+            Utc::now().format("%Y%m%d%H%M%S")
+        ));
+        let mut spec = json!({
+            "includedNamespaces": [namespace],
+            "ttl": ttl.unwrap_or(DEFAULT_TTL.to_string()),
+        });
+        if let Some(location) = storage_location {
+            spec["storageLocation"] = Value::String(location);
+        }
+        let backup: DynamicObject = serde_json::from_value(json!({
+            "apiVersion": "velero.io/v1",
+            "kind": "Backup",
+            "metadata": {"name": name},
+            "spec": spec,
+        }))
+        .or(Err("Failed to build Backup.".to_string()))?;
+        let created = api
+            .create(&PostParams::default(), &backup)
+            .await
+            .context("Failed to create Backup")?;
+        Ok(backup_summary(&created))
+    }
+
+    /// Current progress of a Backup, for polling until `finished` is set.
+    pub async fn backup_progress(
+        client: Client,
+        velero_namespace: Option<String>,
+        name: String,
+    ) -> Result<BackupSummary, KubiousError> {
+        let backup = api_for(&client, "Backup", velero_namespace)
+            .await?
+            .get(name.as_str())
+            .await
+            .context("Unknown Backup")?;
+        Ok(backup_summary(&backup))
+    }
+
+    pub async fn restore_progress(
+        client: Client,
+        velero_namespace: Option<String>,
+        name: String,
+    ) -> Result<RestoreSummary, KubiousError> {
+        let restore = api_for(&client, "Restore", velero_namespace)
+            .await?
+            .get(name.as_str())
+            .await
+            .context("Unknown Restore")?;
+        Ok(restore_summary(&restore))
+    }
+}
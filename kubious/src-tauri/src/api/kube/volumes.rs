@@ -0,0 +1,191 @@
+pub mod volume_recovery {
+    use std::collections::HashMap;
+
+    use k8s_openapi::api::core::v1::{PersistentVolume, PersistentVolumeClaim};
+    use kube::{
+        api::{ListParams, Patch, PatchParams},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::util::kubious_error::{ErrorContext, KubiousError};
+
+    const RECLAIM_POLICIES: [&str; 3] = ["Retain", "Delete", "Recycle"];
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum VolumeBinding {
+        /// Bound to a claim that still exists.
+        Bound,
+        /// Available for new claims.
+        Available,
+        /// The claim was deleted and the reclaim policy kept the volume.
+        Released,
+        /// Bound to a claim that no longer exists, or was recreated with a
+        /// different uid.
+        Orphaned,
+        Pending,
+        Failed,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct VolumeStatus {
+        pub name: String,
+        pub phase: Option<String>,
+        pub binding: VolumeBinding,
+        pub reclaim_policy: Option<String>,
+        pub storage_class: Option<String>,
+        pub capacity: Option<String>,
+        /// `namespace/name` of the claim the volume references.
+        pub claim: Option<String>,
+        pub claim_exists: bool,
+    }
+
+    fn claim_key(namespace: &str, name: &str) -> String {
+        format!("{}/{}", namespace, name)
+    }
+
+    fn volume_status(
+        pv: &PersistentVolume,
+        claims: &HashMap<String, Option<String>>,
+    ) -> VolumeStatus {
+        let spec = pv.spec.clone().unwrap_or_default();
+        let phase = pv.status.as_ref().and_then(|s| s.phase.clone());
+        let claim_ref = spec.claim_ref.as_ref().and_then(|r| {
+            Some((
+                claim_key(r.namespace.as_deref()?, r.name.as_deref()?),
+                r.uid.clone(),
+            ))
+        });
+        // A claim only counts if it is the same object the volume was bound
+        // to, not a later claim reusing the name.
+        let claim_exists = claim_ref
+            .as_ref()
+            .and_then(|(key, uid)| claims.get(key).map(|live| uid.is_none() || live == uid))
+            .unwrap_or(false);
+        let binding = match (phase.as_deref(), &claim_ref) {
+            (Some("Released"), _) => VolumeBinding::Released,
+            (Some("Failed"), _) => VolumeBinding::Failed,
+            (Some("Pending"), _) => VolumeBinding::Pending,
+            (Some("Bound"), Some(_)) if !claim_exists => VolumeBinding::Orphaned,
+            (Some("Bound"), _) => VolumeBinding::Bound,
+            _ => VolumeBinding::Available,
+        };
+        VolumeStatus {
+            name: pv.metadata.name.clone().unwrap_or_default(),
+            phase,
+            binding,
+            reclaim_policy: spec.persistent_volume_reclaim_policy,
+            storage_class: spec.storage_class_name,
+            capacity: spec
+                .capacity
+                .and_then(|c| c.get("storage").map(|q| q.0.clone())),
+            claim: claim_ref.map(|(key, _)| key),
+            claim_exists,
+        }
+    }
+
+    async fn claim_uids(client: &Client) -> Result<HashMap<String, Option<String>>, KubiousError> {
+        let claims = Api::<PersistentVolumeClaim>::all(client.clone())
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list PersistentVolumeClaims.")?;
+        Ok(claims
+            .items
+            .into_iter()
+            .filter_map(|c| {
+                Some((
+                    claim_key(
+                        c.metadata.namespace.as_deref()?,
+                        c.metadata.name.as_deref()?,
+                    ),
+                    c.metadata.uid,
+                ))
+            })
+            .collect())
+    }
+
+    /// Every PV with how it relates to its claim, orphaned and released
+    /// volumes first.
+    pub async fn list_volumes(client: Client) -> Result<Vec<VolumeStatus>, KubiousError> {
+        let claims = claim_uids(&client).await?;
+        let volumes = Api::<PersistentVolume>::all(client)
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list PersistentVolumes.")?;
+        let mut statuses: Vec<VolumeStatus> = volumes
+            .items
+            .iter()
+            .map(|pv| volume_status(pv, &claims))
+            .collect();
+        let rank = |binding: &VolumeBinding| match binding {
+            VolumeBinding::Orphaned => 0,
+            VolumeBinding::Released => 1,
+            VolumeBinding::Failed => 2,
+            _ => 3,
+        };
+        statuses.sort_by(|a, b| {
+            rank(&a.binding)
+                .cmp(&rank(&b.binding))
+                .then(a.name.cmp(&b.name))
+        });
+        Ok(statuses)
+    }
+
+    pub async fn set_reclaim_policy(
+        client: Client,
+        name: String,
+        policy: String,
+    ) -> Result<VolumeStatus, KubiousError> {
+        if !RECLAIM_POLICIES.contains(&policy.as_str()) {
+            return Err(format!("Unknown reclaim policy {}", policy).into());
+        }
+        let api: Api<PersistentVolume> = Api::all(client.clone());
+        let patch = json!({ "spec": { "persistentVolumeReclaimPolicy": policy } });
+        let pv = api
+            .patch(
+                name.as_str(),
+                &PatchParams::default(),
+                &Patch::Merge(&patch),
+            )
+            .await
+            .context(format!("Failed to patch {}", name))?;
+        Ok(volume_status(&pv, &claim_uids(&client).await?))
+    }
+
+    /// Clears the claim reference of a Released (or orphaned) volume so it
+    /// becomes Available and can be bound again. The data on the volume is
+    /// kept; a new claim binds to it by matching `volumeName` or selectors.
+    pub async fn make_available(
+        client: Client,
+        name: String,
+    ) -> Result<VolumeStatus, KubiousError> {
+        let claims = claim_uids(&client).await?;
+        let api: Api<PersistentVolume> = Api::all(client);
+        let current = api
+            .get(name.as_str())
+            .await
+            .context(format!("Failed to get {}", name))?;
+        let status = volume_status(&current, &claims);
+        if !matches!(
+            status.binding,
+            VolumeBinding::Released | VolumeBinding::Orphaned
+        ) {
+            return Err(format!(
+                "{} is {:?}; only Released or orphaned volumes can be made available",
+                name, status.binding
+            )
+            .into());
+        }
+        let patch = json!({ "spec": { "claimRef": null } });
+        let pv = api
+            .patch(
+                name.as_str(),
+                &PatchParams::default(),
+                &Patch::Merge(&patch),
+            )
+            .await
+            .context(format!("Failed to patch {}", name))?;
+        Ok(volume_status(&pv, &claims))
+    }
+}
@@ -0,0 +1,258 @@
+pub mod vpa_insights {
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams, Patch, PatchParams},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::util::{
+        kube_quantity::parse_quantity,
+        kubious_error::{ErrorContext, KubiousError},
+    };
+
+    const DEFAULT_THRESHOLD: f64 = 0.3;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum Provisioning {
+        Over,
+        Under,
+        Unset,
+        Balanced,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ContainerRecommendation {
+        pub container: String,
+        pub resource: String,
+        pub current: Option<String>,
+        pub target: String,
+        pub lower_bound: Option<String>,
+        pub upper_bound: Option<String>,
+        pub deviation: Option<f64>,
+        pub provisioning: Provisioning,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct VpaReport {
+        pub name: String,
+        pub namespace: String,
+        pub target_kind: String,
+        pub target_name: String,
+        pub update_mode: Option<String>,
+        pub flagged: bool,
+        pub containers: Vec<ContainerRecommendation>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct VpaPatchPreview {
+        pub target_kind: String,
+        pub target_name: String,
+        pub namespace: String,
+        pub patch: Value,
+        pub applied: bool,
+    }
+
+    async fn vpa_api(
+        client: &Client,
+        namespace: Option<&str>,
+    ) -> Result<Api<DynamicObject>, KubiousError> {
+        let gvk = GroupVersionKind::gvk("autoscaling.k8s.io", "v1", "VerticalPodAutoscaler");
+        let (resource, _) = pinned_kind(client, &gvk)
+            .await
+            .context("VerticalPodAutoscaler CRDs are not installed")?;
+        Ok(match namespace {
+            Some(ns) => Api::namespaced_with(client.clone(), ns, &resource),
+            None => Api::all_with(client.clone(), &resource),
+        })
+    }
+
+    async fn target_api(
+        client: &Client,
+        target: &Value,
+        namespace: &str,
+    ) -> Result<(Api<DynamicObject>, String, String), KubiousError> {
+        let (Some(api_version), Some(kind), Some(name)) = (
+            target["apiVersion"].as_str(),
+            target["kind"].as_str(),
+            target["name"].as_str(),
+        ) else {
+            return Err("VPA has an incomplete targetRef".into());
+        };
+        let (group, version) = api_version.split_once('/').unwrap_or(("", api_version));
+        let (resource, _) = pinned_kind(client, &GroupVersionKind::gvk(group, version, kind))
+            .await
+            .context(format!("Unknown target kind {}", kind))?;
+        Ok((
+            Api::namespaced_with(client.clone(), namespace, &resource),
+            kind.to_string(),
+            name.to_string(),
+        ))
+    }
+
+    fn assess(current: Option<&str>, target: &str, threshold: f64) -> (Option<f64>, Provisioning) {
+        let (Some(current), Some(target)) =
+            (current.and_then(parse_quantity), parse_quantity(target))
+        else {
+            return (None, Provisioning::Unset);
+        };
+        if target <= 0.0 {
+            return (None, Provisioning::Balanced);
+        }
+        let deviation = (current - target) / target;
+        let provisioning = if deviation > threshold {
+            Provisioning::Over
+        } else if deviation < -threshold {
+            Provisioning::Under
+        } else {
+            Provisioning::Balanced
+        };
+        (Some(deviation), provisioning)
+    }
+
+    fn containers_of(workload: &DynamicObject) -> Vec<Value> {
+        workload.data["spec"]["template"]["spec"]["containers"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    async fn evaluate(
+        client: &Client,
+        vpa: &DynamicObject,
+        threshold: f64,
+    ) -> Result<VpaReport, KubiousError> {
+        let namespace = vpa.metadata.namespace.clone().unwrap_or_default();
+        let (api, target_kind, target_name) =
+            target_api(client, &vpa.data["spec"]["targetRef"], namespace.as_str()).await?;
+        let workload = api
+            .get(target_name.as_str())
+            .await
+            .context(format!("Failed to fetch {}/{}", target_kind, target_name))?;
+        let containers = containers_of(&workload);
+
+        let mut recommendations: Vec<ContainerRecommendation> = Vec::new();
+        for recommendation in vpa.data["status"]["recommendation"]["containerRecommendations"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            let container = recommendation["containerName"].as_str().unwrap_or_default();
+            let requests = containers
+                .iter()
+                .find(|c| c["name"].as_str() == Some(container))
+                .map(|c| c["resources"]["requests"].clone())
+                .unwrap_or(Value::Null);
+            for (resource, target) in recommendation["target"].as_object().into_iter().flatten() {
+                let Some(target) = target.as_str() else {
+                    continue;
+                };
+                let current = requests[resource].as_str();
+                let (deviation, provisioning) = assess(current, target, threshold);
+                recommendations.push(ContainerRecommendation {
+                    container: container.to_string(),
+                    resource: resource.clone(),
+                    current: current.map(|c| c.to_string()),
+                    target: target.to_string(),
+                    lower_bound: recommendation["lowerBound"][resource]
+                        .as_str()
+                        .map(|v| v.to_string()),
+                    upper_bound: recommendation["upperBound"][resource]
+                        .as_str()
+                        .map(|v| v.to_string()),
+                    deviation,
+                    provisioning,
+                });
+            }
+        }
+
+        Ok(VpaReport {
+            name: vpa.metadata.name.clone().unwrap_or_default(),
+            namespace,
+            target_kind,
+            target_name,
+            update_mode: vpa.data["spec"]["updatePolicy"]["updateMode"]
+                .as_str()
+                .map(|m| m.to_string()),
+            flagged: recommendations
+                .iter()
+                .any(|r| r.provisioning != Provisioning::Balanced),
+            containers: recommendations,
+        })
+    }
+
+    pub async fn recommendations(
+        client: Client,
+        namespace: Option<String>,
+        threshold: Option<f64>,
+        only_flagged: bool,
+    ) -> Result<Vec<VpaReport>, KubiousError> {
+        let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD);
+        let vpas = vpa_api(&client, namespace.as_deref())
+            .await?
+            .list(&ListParams::default())
+            .await
+            .context("Failed to list VerticalPodAutoscalers.")?;
+        let mut reports: Vec<VpaReport> = Vec::new();
+        for vpa in vpas.items {
+            if let Ok(report) = evaluate(&client, &vpa, threshold).await {
+                if report.flagged || !only_flagged {
+                    reports.push(report);
+                }
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Builds the request patch for a VPA's target workload. Nothing is sent to
+    /// the cluster unless `confirm` is set, so callers can show the patch first.
+    pub async fn adopt_recommendation(
+        client: Client,
+        namespace: String,
+        name: String,
+        confirm: bool,
+    ) -> Result<VpaPatchPreview, KubiousError> {
+        let vpa = vpa_api(&client, Some(namespace.as_str()))
+            .await?
+            .get(name.as_str())
+            .await
+            .context("Unknown VerticalPodAutoscaler")?;
+        let containers: Vec<Value> = vpa.data["status"]["recommendation"]
+            ["containerRecommendations"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|r| {
+                Some(json!({
+                    "name": r["containerName"].as_str()?,
+                    "resources": {"requests": r["target"].clone()}
+                }))
+            })
+            .collect();
+        if containers.is_empty() {
+            return Err("VPA has no recommendations yet".into());
+        }
+
+        let (api, target_kind, target_name) =
+            target_api(&client, &vpa.data["spec"]["targetRef"], namespace.as_str()).await?;
+        let patch = json!({"spec": {"template": {"spec": {"containers": containers}}}});
+        if confirm {
+            api.patch(
+                target_name.as_str(),
+                &PatchParams::default(),
+                &Patch::Strategic(&patch),
+            )
+            .await
+            .context(format!("Failed to patch {}", target_kind))?;
+        }
+
+        Ok(VpaPatchPreview {
+            target_kind,
+            target_name,
+            namespace,
+            patch,
+            applied: confirm,
+        })
+    }
+}
@@ -0,0 +1,117 @@
+pub mod log_bookmarks {
+    use std::fs;
+
+    use k8s_openapi::chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        api::app_settings::LogBookmark,
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    /// Characters of the line kept with a bookmark for display.
+    const PREVIEW_LENGTH: usize = 200;
+
+    /// Where a bookmark was found in a log.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BookmarkLocation {
+        pub bookmark: LogBookmark,
+        /// Zero-based line index, or `None` if the line is not in the log.
+        pub line: Option<usize>,
+        /// Whether the timestamp matched too, rather than only the content.
+        pub exact: bool,
+    }
+
+    /// Splits the RFC 3339 timestamp the API server prefixes lines with when
+    /// timestamps are requested.
+    fn split_timestamp(line: &str) -> (Option<&str>, &str) {
+        if let Some((prefix, rest)) = line.split_once(' ') {
+            if DateTime::parse_from_rfc3339(prefix).is_ok() {
+                return (Some(prefix), rest);
+            }
+        }
+        (None, line)
+    }
+
+    /// Hashes a line's content without its timestamp, so the same line is
+    /// found in streams opened with and without timestamps.
+    fn line_hash(content: &str) -> String {
+        format!(
+            "{:x}",
+            Sha256::digest(content.trim_end_matches('\r').as_bytes())
+        )
+    }
+
+    pub fn create(
+        cluster: String,
+        namespace: String,
+        pod: String,
+        container: Option<String>,
+        line: &str,
+        note: String,
+    ) -> LogBookmark {
+        let (timestamp, content) = split_timestamp(line);
+        let created = Utc::now();
+        LogBookmark {
+            id: format!(
+                "bookmark-{}",
+                created.timestamp_nanos_opt().unwrap_or_default()
+            ),
+            cluster,
+            namespace,
+            pod,
+            container,
+            timestamp: timestamp.map(|t| t.to_string()),
+            line_hash: line_hash(content),
+            preview: content.trim_end().chars().take(PREVIEW_LENGTH).collect(),
+            note,
+            created: created.to_rfc3339(),
+        }
+    }
+
+    /// Finds bookmarked lines in a log, either the buffer of a reopened
+    /// stream or a saved log file. A line with the same content and
+    /// timestamp wins; otherwise the first line with the same content is
+    /// used.
+    pub fn locate(
+        bookmarks: Vec<LogBookmark>,
+        text: Option<String>,
+        path: Option<String>,
+    ) -> Result<Vec<BookmarkLocation>, KubiousError> {
+        let text = match (text, path) {
+            (Some(text), _) => text,
+            (None, Some(path)) => {
+                fs::read_to_string(&path).context(format!("Failed to read {}", path))?
+            }
+            (None, None) => return Err("Either text or path is required".into()),
+        };
+        let lines: Vec<(Option<&str>, String)> = text
+            .lines()
+            .map(|line| {
+                let (timestamp, content) = split_timestamp(line);
+                (timestamp, line_hash(content))
+            })
+            .collect();
+        Ok(bookmarks
+            .into_iter()
+            .map(|bookmark| {
+                let exact = lines.iter().position(|(timestamp, hash)| {
+                    *hash == bookmark.line_hash
+                        && timestamp.is_some()
+                        && *timestamp == bookmark.timestamp.as_deref()
+                });
+                let line = exact.or_else(|| {
+                    lines
+                        .iter()
+                        .position(|(_, hash)| *hash == bookmark.line_hash)
+                });
+                BookmarkLocation {
+                    exact: exact.is_some(),
+                    line,
+                    bookmark,
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,188 @@
+pub mod logs_api {
+    use crate::{api::app_state::AppState, util::kubious_error::KubiousError, CommandHandler};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::Manager;
+
+    use super::{
+        log_bookmarks,
+        log_sessions::{self, LogOptions, LogSessions},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "command")]
+    pub enum LogsCommand {
+        /// Follows a pod's log in the current cluster. Output arrives on the
+        /// returned session's `channel`.
+        StartLogSession {
+            namespace: String,
+            pod: String,
+            #[serde(default)]
+            options: LogOptions,
+        },
+        StopLogSession {
+            id: String,
+        },
+        ListLogSessions {},
+        /// Bookmarks a line of a pod's log in the current cluster, or the
+        /// one given.
+        AddLogBookmark {
+            cluster: Option<String>,
+            namespace: String,
+            pod: String,
+            container: Option<String>,
+            line: String,
+            note: String,
+        },
+        UpdateLogBookmark {
+            id: String,
+            note: String,
+        },
+        RemoveLogBookmark {
+            id: String,
+        },
+        ListLogBookmarks {
+            cluster: Option<String>,
+            namespace: Option<String>,
+            pod: Option<String>,
+        },
+        /// Finds a stream's bookmarked lines in its reopened buffer (`text`)
+        /// or in a saved log file (`path`).
+        LocateLogBookmarks {
+            cluster: Option<String>,
+            namespace: String,
+            pod: String,
+            container: Option<String>,
+            text: Option<String>,
+            path: Option<String>,
+        },
+    }
+
+    fn bookmark_cluster(
+        handle: &tauri::AppHandle,
+        cluster: &Option<String>,
+    ) -> Result<String, KubiousError> {
+        cluster
+            .clone()
+            .or(handle
+                .state::<AppState>()
+                .get_current_config()
+                .map(|(key, _)| key))
+            .ok_or(KubiousError::config("No config selected."))
+    }
+
+    impl CommandHandler for LogsCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            match self {
+                LogsCommand::StartLogSession {
+                    namespace,
+                    pod,
+                    options,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) =
+                        (state.client().await, state.get_current_config())
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(Ok(log_sessions::start(
+                        handle,
+                        client,
+                        cluster,
+                        namespace.clone(),
+                        pod.clone(),
+                        options.clone(),
+                    )))
+                }
+                LogsCommand::StopLogSession { id } => {
+                    self.wrap_in_value(Ok(log_sessions::stop(handle, id.as_str())))
+                }
+                LogsCommand::ListLogSessions {} => {
+                    self.wrap_in_value(Ok(handle.state::<LogSessions>().active()))
+                }
+                LogsCommand::AddLogBookmark {
+                    cluster,
+                    namespace,
+                    pod,
+                    container,
+                    line,
+                    note,
+                } => {
+                    let bookmark = log_bookmarks::create(
+                        bookmark_cluster(handle, cluster)?,
+                        namespace.clone(),
+                        pod.clone(),
+                        container.clone(),
+                        line.as_str(),
+                        note.clone(),
+                    );
+                    let state = handle.state::<AppState>();
+                    state.put_log_bookmark(bookmark.clone());
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(bookmark))
+                }
+                LogsCommand::UpdateLogBookmark { id, note } => {
+                    let state = handle.state::<AppState>();
+                    let Some(mut bookmark) = state
+                        .get_settings()
+                        .log_bookmarks
+                        .into_iter()
+                        .find(|b| &b.id == id)
+                    else {
+                        return Err("Unknown bookmark id".into());
+                    };
+                    bookmark.note = note.clone();
+                    state.put_log_bookmark(bookmark.clone());
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(bookmark))
+                }
+                LogsCommand::RemoveLogBookmark { id } => {
+                    let state = handle.state::<AppState>();
+                    if !state.remove_log_bookmark(id) {
+                        return Err("Unknown bookmark id".into());
+                    }
+                    state.save_state(handle.clone())?;
+                    self.wrap_in_value(Ok(()))
+                }
+                LogsCommand::ListLogBookmarks {
+                    cluster,
+                    namespace,
+                    pod,
+                } => {
+                    let cluster = bookmark_cluster(handle, cluster)?;
+                    self.wrap_in_value(Ok(handle.state::<AppState>().log_bookmarks(
+                        cluster.as_str(),
+                        namespace.as_deref(),
+                        pod.as_deref(),
+                    )))
+                }
+                LogsCommand::LocateLogBookmarks {
+                    cluster,
+                    namespace,
+                    pod,
+                    container,
+                    text,
+                    path,
+                } => {
+                    let cluster = bookmark_cluster(handle, cluster)?;
+                    let bookmarks = handle
+                        .state::<AppState>()
+                        .log_bookmarks(
+                            cluster.as_str(),
+                            Some(namespace.as_str()),
+                            Some(pod.as_str()),
+                        )
+                        .into_iter()
+                        .filter(|b| container.is_none() || b.container == *container)
+                        .collect();
+                    self.wrap_in_value(log_bookmarks::locate(bookmarks, text.clone(), path.clone()))
+                }
+            }
+        }
+    }
+}
+
+mod session;
+pub use session::log_sessions;
+mod bookmarks;
+pub use bookmarks::log_bookmarks;
@@ -0,0 +1,233 @@
+pub mod log_sessions {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard,
+        },
+    };
+
+    use futures::AsyncReadExt;
+    use k8s_openapi::{
+        api::core::v1::Pod,
+        chrono::{DateTime, Utc},
+    };
+    use kube::{api::LogParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use tauri::{async_runtime::JoinHandle, AppHandle, Emitter, Manager};
+
+    use crate::{
+        api::multi_cluster_logs::container_running,
+        util::{
+            lock_recovery::recover,
+            stream_reconnect::{BackoffPolicy, Reconnector},
+            utf8_chunks::take_text,
+        },
+    };
+
+    /// Prefix of the per-session event channel; the full channel is
+    /// `log-session/<id>`.
+    pub const LOG_SESSION_CHANNEL_PREFIX: &str = "log-session/";
+
+    const CHUNK_SIZE: usize = 8192;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "type")]
+    pub enum LogSessionEvent {
+        /// Raw log output as received; chunks do not necessarily end on a
+        /// line break.
+        Chunk { data: String },
+        /// The stream closed, either because the container exited or the
+        /// connection could not be re-established.
+        Ended { error: Option<String> },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct LogSession {
+        pub id: String,
+        pub channel: String,
+        pub cluster: String,
+        pub namespace: String,
+        pub pod: String,
+        pub container: Option<String>,
+        pub started: String,
+    }
+
+    struct RunningSession {
+        session: LogSession,
+        task: JoinHandle<()>,
+    }
+
+    /// Open follow streams keyed by session id.
+    #[derive(Default)]
+    pub struct LogSessions {
+        sessions: Mutex<HashMap<String, RunningSession>>,
+        counter: AtomicU64,
+    }
+
+    impl LogSessions {
+        fn sessions_mutable(&self) -> MutexGuard<'_, HashMap<String, RunningSession>> {
+            recover(&self.sessions)
+        }
+
+        fn next_id(&self) -> String {
+            format!("log-{}", self.counter.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        pub fn active(&self) -> Vec<LogSession> {
+            let mut sessions: Vec<LogSession> = self
+                .sessions_mutable()
+                .values()
+                .map(|running| running.session.clone())
+                .collect();
+            sessions.sort_by(|a, b| a.started.cmp(&b.started));
+            sessions
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    pub struct LogOptions {
+        pub container: Option<String>,
+        pub tail_lines: Option<i64>,
+        pub since_seconds: Option<i64>,
+        #[serde(default)]
+        pub timestamps: bool,
+    }
+
+    /// Streams one connection's worth of log output, recording when data
+    /// last arrived so a reconnect can resume from there.
+    async fn stream_log(
+        handle: &AppHandle,
+        api: &Api<Pod>,
+        session: &LogSession,
+        params: &LogParams,
+        reconnector: &mut Reconnector,
+        received: &mut Option<DateTime<Utc>>,
+    ) -> Result<(), String> {
+        let mut reader = api
+            .log_stream(session.pod.as_str(), params)
+            .await
+            .map_err(|e| format!("Failed to open log stream: {}", e))?;
+        reconnector.connected();
+        received.get_or_insert(Utc::now());
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        let mut pending: Vec<u8> = Vec::new();
+        loop {
+            match reader.read(&mut chunk).await {
+                Ok(0) => return Ok(()),
+                Ok(read) => {
+                    *received = Some(Utc::now());
+                    pending.extend_from_slice(&chunk[..read]);
+                    let data = take_text(&mut pending);
+                    if !data.is_empty() {
+                        let _ =
+                            handle.emit(session.channel.as_str(), LogSessionEvent::Chunk { data });
+                    }
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+
+    /// Follows the log until the container stops. A stream that drops while
+    /// the container is still running is reopened with backoff, starting at
+    /// the time output was last received; lines from that second may repeat.
+    async fn forward(
+        handle: AppHandle,
+        client: Client,
+        session: LogSession,
+        mut params: LogParams,
+    ) {
+        let api: Api<Pod> = Api::namespaced(client, session.namespace.as_str());
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "logs",
+            session.id.as_str(),
+            BackoffPolicy::default(),
+        );
+        let mut received: Option<DateTime<Utc>> = None;
+        let error = loop {
+            let result = stream_log(
+                &handle,
+                &api,
+                &session,
+                &params,
+                &mut reconnector,
+                &mut received,
+            )
+            .await;
+            let container = session.container.as_deref();
+            if let Ok(false) = container_running(&api, session.pod.as_str(), container).await {
+                break result.err();
+            }
+            let reason = result.err().unwrap_or("Log stream closed".to_string());
+            if let Some(since) = received {
+                params.tail_lines = None;
+                params.since_seconds = None;
+                params.since_time = Some(since);
+            }
+            if !reconnector.retry(reason.as_str()).await {
+                break Some(reason);
+            }
+        };
+        reconnector.closed();
+        handle
+            .state::<LogSessions>()
+            .sessions_mutable()
+            .remove(&session.id);
+        let _ = handle.emit(session.channel.as_str(), LogSessionEvent::Ended { error });
+    }
+
+    /// Follows one pod's log and forwards the output to the session's own
+    /// event channel until the stream ends or the session is stopped.
+    pub fn start(
+        handle: &AppHandle,
+        client: Client,
+        cluster: String,
+        namespace: String,
+        pod: String,
+        options: LogOptions,
+    ) -> LogSession {
+        let sessions = handle.state::<LogSessions>();
+        let id = sessions.next_id();
+        let session = LogSession {
+            channel: format!("{}{}", LOG_SESSION_CHANNEL_PREFIX, id),
+            id: id.clone(),
+            cluster,
+            namespace,
+            pod,
+            container: options.container.clone(),
+            started: Utc::now().to_rfc3339(),
+        };
+        let params = LogParams {
+            container: options.container,
+            follow: true,
+            tail_lines: options.tail_lines,
+            since_seconds: options.since_seconds,
+            timestamps: options.timestamps,
+            ..LogParams::default()
+        };
+
+        let mut running = sessions.sessions_mutable();
+        let task =
+            tauri::async_runtime::spawn(forward(handle.clone(), client, session.clone(), params));
+        running.insert(
+            id,
+            RunningSession {
+                session: session.clone(),
+                task,
+            },
+        );
+        session
+    }
+
+    pub fn stop(handle: &AppHandle, id: &str) -> bool {
+        let removed = handle.state::<LogSessions>().sessions_mutable().remove(id);
+        if let Some(running) = removed {
+            running.task.abort();
+            true
+        } else {
+            false
+        }
+    }
+}
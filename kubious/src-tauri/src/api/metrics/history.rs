@@ -0,0 +1,191 @@
+pub mod metrics_history {
+    use std::{
+        collections::{HashMap, VecDeque},
+        sync::{Mutex, MutexGuard},
+        time::{Duration, Instant},
+    };
+
+    use k8s_openapi::chrono::Utc;
+    use serde::{Deserialize, Serialize};
+    use tauri::{AppHandle, Manager};
+
+    use crate::api::{
+        app_state::AppState,
+        metrics::resource_metrics::{self, NodeSample, PodSample},
+    };
+    use crate::util::lock_recovery::recover;
+
+    /// Points kept per pod or node; a quarter of an hour at the sampling
+    /// interval.
+    const CAPACITY: usize = 60;
+
+    const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+    /// The sampler pauses once usage has not been asked for in this long.
+    const IDLE_AFTER: Duration = Duration::from_secs(300);
+
+    /// Series not updated in this long, e.g. of deleted pods, are dropped.
+    const STALE_AFTER: Duration = Duration::from_secs(3600);
+
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+    pub enum UsageTarget {
+        Pod,
+        Node,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct UsagePoint {
+        pub at: String,
+        pub cpu: f64,
+        pub memory: f64,
+    }
+
+    struct Series {
+        updated: Instant,
+        points: VecDeque<UsagePoint>,
+    }
+
+    #[derive(Default)]
+    struct History {
+        series: HashMap<String, Series>,
+        last_used: Option<Instant>,
+        started: bool,
+    }
+
+    /// Recent usage per pod and node for sparklines, kept in memory only.
+    /// Filled from every usage query and, while usage is being looked at,
+    /// by a background sampler for the current cluster.
+    #[derive(Default)]
+    pub struct MetricsHistory {
+        history: Mutex<History>,
+    }
+
+    impl MetricsHistory {
+        fn history_mutable(&self) -> MutexGuard<'_, History> {
+            recover(&self.history)
+        }
+
+        /// Notes that usage was asked for, keeping the sampler running.
+        pub fn touch(&self) {
+            self.history_mutable().last_used = Some(Instant::now());
+        }
+
+        /// Appends a point unless metrics-server has not scraped since the
+        /// last one.
+        fn record(&self, key: String, point: UsagePoint) {
+            let mut history = self.history_mutable();
+            let series = history.series.entry(key).or_insert(Series {
+                updated: Instant::now(),
+                points: VecDeque::new(),
+            });
+            series.updated = Instant::now();
+            if series.points.back().is_some_and(|last| last.at == point.at) {
+                return;
+            }
+            series.points.push_back(point);
+            while series.points.len() > CAPACITY {
+                series.points.pop_front();
+            }
+        }
+
+        pub fn series(&self, key: &str) -> Vec<UsagePoint> {
+            self.history_mutable()
+                .series
+                .get(key)
+                .map(|s| s.points.iter().cloned().collect())
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn series_key(
+        cluster: &str,
+        target: UsageTarget,
+        namespace: Option<&str>,
+        name: &str,
+    ) -> String {
+        match (target, namespace) {
+            (UsageTarget::Pod, Some(namespace)) => {
+                format!("{}\npod/{}/{}", cluster, namespace, name)
+            }
+            _ => format!("{}\nnode/{}", cluster, name),
+        }
+    }
+
+    fn point(timestamp: &Option<String>, cpu: f64, memory: f64) -> UsagePoint {
+        UsagePoint {
+            at: timestamp.clone().unwrap_or(Utc::now().to_rfc3339()),
+            cpu,
+            memory,
+        }
+    }
+
+    pub fn record_pods(handle: &AppHandle, cluster: &str, samples: &[PodSample]) {
+        let history = handle.state::<MetricsHistory>();
+        for sample in samples {
+            history.record(
+                series_key(
+                    cluster,
+                    UsageTarget::Pod,
+                    Some(sample.namespace.as_str()),
+                    sample.name.as_str(),
+                ),
+                point(&sample.timestamp, sample.cpu, sample.memory),
+            );
+        }
+    }
+
+    pub fn record_nodes(handle: &AppHandle, cluster: &str, samples: &[NodeSample]) {
+        let history = handle.state::<MetricsHistory>();
+        for sample in samples {
+            history.record(
+                series_key(cluster, UsageTarget::Node, None, sample.name.as_str()),
+                point(&sample.timestamp, sample.cpu, sample.memory),
+            );
+        }
+    }
+
+    /// Samples every pod and node of the current cluster if usage was asked
+    /// for recently. Clusters without metrics-server are skipped quietly.
+    pub async fn sample(handle: &AppHandle) {
+        {
+            let history = handle.state::<MetricsHistory>();
+            let mut history = history.history_mutable();
+            history
+                .series
+                .retain(|_, series| series.updated.elapsed() <= STALE_AFTER);
+            if history
+                .last_used
+                .is_none_or(|used| used.elapsed() > IDLE_AFTER)
+            {
+                return;
+            }
+        }
+        let state = handle.state::<AppState>();
+        let (Some(client), Some((cluster, _))) = (state.client().await, state.get_current_config())
+        else {
+            return;
+        };
+        if let Ok(nodes) = resource_metrics::node_samples(client.clone()).await {
+            record_nodes(handle, cluster.as_str(), &nodes);
+        }
+        if let Ok(pods) = resource_metrics::pod_samples(client, None, None).await {
+            record_pods(handle, cluster.as_str(), &pods);
+        }
+    }
+
+    /// Starts the background sampler once; later calls do nothing.
+    pub async fn start(handle: AppHandle) {
+        {
+            let history = handle.state::<MetricsHistory>();
+            let mut history = history.history_mutable();
+            if history.started {
+                return;
+            }
+            history.started = true;
+        }
+        loop {
+            sample(&handle).await;
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+        }
+    }
+}
@@ -0,0 +1,138 @@
+pub mod metrics_api {
+    use crate::{
+        api::{app_settings::TenancyFilter, app_state::AppState},
+        util::kubious_error::KubiousError,
+        CommandHandler,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::Manager;
+
+    use super::{
+        metrics_history::{self, MetricsHistory, UsageTarget},
+        resource_metrics::{self, MetricsReport, UsageSort},
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "command")]
+    pub enum MetricsCommand {
+        /// CPU cores and memory bytes per pod from metrics-server, with the
+        /// pods' requests and limits.
+        TopPods {
+            namespace: Option<String>,
+            label_selector: Option<String>,
+            #[serde(default)]
+            sort: UsageSort,
+            limit: Option<usize>,
+        },
+        TopNodes {
+            #[serde(default)]
+            sort: UsageSort,
+        },
+        /// Recent usage points of a pod or node, oldest first.
+        UsageHistory {
+            target: UsageTarget,
+            namespace: Option<String>,
+            name: String,
+        },
+    }
+
+    fn check_namespace(
+        filter: Option<&TenancyFilter>,
+        namespace: Option<&str>,
+    ) -> Result<(), KubiousError> {
+        let Some(filter) = filter else {
+            return Ok(());
+        };
+        let allowed = match namespace {
+            Some(namespace) => filter.allows_namespace(namespace),
+            None => filter.namespaces.is_empty(),
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(format!("Usage outside the tenancy filter {}", filter.name).into())
+        }
+    }
+
+    impl CommandHandler for MetricsCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            let state = handle.state::<AppState>();
+            let (Some(client), Some((cluster, _))) =
+                (state.client().await, state.get_current_config())
+            else {
+                return Err(KubiousError::no_client());
+            };
+            let filter = state.active_tenancy_filter(cluster.as_str());
+            let filter = filter.as_ref();
+            handle.state::<MetricsHistory>().touch();
+
+            match self {
+                MetricsCommand::TopPods {
+                    namespace,
+                    label_selector,
+                    sort,
+                    limit,
+                } => {
+                    check_namespace(filter, namespace.as_deref())?;
+                    let selector = match filter {
+                        Some(f) => f.label_selector(label_selector.as_deref()),
+                        None => label_selector.clone(),
+                    };
+                    let report = match resource_metrics::top_pods(
+                        client,
+                        namespace.as_deref(),
+                        selector.as_deref(),
+                        *sort,
+                    )
+                    .await
+                    {
+                        Ok((samples, mut usages)) => {
+                            metrics_history::record_pods(handle, cluster.as_str(), &samples);
+                            if let Some(limit) = limit {
+                                usages.truncate(*limit);
+                            }
+                            MetricsReport::of(usages)
+                        }
+                        Err(message) => MetricsReport::unavailable(message.to_string()),
+                    };
+                    self.wrap_in_value(Ok(report))
+                }
+                MetricsCommand::TopNodes { sort } => {
+                    let report = match resource_metrics::top_nodes(client, *sort).await {
+                        Ok((samples, usages)) => {
+                            metrics_history::record_nodes(handle, cluster.as_str(), &samples);
+                            MetricsReport::of(usages)
+                        }
+                        Err(message) => MetricsReport::unavailable(message.to_string()),
+                    };
+                    self.wrap_in_value(Ok(report))
+                }
+                MetricsCommand::UsageHistory {
+                    target,
+                    namespace,
+                    name,
+                } => {
+                    if *target == UsageTarget::Pod {
+                        let Some(namespace) = namespace else {
+                            return Err("A namespace is required for pod usage".into());
+                        };
+                        check_namespace(filter, Some(namespace.as_str()))?;
+                    }
+                    let key = metrics_history::series_key(
+                        cluster.as_str(),
+                        *target,
+                        namespace.as_deref(),
+                        name.as_str(),
+                    );
+                    self.wrap_in_value(Ok(handle.state::<MetricsHistory>().series(key.as_str())))
+                }
+            }
+        }
+    }
+}
+
+mod history;
+pub use history::metrics_history;
+mod usage;
+pub use usage::resource_metrics;
@@ -0,0 +1,329 @@
+pub mod resource_metrics {
+    use std::collections::HashMap;
+
+    use k8s_openapi::api::core::v1::{Node, Pod};
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams},
+        discovery::pinned_kind,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::util::{
+        kube_quantity::{parse_quantity, quantity_value},
+        kubious_error::{ErrorContext, KubiousError},
+        resource_accounting::{effective_limits, effective_requests},
+    };
+
+    /// Usage of one container in cores and bytes.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ContainerUsage {
+        pub name: String,
+        pub cpu: f64,
+        pub memory: f64,
+    }
+
+    /// A pod's usage as metrics-server reports it, before it is matched up
+    /// with the pod's requests.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PodSample {
+        pub namespace: String,
+        pub name: String,
+        pub cpu: f64,
+        pub memory: f64,
+        pub containers: Vec<ContainerUsage>,
+        /// When metrics-server scraped the sample.
+        pub timestamp: Option<String>,
+        /// The scrape window, e.g. `15s`.
+        pub window: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NodeSample {
+        pub name: String,
+        pub cpu: f64,
+        pub memory: f64,
+        pub timestamp: Option<String>,
+        pub window: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PodUsage {
+        #[serde(flatten)]
+        pub sample: PodSample,
+        pub node: Option<String>,
+        pub cpu_request: Option<f64>,
+        pub cpu_limit: Option<f64>,
+        pub memory_request: Option<f64>,
+        pub memory_limit: Option<f64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NodeUsage {
+        #[serde(flatten)]
+        pub sample: NodeSample,
+        pub cpu_allocatable: Option<f64>,
+        pub memory_allocatable: Option<f64>,
+        /// Usage as a percentage of allocatable.
+        pub cpu_percent: Option<f64>,
+        pub memory_percent: Option<f64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+    pub enum UsageSort {
+        #[default]
+        Cpu,
+        Memory,
+        Name,
+    }
+
+    /// Usage listing that degrades instead of failing: without a working
+    /// metrics API `available` is false, `items` is empty and `message`
+    /// says why.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct MetricsReport<T> {
+        pub available: bool,
+        pub message: Option<String>,
+        pub items: Vec<T>,
+    }
+
+    impl<T> MetricsReport<T> {
+        pub fn unavailable(message: String) -> Self {
+            MetricsReport {
+                available: false,
+                message: Some(message),
+                items: Vec::new(),
+            }
+        }
+
+        pub fn of(items: Vec<T>) -> Self {
+            MetricsReport {
+                available: true,
+                message: None,
+                items,
+            }
+        }
+    }
+
+    fn usage(value: &Value, key: &str) -> f64 {
+        value["usage"][key]
+            .as_str()
+            .and_then(parse_quantity)
+            .unwrap_or(0.0)
+    }
+
+    fn text(value: &Value) -> Option<String> {
+        value.as_str().map(|s| s.to_string())
+    }
+
+    /// Lists PodMetrics or NodeMetrics. Errors are phrased for display,
+    /// since they mostly mean metrics-server is missing or not ready.
+    async fn list_metrics(
+        client: Client,
+        kind: &str,
+        namespace: Option<&str>,
+        label_selector: Option<&str>,
+    ) -> Result<Vec<Value>, KubiousError> {
+        let gvk = GroupVersionKind::gvk("metrics.k8s.io", "v1beta1", kind);
+        let (resource, _) = pinned_kind(&client, &gvk)
+            .await
+            .context("metrics-server is not installed")?;
+        let api: Api<DynamicObject> = match namespace {
+            Some(namespace) => Api::namespaced_with(client, namespace, &resource),
+            None => Api::all_with(client, &resource),
+        };
+        let mut params = ListParams::default();
+        if let Some(selector) = label_selector {
+            params = params.labels(selector);
+        }
+        let list = api.list(&params).await.map_err(|e| match e {
+            kube::Error::Api(e) if e.code == 503 || e.code == 404 => {
+                format!("The metrics API is not serving yet: {}", e.message)
+            }
+            e => format!("Failed to read metrics: {}", e),
+        })?;
+        Ok(list
+            .items
+            .into_iter()
+            .filter_map(|m| serde_json::to_value(m).ok())
+            .collect())
+    }
+
+    pub async fn pod_samples(
+        client: Client,
+        namespace: Option<&str>,
+        label_selector: Option<&str>,
+    ) -> Result<Vec<PodSample>, KubiousError> {
+        Ok(
+            list_metrics(client, "PodMetrics", namespace, label_selector)
+                .await?
+                .into_iter()
+                .map(|m| {
+                    let containers: Vec<ContainerUsage> = m["containers"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|c| ContainerUsage {
+                            name: c["name"].as_str().unwrap_or_default().to_string(),
+                            cpu: usage(c, "cpu"),
+                            memory: usage(c, "memory"),
+                        })
+                        .collect();
+                    PodSample {
+                        namespace: m["metadata"]["namespace"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        name: m["metadata"]["name"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        cpu: containers.iter().map(|c| c.cpu).sum(),
+                        memory: containers.iter().map(|c| c.memory).sum(),
+                        containers,
+                        timestamp: text(&m["timestamp"]),
+                        window: text(&m["window"]),
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    pub async fn node_samples(client: Client) -> Result<Vec<NodeSample>, KubiousError> {
+        Ok(list_metrics(client, "NodeMetrics", None, None)
+            .await?
+            .into_iter()
+            .map(|m| NodeSample {
+                name: m["metadata"]["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                cpu: usage(&m, "cpu"),
+                memory: usage(&m, "memory"),
+                timestamp: text(&m["timestamp"]),
+                window: text(&m["window"]),
+            })
+            .collect())
+    }
+
+    fn sort_by<T>(items: &mut [T], sort: UsageSort, key: impl Fn(&T) -> (f64, f64, String)) {
+        items.sort_by(|a, b| {
+            let (a, b) = (key(a), key(b));
+            match sort {
+                UsageSort::Cpu => b.0.total_cmp(&a.0),
+                UsageSort::Memory => b.1.total_cmp(&a.1),
+                UsageSort::Name => a.2.cmp(&b.2),
+            }
+        });
+    }
+
+    /// Pod usage alongside requests and limits, like `kubectl top pods`.
+    pub async fn top_pods(
+        client: Client,
+        namespace: Option<&str>,
+        label_selector: Option<&str>,
+        sort: UsageSort,
+    ) -> Result<(Vec<PodSample>, Vec<PodUsage>), KubiousError> {
+        let samples = pod_samples(client.clone(), namespace, label_selector).await?;
+        let api: Api<Pod> = match namespace {
+            Some(namespace) => Api::namespaced(client, namespace),
+            None => Api::all(client),
+        };
+        let mut params = ListParams::default();
+        if let Some(selector) = label_selector {
+            params = params.labels(selector);
+        }
+        // Requests are a nicety; usage is still shown if pods cannot be read.
+        let pods: HashMap<(String, String), Pod> = api
+            .list(&params)
+            .await
+            .map(|list| list.items)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|pod| {
+                (
+                    (
+                        pod.metadata.namespace.clone().unwrap_or_default(),
+                        pod.metadata.name.clone().unwrap_or_default(),
+                    ),
+                    pod,
+                )
+            })
+            .collect();
+
+        let mut usages: Vec<PodUsage> = samples
+            .iter()
+            .map(|sample| {
+                let pod = pods.get(&(sample.namespace.clone(), sample.name.clone()));
+                let spec = pod.and_then(|p| p.spec.as_ref());
+                let requests = spec.map(effective_requests).unwrap_or_default();
+                let limits = spec.map(effective_limits).unwrap_or_default();
+                PodUsage {
+                    sample: sample.clone(),
+                    node: spec.and_then(|s| s.node_name.clone()),
+                    cpu_request: requests.get("cpu").copied(),
+                    cpu_limit: limits.get("cpu").copied(),
+                    memory_request: requests.get("memory").copied(),
+                    memory_limit: limits.get("memory").copied(),
+                }
+            })
+            .collect();
+        sort_by(&mut usages, sort, |u| {
+            (
+                u.sample.cpu,
+                u.sample.memory,
+                format!("{}/{}", u.sample.namespace, u.sample.name),
+            )
+        });
+        Ok((samples, usages))
+    }
+
+    /// Node usage against allocatable, like `kubectl top nodes`.
+    pub async fn top_nodes(
+        client: Client,
+        sort: UsageSort,
+    ) -> Result<(Vec<NodeSample>, Vec<NodeUsage>), KubiousError> {
+        let samples = node_samples(client.clone()).await?;
+        let allocatable: HashMap<String, (Option<f64>, Option<f64>)> = Api::<Node>::all(client)
+            .list(&ListParams::default())
+            .await
+            .map(|list| list.items)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|node| {
+                let allocatable = node.status.and_then(|s| s.allocatable).unwrap_or_default();
+                (
+                    node.metadata.name.unwrap_or_default(),
+                    (
+                        allocatable.get("cpu").map(quantity_value),
+                        allocatable.get("memory").map(quantity_value),
+                    ),
+                )
+            })
+            .collect();
+        let percent = |used: f64, total: Option<f64>| {
+            total
+                .filter(|total| *total > 0.0)
+                .map(|total| used / total * 100.0)
+        };
+
+        let mut usages: Vec<NodeUsage> = samples
+            .iter()
+            .map(|sample| {
+                let (cpu, memory) = allocatable.get(&sample.name).copied().unwrap_or_default();
+                NodeUsage {
+                    sample: sample.clone(),
+                    cpu_allocatable: cpu,
+                    memory_allocatable: memory,
+                    cpu_percent: percent(sample.cpu, cpu),
+                    memory_percent: percent(sample.memory, memory),
+                }
+            })
+            .collect();
+        sort_by(&mut usages, sort, |u| {
+            (u.sample.cpu, u.sample.memory, u.sample.name.clone())
+        });
+        Ok((samples, usages))
+    }
+}
@@ -3,6 +3,15 @@ pub use common::kubious_api::{ApiCommand, execute_command, CommandHandler, Comma
 mod application;
 pub use application::application_api;
 pub use application::app_state;
+pub use application::app_settings;
+pub use application::state_migrations;
+pub use application::app_credentials;
+pub use application::app_support;
+pub use application::app_terminal;
+pub use application::app_requests;
+pub use application::app_command_cache;
+pub use application::session_limits;
+pub use application::token_expiry;
 
 mod artifacts;
 pub use artifacts::artifacts_api;
@@ -14,4 +23,45 @@ mod kompose;
 pub use kompose::kompose_api;
 
 mod kube;
-pub use kube::kube_api;
\ No newline at end of file
+pub use kube::kube_api;
+pub use kube::termination_history;
+pub use kube::rbac_capabilities;
+pub use kube::multi_cluster_logs;
+pub use kube::port_forwards;
+pub use kube::favorite_watches;
+pub use kube::rollout_triggers;
+pub use kube::namespace_budgets;
+pub use kube::scratch_namespaces;
+
+mod resources;
+pub use resources::resources_api;
+pub use resources::resource_deletion;
+
+mod logs;
+pub use logs::logs_api;
+pub use logs::log_sessions;
+
+mod exec;
+pub use exec::exec_api;
+pub use exec::exec_sessions;
+
+mod events;
+pub use events::events_api;
+pub use events::event_feeds;
+
+mod views;
+pub use views::views_api;
+pub use views::view_store;
+pub use views::resource_informers;
+
+mod dynamic;
+pub use dynamic::dynamic_api;
+pub use dynamic::discovery_cache;
+
+mod journal;
+pub use journal::journal_api;
+pub use journal::incident_journal;
+
+mod metrics;
+pub use metrics::metrics_api;
+pub use metrics::metrics_history;
\ No newline at end of file
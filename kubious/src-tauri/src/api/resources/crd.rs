@@ -0,0 +1,186 @@
+pub mod crd_deletion {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+    use kube::{
+        api::{DeleteParams, DynamicObject, ListParams, TypeMeta},
+        discovery::ApiResource,
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::{
+            artifacts::{
+                artifact_bundle,
+                artifact_export::{self, YamlExport},
+            },
+            views::resource_access::ResourceRef,
+        },
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    /// Returned when a generic delete targets a CRD, which would silently
+    /// take every instance with it.
+    pub const CRD_DELETE_REFUSED: &str =
+        "CustomResourceDefinitions are deleted with DeleteCrd after a preflight";
+
+    /// Instances listed by name in a preflight; the rest are only counted.
+    const MAX_LISTED: usize = 200;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CrdDeletePreflight {
+        pub crd: String,
+        pub group: String,
+        pub kind: String,
+        pub namespaced: bool,
+        pub versions: Vec<String>,
+        /// Every instance, all of which are deleted with the CRD. Pass this
+        /// count as the acknowledgement to delete.
+        pub instances: usize,
+        /// Instances per namespace; cluster-scoped instances are under "".
+        pub namespaces: BTreeMap<String, usize>,
+        /// Up to 200 instances.
+        pub listed: Vec<ResourceRef>,
+        /// Set when the instances were exported.
+        pub export: Option<YamlExport>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct CrdDeleteResult {
+        pub crd: String,
+        pub instances_deleted: usize,
+    }
+
+    pub fn is_crd(target: &ResourceRef) -> bool {
+        target.kind == "CustomResourceDefinition" && target.group == "apiextensions.k8s.io"
+    }
+
+    async fn definition(
+        client: &Client,
+        name: &str,
+    ) -> Result<CustomResourceDefinition, KubiousError> {
+        Api::<CustomResourceDefinition>::all(client.clone())
+            .get(name)
+            .await
+            .context(format!("Failed to get CRD {}", name))
+    }
+
+    /// The API of the CRD's storage version, through which every instance
+    /// can be listed.
+    fn instance_resource(crd: &CustomResourceDefinition) -> Result<ApiResource, KubiousError> {
+        let spec = &crd.spec;
+        let version = spec
+            .versions
+            .iter()
+            .find(|v| v.storage)
+            .or(spec.versions.iter().find(|v| v.served))
+            .ok_or("CRD serves no version".to_string())?;
+        Ok(ApiResource {
+            group: spec.group.clone(),
+            version: version.name.clone(),
+            api_version: format!("{}/{}", spec.group, version.name),
+            kind: spec.names.kind.clone(),
+            plural: spec.names.plural.clone(),
+        })
+    }
+
+    async fn instances(
+        client: &Client,
+        resource: &ApiResource,
+    ) -> Result<Vec<DynamicObject>, KubiousError> {
+        Api::<DynamicObject>::all_with(client.clone(), resource)
+            .list(&ListParams::default())
+            .await
+            .map(|list| list.items)
+            .context(format!("Failed to list {} instances", resource.kind))
+    }
+
+    /// Counts the instances that deleting the CRD would destroy, exporting
+    /// them as YAML to `export_path` when given.
+    pub async fn preflight(
+        client: Client,
+        name: String,
+        export_path: Option<String>,
+    ) -> Result<CrdDeletePreflight, KubiousError> {
+        let crd = definition(&client, name.as_str()).await?;
+        let resource = instance_resource(&crd)?;
+        let objects = instances(&client, &resource).await?;
+
+        let mut namespaces: BTreeMap<String, usize> = BTreeMap::new();
+        for object in objects.iter() {
+            *namespaces
+                .entry(object.metadata.namespace.clone().unwrap_or_default())
+                .or_default() += 1;
+        }
+        let listed = objects
+            .iter()
+            .take(MAX_LISTED)
+            .map(|object| ResourceRef {
+                group: resource.group.clone(),
+                version: resource.version.clone(),
+                kind: resource.kind.clone(),
+                namespace: object.metadata.namespace.clone(),
+                name: object.metadata.name.clone().unwrap_or_default(),
+            })
+            .collect();
+        let export = match export_path {
+            Some(path) if !objects.is_empty() => {
+                let manifests: Vec<Value> = objects
+                    .iter()
+                    .cloned()
+                    .filter_map(|mut object| {
+                        object.types = Some(TypeMeta {
+                            api_version: resource.api_version.clone(),
+                            kind: resource.kind.clone(),
+                        });
+                        let mut manifest = serde_json::to_value(object).ok()?;
+                        artifact_bundle::clean_manifest(&mut manifest);
+                        Some(manifest)
+                    })
+                    .collect();
+                Some(artifact_export::to_export(&manifests, Some(path))?)
+            }
+            _ => None,
+        };
+        Ok(CrdDeletePreflight {
+            crd: name,
+            group: resource.group,
+            kind: resource.kind,
+            namespaced: crd.spec.scope == "Namespaced",
+            versions: crd.spec.versions.iter().map(|v| v.name.clone()).collect(),
+            instances: objects.len(),
+            namespaces,
+            listed,
+            export,
+        })
+    }
+
+    /// Deletes a CRD and, with it, every instance. Refused while instances
+    /// exist unless `acknowledged_instances` matches their current count, so
+    /// the caller confirms the exact cascade shown by a preflight.
+    pub async fn delete(
+        client: Client,
+        name: String,
+        acknowledged_instances: Option<usize>,
+    ) -> Result<CrdDeleteResult, KubiousError> {
+        let crd = definition(&client, name.as_str()).await?;
+        let count = instances(&client, &instance_resource(&crd)?).await?.len();
+        if count > 0 && acknowledged_instances != Some(count) {
+            return Err(format!(
+                "Deleting {} destroys its {} instances; acknowledge {} instances to proceed",
+                name, count, count
+            )
+            .into());
+        }
+        Api::<CustomResourceDefinition>::all(client)
+            .delete(name.as_str(), &DeleteParams::default())
+            .await
+            .context(format!("Failed to delete CRD {}", name))?;
+        Ok(CrdDeleteResult {
+            crd: name,
+            instances_deleted: count,
+        })
+    }
+}
@@ -0,0 +1,190 @@
+pub mod resource_deletion {
+    use std::{
+        collections::HashMap,
+        sync::{Mutex, MutexGuard},
+        time::{Duration, Instant},
+    };
+
+    use kube::{
+        api::{DeleteParams, PropagationPolicy},
+        core::Status,
+        Client,
+    };
+    use rand::RngCore;
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        api::{
+            resources::crd_deletion::{self, CRD_DELETE_REFUSED},
+            views::resource_access::{self, ResourceRef},
+        },
+        util::{
+            kubious_error::{ErrorContext, KubiousError},
+            lock_recovery::recover,
+        },
+    };
+
+    /// How long a confirmation token from a dry run stays valid.
+    const CONFIRMATION_TTL: Duration = Duration::from_secs(300);
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub enum Propagation {
+        /// Dependents are deleted before the object itself is removed.
+        Foreground,
+        /// The object is removed at once and dependents are collected after.
+        Background,
+        /// Dependents are kept and lose their owner reference.
+        Orphan,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DeleteOptions {
+        pub propagation: Option<Propagation>,
+        pub grace_period_seconds: Option<u32>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DeleteOutcome {
+        pub target: ResourceRef,
+        pub success: bool,
+        /// The object still exists, waiting on finalizers or, with
+        /// foreground propagation, on its dependents.
+        pub pending: bool,
+        /// The server's final `Status`, when it returned one rather than the
+        /// object being deleted.
+        pub status: Option<Status>,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DeleteResult {
+        pub dry_run: bool,
+        /// Returned by dry runs. Passing it back deletes exactly the same
+        /// objects with the same options, once, within five minutes.
+        pub confirmation: Option<String>,
+        pub outcomes: Vec<DeleteOutcome>,
+    }
+
+    /// Confirmation tokens mapped to the request they confirm and when they
+    /// were issued.
+    #[derive(Default)]
+    pub struct DeleteConfirmations {
+        confirmations: Mutex<HashMap<String, (String, Instant)>>,
+    }
+
+    impl DeleteConfirmations {
+        fn confirmations_mutable(&self) -> MutexGuard<'_, HashMap<String, (String, Instant)>> {
+            recover(&self.confirmations)
+        }
+
+        fn issue(&self, fingerprint: String) -> String {
+            let mut bytes = [0u8; 16];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            let mut confirmations = self.confirmations_mutable();
+            confirmations.retain(|_, (_, issued)| issued.elapsed() < CONFIRMATION_TTL);
+            confirmations.insert(token.clone(), (fingerprint, Instant::now()));
+            token
+        }
+
+        /// Consumes a token, returning whether it was issued for this request.
+        fn redeem(&self, token: &str, fingerprint: &str) -> bool {
+            self.confirmations_mutable()
+                .remove(token)
+                .is_some_and(|(expected, issued)| {
+                    expected == fingerprint && issued.elapsed() < CONFIRMATION_TTL
+                })
+        }
+    }
+
+    /// Identifies a deletion request, so a token only confirms the targets
+    /// and options it was issued for.
+    fn fingerprint(cluster: &str, targets: &[ResourceRef], options: &DeleteOptions) -> String {
+        let serialized = serde_json::to_string(&(cluster, targets, options)).unwrap_or_default();
+        format!("{:x}", Sha256::digest(serialized.as_bytes()))
+    }
+
+    async fn delete_one(
+        client: Client,
+        target: ResourceRef,
+        params: &DeleteParams,
+    ) -> DeleteOutcome {
+        let result = match resource_access::object_api(client, &target).await {
+            Ok((api, _)) => api
+                .delete(target.name.as_str(), params)
+                .await
+                .context(format!("Failed to delete {} {}", target.kind, target.name)),
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(deleted) => DeleteOutcome {
+                success: true,
+                pending: deleted.is_left(),
+                status: deleted.right(),
+                error: None,
+                target,
+            },
+            Err(error) => DeleteOutcome {
+                success: false,
+                pending: false,
+                status: None,
+                error: Some(error.to_string()),
+                target,
+            },
+        }
+    }
+
+    /// Deletes objects one at a time, continuing past failures. Dry runs are
+    /// sent to the server with `dryRun=All` and return a confirmation token;
+    /// a real deletion requires the token of a matching dry run.
+    pub async fn delete(
+        confirmations: &DeleteConfirmations,
+        client: Client,
+        cluster: &str,
+        targets: Vec<ResourceRef>,
+        options: DeleteOptions,
+        dry_run: bool,
+        confirmation: Option<String>,
+    ) -> Result<DeleteResult, KubiousError> {
+        if targets.is_empty() {
+            return Err("Nothing to delete".into());
+        }
+        if targets.iter().any(crd_deletion::is_crd) {
+            return Err(CRD_DELETE_REFUSED.to_string().into());
+        }
+        let fingerprint = fingerprint(cluster, &targets, &options);
+        if !dry_run {
+            let confirmed = confirmation
+                .as_deref()
+                .is_some_and(|token| confirmations.redeem(token, fingerprint.as_str()));
+            if !confirmed {
+                return Err(
+                    ("Deletion needs the confirmation token of a dry run of the same request"
+                        .to_string())
+                    .into(),
+                );
+            }
+        }
+
+        let params = DeleteParams {
+            dry_run,
+            grace_period_seconds: options.grace_period_seconds,
+            propagation_policy: options.propagation.as_ref().map(|p| match p {
+                Propagation::Foreground => PropagationPolicy::Foreground,
+                Propagation::Background => PropagationPolicy::Background,
+                Propagation::Orphan => PropagationPolicy::Orphan,
+            }),
+            preconditions: None,
+        };
+        let mut outcomes: Vec<DeleteOutcome> = Vec::new();
+        for target in targets {
+            outcomes.push(delete_one(client.clone(), target, &params).await);
+        }
+        Ok(DeleteResult {
+            dry_run,
+            confirmation: dry_run.then(|| confirmations.issue(fingerprint)),
+            outcomes,
+        })
+    }
+}
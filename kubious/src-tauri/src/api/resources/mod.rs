@@ -0,0 +1,274 @@
+pub mod resources_api {
+    use std::fmt::Debug;
+
+    use crate::{
+        api::{
+            app_settings::TenancyFilter, app_state::AppState, views::resource_access::ResourceRef,
+        },
+        util::kubious_error::{ErrorContext, KubiousError},
+        CommandHandler,
+    };
+    use k8s_openapi::{
+        api::{
+            apps::v1::{DaemonSet, Deployment, StatefulSet},
+            core::v1::{Namespace, Node, Pod, Service},
+        },
+        NamespaceResourceScope,
+    };
+    use kube::{api::ListParams, Api, Client, Resource};
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::Manager;
+
+    use super::{
+        crd_deletion,
+        resource_deletion::{self, DeleteConfirmations, DeleteOptions, Propagation},
+        resource_summaries::{
+            NamespaceSummary, NodeSummary, PodSummary, ServiceSummary, WorkloadSummary,
+        },
+    };
+
+    /// Lists a namespaced kind, narrowed to the active tenancy filter. Without
+    /// a namespace, a filter with namespaces is listed one namespace at a
+    /// time so tenants without cluster-wide list permission still get results.
+    async fn list_namespaced<K>(
+        client: Client,
+        namespace: Option<&str>,
+        filter: Option<&TenancyFilter>,
+    ) -> Result<Vec<K>, KubiousError>
+    where
+        K: Resource<Scope = NamespaceResourceScope> + Clone + DeserializeOwned + Debug,
+        K::DynamicType: Default,
+    {
+        let kind = K::kind(&K::DynamicType::default()).to_string();
+        let namespaces: Vec<Option<String>> = match (filter, namespace) {
+            (Some(f), _) if !f.allows_kind(kind.as_str()) => {
+                return Err(format!("{} is outside the tenancy filter {}", kind, f.name).into())
+            }
+            (Some(f), Some(ns)) if !f.allows_namespace(ns) => {
+                return Err(
+                    format!("Namespace {} is outside the tenancy filter {}", ns, f.name).into(),
+                )
+            }
+            (_, Some(ns)) => vec![Some(ns.to_string())],
+            (Some(f), None) if !f.namespaces.is_empty() => {
+                f.namespaces.iter().cloned().map(Some).collect()
+            }
+            (_, None) => vec![None],
+        };
+        let mut params = ListParams::default();
+        if let Some(selector) = filter.and_then(|f| f.label_selector(None)) {
+            params = params.labels(selector.as_str());
+        }
+
+        let mut items = Vec::new();
+        for ns in namespaces {
+            let api: Api<K> = match ns {
+                Some(ns) => Api::namespaced(client.clone(), ns.as_str()),
+                None => Api::all(client.clone()),
+            };
+            let list = api
+                .list(&params)
+                .await
+                .context(format!("Failed to list {}", kind))?;
+            items.extend(list.items);
+        }
+        Ok(items)
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "command")]
+    #[allow(clippy::enum_variant_names)]
+    pub enum ResourcesCommand {
+        ListNamespaces {},
+        ListNodes {},
+        ListPods {
+            namespace: Option<String>,
+        },
+        ListDeployments {
+            namespace: Option<String>,
+        },
+        ListStatefulSets {
+            namespace: Option<String>,
+        },
+        ListDaemonSets {
+            namespace: Option<String>,
+        },
+        ListServices {
+            namespace: Option<String>,
+        },
+        /// Deletes one or more objects. Run with `dry_run` first; the
+        /// returned `confirmation` token is required to delete for real.
+        Delete {
+            targets: Vec<ResourceRef>,
+            propagation: Option<Propagation>,
+            grace_period_seconds: Option<u32>,
+            #[serde(default)]
+            dry_run: bool,
+            confirmation: Option<String>,
+        },
+        /// Counts the instances deleting a CRD would destroy, optionally
+        /// exporting them as YAML first.
+        CrdDeletePreflight {
+            crd: String,
+            export_path: Option<String>,
+        },
+        /// Deletes a CRD; `acknowledge_instances` must match the live
+        /// instance count whenever instances exist.
+        DeleteCrd {
+            crd: String,
+            acknowledge_instances: Option<usize>,
+        },
+    }
+
+    impl CommandHandler for ResourcesCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            let state = handle.state::<AppState>();
+            let (Some(client), Some((cluster, _))) =
+                (state.client().await, state.get_current_config())
+            else {
+                return Err(KubiousError::no_client());
+            };
+            let filter = state.active_tenancy_filter(cluster.as_str());
+            let filter = filter.as_ref();
+
+            match self {
+                ResourcesCommand::ListNamespaces {} => {
+                    let api: Api<Namespace> = Api::all(client);
+                    let namespaces = api
+                        .list(&ListParams::default())
+                        .await
+                        .context("Failed to list Namespace");
+                    self.wrap_in_value(namespaces.map(|list| {
+                        list.items
+                            .into_iter()
+                            .map(NamespaceSummary::from)
+                            .filter(|ns| filter.is_none_or(|f| f.allows_namespace(&ns.name)))
+                            .collect::<Vec<_>>()
+                    }))
+                }
+                ResourcesCommand::ListNodes {} => {
+                    let api: Api<Node> = Api::all(client);
+                    let nodes = api
+                        .list(&ListParams::default())
+                        .await
+                        .context("Failed to list Node");
+                    self.wrap_in_value(nodes.map(|list| {
+                        list.items
+                            .into_iter()
+                            .map(NodeSummary::from)
+                            .collect::<Vec<_>>()
+                    }))
+                }
+                ResourcesCommand::ListPods { namespace } => {
+                    let pods = list_namespaced::<Pod>(client, namespace.as_deref(), filter).await;
+                    self.wrap_in_value(
+                        pods.map(|items| {
+                            items.into_iter().map(PodSummary::from).collect::<Vec<_>>()
+                        }),
+                    )
+                }
+                ResourcesCommand::ListDeployments { namespace } => {
+                    let deployments =
+                        list_namespaced::<Deployment>(client, namespace.as_deref(), filter).await;
+                    self.wrap_in_value(deployments.map(|items| {
+                        items
+                            .into_iter()
+                            .map(WorkloadSummary::from)
+                            .collect::<Vec<_>>()
+                    }))
+                }
+                ResourcesCommand::ListStatefulSets { namespace } => {
+                    let sets =
+                        list_namespaced::<StatefulSet>(client, namespace.as_deref(), filter).await;
+                    self.wrap_in_value(sets.map(|items| {
+                        items
+                            .into_iter()
+                            .map(WorkloadSummary::from)
+                            .collect::<Vec<_>>()
+                    }))
+                }
+                ResourcesCommand::ListDaemonSets { namespace } => {
+                    let sets =
+                        list_namespaced::<DaemonSet>(client, namespace.as_deref(), filter).await;
+                    self.wrap_in_value(sets.map(|items| {
+                        items
+                            .into_iter()
+                            .map(WorkloadSummary::from)
+                            .collect::<Vec<_>>()
+                    }))
+                }
+                ResourcesCommand::Delete {
+                    targets,
+                    propagation,
+                    grace_period_seconds,
+                    dry_run,
+                    confirmation,
+                } => {
+                    if let Some(f) = filter {
+                        let outside = targets.iter().find(|t| {
+                            !f.allows_kind(t.kind.as_str())
+                                || t.namespace
+                                    .as_ref()
+                                    .is_some_and(|ns| !f.allows_namespace(ns))
+                        });
+                        if let Some(target) = outside {
+                            return Err(format!(
+                                "{} {} is outside the tenancy filter {}",
+                                target.kind, target.name, f.name
+                            )
+                            .into());
+                        }
+                    }
+                    let options = DeleteOptions {
+                        propagation: propagation.clone(),
+                        grace_period_seconds: *grace_period_seconds,
+                    };
+                    self.wrap_in_value(
+                        resource_deletion::delete(
+                            &handle.state::<DeleteConfirmations>(),
+                            client,
+                            cluster.as_str(),
+                            targets.clone(),
+                            options,
+                            *dry_run,
+                            confirmation.clone(),
+                        )
+                        .await,
+                    )
+                }
+                ResourcesCommand::CrdDeletePreflight { crd, export_path } => self.wrap_in_value(
+                    crd_deletion::preflight(client, crd.clone(), export_path.clone()).await,
+                ),
+                ResourcesCommand::DeleteCrd {
+                    crd,
+                    acknowledge_instances,
+                } => {
+                    if filter.is_some_and(|f| !f.allows_kind("CustomResourceDefinition")) {
+                        return Err(format!("CRD {} is outside the tenancy filter", crd).into());
+                    }
+                    self.wrap_in_value(
+                        crd_deletion::delete(client, crd.clone(), *acknowledge_instances).await,
+                    )
+                }
+                ResourcesCommand::ListServices { namespace } => {
+                    let services =
+                        list_namespaced::<Service>(client, namespace.as_deref(), filter).await;
+                    self.wrap_in_value(services.map(|items| {
+                        items
+                            .into_iter()
+                            .map(ServiceSummary::from)
+                            .collect::<Vec<_>>()
+                    }))
+                }
+            }
+        }
+    }
+}
+
+mod summary;
+pub use summary::resource_summaries;
+mod delete;
+pub use delete::resource_deletion;
+mod crd;
+pub use crd::crd_deletion;
@@ -0,0 +1,251 @@
+pub mod resource_summaries {
+    use k8s_openapi::{
+        api::{
+            apps::v1::{DaemonSet, Deployment, StatefulSet},
+            core::v1::{Namespace, Node, Pod, PodSpec, Service},
+        },
+        apimachinery::pkg::apis::meta::v1::{ObjectMeta, Time},
+    };
+    use serde::{Deserialize, Serialize};
+
+    fn created(metadata: &ObjectMeta) -> Option<String> {
+        metadata
+            .creation_timestamp
+            .as_ref()
+            .map(|Time(t)| t.to_rfc3339())
+    }
+
+    fn images(spec: Option<&PodSpec>) -> Vec<String> {
+        spec.map(|s| {
+            s.containers
+                .iter()
+                .filter_map(|c| c.image.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PodSummary {
+        pub name: String,
+        pub namespace: String,
+        pub phase: Option<String>,
+        pub ready: usize,
+        pub containers: usize,
+        pub restarts: i32,
+        pub node: Option<String>,
+        pub ip: Option<String>,
+        pub created: Option<String>,
+    }
+
+    impl From<Pod> for PodSummary {
+        fn from(pod: Pod) -> Self {
+            let statuses = pod
+                .status
+                .as_ref()
+                .and_then(|s| s.container_statuses.clone())
+                .unwrap_or_default();
+            PodSummary {
+                name: pod.metadata.name.clone().unwrap_or_default(),
+                namespace: pod.metadata.namespace.clone().unwrap_or_default(),
+                phase: pod.status.as_ref().and_then(|s| s.phase.clone()),
+                ready: statuses.iter().filter(|s| s.ready).count(),
+                containers: pod.spec.as_ref().map_or(0, |s| s.containers.len()),
+                restarts: statuses.iter().map(|s| s.restart_count).sum(),
+                node: pod.spec.as_ref().and_then(|s| s.node_name.clone()),
+                ip: pod.status.as_ref().and_then(|s| s.pod_ip.clone()),
+                created: created(&pod.metadata),
+            }
+        }
+    }
+
+    /// Shared shape for Deployments, StatefulSets and DaemonSets. For
+    /// DaemonSets `desired` is the number of nodes that should run a pod.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct WorkloadSummary {
+        pub kind: String,
+        pub name: String,
+        pub namespace: String,
+        pub desired: i32,
+        pub ready: i32,
+        pub updated: i32,
+        pub available: i32,
+        pub images: Vec<String>,
+        pub created: Option<String>,
+    }
+
+    impl From<Deployment> for WorkloadSummary {
+        fn from(deployment: Deployment) -> Self {
+            let status = deployment.status.unwrap_or_default();
+            let spec = deployment.spec.unwrap_or_default();
+            WorkloadSummary {
+                kind: "Deployment".to_string(),
+                name: deployment.metadata.name.clone().unwrap_or_default(),
+                namespace: deployment.metadata.namespace.clone().unwrap_or_default(),
+                desired: spec.replicas.unwrap_or(1),
+                ready: status.ready_replicas.unwrap_or(0),
+                updated: status.updated_replicas.unwrap_or(0),
+                available: status.available_replicas.unwrap_or(0),
+                images: images(spec.template.spec.as_ref()),
+                created: created(&deployment.metadata),
+            }
+        }
+    }
+
+    impl From<StatefulSet> for WorkloadSummary {
+        fn from(set: StatefulSet) -> Self {
+            let status = set.status.unwrap_or_default();
+            let spec = set.spec.unwrap_or_default();
+            WorkloadSummary {
+                kind: "StatefulSet".to_string(),
+                name: set.metadata.name.clone().unwrap_or_default(),
+                namespace: set.metadata.namespace.clone().unwrap_or_default(),
+                desired: spec.replicas.unwrap_or(1),
+                ready: status.ready_replicas.unwrap_or(0),
+                updated: status.updated_replicas.unwrap_or(0),
+                available: status.available_replicas.unwrap_or(0),
+                images: images(spec.template.spec.as_ref()),
+                created: created(&set.metadata),
+            }
+        }
+    }
+
+    impl From<DaemonSet> for WorkloadSummary {
+        fn from(set: DaemonSet) -> Self {
+            let status = set.status.unwrap_or_default();
+            let spec = set.spec.unwrap_or_default();
+            WorkloadSummary {
+                kind: "DaemonSet".to_string(),
+                name: set.metadata.name.clone().unwrap_or_default(),
+                namespace: set.metadata.namespace.clone().unwrap_or_default(),
+                desired: status.desired_number_scheduled,
+                ready: status.number_ready,
+                updated: status.updated_number_scheduled.unwrap_or(0),
+                available: status.number_available.unwrap_or(0),
+                images: images(spec.template.spec.as_ref()),
+                created: created(&set.metadata),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ServiceSummary {
+        pub name: String,
+        pub namespace: String,
+        pub service_type: Option<String>,
+        pub cluster_ip: Option<String>,
+        pub external_ips: Vec<String>,
+        /// Formatted like kubectl, e.g. `80:30080/TCP`.
+        pub ports: Vec<String>,
+        pub created: Option<String>,
+    }
+
+    impl From<Service> for ServiceSummary {
+        fn from(service: Service) -> Self {
+            let spec = service.spec.clone().unwrap_or_default();
+            let ingress = service
+                .status
+                .as_ref()
+                .and_then(|s| s.load_balancer.as_ref())
+                .and_then(|lb| lb.ingress.clone())
+                .unwrap_or_default();
+            let external_ips = spec
+                .external_ips
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .chain(ingress.into_iter().filter_map(|i| i.ip.or(i.hostname)))
+                .collect();
+            let ports = spec
+                .ports
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|p| {
+                    let protocol = p.protocol.unwrap_or("TCP".to_string());
+                    match p.node_port {
+                        Some(node_port) => format!("{}:{}/{}", p.port, node_port, protocol),
+                        None => format!("{}/{}", p.port, protocol),
+                    }
+                })
+                .collect();
+            ServiceSummary {
+                name: service.metadata.name.clone().unwrap_or_default(),
+                namespace: service.metadata.namespace.clone().unwrap_or_default(),
+                service_type: spec.type_,
+                cluster_ip: spec.cluster_ip,
+                external_ips,
+                ports,
+                created: created(&service.metadata),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NamespaceSummary {
+        pub name: String,
+        pub phase: Option<String>,
+        pub created: Option<String>,
+    }
+
+    impl From<Namespace> for NamespaceSummary {
+        fn from(namespace: Namespace) -> Self {
+            NamespaceSummary {
+                name: namespace.metadata.name.clone().unwrap_or_default(),
+                phase: namespace.status.and_then(|s| s.phase),
+                created: created(&namespace.metadata),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NodeSummary {
+        pub name: String,
+        pub ready: bool,
+        pub roles: Vec<String>,
+        pub version: Option<String>,
+        pub internal_ip: Option<String>,
+        pub unschedulable: bool,
+        pub created: Option<String>,
+    }
+
+    impl From<Node> for NodeSummary {
+        fn from(node: Node) -> Self {
+            let status = node.status.clone().unwrap_or_default();
+            let roles = node
+                .metadata
+                .labels
+                .as_ref()
+                .map(|labels| {
+                    labels
+                        .keys()
+                        .filter_map(|k| k.strip_prefix("node-role.kubernetes.io/"))
+                        .map(|r| r.to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            NodeSummary {
+                name: node.metadata.name.clone().unwrap_or_default(),
+                ready: status
+                    .conditions
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|c| c.type_ == "Ready" && c.status == "True"),
+                roles,
+                version: status.node_info.map(|i| i.kubelet_version),
+                internal_ip: status
+                    .addresses
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|a| a.type_ == "InternalIP")
+                    .map(|a| a.address),
+                unschedulable: node
+                    .spec
+                    .as_ref()
+                    .and_then(|s| s.unschedulable)
+                    .unwrap_or(false),
+                created: created(&node.metadata),
+            }
+        }
+    }
+}
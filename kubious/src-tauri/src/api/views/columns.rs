@@ -0,0 +1,75 @@
+pub mod printer_columns {
+    use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+    use kube::{discovery::ApiResource, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::util::value_path::evaluate_jsonpath;
+
+    /// A column declared in a CRD's `additionalPrinterColumns`.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct PrinterColumn {
+        pub name: String,
+        pub json_path: String,
+        /// `integer`, `number`, `string`, `boolean` or `date`.
+        pub column_type: String,
+        pub format: Option<String>,
+        pub description: Option<String>,
+        /// Columns above 0 are only shown in wide output.
+        pub priority: i32,
+    }
+
+    /// The printer columns of a custom resource's version; empty for
+    /// built-in kinds and CRDs that declare none.
+    pub async fn for_kind(client: Client, resource: &ApiResource) -> Vec<PrinterColumn> {
+        if resource.group.is_empty() {
+            return Vec::new();
+        }
+        let name = format!("{}.{}", resource.plural, resource.group);
+        let Ok(crd) = Api::<CustomResourceDefinition>::all(client)
+            .get(name.as_str())
+            .await
+        else {
+            return Vec::new();
+        };
+        crd.spec
+            .versions
+            .into_iter()
+            .find(|v| v.name == resource.version)
+            .and_then(|v| v.additional_printer_columns)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|column| PrinterColumn {
+                name: column.name,
+                json_path: column.json_path,
+                column_type: column.type_,
+                format: column.format,
+                description: column.description,
+                priority: column.priority.unwrap_or(0),
+            })
+            .collect()
+    }
+
+    /// Evaluates one column against an object. Several matches are joined
+    /// with commas as `kubectl get` prints them; no match gives `null`.
+    pub fn cell(column: &PrinterColumn, item: &Value) -> Value {
+        let matches = evaluate_jsonpath(item, column.json_path.as_str()).unwrap_or_default();
+        match matches.as_slice() {
+            [] => Value::Null,
+            [single] => single.clone(),
+            many => Value::String(
+                many.iter()
+                    .map(|v| match v {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+        }
+    }
+
+    pub fn cells(columns: &[PrinterColumn], item: &Value) -> Vec<Value> {
+        columns.iter().map(|column| cell(column, item)).collect()
+    }
+}
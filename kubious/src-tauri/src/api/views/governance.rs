@@ -0,0 +1,208 @@
+pub mod resource_governance {
+    use kube::{
+        api::{ListParams, Patch, PatchParams},
+        Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::{
+        api::views::resource_access::{self, ResourceRef},
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    /// Team responsible for the object.
+    pub const OWNER_ANNOTATION: &str = "kubious/owner-team";
+    /// Where to look when the object misbehaves.
+    pub const RUNBOOK_ANNOTATION: &str = "kubious/runbook";
+    pub const NOTES_ANNOTATION: &str = "kubious/notes";
+
+    /// The app-recognized annotations of an object. Unset fields are removed
+    /// from the object when written.
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    pub struct ResourceMetadata {
+        pub owner: Option<String>,
+        pub runbook: Option<String>,
+        pub notes: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct GovernedKind {
+        pub group: String,
+        pub version: String,
+        pub kind: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct MissingOwnership {
+        pub resource: ResourceRef,
+        /// `owner` and/or `runbook`.
+        pub missing: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct OwnershipReport {
+        pub checked: usize,
+        pub missing: Vec<MissingOwnership>,
+        /// Kinds that could not be listed, with the reason.
+        pub errors: Vec<String>,
+    }
+
+    /// Checked when no kinds are given: the objects people create directly.
+    fn default_kinds() -> Vec<GovernedKind> {
+        [
+            ("apps", "v1", "Deployment"),
+            ("apps", "v1", "StatefulSet"),
+            ("apps", "v1", "DaemonSet"),
+            ("batch", "v1", "CronJob"),
+            ("", "v1", "Service"),
+        ]
+        .into_iter()
+        .map(|(group, version, kind)| GovernedKind {
+            group: group.to_string(),
+            version: version.to_string(),
+            kind: kind.to_string(),
+        })
+        .collect()
+    }
+
+    pub fn read(object: &Value) -> ResourceMetadata {
+        let annotation = |key: &str| {
+            object["metadata"]["annotations"][key]
+                .as_str()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string())
+        };
+        ResourceMetadata {
+            owner: annotation(OWNER_ANNOTATION),
+            runbook: annotation(RUNBOOK_ANNOTATION),
+            notes: annotation(NOTES_ANNOTATION),
+        }
+    }
+
+    pub async fn get(
+        client: Client,
+        target: &ResourceRef,
+    ) -> Result<ResourceMetadata, KubiousError> {
+        Ok(read(&resource_access::get(client, target).await?))
+    }
+
+    /// Writes the annotations with a merge patch, leaving all others alone.
+    pub async fn set(
+        client: Client,
+        target: &ResourceRef,
+        metadata: ResourceMetadata,
+    ) -> Result<ResourceMetadata, KubiousError> {
+        let clean = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let metadata = ResourceMetadata {
+            owner: clean(metadata.owner),
+            runbook: clean(metadata.runbook),
+            notes: clean(metadata.notes),
+        };
+        if metadata
+            .runbook
+            .as_ref()
+            .is_some_and(|r| !r.starts_with("https://") && !r.starts_with("http://"))
+        {
+            return Err("Runbook must be an http(s) URL".into());
+        }
+        let patch = json!({
+            "metadata": {
+                "annotations": {
+                    OWNER_ANNOTATION: metadata.owner,
+                    RUNBOOK_ANNOTATION: metadata.runbook,
+                    NOTES_ANNOTATION: metadata.notes,
+                }
+            }
+        });
+        let (api, _) = resource_access::object_api(client, target).await?;
+        let patched = api
+            .patch(
+                target.name.as_str(),
+                &PatchParams::default(),
+                &Patch::Merge(&patch),
+            )
+            .await
+            .context(format!(
+                "Failed to annotate {} {}",
+                target.kind, target.name
+            ))?;
+        Ok(read(&serde_json::to_value(patched).unwrap_or_default()))
+    }
+
+    /// Lists objects without an owner or runbook. Objects managed by a
+    /// controller inherit ownership from it and are skipped.
+    pub async fn report(
+        client: Client,
+        namespace: Option<String>,
+        kinds: Option<Vec<GovernedKind>>,
+    ) -> OwnershipReport {
+        let mut report = OwnershipReport {
+            checked: 0,
+            missing: Vec::new(),
+            errors: Vec::new(),
+        };
+        for kind in kinds.unwrap_or_else(default_kinds) {
+            let listed = match resource_access::resolve(
+                &client,
+                kind.group.as_str(),
+                kind.version.as_str(),
+                kind.kind.as_str(),
+            )
+            .await
+            {
+                Ok((resource, caps)) => {
+                    resource_access::api_for(client.clone(), &resource, &caps, namespace.as_deref())
+                        .list(&ListParams::default())
+                        .await
+                        .context(format!("Failed to list {}", kind.kind))
+                }
+                Err(error) => Err(error),
+            };
+            let list = match listed {
+                Ok(list) => list,
+                Err(error) => {
+                    report.errors.push(error.to_string());
+                    continue;
+                }
+            };
+            for object in list.items {
+                let controlled = object
+                    .metadata
+                    .owner_references
+                    .as_ref()
+                    .is_some_and(|refs| refs.iter().any(|r| r.controller.unwrap_or(false)));
+                if controlled {
+                    continue;
+                }
+                report.checked += 1;
+                let metadata = read(&serde_json::to_value(&object).unwrap_or_default());
+                let mut missing: Vec<String> = Vec::new();
+                if metadata.owner.is_none() {
+                    missing.push("owner".to_string());
+                }
+                if metadata.runbook.is_none() {
+                    missing.push("runbook".to_string());
+                }
+                if missing.is_empty() {
+                    continue;
+                }
+                report.missing.push(MissingOwnership {
+                    resource: ResourceRef {
+                        group: kind.group.clone(),
+                        version: kind.version.clone(),
+                        kind: kind.kind.clone(),
+                        namespace: object.metadata.namespace.clone(),
+                        name: object.metadata.name.clone().unwrap_or_default(),
+                    },
+                    missing,
+                });
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,403 @@
+pub mod delete_impact {
+    use std::collections::HashSet;
+
+    use kube::{api::ListParams, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::views::resource_access::{self, ResourceRef},
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    /// Kinds searched for objects owned by the target. Only these are
+    /// reported; custom resources owned by the target are still collected
+    /// by the cluster.
+    const OWNED_KINDS: [(&str, &str, &str); 11] = [
+        ("apps", "v1", "Deployment"),
+        ("apps", "v1", "StatefulSet"),
+        ("apps", "v1", "ReplicaSet"),
+        ("apps", "v1", "ControllerRevision"),
+        ("batch", "v1", "Job"),
+        ("", "v1", "Pod"),
+        ("", "v1", "Service"),
+        ("", "v1", "ConfigMap"),
+        ("", "v1", "Secret"),
+        ("", "v1", "PersistentVolumeClaim"),
+        ("discovery.k8s.io", "v1", "EndpointSlice"),
+    ];
+
+    /// Kinds with a pod template, with the path to it.
+    const WORKLOAD_KINDS: [(&str, &str, &str, &[&str]); 7] = [
+        ("", "v1", "Pod", &[]),
+        ("apps", "v1", "Deployment", &["spec", "template"]),
+        ("apps", "v1", "StatefulSet", &["spec", "template"]),
+        ("apps", "v1", "DaemonSet", &["spec", "template"]),
+        ("apps", "v1", "ReplicaSet", &["spec", "template"]),
+        ("batch", "v1", "Job", &["spec", "template"]),
+        (
+            "batch",
+            "v1",
+            "CronJob",
+            &["spec", "jobTemplate", "spec", "template"],
+        ),
+    ];
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum Impact {
+        /// Owned by the target (directly or transitively) and deleted with
+        /// it by the garbage collector.
+        GarbageCollected,
+        /// A workload mounting or otherwise referencing the target.
+        References,
+        /// A Service whose selector matches the target's pods.
+        SelectedBy,
+        /// An Ingress routing to the target or a Service selecting it.
+        RoutedBy,
+        /// A HorizontalPodAutoscaler scaling the target.
+        ScaledBy,
+        /// Deleted together with the namespace being deleted.
+        Contained,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ImpactedObject {
+        pub kind: String,
+        pub namespace: Option<String>,
+        pub name: String,
+        pub impact: Impact,
+        pub detail: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct DeleteImpact {
+        pub target: ResourceRef,
+        pub impacted: Vec<ImpactedObject>,
+        /// One line per impact kind, for the confirmation dialog.
+        pub summary: Vec<String>,
+        /// Kinds that could not be listed, so the impact may be incomplete.
+        pub warnings: Vec<String>,
+    }
+
+    struct Lister {
+        client: Client,
+        namespace: Option<String>,
+        warnings: Vec<String>,
+    }
+
+    impl Lister {
+        async fn list(&mut self, group: &str, version: &str, kind: &str) -> Vec<Value> {
+            let result = async {
+                let (resource, caps) =
+                    resource_access::resolve(&self.client, group, version, kind).await?;
+                let api = resource_access::api_for(
+                    self.client.clone(),
+                    &resource,
+                    &caps,
+                    self.namespace.as_deref(),
+                );
+                let objects = api
+                    .list(&ListParams::default())
+                    .await
+                    .context(format!("Failed to list {}", kind))?;
+                Ok::<Vec<Value>, String>(
+                    objects
+                        .items
+                        .into_iter()
+                        .filter_map(|o| resource_access::to_item(o, &resource))
+                        .collect(),
+                )
+            };
+            match result.await {
+                Ok(items) => items,
+                Err(error) => {
+                    self.warnings.push(error);
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    fn impacted(object: &Value, impact: Impact, detail: String) -> ImpactedObject {
+        ImpactedObject {
+            kind: object["kind"].as_str().unwrap_or_default().to_string(),
+            namespace: object["metadata"]["namespace"]
+                .as_str()
+                .map(|s| s.to_string()),
+            name: object["metadata"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            impact,
+            detail,
+        }
+    }
+
+    fn template<'a>(object: &'a Value, path: &[&str]) -> &'a Value {
+        path.iter().fold(object, |value, key| &value[*key])
+    }
+
+    /// Whether a pod spec references the named object of the given kind.
+    fn references(value: &Value, kind: &str, name: &str) -> bool {
+        let keys: &[(&str, &str)] = match kind {
+            "ConfigMap" => &[
+                ("configMap", "name"),
+                ("configMapRef", "name"),
+                ("configMapKeyRef", "name"),
+            ],
+            "Secret" => &[
+                ("secret", "secretName"),
+                ("secret", "name"),
+                ("secretRef", "name"),
+                ("secretKeyRef", "name"),
+                ("imagePullSecrets", "name"),
+            ],
+            "PersistentVolumeClaim" => &[("persistentVolumeClaim", "claimName")],
+            "ServiceAccount" => {
+                return value["serviceAccountName"].as_str() == Some(name)
+                    || value["serviceAccount"].as_str() == Some(name)
+            }
+            _ => return false,
+        };
+        fn walk(value: &Value, keys: &[(&str, &str)], name: &str) -> bool {
+            match value {
+                Value::Object(fields) => fields.iter().any(|(key, child)| {
+                    keys.iter().any(|(k, field)| {
+                        *k == key.as_str()
+                            && match child {
+                                Value::Array(items) => {
+                                    items.iter().any(|i| i[*field].as_str() == Some(name))
+                                }
+                                other => other[*field].as_str() == Some(name),
+                            }
+                    }) || walk(child, keys, name)
+                }),
+                Value::Array(items) => items.iter().any(|i| walk(i, keys, name)),
+                _ => false,
+            }
+        }
+        walk(value, keys, name)
+    }
+
+    fn selects(selector: &Value, labels: &Value) -> bool {
+        match selector.as_object() {
+            Some(selector) if !selector.is_empty() => {
+                selector.iter().all(|(key, value)| labels[key] == *value)
+            }
+            _ => false,
+        }
+    }
+
+    fn routes_to(ingress: &Value, service: &str) -> bool {
+        let backend = |b: &Value| b["service"]["name"].as_str() == Some(service);
+        backend(&ingress["spec"]["defaultBackend"])
+            || ingress["spec"]["rules"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .flat_map(|rule| rule["http"]["paths"].as_array().into_iter().flatten())
+                .any(|path| backend(&path["backend"]))
+    }
+
+    fn owned_by(object: &Value, uids: &HashSet<String>) -> bool {
+        object["metadata"]["ownerReferences"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|r| r["uid"].as_str().is_some_and(|uid| uids.contains(uid)))
+    }
+
+    async fn garbage_collected(
+        lister: &mut Lister,
+        uid: &str,
+        impacted_objects: &mut Vec<ImpactedObject>,
+    ) {
+        let mut candidates: Vec<Value> = Vec::new();
+        for (group, version, kind) in OWNED_KINDS {
+            candidates.extend(lister.list(group, version, kind).await);
+        }
+        // Follow ownership transitively, e.g. Deployment -> ReplicaSet -> Pod.
+        let mut owners: HashSet<String> = HashSet::from([uid.to_string()]);
+        let mut collected: HashSet<String> = HashSet::new();
+        loop {
+            let found: Vec<&Value> = candidates
+                .iter()
+                .filter(|c| {
+                    c["metadata"]["uid"]
+                        .as_str()
+                        .is_some_and(|uid| !collected.contains(uid))
+                        && owned_by(c, &owners)
+                })
+                .collect();
+            if found.is_empty() {
+                break;
+            }
+            for object in found {
+                let uid = object["metadata"]["uid"].as_str().unwrap_or_default();
+                collected.insert(uid.to_string());
+                owners.insert(uid.to_string());
+                impacted_objects.push(impacted(
+                    object,
+                    Impact::GarbageCollected,
+                    "Owned by the deleted object".to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Works out what deleting an object affects: objects the garbage
+    /// collector removes with it, workloads referencing it, Services
+    /// selecting its pods, Ingresses routing to it and autoscalers scaling
+    /// it. Deleting a Namespace lists its workloads and Services instead.
+    pub async fn analyze(
+        client: Client,
+        target: ResourceRef,
+    ) -> Result<DeleteImpact, KubiousError> {
+        let object = resource_access::get(client.clone(), &target).await?;
+        let uid = object["metadata"]["uid"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let kind = target.kind.as_str();
+        let name = target.name.as_str();
+        let mut lister = Lister {
+            client,
+            namespace: match kind {
+                "Namespace" => Some(target.name.clone()),
+                _ => object["metadata"]["namespace"]
+                    .as_str()
+                    .map(|s| s.to_string()),
+            },
+            warnings: Vec::new(),
+        };
+        let mut impacted_objects: Vec<ImpactedObject> = Vec::new();
+
+        if kind == "Namespace" {
+            for (group, version, contained, _) in WORKLOAD_KINDS {
+                for item in lister.list(group, version, contained).await {
+                    // Objects owned by a workload are covered by the workload.
+                    if item["metadata"]["ownerReferences"].is_array() {
+                        continue;
+                    }
+                    impacted_objects.push(impacted(&item, Impact::Contained, String::new()));
+                }
+            }
+            for item in lister.list("", "v1", "Service").await {
+                impacted_objects.push(impacted(&item, Impact::Contained, String::new()));
+            }
+            for item in lister.list("", "v1", "PersistentVolumeClaim").await {
+                impacted_objects.push(impacted(
+                    &item,
+                    Impact::Contained,
+                    "Data may be lost, depending on the volume's reclaim policy".to_string(),
+                ));
+            }
+        } else if !uid.is_empty() {
+            garbage_collected(&mut lister, uid.as_str(), &mut impacted_objects).await;
+        }
+
+        if matches!(
+            kind,
+            "ConfigMap" | "Secret" | "PersistentVolumeClaim" | "ServiceAccount"
+        ) && target.group.is_empty()
+        {
+            for (group, version, workload, path) in WORKLOAD_KINDS {
+                for item in lister.list(group, version, workload).await {
+                    if item["metadata"]["ownerReferences"].is_array() {
+                        continue;
+                    }
+                    if references(&template(&item, path)["spec"], kind, name) {
+                        impacted_objects.push(impacted(
+                            &item,
+                            Impact::References,
+                            format!("Pod template references {} {}", kind, name),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let template_path = WORKLOAD_KINDS
+            .iter()
+            .find(|(group, _, workload, _)| *group == target.group && *workload == kind)
+            .map(|(_, _, _, path)| *path);
+        let mut routed_services: Vec<String> = Vec::new();
+        if let Some(path) = template_path {
+            let labels = &template(&object, path)["metadata"]["labels"];
+            for service in lister.list("", "v1", "Service").await {
+                if selects(&service["spec"]["selector"], labels) {
+                    routed_services.push(
+                        service["metadata"]["name"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                    );
+                    impacted_objects.push(impacted(
+                        &service,
+                        Impact::SelectedBy,
+                        "Loses these endpoints".to_string(),
+                    ));
+                }
+            }
+            for hpa in lister
+                .list("autoscaling", "v2", "HorizontalPodAutoscaler")
+                .await
+            {
+                let scale_target = &hpa["spec"]["scaleTargetRef"];
+                if scale_target["kind"].as_str() == Some(kind)
+                    && scale_target["name"].as_str() == Some(name)
+                {
+                    impacted_objects.push(impacted(
+                        &hpa,
+                        Impact::ScaledBy,
+                        "Left without a scale target".to_string(),
+                    ));
+                }
+            }
+        }
+        if kind == "Service" && target.group.is_empty() {
+            routed_services.push(name.to_string());
+        }
+        if !routed_services.is_empty() {
+            for ingress in lister.list("networking.k8s.io", "v1", "Ingress").await {
+                for service in routed_services.iter() {
+                    if routes_to(&ingress, service.as_str()) {
+                        impacted_objects.push(impacted(
+                            &ingress,
+                            Impact::RoutedBy,
+                            format!("Routes to Service {}", service),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let summary = [
+            (
+                Impact::GarbageCollected,
+                "owned objects will be garbage-collected",
+            ),
+            (Impact::References, "workloads reference it and may fail"),
+            (Impact::SelectedBy, "Services select its pods"),
+            (Impact::RoutedBy, "Ingress routes lead to it"),
+            (Impact::ScaledBy, "autoscalers target it"),
+            (Impact::Contained, "objects are deleted with the namespace"),
+        ]
+        .into_iter()
+        .filter_map(|(impact, text)| {
+            let count = impacted_objects
+                .iter()
+                .filter(|o| o.impact == impact)
+                .count();
+            (count > 0).then(|| format!("{} {}", count, text))
+        })
+        .collect();
+
+        Ok(DeleteImpact {
+            target,
+            impacted: impacted_objects,
+            summary,
+            warnings: lister.warnings,
+        })
+    }
+}
@@ -0,0 +1,398 @@
+pub mod resource_informers {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard,
+        },
+    };
+
+    use futures::StreamExt;
+    use k8s_openapi::chrono::Utc;
+    use kube::{
+        api::DynamicObject,
+        discovery::{ApiResource, Scope},
+        runtime::{
+            reflector::{store::Writer, ObjectRef, Store},
+            watcher::{self, watcher, Event},
+        },
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::{async_runtime::JoinHandle, AppHandle, Emitter, Manager};
+
+    use crate::{
+        api::{
+            app_settings::TenancyFilter, app_state::AppState, discovery_cache,
+            views::resource_access,
+        },
+        util::{
+            kubious_error::KubiousError,
+            lock_recovery::recover,
+            stream_reconnect::{BackoffPolicy, Reconnector},
+            watch_gaps::{GapTracker, SyntheticEvent},
+        },
+    };
+
+    /// Prefix of the per-informer event channel; the full channel is
+    /// `informer/<id>`.
+    pub const INFORMER_CHANNEL_PREFIX: &str = "informer/";
+
+    /// What an informer watches. `namespace` is `None` for cluster-scoped
+    /// kinds and for watching a namespaced kind across all namespaces.
+    /// `tenancy_filter` is the id of the filter the informer was scoped by,
+    /// so informers are only shared under the same filter.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+    pub struct InformerKey {
+        pub cluster: String,
+        pub group: String,
+        pub version: String,
+        pub kind: String,
+        pub namespace: Option<String>,
+        #[serde(default)]
+        pub tenancy_filter: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "type")]
+    pub enum InformerEvent {
+        Added {
+            object: Value,
+        },
+        Modified {
+            object: Value,
+        },
+        Deleted {
+            object: Value,
+        },
+        /// The cache finished a full list, either the first one or a relist
+        /// after the watch fell too far behind (410 Gone). Changes missed
+        /// before a relist are sent as the events above first.
+        Synced {
+            count: usize,
+        },
+        /// The watch gave up; the informer is removed.
+        Closed {
+            error: Option<String>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct InformerInfo {
+        pub id: String,
+        pub channel: String,
+        pub key: InformerKey,
+        pub subscribers: usize,
+        pub synced: bool,
+        pub started: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct InformerSnapshot {
+        pub info: InformerInfo,
+        pub items: Vec<Value>,
+    }
+
+    struct RunningInformer {
+        info: InformerInfo,
+        resource: ApiResource,
+        store: Store<DynamicObject>,
+        task: JoinHandle<()>,
+    }
+
+    /// Reflector caches shared by every subscriber of the same cluster,
+    /// kind and namespace. An informer keeps watching until its last
+    /// subscriber leaves.
+    #[derive(Default)]
+    pub struct Informers {
+        informers: Mutex<HashMap<String, RunningInformer>>,
+        counter: AtomicU64,
+    }
+
+    impl Informers {
+        fn informers_mutable(&self) -> MutexGuard<'_, HashMap<String, RunningInformer>> {
+            recover(&self.informers)
+        }
+
+        fn next_id(&self) -> String {
+            format!(
+                "informer-{}",
+                self.counter.fetch_add(1, Ordering::SeqCst) + 1
+            )
+        }
+
+        pub fn active(&self) -> Vec<InformerInfo> {
+            let mut informers: Vec<InformerInfo> = self
+                .informers_mutable()
+                .values()
+                .map(|running| running.info.clone())
+                .collect();
+            informers.sort_by(|a, b| a.started.cmp(&b.started));
+            informers
+        }
+    }
+
+    /// The cached objects of an informer, without another request to the
+    /// cluster. Objects outside the cluster's active tenancy filter are left
+    /// out, even if the filter changed after the informer started.
+    pub fn snapshot(handle: &AppHandle, id: &str) -> Result<InformerSnapshot, KubiousError> {
+        let informers = handle.state::<Informers>();
+        let informers = informers.informers_mutable();
+        let running = informers.get(id).ok_or("Unknown informer".to_string())?;
+        let filter = handle
+            .state::<AppState>()
+            .active_tenancy_filter(running.info.key.cluster.as_str());
+        if let Some(filter) = &filter {
+            if !filter.allows_kind(running.info.key.kind.as_str()) {
+                return Err(format!(
+                    "{} is outside the tenancy filter {}",
+                    running.info.key.kind, filter.name
+                )
+                .into());
+            }
+        }
+        let mut items: Vec<Value> = running
+            .store
+            .state()
+            .iter()
+            .filter(|object| in_filter(filter.as_ref(), object.as_ref()))
+            .filter_map(|object| {
+                resource_access::to_item(object.as_ref().clone(), &running.resource)
+            })
+            .collect();
+        items.sort_by(|a, b| {
+            let key = |v: &Value| {
+                (
+                    v["metadata"]["namespace"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    v["metadata"]["name"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                )
+            };
+            key(a).cmp(&key(b))
+        });
+        Ok(InformerSnapshot {
+            info: running.info.clone(),
+            items,
+        })
+    }
+
+    fn in_filter(filter: Option<&TenancyFilter>, object: &DynamicObject) -> bool {
+        filter.is_none_or(|filter| {
+            object
+                .metadata
+                .namespace
+                .as_deref()
+                .is_none_or(|namespace| filter.allows_namespace(namespace))
+        })
+    }
+
+    fn emit(handle: &AppHandle, channel: &str, events: Vec<InformerEvent>) {
+        for event in events {
+            let _ = handle.emit(channel, event);
+        }
+    }
+
+    fn set_synced(handle: &AppHandle, id: &str) {
+        if let Some(running) = handle.state::<Informers>().informers_mutable().get_mut(id) {
+            running.info.synced = true;
+        }
+    }
+
+    async fn run(
+        handle: AppHandle,
+        api: Api<DynamicObject>,
+        resource: ApiResource,
+        filter: Option<TenancyFilter>,
+        mut writer: Writer<DynamicObject>,
+        id: String,
+        channel: String,
+    ) {
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "informers",
+            id.as_str(),
+            BackoffPolicy::default(),
+        );
+        let mut gaps = GapTracker::new(handle.clone(), "informers", id.as_str());
+        let reader = writer.as_reader();
+        let to_item = |object: &DynamicObject| resource_access::to_item(object.clone(), &resource);
+
+        let mut error: Option<String> = None;
+        let mut config = watcher::Config::default();
+        if let Some(selector) = filter.as_ref().and_then(|f| f.label_selector(None)) {
+            config = config.labels(selector.as_str());
+        }
+        let mut stream = watcher(api, config).boxed();
+        while let Some(event) = stream.next().await {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    gaps.failed(e.to_string().as_str());
+                    if !reconnector.retry(e.to_string().as_str()).await {
+                        error = Some(e.to_string());
+                        break;
+                    }
+                    continue;
+                }
+            };
+            let mut events: Vec<InformerEvent> = Vec::new();
+            let outside = |object: &DynamicObject| !in_filter(filter.as_ref(), object);
+            match &event {
+                Event::Init => gaps.init(),
+                Event::InitApply(object) => {
+                    if let Some(item) = to_item(object) {
+                        gaps.init_apply(&item);
+                    }
+                }
+                Event::InitDone => {
+                    for missed in gaps.init_done().unwrap_or_default() {
+                        events.push(match missed {
+                            SyntheticEvent::Added { object } => InformerEvent::Added { object },
+                            SyntheticEvent::Modified { object } => {
+                                InformerEvent::Modified { object }
+                            }
+                            SyntheticEvent::Removed { object } => InformerEvent::Deleted { object },
+                        });
+                    }
+                    reconnector.connected();
+                }
+                Event::Apply(object) if outside(object) => {}
+                Event::Delete(object) if outside(object) => {}
+                Event::Apply(object) => {
+                    let known = reader
+                        .get(&ObjectRef::from_obj_with(object, resource.clone()))
+                        .is_some();
+                    if let Some(object) = to_item(object) {
+                        gaps.applied(&object);
+                        events.push(if known {
+                            InformerEvent::Modified { object }
+                        } else {
+                            InformerEvent::Added { object }
+                        });
+                    }
+                }
+                Event::Delete(object) => {
+                    if let Some(object) = to_item(object) {
+                        gaps.deleted(&object);
+                        events.push(InformerEvent::Deleted { object });
+                    }
+                }
+            }
+            writer.apply_watcher_event(&event);
+            if let Event::InitDone = event {
+                set_synced(&handle, id.as_str());
+                events.push(InformerEvent::Synced {
+                    count: reader.len(),
+                });
+            }
+            emit(&handle, channel.as_str(), events);
+        }
+        reconnector.closed();
+
+        handle.state::<Informers>().informers_mutable().remove(&id);
+        let _ = handle.emit(channel.as_str(), InformerEvent::Closed { error });
+    }
+
+    /// Subscribes to a kind, joining the informer already running for the
+    /// same key or starting one. The watcher relists on its own when the
+    /// server expires its resourceVersion, so the cache stays complete.
+    /// The active tenancy filter narrows the watch by its label selector,
+    /// and across all namespaces only objects in its namespaces are sent.
+    pub async fn subscribe(
+        handle: &AppHandle,
+        client: Client,
+        filter: Option<TenancyFilter>,
+        mut key: InformerKey,
+    ) -> Result<InformerInfo, KubiousError> {
+        let (resource, caps) = discovery_cache::resolve(
+            handle,
+            &client,
+            key.cluster.as_str(),
+            key.group.as_str(),
+            key.version.as_str(),
+            key.kind.as_str(),
+        )
+        .await?;
+        if caps.scope != Scope::Namespaced {
+            key.namespace = None;
+        }
+        if let Some(filter) = &filter {
+            if !filter.allows_kind(key.kind.as_str()) {
+                return Err(
+                    format!("{} is outside the tenancy filter {}", key.kind, filter.name).into(),
+                );
+            }
+            if let Some(namespace) = &key.namespace {
+                if !filter.allows_namespace(namespace.as_str()) {
+                    return Err(format!(
+                        "Namespace {} is outside the tenancy filter {}",
+                        namespace, filter.name
+                    )
+                    .into());
+                }
+            }
+        }
+        key.tenancy_filter = filter.as_ref().map(|f| f.id.clone());
+
+        let informers = handle.state::<Informers>();
+        let mut running = informers.informers_mutable();
+        if let Some(existing) = running.values_mut().find(|r| r.info.key == key) {
+            existing.info.subscribers += 1;
+            return Ok(existing.info.clone());
+        }
+
+        let id = informers.next_id();
+        let info = InformerInfo {
+            channel: format!("{}{}", INFORMER_CHANNEL_PREFIX, id),
+            id: id.clone(),
+            key: key.clone(),
+            subscribers: 1,
+            synced: false,
+            started: Utc::now().to_rfc3339(),
+        };
+        let writer: Writer<DynamicObject> = Writer::new(resource.clone());
+        let store = writer.as_reader();
+        let api = resource_access::api_for(client, &resource, &caps, key.namespace.as_deref());
+        let task = tauri::async_runtime::spawn(run(
+            handle.clone(),
+            api,
+            resource.clone(),
+            filter,
+            writer,
+            id.clone(),
+            info.channel.clone(),
+        ));
+        running.insert(
+            id,
+            RunningInformer {
+                info: info.clone(),
+                resource,
+                store,
+                task,
+            },
+        );
+        Ok(info)
+    }
+
+    /// Leaves an informer, stopping it once nobody is subscribed. Returns
+    /// the remaining subscriber count.
+    pub fn unsubscribe(handle: &AppHandle, id: &str) -> Result<usize, KubiousError> {
+        let informers = handle.state::<Informers>();
+        let mut running = informers.informers_mutable();
+        let informer = running.get_mut(id).ok_or("Unknown informer".to_string())?;
+        informer.info.subscribers = informer.info.subscribers.saturating_sub(1);
+        let remaining = informer.info.subscribers;
+        if remaining == 0 {
+            if let Some(stopped) = running.remove(id) {
+                stopped.task.abort();
+            }
+        }
+        Ok(remaining)
+    }
+}
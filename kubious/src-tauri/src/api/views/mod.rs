@@ -0,0 +1,422 @@
+pub mod views_api {
+    use crate::{
+        api::{app_settings::TenancyFilter, app_state::AppState},
+        util::kubious_error::{ErrorContext, KubiousError},
+        CommandHandler,
+    };
+    use kube::{
+        api::{DynamicObject, GroupVersionKind, ListParams},
+        discovery::{pinned_kind, Scope},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use tauri::Manager;
+
+    use super::{
+        delete_impact, printer_columns,
+        resource_access::{self, ResourceRef},
+        resource_governance::{self, GovernedKind, ResourceMetadata},
+        resource_informers::{self, InformerKey, Informers},
+        view_store::{usage_key, ResourceUsage, SortSpec, View, ViewSpec, ViewStore},
+        view_tenancy, view_watch,
+    };
+    use crate::util::{kube_quantity::parse_quantity, time_normalization};
+
+    fn sum_usage(containers: &[Value]) -> ResourceUsage {
+        containers
+            .iter()
+            .fold(ResourceUsage::default(), |total, c| ResourceUsage {
+                cpu: total.cpu
+                    + c["usage"]["cpu"]
+                        .as_str()
+                        .and_then(parse_quantity)
+                        .unwrap_or(0.0),
+                memory: total.memory
+                    + c["usage"]["memory"]
+                        .as_str()
+                        .and_then(parse_quantity)
+                        .unwrap_or(0.0),
+            })
+    }
+
+    /// Fetches live usage for Pod and Node views from metrics.k8s.io. Clusters
+    /// without metrics-server simply get no usage columns.
+    pub async fn fetch_usage(client: Client, spec: &ViewSpec) -> HashMap<String, ResourceUsage> {
+        let kind = match (spec.group.as_str(), spec.kind.as_str()) {
+            ("", "Pod") => "PodMetrics",
+            ("", "Node") => "NodeMetrics",
+            _ => return HashMap::new(),
+        };
+        let gvk = GroupVersionKind::gvk("metrics.k8s.io", "v1beta1", kind);
+        let Ok((resource, _)) = pinned_kind(&client, &gvk).await else {
+            return HashMap::new();
+        };
+        let api: Api<DynamicObject> = match (kind, &spec.namespace) {
+            ("PodMetrics", Some(ns)) => Api::namespaced_with(client, ns.as_str(), &resource),
+            _ => Api::all_with(client, &resource),
+        };
+        let Ok(metrics) = api.list(&ListParams::default()).await else {
+            return HashMap::new();
+        };
+
+        metrics
+            .items
+            .into_iter()
+            .filter_map(|m| serde_json::to_value(m).ok())
+            .map(|m| {
+                let usage = match m["containers"].as_array() {
+                    Some(containers) => sum_usage(containers),
+                    None => sum_usage(std::slice::from_ref(&m)),
+                };
+                (usage_key(&m), usage)
+            })
+            .collect()
+    }
+
+    /// Lists the collection described by a view spec as plain JSON objects,
+    /// narrowed to the cluster's active tenancy filter. The namespace is
+    /// ignored for cluster-scoped kinds.
+    pub async fn fetch_items(
+        client: Client,
+        spec: &ViewSpec,
+        filter: Option<&TenancyFilter>,
+    ) -> Result<Vec<Value>, KubiousError> {
+        let (resource, caps) = resource_access::resolve(
+            &client,
+            spec.group.as_str(),
+            spec.version.as_str(),
+            spec.kind.as_str(),
+        )
+        .await?;
+        let scope = view_tenancy::scope(filter, spec, caps.scope == Scope::Namespaced)?;
+        let mut params = ListParams::default();
+        if let Some(selector) = &scope.label_selector {
+            params = params.labels(selector.as_str());
+        }
+
+        let mut items: Vec<Value> = Vec::new();
+        for namespace in scope.namespaces.iter() {
+            let api =
+                resource_access::api_for(client.clone(), &resource, &caps, namespace.as_deref());
+            let objects = api
+                .list(&params)
+                .await
+                .context(format!("Failed to list {}", spec.kind))?;
+            items.extend(
+                objects
+                    .items
+                    .into_iter()
+                    .filter_map(|object| resource_access::to_item(object, &resource)),
+            );
+        }
+        Ok(items)
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[serde(tag = "command")]
+    pub enum ViewsCommand {
+        OpenView {
+            spec: ViewSpec,
+        },
+        QueryWindow {
+            id: String,
+            offset: usize,
+            limit: usize,
+            /// The viewer's UTC offset in minutes; timestamps are shown in UTC
+            /// when omitted.
+            #[serde(default)]
+            utc_offset: Option<i32>,
+        },
+        UpdateView {
+            id: String,
+            filter: Option<String>,
+            sort: Option<SortSpec>,
+        },
+        RefreshView {
+            id: String,
+        },
+        AggregateView {
+            id: String,
+            group_by: String,
+        },
+        CloseView {
+            id: String,
+        },
+        ListViews {},
+        WatchView {
+            id: String,
+        },
+        UnwatchView {
+            id: String,
+        },
+        /// Lists served kinds; `namespaced: Some(false)` gives the
+        /// cluster-scoped ones (Nodes, PVs, ClusterRoles, CRDs, ...).
+        ListKinds {
+            namespaced: Option<bool>,
+        },
+        GetResource {
+            resource: ResourceRef,
+        },
+        DeleteResource {
+            resource: ResourceRef,
+        },
+        /// What else breaks or is garbage-collected if the object is deleted.
+        DeleteImpact {
+            resource: ResourceRef,
+        },
+        /// Owner team, runbook and notes annotations of the object.
+        GetResourceMetadata {
+            resource: ResourceRef,
+        },
+        /// Replaces the owner team, runbook and notes annotations; unset
+        /// fields are removed from the object.
+        SetResourceMetadata {
+            resource: ResourceRef,
+            metadata: ResourceMetadata,
+        },
+        /// Objects in `namespace`, or every namespace, without an owner team
+        /// or runbook. Defaults to the common workload kinds and Services.
+        OwnershipReport {
+            namespace: Option<String>,
+            kinds: Option<Vec<GovernedKind>>,
+        },
+        /// Joins or starts a shared informer for a kind in `cluster`, the
+        /// current config if omitted. Changes are pushed on its channel.
+        SubscribeKind {
+            cluster: Option<String>,
+            group: String,
+            version: String,
+            kind: String,
+            namespace: Option<String>,
+        },
+        UnsubscribeKind {
+            id: String,
+        },
+        InformerSnapshot {
+            id: String,
+        },
+        ListInformers {},
+    }
+
+    impl CommandHandler for ViewsCommand {
+        async fn execute(&self, handle: &tauri::AppHandle) -> Result<Value, KubiousError> {
+            let views = handle.state::<ViewStore>();
+            match self {
+                ViewsCommand::OpenView { spec } => {
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) =
+                        (state.client().await, state.get_current_config())
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let (resource, caps) = resource_access::resolve(
+                        &client,
+                        spec.group.as_str(),
+                        spec.version.as_str(),
+                        spec.kind.as_str(),
+                    )
+                    .await?;
+                    let namespaced = caps.scope == Scope::Namespaced;
+                    let mut spec = spec.clone();
+                    if !namespaced {
+                        spec.namespace = None;
+                    }
+                    let filter = state.active_tenancy_filter(cluster.as_str());
+                    let items = fetch_items(client.clone(), &spec, filter.as_ref()).await?;
+                    let usage = fetch_usage(client.clone(), &spec).await;
+                    let mut view = View::new(views.next_id(), cluster, spec, items, usage);
+                    view.namespaced = namespaced;
+                    view.columns = printer_columns::for_kind(client.clone(), &resource).await;
+                    view.utc_offset = time_normalization::detect_cluster_offset(client).await;
+                    self.wrap_in_value(Ok(views.insert(view)))
+                }
+                ViewsCommand::QueryWindow {
+                    id,
+                    offset,
+                    limit,
+                    utc_offset,
+                } => self.wrap_in_value(
+                    views
+                        .with_view(id, |view| view.window(*offset, *limit, *utc_offset))
+                        .ok_or("Unknown view".into()),
+                ),
+                ViewsCommand::UpdateView { id, filter, sort } => self.wrap_in_value(
+                    views
+                        .with_view(id, |view| {
+                            view.spec.filter = filter.clone();
+                            view.spec.sort = sort.clone();
+                            view.rebuild();
+                            view.summary()
+                        })
+                        .ok_or("Unknown view".into()),
+                ),
+                ViewsCommand::RefreshView { id } => {
+                    let Some((cluster, spec)) =
+                        views.read(id, |view| (view.cluster.clone(), view.spec.clone()))
+                    else {
+                        return Err("Unknown view".into());
+                    };
+                    let state = handle.state::<AppState>();
+                    let Some(client) = state.client_for(cluster.as_str()).await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let filter = state.active_tenancy_filter(cluster.as_str());
+                    let items = fetch_items(client.clone(), &spec, filter.as_ref()).await?;
+                    let usage = fetch_usage(client.clone(), &spec).await;
+                    let utc_offset = time_normalization::detect_cluster_offset(client).await;
+                    self.wrap_in_value(
+                        views
+                            .with_view(id, |view| {
+                                view.replace_items(items, usage);
+                                view.utc_offset = utc_offset;
+                                view.summary()
+                            })
+                            .ok_or("Unknown view".into()),
+                    )
+                }
+                ViewsCommand::AggregateView { id, group_by } => self.wrap_in_value(
+                    views
+                        .with_view(id, |view| view.aggregate(group_by.as_str()))
+                        .ok_or("Unknown view".into()),
+                ),
+                ViewsCommand::CloseView { id } => {
+                    if views.remove(id) {
+                        self.wrap_in_value(Ok(()))
+                    } else {
+                        Err("Unknown view".into())
+                    }
+                }
+                ViewsCommand::ListViews {} => self.wrap_in_value(Ok(views.summaries())),
+                ViewsCommand::WatchView { id } => {
+                    let Some(cluster) = views.read(id, |view| view.cluster.clone()) else {
+                        return Err("Unknown view".into());
+                    };
+                    let Some(client) = handle
+                        .state::<AppState>()
+                        .client_for(cluster.as_str())
+                        .await
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(Ok(view_watch::start(handle, client, id.as_str())))
+                }
+                ViewsCommand::UnwatchView { id } => self.wrap_in_value(Ok(views.stop_watch(id))),
+                ViewsCommand::ListKinds { namespaced } => {
+                    let state = handle.state::<AppState>();
+                    let (Some(client), Some((cluster, _))) =
+                        (state.client().await, state.get_current_config())
+                    else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let filter = state.active_tenancy_filter(cluster.as_str());
+                    let mut kinds = resource_access::list_kinds(client, *namespaced).await?;
+                    if let Some(filter) = filter {
+                        kinds.retain(|k| filter.allows_kind(k.kind.as_str()));
+                    }
+                    self.wrap_in_value(Ok(kinds))
+                }
+                ViewsCommand::GetResource { resource } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(resource_access::get(client, resource).await)
+                }
+                ViewsCommand::DeleteResource { resource } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(resource_access::delete(client, resource).await)
+                }
+                ViewsCommand::DeleteImpact { resource } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(delete_impact::analyze(client, resource.clone()).await)
+                }
+                ViewsCommand::GetResourceMetadata { resource } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(resource_governance::get(client, resource).await)
+                }
+                ViewsCommand::SetResourceMetadata { resource, metadata } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(
+                        resource_governance::set(client, resource, metadata.clone()).await,
+                    )
+                }
+                ViewsCommand::OwnershipReport { namespace, kinds } => {
+                    let Some(client) = handle.state::<AppState>().client().await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    self.wrap_in_value(Ok(resource_governance::report(
+                        client,
+                        namespace.clone(),
+                        kinds.clone(),
+                    )
+                    .await))
+                }
+                ViewsCommand::SubscribeKind {
+                    cluster,
+                    group,
+                    version,
+                    kind,
+                    namespace,
+                } => {
+                    let state = handle.state::<AppState>();
+                    let Some(cluster) = cluster
+                        .clone()
+                        .or(state.get_current_config().map(|(key, _)| key))
+                    else {
+                        return Err(KubiousError::config("No config selected."));
+                    };
+                    let Some(client) = state.client_for(cluster.as_str()).await else {
+                        return Err(KubiousError::no_client());
+                    };
+                    let key = InformerKey {
+                        cluster,
+                        group: group.clone(),
+                        version: version.clone(),
+                        kind: kind.clone(),
+                        namespace: namespace.clone(),
+                        tenancy_filter: None,
+                    };
+                    let filter = state.active_tenancy_filter(key.cluster.as_str());
+                    self.wrap_in_value(
+                        resource_informers::subscribe(handle, client, filter, key).await,
+                    )
+                }
+                ViewsCommand::UnsubscribeKind { id } => {
+                    self.wrap_in_value(resource_informers::unsubscribe(handle, id.as_str()))
+                }
+                ViewsCommand::InformerSnapshot { id } => {
+                    self.wrap_in_value(resource_informers::snapshot(handle, id.as_str()))
+                }
+                ViewsCommand::ListInformers {} => {
+                    self.wrap_in_value(Ok(handle.state::<Informers>().active()))
+                }
+            }
+        }
+    }
+}
+
+mod store;
+pub use store::view_store;
+mod resources;
+pub use resources::resource_access;
+mod watch;
+pub use watch::view_watch;
+mod tenancy;
+pub use tenancy::view_tenancy;
+mod impact;
+pub use impact::delete_impact;
+mod informer;
+pub use informer::resource_informers;
+mod governance;
+pub use governance::resource_governance;
+mod columns;
+pub use columns::printer_columns;
@@ -0,0 +1,139 @@
+pub mod resource_access {
+    use kube::{
+        api::{DeleteParams, DynamicObject, GroupVersionKind, TypeMeta},
+        discovery::{pinned_kind, verbs, ApiCapabilities, ApiResource, Discovery, Scope},
+        Api, Client,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::{
+        api::resources::crd_deletion::{self, CRD_DELETE_REFUSED},
+        util::kubious_error::{ErrorContext, KubiousError},
+    };
+
+    /// A browsable kind as reported by discovery.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ResourceKind {
+        pub group: String,
+        pub version: String,
+        pub kind: String,
+        pub plural: String,
+        pub namespaced: bool,
+        pub verbs: Vec<String>,
+    }
+
+    /// Identifies one object. `namespace` is ignored for cluster-scoped kinds.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ResourceRef {
+        pub group: String,
+        pub version: String,
+        pub kind: String,
+        pub namespace: Option<String>,
+        pub name: String,
+    }
+
+    /// Converts a fetched object into the plain JSON the views work with.
+    pub fn to_item(mut object: DynamicObject, resource: &ApiResource) -> Option<Value> {
+        object.metadata.managed_fields = None;
+        object.types = Some(TypeMeta {
+            api_version: resource.api_version.clone(),
+            kind: resource.kind.clone(),
+        });
+        serde_json::to_value(object).ok()
+    }
+
+    pub async fn resolve(
+        client: &Client,
+        group: &str,
+        version: &str,
+        kind: &str,
+    ) -> Result<(ApiResource, ApiCapabilities), KubiousError> {
+        pinned_kind(client, &GroupVersionKind::gvk(group, version, kind))
+            .await
+            .context(format!("Unknown resource kind {}", kind))
+    }
+
+    /// Builds the API for a kind, scoping by namespace only when the kind is
+    /// namespaced. Cluster-scoped kinds (Nodes, PVs, ClusterRoles, ...) are
+    /// always addressed cluster-wide.
+    pub fn api_for(
+        client: Client,
+        resource: &ApiResource,
+        caps: &ApiCapabilities,
+        namespace: Option<&str>,
+    ) -> Api<DynamicObject> {
+        match (&caps.scope, namespace) {
+            (Scope::Namespaced, Some(ns)) => Api::namespaced_with(client, ns, resource),
+            _ => Api::all_with(client, resource),
+        }
+    }
+
+    /// Like `api_for`, but requires a namespace for namespaced kinds, as
+    /// needed for single-object operations.
+    pub async fn object_api(
+        client: Client,
+        target: &ResourceRef,
+    ) -> Result<(Api<DynamicObject>, ApiResource), KubiousError> {
+        let (resource, caps) = resolve(
+            &client,
+            target.group.as_str(),
+            target.version.as_str(),
+            target.kind.as_str(),
+        )
+        .await?;
+        if caps.scope == Scope::Namespaced && target.namespace.is_none() {
+            return Err(format!("{} is namespaced; a namespace is required", target.kind).into());
+        }
+        let api = api_for(client, &resource, &caps, target.namespace.as_deref());
+        Ok((api, resource))
+    }
+
+    /// Lists every kind the server serves, optionally only the namespaced
+    /// (`Some(true)`) or cluster-scoped (`Some(false)`) ones.
+    pub async fn list_kinds(
+        client: Client,
+        namespaced: Option<bool>,
+    ) -> Result<Vec<ResourceKind>, KubiousError> {
+        let discovery = Discovery::new(client)
+            .run()
+            .await
+            .context("Failed to run API discovery.")?;
+        let mut kinds: Vec<ResourceKind> = discovery
+            .groups()
+            .flat_map(|group| group.recommended_resources())
+            .filter(|(_, caps)| caps.supports_operation(verbs::LIST))
+            .map(|(resource, caps)| ResourceKind {
+                group: resource.group,
+                version: resource.version,
+                kind: resource.kind,
+                plural: resource.plural,
+                namespaced: caps.scope == Scope::Namespaced,
+                verbs: caps.operations,
+            })
+            .filter(|kind| namespaced.map(|n| n == kind.namespaced).unwrap_or(true))
+            .collect();
+        kinds.sort_by(|a, b| a.kind.cmp(&b.kind).then(a.group.cmp(&b.group)));
+        Ok(kinds)
+    }
+
+    pub async fn get(client: Client, target: &ResourceRef) -> Result<Value, KubiousError> {
+        let (api, resource) = object_api(client, target).await?;
+        let object = api
+            .get(target.name.as_str())
+            .await
+            .context(format!("Failed to get {} {}", target.kind, target.name))?;
+        to_item(object, &resource).ok_or("Failed to serialize object.".into())
+    }
+
+    pub async fn delete(client: Client, target: &ResourceRef) -> Result<(), KubiousError> {
+        if crd_deletion::is_crd(target) {
+            return Err(CRD_DELETE_REFUSED.to_string().into());
+        }
+        let (api, _) = object_api(client, target).await?;
+        api.delete(target.name.as_str(), &DeleteParams::background())
+            .await
+            .map(|_| ())
+            .context(format!("Failed to delete {} {}", target.kind, target.name))
+    }
+}
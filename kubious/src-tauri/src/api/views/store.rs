@@ -0,0 +1,478 @@
+pub mod view_store {
+    use std::{
+        cmp,
+        collections::{BTreeMap, HashMap},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard,
+        },
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use tauri::async_runtime::JoinHandle;
+
+    use crate::{
+        api::views::printer_columns::{self, PrinterColumn},
+        util::{
+            lock_recovery::recover,
+            resource_health::{parse_health, HealthBadge},
+            time_normalization::{NormalizedTime, TimeContext},
+            value_path::{compare_values, lookup, parse_path},
+        },
+    };
+
+    /// CPU (cores) and memory (bytes) usage joined from the metrics API.
+    #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+    pub struct ResourceUsage {
+        pub cpu: f64,
+        pub memory: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct SortSpec {
+        pub field: String,
+        pub descending: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ViewSpec {
+        pub group: String,
+        pub version: String,
+        pub kind: String,
+        pub namespace: Option<String>,
+        pub label_selector: Option<String>,
+        pub filter: Option<String>,
+        pub sort: Option<SortSpec>,
+    }
+
+    /// A cached collection plus the filtered, sorted row order served to the
+    /// frontend in windows.
+    #[derive(Clone, Debug)]
+    pub struct View {
+        pub id: String,
+        pub cluster: String,
+        pub spec: ViewSpec,
+        pub items: Vec<Value>,
+        pub rows: Vec<usize>,
+        pub usage: HashMap<String, ResourceUsage>,
+        pub fetched: u64,
+        /// The cluster's detected UTC offset in minutes.
+        pub utc_offset: Option<i32>,
+        /// Whether the kind is namespaced; cluster-scoped views ignore
+        /// `spec.namespace`.
+        pub namespaced: bool,
+        /// Printer columns of a custom resource kind.
+        pub columns: Vec<PrinterColumn>,
+        /// Position in `items` by uid.
+        index: HashMap<String, usize>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ViewSummary {
+        pub id: String,
+        pub cluster: String,
+        pub spec: ViewSpec,
+        pub total: usize,
+        pub cached: usize,
+        pub fetched: u64,
+        pub utc_offset: Option<i32>,
+        pub namespaced: bool,
+        pub columns: Vec<PrinterColumn>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ViewWindow {
+        pub id: String,
+        pub total: usize,
+        pub offset: usize,
+        pub rows: Vec<Value>,
+        /// Health badges aligned with `rows`.
+        pub health: Vec<HealthBadge>,
+        /// Normalized timestamps by field path, aligned with `rows`.
+        pub timestamps: Vec<BTreeMap<String, NormalizedTime>>,
+        /// Printer column values per row, in the order of the view's
+        /// `columns`.
+        pub cells: Vec<Vec<Value>>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct GroupCount {
+        pub key: String,
+        pub count: usize,
+    }
+
+    pub fn usage_key(item: &Value) -> String {
+        format!(
+            "{}/{}",
+            item["metadata"]["namespace"].as_str().unwrap_or_default(),
+            item["metadata"]["name"].as_str().unwrap_or_default()
+        )
+    }
+
+    fn restart_count(item: &Value) -> u64 {
+        ["containerStatuses", "initContainerStatuses"]
+            .iter()
+            .flat_map(|field| item["status"][field].as_array().into_iter().flatten())
+            .map(|status| status["restartCount"].as_u64().unwrap_or(0))
+            .sum()
+    }
+
+    fn status_of(item: &Value) -> String {
+        if item["metadata"]["deletionTimestamp"].is_string() {
+            return "Terminating".to_string();
+        }
+        if let Some(phase) = item["status"]["phase"].as_str() {
+            return phase.to_string();
+        }
+        item["status"]["conditions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|c| c["type"] == "Ready" || c["type"] == "Available")
+            .map(|c| match c["status"].as_str() {
+                Some("True") => "Ready".to_string(),
+                _ => "NotReady".to_string(),
+            })
+            .unwrap_or("Unknown".to_string())
+    }
+
+    fn uid(item: &Value) -> Option<&str> {
+        item["metadata"]["uid"].as_str()
+    }
+
+    /// Orders two sort keys the way the view lists them.
+    fn ordering(sort: &SortSpec, a: Option<&Value>, b: Option<&Value>) -> cmp::Ordering {
+        let mut ordering = compare_values(a, b);
+        // Older objects have smaller timestamps, so age runs the other way.
+        if sort.field == "age" {
+            ordering = ordering.reverse();
+        }
+        if sort.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    fn matches_filter(item: &Value, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        ["name", "namespace"].iter().any(|field| {
+            item["metadata"][field]
+                .as_str()
+                .map(|v| v.to_lowercase().contains(filter.as_str()))
+                .unwrap_or(false)
+        })
+    }
+
+    impl View {
+        pub fn new(
+            id: String,
+            cluster: String,
+            spec: ViewSpec,
+            items: Vec<Value>,
+            usage: HashMap<String, ResourceUsage>,
+        ) -> Self {
+            let mut view = View {
+                id,
+                cluster,
+                spec,
+                items: Vec::new(),
+                rows: Vec::new(),
+                usage: HashMap::new(),
+                fetched: 0,
+                utc_offset: None,
+                namespaced: true,
+                columns: Vec::new(),
+                index: HashMap::new(),
+            };
+            view.replace_items(items, usage);
+            view
+        }
+
+        pub fn replace_items(&mut self, items: Vec<Value>, usage: HashMap<String, ResourceUsage>) {
+            self.index = items
+                .iter()
+                .enumerate()
+                .filter_map(|(i, item)| Some((uid(item)?.to_string(), i)))
+                .collect();
+            self.items = items;
+            self.usage = usage;
+            self.fetched = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            self.rebuild();
+        }
+
+        /// Replaces the items of one namespace, keeping those of others, as
+        /// when a view is watched one namespace at a time. `None` replaces
+        /// every item.
+        pub fn replace_scope(&mut self, namespace: Option<&str>, items: Vec<Value>) {
+            let mut kept: Vec<Value> = match namespace {
+                Some(ns) => self
+                    .items
+                    .drain(..)
+                    .filter(|item| item["metadata"]["namespace"].as_str() != Some(ns))
+                    .collect(),
+                None => Vec::new(),
+            };
+            kept.extend(items);
+            let usage = self.usage.clone();
+            self.replace_items(kept, usage);
+        }
+
+        /// Where the item at `index` sits, or would sit, in `rows`. Rows
+        /// are kept ordered by sort key and then by item position, the order
+        /// `rebuild` produces.
+        fn row_position(&self, index: usize) -> usize {
+            match &self.spec.sort {
+                Some(sort) => {
+                    let key = self.field_value(&self.items[index], sort.field.as_str());
+                    self.rows.partition_point(|row| {
+                        let row_key = self.field_value(&self.items[*row], sort.field.as_str());
+                        ordering(sort, row_key.as_ref(), key.as_ref()).then(row.cmp(&index))
+                            == cmp::Ordering::Less
+                    })
+                }
+                None => self.rows.partition_point(|row| *row < index),
+            }
+        }
+
+        fn remove_row(&mut self, index: usize) {
+            let position = self.row_position(index);
+            if self.rows.get(position) == Some(&index) {
+                self.rows.remove(position);
+            }
+        }
+
+        fn insert_row(&mut self, index: usize) {
+            let matches = self
+                .spec
+                .filter
+                .as_ref()
+                .is_none_or(|f| matches_filter(&self.items[index], f.as_str()));
+            if matches {
+                let position = self.row_position(index);
+                self.rows.insert(position, index);
+            }
+        }
+
+        /// Adds or replaces a single item from a watch event, moving only its
+        /// own row.
+        pub fn upsert(&mut self, item: Value) {
+            let known = uid(&item).and_then(|uid| self.index.get(uid)).copied();
+            let index = match known {
+                Some(index) => {
+                    self.remove_row(index);
+                    self.items[index] = item;
+                    index
+                }
+                None => {
+                    if let Some(uid) = uid(&item) {
+                        self.index.insert(uid.to_string(), self.items.len());
+                    }
+                    self.items.push(item);
+                    self.items.len() - 1
+                }
+            };
+            self.insert_row(index);
+        }
+
+        /// Drops a single item from a watch event.
+        pub fn remove_item(&mut self, item: &Value) {
+            let Some(index) = uid(item).and_then(|uid| self.index.remove(uid)) else {
+                return;
+            };
+            self.remove_row(index);
+            self.items.remove(index);
+            // Every later item moved up by one.
+            for row in self.rows.iter_mut().filter(|row| **row > index) {
+                *row -= 1;
+            }
+            for i in self.index.values_mut().filter(|i| **i > index) {
+                *i -= 1;
+            }
+        }
+
+        /// Recomputes the row order from the cached items and current spec.
+        pub fn rebuild(&mut self) {
+            let mut rows: Vec<usize> = (0..self.items.len())
+                .filter(|i| {
+                    self.spec
+                        .filter
+                        .as_ref()
+                        .map(|f| matches_filter(&self.items[*i], f.as_str()))
+                        .unwrap_or(true)
+                })
+                .collect();
+            if let Some(sort) = &self.spec.sort {
+                let keys: HashMap<usize, Value> = rows
+                    .iter()
+                    .filter_map(|i| {
+                        self.field_value(&self.items[*i], sort.field.as_str())
+                            .map(|v| (*i, v))
+                    })
+                    .collect();
+                rows.sort_by(|a, b| ordering(sort, keys.get(a), keys.get(b)));
+            }
+            self.rows = rows;
+        }
+
+        /// Resolves a sort/group field. Besides plain field paths this accepts
+        /// the derived fields `age`, `restarts`, `cpu`, `memory`, `namespace`,
+        /// `node`, `status` and `health`, and `column:<name>` for a printer
+        /// column.
+        fn field_value(&self, item: &Value, field: &str) -> Option<Value> {
+            match field {
+                "age" => Some(item["metadata"]["creationTimestamp"].clone()),
+                "restarts" => Some(json!(restart_count(item))),
+                "cpu" => self.usage.get(&usage_key(item)).map(|u| json!(u.cpu)),
+                "memory" => self.usage.get(&usage_key(item)).map(|u| json!(u.memory)),
+                "namespace" => Some(item["metadata"]["namespace"].clone()),
+                "node" => Some(item["spec"]["nodeName"].clone()),
+                "status" => Some(json!(status_of(item))),
+                "health" => Some(json!(parse_health(item).state)),
+                column if column.starts_with("column:") => self
+                    .columns
+                    .iter()
+                    .find(|c| Some(c.name.as_str()) == column.strip_prefix("column:"))
+                    .map(|c| printer_columns::cell(c, item)),
+                path => lookup(item, &parse_path(path)).cloned(),
+            }
+        }
+
+        /// Counts the filtered rows per distinct value of `field`, largest
+        /// groups first.
+        pub fn aggregate(&self, field: &str) -> Vec<GroupCount> {
+            let mut counts: HashMap<String, usize> = HashMap::new();
+            for i in &self.rows {
+                let key = match self.field_value(&self.items[*i], field) {
+                    Some(Value::String(s)) => s,
+                    Some(Value::Null) | None => "<none>".to_string(),
+                    Some(other) => other.to_string(),
+                };
+                *counts.entry(key).or_insert(0) += 1;
+            }
+            let mut groups: Vec<GroupCount> = counts
+                .into_iter()
+                .map(|(key, count)| GroupCount { key, count })
+                .collect();
+            groups.sort_by(|a, b| b.count.cmp(&a.count).then(a.key.cmp(&b.key)));
+            groups
+        }
+
+        /// `local_offset` is the viewer's UTC offset in minutes.
+        pub fn window(&self, offset: usize, limit: usize, local_offset: Option<i32>) -> ViewWindow {
+            let rows: Vec<Value> = self
+                .rows
+                .iter()
+                .skip(offset)
+                .take(limit)
+                .map(|i| self.items[*i].clone())
+                .collect();
+            let times = TimeContext::new(local_offset, self.utc_offset);
+            ViewWindow {
+                id: self.id.clone(),
+                total: self.rows.len(),
+                offset,
+                health: rows.iter().map(parse_health).collect(),
+                timestamps: rows.iter().map(|row| times.normalize_item(row)).collect(),
+                cells: rows
+                    .iter()
+                    .map(|row| printer_columns::cells(&self.columns, row))
+                    .collect(),
+                rows,
+            }
+        }
+
+        pub fn summary(&self) -> ViewSummary {
+            ViewSummary {
+                id: self.id.clone(),
+                cluster: self.cluster.clone(),
+                spec: self.spec.clone(),
+                total: self.rows.len(),
+                cached: self.items.len(),
+                fetched: self.fetched,
+                utc_offset: self.utc_offset,
+                namespaced: self.namespaced,
+                columns: self.columns.clone(),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub struct ViewStore {
+        views: Mutex<HashMap<String, View>>,
+        watches: Mutex<HashMap<String, JoinHandle<()>>>,
+        counter: AtomicU64,
+    }
+
+    impl ViewStore {
+        fn views_mutable(&self) -> MutexGuard<'_, HashMap<String, View>> {
+            recover(&self.views)
+        }
+
+        fn watches_mutable(&self) -> MutexGuard<'_, HashMap<String, JoinHandle<()>>> {
+            recover(&self.watches)
+        }
+
+        pub fn is_watched(&self, id: &str) -> bool {
+            self.watches_mutable().contains_key(id)
+        }
+
+        /// Records the watch task for a view. Returns `false` (and aborts the
+        /// task) if the view is already watched.
+        pub fn set_watch(&self, id: &str, task: JoinHandle<()>) -> bool {
+            let mut watches = self.watches_mutable();
+            if watches.contains_key(id) {
+                task.abort();
+                return false;
+            }
+            watches.insert(id.to_string(), task);
+            true
+        }
+
+        pub fn stop_watch(&self, id: &str) -> bool {
+            let removed = self.watches_mutable().remove(id);
+            if let Some(task) = removed {
+                task.abort();
+                true
+            } else {
+                false
+            }
+        }
+
+        pub fn next_id(&self) -> String {
+            format!("view-{}", self.counter.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        pub fn insert(&self, view: View) -> ViewSummary {
+            let summary = view.summary();
+            self.views_mutable().insert(view.id.clone(), view);
+            summary
+        }
+
+        /// Reads a view in place, without copying its items.
+        pub fn read<T>(&self, id: &str, f: impl FnOnce(&View) -> T) -> Option<T> {
+            self.views_mutable().get(id).map(f)
+        }
+
+        pub fn with_view<T>(&self, id: &str, f: impl FnOnce(&mut View) -> T) -> Option<T> {
+            self.views_mutable().get_mut(id).map(f)
+        }
+
+        pub fn remove(&self, id: &str) -> bool {
+            self.stop_watch(id);
+            self.views_mutable().remove(id).is_some()
+        }
+
+        pub fn summaries(&self) -> Vec<ViewSummary> {
+            let mut summaries: Vec<ViewSummary> =
+                self.views_mutable().values().map(|v| v.summary()).collect();
+            summaries.sort_by(|a, b| a.id.cmp(&b.id));
+            summaries
+        }
+    }
+}
@@ -0,0 +1,54 @@
+pub mod view_tenancy {
+    use crate::{
+        api::{app_settings::TenancyFilter, views::view_store::ViewSpec},
+        util::kubious_error::KubiousError,
+    };
+
+    /// Where a view lists and watches from once the active tenancy filter is
+    /// applied. `None` stands for all namespaces (or a cluster-scoped kind).
+    #[derive(Clone, Debug)]
+    pub struct ViewScope {
+        pub namespaces: Vec<Option<String>>,
+        pub label_selector: Option<String>,
+    }
+
+    /// Narrows a view spec to the active tenancy filter. A view across all
+    /// namespaces becomes one list per namespace of the filter, so tenants
+    /// without cluster-wide list permission can still use it.
+    pub fn scope(
+        filter: Option<&TenancyFilter>,
+        spec: &ViewSpec,
+        namespaced: bool,
+    ) -> Result<ViewScope, KubiousError> {
+        let Some(filter) = filter else {
+            return Ok(ViewScope {
+                namespaces: vec![spec.namespace.clone().filter(|_| namespaced)],
+                label_selector: spec.label_selector.clone(),
+            });
+        };
+        if !filter.allows_kind(spec.kind.as_str()) {
+            return Err(format!(
+                "{} is outside the tenancy filter {}",
+                spec.kind, filter.name
+            )
+            .into());
+        }
+        let namespaces = match (namespaced, &spec.namespace) {
+            (false, _) => vec![None],
+            (true, Some(ns)) if filter.allows_namespace(ns.as_str()) => vec![Some(ns.clone())],
+            (true, Some(ns)) => {
+                return Err(format!(
+                    "Namespace {} is outside the tenancy filter {}",
+                    ns, filter.name
+                )
+                .into())
+            }
+            (true, None) if filter.namespaces.is_empty() => vec![None],
+            (true, None) => filter.namespaces.iter().cloned().map(Some).collect(),
+        };
+        Ok(ViewScope {
+            namespaces,
+            label_selector: filter.label_selector(spec.label_selector.as_deref()),
+        })
+    }
+}
@@ -0,0 +1,191 @@
+pub mod view_watch {
+    use futures::{future::join_all, StreamExt};
+    use kube::{
+        api::DynamicObject,
+        discovery::{ApiResource, Scope},
+        runtime::watcher::{self, watcher, Event},
+        Api, Client,
+    };
+    use serde_json::Value;
+    use tauri::{AppHandle, Emitter, Manager};
+
+    use crate::{
+        api::{
+            app_state::AppState,
+            views::{
+                resource_access,
+                view_store::{View, ViewStore},
+                view_tenancy,
+            },
+        },
+        util::{
+            stream_reconnect::{BackoffPolicy, Reconnector},
+            watch_gaps::{GapTracker, SyntheticEvent},
+        },
+    };
+
+    /// Event channel carrying the `ViewSummary` of a watched view after each
+    /// change, so the frontend knows to re-query its window.
+    pub const VIEW_CHANGED_EVENT: &str = "view-changed";
+
+    fn changed(handle: &AppHandle, id: &str, apply: impl FnOnce(&mut View)) {
+        let summary = handle.state::<ViewStore>().with_view(id, |view| {
+            apply(view);
+            view.summary()
+        });
+        if let Some(summary) = summary {
+            let _ = handle.emit(VIEW_CHANGED_EVENT, summary);
+        }
+    }
+
+    /// Watches one namespace of a view (or all of it for `None`), feeding
+    /// changes into the view until the stream ends.
+    async fn watch_scope(
+        handle: AppHandle,
+        api: Api<DynamicObject>,
+        resource: ApiResource,
+        config: watcher::Config,
+        id: String,
+        namespace: Option<String>,
+    ) {
+        let stream_name = match &namespace {
+            Some(ns) => format!("{}/{}", id, ns),
+            None => id.clone(),
+        };
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "views",
+            stream_name.as_str(),
+            BackoffPolicy::default(),
+        );
+        let mut gaps = GapTracker::new(handle.clone(), "views", stream_name.as_str());
+        let to_item = |object: DynamicObject| resource_access::to_item(object, &resource);
+
+        let mut initial: Vec<Value> = Vec::new();
+        let mut stream = watcher(api, config).boxed();
+        while let Some(event) = stream.next().await {
+            match event {
+                Ok(Event::Init) => {
+                    initial.clear();
+                    gaps.init();
+                }
+                Ok(Event::InitApply(object)) => {
+                    if let Some(item) = to_item(object) {
+                        gaps.init_apply(&item);
+                        initial.push(item);
+                    }
+                }
+                Ok(Event::InitDone) => {
+                    let items = std::mem::take(&mut initial);
+                    match gaps.init_done() {
+                        // After a gap, only the missed changes are applied so
+                        // the view keeps its order and selection.
+                        Some(events) => changed(&handle, id.as_str(), |view| {
+                            for event in events {
+                                match event {
+                                    SyntheticEvent::Added { object }
+                                    | SyntheticEvent::Modified { object } => view.upsert(object),
+                                    SyntheticEvent::Removed { object } => view.remove_item(&object),
+                                }
+                            }
+                        }),
+                        None => changed(&handle, id.as_str(), |view| {
+                            view.replace_scope(namespace.as_deref(), items)
+                        }),
+                    }
+                    reconnector.connected();
+                }
+                Ok(Event::Apply(object)) => {
+                    if let Some(item) = to_item(object) {
+                        gaps.applied(&item);
+                        changed(&handle, id.as_str(), |view| view.upsert(item));
+                    }
+                }
+                Ok(Event::Delete(object)) => {
+                    if let Some(item) = to_item(object) {
+                        gaps.deleted(&item);
+                        changed(&handle, id.as_str(), |view| view.remove_item(&item));
+                    }
+                }
+                Err(error) => {
+                    gaps.failed(error.to_string().as_str());
+                    if !reconnector.retry(error.to_string().as_str()).await {
+                        break;
+                    }
+                }
+            }
+        }
+        reconnector.closed();
+    }
+
+    async fn watch(handle: AppHandle, client: Client, id: String) {
+        let Some((cluster, spec)) = handle.state::<ViewStore>().read(id.as_str(), |view| {
+            (view.cluster.clone(), view.spec.clone())
+        }) else {
+            return;
+        };
+        let mut reconnector = Reconnector::new(
+            handle.clone(),
+            "views",
+            id.as_str(),
+            BackoffPolicy::default(),
+        );
+        let (resource, caps) = loop {
+            match resource_access::resolve(
+                &client,
+                spec.group.as_str(),
+                spec.version.as_str(),
+                spec.kind.as_str(),
+            )
+            .await
+            {
+                Ok(resolved) => break resolved,
+                Err(error) => {
+                    if !reconnector.retry(error.message()).await {
+                        return;
+                    }
+                }
+            }
+        };
+        let filter = handle
+            .state::<AppState>()
+            .active_tenancy_filter(cluster.as_str());
+        let scope =
+            match view_tenancy::scope(filter.as_ref(), &spec, caps.scope == Scope::Namespaced) {
+                Ok(scope) => scope,
+                Err(error) => {
+                    reconnector.failed(error.message());
+                    handle.state::<ViewStore>().stop_watch(id.as_str());
+                    return;
+                }
+            };
+        let mut config = watcher::Config::default();
+        if let Some(selector) = &scope.label_selector {
+            config = config.labels(selector.as_str());
+        }
+
+        join_all(scope.namespaces.into_iter().map(|namespace| {
+            watch_scope(
+                handle.clone(),
+                resource_access::api_for(client.clone(), &resource, &caps, namespace.as_deref()),
+                resource.clone(),
+                config.clone(),
+                id.clone(),
+                namespace,
+            )
+        }))
+        .await;
+        handle.state::<ViewStore>().stop_watch(id.as_str());
+    }
+
+    /// Keeps a view's items current from a watch until the view is closed
+    /// or unwatched. Returns `false` if the view was already watched.
+    pub fn start(handle: &AppHandle, client: Client, id: &str) -> bool {
+        let views = handle.state::<ViewStore>();
+        if views.is_watched(id) {
+            return false;
+        }
+        let task = tauri::async_runtime::spawn(watch(handle.clone(), client, id.to_string()));
+        views.set_watch(id, task)
+    }
+}
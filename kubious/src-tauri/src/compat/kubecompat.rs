@@ -2,11 +2,181 @@ pub mod kube_compat {
     use http::{
         HeaderName, HeaderValue, Uri,
     };
-    use std::{str::FromStr, time::Duration};
+    use std::{
+        path::{Path, PathBuf},
+        str::FromStr,
+        time::Duration,
+    };
 
-    use kube::{config::AuthInfo, Config};
+    use kube::{
+        config::{AuthInfo, Kubeconfig},
+        Config,
+    };
     use serde::{Deserialize, Serialize};
 
+    fn home_dir() -> Option<PathBuf> {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+    }
+
+    fn is_wsl() -> bool {
+        cfg!(target_os = "linux")
+            && (std::env::var_os("WSL_DISTRO_NAME").is_some()
+                || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+                    .map(|release| release.to_lowercase().contains("microsoft"))
+                    .unwrap_or(false))
+    }
+
+    /// Splits `C:\foo` or `C:/foo` into the drive letter and the rest.
+    fn windows_drive(path: &str) -> Option<(char, &str)> {
+        let mut chars = path.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(drive), Some(':'), Some('\\' | '/')) if drive.is_ascii_alphabetic() => {
+                Some((drive, &path[3..]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Splits `/mnt/c/foo` into the drive letter and the rest.
+    fn wsl_mount(path: &str) -> Option<(char, &str)> {
+        let rest = path.strip_prefix("/mnt/")?;
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(drive), Some('/')) if drive.is_ascii_alphabetic() => Some((drive, &rest[2..])),
+            (Some(drive), None) if drive.is_ascii_alphabetic() => Some((drive, "")),
+            _ => None,
+        }
+    }
+
+    /// Resolves a file reference from a kubeconfig so it can be opened on this
+    /// machine. Expands `~`, maps Windows drive paths to `/mnt/<drive>` under
+    /// WSL (and back on Windows), maps `\\wsl$` shares to the distro's own
+    /// paths, and joins relative paths onto the kubeconfig's directory.
+    /// Named pipes are left alone.
+    pub fn resolve_path(path: &str, base: Option<&Path>) -> String {
+        let path = path.trim();
+        if path.is_empty() || path.starts_with("\\\\.\\pipe\\") || path.starts_with("//./pipe/") {
+            return path.to_string();
+        }
+
+        let expanded = match path.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => match home_dir() {
+                Some(home) => home
+                    .join(rest.trim_start_matches(['/', '\\']))
+                    .to_string_lossy()
+                    .to_string(),
+                None => path.to_string(),
+            },
+            _ => path.to_string(),
+        };
+
+        let mapped = if cfg!(windows) {
+            match wsl_mount(expanded.as_str()) {
+                Some((drive, rest)) => {
+                    format!("{}:\\{}", drive.to_ascii_uppercase(), rest.replace('/', "\\"))
+                }
+                None => expanded,
+            }
+        } else if let Some((drive, rest)) = windows_drive(expanded.as_str()) {
+            if is_wsl() {
+                format!("/mnt/{}/{}", drive.to_ascii_lowercase(), rest.replace('\\', "/"))
+            } else {
+                expanded
+            }
+        } else if let Some(share) = expanded
+            .strip_prefix("\\\\wsl$\\")
+            .or_else(|| expanded.strip_prefix("\\\\wsl.localhost\\"))
+            .filter(|_| is_wsl())
+        {
+            match share.split_once('\\') {
+                Some((_distro, rest)) => format!("/{}", rest.replace('\\', "/")),
+                None => "/".to_string(),
+            }
+        } else if expanded.contains('\\') && !expanded.contains('/') {
+            expanded.replace('\\', "/")
+        } else {
+            expanded
+        };
+
+        match base {
+            Some(dir) if Path::new(mapped.as_str()).is_relative() => {
+                dir.join(mapped.as_str()).to_string_lossy().to_string()
+            }
+            _ => mapped,
+        }
+    }
+
+    /// Resolves the certificate, key, token and exec command references of a
+    /// user entry. Bare exec commands such as `aws` are looked up on PATH and
+    /// stay untouched.
+    pub fn resolve_auth_paths(mut auth: AuthInfo, base: Option<&Path>) -> AuthInfo {
+        for field in [
+            &mut auth.client_certificate,
+            &mut auth.client_key,
+            &mut auth.token_file,
+        ] {
+            *field = field.as_deref().map(|p| resolve_path(p, base));
+        }
+        if let Some(exec) = auth.exec.as_mut() {
+            exec.command = exec.command.as_deref().map(|command| {
+                if command.starts_with('~') || command.contains(['/', '\\']) {
+                    resolve_path(command, base)
+                } else {
+                    command.to_string()
+                }
+            });
+        }
+        auth
+    }
+
+    /// Resolves every file reference in an imported kubeconfig. `source` is
+    /// the file the kubeconfig was read from, if known, so relative paths
+    /// resolve like they do for kubectl.
+    pub fn resolve_kubeconfig_paths(mut config: Kubeconfig, source: Option<&Path>) -> Kubeconfig {
+        let base = source.and_then(|s| s.parent());
+        for named in config.clusters.iter_mut() {
+            if let Some(cluster) = named.cluster.as_mut() {
+                cluster.certificate_authority = cluster
+                    .certificate_authority
+                    .as_deref()
+                    .map(|p| resolve_path(p, base));
+            }
+        }
+        for named in config.auth_infos.iter_mut() {
+            named.auth_info = named.auth_info.take().map(|auth| resolve_auth_paths(auth, base));
+        }
+        config
+    }
+
+    /// Connection keep-alive tuning for clusters reached through NAT or VPN
+    /// gateways that silently drop idle flows, which otherwise leaves watches
+    /// hanging until the read timeout.
+    #[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+    pub struct KeepAliveSettings {
+        /// Offer HTTP/2 via ALPN. Without it the client only speaks HTTP/1.1
+        /// and the HTTP/2 ping settings below have no effect.
+        #[serde(default)]
+        pub http2: bool,
+        /// Interval between HTTP/2 PING frames.
+        pub http2_interval_secs: Option<u64>,
+        /// How long to wait for a PING acknowledgement before closing the
+        /// connection.
+        pub http2_timeout_secs: Option<u64>,
+        /// Also ping while no request or watch is open.
+        #[serde(default)]
+        pub http2_while_idle: bool,
+        /// SO_KEEPALIVE idle time for the TCP socket.
+        pub tcp_keepalive_secs: Option<u64>,
+    }
+
+    impl KeepAliveSettings {
+        pub fn is_default(&self) -> bool {
+            *self == KeepAliveSettings::default()
+        }
+    }
+
     #[derive(Serialize, Deserialize, Clone, Debug)]
     pub struct KubeConfig {
         pub cluster_url: String,
@@ -20,6 +190,8 @@ pub mod kube_compat {
         pub proxy_url: Option<String>,
         pub tls_server_name: Option<String>,
         pub headers: Vec<(String, Option<String>)>,
+        #[serde(default)]
+        pub keep_alive: KeepAliveSettings,
     }
 
     impl From<Config> for KubeConfig {
@@ -48,6 +220,7 @@ pub mod kube_compat {
                         )
                     })
                     .collect(),
+                keep_alive: KeepAliveSettings::default(),
             }
         }
     }
@@ -63,7 +236,7 @@ pub mod kube_compat {
                 connect_timeout: self.connect_timeout,
                 write_timeout: self.write_timeout,
                 accept_invalid_certs: self.accept_invalid_certs,
-                auth_info: self.auth_info,
+                auth_info: resolve_auth_paths(self.auth_info, None),
                 proxy_url: match self.proxy_url {
                     Some(p) => Some(Uri::from_str(p.as_str()).expect("URI Parsing failed")),
                     None => None,
@@ -1,50 +1,69 @@
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 mod api;
-use std::{fs::{self, File}, io::{Read, Write}};
-
-use api::{app_state::AppState, execute_command, ApiCommand, CommandHandler, CommandResult};
-use tauri::{AppHandle, Manager};
+use api::{app_command_cache::CommandCache, app_requests::RequestRecorder, app_state::AppState, app_support::SupportLog, app_terminal, discovery_cache::DiscoveryCache, event_feeds::EventFeeds, execute_command, exec_sessions::ExecSessions, incident_journal::IncidentJournal, favorite_watches::{self, FavoriteWatches}, log_sessions::LogSessions, metrics_history::{self, MetricsHistory}, multi_cluster_logs::LogTails, namespace_budgets::{self, BudgetAlerts}, port_forwards::PortForwardManager, scratch_namespaces::{self, ScratchCleanup}, session_limits::{self, SessionReaper}, token_expiry::{self, TokenExpiries}, rbac_capabilities::CapabilityCache, resource_deletion::DeleteConfirmations, resource_informers::Informers, rollout_triggers::{self, RolloutTriggers}, termination_history::TerminationWatches, view_store::ViewStore, ApiCommand, CommandHandler, CommandResult};
+use tauri::{AppHandle, Manager, RunEvent};
 
 mod compat;
+mod util;
 
 #[tauri::command]
-async fn execute_api_command(app_handle: AppHandle, command: ApiCommand) -> CommandResult {
-    execute_command(app_handle, command).await
+async fn execute_api_command(app_handle: AppHandle, command: ApiCommand, force_refresh: Option<bool>) -> CommandResult {
+    execute_command(app_handle, command, force_refresh.unwrap_or(false)).await
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .setup(|app| {
-            let resolver = app.path();
-            let config_root = resolver.app_config_dir().unwrap();
-            if !config_root.exists() {
-                fs::create_dir(config_root).expect("Unable to create config directory");
-            }
-            if !resolver.parse("$APPCONFIG/config.json").unwrap().exists() {
-                let mut config_file = File::create(resolver.parse("$APPCONFIG/config.json").unwrap()).expect("Failed to create config.json");
-
-                let new_state = AppState::new();
-                let registered = tauri::async_runtime::block_on(async move {
-                    new_state.register_default().await;
-                    new_state
-                });
-
-                config_file.write_all(registered.to_json().expect("Failed to serialize new state").as_bytes()).expect("Failed to write to config.json");
-            }
-
-            let mut config_file = File::open(resolver.parse("$APPCONFIG/config.json").unwrap()).expect("Failed to open config.json");
-            let mut contents = String::new();
-            config_file.read_to_string(&mut contents).expect("Failed to read config.json");
-            app.manage(AppState::from_json(contents.as_str()).expect("Failed to parse config"));
+            app_terminal::clear_contexts(app.handle());
+            let state = tauri::async_runtime::block_on(AppState::load_state(app.handle().clone()))?;
+            app.manage(state);
+            tauri::async_runtime::spawn(favorite_watches::sync(app.handle().clone()));
+            tauri::async_runtime::spawn(rollout_triggers::sync(app.handle().clone()));
+            tauri::async_runtime::spawn(namespace_budgets::start(app.handle().clone()));
+            tauri::async_runtime::spawn(scratch_namespaces::start(app.handle().clone()));
+            tauri::async_runtime::spawn(session_limits::start(app.handle().clone()));
+            tauri::async_runtime::spawn(token_expiry::start(app.handle().clone()));
+            tauri::async_runtime::spawn(metrics_history::start(app.handle().clone()));
 
             Ok(())
         })
+        .manage(ViewStore::default())
+        .manage(TerminationWatches::default())
+        .manage(SupportLog::default())
+        .manage(RequestRecorder::default())
+        .manage(CapabilityCache::default())
+        .manage(CommandCache::default())
+        .manage(DiscoveryCache::default())
+        .manage(LogTails::default())
+        .manage(LogSessions::default())
+        .manage(ExecSessions::default())
+        .manage(EventFeeds::default())
+        .manage(IncidentJournal::default())
+        .manage(MetricsHistory::default())
+        .manage(PortForwardManager::default())
+        .manage(Informers::default())
+        .manage(FavoriteWatches::default())
+        .manage(RolloutTriggers::default())
+        .manage(BudgetAlerts::default())
+        .manage(ScratchCleanup::default())
+        .manage(SessionReaper::default())
+        .manage(TokenExpiries::default())
+        .manage(DeleteConfirmations::default())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(tauri::generate_handler![execute_api_command])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|handle, event| {
+            if let RunEvent::Exit = event {
+                session_limits::stop_all(handle);
+                app_terminal::clear_contexts(handle);
+                if let Some(state) = handle.try_state::<AppState>() {
+                    state.flush_state(handle);
+                }
+            }
+        });
 }
@@ -0,0 +1,57 @@
+pub mod clock_skew {
+    use http::{header::DATE, Request};
+    use k8s_openapi::chrono::{DateTime, Utc};
+    use kube::{client::Body, Client};
+    use serde::{Deserialize, Serialize};
+
+    use crate::util::api_warnings;
+
+    /// Skew beyond which token-based auth (OIDC, projected service account
+    /// tokens) starts rejecting otherwise valid credentials.
+    pub const SKEW_WARNING_SECONDS: i64 = 10;
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ClockSkew {
+        pub local_time: String,
+        pub server_time: String,
+        /// Server time minus local time; positive when the local clock is behind.
+        pub skew_seconds: i64,
+        pub round_trip_ms: i64,
+        pub significant: bool,
+    }
+
+    /// Compares the local clock with the `Date` header of a `/version`
+    /// request, using the midpoint of the round trip as local time. Also
+    /// reports significant skew as a command warning.
+    pub async fn measure(client: &Client) -> Option<ClockSkew> {
+        let request = Request::get("/version").body(Body::empty()).ok()?;
+        let sent = Utc::now();
+        let response = client.send(request).await.ok()?;
+        let received = Utc::now();
+        let date = response.headers().get(DATE)?.to_str().ok()?;
+        let server = DateTime::parse_from_rfc2822(date).ok()?.with_timezone(&Utc);
+
+        let round_trip = received - sent;
+        let local = sent + round_trip / 2;
+        let skew_seconds = (server - local).num_seconds();
+        let significant = skew_seconds.abs() > SKEW_WARNING_SECONDS;
+        if significant {
+            api_warnings::push(format!(
+                "Local clock is {} seconds {} the API server; token authentication may fail",
+                skew_seconds.abs(),
+                if skew_seconds > 0 {
+                    "behind"
+                } else {
+                    "ahead of"
+                }
+            ));
+        }
+        Some(ClockSkew {
+            local_time: local.to_rfc3339(),
+            server_time: server.to_rfc3339(),
+            skew_seconds,
+            round_trip_ms: round_trip.num_milliseconds(),
+            significant,
+        })
+    }
+}
@@ -0,0 +1,180 @@
+pub mod resource_health {
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub enum HealthState {
+        Healthy,
+        Progressing,
+        Degraded,
+        Suspended,
+        Unknown,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct HealthBadge {
+        pub state: HealthState,
+        /// The condition or status field the state was derived from.
+        pub source: Option<String>,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+    }
+
+    /// Conditions whose `True` status means the object is working, most
+    /// authoritative first.
+    const POSITIVE_CONDITIONS: [&str; 7] = [
+        "Ready",
+        "Healthy",
+        "Available",
+        "Succeeded",
+        "Complete",
+        "Established",
+        "Synced",
+    ];
+
+    /// Conditions whose `True` status means something is wrong (Flux `Stalled`,
+    /// operator-style `Degraded`).
+    const NEGATIVE_CONDITIONS: [&str; 4] = ["Stalled", "Degraded", "Failed", "Error"];
+
+    /// Conditions whose `True` status means a change is being rolled out
+    /// (Flux `Reconciling`, cert-manager `Issuing`).
+    const PROGRESS_CONDITIONS: [&str; 3] = ["Reconciling", "Progressing", "Issuing"];
+
+    fn badge(
+        state: HealthState,
+        source: &str,
+        reason: Option<&str>,
+        message: Option<&str>,
+    ) -> HealthBadge {
+        HealthBadge {
+            state,
+            source: Some(source.to_string()),
+            reason: reason.map(|r| r.to_string()),
+            message: message.map(|m| m.to_string()),
+        }
+    }
+
+    /// Maps free-form phase/state strings used by operators onto a state.
+    fn phase_state(phase: &str) -> Option<HealthState> {
+        match phase.to_lowercase().as_str() {
+            "healthy" | "ready" | "running" | "active" | "available" | "bound" | "succeeded"
+            | "success" | "complete" | "completed" | "deployed" | "established" | "synced" => {
+                Some(HealthState::Healthy)
+            }
+            "pending" | "progressing" | "provisioning" | "creating" | "updating"
+            | "reconciling" | "inprogress" | "in progress" | "initializing" | "waiting"
+            | "syncing" | "outofsync" => Some(HealthState::Progressing),
+            "degraded" | "failed" | "failure" | "error" | "errored" | "missing" | "unhealthy"
+            | "crashloopbackoff" | "lost" => Some(HealthState::Degraded),
+            "paused" | "suspended" | "stopped" | "hibernating" => Some(HealthState::Suspended),
+            _ => None,
+        }
+    }
+
+    fn condition_health(conditions: &[Value]) -> Option<HealthBadge> {
+        let find = |name: &str| conditions.iter().find(|c| c["type"].as_str() == Some(name));
+        let describe = |condition: &Value, state: HealthState| {
+            badge(
+                state,
+                condition["type"].as_str().unwrap_or_default(),
+                condition["reason"].as_str(),
+                condition["message"].as_str(),
+            )
+        };
+
+        for name in NEGATIVE_CONDITIONS {
+            if let Some(condition) = find(name).filter(|c| c["status"] == "True") {
+                return Some(describe(condition, HealthState::Degraded));
+            }
+        }
+        let progressing = PROGRESS_CONDITIONS
+            .iter()
+            .filter_map(|name| find(name))
+            .find(|c| c["status"] == "True" && c["reason"] != "NewReplicaSetAvailable");
+
+        for name in POSITIVE_CONDITIONS {
+            let Some(condition) = find(name) else {
+                continue;
+            };
+            return Some(match (condition["status"].as_str(), progressing) {
+                (Some("True"), _) => describe(condition, HealthState::Healthy),
+                (_, Some(progress)) => describe(progress, HealthState::Progressing),
+                // Ready=Unknown is how Flux and most operators say "working on it".
+                (Some("Unknown"), None) => describe(condition, HealthState::Progressing),
+                _ => describe(condition, HealthState::Degraded),
+            });
+        }
+        progressing.map(|c| describe(c, HealthState::Progressing))
+    }
+
+    /// Derives a health badge for any object from common status conventions:
+    /// `spec.suspend`, Argo CD's `status.health`, Ready-style conditions, and
+    /// finally `status.phase`/`status.state`. Objects whose controller has not
+    /// observed the latest generation are reported as progressing.
+    pub fn parse_health(item: &Value) -> HealthBadge {
+        let status = &item["status"];
+        if item["metadata"]["deletionTimestamp"].is_string() {
+            return badge(
+                HealthState::Progressing,
+                "metadata.deletionTimestamp",
+                Some("Terminating"),
+                None,
+            );
+        }
+        if item["spec"]["suspend"].as_bool() == Some(true) {
+            return badge(HealthState::Suspended, "spec.suspend", None, None);
+        }
+
+        let mut health = if let Some(argo) = status["health"]["status"].as_str() {
+            // Argo CD Applications and Argo Rollouts.
+            phase_state(argo).map(|state| {
+                badge(
+                    state,
+                    "status.health",
+                    None,
+                    status["health"]["message"].as_str(),
+                )
+            })
+        } else {
+            None
+        };
+        if health.is_none() {
+            health = status["conditions"]
+                .as_array()
+                .and_then(|conditions| condition_health(conditions));
+        }
+        if health.is_none() {
+            health = ["phase", "state"].iter().find_map(|field| {
+                let value = status[field].as_str()?;
+                phase_state(value).map(|state| {
+                    badge(
+                        state,
+                        format!("status.{}", field).as_str(),
+                        Some(value),
+                        status["message"].as_str(),
+                    )
+                })
+            });
+        }
+        let mut health = health.unwrap_or(HealthBadge {
+            state: HealthState::Unknown,
+            source: None,
+            reason: None,
+            message: None,
+        });
+
+        let generation = item["metadata"]["generation"].as_i64();
+        let observed = status["observedGeneration"].as_i64();
+        if let (Some(generation), Some(observed)) = (generation, observed) {
+            if observed < generation && health.state == HealthState::Healthy {
+                health = badge(
+                    HealthState::Progressing,
+                    "status.observedGeneration",
+                    Some("NotObserved"),
+                    Some("The controller has not processed the latest spec yet"),
+                );
+            }
+        }
+        health
+    }
+}
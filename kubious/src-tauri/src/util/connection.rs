@@ -0,0 +1,289 @@
+pub mod connection_tuning {
+    use std::time::{Duration, Instant};
+
+    use http::Request;
+    use hyper_rustls::{FixedServerNameResolver, HttpsConnector, MaybeHttpsStream};
+    use hyper_timeout::TimeoutConnector;
+    use hyper_util::{
+        client::legacy::{connect::HttpConnector, Client as HttpClient},
+        rt::{TokioExecutor, TokioTimer},
+    };
+    use kube::{
+        client::{Body, ClientBuilder, ConfigExt},
+        Client, Config,
+    };
+    use rustls::pki_types::ServerName;
+    use serde::{Deserialize, Serialize};
+    use tower::{service_fn, util::BoxService, BoxError, Service, ServiceBuilder};
+
+    use crate::compat::kube_compat::KeepAliveSettings;
+
+    /// Idle time after which many NAT gateways and VPN concentrators drop a
+    /// TCP flow without telling either side.
+    const NAT_IDLE_SECS: u64 = 300;
+
+    pub type TunedService =
+        BoxService<Request<Body>, http::Response<hyper::body::Incoming>, BoxError>;
+
+    fn tcp_connector(config: &Config, settings: &KeepAliveSettings) -> HttpConnector {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_keepalive(settings.tcp_keepalive_secs.map(Duration::from_secs));
+        http.set_connect_timeout(config.connect_timeout);
+        http
+    }
+
+    /// Connector for requests that may be upgraded to a websocket (exec,
+    /// attach, port-forward). Upgrades only work over HTTP/1.1, so this is
+    /// kube's own connector with only the socket options changed.
+    fn upgrade_connector(
+        config: &Config,
+        settings: &KeepAliveSettings,
+    ) -> Result<HttpsConnector<HttpConnector>, String> {
+        config
+            .rustls_https_connector_with_connector(tcp_connector(config, settings))
+            .map_err(|e| format!("Failed to set up TLS: {}", e))
+    }
+
+    /// Connector for every other request, which also offers h2 through ALPN
+    /// when HTTP/2 is enabled.
+    fn plain_connector(
+        config: &Config,
+        settings: &KeepAliveSettings,
+    ) -> Result<HttpsConnector<HttpConnector>, String> {
+        if !settings.http2 {
+            return upgrade_connector(config, settings);
+        }
+        let tls = config
+            .rustls_client_config()
+            .map_err(|e| format!("Failed to set up TLS: {}", e))?;
+        let mut builder = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_tls_config(tls)
+            .https_or_http();
+        if let Some(name) = config.tls_server_name.as_ref() {
+            let name = ServerName::try_from(name.clone())
+                .map_err(|e| format!("Invalid TLS server name {}: {}", name, e))?;
+            builder = builder.with_server_name_resolver(FixedServerNameResolver::new(name));
+        }
+        Ok(builder
+            .enable_all_versions()
+            .wrap_connector(tcp_connector(config, settings)))
+    }
+
+    fn http_client(
+        connector: HttpsConnector<HttpConnector>,
+        config: &Config,
+        settings: &KeepAliveSettings,
+    ) -> HttpClient<TimeoutConnector<HttpsConnector<HttpConnector>>, Body> {
+        let mut connector = TimeoutConnector::new(connector);
+        connector.set_connect_timeout(config.connect_timeout);
+        connector.set_read_timeout(config.read_timeout);
+        connector.set_write_timeout(config.write_timeout);
+
+        let mut http = HttpClient::builder(TokioExecutor::new());
+        http.timer(TokioTimer::new());
+        if settings.http2 {
+            http.http2_keep_alive_interval(settings.http2_interval_secs.map(Duration::from_secs))
+                .http2_keep_alive_while_idle(settings.http2_while_idle);
+            if let Some(timeout) = settings.http2_timeout_secs {
+                http.http2_keep_alive_timeout(Duration::from_secs(timeout));
+            }
+        }
+        http.build(connector)
+    }
+
+    fn tuned_builder(
+        config: &Config,
+        settings: &KeepAliveSettings,
+    ) -> Result<ClientBuilder<TunedService>, String> {
+        let plain = http_client(plain_connector(config, settings)?, config, settings);
+        let upgrades = http_client(upgrade_connector(config, settings)?, config, settings);
+        let http = service_fn(move |request: Request<Body>| {
+            if request.headers().contains_key(http::header::UPGRADE) {
+                upgrades.request(request)
+            } else {
+                plain.request(request)
+            }
+        });
+
+        let auth = config
+            .auth_layer()
+            .map_err(|e| format!("Failed to set up authentication: {}", e))?;
+        let headers = config
+            .extra_headers_layer()
+            .map_err(|e| format!("Invalid headers: {}", e))?;
+        let service = ServiceBuilder::new()
+            .layer(config.base_uri_layer())
+            .option_layer(auth)
+            .layer(headers)
+            .map_err(BoxError::from)
+            .service(http);
+        Ok(ClientBuilder::new(
+            BoxService::new(service),
+            config.default_namespace.clone(),
+        ))
+    }
+
+    /// Builds the same service stack as `ClientBuilder::try_from`, but with
+    /// the keep-alive settings applied to the socket and the HTTP/2
+    /// connection. Websocket upgrades always go out over HTTP/1.1. Proxied configs are not supported and return `None`, so
+    /// the caller falls back to the default stack.
+    pub fn client_builder(
+        config: &Config,
+        settings: &KeepAliveSettings,
+    ) -> Option<Result<ClientBuilder<TunedService>, String>> {
+        if settings.is_default() || config.proxy_url.is_some() {
+            return None;
+        }
+        Some(tuned_builder(config, settings))
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RoundTrip {
+        pub duration_ms: Option<i64>,
+        pub status: Option<u16>,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ConnectionDiagnostics {
+        pub server: String,
+        pub keep_alive: KeepAliveSettings,
+        /// TCP connect plus TLS handshake on a fresh connection.
+        pub handshake_ms: Option<i64>,
+        /// ALPN result, `http/1.1` when nothing was negotiated.
+        pub protocol: Option<String>,
+        pub tls_version: Option<String>,
+        pub cipher_suite: Option<String>,
+        pub handshake_error: Option<String>,
+        pub round_trips: Vec<RoundTrip>,
+        pub average_ms: Option<i64>,
+        pub max_ms: Option<i64>,
+        pub healthy: bool,
+        pub notes: Vec<String>,
+    }
+
+    async fn handshake(
+        config: &Config,
+        settings: &KeepAliveSettings,
+    ) -> Result<(i64, String, Option<String>, Option<String>), String> {
+        let mut connector = plain_connector(config, settings)?;
+        let started = Instant::now();
+        let stream = connector
+            .call(config.cluster_url.clone())
+            .await
+            .map_err(|e| format!("Connection failed: {}", e))?;
+        let elapsed = started.elapsed().as_millis() as i64;
+        Ok(match stream {
+            MaybeHttpsStream::Http(_) => (elapsed, "http/1.1".to_string(), None, None),
+            MaybeHttpsStream::Https(tls) => {
+                let (_, session) = tls.inner().get_ref();
+                (
+                    elapsed,
+                    session
+                        .alpn_protocol()
+                        .map(|p| String::from_utf8_lossy(p).to_string())
+                        .unwrap_or("http/1.1".to_string()),
+                    session.protocol_version().map(|v| format!("{:?}", v)),
+                    session
+                        .negotiated_cipher_suite()
+                        .map(|s| format!("{:?}", s.suite())),
+                )
+            }
+        })
+    }
+
+    /// Opens a fresh connection to report what the server negotiates, then
+    /// times `samples` requests to `/version` through the config's client.
+    pub async fn diagnose(
+        config: Config,
+        settings: KeepAliveSettings,
+        client: &Client,
+        samples: usize,
+    ) -> ConnectionDiagnostics {
+        let mut diagnostics = ConnectionDiagnostics {
+            server: config.cluster_url.to_string(),
+            keep_alive: settings.clone(),
+            handshake_ms: None,
+            protocol: None,
+            tls_version: None,
+            cipher_suite: None,
+            handshake_error: None,
+            round_trips: Vec::new(),
+            average_ms: None,
+            max_ms: None,
+            healthy: false,
+            notes: Vec::new(),
+        };
+        match handshake(&config, &settings).await {
+            Ok((elapsed, protocol, version, suite)) => {
+                diagnostics.handshake_ms = Some(elapsed);
+                diagnostics.protocol = Some(protocol);
+                diagnostics.tls_version = version;
+                diagnostics.cipher_suite = suite;
+            }
+            Err(error) => diagnostics.handshake_error = Some(error),
+        }
+
+        for _ in 0..samples.max(1) {
+            let Ok(request) = Request::get("/version").body(Body::empty()) else {
+                break;
+            };
+            let started = Instant::now();
+            diagnostics
+                .round_trips
+                .push(match client.send(request).await {
+                    Ok(response) => RoundTrip {
+                        duration_ms: Some(started.elapsed().as_millis() as i64),
+                        status: Some(response.status().as_u16()),
+                        error: None,
+                    },
+                    Err(e) => RoundTrip {
+                        duration_ms: None,
+                        status: None,
+                        error: Some(e.to_string()),
+                    },
+                });
+        }
+        let timings: Vec<i64> = diagnostics
+            .round_trips
+            .iter()
+            .filter_map(|r| r.duration_ms)
+            .collect();
+        if !timings.is_empty() {
+            diagnostics.average_ms = Some(timings.iter().sum::<i64>() / timings.len() as i64);
+            diagnostics.max_ms = timings.iter().max().copied();
+        }
+        diagnostics.healthy = diagnostics.handshake_error.is_none()
+            && diagnostics
+                .round_trips
+                .iter()
+                .all(|r| r.status.is_some_and(|s| s < 500));
+
+        let notes = &mut diagnostics.notes;
+        if settings.http2 && diagnostics.protocol.as_deref() == Some("http/1.1") {
+            notes.push("HTTP/2 is enabled but the server negotiated HTTP/1.1, so HTTP/2 pings are not sent".to_string());
+        }
+        if settings.http2 && settings.http2_interval_secs.is_none() {
+            notes.push("HTTP/2 is enabled without a ping interval".to_string());
+        }
+        if settings.http2_interval_secs.is_some() && !settings.http2_while_idle {
+            notes.push("HTTP/2 pings only run while a request or watch is open".to_string());
+        }
+        match settings.tcp_keepalive_secs {
+            None if !settings.http2 => notes.push(format!(
+                "No keep-alive is configured; gateways that drop idle flows after about {} seconds will leave quiet watches hanging until the read timeout",
+                NAT_IDLE_SECS
+            )),
+            Some(secs) if secs >= NAT_IDLE_SECS => notes.push(format!(
+                "TCP keep-alive of {} seconds is longer than the idle timeout of many NAT gateways",
+                secs
+            )),
+            _ => {}
+        }
+        if config.proxy_url.is_some() && !settings.is_default() {
+            notes.push("Keep-alive settings are not applied to proxied connections".to_string());
+        }
+        diagnostics
+    }
+}
@@ -0,0 +1,172 @@
+pub mod kubious_error {
+    use std::fmt;
+
+    use kube::config::KubeconfigError;
+    use serde::{Deserialize, Serialize};
+
+    /// The error every command returns to the frontend, tagged by `kind` so
+    /// the UI can react to e.g. expired credentials differently from a
+    /// missing resource. Modules that still produce plain `String` errors
+    /// convert into `Other` through `?`.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[serde(tag = "kind", rename_all = "snake_case")]
+    pub enum KubiousError {
+        /// No usable config: none selected, unknown key, or one that could
+        /// not be parsed or turned into a client.
+        Config {
+            message: String,
+        },
+        /// The cluster could not verify the credentials (401, exec plugin
+        /// or token failures). A 403 is an `Api` error, since the
+        /// credentials were accepted but lack the permission.
+        Auth {
+            message: String,
+        },
+        /// A request to the API server failed. `status` and `reason` come
+        /// from the server's Status object when it sent one.
+        Api {
+            message: String,
+            status: Option<u16>,
+            reason: Option<String>,
+        },
+        /// Reading or writing a local file failed.
+        Io {
+            message: String,
+            path: Option<String>,
+        },
+        Other {
+            message: String,
+        },
+    }
+
+    impl KubiousError {
+        pub fn config(message: impl Into<String>) -> Self {
+            KubiousError::Config {
+                message: message.into(),
+            }
+        }
+
+        pub fn io(error: std::io::Error, path: impl Into<String>) -> Self {
+            KubiousError::Io {
+                message: error.to_string(),
+                path: Some(path.into()),
+            }
+        }
+
+        /// Returned by handlers when no config is selected or its client
+        /// could not be built.
+        pub fn no_client() -> Self {
+            KubiousError::config("Could not establish connection.")
+        }
+
+        /// Prefixes the message with what was being done, keeping the kind,
+        /// status and reason.
+        pub fn with_context(mut self, context: impl Into<String>) -> Self {
+            let context = context.into();
+            let context = context.trim_end_matches('.');
+            match &mut self {
+                KubiousError::Config { message }
+                | KubiousError::Auth { message }
+                | KubiousError::Api { message, .. }
+                | KubiousError::Io { message, .. }
+                | KubiousError::Other { message } => {
+                    *message = if message.is_empty() {
+                        context.to_string()
+                    } else {
+                        format!("{}: {}", context, message)
+                    };
+                }
+            }
+            self
+        }
+
+        pub fn message(&self) -> &str {
+            match self {
+                KubiousError::Config { message }
+                | KubiousError::Auth { message }
+                | KubiousError::Api { message, .. }
+                | KubiousError::Io { message, .. }
+                | KubiousError::Other { message } => message.as_str(),
+            }
+        }
+    }
+
+    /// `.context(..)` on any result whose error converts into
+    /// `KubiousError`, most often a `kube::Error` from a request.
+    pub trait ErrorContext<T> {
+        fn context(self, context: impl Into<String>) -> Result<T, KubiousError>;
+    }
+
+    impl<T, E: Into<KubiousError>> ErrorContext<T> for Result<T, E> {
+        fn context(self, context: impl Into<String>) -> Result<T, KubiousError> {
+            self.map_err(|e| e.into().with_context(context))
+        }
+    }
+
+    impl fmt::Display for KubiousError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message())
+        }
+    }
+
+    impl std::error::Error for KubiousError {}
+
+    impl From<String> for KubiousError {
+        fn from(message: String) -> Self {
+            KubiousError::Other { message }
+        }
+    }
+
+    impl From<&str> for KubiousError {
+        fn from(message: &str) -> Self {
+            KubiousError::Other {
+                message: message.to_string(),
+            }
+        }
+    }
+
+    impl From<KubiousError> for String {
+        fn from(error: KubiousError) -> Self {
+            error.to_string()
+        }
+    }
+
+    impl From<kube::Error> for KubiousError {
+        fn from(error: kube::Error) -> Self {
+            match error {
+                kube::Error::Api(response) if response.code == 401 => KubiousError::Auth {
+                    message: response.message,
+                },
+                kube::Error::Api(response) => KubiousError::Api {
+                    message: response.message,
+                    status: Some(response.code),
+                    reason: Some(response.reason).filter(|r| !r.is_empty()),
+                },
+                kube::Error::Auth(error) => KubiousError::Auth {
+                    message: error.to_string(),
+                },
+                kube::Error::InferConfig(error) => KubiousError::config(error.to_string()),
+                error => KubiousError::Api {
+                    message: error.to_string(),
+                    status: None,
+                    reason: None,
+                },
+            }
+        }
+    }
+
+    impl From<KubeconfigError> for KubiousError {
+        fn from(error: KubeconfigError) -> Self {
+            KubiousError::config(error.to_string())
+        }
+    }
+
+    impl From<std::io::Error> for KubiousError {
+        fn from(error: std::io::Error) -> Self {
+            KubiousError::Io {
+                message: error.to_string(),
+                path: None,
+            }
+        }
+    }
+}
@@ -0,0 +1,25 @@
+pub mod atomic_file {
+    use std::{
+        fs::{self, File},
+        io::Write,
+        path::{Path, PathBuf},
+    };
+
+    use crate::util::kubious_error::KubiousError;
+
+    /// Writes `contents` to a temporary file next to `path` and renames it
+    /// over `path`, so a crash or full disk mid-write never leaves a
+    /// truncated file behind.
+    pub fn write(path: &Path, contents: &[u8]) -> Result<(), KubiousError> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+        File::create(&temporary)
+            .and_then(|mut file| {
+                file.write_all(contents)?;
+                file.sync_all()
+            })
+            .map_err(|e| KubiousError::io(e, temporary.display().to_string()))?;
+        fs::rename(&temporary, path).map_err(|e| KubiousError::io(e, path.display().to_string()))
+    }
+}
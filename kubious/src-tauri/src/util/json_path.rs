@@ -0,0 +1,252 @@
+pub mod value_path {
+    use std::cmp::Ordering;
+
+    use serde_json::Value;
+
+    /// Splits a field path such as `metadata.annotations[sidecar.istio.io/status]`
+    /// into segments. Bracketed segments may contain dots.
+    pub fn parse_path(path: &str) -> Vec<String> {
+        let mut segments: Vec<String> = Vec::new();
+        let mut current = String::new();
+        let mut bracketed = false;
+        for c in path.chars() {
+            match (c, bracketed) {
+                ('[', false) => {
+                    if !current.is_empty() {
+                        segments.push(current.clone());
+                        current.clear();
+                    }
+                    bracketed = true;
+                }
+                (']', true) => {
+                    segments.push(current.clone());
+                    current.clear();
+                    bracketed = false;
+                }
+                ('.', false) => {
+                    if !current.is_empty() {
+                        segments.push(current.clone());
+                        current.clear();
+                    }
+                }
+                _ => current.push(c),
+            }
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+        segments
+    }
+
+    pub fn format_path(segments: &[String]) -> String {
+        let mut path = String::new();
+        for segment in segments {
+            if segment.contains('.') || segment.contains('/') {
+                path.push_str(format!("[{}]", segment).as_str());
+            } else {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(segment.as_str());
+            }
+        }
+        path
+    }
+
+    /// Resolves a parsed path against a value; numeric segments index arrays.
+    pub fn lookup<'a>(value: &'a Value, segments: &[String]) -> Option<&'a Value> {
+        segments
+            .iter()
+            .try_fold(value, |current, segment| match current {
+                Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+                other => other.get(segment.as_str()),
+            })
+    }
+
+    /// Orders JSON values for sorting: numbers numerically, strings and
+    /// booleans naturally, with missing/null values last.
+    pub fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+        let a = a.filter(|v| !v.is_null());
+        let b = b.filter(|v| !v.is_null());
+        match (a, b) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => match (a, b) {
+                (Value::Number(x), Value::Number(y)) => x
+                    .as_f64()
+                    .unwrap_or(0.0)
+                    .partial_cmp(&y.as_f64().unwrap_or(0.0))
+                    .unwrap_or(Ordering::Equal),
+                (Value::String(x), Value::String(y)) => x.cmp(y),
+                (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+                (x, y) => x.to_string().cmp(&y.to_string()),
+            },
+        }
+    }
+
+    enum Step {
+        Field(String),
+        Index(i64),
+        All,
+        /// `[?(@.path)]`, or `[?(@.path == value)]` / `!=`.
+        Filter {
+            path: Vec<String>,
+            test: Option<(bool, Value)>,
+        },
+    }
+
+    fn unquote(text: &str) -> Option<&str> {
+        let text = text.trim();
+        ['\'', '"']
+            .iter()
+            .find(|q| text.len() >= 2 && text.starts_with(**q) && text.ends_with(**q))
+            .map(|_| &text[1..text.len() - 1])
+    }
+
+    fn parse_filter(text: &str) -> Option<Step> {
+        let inner = text.strip_prefix("?(")?.strip_suffix(')')?.trim();
+        let (left, test) = match ["==", "!="].iter().find_map(|op| {
+            inner
+                .split_once(op)
+                .map(|(l, r)| (l, (*op == "==", r.trim())))
+        }) {
+            Some((left, (equal, literal))) => {
+                let value = match unquote(literal) {
+                    Some(text) => Value::String(text.to_string()),
+                    None => serde_json::from_str(literal).ok()?,
+                };
+                (left, Some((equal, value)))
+            }
+            None => (inner, None),
+        };
+        let path = left.trim().strip_prefix('@')?;
+        Some(Step::Filter {
+            path: parse_path(path),
+            test,
+        })
+    }
+
+    fn parse_bracket(text: &str) -> Option<Step> {
+        let text = text.trim();
+        if text == "*" {
+            Some(Step::All)
+        } else if text.starts_with('?') {
+            parse_filter(text)
+        } else if let Some(name) = unquote(text) {
+            Some(Step::Field(name.to_string()))
+        } else {
+            text.parse::<i64>().ok().map(Step::Index)
+        }
+    }
+
+    /// Splits a JSONPath expression such as
+    /// `.status.conditions[?(@.type=="Ready")].status` into steps. Returns
+    /// `None` for syntax outside the subset used by printer columns, such as
+    /// slices and recursive descent.
+    fn parse_jsonpath(expression: &str) -> Option<Vec<Step>> {
+        let expression = expression.trim();
+        let expression = expression
+            .strip_prefix('{')
+            .and_then(|e| e.strip_suffix('}'))
+            .unwrap_or(expression);
+        let expression = expression.strip_prefix('$').unwrap_or(expression);
+        let chars: Vec<char> = expression.chars().collect();
+        let mut steps: Vec<Step> = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    if chars.get(i + 1) == Some(&'.') {
+                        return None;
+                    }
+                    let start = i + 1;
+                    i = start;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
+                    }
+                    let name: String = chars[start..i].iter().collect();
+                    match name.as_str() {
+                        "" => {}
+                        "*" => steps.push(Step::All),
+                        _ => steps.push(Step::Field(name)),
+                    }
+                }
+                '[' => {
+                    let start = i + 1;
+                    let mut depth = 1;
+                    let mut quote: Option<char> = None;
+                    i = start;
+                    while i < chars.len() {
+                        match (chars[i], quote) {
+                            (c, Some(q)) if c == q => quote = None,
+                            (_, Some(_)) => {}
+                            ('\'' | '"', None) => quote = Some(chars[i]),
+                            ('[', None) => depth += 1,
+                            (']', None) => {
+                                depth -= 1;
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            _ => {}
+                        }
+                        i += 1;
+                    }
+                    if i >= chars.len() {
+                        return None;
+                    }
+                    let content: String = chars[start..i].iter().collect();
+                    steps.push(parse_bracket(content.as_str())?);
+                    i += 1;
+                }
+                _ => return None,
+            }
+        }
+        Some(steps)
+    }
+
+    /// Evaluates a kubectl-style JSONPath expression, returning every match
+    /// in document order, or `None` if the expression is not supported.
+    pub fn evaluate_jsonpath(value: &Value, expression: &str) -> Option<Vec<Value>> {
+        let mut current: Vec<&Value> = vec![value];
+        for step in parse_jsonpath(expression)? {
+            current = current
+                .into_iter()
+                .flat_map(|v| -> Vec<&Value> {
+                    match (&step, v) {
+                        (Step::Field(name), Value::Object(map)) => {
+                            map.get(name).into_iter().collect()
+                        }
+                        (Step::Index(index), Value::Array(items)) => {
+                            let index = if *index < 0 {
+                                items.len() as i64 + index
+                            } else {
+                                *index
+                            };
+                            usize::try_from(index)
+                                .ok()
+                                .and_then(|i| items.get(i))
+                                .into_iter()
+                                .collect()
+                        }
+                        (Step::All, Value::Array(items)) => items.iter().collect(),
+                        (Step::All, Value::Object(map)) => map.values().collect(),
+                        (Step::Filter { path, test }, Value::Array(items)) => items
+                            .iter()
+                            .filter(|item| match (lookup(item, path), test) {
+                                (None, _) => false,
+                                (Some(found), None) => !found.is_null(),
+                                (Some(found), Some((equal, expected))) => {
+                                    (found == expected) == *equal
+                                }
+                            })
+                            .collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+        Some(current.into_iter().cloned().collect())
+    }
+}
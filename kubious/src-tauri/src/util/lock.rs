@@ -0,0 +1,14 @@
+pub mod lock_recovery {
+    use std::sync::{Mutex, MutexGuard};
+
+    /// Locks a mutex even if a command panicked while holding it. Every
+    /// update behind these locks is a single insert, remove or replace, so
+    /// a poisoned lock still guards consistent data, and recovering keeps
+    /// one failed command from taking every later one down with it.
+    pub fn recover<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+        mutex.lock().unwrap_or_else(|poisoned| {
+            mutex.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+}
@@ -0,0 +1,221 @@
+pub mod api_metrics {
+    use std::{
+        collections::{HashMap, VecDeque},
+        sync::{LazyLock, Mutex, MutexGuard},
+        task::{Context, Poll},
+        time::{Instant, SystemTime, UNIX_EPOCH},
+    };
+
+    use futures::future::BoxFuture;
+    use http::{Request, Response};
+    use kube::client::Body;
+    use serde::{Deserialize, Serialize};
+    use tower::{Layer, Service};
+
+    use crate::util::lock_recovery::recover;
+
+    /// Latency samples kept per cluster for the percentiles.
+    const MAX_SAMPLES: usize = 1000;
+
+    #[derive(Default)]
+    struct ClusterUsage {
+        since: u64,
+        requests: u64,
+        client_errors: u64,
+        server_errors: u64,
+        throttled: u64,
+        failures: u64,
+        last_error: Option<String>,
+        latencies: VecDeque<u64>,
+    }
+
+    static USAGE: LazyLock<Mutex<HashMap<String, ClusterUsage>>> =
+        LazyLock::new(|| Mutex::new(HashMap::new()));
+
+    fn usage_mutable() -> MutexGuard<'static, HashMap<String, ClusterUsage>> {
+        recover(&USAGE)
+    }
+
+    /// API usage of one cluster since the app started or the stats were
+    /// reset. Latencies are the time until response headers arrive, so they
+    /// reflect the API server and network rather than the app.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct ClusterApiStats {
+        pub cluster: String,
+        /// Seconds since the Unix epoch when recording started.
+        pub since: u64,
+        pub requests: u64,
+        /// Responses with a 4xx status other than 429.
+        pub client_errors: u64,
+        /// Responses with a 5xx status.
+        pub server_errors: u64,
+        /// Responses with status 429, sent when API priority and fairness
+        /// rejects requests.
+        pub throttled: u64,
+        /// Requests that got no response, such as connection failures and
+        /// timeouts.
+        pub failures: u64,
+        /// Share of requests that failed, got a 5xx status or were throttled.
+        pub error_rate: f64,
+        pub last_error: Option<String>,
+        pub p50_ms: Option<u64>,
+        pub p90_ms: Option<u64>,
+        pub p99_ms: Option<u64>,
+        pub max_ms: Option<u64>,
+    }
+
+    fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+        if sorted.is_empty() {
+            return None;
+        }
+        let rank = ((sorted.len() as f64) * p).ceil() as usize;
+        sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
+    }
+
+    fn record(cluster: &str, status: Result<u16, String>, latency_ms: u64) {
+        let mut usage = usage_mutable();
+        let entry = usage
+            .entry(cluster.to_string())
+            .or_insert_with(|| ClusterUsage {
+                since: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                ..ClusterUsage::default()
+            });
+        entry.requests += 1;
+        match status {
+            Ok(429) => {
+                entry.throttled += 1;
+                entry.last_error = Some("429 Too Many Requests".to_string());
+            }
+            Ok(status) if status >= 500 => {
+                entry.server_errors += 1;
+                entry.last_error = Some(format!("Server error {}", status));
+            }
+            Ok(status) if status >= 400 => entry.client_errors += 1,
+            Ok(_) => {}
+            Err(error) => {
+                entry.failures += 1;
+                entry.last_error = Some(error);
+                return;
+            }
+        }
+        entry.latencies.push_back(latency_ms);
+        while entry.latencies.len() > MAX_SAMPLES {
+            entry.latencies.pop_front();
+        }
+    }
+
+    /// Usage per cluster, or for the one given, sorted by cluster key.
+    pub fn stats(cluster: Option<&str>) -> Vec<ClusterApiStats> {
+        let usage = usage_mutable();
+        let mut stats: Vec<ClusterApiStats> = usage
+            .iter()
+            .filter(|(key, _)| cluster.is_none_or(|c| c == key.as_str()))
+            .map(|(key, entry)| {
+                let mut sorted: Vec<u64> = entry.latencies.iter().copied().collect();
+                sorted.sort_unstable();
+                let errors = entry.failures + entry.server_errors + entry.throttled;
+                ClusterApiStats {
+                    cluster: key.clone(),
+                    since: entry.since,
+                    requests: entry.requests,
+                    client_errors: entry.client_errors,
+                    server_errors: entry.server_errors,
+                    throttled: entry.throttled,
+                    failures: entry.failures,
+                    error_rate: if entry.requests == 0 {
+                        0.0
+                    } else {
+                        errors as f64 / entry.requests as f64
+                    },
+                    last_error: entry.last_error.clone(),
+                    p50_ms: percentile(&sorted, 0.5),
+                    p90_ms: percentile(&sorted, 0.9),
+                    p99_ms: percentile(&sorted, 0.99),
+                    max_ms: sorted.last().copied(),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.cluster.cmp(&b.cluster));
+        stats
+    }
+
+    /// Clears the usage of one cluster, or of all clusters.
+    pub fn reset(cluster: Option<&str>) {
+        let mut usage = usage_mutable();
+        match cluster {
+            Some(cluster) => {
+                usage.remove(cluster);
+            }
+            None => usage.clear(),
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct MetricsLayer {
+        cluster: String,
+    }
+
+    impl MetricsLayer {
+        pub fn new(cluster: &str) -> Self {
+            MetricsLayer {
+                cluster: cluster.to_string(),
+            }
+        }
+    }
+
+    impl<S> Layer<S> for MetricsLayer {
+        type Service = MetricsService<S>;
+
+        fn layer(&self, inner: S) -> Self::Service {
+            MetricsService {
+                inner,
+                cluster: self.cluster.clone(),
+            }
+        }
+    }
+
+    /// Counts every request sent through a client and times it until the
+    /// response headers arrive. Unlike `RecorderService`, this is always on.
+    #[derive(Clone, Debug)]
+    pub struct MetricsService<S> {
+        inner: S,
+        cluster: String,
+    }
+
+    impl<S, ResBody> Service<Request<Body>> for MetricsService<S>
+    where
+        S: Service<Request<Body>, Response = Response<ResBody>>,
+        S::Future: Send + 'static,
+        S::Error: std::fmt::Display,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.inner.poll_ready(cx)
+        }
+
+        fn call(&mut self, request: Request<Body>) -> Self::Future {
+            let cluster = self.cluster.clone();
+            let started = Instant::now();
+            let response = self.inner.call(request);
+            Box::pin(async move {
+                let response = response.await;
+                let status = match &response {
+                    Ok(response) => Ok(response.status().as_u16()),
+                    Err(error) => Err(error.to_string()),
+                };
+                record(
+                    cluster.as_str(),
+                    status,
+                    started.elapsed().as_millis() as u64,
+                );
+                response
+            })
+        }
+    }
+}
@@ -0,0 +1,36 @@
+mod quantity;
+pub use quantity::kube_quantity;
+mod resources;
+pub use resources::resource_accounting;
+mod reconnect;
+pub use reconnect::stream_reconnect;
+mod warnings;
+pub use warnings::api_warnings;
+mod recorder;
+pub use recorder::api_recorder;
+mod metrics;
+pub use metrics::api_metrics;
+mod json_path;
+pub use json_path::value_path;
+mod clock;
+pub use clock::clock_skew;
+mod conditions;
+pub use conditions::resource_health;
+mod registry;
+pub use registry::registry_client;
+mod timestamps;
+pub use timestamps::time_normalization;
+mod watch_gap;
+pub use watch_gap::watch_gaps;
+mod connection;
+pub use connection::connection_tuning;
+mod text;
+pub use text::utf8_chunks;
+mod tar;
+pub use tar::tar_stream;
+mod error;
+pub use error::kubious_error;
+mod lock;
+pub use lock::lock_recovery;
+mod file;
+pub use file::atomic_file;
\ No newline at end of file
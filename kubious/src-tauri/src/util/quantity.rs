@@ -0,0 +1,156 @@
+pub mod kube_quantity {
+    use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+
+    const BINARY_SUFFIXES: [(&str, f64); 6] = [
+        ("Ki", 1024.0),
+        ("Mi", 1048576.0),
+        ("Gi", 1073741824.0),
+        ("Ti", 1099511627776.0),
+        ("Pi", 1125899906842624.0),
+        ("Ei", 1152921504606846976.0),
+    ];
+
+    const DECIMAL_SUFFIXES: [(char, f64); 9] = [
+        ('n', 1e-9),
+        ('u', 1e-6),
+        ('m', 1e-3),
+        ('k', 1e3),
+        ('M', 1e6),
+        ('G', 1e9),
+        ('T', 1e12),
+        ('P', 1e15),
+        ('E', 1e18),
+    ];
+
+    /// Parses a Kubernetes quantity string (`250m`, `1Gi`, `2e3`) into its
+    /// value in base units (cores, bytes, or plain counts).
+    pub fn parse_quantity(value: &str) -> Option<f64> {
+        let value = value.trim();
+        for (suffix, multiplier) in BINARY_SUFFIXES {
+            if let Some(number) = value.strip_suffix(suffix) {
+                return number.parse::<f64>().ok().map(|n| n * multiplier);
+            }
+        }
+        if let Ok(number) = value.parse::<f64>() {
+            return Some(number);
+        }
+        let last = value.chars().last()?;
+        DECIMAL_SUFFIXES
+            .iter()
+            .find(|(suffix, _)| *suffix == last)
+            .and_then(|(_, multiplier)| {
+                value[..value.len() - 1]
+                    .parse::<f64>()
+                    .ok()
+                    .map(|n| n * multiplier)
+            })
+    }
+
+    pub fn quantity_value(quantity: &Quantity) -> f64 {
+        parse_quantity(quantity.0.as_str()).unwrap_or(0.0)
+    }
+
+    /// Parses a quantity exactly, in billionths of the base unit. `None`
+    /// for malformed values and for precision below nano or beyond `i128`.
+    fn parse_exact(value: &str) -> Option<i128> {
+        let value = value.trim();
+        let (number, binary_power, mut exponent) = if let Some((number, power)) = BINARY_SUFFIXES
+            .iter()
+            .enumerate()
+            .find_map(|(i, (suffix, _))| {
+                value.strip_suffix(suffix).map(|n| (n, (i as u32 + 1) * 10))
+            }) {
+            (number, power, 0i32)
+        } else if let Some((number, exponent)) = value.rsplit_once(['e', 'E']).filter(|(n, e)| {
+            !n.is_empty()
+                && !e.is_empty()
+                && e.trim_start_matches(['+', '-'])
+                    .chars()
+                    .all(|c| c.is_ascii_digit())
+        }) {
+            (number, 0, exponent.parse::<i32>().ok()?)
+        } else {
+            let last = value.chars().last()?;
+            let exponent = ['n', 'u', 'm', 'k', 'M', 'G', 'T', 'P', 'E']
+                .iter()
+                .position(|suffix| *suffix == last)
+                .map(|i| match i {
+                    0..=2 => (i as i32 - 3) * 3,
+                    _ => (i as i32 - 2) * 3,
+                });
+            match exponent {
+                Some(exponent) => (&value[..value.len() - 1], 0, exponent),
+                None => (value, 0, 0),
+            }
+        };
+
+        let (negative, number) = match number.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if (whole.is_empty() && fraction.is_empty())
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let mut digits: i128 = format!("{}{}", whole, fraction).parse().ok()?;
+        digits = digits.checked_mul(1i128.checked_shl(binary_power)?)?;
+        exponent += 9 - fraction.len() as i32;
+        let scale = 10i128.checked_pow(exponent.unsigned_abs())?;
+        let nanos = if exponent >= 0 {
+            digits.checked_mul(scale)?
+        } else if digits % scale == 0 {
+            digits / scale
+        } else {
+            return None;
+        };
+        Some(if negative { -nanos } else { nanos })
+    }
+
+    /// Writes a quantity in its shortest exact form, so `1024Mi` and `1Gi`,
+    /// or `0.5` and `500m`, read the same. Unparseable values are returned
+    /// unchanged.
+    pub fn canonical_quantity(value: &str) -> String {
+        let Some(nanos) = parse_exact(value) else {
+            return value.to_string();
+        };
+        if nanos == 0 {
+            return "0".to_string();
+        }
+        let mut decimal = format!("{}n", nanos);
+        for (exponent, suffix) in [
+            (-6, "u"),
+            (-3, "m"),
+            (0, ""),
+            (3, "k"),
+            (6, "M"),
+            (9, "G"),
+            (12, "T"),
+            (15, "P"),
+            (18, "E"),
+        ] {
+            let scale = 10i128.pow((exponent + 9) as u32);
+            if nanos % scale == 0 {
+                decimal = format!("{}{}", nanos / scale, suffix);
+            }
+        }
+        let mut shortest = decimal;
+        if nanos % 1_000_000_000 == 0 {
+            let units = nanos / 1_000_000_000;
+            for (i, (suffix, _)) in BINARY_SUFFIXES.iter().enumerate() {
+                let scale = 1i128 << ((i as u32 + 1) * 10);
+                if units % scale == 0 {
+                    let binary = format!("{}{}", units / scale, suffix);
+                    if binary.len() < shortest.len() {
+                        shortest = binary;
+                    }
+                }
+            }
+        }
+        shortest
+    }
+}
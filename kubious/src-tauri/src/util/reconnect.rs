@@ -0,0 +1,156 @@
+pub mod stream_reconnect {
+    use std::time::Duration;
+
+    use rand::Rng;
+    use serde::{Deserialize, Serialize};
+    use tauri::{AppHandle, Emitter, Manager};
+
+    use crate::api::app_support::{LogLevel, SupportLog};
+
+    /// Event channel carrying `StreamStateEvent`s for every reconnecting stream.
+    pub const STREAM_STATE_EVENT: &str = "stream-state";
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BackoffPolicy {
+        pub initial_ms: u64,
+        pub max_ms: u64,
+        pub multiplier: f64,
+        /// Fraction of each delay that is randomized, between 0 and 1.
+        pub jitter: f64,
+        pub max_attempts: Option<u32>,
+    }
+
+    impl Default for BackoffPolicy {
+        fn default() -> Self {
+            BackoffPolicy {
+                initial_ms: 500,
+                max_ms: 30_000,
+                multiplier: 2.0,
+                jitter: 0.2,
+                max_attempts: None,
+            }
+        }
+    }
+
+    impl BackoffPolicy {
+        /// Delay before the given (zero-based) retry attempt.
+        pub fn delay(&self, attempt: u32) -> Duration {
+            let base = (self.initial_ms as f64 * self.multiplier.powi(attempt as i32))
+                .min(self.max_ms as f64);
+            let jitter = self.jitter.clamp(0.0, 1.0);
+            let factor = if jitter > 0.0 {
+                rand::thread_rng().gen_range((1.0 - jitter)..=(1.0 + jitter))
+            } else {
+                1.0
+            };
+            Duration::from_millis((base * factor).min(self.max_ms as f64) as u64)
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[serde(tag = "state")]
+    pub enum StreamState {
+        Connected,
+        Degraded { reason: String },
+        Reconnecting { attempt: u32, delay_ms: u64 },
+        Failed { reason: String },
+        Closed,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct StreamStateEvent {
+        pub stream: String,
+        pub kind: String,
+        pub state: StreamState,
+    }
+
+    /// Tracks retry state for one long-lived stream and reports transitions
+    /// to the frontend. Streams call `connected()` after (re)establishing,
+    /// and `retry()` after a failure to wait out the next backoff delay.
+    pub struct Reconnector {
+        handle: AppHandle,
+        stream: String,
+        kind: String,
+        policy: BackoffPolicy,
+        attempt: u32,
+        state: Option<StreamState>,
+    }
+
+    impl Reconnector {
+        pub fn new(handle: AppHandle, kind: &str, stream: &str, policy: BackoffPolicy) -> Self {
+            Reconnector {
+                handle,
+                stream: stream.to_string(),
+                kind: kind.to_string(),
+                policy,
+                attempt: 0,
+                state: None,
+            }
+        }
+
+        fn transition(&mut self, state: StreamState) {
+            if self.state.as_ref() == Some(&state) {
+                return;
+            }
+            self.state = Some(state.clone());
+            let level = match &state {
+                StreamState::Connected | StreamState::Closed => LogLevel::Info,
+                StreamState::Degraded { .. } | StreamState::Reconnecting { .. } => LogLevel::Warning,
+                StreamState::Failed { .. } => LogLevel::Error,
+            };
+            self.handle.state::<SupportLog>().log(
+                level,
+                format!("{}/{}", self.kind, self.stream).as_str(),
+                format!("{:?}", state).as_str(),
+            );
+            let _ = self.handle.emit(
+                STREAM_STATE_EVENT,
+                StreamStateEvent {
+                    stream: self.stream.clone(),
+                    kind: self.kind.clone(),
+                    state,
+                },
+            );
+        }
+
+        pub fn connected(&mut self) {
+            self.attempt = 0;
+            self.transition(StreamState::Connected);
+        }
+
+        pub fn degraded(&mut self, reason: &str) {
+            self.transition(StreamState::Degraded {
+                reason: reason.to_string(),
+            });
+        }
+
+        pub fn closed(&mut self) {
+            self.transition(StreamState::Closed);
+        }
+
+        pub fn failed(&mut self, reason: &str) {
+            self.transition(StreamState::Failed {
+                reason: reason.to_string(),
+            });
+        }
+
+        /// Sleeps for the next backoff delay. Returns `false` (after emitting
+        /// `Failed`) once the policy's attempt budget is exhausted.
+        pub async fn retry(&mut self, reason: &str) -> bool {
+            if let Some(max) = self.policy.max_attempts {
+                if self.attempt >= max {
+                    self.failed(reason);
+                    return false;
+                }
+            }
+            let delay = self.policy.delay(self.attempt);
+            self.attempt += 1;
+            self.transition(StreamState::Reconnecting {
+                attempt: self.attempt,
+                delay_ms: delay.as_millis() as u64,
+            });
+            tokio::time::sleep(delay).await;
+            true
+        }
+    }
+}
@@ -0,0 +1,164 @@
+pub mod api_recorder {
+    use std::{
+        cell::RefCell,
+        future::Future,
+        task::{Context, Poll},
+        time::{Instant, SystemTime, UNIX_EPOCH},
+    };
+
+    use futures::{future::BoxFuture, FutureExt};
+    use http::{Method, Request, Response};
+    use kube::client::Body;
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use tower::{Layer, Service};
+
+    tokio::task_local! {
+        static RECORDED: RefCell<Vec<RecordedRequest>>;
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct RecordedRequest {
+        /// Milliseconds since the Unix epoch when the request was sent.
+        pub started_at: u128,
+        pub method: String,
+        /// Path and query relative to the cluster URL.
+        pub path: String,
+        pub content_type: Option<String>,
+        pub body_size: usize,
+        /// Hex SHA-256 of the request body, comparable with `sha256sum`.
+        pub body_hash: Option<String>,
+        pub status: Option<u16>,
+        pub error: Option<String>,
+        pub duration_ms: u128,
+        /// An equivalent `kubectl --raw` invocation, where one exists.
+        pub kubectl: Option<String>,
+    }
+
+    fn kubectl_equivalent(method: &Method, path: &str, has_body: bool) -> Option<String> {
+        let verb = match *method {
+            Method::GET => "get",
+            Method::POST => "create",
+            Method::PUT => "replace",
+            Method::DELETE => "delete",
+            _ => return None,
+        };
+        let body = if has_body { " -f body.json" } else { "" };
+        Some(format!("kubectl {} --raw '{}'{}", verb, path, body))
+    }
+
+    /// Runs a command future, recording every API request made on the same
+    /// task by clients built with `RecorderLayer`. Nothing is recorded when
+    /// `enabled` is false.
+    pub async fn record<F: Future>(future: F, enabled: bool) -> (F::Output, Vec<RecordedRequest>) {
+        if !enabled {
+            return (future.await, Vec::new());
+        }
+        RECORDED
+            .scope(RefCell::new(Vec::new()), async move {
+                let output = future.await;
+                let requests = RECORDED.with(|recorded| recorded.take());
+                (output, requests)
+            })
+            .await
+    }
+
+    fn recording() -> bool {
+        RECORDED.try_with(|_| ()).is_ok()
+    }
+
+    fn push(request: RecordedRequest) {
+        let _ = RECORDED.try_with(|recorded| recorded.borrow_mut().push(request));
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct RecorderLayer;
+
+    impl<S> Layer<S> for RecorderLayer {
+        type Service = RecorderService<S>;
+
+        fn layer(&self, inner: S) -> Self::Service {
+            RecorderService { inner }
+        }
+    }
+
+    /// Records method, path, body hash, status and timing of requests sent
+    /// inside a `record` scope. Like `WarningService`, it relies on the
+    /// caller's task polling the response future.
+    #[derive(Clone, Debug)]
+    pub struct RecorderService<S> {
+        inner: S,
+    }
+
+    impl<S, ResBody> Service<Request<Body>> for RecorderService<S>
+    where
+        S: Service<Request<Body>, Response = Response<ResBody>>,
+        S::Future: Send + 'static,
+        S::Error: std::fmt::Display,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.inner.poll_ready(cx)
+        }
+
+        fn call(&mut self, request: Request<Body>) -> Self::Future {
+            if !recording() {
+                return Box::pin(self.inner.call(request));
+            }
+            let (parts, body) = request.into_parts();
+            // Request bodies are always buffered, so collecting them never
+            // has to wait.
+            let bytes = body
+                .collect_bytes()
+                .now_or_never()
+                .and_then(|b| b.ok())
+                .unwrap_or_default();
+            let path = parts
+                .uri
+                .path_and_query()
+                .map(|p| p.to_string())
+                .unwrap_or(parts.uri.to_string());
+            let mut recorded = RecordedRequest {
+                started_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis())
+                    .unwrap_or(0),
+                kubectl: kubectl_equivalent(&parts.method, path.as_str(), !bytes.is_empty()),
+                method: parts.method.to_string(),
+                path,
+                content_type: parts
+                    .headers
+                    .get(http::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string()),
+                body_size: bytes.len(),
+                body_hash: (!bytes.is_empty()).then(|| {
+                    Sha256::digest(&bytes)
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect()
+                }),
+                status: None,
+                error: None,
+                duration_ms: 0,
+            };
+            let started = Instant::now();
+            let response = self
+                .inner
+                .call(Request::from_parts(parts, Body::from(bytes)));
+            Box::pin(async move {
+                let response = response.await;
+                recorded.duration_ms = started.elapsed().as_millis();
+                match &response {
+                    Ok(response) => recorded.status = Some(response.status().as_u16()),
+                    Err(error) => recorded.error = Some(error.to_string()),
+                }
+                push(recorded);
+                response
+            })
+        }
+    }
+}
@@ -0,0 +1,135 @@
+pub mod registry_client {
+    use serde_json::Value;
+    use tauri_plugin_http::reqwest::{self, header, StatusCode};
+
+    #[derive(Clone, Debug, Default)]
+    pub struct RegistryCredential {
+        /// Base64 `user:password`, as found in docker config `auth` fields.
+        pub basic: Option<String>,
+        pub username: Option<String>,
+        pub password: Option<String>,
+    }
+
+    /// The host serving a registry's API; Docker Hub is served elsewhere.
+    pub fn registry_host(registry: &str) -> &str {
+        if registry == "docker.io" {
+            "registry-1.docker.io"
+        } else {
+            registry
+        }
+    }
+
+    /// Reduces a docker config key or URL to a registry host, folding the
+    /// Docker Hub aliases into `docker.io`.
+    pub fn normalize_registry_key(key: &str) -> String {
+        let host = key
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        match host {
+            "index.docker.io" | "registry-1.docker.io" | "registry.hub.docker.com" => {
+                "docker.io".to_string()
+            }
+            other => other.to_string(),
+        }
+    }
+
+    fn with_credential(
+        request: reqwest::RequestBuilder,
+        credential: &Option<RegistryCredential>,
+    ) -> reqwest::RequestBuilder {
+        match credential {
+            Some(RegistryCredential {
+                username: Some(username),
+                password,
+                ..
+            }) => request.basic_auth(username, password.clone()),
+            Some(RegistryCredential {
+                basic: Some(basic), ..
+            }) => request.header(header::AUTHORIZATION, format!("Basic {}", basic)),
+            _ => request,
+        }
+    }
+
+    /// Extracts `key="value"` parameters from a `WWW-Authenticate` challenge.
+    fn challenge_param(challenge: &str, key: &str) -> Option<String> {
+        let marker = format!("{}=\"", key);
+        let start = challenge.find(marker.as_str())? + marker.len();
+        let end = challenge[start..].find('"')? + start;
+        Some(challenge[start..end].to_string())
+    }
+
+    async fn bearer_token(
+        http: &reqwest::Client,
+        challenge: &str,
+        credential: &Option<RegistryCredential>,
+    ) -> Result<Option<String>, reqwest::Error> {
+        let Some(realm) = challenge_param(challenge, "realm") else {
+            return Ok(None);
+        };
+        let mut query: Vec<(&str, String)> = Vec::new();
+        if let Some(service) = challenge_param(challenge, "service") {
+            query.push(("service", service));
+        }
+        if let Some(scope) = challenge_param(challenge, "scope") {
+            query.push(("scope", scope));
+        }
+        let response = with_credential(http.get(realm).query(&query), credential)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+        let body: Value =
+            serde_json::from_str(response.text().await?.as_str()).unwrap_or(Value::Null);
+        Ok(body["token"]
+            .as_str()
+            .or(body["access_token"].as_str())
+            .map(|t| t.to_string()))
+    }
+
+    /// GETs a registry URL, answering basic or bearer auth challenges with
+    /// `credential`.
+    pub async fn registry_get(
+        http: &reqwest::Client,
+        url: &str,
+        accept: &str,
+        credential: &Option<RegistryCredential>,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let response = http.get(url).header(header::ACCEPT, accept).send().await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        let challenge = response
+            .headers()
+            .get(header::WWW_AUTHENTICATE)
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let request = http.get(url).header(header::ACCEPT, accept);
+        if challenge.to_lowercase().starts_with("bearer") {
+            match bearer_token(http, challenge.as_str(), credential).await? {
+                Some(token) => request.bearer_auth(token).send().await,
+                None => Ok(response),
+            }
+        } else {
+            with_credential(request, credential).send().await
+        }
+    }
+
+    /// Checks whether `credential` is accepted by the registry's API root,
+    /// returning the final status code.
+    pub async fn check_login(
+        registry: &str,
+        credential: &Option<RegistryCredential>,
+    ) -> Result<u16, String> {
+        let http = reqwest::Client::new();
+        let url = format!("https://{}/v2/", registry_host(registry));
+        registry_get(&http, url.as_str(), "application/json", credential)
+            .await
+            .map(|response| response.status().as_u16())
+            .map_err(|e| e.to_string())
+    }
+}
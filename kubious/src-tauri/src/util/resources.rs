@@ -0,0 +1,127 @@
+pub mod resource_accounting {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::{
+        api::core::v1::{Container, Pod, PodSpec},
+        apimachinery::pkg::apis::meta::v1::OwnerReference,
+    };
+
+    use crate::util::kube_quantity::quantity_value;
+
+    fn container_requests(container: &Container) -> BTreeMap<String, f64> {
+        let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+        if let Some(resources) = &container.resources {
+            // Limits stand in for requests when only limits are set, matching
+            // the API server's defaulting.
+            if let Some(limits) = &resources.limits {
+                for (name, quantity) in limits {
+                    totals.insert(name.clone(), quantity_value(quantity));
+                }
+            }
+            if let Some(requests) = &resources.requests {
+                for (name, quantity) in requests {
+                    totals.insert(name.clone(), quantity_value(quantity));
+                }
+            }
+        }
+        totals
+    }
+
+    fn container_limits(container: &Container) -> BTreeMap<String, f64> {
+        container
+            .resources
+            .as_ref()
+            .and_then(|r| r.limits.as_ref())
+            .map(|limits| {
+                limits
+                    .iter()
+                    .map(|(name, quantity)| (name.clone(), quantity_value(quantity)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The larger of the summed app containers and the largest single init
+    /// container, plus overhead, for the per-container amounts given.
+    fn effective_totals(
+        spec: &PodSpec,
+        amounts: fn(&Container) -> BTreeMap<String, f64>,
+    ) -> BTreeMap<String, f64> {
+        let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+        for container in &spec.containers {
+            for (name, value) in amounts(container) {
+                *totals.entry(name).or_insert(0.0) += value;
+            }
+        }
+        for container in spec.init_containers.iter().flatten() {
+            for (name, value) in amounts(container) {
+                let entry = totals.entry(name).or_insert(0.0);
+                if value > *entry {
+                    *entry = value;
+                }
+            }
+        }
+        for (name, quantity) in spec.overhead.iter().flatten() {
+            *totals.entry(name.clone()).or_insert(0.0) += quantity_value(quantity);
+        }
+        totals
+    }
+
+    /// Effective scheduling requests of a pod spec: the larger of the summed
+    /// app containers and the largest single init container, plus overhead.
+    pub fn effective_requests(spec: &PodSpec) -> BTreeMap<String, f64> {
+        effective_totals(spec, container_requests)
+    }
+
+    /// Effective limits of a pod spec, computed the same way as requests.
+    pub fn effective_limits(spec: &PodSpec) -> BTreeMap<String, f64> {
+        effective_totals(spec, container_limits)
+    }
+
+    pub fn pod_requests(pod: &Pod) -> BTreeMap<String, f64> {
+        pod.spec
+            .as_ref()
+            .map(effective_requests)
+            .unwrap_or_default()
+    }
+
+    /// Whether a pod still holds node resources (scheduled and not finished).
+    pub fn pod_is_active(pod: &Pod) -> bool {
+        let phase = pod.status.as_ref().and_then(|s| s.phase.clone());
+        !matches!(phase.as_deref(), Some("Succeeded") | Some("Failed"))
+    }
+
+    /// Extended resources are anything outside the core cpu/memory/storage set,
+    /// such as `nvidia.com/gpu` or `hugepages-2Mi`.
+    pub fn is_extended_resource(name: &str) -> bool {
+        name.starts_with("hugepages-")
+            || (name.contains('/') && !name.starts_with("kubernetes.io/"))
+    }
+
+    /// Describes the controller of an object as `Kind/name`.
+    pub fn controller_of(owners: &Option<Vec<OwnerReference>>) -> Option<String> {
+        owners
+            .iter()
+            .flatten()
+            .find(|o| o.controller.unwrap_or(false))
+            .map(|o| format!("{}/{}", o.kind, o.name))
+    }
+
+    /// Resolves the owning workload, collapsing ReplicaSets created by a
+    /// Deployment into the Deployment itself.
+    pub fn workload_of(pod: &Pod) -> Option<String> {
+        let owner = controller_of(&pod.metadata.owner_references)?;
+        let hash = pod
+            .metadata
+            .labels
+            .as_ref()
+            .and_then(|l| l.get("pod-template-hash"));
+        match (owner.strip_prefix("ReplicaSet/"), hash) {
+            (Some(name), Some(hash)) => name
+                .strip_suffix(format!("-{}", hash).as_str())
+                .map(|deployment| format!("Deployment/{}", deployment))
+                .or(Some(owner.clone())),
+            _ => Some(owner),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+pub mod tar_stream {
+    pub const BLOCK_SIZE: usize = 512;
+
+    /// GNU tar reads in records of 20 blocks and stops at the end marker,
+    /// so the archive is padded to a full record.
+    const RECORD_SIZE: usize = BLOCK_SIZE * 20;
+
+    const NAME_LENGTH: usize = 100;
+    const LONG_LINK: &str = "././@LongLink";
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum EntryType {
+        File,
+        Directory,
+        Symlink,
+        /// GNU long name for the next entry.
+        LongName,
+        /// PAX extended header for the next entry.
+        Extended,
+        Other,
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct Header {
+        pub path: String,
+        pub size: u64,
+        pub entry_type: EntryType,
+    }
+
+    fn write_octal(field: &mut [u8], value: u64) {
+        let digits = field.len() - 1;
+        let text = format!("{:0width$o}", value, width = digits);
+        field[..digits].copy_from_slice(&text.as_bytes()[text.len() - digits..]);
+        field[digits] = 0;
+    }
+
+    /// Sizes over 8 GiB do not fit the octal field and use base-256.
+    fn write_size(field: &mut [u8], size: u64) {
+        if size < 0o77777777777 {
+            write_octal(field, size);
+        } else {
+            field.fill(0);
+            field[0] = 0x80;
+            let bytes = size.to_be_bytes();
+            let start = field.len() - bytes.len();
+            field[start..].copy_from_slice(&bytes);
+        }
+    }
+
+    fn block(path: &[u8], size: u64, mode: u32, mtime: u64, type_flag: u8) -> [u8; BLOCK_SIZE] {
+        let mut header = [0u8; BLOCK_SIZE];
+        let length = path.len().min(NAME_LENGTH);
+        header[..length].copy_from_slice(&path[..length]);
+        write_octal(&mut header[100..108], mode as u64);
+        write_octal(&mut header[108..116], 0);
+        write_octal(&mut header[116..124], 0);
+        write_size(&mut header[124..136], size);
+        write_octal(&mut header[136..148], mtime);
+        header[156] = type_flag;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+
+        header[148..156].fill(b' ');
+        let checksum: u64 = header.iter().map(|b| *b as u64).sum();
+        write_octal(&mut header[148..155], checksum);
+        header[155] = b' ';
+        header
+    }
+
+    /// Pads `size` bytes of entry data to the next block.
+    pub fn padding(size: u64) -> usize {
+        let rest = (size % BLOCK_SIZE as u64) as usize;
+        if rest == 0 {
+            0
+        } else {
+            BLOCK_SIZE - rest
+        }
+    }
+
+    /// The header blocks for an entry; paths too long for the name field
+    /// are preceded by a GNU long name entry.
+    pub fn header(path: &str, entry_type: EntryType, size: u64, mode: u32, mtime: u64) -> Vec<u8> {
+        let type_flag = match entry_type {
+            EntryType::Directory => b'5',
+            EntryType::Symlink => b'2',
+            _ => b'0',
+        };
+        let mut path = path.to_string();
+        if entry_type == EntryType::Directory && !path.ends_with('/') {
+            path.push('/');
+        }
+        let mut out = Vec::new();
+        if path.len() > NAME_LENGTH {
+            let mut name = path.as_bytes().to_vec();
+            name.push(0);
+            out.extend_from_slice(&block(
+                LONG_LINK.as_bytes(),
+                name.len() as u64,
+                0o644,
+                0,
+                b'L',
+            ));
+            let data_size = name.len() as u64;
+            out.extend_from_slice(&name);
+            out.resize(out.len() + padding(data_size), 0);
+        }
+        out.extend_from_slice(&block(path.as_bytes(), size, mode, mtime, type_flag));
+        out
+    }
+
+    /// Two zero blocks, padded out to a full record.
+    pub fn end_of_archive(written: u64) -> Vec<u8> {
+        let total = written as usize + 2 * BLOCK_SIZE;
+        let rest = total % RECORD_SIZE;
+        let pad = if rest == 0 { 0 } else { RECORD_SIZE - rest };
+        vec![0u8; 2 * BLOCK_SIZE + pad]
+    }
+
+    fn text(field: &[u8]) -> String {
+        let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+        String::from_utf8_lossy(&field[..end]).to_string()
+    }
+
+    fn number(field: &[u8]) -> u64 {
+        if field[0] & 0x80 != 0 {
+            let mut value: u64 = (field[0] & 0x7f) as u64;
+            for byte in &field[1..] {
+                value = (value << 8) | *byte as u64;
+            }
+            value
+        } else {
+            let digits = text(field);
+            u64::from_str_radix(digits.trim_matches(|c: char| c == ' ' || c == '\0'), 8)
+                .unwrap_or(0)
+        }
+    }
+
+    /// Reads a header block. `None` marks the end of the archive.
+    pub fn parse_header(block: &[u8; BLOCK_SIZE]) -> Option<Header> {
+        if block.iter().all(|b| *b == 0) {
+            return None;
+        }
+        let mut path = text(&block[..100]);
+        if &block[257..262] == b"ustar" {
+            let prefix = text(&block[345..500]);
+            if !prefix.is_empty() {
+                path = format!("{}/{}", prefix, path);
+            }
+        }
+        let entry_type = match block[156] {
+            b'0' | 0 | b'7' => EntryType::File,
+            b'5' => EntryType::Directory,
+            b'2' => EntryType::Symlink,
+            b'L' => EntryType::LongName,
+            b'x' => EntryType::Extended,
+            _ => EntryType::Other,
+        };
+        Some(Header {
+            path,
+            size: number(&block[124..136]),
+            entry_type,
+        })
+    }
+
+    /// The `path` record of a PAX extended header, if it has one.
+    pub fn pax_path(data: &[u8]) -> Option<String> {
+        let mut rest = data;
+        while !rest.is_empty() {
+            let space = rest.iter().position(|b| *b == b' ')?;
+            let length: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+            if length <= space || length > rest.len() {
+                return None;
+            }
+            let record = &rest[space + 1..length];
+            let record = record.strip_suffix(b"\n").unwrap_or(record);
+            if let Some(path) = record.strip_prefix(b"path=") {
+                return Some(String::from_utf8_lossy(path).to_string());
+            }
+            rest = &rest[length..];
+        }
+        None
+    }
+
+    /// The name of a GNU long name entry's data.
+    pub fn long_name(data: &[u8]) -> String {
+        text(data)
+    }
+}
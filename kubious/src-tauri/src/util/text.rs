@@ -0,0 +1,16 @@
+pub mod utf8_chunks {
+    /// Splits `buffer` at the last complete UTF-8 sequence, leaving a
+    /// multi-byte character cut off by the chunk boundary for the next read.
+    /// Invalid bytes elsewhere are replaced.
+    pub fn take_text(buffer: &mut Vec<u8>) -> String {
+        let valid = match std::str::from_utf8(buffer) {
+            Ok(_) => buffer.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => buffer.len(),
+        };
+        let rest = buffer.split_off(valid);
+        let text = String::from_utf8_lossy(buffer).to_string();
+        *buffer = rest;
+        text
+    }
+}
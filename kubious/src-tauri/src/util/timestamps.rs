@@ -0,0 +1,195 @@
+pub mod time_normalization {
+    use std::collections::{BTreeMap, HashMap};
+
+    use k8s_openapi::{
+        api::batch::v1::CronJob,
+        chrono::{DateTime, FixedOffset, SecondsFormat, Timelike, Utc},
+    };
+    use kube::{api::ListParams, Api, Client};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    /// Field name suffixes that hold timestamps in Kubernetes objects.
+    const TIMESTAMP_SUFFIXES: [&str; 3] = ["Timestamp", "Time", "At"];
+
+    /// One timestamp in every form the views display.
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct NormalizedTime {
+        /// RFC 3339 in UTC, as stored by the API server.
+        pub absolute: String,
+        /// RFC 3339 in the viewer's timezone.
+        pub local: String,
+        /// RFC 3339 in the cluster's timezone, when it differs from local.
+        pub cluster: Option<String>,
+        /// Humanized distance from now, e.g. `5m ago` or `in 2h`.
+        pub relative: String,
+        /// Seconds since the timestamp; negative for future timestamps.
+        pub age_seconds: i64,
+    }
+
+    /// Reference time and timezones shared by every timestamp of a projection,
+    /// so ages within one window are consistent.
+    #[derive(Clone, Debug)]
+    pub struct TimeContext {
+        now: DateTime<Utc>,
+        local: FixedOffset,
+        cluster: Option<FixedOffset>,
+    }
+
+    impl TimeContext {
+        /// Offsets are minutes east of UTC. The viewer's offset comes from the
+        /// frontend, since the webview knows the user's timezone; the
+        /// cluster's is only shown when it differs.
+        pub fn new(local_offset: Option<i32>, cluster_offset: Option<i32>) -> Self {
+            let local = local_offset
+                .and_then(|minutes| FixedOffset::east_opt(minutes * 60))
+                .unwrap_or(FixedOffset::east_opt(0).expect("zero offset"));
+            TimeContext {
+                now: Utc::now(),
+                cluster: cluster_offset
+                    .and_then(|minutes| FixedOffset::east_opt(minutes * 60))
+                    .filter(|offset| *offset != local),
+                local,
+            }
+        }
+
+        pub fn normalize(&self, time: &DateTime<Utc>) -> NormalizedTime {
+            let age_seconds = (self.now - *time).num_seconds();
+            NormalizedTime {
+                absolute: time.to_rfc3339_opts(SecondsFormat::Secs, true),
+                local: time
+                    .with_timezone(&self.local)
+                    .to_rfc3339_opts(SecondsFormat::Secs, false),
+                cluster: self.cluster.map(|offset| {
+                    time.with_timezone(&offset)
+                        .to_rfc3339_opts(SecondsFormat::Secs, false)
+                }),
+                relative: humanize(age_seconds),
+                age_seconds,
+            }
+        }
+
+        /// Normalizes every timestamp field of an object, keyed by field path
+        /// (`status.conditions[0].lastTransitionTime`).
+        pub fn normalize_item(&self, item: &Value) -> BTreeMap<String, NormalizedTime> {
+            let mut times: BTreeMap<String, NormalizedTime> = BTreeMap::new();
+            self.collect(item, String::new(), &mut times);
+            times
+        }
+
+        fn collect(
+            &self,
+            value: &Value,
+            path: String,
+            times: &mut BTreeMap<String, NormalizedTime>,
+        ) {
+            match value {
+                Value::Object(fields) => {
+                    for (key, child) in fields {
+                        let child_path = if path.is_empty() {
+                            key.clone()
+                        } else {
+                            format!("{}.{}", path, key)
+                        };
+                        if let Some(text) = child.as_str() {
+                            if TIMESTAMP_SUFFIXES.iter().any(|s| key.ends_with(s)) {
+                                if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+                                    times.insert(
+                                        child_path,
+                                        self.normalize(&time.with_timezone(&Utc)),
+                                    );
+                                }
+                            }
+                        } else {
+                            self.collect(child, child_path, times);
+                        }
+                    }
+                }
+                Value::Array(items) => {
+                    for (index, child) in items.iter().enumerate() {
+                        self.collect(child, format!("{}[{}]", path, index), times);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Formats a distance in seconds using its largest unit.
+    pub fn humanize(seconds: i64) -> String {
+        let distance = seconds.abs();
+        if distance < 5 {
+            return "just now".to_string();
+        }
+        let amount = match distance {
+            d if d < 60 => format!("{}s", d),
+            d if d < 3600 => format!("{}m", d / 60),
+            d if d < 86400 => format!("{}h", d / 3600),
+            d if d < 86400 * 365 => format!("{}d", d / 86400),
+            d => format!("{}y", d / (86400 * 365)),
+        };
+        if seconds >= 0 {
+            format!("{} ago", amount)
+        } else {
+            format!("in {}", amount)
+        }
+    }
+
+    /// Parses the minute and hour of a daily-or-rarer schedule such as
+    /// `30 2 * * *`; schedules with ranges, steps or lists are skipped.
+    fn fixed_time_of_day(schedule: &str) -> Option<(i32, i32)> {
+        let fields: Vec<&str> = schedule.split_whitespace().collect();
+        if fields.len() != 5 {
+            return None;
+        }
+        let minute = fields[0]
+            .parse::<i32>()
+            .ok()
+            .filter(|m| (0..60).contains(m))?;
+        let hour = fields[1]
+            .parse::<i32>()
+            .ok()
+            .filter(|h| (0..24).contains(h))?;
+        Some((minute, hour))
+    }
+
+    /// Detects the timezone of kube-controller-manager, which interprets
+    /// CronJob schedules without `timeZone`. The offset between a fixed
+    /// schedule's wall-clock time and its last run in UTC gives the zone.
+    /// Returns the most common offset in minutes east of UTC.
+    pub async fn detect_cluster_offset(client: Client) -> Option<i32> {
+        let cronjobs = Api::<CronJob>::all(client)
+            .list(&ListParams::default())
+            .await
+            .ok()?;
+        let mut offsets: HashMap<i32, usize> = HashMap::new();
+        for cronjob in cronjobs.items {
+            let Some(spec) = cronjob.spec else {
+                continue;
+            };
+            if spec.time_zone.is_some()
+                || spec.schedule.starts_with("CRON_TZ")
+                || spec.schedule.starts_with("TZ")
+            {
+                continue;
+            }
+            let (Some((minute, hour)), Some(last)) = (
+                fixed_time_of_day(spec.schedule.as_str()),
+                cronjob.status.and_then(|s| s.last_schedule_time),
+            ) else {
+                continue;
+            };
+            let scheduled = hour * 60 + minute;
+            let actual = (last.0.hour() * 60 + last.0.minute()) as i32;
+            let mut offset = (scheduled - actual).rem_euclid(1440);
+            if offset > 840 {
+                offset -= 1440;
+            }
+            *offsets.entry(offset).or_insert(0) += 1;
+        }
+        offsets
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.abs().cmp(&a.0.abs())))
+            .map(|(offset, _)| offset)
+    }
+}
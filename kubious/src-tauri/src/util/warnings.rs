@@ -0,0 +1,108 @@
+pub mod api_warnings {
+    use std::{
+        cell::RefCell,
+        future::Future,
+        task::{Context, Poll},
+    };
+
+    use futures::future::BoxFuture;
+    use http::{header::WARNING, HeaderMap, Request, Response};
+    use tower::{Layer, Service};
+
+    tokio::task_local! {
+        static COLLECTED: RefCell<Vec<String>>;
+    }
+
+    /// Extracts the human-readable text from a `Warning` header value such as
+    /// `299 - "extensions/v1beta1 Ingress is deprecated"`.
+    fn warning_text(value: &str) -> String {
+        match (value.find('"'), value.rfind('"')) {
+            (Some(start), Some(end)) if end > start => {
+                value[start + 1..end].replace("\\\"", "\"")
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    fn record(headers: &HeaderMap) {
+        let warnings: Vec<String> = headers
+            .get_all(WARNING)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .map(warning_text)
+            .collect();
+        extend(warnings);
+    }
+
+    /// Adds a warning raised by the backend itself to the current command.
+    pub fn push(warning: String) {
+        extend(vec![warning]);
+    }
+
+    fn extend(warnings: Vec<String>) {
+        if warnings.is_empty() {
+            return;
+        }
+        let _ = COLLECTED.try_with(|collected| {
+            let mut collected = collected.borrow_mut();
+            for warning in warnings {
+                if !collected.contains(&warning) {
+                    collected.push(warning);
+                }
+            }
+        });
+    }
+
+    /// Runs a command future while collecting every API server warning seen by
+    /// clients built with `WarningLayer` on the same task.
+    pub async fn collect<F: Future>(future: F) -> (F::Output, Vec<String>) {
+        COLLECTED
+            .scope(RefCell::new(Vec::new()), async move {
+                let output = future.await;
+                let warnings = COLLECTED.with(|collected| collected.take());
+                (output, warnings)
+            })
+            .await
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct WarningLayer;
+
+    impl<S> Layer<S> for WarningLayer {
+        type Service = WarningService<S>;
+
+        fn layer(&self, inner: S) -> Self::Service {
+            WarningService { inner }
+        }
+    }
+
+    /// Records `Warning` headers of every response. The response future is
+    /// polled by the caller's task, so warnings land in that command's scope.
+    #[derive(Clone, Debug)]
+    pub struct WarningService<S> {
+        inner: S,
+    }
+
+    impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for WarningService<S>
+    where
+        S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+        S::Future: Send + 'static,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.inner.poll_ready(cx)
+        }
+
+        fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+            let response = self.inner.call(request);
+            Box::pin(async move {
+                let response = response.await?;
+                record(response.headers());
+                Ok(response)
+            })
+        }
+    }
+}
@@ -0,0 +1,173 @@
+pub mod watch_gaps {
+    use std::{collections::HashMap, time::Instant};
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use tauri::{AppHandle, Emitter, Manager};
+
+    use crate::api::app_support::{LogLevel, SupportLog};
+
+    /// Event channel carrying a `WatchGapEvent` whenever a watch had to relist
+    /// because it missed events.
+    pub const WATCH_GAP_EVENT: &str = "watch-gap";
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[serde(tag = "type")]
+    pub enum SyntheticEvent {
+        Added { object: Value },
+        Modified { object: Value },
+        Removed { object: Value },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct WatchGapEvent {
+        pub stream: String,
+        pub kind: String,
+        /// How long the watch was disconnected before the relist, if it
+        /// failed at all. The server can also expire a resourceVersion on a
+        /// healthy connection.
+        pub disconnected_ms: Option<u64>,
+        pub reason: Option<String>,
+        /// The changes missed during the gap, found by diffing the relist
+        /// against what the watch had seen before it.
+        pub events: Vec<SyntheticEvent>,
+    }
+
+    fn identity(object: &Value) -> Option<String> {
+        let metadata = &object["metadata"];
+        metadata["uid"]
+            .as_str()
+            .map(|uid| uid.to_string())
+            .or_else(|| {
+                Some(format!(
+                    "{}/{}",
+                    metadata["namespace"].as_str().unwrap_or_default(),
+                    metadata["name"].as_str()?
+                ))
+            })
+    }
+
+    fn version(object: &Value) -> Option<&str> {
+        object["metadata"]["resourceVersion"].as_str()
+    }
+
+    /// Tracks the objects a watch has delivered so that a relist after the
+    /// first sync, which the watcher performs when its resourceVersion has
+    /// expired, can be turned into the add/modify/remove events that were
+    /// missed. Streams feed every watch event through the tracker.
+    pub struct GapTracker {
+        handle: AppHandle,
+        stream: String,
+        kind: String,
+        known: HashMap<String, Value>,
+        relist: Option<HashMap<String, Value>>,
+        synced: bool,
+        disconnected_at: Option<Instant>,
+        reason: Option<String>,
+    }
+
+    impl GapTracker {
+        pub fn new(handle: AppHandle, kind: &str, stream: &str) -> Self {
+            GapTracker {
+                handle,
+                stream: stream.to_string(),
+                kind: kind.to_string(),
+                known: HashMap::new(),
+                relist: None,
+                synced: false,
+                disconnected_at: None,
+                reason: None,
+            }
+        }
+
+        /// Records a watch failure. The gap is only reported if the watch
+        /// has to relist afterwards.
+        pub fn failed(&mut self, reason: &str) {
+            self.disconnected_at.get_or_insert_with(Instant::now);
+            self.reason = Some(reason.to_string());
+        }
+
+        /// The watch resumed from its resourceVersion, so nothing was missed.
+        fn resumed(&mut self) {
+            if self.relist.is_none() {
+                self.disconnected_at = None;
+                self.reason = None;
+            }
+        }
+
+        pub fn applied(&mut self, object: &Value) {
+            self.resumed();
+            if let Some(id) = identity(object) {
+                self.known.insert(id, object.clone());
+            }
+        }
+
+        pub fn deleted(&mut self, object: &Value) {
+            self.resumed();
+            if let Some(id) = identity(object) {
+                self.known.remove(&id);
+            }
+        }
+
+        pub fn init(&mut self) {
+            self.relist = Some(HashMap::new());
+        }
+
+        pub fn init_apply(&mut self, object: &Value) {
+            if let (Some(relist), Some(id)) = (self.relist.as_mut(), identity(object)) {
+                relist.insert(id, object.clone());
+            }
+        }
+
+        /// Finishes a relist. On the first sync this only records the
+        /// objects; after that the missed changes are emitted on
+        /// `WATCH_GAP_EVENT` and returned.
+        pub fn init_done(&mut self) -> Option<Vec<SyntheticEvent>> {
+            let relist = self.relist.take().unwrap_or_default();
+            let previous = std::mem::replace(&mut self.known, relist);
+            let disconnected_at = self.disconnected_at.take();
+            let reason = self.reason.take();
+            if !self.synced {
+                self.synced = true;
+                return None;
+            }
+
+            let mut events: Vec<SyntheticEvent> = Vec::new();
+            for (id, object) in self.known.iter() {
+                match previous.get(id) {
+                    None => events.push(SyntheticEvent::Added {
+                        object: object.clone(),
+                    }),
+                    Some(old) if version(old) != version(object) => {
+                        events.push(SyntheticEvent::Modified {
+                            object: object.clone(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            for (id, object) in previous {
+                if !self.known.contains_key(&id) {
+                    events.push(SyntheticEvent::Removed { object });
+                }
+            }
+
+            self.handle.state::<SupportLog>().log(
+                LogLevel::Warning,
+                format!("{}/{}", self.kind, self.stream).as_str(),
+                format!("Relisted after watch gap, {} missed changes", events.len()).as_str(),
+            );
+            let _ = self.handle.emit(
+                WATCH_GAP_EVENT,
+                WatchGapEvent {
+                    stream: self.stream.clone(),
+                    kind: self.kind.clone(),
+                    disconnected_ms: disconnected_at.map(|at| at.elapsed().as_millis() as u64),
+                    reason,
+                    events: events.clone(),
+                },
+            );
+            Some(events)
+        }
+    }
+}
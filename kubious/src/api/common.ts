@@ -10,7 +10,8 @@ export async function execute_command<
 >(
     scope: Scope,
     command: Command,
-    options?: Args
+    options?: Args,
+    forceRefresh?: boolean
 ): Promise<CommandResult<CommandSpec<Scope, Command, Args>, Result>> {
     const scopeNames = {
         [CommandScope.Application]: "Application",
@@ -18,6 +19,14 @@ export async function execute_command<
         [CommandScope.Helm]: "Helm",
         [CommandScope.Kompose]: "Kompose",
         [CommandScope.Kube]: "Kube",
+        [CommandScope.Views]: "Views",
+        [CommandScope.Resources]: "Resources",
+        [CommandScope.Logs]: "Logs",
+        [CommandScope.Exec]: "Exec",
+        [CommandScope.Events]: "Events",
+        [CommandScope.Dynamic]: "Dynamic",
+        [CommandScope.Journal]: "Journal",
+        [CommandScope.Metrics]: "Metrics",
     };
 
     const command_scope = scopeNames[scope];
@@ -32,6 +41,7 @@ export async function execute_command<
                 command: command_name,
                 ...(options ?? {}),
             },
+            forceRefresh,
         });
         console.log(result);
         return result;
@@ -43,7 +53,10 @@ export async function execute_command<
                 ...(options ?? {}),
             } as any,
             success: false,
-            error: `Command invocation failed:\n${e}`,
+            error: {
+                kind: "other",
+                message: `Command invocation failed:\n${e}`,
+            },
         };
     }
 }
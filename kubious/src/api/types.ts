@@ -7,6 +7,14 @@ export enum CommandScope {
     Helm = "helm",
     Kompose = "kompose",
     Artifacts = "artifacts",
+    Views = "views",
+    Resources = "resources",
+    Logs = "logs",
+    Exec = "exec",
+    Events = "events",
+    Dynamic = "dynamic",
+    Journal = "journal",
+    Metrics = "metrics",
 }
 
 export type CommandSpec<
@@ -18,12 +26,21 @@ export type CommandSpec<
     command: Command;
 } & Args;
 
+export type KubiousError =
+    | { kind: "config"; message: string }
+    | { kind: "auth"; message: string }
+    | { kind: "api"; message: string; status: number | null; reason: string | null }
+    | { kind: "io"; message: string; path: string | null }
+    | { kind: "other"; message: string };
+
 export type CommandResult<
     Command extends CommandSpec<any, any, any>,
     Success = any
 > = {
     command: Command;
-} & ({ success: true; value: Success } | { success: false; error: string });
+    warnings?: string[];
+    cached?: boolean;
+} & ({ success: true; value: Success } | { success: false; error: KubiousError });
 
 export type K8SGroup = {
     name: string;